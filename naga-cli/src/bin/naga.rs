@@ -42,7 +42,8 @@ struct Args {
     #[argh(option)]
     block_ctx_dir: Option<String>,
 
-    /// the shader entrypoint to use when compiling to GLSL
+    /// the shader entrypoint to use when compiling to GLSL, or to parse as
+    /// the `--shader-stage` entry point of HLSL input (`main` by default)
     #[argh(option)]
     entry_point: Option<String>,
 
@@ -62,7 +63,7 @@ struct Args {
     #[argh(option)]
     shader_stage: Option<ShaderStage>,
 
-    /// the kind of input, e.g. 'glsl', 'wgsl', 'spv', or 'bin'.
+    /// the kind of input, e.g. 'glsl', 'hlsl', 'wgsl', 'spv', or 'bin'.
     #[argh(option)]
     input_kind: Option<InputKind>,
 
@@ -87,6 +88,14 @@ struct Args {
     #[argh(switch, short = 'g')]
     generate_debug_symbols: bool,
 
+    /// optimize the module's IR and revalidate.
+    ///
+    /// This applies every pass in `naga::opt`: function inlining, constant
+    /// propagation, common subexpression elimination, loop-invariant
+    /// hoisting, and dead store elimination.
+    #[argh(switch)]
+    optimize: bool,
+
     /// compact the module's IR and revalidate.
     ///
     /// Output files will reflect the compacted IR. If you want to see the IR as
@@ -101,6 +110,13 @@ struct Args {
     #[argh(option)]
     before_compaction: Option<String>,
 
+    /// print the validation capabilities and subgroup operations the module
+    /// needs, and the source location of each construct that needs them. The
+    /// capabilities are naga's, `wgpu_core::device::required_features` maps
+    /// them to wgpu features.
+    #[argh(switch)]
+    required_features: bool,
+
     /// bulk validation mode: all filenames are inputs to read and validate.
     #[argh(switch)]
     bulk_validate: bool,
@@ -129,6 +145,68 @@ struct Args {
     /// defines to be passed to the parser (only glsl is supported)
     #[argh(option, short = 'D')]
     defines: Vec<Defines>,
+
+    /// directories to search for files named by GLSL `#include <...>`
+    /// directives, repeatable. `#include "..."` also searches them, after
+    /// the directory of the including file.
+    #[argh(option, short = 'I')]
+    include_paths: Vec<std::path::PathBuf>,
+
+    /// shifts added to the indices of HLSL registers of a class to get
+    /// binding numbers, like DXC's `-fvk-t-shift`. For example, `t=16,s=32`.
+    /// The classes are `b`, `t`, `s` and `u`.
+    #[argh(option)]
+    register_shift: Vec<RegisterShifts>,
+
+    /// WGSL modules to link with the input, repeatable. Declarations in
+    /// any of the modules can be used from all of them.
+    #[argh(option)]
+    link: Vec<std::path::PathBuf>,
+
+    /// how to report WGSL parse errors, validation errors and warnings.
+    ///
+    /// May be `human` (the default), or `json`, which writes each diagnostic
+    /// to stdout as a JSON object on a line of its own. Only WGSL parse
+    /// errors have a `code`; it is `null` for validation errors and
+    /// warnings.
+    #[argh(option)]
+    diagnostics_format: Option<DiagnosticsFormat>,
+}
+
+/// Print the resources, inputs and outputs of a shader's entry points.
+#[derive(argh::FromArgs, Debug, Clone)]
+struct ReflectArgs {
+    /// write the result to stdout as JSON, rather than as text
+    #[argh(switch)]
+    json: bool,
+
+    /// the shader stage, for GLSL inputs whose file extension doesn't give it
+    #[argh(option)]
+    shader_stage: Option<ShaderStage>,
+
+    /// the kind of input, e.g. 'glsl', 'hlsl', 'wgsl', 'spv', or 'bin'.
+    #[argh(option)]
+    input_kind: Option<InputKind>,
+
+    /// the shader to reflect
+    #[argh(positional)]
+    input: String,
+}
+
+/// Format WGSL source code.
+#[derive(argh::FromArgs, Debug, Clone)]
+struct FmtArgs {
+    /// don't write anything, but exit with an error if any of the files is
+    /// not already formatted
+    #[argh(switch)]
+    check: bool,
+
+    /// the WGSL files to format in place.
+    ///
+    /// If none are given, the input is read from stdin, and the formatted
+    /// result is written to stdout.
+    #[argh(positional)]
+    files: Vec<String>,
 }
 
 /// Newtype so we can implement [`FromStr`] for `BoundsCheckPolicy`.
@@ -201,6 +279,7 @@ impl FromStr for ShaderStage {
 enum InputKind {
     Bincode,
     Glsl,
+    Hlsl,
     SpirV,
     Wgsl,
 }
@@ -211,6 +290,7 @@ impl FromStr for InputKind {
         Ok(match s.to_lowercase().as_str() {
             "bin" => InputKind::Bincode,
             "glsl" => InputKind::Glsl,
+            "hlsl" => InputKind::Hlsl,
             "spv" => InputKind::SpirV,
             "wgsl" => InputKind::Wgsl,
             _ => return Err(anyhow!("Invalid value for --input-kind: {s}")),
@@ -218,6 +298,28 @@ impl FromStr for InputKind {
     }
 }
 
+/// How to report errors.
+#[derive(Debug, Default, Clone, Copy)]
+enum DiagnosticsFormat {
+    /// Human-readable text, with source code snippets, on stderr.
+    #[default]
+    Human,
+    /// One JSON object per error, on stdout.
+    Json,
+}
+
+impl FromStr for DiagnosticsFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.to_lowercase().as_str() {
+            "human" => DiagnosticsFormat::Human,
+            "json" => DiagnosticsFormat::Json,
+            _ => return Err(anyhow!("Invalid value for --diagnostics-format: {s}")),
+        })
+    }
+}
+
 /// Newtype so we can implement [`FromStr`] for [`naga::back::glsl::Version`].
 #[derive(Clone, Debug)]
 struct GlslProfileArg(naga::back::glsl::Version);
@@ -303,6 +405,34 @@ impl FromStr for Defines {
     }
 }
 
+#[derive(Clone, Debug)]
+struct RegisterShifts {
+    pairs: Vec<(char, u32)>,
+}
+
+impl FromStr for RegisterShifts {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut pairs = vec![];
+        for pair in s.split(',') {
+            let Some((class, shift)) = pair.split_once('=') else {
+                return Err(format!("value needs a `=`: {pair:?}"));
+            };
+            let class = match class.trim() {
+                "b" => 'b',
+                "t" => 't',
+                "s" => 's',
+                "u" => 'u',
+                _ => return Err(format!("unknown register class: {class:?}")),
+            };
+            let shift = u32::from_str(shift.trim()).map_err(|err| format!("{err}: {shift:?}"))?;
+            pairs.push((class, shift));
+        }
+        Ok(RegisterShifts { pairs })
+    }
+}
+
 #[derive(Default)]
 struct Parameters<'a> {
     validation_flags: naga::valid::ValidationFlags,
@@ -316,9 +446,13 @@ struct Parameters<'a> {
     msl: naga::back::msl::Options,
     glsl: naga::back::glsl::Options,
     hlsl: naga::back::hlsl::Options,
+    register_shifts: naga::front::hlsl::RegisterShifts,
     input_kind: Option<InputKind>,
     shader_stage: Option<ShaderStage>,
     defines: FastHashMap<String, String>,
+    include_paths: Vec<std::path::PathBuf>,
+    link: Vec<std::path::PathBuf>,
+    diagnostics_format: DiagnosticsFormat,
 }
 
 trait PrettyResult {
@@ -380,6 +514,14 @@ fn run() -> anyhow::Result<()> {
         .parse_default_env()
         .init();
 
+    // `naga fmt` and `naga reflect` take their own sets of arguments.
+    let cli_args: Vec<String> = std::env::args().collect();
+    match cli_args.get(1).map(String::as_str) {
+        Some("fmt") => return format_files(subcommand_args("naga fmt", &cli_args[2..])),
+        Some("reflect") => return reflect(subcommand_args("naga reflect", &cli_args[2..])),
+        _ => {}
+    }
+
     // Parse commandline arguments
     let args: Args = argh::from_env();
     if args.version {
@@ -420,6 +562,17 @@ fn run() -> anyhow::Result<()> {
         .flat_map(|o| &o.pairs)
         .cloned()
         .collect();
+    params.include_paths = args.include_paths.clone();
+    for &(class, shift) in args.register_shift.iter().flat_map(|o| &o.pairs) {
+        let shifts = &mut params.register_shifts;
+        match class {
+            'b' => shifts.constant_buffer = shift,
+            't' => shifts.texture = shift,
+            's' => shifts.sampler = shift,
+            _ => shifts.unordered_access = shift,
+        }
+    }
+    params.link = args.link.clone();
 
     params.spv_in = naga::front::spv::Options {
         adjust_coordinate_space: !args.keep_coordinate_space,
@@ -438,6 +591,7 @@ fn run() -> anyhow::Result<()> {
         params.msl.lang_version = version.0;
     }
     params.keep_coordinate_space = args.keep_coordinate_space;
+    params.diagnostics_format = args.diagnostics_format.unwrap_or_default();
 
     params.dot.cfg_only = args.dot_cfg_only;
 
@@ -470,8 +624,10 @@ fn run() -> anyhow::Result<()> {
     let Parsed {
         mut module,
         input_text,
+        source_path,
         language,
     } = parse_input(input_path, input, &params)?;
+    let source_path = source_path.as_deref().unwrap_or(input_path);
 
     // Include debugging information if requested.
     if args.generate_debug_symbols {
@@ -482,7 +638,7 @@ fn run() -> anyhow::Result<()> {
                 .set(naga::back::spv::WriterFlags::DEBUG, true);
             params.spv_out.debug_info = Some(naga::back::spv::DebugInfo {
                 source_code: input_text,
-                file_name: input_path,
+                file_name: source_path,
                 language,
             })
         } else {
@@ -516,17 +672,54 @@ fn run() -> anyhow::Result<()> {
         .subgroup_operations(naga::valid::SubgroupOperationSet::all())
         .validate(&module)
     {
-        Ok(info) => Some(info),
+        Ok(info) => {
+            report_diagnostics(
+                info.diagnostics(),
+                input_text.as_deref(),
+                source_path,
+                &params,
+            );
+            Some(info)
+        }
         Err(error) => {
             // Validation failure is not fatal. Just report the error.
-            if let Some(input) = &input_text {
-                let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
-                error.emit_to_stderr_with_path(input, filename.unwrap_or("input"));
-            } else {
-                print_err(&error);
+            report_validation_error(&error, input_text.as_deref(), source_path, &params);
+            None
+        }
+    };
+
+    if args.required_features {
+        if let Some(ref info) = info {
+            print_required_features(&module, info, input_text.as_deref(), source_path);
+        }
+    }
+
+    // Optimize the module, if requested.
+    let info = if args.optimize {
+        // Optimize only if validation succeeded. Otherwise, the passes may panic.
+        if info.is_some() {
+            naga::opt::optimize(&mut module, &naga::opt::Options::all());
+
+            // Re-validate the IR after optimization.
+            match naga::valid::Validator::new(params.validation_flags, validation_caps)
+                .subgroup_stages(naga::valid::ShaderStages::all())
+                .subgroup_operations(naga::valid::SubgroupOperationSet::all())
+                .validate(&module)
+            {
+                Ok(info) => Some(info),
+                Err(error) => {
+                    // Validation failure is not fatal. Just report the error.
+                    eprintln!("Error validating optimized module:");
+                    report_validation_error(&error, input_text.as_deref(), source_path, &params);
+                    None
+                }
             }
+        } else {
+            eprintln!("Skipping optimization due to validation failure.");
             None
         }
+    } else {
+        info
     };
 
     // Compact the module, if requested.
@@ -548,12 +741,7 @@ fn run() -> anyhow::Result<()> {
                 Err(error) => {
                     // Validation failure is not fatal. Just report the error.
                     eprintln!("Error validating compacted module:");
-                    if let Some(input) = &input_text {
-                        let filename = input_path.file_name().and_then(std::ffi::OsStr::to_str);
-                        error.emit_to_stderr_with_path(input, filename.unwrap_or("input"));
-                    } else {
-                        print_err(&error);
-                    }
+                    report_validation_error(&error, input_text.as_deref(), source_path, &params);
                     None
                 }
             }
@@ -571,7 +759,9 @@ fn run() -> anyhow::Result<()> {
     // ".dot", ".bin") can be generated even without a `ModuleInfo`.
     if output_paths.clone().next().is_none() {
         if info.is_some() {
-            println!("Validation successful");
+            if let DiagnosticsFormat::Human = params.diagnostics_format {
+                println!("Validation successful");
+            }
             return Ok(());
         } else {
             std::process::exit(-1);
@@ -585,9 +775,64 @@ fn run() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Resolves GLSL `#include` directives from the file system.
+struct IncludeResolver<'a> {
+    include_paths: &'a [std::path::PathBuf],
+}
+
+impl naga::front::glsl::IncludeResolver for IncludeResolver<'_> {
+    fn resolve(
+        &mut self,
+        path: &str,
+        kind: naga::front::glsl::IncludeKind,
+        includer: &str,
+    ) -> Result<naga::front::glsl::IncludedSource, String> {
+        let relative_dir = match kind {
+            naga::front::glsl::IncludeKind::Relative => Path::new(includer).parent(),
+            naga::front::glsl::IncludeKind::Standard => None,
+        };
+        let candidate = relative_dir
+            .into_iter()
+            .chain(self.include_paths.iter().map(|dir| dir.as_path()))
+            .map(|dir| normalize_path(&dir.join(path)))
+            .find(|candidate| candidate.is_file())
+            .ok_or("file not found")?;
+        let source = fs::read_to_string(&candidate).map_err(|e| e.to_string())?;
+        Ok(naga::front::glsl::IncludedSource {
+            name: candidate.display().to_string(),
+            source,
+        })
+    }
+}
+
+/// Remove `.` components and the components that `..` components undo, so
+/// that each file gets a single name.
+fn normalize_path(path: &Path) -> std::path::PathBuf {
+    use std::path::Component;
+
+    let mut normalized = std::path::PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir
+                if matches!(
+                    normalized.components().next_back(),
+                    Some(Component::Normal(_))
+                ) =>
+            {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+    normalized
+}
+
 struct Parsed {
     module: naga::Module,
     input_text: Option<String>,
+    /// The file `input_text` was read from, if it isn't the input itself.
+    source_path: Option<std::path::PathBuf>,
     language: naga::back::spv::SourceLanguage,
 }
 
@@ -603,31 +848,108 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
             .context("Unable to determine --input-kind from filename")?,
     };
 
+    if !params.link.is_empty() && !matches!(input_kind, InputKind::Wgsl) {
+        return Err(CliError("--link is only supported for WGSL input").into());
+    }
+
     Ok(match input_kind {
         InputKind::Bincode => Parsed {
             module: bincode::deserialize(&input)?,
             input_text: None,
+            source_path: None,
             language: naga::back::spv::SourceLanguage::Unknown,
         },
-        InputKind::SpirV => Parsed {
-            module: naga::front::spv::parse_u8_slice(&input, &params.spv_in)?,
-            input_text: None,
-            language: naga::back::spv::SourceLanguage::Unknown,
-        },
+        InputKind::SpirV => {
+            let (module, source) =
+                naga::front::spv::parse_u8_slice_with_source(&input, &params.spv_in)?;
+            match source {
+                // Spans refer to the source code embedded in the module.
+                Some(source) => Parsed {
+                    module,
+                    input_text: Some(source.source_code),
+                    source_path: source.file_name.map(Into::into),
+                    language: source.language,
+                },
+                None => Parsed {
+                    module,
+                    input_text: None,
+                    source_path: None,
+                    language: naga::back::spv::SourceLanguage::Unknown,
+                },
+            }
+        }
+        InputKind::Wgsl if !params.link.is_empty() => {
+            let input = String::from_utf8(input)?;
+            let mut linker = naga::front::wgsl::Linker::new();
+            linker.add_module(input_path.display().to_string(), &input);
+            for path in &params.link {
+                let source = fs::read_to_string(path)
+                    .with_context(|| format!("Unable to read {}", path.display()))?;
+                linker.add_module(path.display().to_string(), &source);
+            }
+            let result = linker.link();
+            report_linked_diagnostics(&linker, params);
+            match result {
+                Ok(module) => Parsed {
+                    module,
+                    // Spans refer to the combined source of all the modules
+                    input_text: Some(linker.source().to_string()),
+                    source_path: None,
+                    language: naga::back::spv::SourceLanguage::WGSL,
+                },
+                Err(ref e) => {
+                    let message = match params.diagnostics_format {
+                        DiagnosticsFormat::Human => anyhow!(
+                            "Could not parse WGSL:\n{}",
+                            e.emit_to_string_with_linker(&linker)
+                        ),
+                        DiagnosticsFormat::Json => {
+                            emit_linked_json_diagnostic(
+                                &linker,
+                                "error",
+                                Some(e.code()),
+                                e.message(),
+                                e.labels().collect(),
+                                e.notes().map(str::to_string),
+                            );
+                            anyhow!("Could not parse WGSL")
+                        }
+                    };
+                    return Err(message);
+                }
+            }
+        }
         InputKind::Wgsl => {
             let input = String::from_utf8(input)?;
-            let result = naga::front::wgsl::parse_str(&input);
+            let mut frontend = naga::front::wgsl::Frontend::new();
+            let result = frontend.parse(&input);
+            report_diagnostics(frontend.diagnostics(), Some(&input), input_path, params);
             match result {
                 Ok(v) => Parsed {
                     module: v,
                     input_text: Some(input),
+                    source_path: None,
                     language: naga::back::spv::SourceLanguage::WGSL,
                 },
                 Err(ref e) => {
-                    let message = anyhow!(
-                        "Could not parse WGSL:\n{}",
-                        e.emit_to_string_with_path(&input, input_path)
-                    );
+                    let message = match params.diagnostics_format {
+                        DiagnosticsFormat::Human => anyhow!(
+                            "Could not parse WGSL:\n{}",
+                            e.emit_to_string_with_path(&input, input_path)
+                        ),
+                        DiagnosticsFormat::Json => {
+                            emit_json_diagnostic(
+                                input_path,
+                                Some(&input),
+                                "error",
+                                Some(e.code()),
+                                e.message(),
+                                e.labels(),
+                                e.notes().map(str::to_string),
+                            );
+                            anyhow!("Could not parse WGSL")
+                        }
+                    };
                     return Err(message);
                 }
             }
@@ -651,28 +973,60 @@ fn parse_input(input_path: &Path, input: Vec<u8>, params: &Parameters) -> anyhow
             };
             let input = String::from_utf8(input)?;
             let mut parser = naga::front::glsl::Frontend::default();
+            let mut resolver = IncludeResolver {
+                include_paths: &params.include_paths,
+            };
+            let module = parser
+                .parse_with_includes(
+                    &naga::front::glsl::Options {
+                        stage: shader_stage.0,
+                        defines: params.defines.clone(),
+                    },
+                    &input,
+                    &input_path.display().to_string(),
+                    &mut resolver,
+                )
+                .unwrap_or_else(|error| {
+                    let mut writer = StandardStream::stderr(ColorChoice::Auto);
+                    error.emit_to_writer_with_files(&mut writer, parser.source_files());
+                    std::process::exit(1);
+                });
             Parsed {
-                module: parser
-                    .parse(
-                        &naga::front::glsl::Options {
-                            stage: shader_stage.0,
-                            defines: params.defines.clone(),
-                        },
-                        &input,
-                    )
-                    .unwrap_or_else(|error| {
-                        let filename = input_path
-                            .file_name()
-                            .and_then(std::ffi::OsStr::to_str)
-                            .unwrap_or("glsl");
-                        let mut writer = StandardStream::stderr(ColorChoice::Auto);
-                        error.emit_to_writer_with_path(&mut writer, &input, filename);
-                        std::process::exit(1);
-                    }),
-                input_text: Some(input),
+                module,
+                // Spans refer to the source with its includes expanded
+                input_text: Some(parser.source_files().expanded_source().to_string()),
+                source_path: None,
                 language: naga::back::spv::SourceLanguage::GLSL,
             }
         }
+        InputKind::Hlsl => {
+            let input = String::from_utf8(input)?;
+            let mut options = naga::front::hlsl::Options {
+                register_shifts: params.register_shifts,
+                ..Default::default()
+            };
+            // Without a stage, the entry points are the functions with a
+            // `[shader("...")]` or `[numthreads(...)]` attribute.
+            if let Some(stage) = params.shader_stage {
+                let name = params.entry_point.as_deref().unwrap_or("main");
+                options.entry_points.insert(name.to_string(), stage.0);
+            }
+            match naga::front::hlsl::Frontend::new().parse(&options, &input) {
+                Ok(module) => Parsed {
+                    module,
+                    input_text: Some(input),
+                    source_path: None,
+                    language: naga::back::spv::SourceLanguage::HLSL,
+                },
+                Err(ref e) => {
+                    let path = input_path.display().to_string();
+                    return Err(anyhow!(
+                        "Could not parse HLSL:\n{}",
+                        e.emit_to_string_with_path(&input, &path)
+                    ));
+                }
+            }
+        }
     })
 }
 
@@ -750,6 +1104,8 @@ fn write_output(
             let (module, info) =
                 naga::back::pipeline_constants::process_overrides(module, info, &params.overrides)
                     .unwrap_pretty();
+            let (module, info) =
+                naga::back::pointer_parameters::specialize(&module, &info).unwrap_pretty();
 
             let spv =
                 spv::write_vec(&module, &info, &params.spv_out, pipeline_options).unwrap_pretty();
@@ -787,6 +1143,8 @@ fn write_output(
             let (module, info) =
                 naga::back::pipeline_constants::process_overrides(module, info, &params.overrides)
                     .unwrap_pretty();
+            let (module, info) =
+                naga::back::pointer_parameters::specialize(&module, &info).unwrap_pretty();
 
             let mut buffer = String::new();
             let mut writer = glsl::Writer::new(
@@ -818,6 +1176,8 @@ fn write_output(
             let (module, info) =
                 naga::back::pipeline_constants::process_overrides(module, info, &params.overrides)
                     .unwrap_pretty();
+            let (module, info) =
+                naga::back::pointer_parameters::specialize(&module, &info).unwrap_pretty();
 
             let mut buffer = String::new();
             let mut writer = hlsl::Writer::new(&mut buffer, &params.hlsl);
@@ -855,6 +1215,7 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
         let Parsed {
             module,
             input_text,
+            source_path,
             language: _,
         } = match parse_input(path, input, params) {
             Ok(parsed) => parsed,
@@ -874,12 +1235,8 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
         if let Err(error) = validator.validate(&module) {
             invalid.push(input_path.clone());
             eprintln!("Error validating {input_path}:");
-            if let Some(input) = &input_text {
-                let filename = path.file_name().and_then(std::ffi::OsStr::to_str);
-                error.emit_to_stderr_with_path(input, filename.unwrap_or("input"));
-            } else {
-                print_err(&error);
-            }
+            let source_path = source_path.as_deref().unwrap_or(path);
+            report_validation_error(&error, input_text.as_deref(), source_path, params);
         }
     }
 
@@ -900,5 +1257,425 @@ fn bulk_validate(args: Args, params: &Parameters) -> anyhow::Result<()> {
     Ok(())
 }
 
+/// Report a validation error in the module read from `path`, whose source
+/// text is `source`, if it has any.
+fn report_validation_error(
+    error: &naga::WithSpan<naga::valid::ValidationError>,
+    source: Option<&str>,
+    path: &Path,
+    params: &Parameters,
+) {
+    match params.diagnostics_format {
+        DiagnosticsFormat::Human => {
+            if let Some(input) = source {
+                let filename = path.file_name().and_then(std::ffi::OsStr::to_str);
+                error.emit_to_stderr_with_path(input, filename.unwrap_or("input"));
+            } else {
+                print_err(error);
+            }
+        }
+        DiagnosticsFormat::Json => {
+            let mut notes = Vec::new();
+            let mut e = error.source();
+            while let Some(source) = e {
+                notes.push(source.to_string());
+                e = source.source();
+            }
+            emit_json_diagnostic(
+                path,
+                source,
+                "error",
+                None,
+                &error.to_string(),
+                error
+                    .spans()
+                    .map(|&(span, ref label)| (span, label.as_str())),
+                notes.into_iter(),
+            );
+        }
+    }
+}
+
+/// Report non-fatal diagnostics, like warnings, for the module read from
+/// `path`, whose source text is `source`, if it has any.
+fn report_diagnostics<'a>(
+    diagnostics: impl IntoIterator<Item = &'a naga::diagnostic_filter::Diagnostic>,
+    source: Option<&str>,
+    path: &Path,
+    params: &Parameters,
+) {
+    use naga::diagnostic_filter::Severity;
+
+    for diagnostic in diagnostics {
+        let severity = match diagnostic.severity() {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Off | Severity::Error => unreachable!(),
+        };
+        match params.diagnostics_format {
+            DiagnosticsFormat::Human => {
+                if let Some(input) = source {
+                    let filename = path.file_name().and_then(std::ffi::OsStr::to_str);
+                    diagnostic.emit_to_stderr_with_path(input, filename.unwrap_or("input"));
+                } else {
+                    eprintln!("{severity}: {diagnostic}");
+                }
+            }
+            DiagnosticsFormat::Json => {
+                emit_json_diagnostic(
+                    path,
+                    source,
+                    severity,
+                    None,
+                    diagnostic.message(),
+                    diagnostic.labels(),
+                    diagnostic.notes().map(str::to_string),
+                );
+            }
+        }
+    }
+}
+
+/// Report the non-fatal diagnostics of the WGSL modules combined by
+/// `linker`, each in the module it came from.
+fn report_linked_diagnostics(linker: &naga::front::wgsl::Linker, params: &Parameters) {
+    use naga::diagnostic_filter::Severity;
+
+    for diagnostic in linker.diagnostics() {
+        let severity = match diagnostic.severity() {
+            Severity::Info => "info",
+            Severity::Warning => "warning",
+            Severity::Off | Severity::Error => unreachable!(),
+        };
+        match params.diagnostics_format {
+            DiagnosticsFormat::Human => {
+                eprint!("{}", diagnostic.emit_to_string_with_linker(linker));
+            }
+            DiagnosticsFormat::Json => emit_linked_json_diagnostic(
+                linker,
+                severity,
+                None,
+                diagnostic.message(),
+                diagnostic.labels().collect(),
+                diagnostic.notes().map(str::to_string),
+            ),
+        }
+    }
+}
+
+/// Write a diagnostic whose spans refer to the combined source of `linker`
+/// to stdout as JSON.
+///
+/// The diagnostic is reported in the module of its primary label, with the
+/// labels that are in that module.
+fn emit_linked_json_diagnostic<'a>(
+    linker: &naga::front::wgsl::Linker,
+    severity: &str,
+    code: Option<&str>,
+    message: &str,
+    labels: Vec<(naga::Span, &'a str)>,
+    notes: impl Iterator<Item = String>,
+) {
+    let module = labels
+        .first()
+        .and_then(|&(span, _)| linker.locate(span))
+        .map_or(0, |(module, _)| module);
+    let (name, source) = linker.module(module).unwrap();
+    emit_json_diagnostic(
+        Path::new(name),
+        Some(source),
+        severity,
+        code,
+        message,
+        labels
+            .into_iter()
+            .filter_map(|(span, label)| match linker.locate(span) {
+                Some((m, range)) if m == module => Some((naga::Span::from(range), label)),
+                _ => None,
+            }),
+        notes,
+    );
+}
+
+/// Write a diagnostic to stdout as a JSON object on a line of its own.
+///
+/// The `code` is written as `null` if it is `None`, as it is for everything
+/// but WGSL parse errors.
+///
+/// The first of `labels` is the primary one. Spans are given as byte offsets,
+/// and, if the source text is available, as 1-based line and column numbers,
+/// with columns counted in bytes.
+fn emit_json_diagnostic<'a>(
+    path: &Path,
+    source: Option<&str>,
+    severity: &str,
+    code: Option<&str>,
+    message: &str,
+    labels: impl Iterator<Item = (naga::Span, &'a str)>,
+    notes: impl Iterator<Item = String>,
+) {
+    let labels = labels
+        .filter_map(|(span, label)| Some((span.to_range()?, label)))
+        .enumerate()
+        .map(|(i, (range, label))| {
+            let mut json = serde_json::json!({
+                "message": label,
+                "primary": i == 0,
+                "start": range.start,
+                "end": range.end,
+            });
+            if let Some(source) = source {
+                for (key, offset) in [("start", range.start), ("end", range.end)] {
+                    let location = naga::Span::new(offset as u32, offset as u32).location(source);
+                    json[format!("{key}_line")] = location.line_number.into();
+                    json[format!("{key}_column")] = location.line_position.into();
+                }
+            }
+            json
+        })
+        .collect::<Vec<_>>();
+
+    let diagnostic = serde_json::json!({
+        "file": path.display().to_string(),
+        "severity": severity,
+        "code": code,
+        "message": message,
+        "labels": labels,
+        "notes": notes.collect::<Vec<_>>(),
+    });
+    println!("{diagnostic}");
+}
+
+/// Parse the arguments of the subcommand `name`, exiting on errors and
+/// `--help`.
+fn subcommand_args<T: argh::FromArgs>(name: &str, args: &[String]) -> T {
+    let args: Vec<&str> = args.iter().map(String::as_str).collect();
+    T::from_args(&[name], &args).unwrap_or_else(|early_exit| match early_exit.status {
+        Ok(()) => {
+            println!("{}", early_exit.output);
+            std::process::exit(0);
+        }
+        Err(()) => {
+            eprintln!("{}", early_exit.output);
+            std::process::exit(1);
+        }
+    })
+}
+
+fn reflect(args: ReflectArgs) -> anyhow::Result<()> {
+    use naga::proc::reflect::{BindingType, Varying, WorkgroupSizeOverride};
+
+    let params = Parameters {
+        input_kind: args.input_kind,
+        shader_stage: args.shader_stage,
+        ..Default::default()
+    };
+    let input_path = Path::new(&args.input);
+    let input = fs::read(input_path).with_context(|| format!("reading {}", args.input))?;
+    let Parsed {
+        module,
+        input_text,
+        source_path,
+        ..
+    } = parse_input(input_path, input, &params)?;
+    let source_path = source_path.as_deref().unwrap_or(input_path);
+
+    let info =
+        naga::valid::Validator::new(params.validation_flags, naga::valid::Capabilities::all())
+            .subgroup_stages(naga::valid::ShaderStages::all())
+            .subgroup_operations(naga::valid::SubgroupOperationSet::all())
+            .validate(&module)
+            .map_err(|error| {
+                report_validation_error(&error, input_text.as_deref(), source_path, &params);
+                anyhow!("Validation failed")
+            })?;
+    let reflection = naga::proc::reflect(&module, &info);
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&reflection)?);
+        return Ok(());
+    }
+
+    fn print_varyings(direction: &str, varyings: &[Varying]) {
+        for varying in varyings {
+            let name = varying.name.as_deref().unwrap_or("_");
+            let binding = match varying.binding {
+                naga::Binding::BuiltIn(built_in) => format!("@builtin({built_in:?})"),
+                naga::Binding::Location { location, .. } => format!("@location({location})"),
+            };
+            match varying.format {
+                Some(format) => println!("  {direction} {binding} {name}: {format:?}"),
+                None => println!("  {direction} {binding} {name}"),
+            }
+        }
+    }
+
+    for entry_point in reflection.entry_points.iter() {
+        println!("{:?} entry point `{}`", entry_point.stage, entry_point.name);
+        if entry_point.stage == naga::ShaderStage::Compute {
+            let size = entry_point
+                .workgroup_size
+                .iter()
+                .zip(entry_point.workgroup_size_overrides.iter())
+                .map(|(&size, overridden)| match *overridden {
+                    None => size.to_string(),
+                    Some(WorkgroupSizeOverride::Override(index)) => reflection.overrides[index]
+                        .name
+                        .clone()
+                        .unwrap_or_else(|| "<override>".to_string()),
+                    Some(WorkgroupSizeOverride::Expression) => "<expression>".to_string(),
+                })
+                .collect::<Vec<_>>();
+            println!("  workgroup size ({})", size.join(", "));
+        }
+        for resource in entry_point.resources.iter() {
+            let name = resource.name.as_deref().unwrap_or("_");
+            let count = match resource.count {
+                Some(count) => format!(" x {count}"),
+                None => String::new(),
+            };
+            let ty = match resource.ty {
+                BindingType::Buffer {
+                    ty,
+                    min_binding_size,
+                } => format!("{ty:?} buffer, at least {min_binding_size} bytes"),
+                ref other => format!("{other:?}"),
+            };
+            println!(
+                "  @group({}) @binding({}) {name}: {ty}{count}",
+                resource.group, resource.binding
+            );
+        }
+        print_varyings("in", &entry_point.inputs);
+        print_varyings("out", &entry_point.outputs);
+    }
+    for o in reflection.overrides.iter() {
+        let name = o.name.as_deref().unwrap_or("_");
+        let id = match o.id {
+            Some(id) => format!("@id({id}) "),
+            None => String::new(),
+        };
+        match o.default {
+            Some(default) => println!("override {id}{name} = {default:?}"),
+            None if o.has_default => println!("override {id}{name} = <expression>"),
+            None => println!("override {id}{name}"),
+        }
+    }
+    Ok(())
+}
+
+fn print_required_features(
+    module: &naga::Module,
+    info: &naga::valid::ModuleInfo,
+    input_text: Option<&str>,
+    path: &Path,
+) {
+    fn join<T>(names: impl Iterator<Item = (&'static str, T)>) -> String {
+        let names: Vec<_> = names.map(|(name, _)| name).collect();
+        if names.is_empty() {
+            "none".to_string()
+        } else {
+            names.join(" | ")
+        }
+    }
+
+    let requirements = naga::valid::required_capabilities(module, info);
+    println!(
+        "Capabilities: {}",
+        join(requirements.capabilities.iter_names())
+    );
+    println!(
+        "Subgroup stages: {}",
+        join(requirements.subgroup_stages.iter_names())
+    );
+    println!(
+        "Subgroup operations: {}",
+        join(requirements.subgroup_operations.iter_names())
+    );
+
+    // Only list uses without a location for capabilities that have no other.
+    let located = requirements
+        .uses
+        .iter()
+        .filter(|use_| input_text.is_some() && use_.span.is_defined())
+        .fold(naga::valid::Capabilities::empty(), |caps, use_| {
+            caps | use_.capabilities
+        });
+    for use_ in requirements.uses.iter() {
+        if !use_.span.is_defined() && located.contains(use_.capabilities) {
+            continue;
+        }
+        let location = match input_text {
+            Some(text) if use_.span.is_defined() => {
+                let location = use_.span.location(text);
+                format!(
+                    "{}:{}:{}",
+                    path.display(),
+                    location.line_number,
+                    location.line_position
+                )
+            }
+            _ => path.display().to_string(),
+        };
+        let mut names = join(use_.capabilities.iter_names());
+        if !use_.subgroup_operations.is_empty() {
+            names += &format!(
+                " (subgroup operations {})",
+                join(use_.subgroup_operations.iter_names())
+            );
+        }
+        println!("  {location}: {names}");
+    }
+}
+
+fn format_files(args: FmtArgs) -> anyhow::Result<()> {
+    if args.files.is_empty() {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        let output = naga::front::wgsl::format_str(&input).unwrap_or_else(|error| {
+            error.emit_to_stderr_with_path(&input, "stdin");
+            std::process::exit(1);
+        });
+        if !args.check {
+            print!("{output}");
+        } else if output != input {
+            return Err(anyhow!("The input is not formatted"));
+        }
+        return Ok(());
+    }
+
+    let mut unformatted = vec![];
+    for path in args.files {
+        let input = fs::read_to_string(&path).with_context(|| format!("reading {path}"))?;
+        let output = match naga::front::wgsl::format_str(&input) {
+            Ok(output) => output,
+            Err(error) => {
+                error.emit_to_stderr_with_path(&input, &path);
+                return Err(anyhow!("Could not parse {path}"));
+            }
+        };
+        if output == input {
+            continue;
+        }
+        if args.check {
+            unformatted.push(path);
+        } else {
+            fs::write(&path, output).with_context(|| format!("writing {path}"))?;
+        }
+    }
+
+    if !unformatted.is_empty() {
+        use std::fmt::Write;
+        let mut formatted = String::new();
+        writeln!(&mut formatted, "The following files are not formatted:").unwrap();
+        for path in unformatted {
+            writeln!(&mut formatted, "  {path}").unwrap();
+        }
+        return Err(anyhow!(formatted));
+    }
+
+    Ok(())
+}
+
 use codespan_reporting::term::termcolor::{ColorChoice, StandardStream};
 use naga::FastHashMap;
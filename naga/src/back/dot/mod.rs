@@ -364,12 +364,13 @@ impl StatementGraph {
                     result,
                 } => {
                     match mode {
-                        crate::GatherMode::BroadcastFirst => {}
+                        crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                         crate::GatherMode::Broadcast(index)
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             self.dependencies.push((id, index, "index"))
                         }
                     }
@@ -382,6 +383,8 @@ impl StatementGraph {
                         crate::GatherMode::ShuffleDown(_) => "SubgroupShuffleDown",
                         crate::GatherMode::ShuffleUp(_) => "SubgroupShuffleUp",
                         crate::GatherMode::ShuffleXor(_) => "SubgroupShuffleXor",
+                        crate::GatherMode::QuadBroadcast(_) => "SubgroupQuadBroadcast",
+                        crate::GatherMode::QuadSwap(_) => "SubgroupQuadSwap",
                     }
                 }
             };
@@ -54,6 +54,10 @@ bitflags::bitflags! {
         const SUBGROUP_OPERATIONS = 1 << 24;
         /// Image atomics
         const TEXTURE_ATOMICS = 1 << 25;
+        /// 2 byte floats.
+        const FLOAT16 = 1 << 26;
+        /// Subgroup quad operations
+        const SUBGROUP_QUAD = 1 << 27;
     }
 }
 
@@ -123,6 +127,8 @@ impl FeaturesManager {
         check_feature!(DUAL_SOURCE_BLENDING, 330, 300 /* with extension */);
         check_feature!(SUBGROUP_OPERATIONS, 430, 310);
         check_feature!(TEXTURE_ATOMICS, 420, 310);
+        check_feature!(FLOAT16, 450, 310);
+        check_feature!(SUBGROUP_QUAD, 430, 310);
         match version {
             Version::Embedded { is_webgl: true, .. } => check_feature!(MULTI_VIEW, 140, 300),
             _ => check_feature!(MULTI_VIEW, 140, 310),
@@ -281,11 +287,24 @@ impl FeaturesManager {
             )?;
         }
 
+        if self.0.contains(Features::SUBGROUP_QUAD) {
+            // https://registry.khronos.org/OpenGL/extensions/KHR/KHR_shader_subgroup.txt
+            writeln!(out, "#extension GL_KHR_shader_subgroup_quad : require")?;
+        }
+
         if self.0.contains(Features::TEXTURE_ATOMICS) {
             // https://www.khronos.org/registry/OpenGL/extensions/OES/OES_shader_image_atomic.txt
             writeln!(out, "#extension GL_OES_shader_image_atomic : require")?;
         }
 
+        if self.0.contains(Features::FLOAT16) {
+            // https://github.com/KhronosGroup/GLSL/blob/main/extensions/ext/GL_EXT_shader_explicit_arithmetic_types.txt
+            writeln!(
+                out,
+                "#extension GL_EXT_shader_explicit_arithmetic_types_float16 : require"
+            )?;
+        }
+
         Ok(())
     }
 }
@@ -569,6 +588,9 @@ impl<W> Writer<'_, W> {
                     _ => {}
                 }
             }
+            if uses_quad_operations(blocks) {
+                features.request(Features::SUBGROUP_QUAD)
+            }
         }
 
         self.features.check_availability(self.options.version)
@@ -576,8 +598,12 @@ impl<W> Writer<'_, W> {
 
     /// Helper method that checks the [`Features`] needed by a scalar
     fn scalar_required_features(&mut self, scalar: Scalar) {
-        if scalar.kind == ScalarKind::Float && scalar.width == 8 {
-            self.features.request(Features::DOUBLE_TYPE);
+        if scalar.kind == ScalarKind::Float {
+            match scalar.width {
+                2 => self.features.request(Features::FLOAT16),
+                8 => self.features.request(Features::DOUBLE_TYPE),
+                _ => {}
+            }
         }
     }
 
@@ -620,3 +646,28 @@ impl<W> Writer<'_, W> {
         }
     }
 }
+
+/// Returns `true` if `block`, or a block nested in it, uses a quad operation.
+fn uses_quad_operations(block: &crate::Block) -> bool {
+    block.iter().any(|stmt| match *stmt {
+        crate::Statement::Block(ref block) => uses_quad_operations(block),
+        crate::Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => uses_quad_operations(accept) || uses_quad_operations(reject),
+        crate::Statement::Switch { ref cases, .. } => {
+            cases.iter().any(|case| uses_quad_operations(&case.body))
+        }
+        crate::Statement::Loop {
+            ref body,
+            ref continuing,
+            ..
+        } => uses_quad_operations(body) || uses_quad_operations(continuing),
+        crate::Statement::SubgroupGather {
+            mode: crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_),
+            ..
+        } => true,
+        _ => false,
+    })
+}
@@ -488,5 +488,7 @@ pub const RESERVED_KEYWORDS: &[&str] = &[
     // Naga utilities:
     super::MODF_FUNCTION,
     super::FREXP_FUNCTION,
+    super::PACK_4X_I8_CLAMP_FUNCTION,
+    super::PACK_4X_U8_CLAMP_FUNCTION,
     super::FIRST_INSTANCE_BINDING,
 ];
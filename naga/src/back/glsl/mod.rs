@@ -54,7 +54,7 @@ use features::FeaturesManager;
 use std::{
     cmp::Ordering,
     fmt::{self, Error as FmtError, Write},
-    mem,
+    iter, mem,
 };
 use thiserror::Error;
 
@@ -74,6 +74,8 @@ const CLAMPED_LOD_SUFFIX: &str = "_clamped_lod";
 
 pub(crate) const MODF_FUNCTION: &str = "naga_modf";
 pub(crate) const FREXP_FUNCTION: &str = "naga_frexp";
+pub(crate) const PACK_4X_I8_CLAMP_FUNCTION: &str = "naga_pack4xI8Clamp";
+pub(crate) const PACK_4X_U8_CLAMP_FUNCTION: &str = "naga_pack4xU8Clamp";
 
 // Must match code in glsl_built_in
 pub const FIRST_INSTANCE_BINDING: &str = "naga_vs_first_instance";
@@ -91,7 +93,7 @@ impl crate::AtomicFunction {
             Self::Min => "Min",
             Self::Max => "Max",
             Self::Exchange { compare: None } => "Exchange",
-            Self::Exchange { compare: Some(_) } => "", //TODO
+            Self::Exchange { compare: Some(_) } => "CompSwap",
         }
     }
 }
@@ -499,6 +501,8 @@ pub enum Error {
     Custom(String),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error("functions with uniform, storage, or workgroup pointer parameters should be specialized at this stage")]
+    PointerParameter,
     /// [`crate::Sampling::First`] is unsupported.
     #[error("`{:?}` sampling is unsupported", crate::Sampling::First)]
     FirstSamplingNotSupported,
@@ -578,6 +582,9 @@ impl<'a, W: Write> Writer<'a, W> {
         if !module.overrides.is_empty() {
             return Err(Error::Override);
         }
+        if super::pointer_parameters::needs_specialization(module) {
+            return Err(Error::PointerParameter);
+        }
 
         // Check if the requested version is supported
         if !options.version.is_supported() {
@@ -749,18 +756,13 @@ impl<'a, W: Write> Writer<'a, W> {
             match type_key {
                 &crate::PredeclaredType::ModfResult { size, scalar }
                 | &crate::PredeclaredType::FrexpResult { size, scalar } => {
+                    let scalar_string = glsl_scalar(scalar)?;
                     let arg_type_name_owner;
                     let arg_type_name = if let Some(size) = size {
-                        arg_type_name_owner = format!(
-                            "{}vec{}",
-                            if scalar.width == 8 { "d" } else { "" },
-                            size as u8
-                        );
+                        arg_type_name_owner = format!("{}vec{}", scalar_string.prefix, size as u8);
                         &arg_type_name_owner
-                    } else if scalar.width == 8 {
-                        "double"
                     } else {
-                        "float"
+                        scalar_string.full
                     };
 
                     let other_type_name_owner;
@@ -806,6 +808,8 @@ impl<'a, W: Write> Writer<'a, W> {
             }
         }
 
+        self.write_pack4x8_clamp_functions()?;
+
         // Write all named constants
         let mut constants = self
             .module
@@ -1334,6 +1338,10 @@ impl<'a, W: Write> Writer<'a, W> {
                             self.need_bake_expressions.insert(arg1.unwrap());
                         }
                     }
+                    crate::MathFunction::Dot4I8Packed | crate::MathFunction::Dot4U8Packed => {
+                        self.need_bake_expressions.insert(arg);
+                        self.need_bake_expressions.insert(arg1.unwrap());
+                    }
                     crate::MathFunction::Pack4xI8
                     | crate::MathFunction::Pack4xU8
                     | crate::MathFunction::Unpack4xI8
@@ -1912,6 +1920,49 @@ impl<'a, W: Write> Writer<'a, W> {
         Ok(())
     }
 
+    /// Helper method used to write the polyfills of `pack4xI8Clamp` and
+    /// `pack4xU8Clamp`, so that their argument is only clamped once
+    fn write_pack4x8_clamp_functions(&mut self) -> BackendResult {
+        use crate::MathFunction as Mf;
+
+        let module = self.module;
+        let entry_point = self.entry_point;
+        let uses = |fun: Mf| {
+            module
+                .functions
+                .iter()
+                .map(|(_, function)| function)
+                .chain(iter::once(&entry_point.function))
+                .flat_map(|function| function.expressions.iter())
+                .any(|(_, expr)| {
+                    matches!(*expr, crate::Expression::Math { fun: other, .. } if other == fun)
+                })
+        };
+
+        if uses(Mf::Pack4xI8Clamp) {
+            writeln!(self.out)?;
+            writeln!(
+                self.out,
+                "uint {PACK_4X_I8_CLAMP_FUNCTION}(ivec4 arg) {{
+    ivec4 clamped = clamp(arg, -128, 127);
+    return uint((clamped[0] & 0xFF) | ((clamped[1] & 0xFF) << 8) | ((clamped[2] & 0xFF) << 16) | ((clamped[3] & 0xFF) << 24));
+}}",
+            )?;
+        }
+        if uses(Mf::Pack4xU8Clamp) {
+            writeln!(self.out)?;
+            writeln!(
+                self.out,
+                "uint {PACK_4X_U8_CLAMP_FUNCTION}(uvec4 arg) {{
+    uvec4 clamped = clamp(arg, 0u, 255u);
+    return (clamped[0] & 0xFFu) | ((clamped[1] & 0xFFu) << 8) | ((clamped[2] & 0xFFu) << 16) | ((clamped[3] & 0xFFu) << 24);
+}}",
+            )?;
+        }
+
+        Ok(())
+    }
+
     /// Helper method used to write structs
     ///
     /// # Notes
@@ -2446,6 +2497,13 @@ impl<'a, W: Write> Writer<'a, W> {
                 value,
                 result,
             } => {
+                if let (crate::AtomicFunction::Exchange { compare: Some(cmp) }, Some(result)) =
+                    (*fun, result)
+                {
+                    return self
+                        .write_atomic_compare_exchange(pointer, cmp, value, result, ctx, level);
+                }
+
                 write!(self.out, "{level}")?;
                 if let Some(result) = result {
                     let res_name = Baked(result).to_string();
@@ -2465,10 +2523,9 @@ impl<'a, W: Write> Writer<'a, W> {
                         // we just wrote `InterlockedAdd`, so negate the argument
                         write!(self.out, "-")?;
                     }
-                    crate::AtomicFunction::Exchange { compare: Some(_) } => {
-                        return Err(Error::Custom(
-                            "atomic CompareExchange is not implemented".to_string(),
-                        ));
+                    crate::AtomicFunction::Exchange { compare: Some(cmp) } => {
+                        self.write_expr(cmp, ctx)?;
+                        write!(self.out, ", ")?;
                     }
                     _ => {}
                 }
@@ -2591,15 +2648,26 @@ impl<'a, W: Write> Writer<'a, W> {
                     crate::GatherMode::ShuffleXor(_) => {
                         write!(self.out, "subgroupShuffleXor(")?;
                     }
+                    crate::GatherMode::QuadBroadcast(_) => {
+                        write!(self.out, "subgroupQuadBroadcast(")?;
+                    }
+                    crate::GatherMode::QuadSwap(direction) => match direction {
+                        crate::Direction::X => write!(self.out, "subgroupQuadSwapHorizontal(")?,
+                        crate::Direction::Y => write!(self.out, "subgroupQuadSwapVertical(")?,
+                        crate::Direction::Diagonal => {
+                            write!(self.out, "subgroupQuadSwapDiagonal(")?
+                        }
+                    },
                 }
                 self.write_expr(argument, ctx)?;
                 match mode {
-                    crate::GatherMode::BroadcastFirst => {}
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                     crate::GatherMode::Broadcast(index)
                     | crate::GatherMode::Shuffle(index)
                     | crate::GatherMode::ShuffleDown(index)
                     | crate::GatherMode::ShuffleUp(index)
-                    | crate::GatherMode::ShuffleXor(index) => {
+                    | crate::GatherMode::ShuffleXor(index)
+                    | crate::GatherMode::QuadBroadcast(index) => {
                         write!(self.out, ", ")?;
                         self.write_expr(index, ctx)?;
                     }
@@ -2668,6 +2736,7 @@ impl<'a, W: Write> Writer<'a, W> {
                     // decimal part even it's zero which is needed for a valid glsl float constant
                     crate::Literal::F64(value) => write!(self.out, "{value:?}LF")?,
                     crate::Literal::F32(value) => write!(self.out, "{value:?}")?,
+                    crate::Literal::F16(value) => write!(self.out, "{value:?}hf")?,
                     // Unsigned integers need a `u` at the end
                     //
                     // While `core` doesn't necessarily need it, it's allowed and since `es` needs it we
@@ -3470,6 +3539,39 @@ impl<'a, W: Write> Writer<'a, W> {
                             "Correct TypeInner for dot product should be already validated"
                         ),
                     },
+                    fun @ (Mf::Dot4I8Packed | Mf::Dot4U8Packed) => {
+                        let is_signed = fun == Mf::Dot4I8Packed;
+                        let arg1 = arg1.unwrap();
+                        let write_component = |this: &mut Self, operand, offset| -> BackendResult {
+                            write!(this.out, "bitfieldExtract(")?;
+                            // Since bitfieldExtract only sign extends if the value is signed, this
+                            // cast is needed
+                            if is_signed {
+                                write!(this.out, "int(")?;
+                                this.write_expr(operand, ctx)?;
+                                write!(this.out, ")")?;
+                            } else {
+                                this.write_expr(operand, ctx)?;
+                            }
+                            write!(this.out, ", {offset}, 8)")?;
+                            Ok(())
+                        };
+
+                        // Write parentheses around the dot product expression to prevent operators
+                        // with different precedences from applying earlier.
+                        write!(self.out, "(")?;
+                        for i in 0..4 {
+                            if i != 0 {
+                                write!(self.out, " + ")?;
+                            }
+                            write_component(self, arg, i * 8)?;
+                            write!(self.out, " * ")?;
+                            write_component(self, arg1, i * 8)?;
+                        }
+                        write!(self.out, ")")?;
+
+                        return Ok(());
+                    }
                     Mf::Outer => "outerProduct",
                     Mf::Cross => "cross",
                     Mf::Distance => "distance",
@@ -3721,6 +3823,8 @@ impl<'a, W: Write> Writer<'a, W> {
                     Mf::Pack2x16snorm => "packSnorm2x16",
                     Mf::Pack2x16unorm => "packUnorm2x16",
                     Mf::Pack2x16float => "packHalf2x16",
+                    Mf::Pack4xI8Clamp => PACK_4X_I8_CLAMP_FUNCTION,
+                    Mf::Pack4xU8Clamp => PACK_4X_U8_CLAMP_FUNCTION,
                     fun @ (Mf::Pack4xI8 | Mf::Pack4xU8) => {
                         let was_signed = match fun {
                             Mf::Pack4xI8 => true,
@@ -4198,6 +4302,60 @@ impl<'a, W: Write> Writer<'a, W> {
         Ok(())
     }
 
+    /// Helper method to write an `Atomic` statement performing a
+    /// compare-exchange whose result is used.
+    ///
+    /// GLSL's `atomicCompSwap` only returns the original value, so we fill in
+    /// the `__atomic_compare_exchange_result` struct ourselves.
+    fn write_atomic_compare_exchange(
+        &mut self,
+        pointer: Handle<crate::Expression>,
+        cmp: Handle<crate::Expression>,
+        value: Handle<crate::Expression>,
+        result: Handle<crate::Expression>,
+        ctx: &back::FunctionCtx,
+        level: back::Level,
+    ) -> Result<(), Error> {
+        // The comparison operand is used twice, so make sure it's only
+        // evaluated once.
+        if !self.named_expressions.contains_key(&cmp) {
+            write!(self.out, "{level}")?;
+            self.write_named_expr(cmp, Baked(cmp).to_string(), cmp, ctx)?;
+        }
+
+        // Validation ensures that the result is an
+        // `__atomic_compare_exchange_result` struct.
+        let res_ty = match ctx.info[result].ty {
+            proc::TypeResolution::Handle(handle) => handle,
+            proc::TypeResolution::Value(_) => unreachable!(),
+        };
+        let res_name = Baked(result).to_string();
+        write!(self.out, "{level}")?;
+        self.write_type(res_ty)?;
+        writeln!(self.out, " {res_name};")?;
+
+        let old_value = &self.names[&NameKey::StructMember(res_ty, 0)];
+        write!(self.out, "{level}{res_name}.{old_value} = atomicCompSwap(")?;
+        self.write_expr(pointer, ctx)?;
+        write!(self.out, ", ")?;
+        self.write_expr(cmp, ctx)?;
+        write!(self.out, ", ")?;
+        self.write_expr(value, ctx)?;
+        writeln!(self.out, ");")?;
+
+        let old_value = &self.names[&NameKey::StructMember(res_ty, 0)];
+        let exchanged = &self.names[&NameKey::StructMember(res_ty, 1)];
+        write!(
+            self.out,
+            "{level}{res_name}.{exchanged} = ({res_name}.{old_value} == "
+        )?;
+        self.write_expr(cmp, ctx)?;
+        writeln!(self.out, ");")?;
+
+        self.named_expressions.insert(result, res_name);
+        Ok(())
+    }
+
     /// Helper method for writing an `ImageLoad` expression.
     #[allow(clippy::too_many_arguments)]
     fn write_image_load(
@@ -4584,6 +4742,9 @@ impl<'a, W: Write> Writer<'a, W> {
         if flags.contains(crate::Barrier::SUB_GROUP) {
             writeln!(self.out, "{level}subgroupMemoryBarrier();")?;
         }
+        if flags.contains(crate::Barrier::TEXTURE) {
+            writeln!(self.out, "{level}memoryBarrierImage();")?;
+        }
         writeln!(self.out, "{level}barrier();")?;
         Ok(())
     }
@@ -4773,7 +4934,7 @@ struct ScalarString<'a> {
 /// Check [`ScalarString`] for the information provided
 ///
 /// # Errors
-/// If a [`Float`](crate::ScalarKind::Float) with an width that isn't 4 or 8
+/// If a [`Float`](crate::ScalarKind::Float) with an width that isn't 2, 4 or 8
 const fn glsl_scalar(scalar: crate::Scalar) -> Result<ScalarString<'static>, Error> {
     use crate::ScalarKind as Sk;
 
@@ -4787,6 +4948,10 @@ const fn glsl_scalar(scalar: crate::Scalar) -> Result<ScalarString<'static>, Err
             full: "uint",
         },
         Sk::Float => match scalar.width {
+            2 => ScalarString {
+                prefix: "f16",
+                full: "float16_t",
+            },
             4 => ScalarString {
                 prefix: "",
                 full: "float",
@@ -28,7 +28,10 @@ int dim_1d = NagaDimensions1D(image_1d);
 
 use super::{
     super::FunctionCtx,
-    writer::{EXTRACT_BITS_FUNCTION, INSERT_BITS_FUNCTION},
+    writer::{
+        EXTRACT_BITS_FUNCTION, INSERT_BITS_FUNCTION, PACK_4X_I8_CLAMP_FUNCTION,
+        PACK_4X_U8_CLAMP_FUNCTION,
+    },
     BackendResult,
 };
 use crate::{arena::Handle, proc::NameKey};
@@ -798,18 +801,13 @@ impl<W: Write> super::Writer<'_, W> {
             match type_key {
                 &crate::PredeclaredType::ModfResult { size, scalar }
                 | &crate::PredeclaredType::FrexpResult { size, scalar } => {
+                    let scalar_name = scalar.to_hlsl_str()?;
                     let arg_type_name_owner;
                     let arg_type_name = if let Some(size) = size {
-                        arg_type_name_owner = format!(
-                            "{}{}",
-                            if scalar.width == 8 { "double" } else { "float" },
-                            size as u8
-                        );
+                        arg_type_name_owner = format!("{scalar_name}{}", size as u8);
                         &arg_type_name_owner
-                    } else if scalar.width == 8 {
-                        "double"
                     } else {
-                        "float"
+                        scalar_name
                     };
 
                     let (defined_func_name, called_func_name, second_field_name, sign_multiplier) =
@@ -1031,6 +1029,53 @@ impl<W: Write> super::Writer<'_, W> {
                         // End of function body
                         writeln!(self.out, "}}")?;
                     }
+                    crate::MathFunction::Pack4xI8Clamp | crate::MathFunction::Pack4xU8Clamp => {
+                        // Clamp the argument once and pack the components of
+                        // the result, like the `pack4xI8` and `pack4xU8` polyfills.
+                        let arg_ty = func_ctx.resolve_type(arg, &module.types);
+                        let scalar = arg_ty.scalar().unwrap();
+                        let components = arg_ty.components();
+
+                        let wrapped = WrappedMath {
+                            fun,
+                            scalar,
+                            components,
+                        };
+
+                        if !self.wrapped.math.insert(wrapped) {
+                            continue;
+                        }
+
+                        let (function_name, min, max) = match fun {
+                            crate::MathFunction::Pack4xI8Clamp => {
+                                (PACK_4X_I8_CLAMP_FUNCTION, "-128", "127")
+                            }
+                            _ => (PACK_4X_U8_CLAMP_FUNCTION, "0", "255"),
+                        };
+
+                        // Write function name and parameters
+                        writeln!(self.out, "uint {function_name}(")?;
+                        write!(self.out, "    ")?;
+                        self.write_value_type(module, arg_ty)?;
+                        writeln!(self.out, " e")?;
+                        writeln!(self.out, ") {{")?;
+
+                        // Write function body
+                        write!(self.out, "    ")?;
+                        self.write_value_type(module, arg_ty)?;
+                        writeln!(self.out, " c = clamp(e, {min}, {max});")?;
+                        write!(self.out, "    return ")?;
+                        if scalar.kind == crate::ScalarKind::Sint {
+                            write!(self.out, "uint")?;
+                        }
+                        writeln!(
+                            self.out,
+                            "((c[0] & 0xFF) | ((c[1] & 0xFF) << 8) | ((c[2] & 0xFF) << 16) | ((c[3] & 0xFF) << 24));"
+                        )?;
+
+                        // End of function body
+                        writeln!(self.out, "}}")?;
+                    }
                     _ => {}
                 }
             }
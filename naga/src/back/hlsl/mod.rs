@@ -123,6 +123,36 @@ pub struct BindTarget {
 // Using `BTreeMap` instead of `HashMap` so that we can hash itself.
 pub type BindingMap = std::collections::BTreeMap<crate::ResourceBinding, BindTarget>;
 
+/// Where the writer loads descriptor heap indices from, in
+/// [`DescriptorHeapOptions`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+pub enum DescriptorHeapIndexSource {
+    /// A `cbuffer` of `uint`s, such as a block of root constants.
+    ConstantBuffer(BindTarget),
+    /// A `ByteAddressBuffer` of `uint`s.
+    Buffer(BindTarget),
+}
+
+/// Configuration for accessing resources through the SM 6.6
+/// `ResourceDescriptorHeap` and `SamplerDescriptorHeap`.
+///
+/// Each resource binding is assigned a slot in a table of `uint` heap
+/// indices, which the host fills in. A resource is fetched from the heap at
+/// the index in its slot; element `i` of a binding array is fetched at that
+/// index plus `i`. Samplers index `SamplerDescriptorHeap`, and everything else
+/// indexes `ResourceDescriptorHeap`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+pub struct DescriptorHeapOptions {
+    /// Where the table of heap indices is bound.
+    pub index_source: DescriptorHeapIndexSource,
+    /// Map of resources to their slot in the table of heap indices.
+    pub index_map: std::collections::BTreeMap<crate::ResourceBinding, u32>,
+}
+
 /// A HLSL shader model version.
 #[allow(non_snake_case, non_camel_case_types)]
 #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, PartialOrd)]
@@ -211,6 +241,10 @@ pub struct Options {
     pub zero_initialize_workgroup_memory: bool,
     /// Should we restrict indexing of vectors, matrices and arrays?
     pub restrict_indexing: bool,
+    /// If set, resources are fetched from the descriptor heaps instead of
+    /// being bound to registers through [`binding_map`](Self::binding_map).
+    /// Requires shader model 6.6.
+    pub descriptor_heap: Option<DescriptorHeapOptions>,
 }
 
 impl Default for Options {
@@ -223,6 +257,7 @@ impl Default for Options {
             push_constants_target: None,
             zero_initialize_workgroup_memory: true,
             restrict_indexing: true,
+            descriptor_heap: None,
         }
     }
 }
@@ -242,6 +277,31 @@ impl Options {
             None => Err(EntryPointError::MissingBinding(res_binding.clone())),
         }
     }
+
+    fn resolve_descriptor_heap_slot(
+        &self,
+        heap: &DescriptorHeapOptions,
+        res_binding: &crate::ResourceBinding,
+    ) -> Result<u32, EntryPointError> {
+        match heap.index_map.get(res_binding) {
+            Some(&slot) => Ok(slot),
+            None if self.fake_missing_bindings => Ok(res_binding.binding),
+            None => Err(EntryPointError::MissingBinding(res_binding.clone())),
+        }
+    }
+
+    /// Check that `res_binding` has a register or descriptor heap slot.
+    fn check_resource_binding(
+        &self,
+        res_binding: &crate::ResourceBinding,
+    ) -> Result<(), EntryPointError> {
+        match self.descriptor_heap {
+            Some(ref heap) => self
+                .resolve_descriptor_heap_slot(heap, res_binding)
+                .map(|_| ()),
+            None => self.resolve_resource_binding(res_binding).map(|_| ()),
+        }
+    }
 }
 
 /// Reflection info for entry point names.
@@ -268,6 +328,10 @@ pub enum Error {
     Custom(String),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error("functions with uniform, storage, or workgroup pointer parameters should be specialized at this stage")]
+    PointerParameter,
+    #[error("descriptor heap access requires shader model 6.6 or later")]
+    UnsupportedDescriptorHeap,
 }
 
 #[derive(Default)]
@@ -355,4 +419,7 @@ pub struct Writer<'a, W> {
     /// [`AccessIndex`]: crate::Expression::AccessIndex
     temp_access_chain: Vec<storage::SubAccess>,
     need_bake_expressions: back::NeedBakeExpressions,
+    /// Names of the functions that fetch elements of binding arrays in the
+    /// descriptor heap with `NonUniformResourceIndex`.
+    non_uniform_heap_helpers: crate::FastHashMap<crate::Handle<crate::GlobalVariable>, String>,
 }
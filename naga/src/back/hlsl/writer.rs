@@ -4,7 +4,8 @@ use super::{
         WrappedZeroValue,
     },
     storage::StoreValue,
-    BackendResult, Error, FragmentEntryPoint, Options,
+    BackendResult, DescriptorHeapIndexSource, DescriptorHeapOptions, Error, FragmentEntryPoint,
+    Options, ShaderModel,
 };
 use crate::{
     back::{self, Baked},
@@ -19,11 +20,15 @@ const SPECIAL_CBUF_VAR: &str = "_NagaConstants";
 const SPECIAL_FIRST_VERTEX: &str = "first_vertex";
 const SPECIAL_FIRST_INSTANCE: &str = "first_instance";
 const SPECIAL_OTHER: &str = "other";
+const DESCRIPTOR_HEAP_INDICES_TYPE: &str = "NagaDescriptorHeapIndices";
+const DESCRIPTOR_HEAP_INDICES_VAR: &str = "_NagaDescriptorHeapIndices";
 
 pub(crate) const MODF_FUNCTION: &str = "naga_modf";
 pub(crate) const FREXP_FUNCTION: &str = "naga_frexp";
 pub(crate) const EXTRACT_BITS_FUNCTION: &str = "naga_extractBits";
 pub(crate) const INSERT_BITS_FUNCTION: &str = "naga_insertBits";
+pub(crate) const PACK_4X_I8_CLAMP_FUNCTION: &str = "naga_pack4xI8Clamp";
+pub(crate) const PACK_4X_U8_CLAMP_FUNCTION: &str = "naga_pack4xU8Clamp";
 
 struct EpStructMember {
     name: String,
@@ -109,6 +114,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             continue_ctx: back::continue_forward::ContinueCtx::default(),
             temp_access_chain: Vec::new(),
             need_bake_expressions: Default::default(),
+            non_uniform_heap_helpers: Default::default(),
         }
     }
 
@@ -129,6 +135,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         self.written_candidate_intersection = false;
         self.continue_ctx.clear();
         self.need_bake_expressions.clear();
+        self.non_uniform_heap_helpers.clear();
     }
 
     /// Helper method used to find which expressions of a given function require baking
@@ -150,7 +157,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 self.need_bake_expressions.insert(fun_handle);
             }
 
-            if let Expression::Math { fun, arg, .. } = *expr {
+            if let Expression::Math { fun, arg, arg1, .. } = *expr {
                 match fun {
                     crate::MathFunction::Asinh
                     | crate::MathFunction::Acosh
@@ -171,6 +178,12 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     | crate::MathFunction::Pack4xU8 => {
                         self.need_bake_expressions.insert(arg);
                     }
+                    crate::MathFunction::Dot4I8Packed | crate::MathFunction::Dot4U8Packed
+                        if self.options.shader_model < ShaderModel::V6_4 =>
+                    {
+                        self.need_bake_expressions.insert(arg);
+                        self.need_bake_expressions.insert(arg1.unwrap());
+                    }
                     crate::MathFunction::CountLeadingZeros => {
                         let inner = info[fun_handle].ty.inner_with(&module.types);
                         if let Some(ScalarKind::Sint) = inner.scalar_kind() {
@@ -212,6 +225,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         if !module.overrides.is_empty() {
             return Err(Error::Override);
         }
+        if back::pointer_parameters::needs_specialization(module) {
+            return Err(Error::PointerParameter);
+        }
 
         self.reset(module);
 
@@ -236,6 +252,17 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             writeln!(self.out)?;
         }
 
+        // Write the table of descriptor heap indices, if needed
+        if let Some(ref heap) = self.options.descriptor_heap {
+            if self.options.shader_model < ShaderModel::V6_6 {
+                return Err(Error::UnsupportedDescriptorHeap);
+            }
+            self.write_descriptor_heap_indices(heap)?;
+
+            // Extra newline for readability
+            writeln!(self.out)?;
+        }
+
         // Save all entry point output types
         let ep_results = module
             .entry_points
@@ -330,7 +357,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         .iter()
                         .find(|&(var_handle, var)| match var.binding {
                             Some(ref binding) if !info[var_handle].is_empty() => {
-                                self.options.resolve_resource_binding(binding).is_err()
+                                self.options.check_resource_binding(binding).is_err()
                             }
                             _ => false,
                         })
@@ -372,7 +399,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 for (var_handle, var) in module.global_variables.iter() {
                     match var.binding {
                         Some(ref binding) if !info[var_handle].is_empty() => {
-                            if let Err(err) = self.options.resolve_resource_binding(binding) {
+                            if let Err(err) = self.options.check_resource_binding(binding) {
                                 ep_error = Some(err);
                                 break;
                             }
@@ -510,6 +537,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
             }
             self.write_type(module, m.ty)?;
             write!(self.out, " {}", &m.name)?;
+            if let TypeInner::Array { base, size, .. } = module.types[m.ty].inner {
+                self.write_array_size(module, base, size)?;
+            }
             self.write_semantic(&m.binding, Some(shader_stage))?;
             writeln!(self.out, ";")?;
         }
@@ -803,7 +833,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         let inner = &module.types[global.ty].inner;
 
         if let Some(ref binding) = global.binding {
-            if let Err(err) = self.options.resolve_resource_binding(binding) {
+            if let Err(err) = self.options.check_resource_binding(binding) {
                 log::info!(
                     "Skipping global {:?} (name {:?}) for being inaccessible: {}",
                     handle,
@@ -812,6 +842,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 );
                 return Ok(());
             }
+
+            if let Some(ref heap) = self.options.descriptor_heap {
+                return self.write_descriptor_heap_global(module, heap, handle, binding);
+            }
         }
 
         // https://docs.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl-variable-register
@@ -956,6 +990,213 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         Ok(())
     }
 
+    /// Helper method used to write the table of descriptor heap indices
+    ///
+    /// # Notes
+    /// Always adds a newline
+    fn write_descriptor_heap_indices(&mut self, heap: &DescriptorHeapOptions) -> BackendResult {
+        match heap.index_source {
+            DescriptorHeapIndexSource::ConstantBuffer(ref bt) => {
+                // `cbuffer` arrays are padded to 16 bytes per element, so pack
+                // the indices into `uint4`s.
+                let count = heap.index_map.values().max().map_or(1, |&max| max / 4 + 1);
+                write!(
+                    self.out,
+                    "cbuffer {DESCRIPTOR_HEAP_INDICES_TYPE} : register(b{}",
+                    bt.register
+                )?;
+                if bt.space != 0 {
+                    write!(self.out, ", space{}", bt.space)?;
+                }
+                writeln!(
+                    self.out,
+                    ") {{ uint4 {DESCRIPTOR_HEAP_INDICES_VAR}[{count}]; }}"
+                )?;
+            }
+            DescriptorHeapIndexSource::Buffer(ref bt) => {
+                write!(
+                    self.out,
+                    "ByteAddressBuffer {DESCRIPTOR_HEAP_INDICES_VAR} : register(t{}",
+                    bt.register
+                )?;
+                if bt.space != 0 {
+                    write!(self.out, ", space{}", bt.space)?;
+                }
+                writeln!(self.out, ");")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper method used to write the descriptor heap index in `slot` of the
+    /// table of descriptor heap indices
+    fn write_descriptor_heap_index(
+        &mut self,
+        heap: &DescriptorHeapOptions,
+        slot: u32,
+    ) -> BackendResult {
+        match heap.index_source {
+            DescriptorHeapIndexSource::ConstantBuffer(_) => write!(
+                self.out,
+                "{DESCRIPTOR_HEAP_INDICES_VAR}[{}].{}",
+                slot / 4,
+                back::COMPONENTS[slot as usize % 4]
+            )?,
+            DescriptorHeapIndexSource::Buffer(_) => {
+                write!(self.out, "{DESCRIPTOR_HEAP_INDICES_VAR}.Load({})", slot * 4)?
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper method used to write the type of a global fetched from the
+    /// descriptor heap, or of the elements of a binding array
+    ///
+    /// Returns the name of the heap to fetch it from.
+    fn write_descriptor_heap_type(
+        &mut self,
+        module: &Module,
+        global: &crate::GlobalVariable,
+    ) -> Result<&'static str, Error> {
+        match global.space {
+            crate::AddressSpace::Uniform => {
+                write!(self.out, "ConstantBuffer<")?;
+                self.write_global_type(module, global.ty)?;
+                if let TypeInner::Array { base, size, .. } = module.types[global.ty].inner {
+                    self.write_array_size(module, base, size)?;
+                }
+                write!(self.out, ">")?;
+            }
+            crate::AddressSpace::Storage { access } => {
+                let prefix = if access.contains(crate::StorageAccess::STORE) {
+                    "RW"
+                } else {
+                    ""
+                };
+                write!(self.out, "{prefix}ByteAddressBuffer")?;
+            }
+            crate::AddressSpace::Handle => {
+                let ty = match module.types[global.ty].inner {
+                    TypeInner::BindingArray { base, .. } => base,
+                    _ => global.ty,
+                };
+                self.write_type(module, ty)?;
+                if let TypeInner::Sampler { .. } = module.types[ty].inner {
+                    return Ok("SamplerDescriptorHeap");
+                }
+            }
+            _ => unreachable!("{:?} globals can't have bindings", global.space),
+        }
+        Ok("ResourceDescriptorHeap")
+    }
+
+    /// Helper method used to write globals fetched from the descriptor heap
+    ///
+    /// Binding arrays become functions from an array index to the element
+    /// at that index, and a second function for non-uniform indices, which
+    /// wraps the heap index in `NonUniformResourceIndex`. Other globals are
+    /// fetched by each function that uses them, see
+    /// [`Self::write_descriptor_heap_locals`].
+    ///
+    /// # Notes
+    /// Always adds a newline, unless nothing is written
+    fn write_descriptor_heap_global(
+        &mut self,
+        module: &Module,
+        heap: &DescriptorHeapOptions,
+        handle: Handle<crate::GlobalVariable>,
+        binding: &crate::ResourceBinding,
+    ) -> BackendResult {
+        let global = &module.global_variables[handle];
+        if let TypeInner::BindingArray { .. } = module.types[global.ty].inner {
+            // this was already resolved earlier by `check_resource_binding`
+            let slot = self
+                .options
+                .resolve_descriptor_heap_slot(heap, binding)
+                .unwrap();
+            let name = self.names[&NameKey::GlobalVariable(handle)].clone();
+            let non_uniform_name = self.namer.call(&format!("{name}_non_uniform"));
+            for (function, non_uniform) in [(name, false), (non_uniform_name.clone(), true)] {
+                let heap_name = self.write_descriptor_heap_type(module, global)?;
+                writeln!(self.out, " {function}(uint index)")?;
+                writeln!(self.out, "{{")?;
+                write!(self.out, "{}return {heap_name}[", back::INDENT)?;
+                if non_uniform {
+                    write!(self.out, "NonUniformResourceIndex(")?;
+                }
+                self.write_descriptor_heap_index(heap, slot)?;
+                write!(self.out, " + index")?;
+                if non_uniform {
+                    write!(self.out, ")")?;
+                }
+                writeln!(self.out, "];")?;
+                writeln!(self.out, "}}")?;
+            }
+            self.non_uniform_heap_helpers
+                .insert(handle, non_uniform_name);
+        }
+        Ok(())
+    }
+
+    /// Helper method used to fetch the globals used by a function from the
+    /// descriptor heap, into locals with the globals' names
+    fn write_descriptor_heap_locals(
+        &mut self,
+        module: &Module,
+        info: &valid::FunctionInfo,
+    ) -> BackendResult {
+        let heap = match self.options.descriptor_heap {
+            Some(ref heap) => heap,
+            None => return Ok(()),
+        };
+
+        let mut wrote_any = false;
+        for (handle, global) in module.global_variables.iter() {
+            let binding = match global.binding {
+                Some(ref binding) if !info[handle].is_empty() => binding,
+                _ => continue,
+            };
+            if let TypeInner::BindingArray { .. } = module.types[global.ty].inner {
+                continue;
+            }
+
+            // this was already resolved earlier by `check_resource_binding`
+            let slot = self
+                .options
+                .resolve_descriptor_heap_slot(heap, binding)
+                .unwrap();
+            write!(self.out, "{}", back::INDENT)?;
+            let heap_name = self.write_descriptor_heap_type(module, global)?;
+            let name = &self.names[&NameKey::GlobalVariable(handle)];
+            write!(self.out, " {name} = {heap_name}[")?;
+            self.write_descriptor_heap_index(heap, slot)?;
+            writeln!(self.out, "];")?;
+            wrote_any = true;
+        }
+
+        if wrote_any {
+            writeln!(self.out)?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if `expr` is a binding array fetched from the
+    /// descriptor heap, which is indexed by calling it.
+    fn is_descriptor_heap_binding_array(
+        &self,
+        module: &Module,
+        expr: Handle<crate::Expression>,
+        func_ctx: &back::FunctionCtx<'_>,
+    ) -> bool {
+        match func_ctx.expressions[expr] {
+            crate::Expression::GlobalVariable(handle) => {
+                self.options.descriptor_heap.is_some()
+                    && module.global_variables[handle].binding.is_some()
+            }
+            _ => false,
+        }
+    }
+
     /// Helper method used to write global constants
     ///
     /// # Notes
@@ -1364,6 +1605,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         writeln!(self.out)?;
         writeln!(self.out, "{{")?;
 
+        self.write_descriptor_heap_locals(module, info)?;
+
         if need_workgroup_variables_initialization {
             self.write_workgroup_variables_initialization(func_ctx, module)?;
         }
@@ -2151,6 +2394,17 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 value,
                 result,
             } => {
+                // `InterlockedCompareExchange` only gives us the original
+                // value, so the comparison operand is needed a second time to
+                // compute the `exchanged` member of the result.
+                if let crate::AtomicFunction::Exchange { compare: Some(cmp) } = *fun {
+                    if result.is_some() && !self.named_expressions.contains_key(&cmp) {
+                        write!(self.out, "{level}")?;
+                        let name = Baked(cmp).to_string();
+                        self.write_named_expr(module, cmp, name, cmp, func_ctx)?;
+                    }
+                }
+
                 write!(self.out, "{level}")?;
                 let res_name = match result {
                     None => None,
@@ -2175,7 +2429,11 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     .pointer_space()
                     .unwrap();
 
-                let fun_str = fun.to_hlsl_suffix();
+                let fun_str = match (fun, result) {
+                    // Without a result, there's no need for the original value.
+                    (&crate::AtomicFunction::Exchange { compare: Some(_) }, None) => "CompareStore",
+                    _ => fun.to_hlsl_suffix(),
+                };
                 match pointer_space {
                     crate::AddressSpace::WorkGroup => {
                         write!(self.out, "Interlocked{fun_str}(")?;
@@ -2209,8 +2467,9 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         // we just wrote `InterlockedAdd`, so negate the argument
                         write!(self.out, "-")?;
                     }
-                    crate::AtomicFunction::Exchange { compare: Some(_) } => {
-                        return Err(Error::Unimplemented("atomic CompareExchange".to_string()));
+                    crate::AtomicFunction::Exchange { compare: Some(cmp) } => {
+                        self.write_expr(module, cmp, func_ctx)?;
+                        write!(self.out, ", ")?;
                     }
                     _ => {}
                 }
@@ -2218,13 +2477,35 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
 
                 // The `original_value` out parameter is optional for all the
                 // `Interlocked` functions we generate other than
-                // `InterlockedExchange`.
-                if let Some((result, name)) = res_name {
-                    write!(self.out, ", {name}")?;
-                    self.named_expressions.insert(result, name);
+                // `InterlockedExchange` and `InterlockedCompareExchange`.
+                match (fun, res_name) {
+                    (
+                        &crate::AtomicFunction::Exchange { compare: Some(cmp) },
+                        Some((result, name)),
+                    ) => {
+                        // Validation ensures that the result is an
+                        // `__atomic_compare_exchange_result` struct.
+                        let ty = match func_ctx.info[result].ty {
+                            proc::TypeResolution::Handle(handle) => handle,
+                            proc::TypeResolution::Value(_) => unreachable!(),
+                        };
+                        let old_value = &self.names[&NameKey::StructMember(ty, 0)];
+                        let exchanged = &self.names[&NameKey::StructMember(ty, 1)];
+                        writeln!(self.out, ", {name}.{old_value});")?;
+                        write!(
+                            self.out,
+                            "{level}{name}.{exchanged} = ({name}.{old_value} == "
+                        )?;
+                        self.write_expr(module, cmp, func_ctx)?;
+                        writeln!(self.out, ");")?;
+                        self.named_expressions.insert(result, name);
+                    }
+                    (_, Some((result, name))) => {
+                        writeln!(self.out, ", {name});")?;
+                        self.named_expressions.insert(result, name);
+                    }
+                    (_, None) => writeln!(self.out, ");")?,
                 }
-
-                writeln!(self.out, ");")?;
             }
             Statement::ImageAtomic {
                 image,
@@ -2395,12 +2676,26 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 if matches!(mode, crate::GatherMode::BroadcastFirst) {
                     write!(self.out, "WaveReadLaneFirst(")?;
                     self.write_expr(module, argument, func_ctx)?;
+                } else if let crate::GatherMode::QuadBroadcast(index) = mode {
+                    write!(self.out, "QuadReadLaneAt(")?;
+                    self.write_expr(module, argument, func_ctx)?;
+                    write!(self.out, ", ")?;
+                    self.write_expr(module, index, func_ctx)?;
+                } else if let crate::GatherMode::QuadSwap(direction) = mode {
+                    match direction {
+                        crate::Direction::X => write!(self.out, "QuadReadAcrossX(")?,
+                        crate::Direction::Y => write!(self.out, "QuadReadAcrossY(")?,
+                        crate::Direction::Diagonal => write!(self.out, "QuadReadAcrossDiagonal(")?,
+                    }
+                    self.write_expr(module, argument, func_ctx)?;
                 } else {
                     write!(self.out, "WaveReadLaneAt(")?;
                     self.write_expr(module, argument, func_ctx)?;
                     write!(self.out, ", ")?;
                     match mode {
-                        crate::GatherMode::BroadcastFirst => unreachable!(),
+                        crate::GatherMode::BroadcastFirst
+                        | crate::GatherMode::QuadBroadcast(_)
+                        | crate::GatherMode::QuadSwap(_) => unreachable!(),
                         crate::GatherMode::Broadcast(index) | crate::GatherMode::Shuffle(index) => {
                             self.write_expr(module, index, func_ctx)?;
                         }
@@ -2456,6 +2751,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                 // decimal part even it's zero
                 crate::Literal::F64(value) => write!(self.out, "{value:?}L")?,
                 crate::Literal::F32(value) => write!(self.out, "{value:?}")?,
+                crate::Literal::F16(value) => write!(self.out, "{value:?}h")?,
                 crate::Literal::U32(value) => write!(self.out, "{value}u")?,
                 crate::Literal::I32(value) => write!(self.out, "{value}")?,
                 crate::Literal::U64(value) => write!(self.out, "{value}uL")?,
@@ -2660,7 +2956,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
 
                     let resolved = func_ctx.resolve_type(base, &module.types);
 
-                    let (indexing_binding_array, non_uniform_qualifier) = match *resolved {
+                    let (indexing_binding_array, mut non_uniform_qualifier) = match *resolved {
                         TypeInner::BindingArray { .. } => {
                             let uniformity = &func_ctx.info[index].uniformity;
 
@@ -2669,8 +2965,30 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         _ => (false, false),
                     };
 
-                    self.write_expr(module, base, func_ctx)?;
-                    write!(self.out, "[")?;
+                    // Binding arrays in the descriptor heap are functions
+                    // from the index to the element, with a separate function
+                    // for non-uniform indices.
+                    let (open, close) = match func_ctx.expressions[base] {
+                        Expression::GlobalVariable(handle)
+                            if indexing_binding_array
+                                && self
+                                    .is_descriptor_heap_binding_array(module, base, func_ctx) =>
+                        {
+                            if non_uniform_qualifier {
+                                let name = &self.non_uniform_heap_helpers[&handle];
+                                write!(self.out, "{name}")?;
+                                non_uniform_qualifier = false;
+                            } else {
+                                self.write_expr(module, base, func_ctx)?;
+                            }
+                            ("(", ")")
+                        }
+                        _ => {
+                            self.write_expr(module, base, func_ctx)?;
+                            ("[", "]")
+                        }
+                    };
+                    write!(self.out, "{open}")?;
 
                     let needs_bound_check = self.options.restrict_indexing
                         && !indexing_binding_array
@@ -2721,7 +3039,7 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                         }
                     }
 
-                    write!(self.out, "]")?;
+                    write!(self.out, "{close}")?;
                 }
             }
             Expression::AccessIndex { base, index } => {
@@ -2817,6 +3135,12 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     }
 
                     self.write_expr(module, base, func_ctx)?;
+                    if let TypeInner::BindingArray { .. } = *resolved {
+                        if self.is_descriptor_heap_binding_array(module, base, func_ctx) {
+                            write!(self.out, "({index})")?;
+                            return Ok(());
+                        }
+                    }
                     write_access(self, resolved, base_ty_handle, index)?;
                 }
             }
@@ -3111,6 +3435,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     Unpack4xI8,
                     Unpack4xU8,
                     QuantizeToF16,
+                    Dot4I8Packed,
+                    Dot4U8Packed,
                     Regular(&'static str),
                     MissingIntOverload(&'static str),
                     MissingIntReturnType(&'static str),
@@ -3158,6 +3484,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     Mf::Pow => Function::Regular("pow"),
                     // geometry
                     Mf::Dot => Function::Regular("dot"),
+                    Mf::Dot4I8Packed => Function::Dot4I8Packed,
+                    Mf::Dot4U8Packed => Function::Dot4U8Packed,
                     //Mf::Outer => ,
                     Mf::Cross => Function::Regular("cross"),
                     Mf::Distance => Function::Regular("distance"),
@@ -3195,6 +3523,8 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                     Mf::Pack4x8unorm => Function::Pack4x8unorm,
                     Mf::Pack4xI8 => Function::Pack4xI8,
                     Mf::Pack4xU8 => Function::Pack4xU8,
+                    Mf::Pack4xI8Clamp => Function::Regular(PACK_4X_I8_CLAMP_FUNCTION),
+                    Mf::Pack4xU8Clamp => Function::Regular(PACK_4X_U8_CLAMP_FUNCTION),
                     // Data Unpacking
                     Mf::Unpack2x16float => Function::Unpack2x16float,
                     Mf::Unpack2x16snorm => Function::Unpack2x16snorm,
@@ -3313,6 +3643,45 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
                             write!(self.out, ")")?;
                         }
                     }
+                    fun @ (Function::Dot4I8Packed | Function::Dot4U8Packed) => {
+                        let arg1 = arg1.unwrap();
+                        let is_signed = matches!(fun, Function::Dot4I8Packed);
+
+                        if self.options.shader_model >= ShaderModel::V6_4 {
+                            // Intrinsics `dot4add_{i, u}8packed` are available in SM 6.4 and later.
+                            let function_name = if is_signed {
+                                "dot4add_i8packed"
+                            } else {
+                                "dot4add_u8packed"
+                            };
+                            let zero = if is_signed { "0" } else { "0u" };
+                            write!(self.out, "{function_name}(")?;
+                            self.write_expr(module, arg, func_ctx)?;
+                            write!(self.out, ", ")?;
+                            self.write_expr(module, arg1, func_ctx)?;
+                            write!(self.out, ", {zero})")?;
+                        } else {
+                            // Fall back to a polyfill that unpacks both operands.
+                            let vec_type = if is_signed { "int4" } else { "uint4" };
+                            let write_unpacked = |this: &mut Self, operand| -> BackendResult {
+                                write!(this.out, "({vec_type}(")?;
+                                this.write_expr(module, operand, func_ctx)?;
+                                write!(this.out, ", ")?;
+                                this.write_expr(module, operand, func_ctx)?;
+                                write!(this.out, " >> 8, ")?;
+                                this.write_expr(module, operand, func_ctx)?;
+                                write!(this.out, " >> 16, ")?;
+                                this.write_expr(module, operand, func_ctx)?;
+                                write!(this.out, " >> 24) << 24 >> 24)")?;
+                                Ok(())
+                            };
+                            write!(self.out, "dot(")?;
+                            write_unpacked(self, arg)?;
+                            write!(self.out, ", ")?;
+                            write_unpacked(self, arg1)?;
+                            write!(self.out, ")")?;
+                        }
+                    }
 
                     Function::Unpack2x16float => {
                         write!(self.out, "float2(f16tof32(")?;
@@ -3745,6 +4114,10 @@ impl<'a, W: fmt::Write> super::Writer<'a, W> {
         if barrier.contains(crate::Barrier::SUB_GROUP) {
             // Does not exist in DirectX
         }
+        // Textures share the device memory barrier with storage buffers.
+        if barrier.contains(crate::Barrier::TEXTURE) && !barrier.contains(crate::Barrier::STORAGE) {
+            writeln!(self.out, "{level}DeviceMemoryBarrierWithGroupSync();")?;
+        }
         Ok(())
     }
 }
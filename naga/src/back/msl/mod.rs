@@ -65,6 +65,27 @@ pub struct BindTarget {
 // Using `BTreeMap` instead of `HashMap` so that we can hash itself.
 pub type BindingMap = std::collections::BTreeMap<crate::ResourceBinding, BindTarget>;
 
+/// The layout of a bind group passed as a tier 2 argument buffer.
+///
+/// The backend emits one `struct` per such group, with one member per global
+/// variable in the group. The member for `binding` is declared with
+/// `[[id(ids[binding])]]`, so that the host can encode the group's resources
+/// into a buffer with the matching `MTLArgumentDescriptor`s.
+///
+/// A binding array of `N` elements is a `metal::array` member that takes the
+/// `N` ids starting at its own, so the ranges of ids must not overlap.
+#[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+#[cfg_attr(any(feature = "serialize", feature = "deserialize"), serde(default))]
+pub struct ArgumentBufferLayout {
+    /// Map from binding index within the group to argument buffer id.
+    pub ids: std::collections::BTreeMap<u32, u32>,
+}
+
+/// Map of bind group index to the layout of its argument buffer.
+pub type ArgumentBufferMap = std::collections::BTreeMap<u32, ArgumentBufferLayout>;
+
 #[derive(Clone, Debug, Default, Hash, Eq, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(serde::Serialize))]
 #[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
@@ -78,6 +99,10 @@ pub struct EntryPointResources {
     /// one for the size of each bound buffer that contains a runtime array,
     /// in order of [`crate::GlobalVariable`] declarations.
     pub sizes_buffer: Option<Slot>,
+
+    /// The slot of the argument buffer for each bind group listed in
+    /// [`Options::argument_buffers`], indexed by group.
+    pub argument_buffers: std::collections::BTreeMap<u32, Slot>,
 }
 
 pub type EntryPointResourceMap = std::collections::BTreeMap<String, EntryPointResources>;
@@ -148,6 +173,14 @@ pub enum Error {
     UnsupportedRayTracing,
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error("argument buffers are not supported prior to MSL 2.0")]
+    UnsupportedArgumentBuffers,
+    #[error("argument buffer id for {0:?} is missing")]
+    MissingArgumentBufferId(crate::ResourceBinding),
+    #[error("argument buffer ids of {0:?} and {1:?} overlap")]
+    OverlappingArgumentBufferIds(crate::ResourceBinding, crate::ResourceBinding),
+    #[error("binding array {0:?} in an argument buffer must have a fixed size")]
+    UnsizedArgumentBufferArray(crate::ResourceBinding),
 }
 
 #[derive(Clone, Debug, PartialEq, thiserror::Error)]
@@ -162,6 +195,8 @@ pub enum EntryPointError {
     MissingPushConstants,
     #[error("mapping for sizes buffer is missing")]
     MissingSizesBuffer,
+    #[error("mapping for the argument buffer of group {0} is missing")]
+    MissingArgumentBuffer(u32),
 }
 
 /// Points in the MSL code where we might emit a pipeline input or output.
@@ -212,6 +247,14 @@ pub struct Options {
     /// If set, loops will have code injected into them, forcing the compiler
     /// to think the number of iterations is bounded.
     pub force_loop_bounding: bool,
+    /// Bind groups to pass to entry points as tier 2 argument buffers,
+    /// indexed by group. Requires MSL 2.0.
+    ///
+    /// Resources in these groups are not looked up in
+    /// [`per_entry_point_map`](Self::per_entry_point_map); instead each entry
+    /// point takes one argument buffer per group, bound at the slot given by
+    /// [`EntryPointResources::argument_buffers`].
+    pub argument_buffers: ArgumentBufferMap,
 }
 
 impl Default for Options {
@@ -225,6 +268,7 @@ impl Default for Options {
             bounds_check_policies: index::BoundsCheckPolicies::default(),
             zero_initialize_workgroup_memory: true,
             force_loop_bounding: true,
+            argument_buffers: ArgumentBufferMap::default(),
         }
     }
 }
@@ -541,6 +585,42 @@ impl Options {
             None => Err(EntryPointError::MissingSizesBuffer),
         }
     }
+
+    /// Return the group of `var` if it is passed in an argument buffer.
+    fn argument_buffer_group(&self, var: &crate::GlobalVariable) -> Option<u32> {
+        match var.space {
+            crate::AddressSpace::Uniform
+            | crate::AddressSpace::Storage { .. }
+            | crate::AddressSpace::Handle => var
+                .binding
+                .as_ref()
+                .map(|br| br.group)
+                .filter(|group| self.argument_buffers.contains_key(group)),
+            _ => None,
+        }
+    }
+
+    fn resolve_argument_buffer(
+        &self,
+        ep: &crate::EntryPoint,
+        group: u32,
+    ) -> Result<ResolvedBinding, EntryPointError> {
+        let slot = self
+            .get_entry_point_resources(ep)
+            .and_then(|res| res.argument_buffers.get(&group).cloned());
+        match slot {
+            Some(slot) => Ok(ResolvedBinding::Resource(BindTarget {
+                buffer: Some(slot),
+                ..Default::default()
+            })),
+            None if self.fake_missing_bindings => Ok(ResolvedBinding::User {
+                prefix: "fake",
+                index: 0,
+                interpolation: None,
+            }),
+            None => Err(EntryPointError::MissingArgumentBuffer(group)),
+        }
+    }
 }
 
 impl ResolvedBinding {
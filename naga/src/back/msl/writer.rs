@@ -44,6 +44,11 @@ pub(crate) const FREXP_FUNCTION: &str = "naga_frexp";
 /// This allows `NagaArgumentBufferWrapper<metal::texture<..>>*` to work. The astute among
 /// you have noticed that this should be exactly the same to the compiler, and you're correct.
 pub(crate) const ARGUMENT_BUFFER_WRAPPER_STRUCT: &str = "NagaArgumentBufferWrapper";
+/// Prefix of the struct generated for each bind group passed as an argument
+/// buffer. See [`Options::argument_buffers`].
+const ARGUMENT_BUFFER_STRUCT: &str = "_mslArgumentBuffer";
+/// Prefix of the entry point argument holding a bind group's argument buffer.
+const ARGUMENT_BUFFER_ARGUMENT: &str = "_argument_buffer";
 
 /// Write the Metal name for a Naga numeric type: scalar, vector, or matrix.
 ///
@@ -183,7 +188,15 @@ impl Display for TypeContext<'_> {
                     Some(name) => name,
                     None => return Ok(()),
                 };
-                write!(out, "{space_name} {sub}&")
+                let access = match space {
+                    crate::AddressSpace::Storage { access }
+                        if !access.contains(crate::StorageAccess::STORE) =>
+                    {
+                        " const"
+                    }
+                    _ => "",
+                };
+                write!(out, "{space_name} {sub}{access}&")
             }
             crate::TypeInner::ValuePointer {
                 size,
@@ -306,6 +319,9 @@ struct TypedGlobalVariable<'a> {
     usage: valid::GlobalUse,
     binding: Option<&'a super::ResolvedBinding>,
     reference: bool,
+    /// Whether the variable is a binding array in an argument buffer, which
+    /// is a `metal::array` of its elements rather than a pointer to them.
+    argument_buffer_array: bool,
 }
 
 impl TypedGlobalVariable<'_> {
@@ -313,6 +329,33 @@ impl TypedGlobalVariable<'_> {
         let var = &self.module.global_variables[self.handle];
         let name = &self.names[&NameKey::GlobalVariable(self.handle)];
 
+        if self.argument_buffer_array {
+            let crate::TypeInner::BindingArray {
+                base,
+                size: crate::ArraySize::Constant(size),
+            } = self.module.types[var.ty].inner
+            else {
+                unreachable!("argument buffer arrays are sized binding arrays");
+            };
+            let base_name = TypeContext {
+                handle: base,
+                gctx: self.module.to_ctx(),
+                names: self.names,
+                access: crate::StorageAccess::empty(),
+                binding: None,
+                first_time: false,
+            };
+            let (space, reference) = if self.reference {
+                ("constant ", "&")
+            } else {
+                ("", "")
+            };
+            return Ok(write!(
+                out,
+                "{space}{NAMESPACE}::array<{base_name}, {size}>{reference} {name}"
+            )?);
+        }
+
         let storage_access = match var.space {
             crate::AddressSpace::Storage { access } => access,
             _ => match self.module.types[var.ty].inner {
@@ -388,12 +431,22 @@ pub struct Writer<W> {
     ///
     /// See `emit_force_bounded_loop_macro` for details.
     force_bounded_loop_macro_name: String,
+
+    /// Binding arrays passed in argument buffers.
+    ///
+    /// These are `metal::array`s of their elements, so indexing them doesn't
+    /// go through [`WRAPPED_ARRAY_FIELD`].
+    argument_buffer_arrays: FastHashSet<Handle<crate::GlobalVariable>>,
 }
 
 impl crate::Scalar {
     fn to_msl_name(self) -> &'static str {
         use crate::ScalarKind as Sk;
         match self {
+            Self {
+                kind: Sk::Float,
+                width: 2,
+            } => "half",
             Self {
                 kind: Sk::Float,
                 width: _,
@@ -686,6 +739,7 @@ impl<W: Write> Writer<W> {
             put_block_stack_pointers: Default::default(),
             struct_member_pads: FastHashSet::default(),
             force_bounded_loop_macro_name: String::default(),
+            argument_buffer_arrays: FastHashSet::default(),
         }
     }
 
@@ -1430,6 +1484,14 @@ impl<W: Write> Writer<W> {
                         write!(self.out, "{value}{suffix}")?;
                     }
                 }
+                crate::Literal::F16(value) => {
+                    let suffix = if value.to_f32().fract() == 0.0 {
+                        ".0"
+                    } else {
+                        ""
+                    };
+                    write!(self.out, "{value}{suffix}h")?;
+                }
                 crate::Literal::U32(value) => {
                     write!(self.out, "{value}u")?;
                 }
@@ -1940,6 +2002,36 @@ impl<W: Write> Writer<W> {
                             "Correct TypeInner for dot product should be already validated"
                         ),
                     },
+                    fun @ (Mf::Dot4I8Packed | Mf::Dot4U8Packed) => {
+                        let (packed_type, conversion) = match fun {
+                            Mf::Dot4I8Packed => ("packed_char4", "int"),
+                            Mf::Dot4U8Packed => ("packed_uchar4", "uint"),
+                            _ => unreachable!(),
+                        };
+                        let arg1 = arg1.unwrap();
+
+                        // Reinterpret the operands as vectors of 8-bit integers,
+                        // then sum the products of their widened components.
+                        write!(self.out, "(")?;
+                        for i in 0..4 {
+                            if i != 0 {
+                                write!(self.out, " + ")?;
+                            }
+                            write!(
+                                self.out,
+                                "{conversion}(as_type<{NAMESPACE}::{packed_type}>("
+                            )?;
+                            self.put_expression(arg, context, true)?;
+                            write!(
+                                self.out,
+                                ")[{i}]) * {conversion}(as_type<{NAMESPACE}::{packed_type}>("
+                            )?;
+                            self.put_expression(arg1, context, true)?;
+                            write!(self.out, ")[{i}])")?;
+                        }
+                        write!(self.out, ")")?;
+                        return Ok(());
+                    }
                     Mf::Outer => return Err(Error::UnsupportedCall(format!("{fun:?}"))),
                     Mf::Cross => "cross",
                     Mf::Distance => "distance",
@@ -1983,6 +2075,8 @@ impl<W: Write> Writer<W> {
                     Mf::Pack2x16float => "",
                     Mf::Pack4xI8 => "",
                     Mf::Pack4xU8 => "",
+                    Mf::Pack4xI8Clamp => "",
+                    Mf::Pack4xU8Clamp => "",
                     // data unpacking
                     Mf::Unpack4x8snorm => "unpack_snorm4x8_to_float",
                     Mf::Unpack4x8unorm => "unpack_unorm4x8_to_float",
@@ -2160,6 +2254,25 @@ impl<W: Write> Writer<W> {
                             write!(self.out, ")")?;
                         }
                     }
+                    fun @ (Mf::Pack4xI8Clamp | Mf::Pack4xU8Clamp) => {
+                        let (narrow_type, vec_type, min, max) = match fun {
+                            Mf::Pack4xI8Clamp => ("char4", "int4", -128, 127),
+                            Mf::Pack4xU8Clamp => ("uchar4", "uint4", 0, 255),
+                            _ => unreachable!(),
+                        };
+
+                        // Clamp the argument once, then reinterpret its
+                        // components narrowed to 8 bits as a `uint`.
+                        write!(
+                            self.out,
+                            "as_type<uint>({NAMESPACE}::{narrow_type}({NAMESPACE}::clamp("
+                        )?;
+                        self.put_expression(arg, context, true)?;
+                        write!(
+                            self.out,
+                            ", {NAMESPACE}::{vec_type}({min}), {NAMESPACE}::{vec_type}({max}))))"
+                        )?;
+                    }
                     fun @ (Mf::Unpack4xI8 | Mf::Unpack4xU8) => {
                         write!(self.out, "(")?;
                         if matches!(fun, Mf::Unpack4xU8) {
@@ -2580,7 +2693,11 @@ impl<W: Write> Writer<W> {
             _ => false,
         };
         let accessing_wrapped_binding_array =
-            matches!(*base_ty, crate::TypeInner::BindingArray { .. });
+            matches!(*base_ty, crate::TypeInner::BindingArray { .. })
+                && !context
+                    .function
+                    .originating_global(base)
+                    .is_some_and(|global| self.argument_buffer_arrays.contains(&global));
 
         self.put_access_chain(base, policy, context)?;
         if accessing_wrapped_array {
@@ -2825,6 +2942,10 @@ impl<W: Write> Writer<W> {
                             }
                         }
                     }
+                    crate::MathFunction::Dot4I8Packed | crate::MathFunction::Dot4U8Packed => {
+                        self.need_bake_expressions.insert(arg);
+                        self.need_bake_expressions.insert(arg1.unwrap());
+                    }
                     crate::MathFunction::FirstLeadingBit
                     | crate::MathFunction::Pack4xI8
                     | crate::MathFunction::Pack4xU8
@@ -3137,7 +3258,15 @@ impl<W: Write> Writer<W> {
                 crate::Statement::Kill => {
                     writeln!(self.out, "{level}{NAMESPACE}::discard_fragment();")?;
                 }
-                crate::Statement::Barrier(flags) => {
+                crate::Statement::Barrier(mut flags) => {
+                    // `mem_flags::mem_texture` requires MSL 1.2; fold it into a
+                    // device memory barrier on older versions.
+                    if flags.contains(crate::Barrier::TEXTURE)
+                        && context.expression.lang_version < (1, 2)
+                    {
+                        flags.remove(crate::Barrier::TEXTURE);
+                        flags.insert(crate::Barrier::STORAGE);
+                    }
                     self.write_barrier(flags, level)?;
                 }
                 crate::Statement::Store { pointer, value } => {
@@ -3502,6 +3631,12 @@ impl<W: Write> Writer<W> {
                         crate::GatherMode::ShuffleXor(_) => {
                             write!(self.out, "{NAMESPACE}::simd_shuffle_xor(")?;
                         }
+                        crate::GatherMode::QuadBroadcast(_) => {
+                            write!(self.out, "{NAMESPACE}::quad_broadcast(")?;
+                        }
+                        crate::GatherMode::QuadSwap(_) => {
+                            write!(self.out, "{NAMESPACE}::quad_shuffle_xor(")?;
+                        }
                     }
                     self.put_expression(argument, &context.expression, true)?;
                     match mode {
@@ -3510,10 +3645,21 @@ impl<W: Write> Writer<W> {
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             write!(self.out, ", ")?;
                             self.put_expression(index, &context.expression, true)?;
                         }
+                        crate::GatherMode::QuadSwap(direction) => {
+                            // The lanes of a quad are numbered row by row, so
+                            // the neighbors differ in the low, high, or both bits.
+                            let mask = match direction {
+                                crate::Direction::X => 1,
+                                crate::Direction::Y => 2,
+                                crate::Direction::Diagonal => 3,
+                            };
+                            write!(self.out, ", {mask}u")?;
+                        }
                     }
                     writeln!(self.out, ");")?;
                 }
@@ -3608,6 +3754,7 @@ impl<W: Write> Writer<W> {
         );
         self.force_bounded_loop_macro_name.clear();
         self.struct_member_pads.clear();
+        self.argument_buffer_arrays.clear();
 
         writeln!(
             self.out,
@@ -3694,7 +3841,8 @@ impl<W: Write> Writer<W> {
             }
         };
 
-        self.write_type_defs(module)?;
+        self.write_type_defs(module, options)?;
+        self.write_argument_buffers(module, options)?;
         self.write_global_constants(module, info)?;
         self.write_functions(module, info, options, pipeline_options)
     }
@@ -3748,11 +3896,127 @@ impl<W: Write> Writer<W> {
         Ok(())
     }
 
-    fn write_type_defs(&mut self, module: &crate::Module) -> BackendResult {
+    /// Write a struct for each bind group passed as an argument buffer.
+    ///
+    /// Buffers become pointers to their contents, and textures, samplers and
+    /// binding arrays of them are stored by value. Each member is tagged with
+    /// the `[[id]]` given by the group's [`ArgumentBufferLayout`].
+    ///
+    /// [`ArgumentBufferLayout`]: super::ArgumentBufferLayout
+    fn write_argument_buffers(
+        &mut self,
+        module: &crate::Module,
+        options: &Options,
+    ) -> BackendResult {
+        for (&group, layout) in options.argument_buffers.iter() {
+            let mut globals = module
+                .global_variables
+                .iter()
+                .filter(|&(_, var)| options.argument_buffer_group(var) == Some(group))
+                .peekable();
+            if globals.peek().is_none() {
+                continue;
+            }
+            if options.lang_version < (2, 0) {
+                return Err(Error::UnsupportedArgumentBuffers);
+            }
+
+            // The range of ids each member takes, to check they don't overlap.
+            let mut id_ranges = Vec::new();
+            writeln!(self.out, "struct {ARGUMENT_BUFFER_STRUCT}{group} {{")?;
+            for (handle, var) in globals {
+                // `argument_buffer_group` only accepts globals with bindings.
+                let br = var.binding.as_ref().unwrap();
+                let mut id_count = 1;
+                if let crate::TypeInner::BindingArray { size, .. } = module.types[var.ty].inner {
+                    if var.space != crate::AddressSpace::Handle {
+                        return Err(Error::UnsupportedArrayOfType(var.ty));
+                    }
+                    match size {
+                        crate::ArraySize::Constant(size) => id_count = size.get(),
+                        _ => return Err(Error::UnsizedArgumentBufferArray(br.clone())),
+                    }
+                    self.argument_buffer_arrays.insert(handle);
+                }
+
+                write!(self.out, "{}", back::INDENT)?;
+                match var.space.to_msl_name() {
+                    Some(space) => {
+                        let ty_name = TypeContext {
+                            handle: var.ty,
+                            gctx: module.to_ctx(),
+                            names: &self.names,
+                            access: crate::StorageAccess::empty(),
+                            binding: None,
+                            first_time: false,
+                        };
+                        let access = match var.space {
+                            crate::AddressSpace::Storage { access }
+                                if !access.contains(crate::StorageAccess::STORE) =>
+                            {
+                                " const"
+                            }
+                            _ => "",
+                        };
+                        let name = &self.names[&NameKey::GlobalVariable(handle)];
+                        write!(self.out, "{space} {ty_name}{access}* {name}")?;
+                    }
+                    None => {
+                        let tyvar = TypedGlobalVariable {
+                            module,
+                            names: &self.names,
+                            handle,
+                            usage: valid::GlobalUse::empty(),
+                            binding: None,
+                            reference: false,
+                            argument_buffer_array: self.argument_buffer_arrays.contains(&handle),
+                        };
+                        tyvar.try_fmt(&mut self.out)?;
+                    }
+                }
+
+                match layout.ids.get(&br.binding) {
+                    Some(&id) => {
+                        write!(self.out, " [[id({id})]]")?;
+                        id_ranges.push((id..id + id_count, br));
+                    }
+                    None if options.fake_missing_bindings => {}
+                    None => return Err(Error::MissingArgumentBufferId(br.clone())),
+                }
+                writeln!(self.out, ";")?;
+            }
+            writeln!(self.out, "}};")?;
+
+            id_ranges.sort_by_key(|&(ref ids, _)| ids.start);
+            for pair in id_ranges.windows(2) {
+                let (ref first, first_br) = pair[0];
+                let (ref second, second_br) = pair[1];
+                if first.end > second.start {
+                    return Err(Error::OverlappingArgumentBufferIds(
+                        first_br.clone(),
+                        second_br.clone(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn write_type_defs(&mut self, module: &crate::Module, options: &Options) -> BackendResult {
         let mut generated_argument_buffer_wrapper = false;
         for (handle, ty) in module.types.iter() {
             if let crate::TypeInner::BindingArray { .. } = ty.inner {
-                if !generated_argument_buffer_wrapper {
+                // Binding arrays in argument buffers are `metal::array`s,
+                // they don't need the wrapper.
+                let mut vars = module
+                    .global_variables
+                    .iter()
+                    .filter(|&(_, var)| var.ty == handle)
+                    .peekable();
+                let in_argument_buffers = vars.peek().is_some()
+                    && vars.all(|(_, var)| options.argument_buffer_group(var).is_some());
+
+                if !generated_argument_buffer_wrapper && !in_argument_buffers {
                     writeln!(self.out, "template <typename T>")?;
                     writeln!(self.out, "struct {ARGUMENT_BUFFER_WRAPPER_STRUCT} {{")?;
                     writeln!(self.out, "{}T {WRAPPED_ARRAY_FIELD};", back::INDENT)?;
@@ -3893,16 +4157,11 @@ impl<W: Write> Writer<W> {
                 | &crate::PredeclaredType::FrexpResult { size, scalar } => {
                     let arg_type_name_owner;
                     let arg_type_name = if let Some(size) = size {
-                        arg_type_name_owner = format!(
-                            "{NAMESPACE}::{}{}",
-                            if scalar.width == 8 { "double" } else { "float" },
-                            size as u8
-                        );
+                        arg_type_name_owner =
+                            format!("{NAMESPACE}::{}{}", scalar.to_msl_name(), size as u8);
                         &arg_type_name_owner
-                    } else if scalar.width == 8 {
-                        "double"
                     } else {
-                        "float"
+                        scalar.to_msl_name()
                     };
 
                     let other_type_name_owner;
@@ -5048,6 +5307,7 @@ template <typename A>
                     usage: fun_info[handle],
                     binding: None,
                     reference: true,
+                    argument_buffer_array: self.argument_buffer_arrays.contains(&handle),
                 };
                 let separator =
                     separate(index + 1 != pass_through_globals.len() || needs_buffer_sizes);
@@ -5175,6 +5435,13 @@ template <typename A>
                     if fun_info[var_handle].is_empty() {
                         continue;
                     }
+                    if let Some(group) = options.argument_buffer_group(var) {
+                        if let Err(e) = options.resolve_argument_buffer(ep, group) {
+                            ep_error = Some(e);
+                            break;
+                        }
+                        continue;
+                    }
                     match var.space {
                         crate::AddressSpace::Uniform
                         | crate::AddressSpace::Storage { .. }
@@ -5636,6 +5903,11 @@ template <typename A>
                     _ => {}
                 }
 
+                // Globals in argument buffers are passed below, one argument per group.
+                if options.argument_buffer_group(var).is_some() {
+                    continue;
+                }
+
                 // the resolves have already been checked for `!fake_missing_bindings` case
                 let resolved = match var.space {
                     crate::AddressSpace::PushConstant => options.resolve_push_constants(ep).ok(),
@@ -5658,6 +5930,7 @@ template <typename A>
                     usage,
                     binding: resolved.as_ref(),
                     reference: true,
+                    argument_buffer_array: self.argument_buffer_arrays.contains(&handle),
                 };
                 let separator = if is_first_argument {
                     is_first_argument = false;
@@ -5677,6 +5950,29 @@ template <typename A>
                 writeln!(self.out)?;
             }
 
+            let argument_buffer_groups: std::collections::BTreeSet<u32> = module
+                .global_variables
+                .iter()
+                .filter(|&(handle, _)| !fun_info[handle].is_empty())
+                .filter_map(|(_, var)| options.argument_buffer_group(var))
+                .collect();
+            for group in argument_buffer_groups {
+                // this is checked earlier
+                let resolved = options.resolve_argument_buffer(ep, group).unwrap();
+                let separator = if is_first_argument {
+                    is_first_argument = false;
+                    ' '
+                } else {
+                    ','
+                };
+                write!(
+                    self.out,
+                    "{separator} constant {ARGUMENT_BUFFER_STRUCT}{group}& {ARGUMENT_BUFFER_ARGUMENT}{group}",
+                )?;
+                resolved.try_fmt(&mut self.out)?;
+                writeln!(self.out)?;
+            }
+
             if do_vertex_pulling {
                 assert!(needs_vertex_id || needs_instance_id);
 
@@ -5871,6 +6167,7 @@ template <typename A>
                         usage,
                         binding: None,
                         reference: false,
+                        argument_buffer_array: self.argument_buffer_arrays.contains(&handle),
                     };
                     write!(self.out, "{}", back::INDENT)?;
                     tyvar.try_fmt(&mut self.out)?;
@@ -5884,6 +6181,29 @@ template <typename A>
                             writeln!(self.out, " = {{}};")?;
                         }
                     };
+                } else if let Some(group) = options.argument_buffer_group(var) {
+                    // give the argument buffer member the global's name, so that
+                    // the rest of the body can refer to it as usual
+                    let tyvar = TypedGlobalVariable {
+                        module,
+                        names: &self.names,
+                        handle,
+                        usage,
+                        binding: None,
+                        reference: true,
+                        argument_buffer_array: self.argument_buffer_arrays.contains(&handle),
+                    };
+                    write!(self.out, "{}", back::INDENT)?;
+                    tyvar.try_fmt(&mut self.out)?;
+                    let deref = match var.space {
+                        crate::AddressSpace::Handle => "",
+                        _ => "*",
+                    };
+                    let name = &self.names[&NameKey::GlobalVariable(handle)];
+                    writeln!(
+                        self.out,
+                        " = {deref}{ARGUMENT_BUFFER_ARGUMENT}{group}.{name};"
+                    )?;
                 } else if let Some(ref binding) = var.binding {
                     // write an inline sampler
                     let resolved = options.resolve_resource_binding(ep, binding).unwrap();
@@ -6047,6 +6367,12 @@ template <typename A>
                 "{level}{NAMESPACE}::simdgroup_barrier({NAMESPACE}::mem_flags::mem_threadgroup);",
             )?;
         }
+        if flags.contains(crate::Barrier::TEXTURE) {
+            writeln!(
+                self.out,
+                "{level}{NAMESPACE}::threadgroup_barrier({NAMESPACE}::mem_flags::mem_texture);",
+            )?;
+        }
         Ok(())
     }
 }
@@ -427,7 +427,10 @@ fn process_function(
 
 /// Replace every expression handle in `expr` with its counterpart
 /// given by `new_pos`.
-fn adjust_expr(new_pos: &HandleVec<Expression, Handle<Expression>>, expr: &mut Expression) {
+pub(super) fn adjust_expr(
+    new_pos: &HandleVec<Expression, Handle<Expression>>,
+    expr: &mut Expression,
+) {
     let adjust = |expr: &mut Handle<Expression>| {
         *expr = new_pos[*expr];
     };
@@ -632,7 +635,7 @@ fn adjust_expr(new_pos: &HandleVec<Expression, Handle<Expression>>, expr: &mut E
 
 /// Replace every expression handle in `block` with its counterpart
 /// given by `new_pos`.
-fn adjust_block(new_pos: &HandleVec<Expression, Handle<Expression>>, block: &mut Block) {
+pub(super) fn adjust_block(new_pos: &HandleVec<Expression, Handle<Expression>>, block: &mut Block) {
     for stmt in block.iter_mut() {
         adjust_stmt(new_pos, stmt);
     }
@@ -780,12 +783,13 @@ fn adjust_stmt(new_pos: &HandleVec<Expression, Handle<Expression>>, stmt: &mut S
             ref mut result,
         } => {
             match *mode {
-                crate::GatherMode::BroadcastFirst => {}
+                crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                 crate::GatherMode::Broadcast(ref mut index)
                 | crate::GatherMode::Shuffle(ref mut index)
                 | crate::GatherMode::ShuffleDown(ref mut index)
                 | crate::GatherMode::ShuffleUp(ref mut index)
-                | crate::GatherMode::ShuffleXor(ref mut index) => {
+                | crate::GatherMode::ShuffleXor(ref mut index)
+                | crate::GatherMode::QuadBroadcast(ref mut index) => {
                     adjust(index);
                 }
             }
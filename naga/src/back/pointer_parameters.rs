@@ -0,0 +1,515 @@
+/*!
+Specialization of functions taking pointers to uniform, storage, or workgroup memory.
+
+WGSL's `unrestricted_pointer_parameters` language extension permits
+functions to take pointers in the [`Uniform`], [`Storage`], and
+[`WorkGroup`] address spaces as arguments. Some shading languages have no
+way to pass such a pointer to a function: HLSL and GLSL can only refer to
+buffers and workgroup variables by name, and SPIR-V's logical addressing
+model restricts which pointers may be passed to `OpFunctionCall`.
+
+The [`specialize`] function removes such parameters from a module by
+making a copy of each affected function for every distinct way it is
+called. Since every pointer in these address spaces must be derived from a
+global variable, each pointer argument can be described as a global
+variable followed by a series of [`Access`] and [`AccessIndex`] steps. The
+specialized copy rebuilds that access chain itself, starting from the
+global variable, with any dynamic indices passed in as ordinary scalar
+arguments.
+
+[`Uniform`]: AddressSpace::Uniform
+[`Storage`]: AddressSpace::Storage
+[`WorkGroup`]: AddressSpace::WorkGroup
+[`Access`]: Expression::Access
+[`AccessIndex`]: Expression::AccessIndex
+*/
+
+use super::pipeline_constants::{adjust_block, adjust_expr};
+use crate::{
+    arena::HandleVec,
+    valid::{Capabilities, FunctionInfo, ModuleInfo, ValidationError, ValidationFlags, Validator},
+    AddressSpace, Arena, Block, Expression, FastHashMap, Function, FunctionArgument,
+    GlobalVariable, Handle, Module, Scalar, Span, Statement, Type, TypeInner, WithSpan,
+};
+use std::{borrow::Cow, mem};
+
+/// Return true if [`specialize`] would change `module`.
+///
+/// Backends that can't pass these pointers to functions check this, and
+/// reject modules that haven't been specialized.
+pub fn needs_specialization(module: &Module) -> bool {
+    module
+        .functions
+        .iter()
+        .any(|(_, function)| function_needs_specialization(module, function))
+}
+
+/// Return true if `function` has a parameter that [`specialize`] would remove.
+fn function_needs_specialization(module: &Module, function: &Function) -> bool {
+    function
+        .arguments
+        .iter()
+        .any(|argument| is_specialized_parameter(&module.types[argument.ty].inner))
+}
+
+/// Replace all functions taking uniform, storage, or workgroup pointers
+/// with specialized copies.
+///
+/// If no function in `module` takes such a pointer, this just returns
+/// `Cow::Borrowed` references to `module` and `module_info`. Otherwise, it
+/// clones `module`, replaces each call to such a function with a call to a
+/// copy specialized for the call's pointer arguments, drops the original
+/// functions, and returns `Cow::Owned` values holding the new module and its
+/// validation results.
+///
+/// The functions in the module returned are ordered so that each function
+/// appears after all the functions it calls.
+pub fn specialize<'a>(
+    module: &'a Module,
+    module_info: &'a ModuleInfo,
+) -> Result<(Cow<'a, Module>, Cow<'a, ModuleInfo>), WithSpan<ValidationError>> {
+    if !needs_specialization(module) {
+        return Ok((Cow::Borrowed(module), Cow::Borrowed(module_info)));
+    }
+
+    let mut new_module = module.clone();
+    new_module.functions = Arena::new();
+    let mut entry_points = mem::take(&mut new_module.entry_points);
+
+    let mut specializer = Specializer {
+        original: module,
+        info: module_info,
+        module: &mut new_module,
+        function_map: HandleVec::with_capacity(module.functions.len()),
+        specialized: FastHashMap::default(),
+    };
+
+    // Original functions only call functions that precede them, so
+    // visiting them in order ensures that every function we call has
+    // already been assigned a new handle, or is one we specialize on
+    // demand.
+    for (handle, function) in module.functions.iter() {
+        if function_needs_specialization(module, function) {
+            specializer.function_map.insert(handle, None);
+            continue;
+        }
+
+        let mut function = function.clone();
+        let scalars = expression_scalars(module, &function, &module_info[handle]);
+        specializer.process_block(&mut function.body, &mut function.expressions, &scalars);
+        let span = module.functions.get_span(handle);
+        let new_handle = specializer.module.functions.append(function, span);
+        specializer.function_map.insert(handle, Some(new_handle));
+    }
+
+    for (index, ep) in entry_points.iter_mut().enumerate() {
+        let scalars = expression_scalars(module, &ep.function, module_info.get_entry_point(index));
+        specializer.process_block(
+            &mut ep.function.body,
+            &mut ep.function.expressions,
+            &scalars,
+        );
+    }
+    new_module.entry_points = entry_points;
+
+    // We've added functions and types, and removed all the pointer
+    // parameters from the module. For the time being, do a full
+    // re-validation.
+    let mut validator = Validator::new(ValidationFlags::all(), Capabilities::all());
+    let module_info = validator.validate(&new_module)?;
+
+    Ok((Cow::Owned(new_module), Cow::Owned(module_info)))
+}
+
+/// One step in an access chain leading from a global variable to a pointer argument.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum AccessStep {
+    /// An [`AccessIndex`] step, with the given index.
+    ///
+    /// [`AccessIndex`]: Expression::AccessIndex
+    Constant(u32),
+
+    /// An [`Access`] step, whose index is a scalar of the given type.
+    ///
+    /// [`Access`]: Expression::Access
+    Dynamic(Scalar),
+}
+
+/// The path from a global variable to a pointer argument.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct PointerPath {
+    global: Handle<GlobalVariable>,
+    steps: Vec<AccessStep>,
+}
+
+/// A function to be specialized, and the pointer paths it is specialized for.
+///
+/// The vector has one element per argument of the original function: `None`
+/// for arguments passed as-is, and `Some` for pointer arguments.
+type SpecializationKey = (Handle<Function>, Vec<Option<PointerPath>>);
+
+/// What an argument of a function being specialized becomes.
+#[derive(Clone, Copy)]
+enum Replacement {
+    /// The argument is passed as-is, at the given index.
+    Argument(u32),
+
+    /// The argument is a pointer, computed by the given expression.
+    Pointer(Handle<Expression>),
+}
+
+struct Specializer<'a> {
+    /// The module we are specializing.
+    original: &'a Module,
+
+    /// Validation results for `original`.
+    info: &'a ModuleInfo,
+
+    /// The module we are building.
+    module: &'a mut Module,
+
+    /// A map from `original`'s function handles to their counterparts in
+    /// `module`, or `None` if the function needs to be specialized.
+    function_map: HandleVec<Function, Option<Handle<Function>>>,
+
+    /// Specialized functions we have already built.
+    specialized: FastHashMap<SpecializationKey, Handle<Function>>,
+}
+
+impl Specializer<'_> {
+    /// Redirect all calls in `block` to functions in the new module,
+    /// specializing callees as needed.
+    ///
+    /// The `scalars` slice gives the scalar type of each expression in
+    /// `expressions` that has one, indexed by handle.
+    fn process_block(
+        &mut self,
+        block: &mut Block,
+        expressions: &mut Arena<Expression>,
+        scalars: &[Option<Scalar>],
+    ) {
+        for statement in block.iter_mut() {
+            match *statement {
+                Statement::Block(ref mut block) => {
+                    self.process_block(block, expressions, scalars);
+                }
+                Statement::If {
+                    ref mut accept,
+                    ref mut reject,
+                    ..
+                } => {
+                    self.process_block(accept, expressions, scalars);
+                    self.process_block(reject, expressions, scalars);
+                }
+                Statement::Switch { ref mut cases, .. } => {
+                    for case in cases.iter_mut() {
+                        self.process_block(&mut case.body, expressions, scalars);
+                    }
+                }
+                Statement::Loop {
+                    ref mut body,
+                    ref mut continuing,
+                    ..
+                } => {
+                    self.process_block(body, expressions, scalars);
+                    self.process_block(continuing, expressions, scalars);
+                }
+                Statement::Call {
+                    ref mut function,
+                    ref mut arguments,
+                    result,
+                } => {
+                    *function = match self.function_map[*function] {
+                        Some(new_function) => new_function,
+                        None => self.specialize_call(*function, arguments, expressions, scalars),
+                    };
+                    if let Some(result) = result {
+                        *expressions.get_mut(result) = Expression::CallResult(*function);
+                    }
+                }
+                Statement::Emit(_)
+                | Statement::Break
+                | Statement::Continue
+                | Statement::Return { .. }
+                | Statement::Kill
+                | Statement::Barrier(_)
+                | Statement::Store { .. }
+                | Statement::ImageStore { .. }
+                | Statement::Atomic { .. }
+                | Statement::ImageAtomic { .. }
+                | Statement::WorkGroupUniformLoad { .. }
+                | Statement::RayQuery { .. }
+                | Statement::SubgroupBallot { .. }
+                | Statement::SubgroupGather { .. }
+                | Statement::SubgroupCollectiveOperation { .. } => {}
+            }
+        }
+    }
+
+    /// Return the specialization of `function` for the pointers in `arguments`.
+    ///
+    /// Replace each pointer argument in `arguments` with the dynamic indices
+    /// used to compute it.
+    fn specialize_call(
+        &mut self,
+        function: Handle<Function>,
+        arguments: &mut Vec<Handle<Expression>>,
+        expressions: &Arena<Expression>,
+        scalars: &[Option<Scalar>],
+    ) -> Handle<Function> {
+        let callee = &self.original.functions[function];
+        let mut paths = Vec::with_capacity(arguments.len());
+        let mut new_arguments = Vec::with_capacity(arguments.len());
+        for (&argument, parameter) in arguments.iter().zip(callee.arguments.iter()) {
+            if !is_specialized_parameter(&self.original.types[parameter.ty].inner) {
+                paths.push(None);
+                new_arguments.push(argument);
+                continue;
+            }
+
+            let (path, indices) = pointer_path(argument, expressions, scalars);
+            paths.push(Some(path));
+            new_arguments.extend(indices);
+        }
+        *arguments = new_arguments;
+
+        let key = (function, paths);
+        if let Some(&handle) = self.specialized.get(&key) {
+            return handle;
+        }
+
+        let (mut specialized, scalars) = self.build_specialized(key.0, &key.1);
+        self.process_block(
+            &mut specialized.body,
+            &mut specialized.expressions,
+            &scalars,
+        );
+        let span = self.original.functions.get_span(function);
+        let handle = self.module.functions.append(specialized, span);
+        self.specialized.insert(key, handle);
+        handle
+    }
+
+    /// Build a copy of `function` specialized for `paths`.
+    ///
+    /// Return the new function, and the scalar types of its expressions, as
+    /// expected by [`process_block`]. The calls in the new function's body
+    /// still refer to `original`'s functions.
+    ///
+    /// [`process_block`]: Specializer::process_block
+    fn build_specialized(
+        &mut self,
+        function: Handle<Function>,
+        paths: &[Option<PointerPath>],
+    ) -> (Function, Vec<Option<Scalar>>) {
+        let original = &self.original.functions[function];
+        let info = &self.info[function];
+
+        let mut arguments = Vec::with_capacity(original.arguments.len());
+        let mut expressions = Arena::new();
+        let mut scalars = Vec::new();
+
+        // What each of the original function's arguments becomes.
+        let mut replacements = Vec::with_capacity(original.arguments.len());
+
+        // For each pointer argument: its index, its path, and the
+        // expressions for its global variable and dynamic indices.
+        let mut roots = Vec::new();
+        for (arg_index, (argument, path)) in original.arguments.iter().zip(paths).enumerate() {
+            let Some(path) = path.as_ref() else {
+                replacements.push(Replacement::Argument(arguments.len() as u32));
+                arguments.push(argument.clone());
+                continue;
+            };
+
+            let global =
+                expressions.append(Expression::GlobalVariable(path.global), Span::UNDEFINED);
+            scalars.push(None);
+            let mut indices = Vec::new();
+            for step in path.steps.iter() {
+                if let AccessStep::Dynamic(scalar) = *step {
+                    let ty = self.module.types.insert(
+                        Type {
+                            name: None,
+                            inner: TypeInner::Scalar(scalar),
+                        },
+                        Span::UNDEFINED,
+                    );
+                    let index = expressions.append(
+                        Expression::FunctionArgument(arguments.len() as u32),
+                        Span::UNDEFINED,
+                    );
+                    scalars.push(Some(scalar));
+                    arguments.push(FunctionArgument {
+                        name: argument
+                            .name
+                            .as_ref()
+                            .map(|name| format!("{name}_index{}", indices.len())),
+                        ty,
+                        binding: None,
+                    });
+                    indices.push(index);
+                }
+            }
+            roots.push((arg_index, path, global, indices));
+            replacements.push(Replacement::Pointer(global));
+        }
+
+        // Rebuild each pointer argument's access chain. Keep the new
+        // expressions contiguous, so that a single `Emit` covers them.
+        let emit_start = expressions.len();
+        for (arg_index, path, mut base, indices) in roots {
+            let mut indices = indices.into_iter();
+            for step in path.steps.iter() {
+                let expr = match *step {
+                    AccessStep::Constant(index) => Expression::AccessIndex { base, index },
+                    AccessStep::Dynamic(_) => Expression::Access {
+                        base,
+                        index: indices.next().unwrap(),
+                    },
+                };
+                base = expressions.append(expr, Span::UNDEFINED);
+                scalars.push(None);
+            }
+            replacements[arg_index] = Replacement::Pointer(base);
+        }
+        let mut body = Block::new();
+        if expressions.len() > emit_start {
+            body.push(
+                Statement::Emit(expressions.range_from(emit_start)),
+                Span::UNDEFINED,
+            );
+        }
+
+        // Copy over the original function's expressions.
+        let mut new_pos = HandleVec::with_capacity(original.expressions.len());
+        for (old_h, expr) in original.expressions.iter() {
+            let new_h = match *expr {
+                Expression::FunctionArgument(index) => match replacements[index as usize] {
+                    Replacement::Pointer(pointer) => pointer,
+                    Replacement::Argument(new_index) => {
+                        scalars.push(scalar_of(self.original, &info[old_h].ty));
+                        expressions.append(
+                            Expression::FunctionArgument(new_index),
+                            original.expressions.get_span(old_h),
+                        )
+                    }
+                },
+                _ => {
+                    let mut expr = expr.clone();
+                    adjust_expr(&new_pos, &mut expr);
+                    scalars.push(scalar_of(self.original, &info[old_h].ty));
+                    expressions.append(expr, original.expressions.get_span(old_h))
+                }
+            };
+            new_pos.insert(old_h, new_h);
+        }
+
+        let mut original_body = original.body.clone();
+        adjust_block(&new_pos, &mut original_body);
+        body.extend_block(original_body);
+
+        let mut local_variables = original.local_variables.clone();
+        for (_, local) in local_variables.iter_mut() {
+            if let Some(ref mut init) = local.init {
+                *init = new_pos[*init];
+            }
+        }
+
+        let named_expressions = original
+            .named_expressions
+            .iter()
+            .map(|(&expr_h, name)| (new_pos[expr_h], name.clone()))
+            .collect();
+
+        let mut name = original
+            .name
+            .clone()
+            .unwrap_or_else(|| "function".to_string());
+        for path in paths.iter().flatten() {
+            name.push('_');
+            name.push_str(
+                self.original.global_variables[path.global]
+                    .name
+                    .as_deref()
+                    .unwrap_or("global"),
+            );
+        }
+
+        let specialized = Function {
+            name: Some(name),
+            arguments,
+            result: original.result.clone(),
+            local_variables,
+            expressions,
+            named_expressions,
+            body,
+            diagnostic_filter_leaf: original.diagnostic_filter_leaf,
+        };
+
+        (specialized, scalars)
+    }
+}
+
+/// Return true if a parameter of type `inner` must be specialized away.
+const fn is_specialized_parameter(inner: &TypeInner) -> bool {
+    matches!(
+        inner.pointer_space(),
+        Some(AddressSpace::Uniform | AddressSpace::Storage { .. } | AddressSpace::WorkGroup)
+    )
+}
+
+/// Decompose the pointer `expr` into a global variable and access steps.
+///
+/// Return the path, along with the handles of the expressions used as
+/// dynamic indices along the way.
+fn pointer_path(
+    mut expr: Handle<Expression>,
+    expressions: &Arena<Expression>,
+    scalars: &[Option<Scalar>],
+) -> (PointerPath, Vec<Handle<Expression>>) {
+    let mut steps = Vec::new();
+    let mut indices = Vec::new();
+    let global = loop {
+        match expressions[expr] {
+            Expression::Access { base, index } => {
+                let scalar = scalars[index.index()]
+                    .expect("dynamic index of a pointer argument must be a scalar");
+                steps.push(AccessStep::Dynamic(scalar));
+                indices.push(index);
+                expr = base;
+            }
+            Expression::AccessIndex { base, index } => {
+                steps.push(AccessStep::Constant(index));
+                expr = base;
+            }
+            Expression::GlobalVariable(global) => break global,
+            ref other => unreachable!(
+                "pointer argument must be derived from a global variable, found {other:?}"
+            ),
+        }
+    };
+    steps.reverse();
+    indices.reverse();
+    (PointerPath { global, steps }, indices)
+}
+
+/// Return the scalar type of each expression in `function`, indexed by handle.
+fn expression_scalars(
+    module: &Module,
+    function: &Function,
+    info: &FunctionInfo,
+) -> Vec<Option<Scalar>> {
+    function
+        .expressions
+        .iter()
+        .map(|(handle, _)| scalar_of(module, &info[handle].ty))
+        .collect()
+}
+
+fn scalar_of(module: &Module, ty: &crate::proc::TypeResolution) -> Option<Scalar> {
+    match *ty.inner_with(&module.types) {
+        TypeInner::Scalar(scalar) => Some(scalar),
+        _ => None,
+    }
+}
@@ -959,6 +959,43 @@ impl BlockContext<'_> {
                             "Correct TypeInner for dot product should be already validated"
                         ),
                     },
+                    fun @ (Mf::Dot4I8Packed | Mf::Dot4U8Packed) => {
+                        let is_signed = matches!(fun, Mf::Dot4I8Packed);
+                        if self.writer.physical_layout.version >= 0x10600
+                            && self
+                                .writer
+                                .require_all(&[
+                                    spirv::Capability::DotProduct,
+                                    spirv::Capability::DotProductInput4x8BitPacked,
+                                ])
+                                .is_ok()
+                        {
+                            let op = if is_signed {
+                                spirv::Op::SDot
+                            } else {
+                                spirv::Op::UDot
+                            };
+                            MathOp::Custom(Instruction::ternary(
+                                op,
+                                result_type_id,
+                                id,
+                                arg0_id,
+                                arg1_id,
+                                spirv::PackedVectorFormat::PackedVectorFormat4x8Bit as Word,
+                            ))
+                        } else {
+                            self.write_dot4_packed_polyfill(
+                                id,
+                                result_type_id,
+                                arg0_id,
+                                arg1_id,
+                                is_signed,
+                                block,
+                            );
+                            self.cached[expr_handle] = id;
+                            return Ok(());
+                        }
+                    }
                     Mf::Outer => MathOp::Custom(Instruction::binary(
                         spirv::Op::OuterProduct,
                         result_type_id,
@@ -1282,10 +1319,12 @@ impl BlockContext<'_> {
                     Mf::Pack2x16float => MathOp::Ext(spirv::GLOp::PackHalf2x16),
                     Mf::Pack2x16unorm => MathOp::Ext(spirv::GLOp::PackUnorm2x16),
                     Mf::Pack2x16snorm => MathOp::Ext(spirv::GLOp::PackSnorm2x16),
-                    fun @ (Mf::Pack4xI8 | Mf::Pack4xU8) => {
-                        let (int_type, is_signed) = match fun {
-                            Mf::Pack4xI8 => (crate::ScalarKind::Sint, true),
-                            Mf::Pack4xU8 => (crate::ScalarKind::Uint, false),
+                    fun @ (Mf::Pack4xI8 | Mf::Pack4xU8 | Mf::Pack4xI8Clamp | Mf::Pack4xU8Clamp) => {
+                        let (int_type, is_signed, should_clamp) = match fun {
+                            Mf::Pack4xI8 => (crate::ScalarKind::Sint, true, false),
+                            Mf::Pack4xU8 => (crate::ScalarKind::Uint, false, false),
+                            Mf::Pack4xI8Clamp => (crate::ScalarKind::Sint, true, true),
+                            Mf::Pack4xU8Clamp => (crate::ScalarKind::Uint, false, true),
                             _ => unreachable!(),
                         };
                         let uint_type_id = self.get_type_id(LookupType::Local(LocalType::Numeric(
@@ -1299,6 +1338,49 @@ impl BlockContext<'_> {
                             }),
                         )));
 
+                        let arg0_id = if should_clamp {
+                            let (min, max, clamp_op) = if is_signed {
+                                (
+                                    crate::Literal::I32(-128),
+                                    crate::Literal::I32(127),
+                                    spirv::GLOp::SClamp,
+                                )
+                            } else {
+                                (
+                                    crate::Literal::U32(0),
+                                    crate::Literal::U32(255),
+                                    spirv::GLOp::UClamp,
+                                )
+                            };
+                            let vec_type =
+                                LookupType::Local(LocalType::Numeric(NumericType::Vector {
+                                    size: crate::VectorSize::Quad,
+                                    scalar: crate::Scalar {
+                                        kind: int_type,
+                                        width: 4,
+                                    },
+                                }));
+                            let vec_type_id = self.get_type_id(vec_type);
+                            let min_id = self.writer.get_constant_scalar(min);
+                            let min_vec_id =
+                                self.writer.get_constant_composite(vec_type, &[min_id; 4]);
+                            let max_id = self.writer.get_constant_scalar(max);
+                            let max_vec_id =
+                                self.writer.get_constant_composite(vec_type, &[max_id; 4]);
+
+                            let clamped_id = self.gen_id();
+                            block.body.push(Instruction::ext_inst(
+                                self.writer.gl450_ext_inst_id,
+                                clamp_op,
+                                vec_type_id,
+                                clamped_id,
+                                &[arg0_id, min_vec_id, max_vec_id],
+                            ));
+                            clamped_id
+                        } else {
+                            arg0_id
+                        };
+
                         let mut last_instruction = Instruction::new(spirv::Op::Nop);
 
                         let zero = self.writer.get_constant_scalar(crate::Literal::U32(0));
@@ -2261,6 +2343,94 @@ impl BlockContext<'_> {
         }
     }
 
+    /// Write a polyfill for `dot4I8Packed` and `dot4U8Packed`.
+    ///
+    /// Each 8-bit component of the packed operands is extracted with
+    /// `OpBitFieldSExtract` or `OpBitFieldUExtract`, and the products of
+    /// the components are summed up like in [`Self::write_dot_product`].
+    fn write_dot4_packed_polyfill(
+        &mut self,
+        result_id: Word,
+        result_type_id: Word,
+        arg0_id: Word,
+        arg1_id: Word,
+        is_signed: bool,
+        block: &mut Block,
+    ) {
+        let (extract_op, arg0_id, arg1_id) = if is_signed {
+            // The operands are `u32`, but sign extension needs an `i32` base.
+            let mut bitcast = |arg_id| {
+                let id = self.gen_id();
+                block.body.push(Instruction::unary(
+                    spirv::Op::Bitcast,
+                    result_type_id,
+                    id,
+                    arg_id,
+                ));
+                id
+            };
+            let arg0_id = bitcast(arg0_id);
+            let arg1_id = bitcast(arg1_id);
+            (spirv::Op::BitFieldSExtract, arg0_id, arg1_id)
+        } else {
+            (spirv::Op::BitFieldUExtract, arg0_id, arg1_id)
+        };
+
+        let eight = self.writer.get_constant_scalar(crate::Literal::U32(8));
+        let mut partial_sum = self.writer.get_constant_null(result_type_id);
+        let last_component = 3;
+        for index in 0..=last_component {
+            // extract the current components and compute their product
+            let offset = self
+                .writer
+                .get_constant_scalar(crate::Literal::U32(index * 8));
+            let a_id = self.gen_id();
+            block.body.push(Instruction::ternary(
+                extract_op,
+                result_type_id,
+                a_id,
+                arg0_id,
+                offset,
+                eight,
+            ));
+            let b_id = self.gen_id();
+            block.body.push(Instruction::ternary(
+                extract_op,
+                result_type_id,
+                b_id,
+                arg1_id,
+                offset,
+                eight,
+            ));
+            let prod_id = self.gen_id();
+            block.body.push(Instruction::binary(
+                spirv::Op::IMul,
+                result_type_id,
+                prod_id,
+                a_id,
+                b_id,
+            ));
+
+            // choose the id for the next sum, depending on current index
+            let id = if index == last_component {
+                result_id
+            } else {
+                self.gen_id()
+            };
+
+            // sum the computed product with the partial sum
+            block.body.push(Instruction::binary(
+                spirv::Op::IAdd,
+                result_type_id,
+                id,
+                partial_sum,
+                prod_id,
+            ));
+            // set the id of the result as the previous partial sum
+            partial_sum = id;
+        }
+    }
+
     /// Generate one or more SPIR-V blocks for `naga_block`.
     ///
     /// Use `label_id` as the label for the SPIR-V entry point block.
@@ -68,6 +68,22 @@ pub(super) fn contains_builtin(
     }
 }
 
+/// Return true if `ty` is or contains a 16-bit float.
+pub(super) fn contains_f16(ty: Handle<crate::Type>, arena: &UniqueArena<crate::Type>) -> bool {
+    match arena[ty].inner {
+        crate::TypeInner::Scalar(scalar)
+        | crate::TypeInner::Vector { scalar, .. }
+        | crate::TypeInner::Matrix { scalar, .. } => scalar == crate::Scalar::F16,
+        crate::TypeInner::Array { base, .. } | crate::TypeInner::BindingArray { base, .. } => {
+            contains_f16(base, arena)
+        }
+        crate::TypeInner::Struct { ref members, .. } => {
+            members.iter().any(|member| contains_f16(member.ty, arena))
+        }
+        _ => false,
+    }
+}
+
 impl crate::AddressSpace {
     pub(super) const fn to_spirv_semantics_and_scope(
         self,
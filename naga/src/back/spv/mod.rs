@@ -73,6 +73,8 @@ pub enum Error {
     Validation(&'static str),
     #[error("overrides should not be present at this stage")]
     Override,
+    #[error("functions with uniform, storage, or workgroup pointer parameters should be specialized at this stage")]
+    PointerParameter,
 }
 
 #[derive(Default)]
@@ -155,6 +155,12 @@ impl BlockContext<'_> {
                     &[spirv::Capability::GroupNonUniformShuffleRelative],
                 )?;
             }
+            crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_) => {
+                self.writer.require_any(
+                    "GroupNonUniformQuad",
+                    &[spirv::Capability::GroupNonUniformQuad],
+                )?;
+            }
         }
 
         let id = self.gen_id();
@@ -179,10 +185,13 @@ impl BlockContext<'_> {
             | crate::GatherMode::Shuffle(index)
             | crate::GatherMode::ShuffleDown(index)
             | crate::GatherMode::ShuffleUp(index)
-            | crate::GatherMode::ShuffleXor(index) => {
+            | crate::GatherMode::ShuffleXor(index)
+            | crate::GatherMode::QuadBroadcast(index) => {
                 let index_id = self.cached[index];
                 let op = match *mode {
-                    crate::GatherMode::BroadcastFirst => unreachable!(),
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {
+                        unreachable!()
+                    }
                     // Use shuffle to emit broadcast to allow the index to
                     // be dynamically uniform on Vulkan 1.1. The argument to
                     // OpGroupNonUniformBroadcast must be a constant pre SPIR-V
@@ -192,6 +201,7 @@ impl BlockContext<'_> {
                     crate::GatherMode::ShuffleDown(_) => spirv::Op::GroupNonUniformShuffleDown,
                     crate::GatherMode::ShuffleUp(_) => spirv::Op::GroupNonUniformShuffleUp,
                     crate::GatherMode::ShuffleXor(_) => spirv::Op::GroupNonUniformShuffleXor,
+                    crate::GatherMode::QuadBroadcast(_) => spirv::Op::GroupNonUniformQuadBroadcast,
                 };
                 block.body.push(Instruction::group_non_uniform_gather(
                     op,
@@ -202,6 +212,17 @@ impl BlockContext<'_> {
                     index_id,
                 ));
             }
+            crate::GatherMode::QuadSwap(direction) => {
+                let direction_id = self.get_index_constant(direction as u32);
+                block.body.push(Instruction::group_non_uniform_gather(
+                    spirv::Op::GroupNonUniformQuadSwap,
+                    result_type_id,
+                    id,
+                    exec_scope_id,
+                    arg_id,
+                    direction_id,
+                ));
+            }
         }
         self.cached[result] = id;
         Ok(())
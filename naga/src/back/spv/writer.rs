@@ -1,6 +1,6 @@
 use super::{
     block::DebugInfoInner,
-    helpers::{contains_builtin, global_needs_wrapper, map_storage_class},
+    helpers::{contains_builtin, contains_f16, global_needs_wrapper, map_storage_class},
     Block, BlockContext, CachedConstant, CachedExpressions, DebugInfo, EntryPointContext, Error,
     Function, FunctionArgument, GlobalVariable, IdGenerator, Instruction, LocalType, LocalVariable,
     LogicalLayout, LookupFunctionType, LookupType, NumericType, Options, PhysicalLayout,
@@ -179,6 +179,35 @@ impl Writer {
         }
     }
 
+    /// Indicate that the code requires all of the listed capabilities.
+    ///
+    /// If all entries of `capabilities` appear in the available capabilities
+    /// specified in the [`Options`] from which this `Writer` was created
+    /// (including the case where no available capabilities were given), add
+    /// them all to this `Writer`'s [`capabilities_used`] table, and return
+    /// `Ok(())`. Otherwise, leave [`capabilities_used`] untouched and return
+    /// the first unavailable capability.
+    ///
+    /// [`capabilities_used`]: Writer::capabilities_used
+    pub(super) fn require_all(
+        &mut self,
+        capabilities: &[spirv::Capability],
+    ) -> Result<(), spirv::Capability> {
+        if let Some(ref available) = self.capabilities_available {
+            for requested in capabilities {
+                if !available.contains(requested) {
+                    return Err(*requested);
+                }
+            }
+        }
+
+        for requested in capabilities {
+            self.capabilities_used.insert(*requested);
+        }
+
+        Ok(())
+    }
+
     /// Indicate that the code uses the given extension.
     pub(super) fn use_extension(&mut self, extension: &'static str) {
         self.extensions_used.insert(extension);
@@ -796,8 +825,14 @@ impl Writer {
                 Instruction::type_int(id, bits, signedness)
             }
             Sk::Float => {
-                if bits == 64 {
-                    self.capabilities_used.insert(spirv::Capability::Float64);
+                match bits {
+                    16 => {
+                        self.capabilities_used.insert(spirv::Capability::Float16);
+                    }
+                    64 => {
+                        self.capabilities_used.insert(spirv::Capability::Float64);
+                    }
+                    _ => {}
                 }
                 Instruction::type_float(id, bits)
             }
@@ -1160,6 +1195,9 @@ impl Writer {
                 Instruction::constant_64bit(type_id, id, bits as u32, (bits >> 32) as u32)
             }
             crate::Literal::F32(value) => Instruction::constant_32bit(type_id, id, value.to_bits()),
+            crate::Literal::F16(value) => {
+                Instruction::constant_16bit(type_id, id, value.to_bits() as u32)
+            }
             crate::Literal::U32(value) => Instruction::constant_32bit(type_id, id, value),
             crate::Literal::I32(value) => Instruction::constant_32bit(type_id, id, value as u32),
             crate::Literal::U64(value) => {
@@ -1274,7 +1312,7 @@ impl Writer {
     }
 
     pub(super) fn write_barrier(&mut self, flags: crate::Barrier, block: &mut Block) {
-        let memory_scope = if flags.contains(crate::Barrier::STORAGE) {
+        let memory_scope = if flags.intersects(crate::Barrier::STORAGE | crate::Barrier::TEXTURE) {
             spirv::Scope::Device
         } else {
             spirv::Scope::Workgroup
@@ -1288,6 +1326,10 @@ impl Writer {
             spirv::MemorySemantics::WORKGROUP_MEMORY,
             flags.contains(crate::Barrier::WORK_GROUP),
         );
+        semantics.set(
+            spirv::MemorySemantics::IMAGE_MEMORY,
+            flags.contains(crate::Barrier::TEXTURE),
+        );
         let exec_scope_id = if flags.contains(crate::Barrier::SUB_GROUP) {
             self.get_index_constant(spirv::Scope::Subgroup as u32)
         } else {
@@ -1409,6 +1451,31 @@ impl Writer {
         Some(next_id)
     }
 
+    /// Request the `SPV_KHR_16bit_storage` capabilities needed to
+    /// declare a variable of type `ty` in `class`, if `ty` contains `f16`.
+    fn request_16bit_storage(
+        &mut self,
+        ir_module: &crate::Module,
+        class: spirv::StorageClass,
+        ty: Handle<crate::Type>,
+    ) -> Result<(), Error> {
+        if !contains_f16(ty, &ir_module.types) {
+            return Ok(());
+        }
+        let capability = match class {
+            spirv::StorageClass::StorageBuffer => spirv::Capability::StorageBuffer16BitAccess,
+            spirv::StorageClass::Uniform => spirv::Capability::UniformAndStorageBuffer16BitAccess,
+            spirv::StorageClass::Input | spirv::StorageClass::Output => {
+                spirv::Capability::StorageInputOutput16
+            }
+            spirv::StorageClass::PushConstant => spirv::Capability::StoragePushConstant16,
+            _ => return Ok(()),
+        };
+        self.require_any("16 bit storage", &[capability])?;
+        self.use_extension("SPV_KHR_16bit_storage");
+        Ok(())
+    }
+
     /// Generate an `OpVariable` for one value in an [`EntryPoint`]'s IO interface.
     ///
     /// The [`Binding`]s of the arguments and result of an [`EntryPoint`]'s
@@ -1437,6 +1504,8 @@ impl Writer {
         ty: Handle<crate::Type>,
         binding: &crate::Binding,
     ) -> Result<Word, Error> {
+        self.request_16bit_storage(ir_module, class, ty)?;
+
         let id = self.id_gen.next();
         let pointer_type_id = self.get_pointer_id(ty, class);
         Instruction::variable(pointer_type_id, id, class, None)
@@ -1655,6 +1724,7 @@ impl Writer {
         let class = map_storage_class(global_variable.space);
 
         //self.check(class.required_capabilities())?;
+        self.request_16bit_storage(ir_module, class, global_variable.ty)?;
 
         if self.flags.contains(WriterFlags::DEBUG) {
             if let Some(ref name) = global_variable.name {
@@ -2075,6 +2145,9 @@ impl Writer {
         if !ir_module.overrides.is_empty() {
             return Err(Error::Override);
         }
+        if crate::back::pointer_parameters::needs_specialization(ir_module) {
+            return Err(Error::PointerParameter);
+        }
 
         self.reset();
 
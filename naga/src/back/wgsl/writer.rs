@@ -13,12 +13,12 @@ type BackendResult = Result<(), Error>;
 /// WGSL [attribute](https://gpuweb.github.io/gpuweb/wgsl/#attributes)
 enum Attribute {
     Binding(u32),
+    BlendSrc(u32),
     BuiltIn(crate::BuiltIn),
     Group(u32),
     Invariant,
     Interpolate(Option<crate::Interpolation>, Option<crate::Sampling>),
     Location(u32),
-    SecondBlendSource,
     Stage(ShaderStage),
     WorkGroupSize([u32; 3]),
 }
@@ -101,6 +101,67 @@ impl<W: Write> Writer<W> {
         self.required_polyfills.clear();
     }
 
+    /// Write the `enable` directives for the extensions `module` depends on.
+    fn write_enable_declarations(&mut self, module: &Module) -> BackendResult {
+        let uses_f16_literal = |expressions: &crate::Arena<crate::Expression>| {
+            expressions.iter().any(|(_, expr)| {
+                matches!(*expr, crate::Expression::Literal(crate::Literal::F16(_)))
+            })
+        };
+
+        let needs_f16 = module
+            .types
+            .iter()
+            .any(|(_, ty)| ty.inner.scalar() == Some(crate::Scalar::F16))
+            || uses_f16_literal(&module.global_expressions)
+            || module
+                .functions
+                .iter()
+                .any(|(_, f)| uses_f16_literal(&f.expressions))
+            || module
+                .entry_points
+                .iter()
+                .any(|ep| uses_f16_literal(&ep.function.expressions));
+
+        let is_clip_distances = |binding: Option<&crate::Binding>| {
+            binding == Some(&crate::Binding::BuiltIn(crate::BuiltIn::ClipDistance))
+        };
+
+        let needs_clip_distances = module.types.iter().any(|(_, ty)| match ty.inner {
+            TypeInner::Struct { ref members, .. } => members
+                .iter()
+                .any(|member| is_clip_distances(member.binding.as_ref())),
+            _ => false,
+        }) || module.entry_points.iter().any(|ep| {
+            ep.function
+                .result
+                .as_ref()
+                .is_some_and(|result| is_clip_distances(result.binding.as_ref()))
+        });
+
+        let needs_dual_source_blending = module.types.iter().any(|(_, ty)| match ty.inner {
+            TypeInner::Struct { ref members, .. } => members
+                .iter()
+                .any(|member| is_second_blend_source(member.binding.as_ref())),
+            _ => false,
+        });
+
+        if needs_f16 {
+            writeln!(self.out, "enable f16;")?;
+        }
+        if needs_clip_distances {
+            writeln!(self.out, "enable clip_distances;")?;
+        }
+        if needs_dual_source_blending {
+            writeln!(self.out, "enable dual_source_blending;")?;
+        }
+        if needs_f16 || needs_clip_distances || needs_dual_source_blending {
+            writeln!(self.out)?;
+        }
+
+        Ok(())
+    }
+
     fn is_builtin_wgsl_struct(&self, module: &Module, handle: Handle<crate::Type>) -> bool {
         module
             .special_types
@@ -125,6 +186,8 @@ impl<W: Write> Writer<W> {
             }
         }
 
+        self.write_enable_declarations(module)?;
+
         // Write all structs
         for (handle, ty) in module.types.iter() {
             if let TypeInner::Struct { ref members, .. } = ty.inner {
@@ -340,7 +403,7 @@ impl<W: Write> Writer<W> {
         for attribute in attributes {
             match *attribute {
                 Attribute::Location(id) => write!(self.out, "@location({id}) ")?,
-                Attribute::SecondBlendSource => write!(self.out, "@second_blend_source ")?,
+                Attribute::BlendSrc(blend_src) => write!(self.out, "@blend_src({blend_src}) ")?,
                 Attribute::BuiltIn(builtin_attrib) => {
                     let builtin = builtin_str(builtin_attrib)?;
                     write!(self.out, "@builtin({builtin}) ")?;
@@ -404,11 +467,27 @@ impl<W: Write> Writer<W> {
         self.write_struct_name(module, handle)?;
         write!(self.out, " {{")?;
         writeln!(self.out)?;
+        // WGSL requires `@blend_src` on both outputs of a dual-source pair,
+        // while the IR only marks the second one.
+        let dual_source_blending = members
+            .iter()
+            .any(|member| is_second_blend_source(member.binding.as_ref()));
         for (index, member) in members.iter().enumerate() {
             // The indentation is only for readability
             write!(self.out, "{}", back::INDENT)?;
             if let Some(ref binding) = member.binding {
-                self.write_attributes(&map_binding_to_attribute(binding))?;
+                let mut attributes = map_binding_to_attribute(binding);
+                if dual_source_blending {
+                    if let crate::Binding::Location {
+                        location: 0,
+                        second_blend_source: false,
+                        ..
+                    } = *binding
+                    {
+                        attributes.insert(1, Attribute::BlendSrc(0));
+                    }
+                }
+                self.write_attributes(&attributes)?;
             }
             // Write struct member name and type
             let member_name = &self.names[&NameKey::StructMember(handle, index as u32)];
@@ -973,6 +1052,10 @@ impl<W: Write> Writer<W> {
                 if barrier.contains(crate::Barrier::SUB_GROUP) {
                     writeln!(self.out, "{level}subgroupBarrier();")?;
                 }
+
+                if barrier.contains(crate::Barrier::TEXTURE) {
+                    writeln!(self.out, "{level}textureBarrier();")?;
+                }
             }
             Statement::RayQuery { .. } => unreachable!(),
             Statement::SubgroupBallot { result, predicate } => {
@@ -1072,15 +1155,24 @@ impl<W: Write> Writer<W> {
                     crate::GatherMode::ShuffleXor(_) => {
                         write!(self.out, "subgroupShuffleXor(")?;
                     }
+                    crate::GatherMode::QuadBroadcast(_) => {
+                        write!(self.out, "quadBroadcast(")?;
+                    }
+                    crate::GatherMode::QuadSwap(direction) => match direction {
+                        crate::Direction::X => write!(self.out, "quadSwapX(")?,
+                        crate::Direction::Y => write!(self.out, "quadSwapY(")?,
+                        crate::Direction::Diagonal => write!(self.out, "quadSwapDiagonal(")?,
+                    },
                 }
                 self.write_expr(module, argument, func_ctx)?;
                 match mode {
-                    crate::GatherMode::BroadcastFirst => {}
+                    crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                     crate::GatherMode::Broadcast(index)
                     | crate::GatherMode::Shuffle(index)
                     | crate::GatherMode::ShuffleDown(index)
                     | crate::GatherMode::ShuffleUp(index)
-                    | crate::GatherMode::ShuffleXor(index) => {
+                    | crate::GatherMode::ShuffleXor(index)
+                    | crate::GatherMode::QuadBroadcast(index) => {
                         write!(self.out, ", ")?;
                         self.write_expr(module, index, func_ctx)?;
                     }
@@ -1263,6 +1355,7 @@ impl<W: Write> Writer<W> {
         match expressions[expr] {
             Expression::Literal(literal) => match literal {
                 crate::Literal::F32(value) => write!(self.out, "{value}f")?,
+                crate::Literal::F16(value) => write!(self.out, "{value}h")?,
                 crate::Literal::U32(value) => write!(self.out, "{value}u")?,
                 crate::Literal::I32(value) => {
                     // `-2147483648i` is not valid WGSL. The most negative `i32`
@@ -1736,6 +1829,8 @@ impl<W: Write> Writer<W> {
                     Mf::Pow => Function::Regular("pow"),
                     // geometry
                     Mf::Dot => Function::Regular("dot"),
+                    Mf::Dot4I8Packed => Function::Regular("dot4I8Packed"),
+                    Mf::Dot4U8Packed => Function::Regular("dot4U8Packed"),
                     Mf::Cross => Function::Regular("cross"),
                     Mf::Distance => Function::Regular("distance"),
                     Mf::Length => Function::Regular("length"),
@@ -1771,6 +1866,8 @@ impl<W: Write> Writer<W> {
                     Mf::Pack2x16float => Function::Regular("pack2x16float"),
                     Mf::Pack4xI8 => Function::Regular("pack4xI8"),
                     Mf::Pack4xU8 => Function::Regular("pack4xU8"),
+                    Mf::Pack4xI8Clamp => Function::Regular("pack4xI8Clamp"),
+                    Mf::Pack4xU8Clamp => Function::Regular("pack4xU8Clamp"),
                     // data unpacking
                     Mf::Unpack4x8snorm => Function::Regular("unpack4x8snorm"),
                     Mf::Unpack4x8unorm => Function::Regular("unpack4x8unorm"),
@@ -1987,13 +2084,13 @@ fn builtin_str(built_in: crate::BuiltIn) -> Result<&'static str, Error> {
         Bi::SampleMask => "sample_mask",
         Bi::PrimitiveIndex => "primitive_index",
         Bi::ViewIndex => "view_index",
+        Bi::ClipDistance => "clip_distances",
         Bi::NumSubgroups => "num_subgroups",
         Bi::SubgroupId => "subgroup_id",
         Bi::SubgroupSize => "subgroup_size",
         Bi::SubgroupInvocationId => "subgroup_invocation_id",
         Bi::BaseInstance
         | Bi::BaseVertex
-        | Bi::ClipDistance
         | Bi::CullDistance
         | Bi::PointSize
         | Bi::PointCoord
@@ -2026,6 +2123,10 @@ const fn scalar_kind_str(scalar: crate::Scalar) -> &'static str {
             kind: Sk::Float,
             width: 4,
         } => "f32",
+        Scalar {
+            kind: Sk::Float,
+            width: 2,
+        } => "f16",
         Scalar {
             kind: Sk::Sint,
             width: 4,
@@ -2149,6 +2250,16 @@ const fn address_space_str(
     )
 }
 
+const fn is_second_blend_source(binding: Option<&crate::Binding>) -> bool {
+    matches!(
+        binding,
+        Some(&crate::Binding::Location {
+            second_blend_source: true,
+            ..
+        })
+    )
+}
+
 fn map_binding_to_attribute(binding: &crate::Binding) -> Vec<Attribute> {
     match *binding {
         crate::Binding::BuiltIn(built_in) => {
@@ -2174,7 +2285,7 @@ fn map_binding_to_attribute(binding: &crate::Binding) -> Vec<Attribute> {
             second_blend_source: true,
         } => vec![
             Attribute::Location(location),
-            Attribute::SecondBlendSource,
+            Attribute::BlendSrc(1),
             Attribute::Interpolate(interpolation, sampling),
         ],
     }
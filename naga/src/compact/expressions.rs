@@ -109,6 +109,10 @@ impl ExpressionTracer<'_> {
 
             Ex::Constant(handle) => {
                 self.constants_used.insert(handle);
+                // `compact::compact` notes the types of the constants
+                // functions use, but constants used only by global
+                // expressions are found after that, so note the type here.
+                self.types_used_insert(self.constants[handle].ty);
                 // Constants and expressions are mutually recursive, which
                 // complicates our nice one-pass algorithm. However, since
                 // constants don't refer to each other, we can get around
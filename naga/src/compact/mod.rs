@@ -484,3 +484,57 @@ fn type_expression_interdependence() {
     compact(&mut module);
     assert!(cmp_modules(&module, &untouched));
 }
+
+#[test]
+fn constant_used_by_global_expression() {
+    let mut module: crate::Module = Default::default();
+    let scalar = |kind| crate::Type {
+        name: None,
+        inner: crate::TypeInner::Scalar(crate::Scalar { kind, width: 4 }),
+    };
+    let u32 = module
+        .types
+        .insert(scalar(crate::ScalarKind::Uint), crate::Span::default());
+    let i32 = module
+        .types
+        .insert(scalar(crate::ScalarKind::Sint), crate::Span::default());
+
+    // An unnamed `i32` constant, used only by an override's initializer.
+    let init = module.global_expressions.append(
+        crate::Expression::Literal(crate::Literal::I32(1)),
+        crate::Span::default(),
+    );
+    let constant = module.constants.append(
+        crate::Constant {
+            name: None,
+            ty: i32,
+            init,
+        },
+        crate::Span::default(),
+    );
+    let expr = module.global_expressions.append(
+        crate::Expression::Constant(constant),
+        crate::Span::default(),
+    );
+    let init = module.global_expressions.append(
+        crate::Expression::As {
+            expr,
+            kind: crate::ScalarKind::Uint,
+            convert: Some(4),
+        },
+        crate::Span::default(),
+    );
+    module.overrides.append(
+        crate::Override {
+            name: Some("o".to_string()),
+            id: None,
+            ty: u32,
+            init: Some(init),
+        },
+        crate::Span::default(),
+    );
+
+    compact(&mut module);
+    let (_, constant) = module.constants.iter().next().unwrap();
+    assert_eq!(module.types[constant.ty], scalar(crate::ScalarKind::Sint));
+}
@@ -134,12 +134,13 @@ impl FunctionTracer<'_> {
                         result,
                     } => {
                         match mode {
-                            crate::GatherMode::BroadcastFirst => {}
+                            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                             crate::GatherMode::Broadcast(index)
                             | crate::GatherMode::Shuffle(index)
                             | crate::GatherMode::ShuffleDown(index)
                             | crate::GatherMode::ShuffleUp(index)
-                            | crate::GatherMode::ShuffleXor(index) => {
+                            | crate::GatherMode::ShuffleXor(index)
+                            | crate::GatherMode::QuadBroadcast(index) => {
                                 self.expressions_used.insert(index);
                             }
                         }
@@ -339,12 +340,13 @@ impl FunctionMap {
                         ref mut result,
                     } => {
                         match *mode {
-                            crate::GatherMode::BroadcastFirst => {}
+                            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                             crate::GatherMode::Broadcast(ref mut index)
                             | crate::GatherMode::Shuffle(ref mut index)
                             | crate::GatherMode::ShuffleDown(ref mut index)
                             | crate::GatherMode::ShuffleUp(ref mut index)
-                            | crate::GatherMode::ShuffleXor(ref mut index) => adjust(index),
+                            | crate::GatherMode::ShuffleXor(ref mut index)
+                            | crate::GatherMode::QuadBroadcast(ref mut index) => adjust(index),
                         }
                         adjust(argument);
                         adjust(result);
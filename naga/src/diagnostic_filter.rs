@@ -1,8 +1,6 @@
 //! [`DiagnosticFilter`]s and supporting functionality.
 
-#[cfg(feature = "wgsl-in")]
-use crate::Span;
-use crate::{Arena, Handle};
+use crate::{Arena, Handle, SourceLocation, Span};
 #[cfg(feature = "arbitrary")]
 use arbitrary::Arbitrary;
 #[cfg(feature = "wgsl-in")]
@@ -27,27 +25,160 @@ pub enum Severity {
 }
 
 impl Severity {
-    /// Checks whether this severity is [`Self::Error`].
+    /// Reports a diagnostic item at this severity.
     ///
-    /// Naga does not yet support diagnostic items at lesser severities than
-    /// [`Severity::Error`]. When this is implemented, this method should be deleted, and the
-    /// severity should be used directly for reporting diagnostics.
+    /// [`Severity::Error`] returns `err`, [`Severity::Off`] drops it, and the
+    /// other severities turn it into a [`Diagnostic`] with `to_diagnostic` and
+    /// add it to `diagnostics`.
     pub(crate) fn report_diag<E>(
         self,
         err: E,
-        log_handler: impl FnOnce(E, log::Level),
+        diagnostics: &mut Vec<Diagnostic>,
+        to_diagnostic: impl FnOnce(E, Severity) -> Diagnostic,
     ) -> Result<(), E> {
-        let log_level = match self {
-            Severity::Off => return Ok(()),
+        match self {
+            Severity::Off => {}
+            Severity::Info | Severity::Warning => diagnostics.push(to_diagnostic(err, self)),
+            Severity::Error => return Err(err),
+        }
+        Ok(())
+    }
+}
 
-            // NOTE: These severities are not yet reported.
-            Severity::Info => log::Level::Info,
-            Severity::Warning => log::Level::Warn,
+/// A diagnostic item that doesn't stop a shader from being processed, like
+/// a warning.
+///
+/// Front ends and the [`Validator`] collect these instead of failing, see
+/// [`wgsl::Frontend::diagnostics`] and [`ModuleInfo::diagnostics`].
+///
+/// [`Validator`]: crate::valid::Validator
+/// [`wgsl::Frontend::diagnostics`]: crate::front::wgsl::Frontend::diagnostics
+/// [`ModuleInfo::diagnostics`]: crate::valid::ModuleInfo::diagnostics
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub(crate) severity: Severity,
+    pub(crate) message: String,
+    // The first span should be the primary span, and the other ones should be complementary.
+    pub(crate) labels: Vec<(Span, String)>,
+    pub(crate) notes: Vec<String>,
+}
 
-            Severity::Error => return Err(err),
+impl Diagnostic {
+    /// Returns the severity of the diagnostic, either [`Severity::Info`] or
+    /// [`Severity::Warning`].
+    pub const fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn labels(&self) -> impl ExactSizeIterator<Item = (Span, &str)> + '_ {
+        self.labels
+            .iter()
+            .map(|&(span, ref msg)| (span, msg.as_str()))
+    }
+
+    pub fn notes(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.notes.iter().map(String::as_str)
+    }
+
+    /// Returns a [`SourceLocation`] for the first label of the diagnostic.
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        self.labels.first().map(|label| label.0.location(source))
+    }
+
+    fn diagnostic(&self) -> codespan_reporting::diagnostic::Diagnostic<()> {
+        self.diagnostic_with(|span| span.to_range().map(|range| ((), range)))
+    }
+
+    /// Builds the diagnostic, using `locate` to find the file and range
+    /// each label's span refers to.
+    fn diagnostic_with<FileId>(
+        &self,
+        locate: impl Fn(Span) -> Option<(FileId, std::ops::Range<usize>)>,
+    ) -> codespan_reporting::diagnostic::Diagnostic<FileId> {
+        use codespan_reporting::diagnostic::{Diagnostic, Label};
+        let diagnostic = match self.severity {
+            Severity::Error => Diagnostic::error(),
+            Severity::Warning => Diagnostic::warning(),
+            Severity::Info | Severity::Off => Diagnostic::note(),
         };
-        log_handler(err, log_level);
-        Ok(())
+        diagnostic
+            .with_message(self.message.clone())
+            .with_labels(
+                self.labels
+                    .iter()
+                    .filter_map(|&(span, ref msg)| {
+                        let (file, range) = locate(span)?;
+                        Some(Label::primary(file, range).with_message(msg.clone()))
+                    })
+                    .collect(),
+            )
+            .with_notes(
+                self.notes
+                    .iter()
+                    .map(|note| format!("note: {note}"))
+                    .collect(),
+            )
+    }
+
+    /// Emits a summary of the diagnostic to standard error stream.
+    pub fn emit_to_stderr_with_path(&self, source: &str, path: &str) {
+        use codespan_reporting::{files, term};
+        use term::termcolor::{ColorChoice, StandardStream};
+
+        let files = files::SimpleFile::new(path, source);
+        let config = term::Config::default();
+        let writer = StandardStream::stderr(ColorChoice::Auto);
+        term::emit(&mut writer.lock(), &config, &files, &self.diagnostic())
+            .expect("cannot write diagnostic");
+    }
+
+    /// Emits a summary of the diagnostic to a string.
+    pub fn emit_to_string(&self, source: &str) -> String {
+        self.emit_to_string_with_path(source, "wgsl")
+    }
+
+    /// Emits a summary of the diagnostic to a string.
+    pub fn emit_to_string_with_path(&self, source: &str, path: &str) -> String {
+        use codespan_reporting::{files, term};
+        use term::termcolor::NoColor;
+
+        let files = files::SimpleFile::new(path, source);
+        let config = term::Config::default();
+        let mut writer = NoColor::new(Vec::new());
+        term::emit(&mut writer, &config, &files, &self.diagnostic())
+            .expect("cannot write diagnostic");
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
+    /// Emits a summary of a diagnostic reported by [`Linker::link`] to a
+    /// string, pointing into the modules the diagnostic came from.
+    ///
+    /// [`Linker::link`]: crate::front::wgsl::Linker::link
+    #[cfg(feature = "wgsl-in")]
+    pub fn emit_to_string_with_linker(&self, linker: &crate::front::wgsl::Linker) -> String {
+        use codespan_reporting::{files, term};
+        use term::termcolor::NoColor;
+
+        let mut files = files::SimpleFiles::new();
+        for index in 0..linker.module_count() {
+            let (name, source) = linker.module(index).unwrap();
+            files.add(name, source);
+        }
+        let diagnostic = self.diagnostic_with(|span| linker.locate(span));
+        let config = term::Config::default();
+        let mut writer = NoColor::new(Vec::new());
+        term::emit(&mut writer, &config, &files, &diagnostic).expect("cannot write diagnostic");
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+}
+
+impl std::fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
     }
 }
 
@@ -251,6 +382,23 @@ impl DiagnosticFilterNode {
         arena: &Arena<Self>,
         triggering_rule: StandardFilterableTriggeringRule,
     ) -> Severity {
+        Self::search_rule(
+            node,
+            arena,
+            &FilterableTriggeringRule::Standard(triggering_rule),
+        )
+        .unwrap_or_else(|| triggering_rule.default_severity())
+    }
+
+    /// Like [`search`], but for any kind of `triggering_rule`, and returning
+    /// `None` if no filter rule applies to it.
+    ///
+    /// [`search`]: Self::search
+    pub(crate) fn search_rule(
+        node: Option<Handle<Self>>,
+        arena: &Arena<Self>,
+        triggering_rule: &FilterableTriggeringRule,
+    ) -> Option<Severity> {
         let mut next = node;
         while let Some(handle) = next {
             let node = &arena[handle];
@@ -260,12 +408,12 @@ impl DiagnosticFilterNode {
                 new_severity,
             } = inner;
 
-            if rule == &FilterableTriggeringRule::Standard(triggering_rule) {
-                return new_severity;
+            if rule == triggering_rule {
+                return Some(new_severity);
             }
 
             next = parent;
         }
-        triggering_rule.default_severity()
+        None
     }
 }
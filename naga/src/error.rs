@@ -24,6 +24,14 @@ impl fmt::Display for ShaderError<crate::front::glsl::ParseErrors> {
         write!(f, "\nShader '{label}' parsing {string}")
     }
 }
+#[cfg(feature = "hlsl-in")]
+impl fmt::Display for ShaderError<crate::front::hlsl::Error> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = self.label.as_deref().unwrap_or_default();
+        let string = self.inner.emit_to_string(&self.source);
+        write!(f, "\nShader '{label}' parsing {string}")
+    }
+}
 #[cfg(feature = "spv-in")]
 impl fmt::Display for ShaderError<crate::front::spv::Error> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1033,9 +1033,30 @@ fn inject_standard_builtins(
                     .push(module.add_builtin(args, MacroCall::Clamp(size)))
             }
         }
-        "barrier" => declaration
-            .overloads
-            .push(module.add_builtin(Vec::new(), MacroCall::Barrier)),
+        "barrier"
+        | "memoryBarrier"
+        | "memoryBarrierShared"
+        | "memoryBarrierBuffer"
+        | "memoryBarrierImage"
+        | "groupMemoryBarrier" => {
+            // Naga only has control barriers, so memory barriers also
+            // synchronize execution, which is stricter than GLSL requires.
+            // `Frontend::add_function` checks that they're only used in
+            // uniform control flow.
+            let barrier = match name {
+                "memoryBarrierShared" => crate::Barrier::WORK_GROUP,
+                "memoryBarrierBuffer" => crate::Barrier::STORAGE,
+                "memoryBarrierImage" => crate::Barrier::TEXTURE,
+                "memoryBarrier" | "groupMemoryBarrier" => {
+                    crate::Barrier::STORAGE | crate::Barrier::WORK_GROUP | crate::Barrier::TEXTURE
+                }
+                _ => crate::Barrier::all(),
+            };
+
+            declaration
+                .overloads
+                .push(module.add_builtin(Vec::new(), MacroCall::Barrier(barrier)))
+        }
         // Add common builtins with floats
         _ => inject_common_builtin(declaration, module, name, 4),
     }
@@ -1571,7 +1592,7 @@ pub enum MacroCall {
     Clamp(Option<VectorSize>),
     BitCast(Sk),
     Derivate(Axis, Ctrl),
-    Barrier,
+    Barrier(crate::Barrier),
     /// SmoothStep needs a separate variant because it might need it's inputs
     /// to be splatted depending on the overload
     SmoothStep {
@@ -2037,10 +2058,18 @@ impl MacroCall {
                 },
                 Span::default(),
             )?,
-            MacroCall::Barrier => {
+            MacroCall::Barrier(barrier) => {
                 ctx.emit_restart();
-                ctx.body
-                    .push(crate::Statement::Barrier(crate::Barrier::all()), meta);
+                // `barrier()` makes a memory barrier right before it
+                // redundant, so replace the latter, which lets the common
+                // `memoryBarrierShared(); barrier();` pair appear anywhere
+                // `barrier()` can.
+                if barrier == crate::Barrier::all() {
+                    if let Some(&crate::Statement::Barrier(_)) = ctx.body.last() {
+                        ctx.body.cull(ctx.body.len() - 1..);
+                    }
+                }
+                ctx.body.push(crate::Statement::Barrier(barrier), meta);
                 return Ok(None);
             }
             MacroCall::SmoothStep { splatted } => {
@@ -438,6 +438,26 @@ impl<'a> Context<'a> {
         res
     }
 
+    /// Returns whether `expr` refers to a dynamically sized value, like a
+    /// buffer block ending in a runtime sized array, which can't be loaded.
+    fn is_dynamically_sized(
+        &mut self,
+        stmt: &StmtContext,
+        expr: Handle<HirExpr>,
+        meta: Span,
+    ) -> Result<bool> {
+        match stmt.hir_exprs[expr].kind {
+            HirExprKind::Variable(ref var) => match *self.resolve_type(var.expr, meta)? {
+                TypeInner::Pointer { base, .. } => Ok(self.module.types[base]
+                    .inner
+                    .is_dynamically_sized(&self.module.types)),
+                _ => Ok(false),
+            },
+            HirExprKind::Select { base, .. } => self.is_dynamically_sized(stmt, base, meta),
+            _ => Ok(false),
+        }
+    }
+
     /// internal implementation of [`lower_expect`](Self::lower_expect)
     ///
     /// this method is only public because it's used in
@@ -579,7 +599,14 @@ impl<'a> Context<'a> {
                 pointer
             }
             HirExprKind::Select { base, ref field } => {
-                let base = self.lower_expect_inner(stmt, frontend, base, pos)?.0;
+                // Buffer blocks with runtime sized arrays can't be loaded as
+                // a whole, so only the selected member is loaded.
+                let base_pos = if self.is_dynamically_sized(stmt, base, meta)? {
+                    pos.maybe_access_base(true)
+                } else {
+                    pos
+                };
+                let base = self.lower_expect_inner(stmt, frontend, base, base_pos)?.0;
 
                 frontend.field_selection(self, pos, base, field, meta)?
             }
@@ -1288,8 +1315,22 @@ impl<'a> Context<'a> {
                                 meta,
                             });
                         }
-                        let lowered_array = self.lower_expect_inner(stmt, frontend, object, pos)?.0;
-                        let array_type = self.resolve_type(lowered_array, meta)?;
+                        // Runtime sized arrays are lowered as an access base so that
+                        // they aren't loaded, `ArrayLength` needs a pointer to them.
+                        let array_pos = if self.is_dynamically_sized(stmt, object, meta)? {
+                            ExprPos::AccessBase {
+                                constant_index: true,
+                            }
+                        } else {
+                            pos
+                        };
+                        let lowered_array = self
+                            .lower_expect_inner(stmt, frontend, object, array_pos)?
+                            .0;
+                        let array_type = match *self.resolve_type(lowered_array, meta)? {
+                            TypeInner::Pointer { base, .. } => &self.module.types[base].inner,
+                            ref inner => inner,
+                        };
 
                         match *array_type {
                             TypeInner::Array {
@@ -1,8 +1,8 @@
-use super::token::TokenValue;
+use super::{token::TokenValue, SourceFiles};
 use crate::SourceLocation;
 use crate::{proc::ConstantEvaluatorError, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFile;
+use codespan_reporting::files::{SimpleFile, SimpleFiles};
 use codespan_reporting::term;
 use pp_rs::token::PreprocessorError;
 use std::borrow::Cow;
@@ -111,6 +111,14 @@ pub enum ErrorKind {
     /// A semantic error was detected in the shader.
     #[error("{0}")]
     SemanticError(Cow<'static, str>),
+    /// An `#include` directive couldn't be resolved.
+    #[error("Cannot include \"{path}\": {reason}")]
+    IncludeError {
+        /// The path given to the directive.
+        path: String,
+        /// The error returned by the [`IncludeResolver`](super::IncludeResolver).
+        reason: String,
+    },
     /// An error was returned by the preprocessor.
     #[error("{0:?}")]
     PreprocessorError(PreprocessorError),
@@ -173,6 +181,29 @@ impl ParseErrors {
         }
     }
 
+    /// Writes the errors for a shader parsed with
+    /// [`Frontend::parse_with_includes`], pointing into the files the
+    /// errors came from.
+    ///
+    /// [`Frontend::parse_with_includes`]: super::Frontend::parse_with_includes
+    pub fn emit_to_writer_with_files(&self, writer: &mut impl WriteColor, files: &SourceFiles) {
+        let mut simple_files = SimpleFiles::new();
+        for file in files.files() {
+            simple_files.add(file.name.as_str(), file.source.as_str());
+        }
+        let config = term::Config::default();
+
+        for err in &self.errors {
+            let mut diagnostic = Diagnostic::error().with_message(err.kind.to_string());
+
+            if let Some((file, range)) = files.locate(err.meta) {
+                diagnostic = diagnostic.with_labels(vec![Label::primary(file, range)]);
+            }
+
+            term::emit(writer, &config, &simple_files, &diagnostic).expect("cannot write error");
+        }
+    }
+
     pub fn emit_to_string(&self, source: &str) -> String {
         let mut writer = NoColor::new(Vec::new());
         self.emit_to_writer(&mut writer, source);
@@ -1031,6 +1031,15 @@ impl Frontend {
         result: Option<FunctionResult>,
         meta: Span,
     ) {
+        if let Err(meta) = check_memory_barriers(&ctx.body, &ctx.module.functions, false) {
+            self.errors.push(Error {
+                kind: ErrorKind::SemanticError(
+                    "Memory barriers can only be used in uniform control flow".into(),
+                ),
+                meta,
+            });
+        }
+
         ensure_block_returns(&mut ctx.body);
 
         let void = result.is_none();
@@ -1615,3 +1624,93 @@ fn builtin_required_variations<'a>(args: impl Iterator<Item = &'a TypeInner>) ->
 
     variations
 }
+
+/// Return true if `barrier` came from a GLSL memory barrier built-in, like
+/// `memoryBarrierShared`, rather than from `barrier`.
+fn is_memory_barrier(barrier: crate::Barrier) -> bool {
+    barrier != crate::Barrier::all()
+}
+
+/// Check that `block` only reaches memory barriers in uniform control flow.
+///
+/// Naga IR has no memory-only barrier, so memory barrier built-ins become
+/// [`Statement::Barrier`]s, which also synchronize execution, and hang if
+/// only some invocations reach them. Rather than analyzing uniformity, reject
+/// memory barriers, and calls to functions that use them, inside branches and
+/// loops or after an early `return` or `discard`. A memory barrier followed
+/// directly by `barrier()` is merged into it, so that pair is still allowed.
+///
+/// If `divergent` is true, `block` may not be reached by all invocations.
+/// On success, return true if `block` may return or discard.
+fn check_memory_barriers(
+    block: &Block,
+    functions: &crate::Arena<Function>,
+    mut divergent: bool,
+) -> std::result::Result<bool, Span> {
+    let mut exits = false;
+    for (statement, &span) in block.span_iter() {
+        match *statement {
+            Statement::Barrier(barrier) if divergent && is_memory_barrier(barrier) => {
+                return Err(span)
+            }
+            Statement::Call { function, .. }
+                if divergent && uses_memory_barrier(&functions[function].body, functions) =>
+            {
+                return Err(span)
+            }
+            Statement::Block(ref block) => {
+                exits |= check_memory_barriers(block, functions, divergent)?;
+            }
+            Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                exits |= check_memory_barriers(accept, functions, true)?;
+                exits |= check_memory_barriers(reject, functions, true)?;
+            }
+            Statement::Switch { ref cases, .. } => {
+                for case in cases {
+                    exits |= check_memory_barriers(&case.body, functions, true)?;
+                }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                exits |= check_memory_barriers(body, functions, true)?;
+                exits |= check_memory_barriers(continuing, functions, true)?;
+            }
+            Statement::Return { .. } | Statement::Kill => exits = true,
+            _ => {}
+        }
+        divergent |= exits;
+    }
+    Ok(exits)
+}
+
+/// Return true if `block`, or any function it calls, uses a memory barrier.
+fn uses_memory_barrier(block: &Block, functions: &crate::Arena<Function>) -> bool {
+    block.iter().any(|statement| match *statement {
+        Statement::Barrier(barrier) => is_memory_barrier(barrier),
+        Statement::Call { function, .. } => {
+            uses_memory_barrier(&functions[function].body, functions)
+        }
+        Statement::Block(ref block) => uses_memory_barrier(block, functions),
+        Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => uses_memory_barrier(accept, functions) || uses_memory_barrier(reject, functions),
+        Statement::Switch { ref cases, .. } => cases
+            .iter()
+            .any(|case| uses_memory_barrier(&case.body, functions)),
+        Statement::Loop {
+            ref body,
+            ref continuing,
+            ..
+        } => uses_memory_barrier(body, functions) || uses_memory_barrier(continuing, functions),
+        _ => false,
+    })
+}
@@ -0,0 +1,362 @@
+/*!
+Expansion of `#include` directives.
+
+The preprocessor doesn't know about `#include`, so the directives of the
+[`GL_GOOGLE_include_directive`] extension are expanded before the source is
+parsed: every `#include "path"` or `#include <path>` line is replaced by the
+text of the file the [`IncludeResolver`] returns for it.
+
+Includes are resolved lazily. The preprocessor runs over the source until it
+reaches an `#include` directive outside of the blocks it skips, that
+directive is replaced by the included file, and the preprocessor starts over.
+This way `#if` blocks, include guards and macros defined by included files
+all work as they do for the rest of the source. A file that contains a
+`#pragma once` the preprocessor has reached isn't included again.
+
+The spans in the resulting [`Module`] are byte ranges in the expanded
+source, which [`SourceFiles`] can map back to the file they came from.
+
+[`GL_GOOGLE_include_directive`]: https://github.com/KhronosGroup/GLSL/blob/main/extensions/google/GL_GOOGLE_include_directive.txt
+[`Module`]: crate::Module
+*/
+
+use super::{
+    lex::{Lexer, LexerResultKind},
+    token::DirectiveKind,
+    Error, ErrorKind,
+};
+use crate::{FastHashMap, FastHashSet, Span};
+use pp_rs::token::{PreprocessorError, TokenValue as PPTokenValue};
+use std::ops::Range;
+
+/// The maximum nesting depth of `#include` directives.
+///
+/// This catches recursive includes that have no include guard.
+const MAX_INCLUDE_DEPTH: usize = 64;
+
+/// The kind of path given to an `#include` directive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IncludeKind {
+    /// `#include "path"`, a path relative to the including file.
+    Relative,
+    /// `#include <path>`, a path to be looked up in the search paths.
+    Standard,
+}
+
+/// A file returned by an [`IncludeResolver`].
+#[derive(Clone, Debug)]
+pub struct IncludedSource {
+    /// The name of the file.
+    ///
+    /// This is used in error messages, is passed back to the resolver as the
+    /// includer of the file's own `#include` directives, and identifies the
+    /// file for `#pragma once`.
+    pub name: String,
+    /// The text of the file.
+    pub source: String,
+}
+
+/// Resolves the files named by `#include` directives.
+///
+/// Resolvers are passed to [`Frontend::parse_with_includes`].
+///
+/// [`Frontend::parse_with_includes`]: super::Frontend::parse_with_includes
+pub trait IncludeResolver {
+    /// Returns the file named by `path` in an `#include` directive of the
+    /// file named `includer`.
+    ///
+    /// The error is reported as the reason the directive failed.
+    fn resolve(
+        &mut self,
+        path: &str,
+        kind: IncludeKind,
+        includer: &str,
+    ) -> Result<IncludedSource, String>;
+}
+
+/// Resolves includes from a map of file names to their text.
+///
+/// Both kinds of paths are looked up in the map as they are.
+impl IncludeResolver for FastHashMap<String, String> {
+    fn resolve(
+        &mut self,
+        path: &str,
+        _kind: IncludeKind,
+        _includer: &str,
+    ) -> Result<IncludedSource, String> {
+        let source = self.get(path).ok_or("file not found")?;
+        Ok(IncludedSource {
+            name: path.to_string(),
+            source: source.clone(),
+        })
+    }
+}
+
+/// A file that contributed to the source of a shader.
+#[derive(Clone, Debug)]
+pub struct SourceFile {
+    /// The name of the file, as returned by the [`IncludeResolver`].
+    pub name: String,
+    /// The text of the file.
+    pub source: String,
+}
+
+/// A run of the expanded source copied from one file.
+#[derive(Clone, Debug)]
+struct Segment {
+    /// The start of the run in the expanded source.
+    start: usize,
+    /// The index of the file in [`SourceFiles::files`].
+    file: usize,
+    /// The start of the run in the file.
+    file_start: usize,
+}
+
+/// The source of a shader with its `#include` directives expanded.
+///
+/// This is returned by [`Frontend::source_files`].
+///
+/// [`Frontend::source_files`]: super::Frontend::source_files
+#[derive(Clone, Debug, Default)]
+pub struct SourceFiles {
+    expanded: String,
+    files: Vec<SourceFile>,
+    segments: Vec<Segment>,
+}
+
+impl SourceFiles {
+    /// Returns the expanded source, which the spans of the module refer to.
+    pub fn expanded_source(&self) -> &str {
+        &self.expanded
+    }
+
+    /// Returns the files that were included, starting with the root file.
+    pub fn files(&self) -> &[SourceFile] {
+        &self.files
+    }
+
+    /// Maps `span`, a range of the expanded source, to the file it came from.
+    ///
+    /// Returns the index of the file in [`files`] and the range of the file.
+    /// Spans that cross into another file are cut short at the end of the
+    /// first one.
+    ///
+    /// [`files`]: SourceFiles::files
+    pub fn locate(&self, span: Span) -> Option<(usize, Range<usize>)> {
+        let range = span.to_range()?;
+        let index = self
+            .segments
+            .partition_point(|segment| segment.start <= range.start)
+            .checked_sub(1)?;
+        let segment = &self.segments[index];
+        let segment_end = self
+            .segments
+            .get(index + 1)
+            .map_or(self.expanded.len(), |next| next.start);
+        let end = range.end.min(segment_end).max(range.start);
+        Some((
+            segment.file,
+            segment.file_start + range.start - segment.start
+                ..segment.file_start + end - segment.start,
+        ))
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.expanded.clear();
+        self.files.clear();
+        self.segments.clear();
+    }
+
+    /// Replaces `range` of the expanded source, the line of an `#include`
+    /// directive, by the text of the file at `file`, or by nothing if `None`.
+    fn splice(&mut self, range: Range<usize>, file: Option<usize>) {
+        let text = match file {
+            Some(file) => self.files[file].source.as_str(),
+            None => "",
+        };
+        // The directive is on a single line, so it's within one segment
+        let index = self
+            .segments
+            .partition_point(|segment| segment.start <= range.start)
+            - 1;
+        let segment = self.segments[index].clone();
+        let segment_end = self
+            .segments
+            .get(index + 1)
+            .map_or(self.expanded.len(), |next| next.start);
+
+        let mut replacement = Vec::with_capacity(3);
+        if segment.start < range.start {
+            replacement.push(segment.clone());
+        }
+        if let (Some(file), false) = (file, text.is_empty()) {
+            replacement.push(Segment {
+                start: range.start,
+                file,
+                file_start: 0,
+            });
+        }
+        if range.end < segment_end {
+            replacement.push(Segment {
+                start: range.start + text.len(),
+                file: segment.file,
+                file_start: segment.file_start + range.end - segment.start,
+            });
+        }
+        for next in self.segments[index + 1..].iter_mut() {
+            next.start = next.start - range.len() + text.len();
+        }
+        self.segments.splice(index..index + 1, replacement);
+        self.expanded.replace_range(range, text);
+    }
+
+    /// Expands `source`, the text of the file `name`, into a new set of files.
+    ///
+    /// Errors are returned for the directives that couldn't be resolved,
+    /// which are left in the expanded source. `defines` are the macros
+    /// defined before the source.
+    pub(super) fn expand(
+        &mut self,
+        name: &str,
+        source: &str,
+        defines: &FastHashMap<String, String>,
+        resolver: &mut dyn IncludeResolver,
+        errors: &mut Vec<Error>,
+    ) {
+        self.clear();
+        self.files.push(SourceFile {
+            name: name.to_string(),
+            source: source.to_string(),
+        });
+        self.expanded.push_str(source);
+        if !source.is_empty() {
+            self.segments.push(Segment {
+                start: 0,
+                file: 0,
+                file_start: 0,
+            });
+        }
+
+        // The nesting depth of each file
+        let mut depths = vec![0];
+        let mut once = FastHashSet::default();
+        // Everything before `resume` has already been expanded
+        let mut resume = 0;
+
+        while let Some(line) = self.next_include(resume, defines, &mut once) {
+            let Some((path, kind, path_range)) = parse_include(&self.expanded[line.clone()]) else {
+                // Not something we can expand, leave it to the parser.
+                resume = line.end;
+                continue;
+            };
+            let path = path.to_string();
+            let includer = self
+                .locate(Span::from(line.clone()))
+                .map_or(0, |(file, _)| file);
+            let depth = depths[includer] + 1;
+            let included = match depth <= MAX_INCLUDE_DEPTH {
+                true => resolver.resolve(&path, kind, &self.files[includer].name),
+                false => Err("too many nested includes".to_string()),
+            };
+
+            match included {
+                Ok(included) if once.contains(&included.name) => {
+                    self.splice(line.clone(), None);
+                    resume = line.start;
+                }
+                Ok(included) => {
+                    let file = self.files.len();
+                    self.files.push(SourceFile {
+                        name: included.name,
+                        source: included.source,
+                    });
+                    depths.push(depth);
+                    self.splice(line.clone(), Some(file));
+                    // The included file's own directives come next
+                    resume = line.start;
+                }
+                Err(reason) => {
+                    // Keep the directive so that the error points into the
+                    // expanded source, parsing stops after expansion anyway.
+                    let start = line.start + path_range.start;
+                    errors.push(Error {
+                        kind: ErrorKind::IncludeError { path, reason },
+                        meta: Span::from(start..start + path_range.len()),
+                    });
+                    resume = line.end;
+                }
+            }
+        }
+
+        if !self.expanded.is_empty() && !self.expanded.ends_with('\n') {
+            self.expanded.push('\n');
+        }
+    }
+
+    /// Runs the preprocessor over the expanded source until it reaches an
+    /// `#include` directive at or after `resume`, and returns the range of
+    /// the directive's line.
+    ///
+    /// The names of the files whose `#pragma once` is reached are added to
+    /// `once`.
+    fn next_include(
+        &self,
+        resume: usize,
+        defines: &FastHashMap<String, String>,
+        once: &mut FastHashSet<String>,
+    ) -> Option<Range<usize>> {
+        for result in Lexer::new(&self.expanded, defines) {
+            let Some(range) = result.meta.to_range() else {
+                continue;
+            };
+            match result.kind {
+                // The preprocessor reports the name of directives it doesn't
+                // know, only when it isn't skipping them.
+                LexerResultKind::Error(PreprocessorError::UnknownDirective)
+                    if range.start >= resume && &self.expanded[range.clone()] == "include" =>
+                {
+                    let start = self.expanded[..range.start]
+                        .rfind('\n')
+                        .map_or(0, |i| i + 1);
+                    let end = self.expanded[range.end..]
+                        .find('\n')
+                        .map_or(self.expanded.len(), |i| range.end + i);
+                    return Some(start..end);
+                }
+                LexerResultKind::Directive(ref directive)
+                    if matches!(directive.kind, DirectiveKind::Pragma) =>
+                {
+                    if let [ref token] = directive.tokens[..] {
+                        if matches!(token.value, PPTokenValue::Ident(ref name) if name == "once") {
+                            if let Some((file, _)) = self.locate(result.meta) {
+                                once.insert(self.files[file].name.clone());
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// Parses an `#include` directive, returning the path, its kind, and the
+/// range of the path within `line`.
+fn parse_include(line: &str) -> Option<(&str, IncludeKind, Range<usize>)> {
+    let trimmed = line.trim_start().strip_prefix('#')?.trim_start();
+    let rest = trimmed.strip_prefix("include")?;
+    if !rest.starts_with(char::is_whitespace) && !rest.starts_with(['"', '<']) {
+        return None;
+    }
+    let rest = rest.trim_start();
+    let (kind, close) = match rest.chars().next()? {
+        '"' => (IncludeKind::Relative, '"'),
+        '<' => (IncludeKind::Standard, '>'),
+        _ => return None,
+    };
+    let path_len = rest[1..].find(close)?;
+    let path = &rest[1..1 + path_len];
+    let start = line.len() - rest.len() + 1;
+    Some((path, kind, start..start + path_len))
+}
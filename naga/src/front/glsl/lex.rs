@@ -85,6 +85,8 @@ impl Iterator for Lexer<'_> {
                     "mediump" => TokenValue::PrecisionQualifier(Precision::Medium),
                     "lowp" => TokenValue::PrecisionQualifier(Precision::Low),
                     "restrict" => TokenValue::Restrict,
+                    "coherent" => TokenValue::Coherent,
+                    "volatile" => TokenValue::Volatile,
                     "readonly" => TokenValue::MemoryQualifier(StorageAccess::LOAD),
                     "writeonly" => TokenValue::MemoryQualifier(StorageAccess::STORE),
                     // values
@@ -14,6 +14,7 @@ To begin, take a look at the documentation for the [`Frontend`].
 
 pub use ast::{Precision, Profile};
 pub use error::{Error, ErrorKind, ExpectedToken, ParseErrors};
+pub use include::{IncludeKind, IncludeResolver, IncludedSource, SourceFile, SourceFiles};
 pub use token::TokenValue;
 
 use crate::{proc::Layouter, FastHashMap, FastHashSet, Handle, Module, ShaderStage, Span, Type};
@@ -25,6 +26,7 @@ mod builtins;
 mod context;
 mod error;
 mod functions;
+mod include;
 mod lex;
 mod offset;
 mod parser;
@@ -175,6 +177,8 @@ pub struct Frontend {
     layouter: Layouter,
 
     errors: Vec<Error>,
+
+    source_files: SourceFiles,
 }
 
 impl Frontend {
@@ -186,6 +190,7 @@ impl Frontend {
         self.global_variables.clear();
         self.entry_args.clear();
         self.layouter.clear();
+        self.source_files.clear();
     }
 
     /// Parses a shader either outputting a shader [`Module`] or a list of
@@ -198,7 +203,44 @@ impl Frontend {
         source: &str,
     ) -> std::result::Result<Module, ParseErrors> {
         self.reset(options.stage);
+        self.parse_source(options, source)
+    }
+
+    /// Parses a shader whose `#include` directives are resolved by `resolver`.
+    ///
+    /// `name` is the name of the shader's file, which is passed to `resolver`
+    /// as the includer of the shader's own `#include` directives.
+    ///
+    /// The spans in the returned [`Module`] or [`Error`]s refer to the
+    /// source with its `#include` directives expanded, which is available
+    /// from [`source_files`] along with the files it came from.
+    ///
+    /// [`source_files`]: Frontend::source_files
+    pub fn parse_with_includes(
+        &mut self,
+        options: &Options,
+        source: &str,
+        name: &str,
+        resolver: &mut dyn IncludeResolver,
+    ) -> std::result::Result<Module, ParseErrors> {
+        self.reset(options.stage);
 
+        let mut source_files = std::mem::take(&mut self.source_files);
+        source_files.expand(name, source, &options.defines, resolver, &mut self.errors);
+        let result = if self.errors.is_empty() {
+            self.parse_source(options, source_files.expanded_source())
+        } else {
+            Err(std::mem::take(&mut self.errors).into())
+        };
+        self.source_files = source_files;
+        result
+    }
+
+    fn parse_source(
+        &mut self,
+        options: &Options,
+        source: &str,
+    ) -> std::result::Result<Module, ParseErrors> {
         let lexer = lex::Lexer::new(source, &options.defines);
         let mut ctx = ParsingContext::new(lexer);
 
@@ -229,4 +271,14 @@ impl Frontend {
     pub const fn metadata(&self) -> &ShaderMetadata {
         &self.meta
     }
+
+    /// Returns the source of the shader parsed by the last call to
+    /// [`parse_with_includes`], with its `#include` directives expanded.
+    ///
+    /// After a call to [`parse`](Frontend::parse), this is empty.
+    ///
+    /// [`parse_with_includes`]: Frontend::parse_with_includes
+    pub const fn source_files(&self) -> &SourceFiles {
+        &self.source_files
+    }
 }
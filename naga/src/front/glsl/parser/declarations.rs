@@ -9,12 +9,12 @@ use crate::{
         offset,
         token::{Token, TokenValue},
         types::scalar_components,
-        variables::{GlobalOrConstant, VarDeclaration},
+        variables::{unreadable_buffer_error, GlobalOrConstant, VarDeclaration},
         Error, ErrorKind, Frontend, Span,
     },
     proc::Alignment,
-    AddressSpace, Expression, FunctionResult, Handle, Scalar, ScalarKind, Statement, StructMember,
-    Type, TypeInner,
+    AddressSpace, Expression, FunctionResult, Handle, Scalar, ScalarKind, Statement, StorageAccess,
+    StructMember, Type, TypeInner,
 };
 
 use super::{DeclarationContext, ParsingContext, Result};
@@ -432,21 +432,35 @@ impl ParsingContext<'_> {
                             )
                             .map(Some)
                         } else {
-                            if qualifiers.invariant.take().is_some() {
-                                frontend.make_variable_invariant(ctx, &ty_name, token.meta)?;
+                            // Redeclarations of existing variables
+                            // type_qualifier IDENTIFIER SEMICOLON
+                            // type_qualifier IDENTIFIER identifier_list SEMICOLON
+                            let mut names = vec![(ty_name, token.meta)];
+                            while self.bump_if(frontend, TokenValue::Comma).is_some() {
+                                names.push(self.expect_ident(frontend)?);
+                            }
+                            self.expect(frontend, TokenValue::Semicolon)?;
 
-                                qualifiers.unused_errors(&mut frontend.errors);
-                                self.expect(frontend, TokenValue::Semicolon)?;
-                                return Ok(Some(qualifiers.span));
+                            let invariant = qualifiers.invariant.take().is_some();
+                            let access = qualifiers.storage_access.take();
+                            if !invariant && access.is_none() {
+                                return Err(Error {
+                                    kind: ErrorKind::NotImplemented("variable qualifier"),
+                                    meta: token.meta,
+                                });
                             }
 
-                            //TODO: declaration
-                            // type_qualifier IDENTIFIER SEMICOLON
-                            // type_qualifier IDENTIFIER identifier_list SEMICOLON
-                            Err(Error {
-                                kind: ErrorKind::NotImplemented("variable qualifier"),
-                                meta: token.meta,
-                            })
+                            for (name, meta) in names {
+                                if invariant {
+                                    frontend.make_variable_invariant(ctx, &name, meta)?;
+                                }
+                                if let Some((access, _)) = access {
+                                    frontend.add_memory_qualifiers(ctx, &name, access, meta)?;
+                                }
+                            }
+
+                            qualifiers.unused_errors(&mut frontend.errors);
+                            Ok(Some(qualifiers.span))
                         }
                     }
                     TokenValue::Semicolon => {
@@ -552,10 +566,33 @@ impl ParsingContext<'_> {
             _ => unreachable!(),
         };
 
+        // Memory qualifiers are only allowed on the members of buffer blocks
+        let mut members_access = match qualifiers.storage.0 {
+            StorageQualifier::AddressSpace(AddressSpace::Storage { .. }) => {
+                Some(StorageAccess::empty())
+            }
+            _ => None,
+        };
+
         let mut members = Vec::new();
-        let span = self.parse_struct_declaration_list(frontend, ctx, &mut members, layout)?;
+        let span = self.parse_struct_declaration_list(
+            frontend,
+            ctx,
+            &mut members,
+            layout,
+            members_access.as_mut(),
+        )?;
         self.expect(frontend, TokenValue::RightBrace)?;
 
+        // Naga can't restrict the access of individual members, so the block
+        // gets all the access needed by any of its members.
+        if let Some(members_access) = members_access {
+            let storage_access = qualifiers
+                .storage_access
+                .get_or_insert((StorageAccess::LOAD | StorageAccess::STORE, Span::default()));
+            storage_access.0 &= members_access;
+        }
+
         let mut ty = ctx.module.types.insert(
             Type {
                 name: Some(ty_name),
@@ -619,6 +656,10 @@ impl ParsingContext<'_> {
         Ok(meta)
     }
 
+    /// Parses the members of a struct or block.
+    ///
+    /// If `members_access` is `Some`, memory qualifiers are allowed on the
+    /// members and the storage access needed by each member is added to it.
     // TODO: Accept layout arguments
     pub fn parse_struct_declaration_list(
         &mut self,
@@ -626,12 +667,28 @@ impl ParsingContext<'_> {
         ctx: &mut Context,
         members: &mut Vec<StructMember>,
         layout: StructLayout,
+        mut members_access: Option<&mut StorageAccess>,
     ) -> Result<u32> {
         let mut span = 0;
         let mut align = Alignment::ONE;
 
         loop {
-            // TODO: type_qualifier
+            let mut qualifiers = self.parse_type_qualifiers(frontend, ctx)?;
+            if let Some(ref mut members_access) = members_access {
+                let access = match qualifiers.storage_access.take() {
+                    Some((access, access_meta)) => {
+                        if !access.contains(StorageAccess::LOAD) {
+                            frontend.errors.push(unreadable_buffer_error(access_meta));
+                        }
+                        access
+                    }
+                    None => StorageAccess::LOAD | StorageAccess::STORE,
+                };
+                **members_access |= access;
+            }
+            // Precision qualifiers have no effect on the member types
+            qualifiers.precision.take();
+            qualifiers.unused_errors(&mut frontend.errors);
 
             let (base_ty, mut meta) = self.parse_type_non_void(frontend, ctx)?;
 
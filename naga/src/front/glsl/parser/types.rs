@@ -90,6 +90,7 @@ impl ParsingContext<'_> {
                     ctx,
                     &mut members,
                     StructLayout::Std140,
+                    None,
                 )?;
                 let end_meta = self.expect(frontend, TokenValue::RightBrace)?.meta;
                 meta.subsume(end_meta);
@@ -159,6 +160,8 @@ impl ParsingContext<'_> {
             | TokenValue::Shared
             | TokenValue::Buffer
             | TokenValue::Restrict
+            | TokenValue::Coherent
+            | TokenValue::Volatile
             | TokenValue::MemoryQualifier(_)
             | TokenValue::Layout => true,
             _ => false,
@@ -291,7 +294,9 @@ impl ParsingContext<'_> {
                     storage_access.0 &= access;
                     storage_access.1.subsume(token.meta);
                 }
-                TokenValue::Restrict => continue,
+                // Naga doesn't represent aliasing or coherence hints, so
+                // these qualifiers are accepted and ignored.
+                TokenValue::Restrict | TokenValue::Coherent | TokenValue::Volatile => continue,
                 _ => unreachable!(),
             };
         }
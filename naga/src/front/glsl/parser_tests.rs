@@ -328,6 +328,213 @@ fn declarations() {
         "#,
         )
         .unwrap();
+
+    frontend
+        .parse(
+            &Options::from(ShaderStage::Compute),
+            r#"
+        #version 450
+        layout(local_size_x = 1) in;
+        layout(std430, set = 0, binding = 0)
+        coherent restrict buffer u_data {
+            readonly uint count;
+            float data[];
+        } u_data_inst;
+
+        layout(std430, set = 0, binding = 1) buffer u_other {
+            float other[];
+        } u_other_inst;
+
+        readonly u_data_inst, u_other_inst;
+
+        void main() {
+            float x = u_data_inst.data[u_data_inst.count] + u_other_inst.other[0];
+        }
+        "#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Compute),
+                r#"
+        #version 450
+        layout(local_size_x = 1) in;
+        layout(std430, set = 0, binding = 0)
+        writeonly buffer u_data {
+            float data[];
+        };
+
+        void main() {}
+        "#,
+            )
+            .unwrap_err(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "Buffers and their members can't be `writeonly`".into()
+                ),
+                meta: Span::new(112, 121),
+            }]
+        },
+    );
+
+    assert_eq!(
+        frontend
+            .parse(
+                &Options::from(ShaderStage::Vertex),
+                r#"
+        #version 450
+        layout(std140, set = 2, binding = 0)
+        uniform u_locals {
+            readonly float load_time;
+        };
+
+        void main() {}
+        "#,
+            )
+            .unwrap_err(),
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::SemanticError(
+                    "Memory qualifiers can only be used in storage variables".into()
+                ),
+                meta: Span::new(106, 114),
+            }]
+        },
+    );
+}
+
+#[test]
+fn memory_barriers() {
+    fn count_barriers(block: &crate::Block) -> usize {
+        block
+            .iter()
+            .map(|statement| match *statement {
+                crate::Statement::Barrier(_) => 1,
+                crate::Statement::Block(ref block) => count_barriers(block),
+                crate::Statement::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => count_barriers(accept) + count_barriers(reject),
+                crate::Statement::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => count_barriers(body) + count_barriers(continuing),
+                _ => 0,
+            })
+            .sum()
+    }
+    let count_module_barriers = |module: &crate::Module| -> usize {
+        module
+            .functions
+            .iter()
+            .map(|(_, function)| count_barriers(&function.body))
+            .sum()
+    };
+    let mut frontend = Frontend::default();
+
+    let uniform = frontend
+        .parse(
+            &Options::from(ShaderStage::Compute),
+            r#"
+        #version 450
+        layout(local_size_x = 64) in;
+        shared float scratch[64];
+
+        void main() {
+            memoryBarrierBuffer();
+            for (uint s = 32u; s > 0u; s >>= 1u) {
+                if (gl_LocalInvocationID.x < s) {
+                    scratch[gl_LocalInvocationID.x] += scratch[gl_LocalInvocationID.x + s];
+                }
+                memoryBarrierShared();
+                barrier();
+            }
+        }
+        "#,
+        )
+        .unwrap();
+    // `memoryBarrierShared()` is merged into the `barrier()` after it.
+    assert_eq!(count_module_barriers(&uniform), 2);
+
+    // Memory barriers become control barriers, which would hang in divergent
+    // control flow, so they're rejected there.
+    let divergent = [
+        r#"
+        #version 450
+        layout(local_size_x = 64) in;
+
+        void main() {
+            if (gl_LocalInvocationID.x == 0u) {
+                memoryBarrierShared();
+            }
+        }
+        "#,
+        r#"
+        #version 450
+        layout(local_size_x = 64) in;
+        layout(std430, set = 0, binding = 0) buffer Data { uint data[]; };
+
+        void main() {
+            uint i = gl_GlobalInvocationID.x;
+            if (i >= 100u) return;
+            data[i] = i;
+            memoryBarrierBuffer();
+        }
+        "#,
+        r#"
+        #version 450
+        layout(local_size_x = 64) in;
+
+        void sync() {
+            groupMemoryBarrier();
+        }
+
+        void main() {
+            for (uint i = 0u; i < gl_LocalInvocationID.x; i++) {
+                sync();
+            }
+        }
+        "#,
+    ];
+    for source in divergent {
+        let errors = frontend
+            .parse(&Options::from(ShaderStage::Compute), source)
+            .unwrap_err()
+            .errors;
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].kind,
+            ErrorKind::SemanticError(
+                "Memory barriers can only be used in uniform control flow".into()
+            )
+        );
+    }
+
+    // A function with a memory barrier can be called in uniform control
+    // flow, and keeps its barrier.
+    let called = frontend
+        .parse(
+            &Options::from(ShaderStage::Compute),
+            r#"
+        #version 450
+        layout(local_size_x = 64) in;
+
+        void sync() {
+            groupMemoryBarrier();
+        }
+
+        void main() {
+            sync();
+        }
+        "#,
+        )
+        .unwrap();
+    assert_eq!(count_module_barriers(&called), 1);
 }
 
 #[test]
@@ -854,3 +1061,145 @@ fn expressions() {
         )
         .unwrap();
 }
+
+#[test]
+fn includes() {
+    let mut frontend = Frontend::default();
+    let mut files = crate::FastHashMap::default();
+    files.insert(
+        "common.glsl".to_string(),
+        r#"#ifndef COMMON_GLSL
+#define COMMON_GLSL
+#include "common.glsl"
+float twice(float x) { return x * 2.0; }
+#endif
+"#
+        .to_string(),
+    );
+    files.insert(
+        "broken.glsl".to_string(),
+        "float broken() { return missing; }".to_string(),
+    );
+    files.insert(
+        "once.glsl".to_string(),
+        "#pragma once\n#define HAS_ONCE\nfloat once() { return 1.0; }\n".to_string(),
+    );
+
+    frontend
+        .parse_with_includes(
+            &Options::from(ShaderStage::Vertex),
+            r#"
+        #version 450
+        #extension GL_GOOGLE_include_directive : require
+        /*
+        #include "commented-out.glsl"
+        */
+        #include "common.glsl"
+        #include <common.glsl>
+
+        void main() {
+            gl_Position = vec4(twice(1.0));
+        }
+        "#,
+            "main.vert",
+            &mut files,
+        )
+        .unwrap();
+    let source_files = frontend.source_files();
+    assert_eq!(source_files.files().len(), 4);
+    assert_eq!(source_files.files()[1].name, "common.glsl");
+
+    // Includes in blocks the preprocessor skips aren't resolved
+    let mut options = Options::from(ShaderStage::Vertex);
+    options
+        .defines
+        .insert("COMMON".to_string(), "2".to_string());
+    frontend
+        .parse_with_includes(
+            &options,
+            r#"
+        #version 450
+        #if 0
+        #include "missing.glsl"
+        #endif
+        #ifdef FEATURE
+        #include "missing.glsl"
+        #elif defined(COMMON) && COMMON > 1
+        #include "common.glsl"
+        #else
+        #include "missing.glsl"
+        #endif
+
+        void main() {
+            gl_Position = vec4(twice(1.0));
+        }
+        "#,
+            "main.vert",
+            &mut files,
+        )
+        .unwrap();
+    assert_eq!(frontend.source_files().files().len(), 3);
+
+    // Files with `#pragma once` are only included once, and macros they
+    // define decide the includes that follow
+    frontend
+        .parse_with_includes(
+            &Options::from(ShaderStage::Vertex),
+            r#"
+        #version 450
+        #include "once.glsl"
+        #include "once.glsl"
+        #ifndef HAS_ONCE
+        #include "missing.glsl"
+        #endif
+
+        void main() {
+            gl_Position = vec4(once());
+        }
+        "#,
+            "main.vert",
+            &mut files,
+        )
+        .unwrap();
+    let source_files = frontend.source_files();
+    assert_eq!(source_files.files().len(), 2);
+    assert_eq!(source_files.files()[1].name, "once.glsl");
+
+    let errors = frontend
+        .parse_with_includes(
+            &Options::from(ShaderStage::Vertex),
+            "#version 450\n#include \"broken.glsl\"\nvoid main() {}\n",
+            "main.vert",
+            &mut files,
+        )
+        .unwrap_err();
+    let source_files = frontend.source_files();
+    assert_eq!(
+        errors.errors[0].kind,
+        ErrorKind::UnknownVariable("missing".into())
+    );
+    let (file, range) = source_files.locate(errors.errors[0].meta).unwrap();
+    assert_eq!(source_files.files()[file].name, "broken.glsl");
+    assert_eq!(range, 24..31);
+
+    let errors = frontend
+        .parse_with_includes(
+            &Options::from(ShaderStage::Vertex),
+            "#version 450\n#include <missing.glsl>\nvoid main() {}\n",
+            "main.vert",
+            &mut files,
+        )
+        .unwrap_err();
+    assert_eq!(
+        errors,
+        ParseErrors {
+            errors: vec![Error {
+                kind: ErrorKind::IncludeError {
+                    path: "missing.glsl".into(),
+                    reason: "file not found".into(),
+                },
+                meta: Span::new(23, 35),
+            }]
+        },
+    );
+}
@@ -38,6 +38,8 @@ pub enum TokenValue {
     Shared,
 
     Restrict,
+    Coherent,
+    Volatile,
     /// A `glsl` memory qualifier such as `writeonly`
     ///
     /// The associated [`crate::StorageAccess`] is the access being allowed
@@ -6,8 +6,8 @@ use super::{
 };
 use crate::{
     AddressSpace, Binding, BuiltIn, Constant, Expression, GlobalVariable, Handle, Interpolation,
-    LocalVariable, ResourceBinding, Scalar, ScalarKind, ShaderStage, SwizzleComponent, Type,
-    TypeInner, VectorSize,
+    LocalVariable, ResourceBinding, Scalar, ScalarKind, ShaderStage, StorageAccess,
+    SwizzleComponent, Type, TypeInner, VectorSize,
 };
 
 pub struct VarDeclaration<'a, 'key> {
@@ -236,6 +236,96 @@ impl Frontend {
         Ok(())
     }
 
+    /// Applies the memory qualifiers of a redeclaration like `readonly buf;`
+    /// to the global variable `name`, restricting its access further.
+    pub(crate) fn add_memory_qualifiers(
+        &mut self,
+        ctx: &mut Context,
+        name: &str,
+        access: StorageAccess,
+        meta: Span,
+    ) -> Result<()> {
+        let handle = match self.global_variables.iter().rev().find(|&(n, _)| n == name) {
+            Some(&(
+                _,
+                GlobalLookup {
+                    kind: GlobalLookupKind::Variable(handle),
+                    ..
+                },
+            )) => handle,
+            Some(_) => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "Memory qualifiers can only be applied to buffers and images".into(),
+                    ),
+                    meta,
+                })
+            }
+            None => {
+                return Err(Error {
+                    kind: ErrorKind::UnknownVariable(name.into()),
+                    meta,
+                })
+            }
+        };
+
+        let var = &mut ctx.module.global_variables[handle];
+        match var.space {
+            AddressSpace::Storage {
+                access: ref mut var_access,
+            } => {
+                if !access.contains(StorageAccess::LOAD) {
+                    return Err(unreadable_buffer_error(meta));
+                }
+                *var_access &= access;
+            }
+            AddressSpace::Handle => match ctx.module.types[var.ty].inner {
+                TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class:
+                        crate::ImageClass::Storage {
+                            format,
+                            access: image_access,
+                        },
+                } => {
+                    var.ty = ctx.module.types.insert(
+                        Type {
+                            name: None,
+                            inner: TypeInner::Image {
+                                dim,
+                                arrayed,
+                                class: crate::ImageClass::Storage {
+                                    format,
+                                    access: image_access & access,
+                                },
+                            },
+                        },
+                        meta,
+                    );
+                }
+                _ => {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "Memory qualifiers can only be applied to storage images".into(),
+                        ),
+                        meta,
+                    })
+                }
+            },
+            _ => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "Memory qualifiers can only be applied to buffers and images".into(),
+                    ),
+                    meta,
+                })
+            }
+        }
+
+        Ok(())
+    }
+
     pub(crate) fn field_selection(
         &mut self,
         ctx: &mut Context,
@@ -492,7 +582,12 @@ impl Frontend {
             StorageQualifier::AddressSpace(mut space) => {
                 match space {
                     AddressSpace::Storage { ref mut access } => {
-                        if let Some((allowed_access, _)) = qualifiers.storage_access.take() {
+                        if let Some((allowed_access, access_meta)) =
+                            qualifiers.storage_access.take()
+                        {
+                            if !allowed_access.contains(StorageAccess::LOAD) {
+                                self.errors.push(unreadable_buffer_error(access_meta));
+                            }
                             *access = allowed_access;
                         }
                     }
@@ -647,3 +742,12 @@ impl Frontend {
         Ok(expr)
     }
 }
+
+/// Naga's storage buffers are always readable, so `writeonly` buffers and
+/// buffer members can't be represented.
+pub(crate) fn unreadable_buffer_error(meta: Span) -> Error {
+    Error {
+        kind: ErrorKind::SemanticError("Buffers and their members can't be `writeonly`".into()),
+        meta,
+    }
+}
@@ -0,0 +1,303 @@
+//! The syntax tree the parser builds and the lowerer turns into a [`Module`].
+//!
+//! [`Module`]: crate::Module
+
+use super::lex::Number;
+use crate::{ImageDimension, Scalar, Span, VectorSize};
+
+#[derive(Clone, Copy, Debug)]
+pub struct Ident<'a> {
+    pub name: &'a str,
+    pub span: Span,
+}
+
+#[derive(Debug, Default)]
+pub struct TranslationUnit<'a> {
+    pub decls: Vec<GlobalDecl<'a>>,
+}
+
+#[derive(Debug)]
+pub enum GlobalDecl<'a> {
+    Struct(Struct<'a>),
+    CBuffer(CBuffer<'a>),
+    Variable(VarDecl<'a>),
+    Function(Function<'a>),
+}
+
+/// An attribute, like `[numthreads(8, 8, 1)]` or `[[vk::binding(0)]]`.
+#[derive(Debug)]
+pub struct Attribute<'a> {
+    /// The namespace of the attribute, like `vk` in `vk::binding`.
+    pub namespace: Option<Ident<'a>>,
+    pub name: Ident<'a>,
+    pub args: Vec<AttributeArg<'a>>,
+}
+
+#[derive(Debug)]
+pub enum AttributeArg<'a> {
+    Expr(Expr<'a>),
+    String(&'a str, Span),
+}
+
+/// A `register(...)` annotation.
+#[derive(Clone, Copy, Debug)]
+pub struct Register {
+    /// The register class: `b`, `t`, `s` or `u`.
+    pub class: char,
+    pub index: u32,
+    pub space: u32,
+    pub span: Span,
+}
+
+/// Keywords that can precede the type of a declaration.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Modifiers {
+    pub is_static: bool,
+    pub is_const: bool,
+    pub groupshared: bool,
+    pub row_major: bool,
+    pub interpolation: Option<crate::Interpolation>,
+    pub sampling: Option<crate::Sampling>,
+    pub direction: Direction,
+}
+
+/// The direction of a function parameter.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    In,
+    Out,
+    InOut,
+}
+
+#[derive(Clone, Debug)]
+pub struct Type<'a> {
+    pub kind: TypeKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum TypeKind<'a> {
+    Void,
+    Scalar(Scalar),
+    Vector(VectorSize, Scalar),
+    /// A matrix with `rows` rows and `columns` columns, as HLSL counts them.
+    Matrix {
+        rows: VectorSize,
+        columns: VectorSize,
+        scalar: Scalar,
+    },
+    /// A struct declared earlier.
+    Named(Ident<'a>),
+    Texture {
+        dim: ImageDimension,
+        arrayed: bool,
+        multisampled: bool,
+        /// Whether this is an `RWTexture`.
+        storage: bool,
+        /// The type of the texels, `float4` if not given.
+        texel: Option<Box<Type<'a>>>,
+    },
+    Sampler {
+        comparison: bool,
+    },
+    StructuredBuffer {
+        element: Box<Type<'a>>,
+        read_write: bool,
+    },
+    ConstantBuffer(Box<Type<'a>>),
+}
+
+/// The declaration of a variable, struct member or function parameter.
+#[derive(Debug)]
+pub struct VarDecl<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub modifiers: Modifiers,
+    pub ty: Type<'a>,
+    pub name: Ident<'a>,
+    /// The sizes in `[]` after the name, outermost first. An empty `[]` has
+    /// no size.
+    pub array_sizes: Vec<Option<Expr<'a>>>,
+    pub semantic: Option<Ident<'a>>,
+    pub register: Option<Register>,
+    pub init: Option<Expr<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Struct<'a> {
+    pub name: Ident<'a>,
+    pub members: Vec<VarDecl<'a>>,
+}
+
+/// A `cbuffer` declaration.
+#[derive(Debug)]
+pub struct CBuffer<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub name: Ident<'a>,
+    pub register: Option<Register>,
+    pub members: Vec<VarDecl<'a>>,
+}
+
+#[derive(Debug)]
+pub struct Function<'a> {
+    pub attributes: Vec<Attribute<'a>>,
+    pub result: Type<'a>,
+    pub name: Ident<'a>,
+    pub params: Vec<VarDecl<'a>>,
+    pub semantic: Option<Ident<'a>>,
+    pub body: Block<'a>,
+}
+
+pub type Block<'a> = Vec<Statement<'a>>;
+
+#[derive(Debug)]
+pub struct Statement<'a> {
+    pub kind: StatementKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum StatementKind<'a> {
+    Empty,
+    Block(Block<'a>),
+    Local(Vec<VarDecl<'a>>),
+    Expr(Expr<'a>),
+    If {
+        condition: Expr<'a>,
+        accept: Box<Statement<'a>>,
+        reject: Option<Box<Statement<'a>>>,
+    },
+    For {
+        init: Option<Box<Statement<'a>>>,
+        condition: Option<Expr<'a>>,
+        update: Option<Expr<'a>>,
+        body: Box<Statement<'a>>,
+    },
+    While {
+        condition: Expr<'a>,
+        body: Box<Statement<'a>>,
+    },
+    DoWhile {
+        body: Box<Statement<'a>>,
+        condition: Expr<'a>,
+    },
+    Switch {
+        selector: Expr<'a>,
+        cases: Vec<SwitchCase<'a>>,
+    },
+    Break,
+    Continue,
+    Return(Option<Expr<'a>>),
+    Discard,
+}
+
+#[derive(Debug)]
+pub struct SwitchCase<'a> {
+    /// The value of a `case` label, or `None` for `default`.
+    pub value: Option<Expr<'a>>,
+    pub body: Block<'a>,
+}
+
+#[derive(Debug)]
+pub struct Expr<'a> {
+    pub kind: ExprKind<'a>,
+    pub span: Span,
+}
+
+#[derive(Debug)]
+pub enum ExprKind<'a> {
+    Literal(Literal),
+    Ident(&'a str),
+    Unary {
+        op: UnaryOp,
+        expr: Box<Expr<'a>>,
+    },
+    Binary {
+        op: BinaryOp,
+        left: Box<Expr<'a>>,
+        right: Box<Expr<'a>>,
+    },
+    /// An assignment, with the operator of a compound assignment like `+=`.
+    Assign {
+        op: Option<BinaryOp>,
+        target: Box<Expr<'a>>,
+        value: Box<Expr<'a>>,
+    },
+    /// `++` or `--`, before or after `target`.
+    IncDec {
+        increment: bool,
+        prefix: bool,
+        target: Box<Expr<'a>>,
+    },
+    Ternary {
+        condition: Box<Expr<'a>>,
+        accept: Box<Expr<'a>>,
+        reject: Box<Expr<'a>>,
+    },
+    /// A call of a user function or an intrinsic.
+    Call {
+        function: Ident<'a>,
+        args: Vec<Expr<'a>>,
+    },
+    /// A constructor, like `float4(uv, 0, 1)`.
+    Construct {
+        ty: Type<'a>,
+        args: Vec<Expr<'a>>,
+    },
+    Cast {
+        ty: Type<'a>,
+        expr: Box<Expr<'a>>,
+    },
+    Member {
+        base: Box<Expr<'a>>,
+        member: Ident<'a>,
+    },
+    Index {
+        base: Box<Expr<'a>>,
+        index: Box<Expr<'a>>,
+    },
+    /// A method call, like `texture.Sample(sampler, uv)`.
+    MethodCall {
+        base: Box<Expr<'a>>,
+        method: Ident<'a>,
+        args: Vec<Expr<'a>>,
+    },
+    /// An initializer list, like `{ 1, 2, 3 }`.
+    InitList(Vec<Expr<'a>>),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum Literal {
+    Bool(bool),
+    Number(Number),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnaryOp {
+    Negate,
+    Plus,
+    LogicalNot,
+    BitwiseNot,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BinaryOp {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Modulo,
+    Equal,
+    NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    ExclusiveOr,
+    InclusiveOr,
+    LogicalAnd,
+    LogicalOr,
+    ShiftLeft,
+    ShiftRight,
+}
@@ -0,0 +1,1707 @@
+//! Lowering HLSL expressions into naga IR.
+
+use super::ast::{self, BinaryOp, ExprKind, UnaryOp};
+use super::error::{Error, ErrorKind};
+use super::lex::Number;
+use super::lower::{CBufferMember, GlobalSymbol, Lowerer};
+use super::Result;
+use crate::front::{SymbolTable, Typifier};
+use crate::proc::{Emitter, ExpressionKindTracker, ResolveContext};
+use crate::{
+    Arena, Block, Expression, FunctionArgument, Handle, Literal, LocalVariable, Scalar, ScalarKind,
+    Span, Statement, SwizzleComponent, Type, TypeInner, VectorSize,
+};
+
+/// An expression that might be assigned to.
+#[derive(Clone, Debug)]
+pub enum Place {
+    /// A value, which can't be assigned to.
+    Value(Handle<Expression>),
+    /// A pointer to a value.
+    ///
+    /// `transposed` is set for pointers to column-major matrices, which are
+    /// stored transposed, or arrays of them. See [`Lowerer::transposed`].
+    Pointer {
+        pointer: Handle<Expression>,
+        transposed: bool,
+    },
+    /// Some components of the vector `pointer` points to, like `v.xy`.
+    Swizzle {
+        pointer: Handle<Expression>,
+        components: Vec<u32>,
+    },
+    /// A texel of a storage texture, like `texture[coordinate]`.
+    Texel {
+        image: Handle<Expression>,
+        coordinate: Handle<Expression>,
+        array_index: Option<Handle<Expression>>,
+    },
+}
+
+/// The values an entry point returns through its `out` parameters.
+#[derive(Debug)]
+pub struct Outputs {
+    /// The struct the entry point returns, holding the return value, if the
+    /// entry point has one, followed by the `out` parameters.
+    pub ty: Handle<Type>,
+    /// Whether the first member of `ty` is the return value.
+    pub has_value: bool,
+    /// The local variables holding the `out` parameters.
+    pub locals: Vec<Handle<LocalVariable>>,
+}
+
+/// The state of lowering the expressions of one function, or of a constant
+/// expression in [`Module::global_expressions`].
+///
+/// [`Module::global_expressions`]: crate::Module::global_expressions
+pub struct Context<'l, 'a> {
+    pub lowerer: &'l mut Lowerer<'a>,
+    /// Whether expressions go to [`Module::global_expressions`] rather
+    /// than to [`expressions`], and must be constant.
+    ///
+    /// [`Module::global_expressions`]: crate::Module::global_expressions
+    /// [`expressions`]: Context::expressions
+    pub is_const: bool,
+    pub expressions: Arena<Expression>,
+    pub locals: Arena<LocalVariable>,
+    pub arguments: Vec<FunctionArgument>,
+    pub named_expressions: crate::NamedExpressions,
+    pub body: Block,
+    pub symbols: SymbolTable<&'a str, Place>,
+    /// The number of loops the current statement is in.
+    pub loop_depth: usize,
+    /// The return type of the function.
+    pub result: Option<Handle<Type>>,
+    pub outputs: Option<Outputs>,
+    emitter: Emitter,
+    typifier: Typifier,
+    local_expression_kind_tracker: ExpressionKindTracker,
+}
+
+impl<'l, 'a> Context<'l, 'a> {
+    pub fn new(lowerer: &'l mut Lowerer<'a>, is_const: bool) -> Self {
+        let mut this = Context {
+            lowerer,
+            is_const,
+            expressions: Arena::new(),
+            locals: Arena::new(),
+            arguments: Vec::new(),
+            named_expressions: Default::default(),
+            body: Block::new(),
+            symbols: SymbolTable::default(),
+            loop_depth: 0,
+            result: None,
+            outputs: None,
+            emitter: Emitter::default(),
+            typifier: Typifier::new(),
+            local_expression_kind_tracker: ExpressionKindTracker::new(),
+        };
+        if !is_const {
+            this.emitter.start(&this.expressions);
+        }
+        this
+    }
+
+    /// Returns a context for constant expressions sharing this one's module.
+    pub fn as_const(&mut self) -> Context<'_, 'a> {
+        Context::new(self.lowerer, true)
+    }
+
+    /// Emits the expressions added since the last call, so that a statement
+    /// can be added after them.
+    pub fn emit_restart(&mut self) {
+        self.body.extend(self.emitter.finish(&self.expressions));
+        self.emitter.start(&self.expressions);
+    }
+
+    /// Finishes the function, returning its expressions and body.
+    pub fn finish(mut self) -> Self {
+        self.body.extend(self.emitter.finish(&self.expressions));
+        self
+    }
+
+    /// Lowers statements into a new block, with `f`.
+    pub fn with_body(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<Block> {
+        self.emit_restart();
+        let outer = std::mem::take(&mut self.body);
+        let result = f(self);
+        self.emit_restart();
+        let body = std::mem::replace(&mut self.body, outer);
+        result.map(|()| body)
+    }
+
+    pub fn add(&mut self, expression: Expression, span: Span) -> Result<Handle<Expression>> {
+        let module = &mut self.lowerer.module;
+        let mut eval = match self.is_const {
+            true => crate::proc::ConstantEvaluator::for_glsl_module(
+                module,
+                &mut self.lowerer.global_expression_kind_tracker,
+            ),
+            false => crate::proc::ConstantEvaluator::for_glsl_function(
+                module,
+                &mut self.expressions,
+                &mut self.local_expression_kind_tracker,
+                &mut self.emitter,
+                &mut self.body,
+            ),
+        };
+        eval.try_eval_and_append(expression, span)
+            .map_err(|error| Error {
+                kind: match error {
+                    crate::proc::ConstantEvaluatorError::RuntimeExpr => ErrorKind::NotConstant,
+                    error => error.into(),
+                },
+                span,
+            })
+    }
+
+    /// Adds an expression that must not be covered by an [`Emit`], like a
+    /// [`CallResult`].
+    ///
+    /// [`Emit`]: Statement::Emit
+    /// [`CallResult`]: Expression::CallResult
+    pub fn interrupt_emitter(
+        &mut self,
+        expression: Expression,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        self.body.extend(self.emitter.finish(&self.expressions));
+        let handle = self.add(expression, span);
+        self.emitter.start(&self.expressions);
+        handle
+    }
+
+    /// Returns whether `handle` is a constant expression.
+    pub fn is_const_expression(&self, handle: Handle<Expression>) -> bool {
+        match self.is_const {
+            true => true,
+            false => self.local_expression_kind_tracker.is_const(handle),
+        }
+    }
+
+    /// Returns the literal `handle` evaluates to, if it is constant.
+    pub fn literal(&self, handle: Handle<Expression>) -> Option<Literal> {
+        let module = &self.lowerer.module;
+        let expressions = match self.is_const {
+            true => &module.global_expressions,
+            false => &self.expressions,
+        };
+        match expressions[handle] {
+            Expression::Literal(literal) => Some(literal),
+            Expression::Constant(c) => module
+                .to_ctx()
+                .eval_expr_to_literal(module.constants[c].init),
+            _ => None,
+        }
+    }
+
+    fn grow_types(&mut self, handle: Handle<Expression>) -> Result<()> {
+        let module = &self.lowerer.module;
+        let empty = Arena::new();
+        let (typifier, expressions, locals, arguments) = match self.is_const {
+            true => (
+                &mut self.lowerer.global_typifier,
+                &module.global_expressions,
+                &empty,
+                &[][..],
+            ),
+            false => (
+                &mut self.typifier,
+                &self.expressions,
+                &self.locals,
+                &self.arguments[..],
+            ),
+        };
+        let resolve_ctx = ResolveContext::with_locals(module, locals, arguments);
+        typifier
+            .grow(handle, expressions, &resolve_ctx)
+            .map_err(|error| Error {
+                kind: ErrorKind::SemanticError(error.to_string().into()),
+                span: expressions.get_span(handle),
+            })
+    }
+
+    fn typifier(&self) -> &Typifier {
+        match self.is_const {
+            true => &self.lowerer.global_typifier,
+            false => &self.typifier,
+        }
+    }
+
+    /// Returns the type of `handle`.
+    pub fn inner(&mut self, handle: Handle<Expression>) -> Result<TypeInner> {
+        self.grow_types(handle)?;
+        let types = &self.lowerer.module.types;
+        Ok(self.typifier().get(handle, types).clone())
+    }
+
+    /// Returns the type of `handle`, adding it to the module if needed.
+    pub fn register_type(&mut self, handle: Handle<Expression>) -> Result<Handle<Type>> {
+        self.grow_types(handle)?;
+        let typifier = match self.is_const {
+            true => &self.lowerer.global_typifier,
+            false => &self.typifier,
+        };
+        Ok(typifier.register_type(handle, &mut self.lowerer.module.types))
+    }
+
+    /// Returns the type of the value `pointer` points to.
+    ///
+    /// Atomics are loaded and stored as the integers they hold.
+    fn pointee(
+        &mut self,
+        pointer: Handle<Expression>,
+    ) -> Result<(TypeInner, Option<Handle<Type>>)> {
+        Ok(match self.inner(pointer)? {
+            TypeInner::Pointer { base, .. } => match self.lowerer.module.types[base].inner {
+                TypeInner::Atomic(scalar) => (TypeInner::Scalar(scalar), None),
+                ref inner => (inner.clone(), Some(base)),
+            },
+            TypeInner::ValuePointer { size, scalar, .. } => (
+                match size {
+                    Some(size) => TypeInner::Vector { size, scalar },
+                    None => TypeInner::Scalar(scalar),
+                },
+                None,
+            ),
+            inner => (inner, None),
+        })
+    }
+
+    pub const fn error<T>(&self, kind: ErrorKind, span: Span) -> Result<T> {
+        Err(Error { kind, span })
+    }
+
+    fn mismatch<T>(&self, expected: &TypeInner, found: &TypeInner, span: Span) -> Result<T> {
+        let types = &self.lowerer.module.types;
+        self.error(
+            ErrorKind::TypeMismatch {
+                expected: super::type_name(expected, types),
+                found: super::type_name(found, types),
+            },
+            span,
+        )
+    }
+
+    pub fn literal_expression(
+        &mut self,
+        literal: Literal,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        self.add(Expression::Literal(literal), span)
+    }
+
+    /// Adds a new local variable of type `ty`, returning a pointer to it.
+    pub fn temporary(&mut self, ty: Handle<Type>, span: Span) -> Result<Handle<Expression>> {
+        let local = self.locals.append(
+            LocalVariable {
+                name: None,
+                ty,
+                init: None,
+            },
+            span,
+        );
+        self.add(Expression::LocalVariable(local), span)
+    }
+
+    // Conversions
+
+    /// Converts `value` to the type `ty`, the way HLSL converts implicitly.
+    pub fn convert(
+        &mut self,
+        value: Handle<Expression>,
+        ty: Handle<Type>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let target = self.lowerer.module.types[ty].inner.clone();
+        self.convert_to_inner(value, &target, Some(ty), span)
+    }
+
+    /// Converts `value` to a type with the shape and scalar of `target`.
+    ///
+    /// Scalars are converted to vectors and matrices by repeating them, and
+    /// vectors are truncated to fewer components or a scalar.
+    pub fn convert_to_inner(
+        &mut self,
+        value: Handle<Expression>,
+        target: &TypeInner,
+        target_ty: Option<Handle<Type>>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let source = self.inner(value)?;
+        if source.equivalent(target, &self.lowerer.module.types) {
+            return Ok(value);
+        }
+        match (&source, target) {
+            (&TypeInner::Scalar(_), &TypeInner::Scalar(scalar)) => self.cast(value, scalar, span),
+            (&TypeInner::Scalar(_), &TypeInner::Vector { size, scalar }) => {
+                let value = self.cast(value, scalar, span)?;
+                self.add(Expression::Splat { size, value }, span)
+            }
+            (
+                &TypeInner::Scalar(_),
+                &TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                },
+            ) => {
+                let value = self.cast(value, scalar, span)?;
+                let column = self.add(Expression::Splat { size: rows, value }, span)?;
+                let ty = match target_ty {
+                    Some(ty) => ty,
+                    None => self.lowerer.insert_type(target.clone()),
+                };
+                self.add(
+                    Expression::Compose {
+                        ty,
+                        components: vec![column; columns as usize],
+                    },
+                    span,
+                )
+            }
+            (&TypeInner::Vector { .. }, &TypeInner::Scalar(scalar)) => {
+                let value = self.add(
+                    Expression::AccessIndex {
+                        base: value,
+                        index: 0,
+                    },
+                    span,
+                )?;
+                self.cast(value, scalar, span)
+            }
+            (
+                &TypeInner::Vector {
+                    size: source_size, ..
+                },
+                &TypeInner::Vector { size, scalar },
+            ) if size <= source_size => {
+                let value = match size < source_size {
+                    true => self.add(
+                        Expression::Swizzle {
+                            size,
+                            vector: value,
+                            pattern: SwizzleComponent::XYZW,
+                        },
+                        span,
+                    )?,
+                    false => value,
+                };
+                self.cast(value, scalar, span)
+            }
+            (
+                &TypeInner::Matrix {
+                    columns: source_columns,
+                    rows: source_rows,
+                    ..
+                },
+                &TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                },
+            ) if (columns, rows) == (source_columns, source_rows) => self.cast(value, scalar, span),
+            _ => self.mismatch(target, &source, span),
+        }
+    }
+
+    /// Converts the scalars of `value`, a scalar, vector or matrix, to
+    /// `scalar`.
+    pub fn cast(
+        &mut self,
+        value: Handle<Expression>,
+        scalar: Scalar,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        match self.inner(value)?.scalar() {
+            Some(source) if source == scalar => Ok(value),
+            _ => self.add(
+                Expression::As {
+                    expr: value,
+                    kind: scalar.kind,
+                    convert: Some(scalar.width),
+                },
+                span,
+            ),
+        }
+    }
+
+    /// Converts `value` to a boolean with the same shape.
+    pub fn cast_to_bool(
+        &mut self,
+        value: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        self.cast(value, Scalar::BOOL, span)
+    }
+
+    /// Converts `value` to a single boolean, for a condition.
+    pub fn condition(&mut self, expr: &ast::Expr<'a>) -> Result<Handle<Expression>> {
+        let value = self.value(expr)?;
+        self.convert_to_inner(value, &TypeInner::Scalar(Scalar::BOOL), None, expr.span)
+    }
+
+    /// Returns the type that `types` are all converted to when they are
+    /// operands of a binary operator or arguments of an intrinsic.
+    pub fn common_type(&self, types: &[TypeInner], span: Span) -> Result<TypeInner> {
+        let mut common: Option<TypeInner> = None;
+        for inner in types {
+            let Some(scalar) = inner.scalar() else {
+                return self.mismatch(&TypeInner::Scalar(Scalar::F32), inner, span);
+            };
+            let Some(ref previous) = common else {
+                common = Some(inner.clone());
+                continue;
+            };
+            let scalar = match previous.scalar() {
+                Some(previous) => common_scalar(previous, scalar),
+                None => scalar,
+            };
+            common = Some(match (previous, inner) {
+                (&TypeInner::Scalar(_), &TypeInner::Scalar(_)) => TypeInner::Scalar(scalar),
+                (&TypeInner::Scalar(_), &TypeInner::Vector { size, .. })
+                | (&TypeInner::Vector { size, .. }, &TypeInner::Scalar(_)) => {
+                    TypeInner::Vector { size, scalar }
+                }
+                (&TypeInner::Vector { size: a, .. }, &TypeInner::Vector { size: b, .. }) => {
+                    TypeInner::Vector {
+                        size: a.min(b),
+                        scalar,
+                    }
+                }
+                (&TypeInner::Scalar(_), &TypeInner::Matrix { columns, rows, .. })
+                | (&TypeInner::Matrix { columns, rows, .. }, &TypeInner::Scalar(_)) => {
+                    TypeInner::Matrix {
+                        columns,
+                        rows,
+                        scalar,
+                    }
+                }
+                (
+                    &TypeInner::Matrix { columns, rows, .. },
+                    &TypeInner::Matrix {
+                        columns: other_columns,
+                        rows: other_rows,
+                        ..
+                    },
+                ) if (columns, rows) == (other_columns, other_rows) => TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                },
+                _ => return self.mismatch(previous, inner, span),
+            });
+        }
+        Ok(common.unwrap_or(TypeInner::Scalar(Scalar::F32)))
+    }
+
+    // Places
+
+    /// Lowers `expr`, which might be assigned to.
+    pub fn place(&mut self, expr: &ast::Expr<'a>) -> Result<Place> {
+        match expr.kind {
+            ExprKind::Ident(name) => self.ident(name, expr.span),
+            ExprKind::Member { ref base, member } => {
+                let base = self.place(base)?;
+                self.member(base, member)
+            }
+            ExprKind::Index {
+                ref base,
+                ref index,
+            } => {
+                let base = self.place(base)?;
+                let index = self.value(index)?;
+                self.index(base, index, expr.span)
+            }
+            _ => Ok(Place::Value(self.value(expr)?)),
+        }
+    }
+
+    fn ident(&mut self, name: &'a str, span: Span) -> Result<Place> {
+        if let Some(place) = self.symbols.lookup(name) {
+            return Ok(place.clone());
+        }
+        let Some(&symbol) = self.lowerer.globals.get(name) else {
+            return self.error(ErrorKind::UnknownIdentifier(name.to_string()), span);
+        };
+        if self.is_const && !matches!(symbol, GlobalSymbol::Constant(_)) {
+            return self.error(ErrorKind::NotConstant, span);
+        }
+        Ok(match symbol {
+            GlobalSymbol::Constant(constant) => {
+                Place::Value(self.add(Expression::Constant(constant), span)?)
+            }
+            GlobalSymbol::Variable(variable) => {
+                let pointer = self.add(Expression::GlobalVariable(variable), span)?;
+                match self.lowerer.module.global_variables[variable].space {
+                    crate::AddressSpace::Handle => Place::Value(pointer),
+                    _ => Place::Pointer {
+                        pointer,
+                        transposed: self.lowerer.transposed_globals.contains(&variable),
+                    },
+                }
+            }
+            GlobalSymbol::CBufferMember(variable, CBufferMember::Member(index)) => {
+                let base = self.add(Expression::GlobalVariable(variable), span)?;
+                let pointer = self.add(Expression::AccessIndex { base, index }, span)?;
+                let ty = self.lowerer.module.global_variables[variable].ty;
+                Place::Pointer {
+                    pointer,
+                    transposed: self.lowerer.transposed.contains(&(ty, index)),
+                }
+            }
+            GlobalSymbol::CBufferMember(variable, CBufferMember::Scalarized { first, ty }) => {
+                // `cbuffer`s are read-only, so the vector can be put back
+                // together from its components.
+                let TypeInner::Vector { size, .. } = self.lowerer.module.types[ty].inner else {
+                    unreachable!()
+                };
+                let base = self.add(Expression::GlobalVariable(variable), span)?;
+                let mut components = Vec::with_capacity(size as usize);
+                for index in first..first + size as u32 {
+                    let pointer = self.add(Expression::AccessIndex { base, index }, span)?;
+                    components.push(self.add(Expression::Load { pointer }, span)?);
+                }
+                Place::Value(self.add(Expression::Compose { ty, components }, span)?)
+            }
+        })
+    }
+
+    /// Lowers `base.member`, which is a struct member or a swizzle.
+    fn member(&mut self, base: Place, member: ast::Ident<'a>) -> Result<Place> {
+        let span = member.span;
+        let base = match base {
+            Place::Pointer {
+                pointer,
+                transposed,
+            } => {
+                let (pointee, pointee_ty) = self.pointee(pointer)?;
+                match pointee {
+                    TypeInner::Struct { ref members, .. } => {
+                        let index = self.member_index(members, member)?;
+                        let struct_ty = pointee_ty.unwrap();
+                        let pointer = self.add(
+                            Expression::AccessIndex {
+                                base: pointer,
+                                index,
+                            },
+                            span,
+                        )?;
+                        return Ok(Place::Pointer {
+                            pointer,
+                            transposed: self.lowerer.transposed.contains(&(struct_ty, index)),
+                        });
+                    }
+                    TypeInner::Vector { size, .. } => {
+                        let components = swizzle(member, size as u32)?;
+                        return Ok(match *components.as_slice() {
+                            [index] => Place::Pointer {
+                                pointer: self.add(
+                                    Expression::AccessIndex {
+                                        base: pointer,
+                                        index,
+                                    },
+                                    span,
+                                )?,
+                                transposed: false,
+                            },
+                            _ => Place::Swizzle {
+                                pointer,
+                                components,
+                            },
+                        });
+                    }
+                    TypeInner::Scalar(_) if swizzle(member, 1)?.len() == 1 => {
+                        return Ok(Place::Pointer {
+                            pointer,
+                            transposed: false,
+                        })
+                    }
+                    TypeInner::Matrix { .. } if !transposed => {
+                        if let Some((row, column)) = matrix_element(member.name) {
+                            let base = self.add(
+                                Expression::AccessIndex {
+                                    base: pointer,
+                                    index: row,
+                                },
+                                span,
+                            )?;
+                            let pointer = self.add(
+                                Expression::AccessIndex {
+                                    base,
+                                    index: column,
+                                },
+                                span,
+                            )?;
+                            return Ok(Place::Pointer {
+                                pointer,
+                                transposed: false,
+                            });
+                        }
+                        self.load(
+                            Place::Pointer {
+                                pointer,
+                                transposed,
+                            },
+                            span,
+                        )?
+                    }
+                    _ => self.load(
+                        Place::Pointer {
+                            pointer,
+                            transposed,
+                        },
+                        span,
+                    )?,
+                }
+            }
+            place => self.load(place, span)?,
+        };
+
+        // Members of values
+        match self.inner(base)? {
+            TypeInner::Struct { ref members, .. } => {
+                let index = self.member_index(members, member)?;
+                let value = self.add(Expression::AccessIndex { base, index }, span)?;
+                let struct_ty = self.register_type(base)?;
+                match self.lowerer.transposed.contains(&(struct_ty, index)) {
+                    true => self.transpose_matrix(value, span).map(Place::Value),
+                    false => Ok(Place::Value(value)),
+                }
+            }
+            TypeInner::Vector { size, .. } => {
+                let components = swizzle(member, size as u32)?;
+                self.swizzle(base, &components, span).map(Place::Value)
+            }
+            TypeInner::Scalar(_) => {
+                let components = swizzle(member, 1)?;
+                self.swizzle(base, &components, span).map(Place::Value)
+            }
+            TypeInner::Matrix { .. } => {
+                let Some((row, column)) = matrix_element(member.name) else {
+                    return self.error(ErrorKind::UnknownMember(member.name.to_string()), span);
+                };
+                let base = self.add(Expression::AccessIndex { base, index: row }, span)?;
+                let value = self.add(
+                    Expression::AccessIndex {
+                        base,
+                        index: column,
+                    },
+                    span,
+                )?;
+                Ok(Place::Value(value))
+            }
+            _ => self.error(ErrorKind::UnknownMember(member.name.to_string()), span),
+        }
+    }
+
+    fn member_index(&self, members: &[crate::StructMember], member: ast::Ident) -> Result<u32> {
+        members
+            .iter()
+            .position(|m| m.name.as_deref() == Some(member.name))
+            .map(|index| index as u32)
+            .ok_or(Error {
+                kind: ErrorKind::UnknownMember(member.name.to_string()),
+                span: member.span,
+            })
+    }
+
+    /// Returns the components of the vector or scalar `value` selected by
+    /// `components`.
+    fn swizzle(
+        &mut self,
+        value: Handle<Expression>,
+        components: &[u32],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let is_scalar = matches!(self.inner(value)?, TypeInner::Scalar(_));
+        match (components.len(), is_scalar) {
+            (1, true) => Ok(value),
+            (1, false) => self.add(
+                Expression::AccessIndex {
+                    base: value,
+                    index: components[0],
+                },
+                span,
+            ),
+            (len, true) => self.add(
+                Expression::Splat {
+                    size: vector_size(len),
+                    value,
+                },
+                span,
+            ),
+            (len, false) => {
+                let mut pattern = [SwizzleComponent::X; 4];
+                for (component, &index) in pattern.iter_mut().zip(components) {
+                    *component = SwizzleComponent::XYZW[index as usize];
+                }
+                self.add(
+                    Expression::Swizzle {
+                        size: vector_size(len),
+                        vector: value,
+                        pattern,
+                    },
+                    span,
+                )
+            }
+        }
+    }
+
+    fn transpose_matrix(
+        &mut self,
+        value: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        match self.inner(value)? {
+            TypeInner::Matrix { .. } => self.add(
+                Expression::Math {
+                    fun: crate::MathFunction::Transpose,
+                    arg: value,
+                    arg1: None,
+                    arg2: None,
+                    arg3: None,
+                },
+                span,
+            ),
+            _ => Ok(value),
+        }
+    }
+
+    /// Lowers `base[index]`.
+    fn index(&mut self, base: Place, index: Handle<Expression>, span: Span) -> Result<Place> {
+        let base = match base {
+            Place::Pointer {
+                pointer,
+                transposed,
+            } => {
+                let (pointee, _) = self.pointee(pointer)?;
+                match pointee {
+                    TypeInner::Array { .. }
+                    | TypeInner::Vector { .. }
+                    | TypeInner::Matrix { .. }
+                        if !(transposed && is_matrix(&pointee)) =>
+                    {
+                        let index = self.index_value(index, span)?;
+                        let pointer = self.add(
+                            Expression::Access {
+                                base: pointer,
+                                index,
+                            },
+                            span,
+                        )?;
+                        return Ok(Place::Pointer {
+                            pointer,
+                            transposed: transposed && matches!(pointee, TypeInner::Array { .. }),
+                        });
+                    }
+                    _ => self.load(
+                        Place::Pointer {
+                            pointer,
+                            transposed,
+                        },
+                        span,
+                    )?,
+                }
+            }
+            place => self.load(place, span)?,
+        };
+
+        match self.inner(base)? {
+            TypeInner::Image {
+                dim,
+                arrayed,
+                class,
+            } => {
+                let size = dimension_count(dim) + u32::from(arrayed);
+                let coordinate = self.convert_to_inner(index, &int_vector(size), None, span)?;
+                let (coordinate, array_index) =
+                    self.split_coordinate(coordinate, dim, arrayed, span)?;
+                match class {
+                    crate::ImageClass::Storage { .. } => Ok(Place::Texel {
+                        image: base,
+                        coordinate,
+                        array_index,
+                    }),
+                    crate::ImageClass::Sampled { multi, .. }
+                    | crate::ImageClass::Depth { multi } => {
+                        let zero = self.literal_expression(Literal::I32(0), span)?;
+                        let value = self.add(
+                            Expression::ImageLoad {
+                                image: base,
+                                coordinate,
+                                array_index,
+                                sample: multi.then_some(zero),
+                                level: (!multi).then_some(zero),
+                            },
+                            span,
+                        )?;
+                        let value = self.texel(base, value, span)?;
+                        Ok(Place::Value(value))
+                    }
+                }
+            }
+            TypeInner::Array { .. }
+            | TypeInner::BindingArray { .. }
+            | TypeInner::Vector { .. }
+            | TypeInner::Matrix { .. } => {
+                let index = self.index_value(index, span)?;
+                Ok(Place::Value(
+                    self.add(Expression::Access { base, index }, span)?,
+                ))
+            }
+            inner => self.error(
+                ErrorKind::SemanticError(
+                    format!(
+                        "cannot index into a value of type `{}`",
+                        super::type_name(&inner, &self.lowerer.module.types)
+                    )
+                    .into(),
+                ),
+                span,
+            ),
+        }
+    }
+
+    /// Converts `index` to an integer, if it isn't one already.
+    fn index_value(&mut self, index: Handle<Expression>, span: Span) -> Result<Handle<Expression>> {
+        match self.inner(index)?.scalar_kind() {
+            Some(ScalarKind::Sint | ScalarKind::Uint) => Ok(index),
+            _ => self.convert_to_inner(index, &TypeInner::Scalar(Scalar::I32), None, span),
+        }
+    }
+
+    /// Splits an image coordinate into the coordinate proper and the array
+    /// index, which is its last component for arrayed images.
+    pub fn split_coordinate(
+        &mut self,
+        coordinate: Handle<Expression>,
+        dim: crate::ImageDimension,
+        arrayed: bool,
+        span: Span,
+    ) -> Result<(Handle<Expression>, Option<Handle<Expression>>)> {
+        if !arrayed {
+            return Ok((coordinate, None));
+        }
+        let size = dimension_count(dim);
+        let components = (0..size).collect::<Vec<_>>();
+        let proper = self.swizzle(coordinate, &components, span)?;
+        let array_index = self.add(
+            Expression::AccessIndex {
+                base: coordinate,
+                index: size,
+            },
+            span,
+        )?;
+        let _ = dim;
+        Ok((proper, Some(array_index)))
+    }
+
+    /// Converts a texel read from `image` to the texel type it was declared
+    /// with, like `float` for a `Texture2D<float>`.
+    pub fn texel(
+        &mut self,
+        image: Handle<Expression>,
+        value: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let mut global = image;
+        let variable = loop {
+            match self.expressions[global] {
+                Expression::GlobalVariable(variable) => break Some(variable),
+                Expression::Access { base, .. } | Expression::AccessIndex { base, .. } => {
+                    global = base
+                }
+                _ => break None,
+            }
+        };
+        match variable.and_then(|variable| self.lowerer.texel_types.get(&variable).copied()) {
+            Some(ty) => self.convert(value, ty, span),
+            None => Ok(value),
+        }
+    }
+
+    /// Returns the value of `place`.
+    pub fn load(&mut self, place: Place, span: Span) -> Result<Handle<Expression>> {
+        match place {
+            Place::Value(value) => Ok(value),
+            Place::Pointer {
+                pointer,
+                transposed,
+            } => {
+                let value = self.add(Expression::Load { pointer }, span)?;
+                match transposed {
+                    true => self.transpose_matrix(value, span),
+                    false => Ok(value),
+                }
+            }
+            Place::Swizzle {
+                pointer,
+                components,
+            } => {
+                let value = self.add(Expression::Load { pointer }, span)?;
+                self.swizzle(value, &components, span)
+            }
+            Place::Texel {
+                image,
+                coordinate,
+                array_index,
+            } => {
+                let value = self.add(
+                    Expression::ImageLoad {
+                        image,
+                        coordinate,
+                        array_index,
+                        sample: None,
+                        level: None,
+                    },
+                    span,
+                )?;
+                self.texel(image, value, span)
+            }
+        }
+    }
+
+    /// Stores `value` in `place`, converting it to the type of `place`.
+    pub fn store(&mut self, place: Place, value: Handle<Expression>, span: Span) -> Result<()> {
+        match place {
+            Place::Value(_) => self.error(ErrorKind::InvalidAssignment, span),
+            Place::Pointer {
+                pointer,
+                transposed,
+            } => {
+                let (pointee, pointee_ty) = self.pointee(pointer)?;
+                let value = match pointee {
+                    TypeInner::Matrix {
+                        columns,
+                        rows,
+                        scalar,
+                    } if transposed => {
+                        let target = TypeInner::Matrix {
+                            columns: rows,
+                            rows: columns,
+                            scalar,
+                        };
+                        let value = self.convert_to_inner(value, &target, None, span)?;
+                        self.transpose_matrix(value, span)?
+                    }
+                    _ => self.convert_to_inner(value, &pointee, pointee_ty, span)?,
+                };
+                self.emit_restart();
+                self.body.push(Statement::Store { pointer, value }, span);
+                Ok(())
+            }
+            Place::Swizzle {
+                pointer,
+                components,
+            } => {
+                let (pointee, _) = self.pointee(pointer)?;
+                let Some(scalar) = pointee.scalar() else {
+                    return self.error(ErrorKind::InvalidAssignment, span);
+                };
+                let target = match components.len() {
+                    1 => TypeInner::Scalar(scalar),
+                    len => TypeInner::Vector {
+                        size: vector_size(len),
+                        scalar,
+                    },
+                };
+                let value = self.convert_to_inner(value, &target, None, span)?;
+                let mut stores = Vec::new();
+                for (i, &index) in components.iter().enumerate() {
+                    let component = match components.len() {
+                        1 => value,
+                        _ => self.add(
+                            Expression::AccessIndex {
+                                base: value,
+                                index: i as u32,
+                            },
+                            span,
+                        )?,
+                    };
+                    let pointer = self.add(
+                        Expression::AccessIndex {
+                            base: pointer,
+                            index,
+                        },
+                        span,
+                    )?;
+                    stores.push(Statement::Store {
+                        pointer,
+                        value: component,
+                    });
+                }
+                self.emit_restart();
+                for store in stores {
+                    self.body.push(store, span);
+                }
+                Ok(())
+            }
+            Place::Texel {
+                image,
+                coordinate,
+                array_index,
+            } => {
+                let TypeInner::Image {
+                    class: crate::ImageClass::Storage { format, .. },
+                    ..
+                } = self.inner(image)?
+                else {
+                    return self.error(ErrorKind::InvalidAssignment, span);
+                };
+                let scalar = Scalar::from(format);
+                let value = self.widen_to_vec4(value, scalar, span)?;
+                self.emit_restart();
+                self.body.push(
+                    Statement::ImageStore {
+                        image,
+                        coordinate,
+                        array_index,
+                        value,
+                    },
+                    span,
+                );
+                Ok(())
+            }
+        }
+    }
+
+    /// Converts `value` to a four-component vector of `scalar`, filling in
+    /// missing components with zero.
+    fn widen_to_vec4(
+        &mut self,
+        value: Handle<Expression>,
+        scalar: Scalar,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let target = TypeInner::Vector {
+            size: VectorSize::Quad,
+            scalar,
+        };
+        match self.inner(value)? {
+            TypeInner::Vector { size, .. } if size != VectorSize::Quad => {
+                let value = self.cast(value, scalar, span)?;
+                let zero = self.add(Expression::Literal(Literal::zero(scalar).unwrap()), span)?;
+                let mut components = vec![value];
+                components.resize(1 + 4 - size as usize, zero);
+                let ty = self.lowerer.insert_type(target);
+                self.add(Expression::Compose { ty, components }, span)
+            }
+            _ => self.convert_to_inner(value, &target, None, span),
+        }
+    }
+
+    // Expressions
+
+    /// Lowers `expr`, which must have a value.
+    pub fn value(&mut self, expr: &ast::Expr<'a>) -> Result<Handle<Expression>> {
+        let span = expr.span;
+        match expr.kind {
+            ExprKind::Literal(literal) => {
+                let literal = match literal {
+                    ast::Literal::Bool(value) => Literal::Bool(value),
+                    ast::Literal::Number(Number::Int(value)) => match i32::try_from(value) {
+                        Ok(value) => Literal::I32(value),
+                        Err(_) => match u32::try_from(value) {
+                            Ok(value) => Literal::U32(value),
+                            Err(_) => Literal::I64(value),
+                        },
+                    },
+                    ast::Literal::Number(Number::Uint(value)) => match u32::try_from(value) {
+                        Ok(value) => Literal::U32(value),
+                        Err(_) => Literal::U64(value),
+                    },
+                    ast::Literal::Number(Number::Float(value)) => Literal::F32(value as f32),
+                    ast::Literal::Number(Number::Double(value)) => Literal::F64(value),
+                };
+                self.literal_expression(literal, span)
+            }
+            ExprKind::Ident(_) | ExprKind::Member { .. } | ExprKind::Index { .. } => {
+                let place = self.place(expr)?;
+                self.load(place, span)
+            }
+            ExprKind::Unary {
+                op,
+                expr: ref operand,
+            } => {
+                let value = self.value(operand)?;
+                let (op, value) = match op {
+                    UnaryOp::Plus => return Ok(value),
+                    UnaryOp::Negate => {
+                        // Negating a bool negates its value as an integer.
+                        let value = match self.inner(value)?.scalar_kind() {
+                            Some(ScalarKind::Bool) => self.cast(value, Scalar::I32, span)?,
+                            _ => value,
+                        };
+                        (crate::UnaryOperator::Negate, value)
+                    }
+                    UnaryOp::LogicalNot => (
+                        crate::UnaryOperator::LogicalNot,
+                        self.cast_to_bool(value, span)?,
+                    ),
+                    UnaryOp::BitwiseNot => (crate::UnaryOperator::BitwiseNot, value),
+                };
+                self.add(Expression::Unary { op, expr: value }, span)
+            }
+            ExprKind::Binary {
+                op,
+                ref left,
+                ref right,
+            } => {
+                let left = self.value(left)?;
+                let right = self.value(right)?;
+                self.binary(op, left, right, span)
+            }
+            ExprKind::Assign {
+                op,
+                ref target,
+                ref value,
+            } => {
+                let place = self.place(target)?;
+                let value = self.value(value)?;
+                let value = match op {
+                    Some(op) => {
+                        let current = self.load(place.clone(), span)?;
+                        self.binary(op, current, value, span)?
+                    }
+                    None => value,
+                };
+                self.store(place.clone(), value, span)?;
+                self.load(place, span)
+            }
+            ExprKind::IncDec {
+                increment,
+                prefix,
+                ref target,
+            } => {
+                let place = self.place(target)?;
+                let old = self.load(place.clone(), span)?;
+                let scalar = self.inner(old)?.scalar().unwrap_or(Scalar::I32);
+                let one = match scalar.kind {
+                    ScalarKind::Float => Literal::F32(1.0),
+                    ScalarKind::Uint => Literal::U32(1),
+                    _ => Literal::I32(1),
+                };
+                let one = self.literal_expression(one, span)?;
+                let op = match increment {
+                    true => BinaryOp::Add,
+                    false => BinaryOp::Subtract,
+                };
+                let new = self.binary(op, old, one, span)?;
+                self.store(place, new, span)?;
+                Ok(match prefix {
+                    true => new,
+                    false => old,
+                })
+            }
+            ExprKind::Ternary {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.value(condition)?;
+                let accept = self.value(accept)?;
+                let reject = self.value(reject)?;
+                let accept_inner = self.inner(accept)?;
+                let reject_inner = self.inner(reject)?;
+                let (accept, reject) =
+                    match accept_inner.scalar().is_some() && reject_inner.scalar().is_some() {
+                        true => {
+                            let common = self.common_type(&[accept_inner, reject_inner], span)?;
+                            (
+                                self.convert_to_inner(accept, &common, None, span)?,
+                                self.convert_to_inner(reject, &common, None, span)?,
+                            )
+                        }
+                        false => (accept, reject),
+                    };
+                // A vector condition selects components.
+                let condition = match self.inner(accept)? {
+                    TypeInner::Vector { size, .. } => match self.inner(condition)? {
+                        TypeInner::Vector { .. } => {
+                            let target = TypeInner::Vector {
+                                size,
+                                scalar: Scalar::BOOL,
+                            };
+                            self.convert_to_inner(condition, &target, None, span)?
+                        }
+                        _ => self.convert_to_inner(
+                            condition,
+                            &TypeInner::Scalar(Scalar::BOOL),
+                            None,
+                            span,
+                        )?,
+                    },
+                    _ => self.convert_to_inner(
+                        condition,
+                        &TypeInner::Scalar(Scalar::BOOL),
+                        None,
+                        span,
+                    )?,
+                };
+                self.add(
+                    Expression::Select {
+                        condition,
+                        accept,
+                        reject,
+                    },
+                    span,
+                )
+            }
+            ExprKind::Call { function, ref args } => match self.call(function, args, span)? {
+                Some(value) => Ok(value),
+                None => self.error(
+                    ErrorKind::SemanticError(
+                        format!("`{}` doesn't return a value", function.name).into(),
+                    ),
+                    span,
+                ),
+            },
+            ExprKind::MethodCall {
+                ref base,
+                method,
+                ref args,
+            } => match self.method_call(base, method, args, span)? {
+                Some(value) => Ok(value),
+                None => self.error(
+                    ErrorKind::SemanticError(
+                        format!("`{}` doesn't return a value", method.name).into(),
+                    ),
+                    span,
+                ),
+            },
+            ExprKind::Construct { ref ty, ref args } => {
+                let ty = self.lowerer.ty(ty, &[], false)?;
+                let args = args
+                    .iter()
+                    .map(|arg| Ok((self.value(arg)?, arg.span)))
+                    .collect::<Result<Vec<_>>>()?;
+                self.construct(ty, &args, span)
+            }
+            ExprKind::Cast { ref ty, ref expr } => {
+                let ty = self.lowerer.ty(ty, &[], false)?;
+                match self.lowerer.module.types[ty].inner {
+                    TypeInner::Struct { .. } | TypeInner::Array { .. } => {
+                        // `(S)0` is the zero value of `S`.
+                        let value = self.value(expr)?;
+                        match self.literal(value) {
+                            Some(literal) if literal_is_zero(literal) => {
+                                self.add(Expression::ZeroValue(ty), span)
+                            }
+                            _ => self.error(
+                                ErrorKind::NotImplemented("casts to structs from anything but 0"),
+                                span,
+                            ),
+                        }
+                    }
+                    _ => {
+                        let value = self.value(expr)?;
+                        self.convert(value, ty, span)
+                    }
+                }
+            }
+            ExprKind::InitList(_) => self.error(
+                ErrorKind::SemanticError(
+                    "initializer lists are only allowed in declarations".into(),
+                ),
+                span,
+            ),
+        }
+    }
+
+    /// Lowers `expr` as the initializer of a variable of type `ty`.
+    pub fn initializer(
+        &mut self,
+        expr: &ast::Expr<'a>,
+        ty: Handle<Type>,
+    ) -> Result<Handle<Expression>> {
+        let ExprKind::InitList(ref items) = expr.kind else {
+            let value = self.value(expr)?;
+            return self.convert(value, ty, expr.span);
+        };
+        let span = expr.span;
+        match self.lowerer.module.types[ty].inner.clone() {
+            TypeInner::Array {
+                base,
+                size: crate::ArraySize::Constant(size),
+                ..
+            } => {
+                if items.len() != size.get() as usize {
+                    return self.error(
+                        ErrorKind::TypeMismatch {
+                            expected: format!("{size} initializers"),
+                            found: items.len().to_string(),
+                        },
+                        span,
+                    );
+                }
+                let components = items
+                    .iter()
+                    .map(|item| self.initializer(item, base))
+                    .collect::<Result<Vec<_>>>()?;
+                self.add(Expression::Compose { ty, components }, span)
+            }
+            TypeInner::Struct { ref members, .. } => {
+                if items.len() != members.len() {
+                    return self.error(
+                        ErrorKind::TypeMismatch {
+                            expected: format!("{} initializers", members.len()),
+                            found: items.len().to_string(),
+                        },
+                        span,
+                    );
+                }
+                let mut components = Vec::new();
+                for (index, (item, member)) in items.iter().zip(members).enumerate() {
+                    let component = match self.lowerer.transposed.contains(&(ty, index as u32)) {
+                        true => {
+                            let value = self.value(item)?;
+                            let value = self.transpose_matrix(value, item.span)?;
+                            self.convert(value, member.ty, item.span)?
+                        }
+                        false => self.initializer(item, member.ty)?,
+                    };
+                    components.push(component);
+                }
+                self.add(Expression::Compose { ty, components }, span)
+            }
+            _ => {
+                let args = items
+                    .iter()
+                    .map(|item| Ok((self.value(item)?, item.span)))
+                    .collect::<Result<Vec<_>>>()?;
+                self.construct(ty, &args, span)
+            }
+        }
+    }
+
+    /// Constructs a value of type `ty` from `args`, like `float4(uv, 0, 1)`.
+    pub fn construct(
+        &mut self,
+        ty: Handle<Type>,
+        args: &[(Handle<Expression>, Span)],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let target = self.lowerer.module.types[ty].inner.clone();
+        if let [(arg, arg_span)] = *args {
+            let inner = self.inner(arg)?;
+            let is_single = match (&inner, &target) {
+                (&TypeInner::Scalar(_), _) => true,
+                (
+                    &TypeInner::Vector { size, .. },
+                    &TypeInner::Vector {
+                        size: target_size, ..
+                    },
+                ) => size >= target_size,
+                (&TypeInner::Matrix { .. }, &TypeInner::Matrix { .. }) => true,
+                _ => false,
+            };
+            if is_single {
+                return self.convert(arg, ty, arg_span);
+            }
+        }
+
+        let (scalar, size, columns) = match target {
+            TypeInner::Vector { size, scalar } => (scalar, size as usize, None),
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => (scalar, rows as usize, Some(columns as usize)),
+            _ => {
+                return self.error(
+                    ErrorKind::SemanticError(
+                        format!(
+                            "cannot construct a `{}`",
+                            super::type_name(&target, &self.lowerer.module.types)
+                        )
+                        .into(),
+                    ),
+                    span,
+                )
+            }
+        };
+
+        // The components of the vector, or the scalars of the matrix, as
+        // vectors and scalars of `scalar`.
+        let mut components = Vec::new();
+        let mut count = 0;
+        for &(arg, arg_span) in args {
+            match self.inner(arg)? {
+                TypeInner::Scalar(_) => {
+                    components.push(self.cast(arg, scalar, arg_span)?);
+                    count += 1;
+                }
+                TypeInner::Vector { size, .. } => {
+                    let arg = self.cast(arg, scalar, arg_span)?;
+                    match columns {
+                        None => components.push(arg),
+                        Some(_) => {
+                            for index in 0..size as u32 {
+                                components.push(
+                                    self.add(
+                                        Expression::AccessIndex { base: arg, index },
+                                        arg_span,
+                                    )?,
+                                );
+                            }
+                        }
+                    }
+                    count += size as usize;
+                }
+                inner => {
+                    return self.mismatch(&TypeInner::Scalar(scalar), &inner, arg_span);
+                }
+            }
+        }
+        let expected = size * columns.unwrap_or(1);
+        if count != expected {
+            return self.error(
+                ErrorKind::TypeMismatch {
+                    expected: format!("{expected} components"),
+                    found: count.to_string(),
+                },
+                span,
+            );
+        }
+
+        if columns.is_some() {
+            // Each row of the HLSL matrix is a column of the naga matrix.
+            let column_ty = self.lowerer.insert_type(TypeInner::Vector {
+                size: vector_size(size),
+                scalar,
+            });
+            let columns = components
+                .chunks(size)
+                .map(|chunk| {
+                    self.add(
+                        Expression::Compose {
+                            ty: column_ty,
+                            components: chunk.to_vec(),
+                        },
+                        span,
+                    )
+                })
+                .collect::<Result<Vec<_>>>()?;
+            return self.add(
+                Expression::Compose {
+                    ty,
+                    components: columns,
+                },
+                span,
+            );
+        }
+        self.add(Expression::Compose { ty, components }, span)
+    }
+
+    /// Lowers a binary operator, converting its operands as HLSL does.
+    pub fn binary(
+        &mut self,
+        op: BinaryOp,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        use crate::BinaryOperator as Bo;
+
+        let left_inner = self.inner(left)?;
+        let right_inner = self.inner(right)?;
+        let op = match op {
+            BinaryOp::Add => Bo::Add,
+            BinaryOp::Subtract => Bo::Subtract,
+            BinaryOp::Multiply => Bo::Multiply,
+            BinaryOp::Divide => Bo::Divide,
+            BinaryOp::Modulo => Bo::Modulo,
+            BinaryOp::Equal => Bo::Equal,
+            BinaryOp::NotEqual => Bo::NotEqual,
+            BinaryOp::Less => Bo::Less,
+            BinaryOp::LessEqual => Bo::LessEqual,
+            BinaryOp::Greater => Bo::Greater,
+            BinaryOp::GreaterEqual => Bo::GreaterEqual,
+            BinaryOp::And => Bo::And,
+            BinaryOp::ExclusiveOr => Bo::ExclusiveOr,
+            BinaryOp::InclusiveOr => Bo::InclusiveOr,
+            BinaryOp::LogicalAnd => Bo::LogicalAnd,
+            BinaryOp::LogicalOr => Bo::LogicalOr,
+            BinaryOp::ShiftLeft => Bo::ShiftLeft,
+            BinaryOp::ShiftRight => Bo::ShiftRight,
+        };
+
+        let (left, right) = match op {
+            Bo::LogicalAnd | Bo::LogicalOr => {
+                let common = self.common_type(&[left_inner, right_inner], span)?;
+                let target = with_scalar(&common, Scalar::BOOL);
+                (
+                    self.convert_to_inner(left, &target, None, span)?,
+                    self.convert_to_inner(right, &target, None, span)?,
+                )
+            }
+            Bo::ShiftLeft | Bo::ShiftRight => {
+                let left = match left_inner.scalar_kind() {
+                    Some(ScalarKind::Sint | ScalarKind::Uint) => left,
+                    _ => self.cast(left, Scalar::I32, span)?,
+                };
+                let target = with_scalar(&self.inner(left)?, Scalar::U32);
+                (left, self.convert_to_inner(right, &target, None, span)?)
+            }
+            Bo::Multiply
+                if is_matrix(&left_inner) != is_matrix(&right_inner)
+                    && matches!(left_inner, TypeInner::Scalar(_))
+                        != matches!(right_inner, TypeInner::Scalar(_)) =>
+            {
+                // A matrix and a scalar, which naga multiplies directly.
+                let common = self.common_type(&[left_inner, right_inner], span)?;
+                let scalar = common.scalar().unwrap();
+                (
+                    self.cast(left, scalar, span)?,
+                    self.cast(right, scalar, span)?,
+                )
+            }
+            Bo::Multiply if is_matrix(&left_inner) && is_matrix(&right_inner) => {
+                return self.error(
+                    ErrorKind::NotImplemented(
+                        "component-wise multiplication of matrices, `mul` multiplies matrices",
+                    ),
+                    span,
+                );
+            }
+            _ => {
+                let mut common = self.common_type(&[left_inner, right_inner], span)?;
+                // Arithmetic on booleans is done on integers.
+                if common.scalar_kind() == Some(ScalarKind::Bool)
+                    && !matches!(op, Bo::Equal | Bo::NotEqual | Bo::And | Bo::InclusiveOr)
+                {
+                    common = with_scalar(&common, Scalar::I32);
+                }
+                (
+                    self.convert_to_inner(left, &common, None, span)?,
+                    self.convert_to_inner(right, &common, None, span)?,
+                )
+            }
+        };
+        self.add(Expression::Binary { op, left, right }, span)
+    }
+}
+
+/// Returns the scalar that `a` and `b` are both converted to.
+///
+/// Booleans convert to integers, integers to unsigned integers, and both to
+/// floating point numbers.
+pub fn common_scalar(a: Scalar, b: Scalar) -> Scalar {
+    const fn rank(kind: ScalarKind) -> u8 {
+        match kind {
+            ScalarKind::Bool => 0,
+            ScalarKind::Sint | ScalarKind::AbstractInt => 1,
+            ScalarKind::Uint => 2,
+            ScalarKind::Float | ScalarKind::AbstractFloat => 3,
+        }
+    }
+    match rank(a.kind).cmp(&rank(b.kind)) {
+        std::cmp::Ordering::Less => b,
+        std::cmp::Ordering::Greater => a,
+        std::cmp::Ordering::Equal => match a.width >= b.width {
+            true => a,
+            false => b,
+        },
+    }
+}
+
+/// Returns `inner`, a scalar, vector or matrix, with its scalar replaced.
+pub const fn with_scalar(inner: &TypeInner, scalar: Scalar) -> TypeInner {
+    match *inner {
+        TypeInner::Vector { size, .. } => TypeInner::Vector { size, scalar },
+        TypeInner::Matrix { columns, rows, .. } => TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        },
+        _ => TypeInner::Scalar(scalar),
+    }
+}
+
+pub const fn is_matrix(inner: &TypeInner) -> bool {
+    matches!(*inner, TypeInner::Matrix { .. })
+}
+
+pub const fn vector_size(len: usize) -> VectorSize {
+    match len {
+        2 => VectorSize::Bi,
+        3 => VectorSize::Tri,
+        _ => VectorSize::Quad,
+    }
+}
+
+/// Returns an `int` vector of `size` components, or an `int` if `size` is 1.
+pub const fn int_vector(size: u32) -> TypeInner {
+    match size {
+        1 => TypeInner::Scalar(Scalar::I32),
+        size => TypeInner::Vector {
+            size: vector_size(size as usize),
+            scalar: Scalar::I32,
+        },
+    }
+}
+
+/// Returns the number of components of a coordinate of an image of `dim`.
+pub const fn dimension_count(dim: crate::ImageDimension) -> u32 {
+    match dim {
+        crate::ImageDimension::D1 => 1,
+        crate::ImageDimension::D2 => 2,
+        crate::ImageDimension::D3 | crate::ImageDimension::Cube => 3,
+    }
+}
+
+/// Returns the components selected by the swizzle `member` of a vector with
+/// `size` components.
+fn swizzle(member: ast::Ident, size: u32) -> Result<Vec<u32>> {
+    let invalid = || Error {
+        kind: ErrorKind::InvalidSwizzle(member.name.to_string()),
+        span: member.span,
+    };
+    if member.name.len() > 4 {
+        return Err(invalid());
+    }
+    let xyzw = member.name.chars().all(|c| "xyzw".contains(c));
+    let rgba = member.name.chars().all(|c| "rgba".contains(c));
+    let set = match (xyzw, rgba) {
+        (true, _) => "xyzw",
+        (_, true) => "rgba",
+        _ => return Err(invalid()),
+    };
+    member
+        .name
+        .chars()
+        .map(|c| set.find(c).unwrap() as u32)
+        .map(|index| match index < size {
+            true => Ok(index),
+            false => Err(invalid()),
+        })
+        .collect()
+}
+
+/// Returns the row and column selected by a matrix swizzle like `_m01` or
+/// `_12`.
+fn matrix_element(member: &str) -> Option<(u32, u32)> {
+    let digits = |s: &str, base: u32| {
+        let mut chars = s.chars();
+        let row = chars.next()?.to_digit(10)?.checked_sub(base)?;
+        let column = chars.next()?.to_digit(10)?.checked_sub(base)?;
+        match chars.next() {
+            Some(_) => None,
+            None => Some((row, column)),
+        }
+    };
+    match member.strip_prefix("_m") {
+        Some(rest) => digits(rest, 0),
+        None => digits(member.strip_prefix('_')?, 1),
+    }
+}
+
+const fn literal_is_zero(literal: Literal) -> bool {
+    match literal {
+        Literal::I32(0) | Literal::U32(0) | Literal::I64(0) | Literal::U64(0) => true,
+        Literal::F32(value) => value == 0.0,
+        Literal::F64(value) => value == 0.0,
+        _ => false,
+    }
+}
@@ -0,0 +1,116 @@
+use crate::{proc::ConstantEvaluatorError, SourceLocation, Span};
+use codespan_reporting::diagnostic::{Diagnostic, Label};
+use codespan_reporting::files::SimpleFile;
+use codespan_reporting::term;
+use std::borrow::Cow;
+use termcolor::{NoColor, WriteColor};
+use thiserror::Error;
+
+/// Information about the cause of an [`Error`].
+#[derive(Clone, Debug, Error)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum ErrorKind {
+    #[error("unexpected end of file")]
+    UnexpectedEndOfFile,
+    #[error("unexpected character `{0}`")]
+    UnexpectedCharacter(char),
+    #[error("invalid number")]
+    InvalidNumber,
+    #[error("expected {expected}, found {found}")]
+    UnexpectedToken {
+        expected: Cow<'static, str>,
+        found: String,
+    },
+    #[error("unknown identifier `{0}`")]
+    UnknownIdentifier(String),
+    #[error("unknown type `{0}`")]
+    UnknownType(String),
+    #[error("unknown function `{0}`")]
+    UnknownFunction(String),
+    #[error("no member named `{0}`")]
+    UnknownMember(String),
+    #[error("invalid swizzle `{0}`")]
+    InvalidSwizzle(String),
+    #[error("unknown semantic `{0}`")]
+    UnknownSemantic(String),
+    #[error("unknown attribute `{0}`")]
+    UnknownAttribute(String),
+    #[error("`{0}` is already defined")]
+    Redefinition(String),
+    #[error("invalid register `{0}`")]
+    InvalidRegister(String),
+    #[error("`{0}` is bound to group {1}, binding {2}, which is already used by another resource")]
+    BindingCollision(String, u32, u32),
+    #[error("expected {expected}, found {found}")]
+    TypeMismatch { expected: String, found: String },
+    #[error("`{function}` expects {expected} arguments, found {found}")]
+    WrongArgumentCount {
+        function: String,
+        expected: usize,
+        found: usize,
+    },
+    #[error("cannot assign to this expression")]
+    InvalidAssignment,
+    #[error("expected a constant expression")]
+    NotConstant,
+    #[error("{0}")]
+    SemanticError(Cow<'static, str>),
+    #[error("not implemented: {0}")]
+    NotImplemented(&'static str),
+}
+
+impl From<ConstantEvaluatorError> for ErrorKind {
+    fn from(err: ConstantEvaluatorError) -> Self {
+        ErrorKind::SemanticError(err.to_string().into())
+    }
+}
+
+/// An error returned by the HLSL front end.
+#[derive(Clone, Debug, Error)]
+#[error("{kind}")]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Error {
+    /// The cause of the error.
+    pub kind: ErrorKind,
+    /// The range of the source the error points to.
+    pub span: Span,
+}
+
+impl Error {
+    /// Returns a [`SourceLocation`] for the error message.
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        Some(self.span.location(source))
+    }
+
+    pub fn emit_to_writer(&self, writer: &mut impl WriteColor, source: &str) {
+        self.emit_to_writer_with_path(writer, source, "hlsl");
+    }
+
+    pub fn emit_to_writer_with_path(&self, writer: &mut impl WriteColor, source: &str, path: &str) {
+        let path = path.to_string();
+        let files = SimpleFile::new(path, source);
+        let config = term::Config::default();
+
+        let mut diagnostic = Diagnostic::error().with_message(self.kind.to_string());
+        if let Some(range) = self.span.to_range() {
+            diagnostic = diagnostic.with_labels(vec![Label::primary((), range)]);
+        }
+        if let ErrorKind::BindingCollision(..) = self.kind {
+            diagnostic = diagnostic.with_notes(vec![
+                "registers of different classes need shifts to get different bindings".to_string(),
+            ]);
+        }
+
+        term::emit(writer, &config, &files, &diagnostic).expect("cannot write error");
+    }
+
+    pub fn emit_to_string(&self, source: &str) -> String {
+        self.emit_to_string_with_path(source, "hlsl")
+    }
+
+    pub fn emit_to_string_with_path(&self, source: &str, path: &str) -> String {
+        let mut writer = NoColor::new(Vec::new());
+        self.emit_to_writer_with_path(&mut writer, source, path);
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+}
@@ -0,0 +1,1138 @@
+//! Lowering calls of functions, intrinsics and resource methods.
+
+use super::ast::{self, BinaryOp, Direction};
+use super::context::{
+    common_scalar, dimension_count, int_vector, vector_size, with_scalar, Context, Place,
+};
+use super::error::ErrorKind;
+use super::Result;
+use crate::{
+    Expression, Handle, ImageClass, Literal, MathFunction as Mf, Scalar, ScalarKind, Span,
+    Statement, TypeInner,
+};
+
+impl<'a> Context<'_, 'a> {
+    /// Lowers a call of a function or intrinsic, returning its result, if it
+    /// has one.
+    pub fn call(
+        &mut self,
+        function: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        if self.lowerer.functions.contains_key(function.name) {
+            if self.is_const {
+                return self.error(ErrorKind::NotConstant, span);
+            }
+            return self.user_call(function, args, span);
+        }
+        self.intrinsic(function, args, span)
+    }
+
+    fn user_call(
+        &mut self,
+        function: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let overloads = &self.lowerer.functions[function.name];
+        let candidates = overloads
+            .iter()
+            .enumerate()
+            .filter(|&(_, info)| info.params.len() == args.len())
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        if candidates.is_empty() {
+            return self.error(
+                ErrorKind::WrongArgumentCount {
+                    function: function.name.to_string(),
+                    expected: overloads[0].params.len(),
+                    found: args.len(),
+                },
+                span,
+            );
+        }
+
+        let places = args
+            .iter()
+            .map(|arg| Ok((self.place(arg)?, arg.span)))
+            .collect::<Result<Vec<_>>>()?;
+
+        // Prefer the overload whose parameters have the types of the
+        // arguments, and otherwise take the first one with the right number of
+        // parameters.
+        let mut chosen = candidates[0];
+        let mut values = vec![None; places.len()];
+        if candidates.len() > 1 {
+            let mut types = Vec::with_capacity(places.len());
+            for (value, &(ref place, arg_span)) in values.iter_mut().zip(places.iter()) {
+                let loaded = self.load(place.clone(), arg_span)?;
+                *value = Some(loaded);
+                types.push(self.inner(loaded)?);
+            }
+            let module = &self.lowerer.module;
+            let overloads = &self.lowerer.functions[function.name];
+            if let Some(&index) = candidates.iter().find(|&&index| {
+                overloads[index]
+                    .params
+                    .iter()
+                    .zip(types.iter())
+                    .all(|(&(ty, _), inner)| {
+                        module.types[ty].inner.equivalent(inner, &module.types)
+                    })
+            }) {
+                chosen = index;
+            }
+        }
+
+        let info = &self.lowerer.functions[function.name][chosen];
+        let handle = info.handle;
+        let has_result = info.result.is_some();
+        let params = info.params.clone();
+
+        let mut arguments = Vec::with_capacity(args.len());
+        let mut write_backs = Vec::new();
+        for (((place, arg_span), value), (ty, direction)) in
+            places.into_iter().zip(values).zip(params)
+        {
+            match direction {
+                Direction::In => {
+                    let value = match value {
+                        Some(value) => value,
+                        None => self.load(place, arg_span)?,
+                    };
+                    arguments.push(self.convert(value, ty, arg_span)?);
+                }
+                Direction::Out | Direction::InOut => {
+                    if let Place::Value(_) = place {
+                        return self.error(ErrorKind::InvalidAssignment, arg_span);
+                    }
+                    // Arguments are passed by a pointer to a temporary, which
+                    // is copied back to the argument after the call.
+                    let temporary = self.temporary(ty, arg_span)?;
+                    if direction == Direction::InOut {
+                        let value = self.load(place.clone(), arg_span)?;
+                        let target = Place::Pointer {
+                            pointer: temporary,
+                            transposed: false,
+                        };
+                        self.store(target, value, arg_span)?;
+                    }
+                    arguments.push(temporary);
+                    write_backs.push((place, temporary, arg_span));
+                }
+            }
+        }
+
+        let result = match has_result {
+            true => Some(self.interrupt_emitter(Expression::CallResult(handle), span)?),
+            false => {
+                self.emit_restart();
+                None
+            }
+        };
+        self.body.push(
+            Statement::Call {
+                function: handle,
+                arguments,
+                result,
+            },
+            span,
+        );
+        for (place, temporary, arg_span) in write_backs {
+            let value = self.add(Expression::Load { pointer: temporary }, arg_span)?;
+            self.store(place, value, arg_span)?;
+        }
+        Ok(result)
+    }
+
+    /// Lowers the arguments of an intrinsic, checking their number.
+    fn args(
+        &mut self,
+        function: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        expected: usize,
+    ) -> Result<Vec<Handle<Expression>>> {
+        if args.len() != expected {
+            return self.error(
+                ErrorKind::WrongArgumentCount {
+                    function: function.name.to_string(),
+                    expected,
+                    found: args.len(),
+                },
+                function.span,
+            );
+        }
+        args.iter().map(|arg| self.value(arg)).collect()
+    }
+
+    /// Converts `args` to their common type, and to floating point if
+    /// `float` is set.
+    fn unify(
+        &mut self,
+        args: Vec<Handle<Expression>>,
+        float: bool,
+        span: Span,
+    ) -> Result<Vec<Handle<Expression>>> {
+        let types = args
+            .iter()
+            .map(|&arg| self.inner(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let mut common = self.common_type(&types, span)?;
+        let scalar = common.scalar().unwrap();
+        if float && scalar.kind != ScalarKind::Float {
+            common = with_scalar(&common, Scalar::F32);
+        } else if scalar.kind == ScalarKind::Bool {
+            common = with_scalar(&common, Scalar::I32);
+        }
+        args.into_iter()
+            .map(|arg| self.convert_to_inner(arg, &common, None, span))
+            .collect()
+    }
+
+    fn math(
+        &mut self,
+        fun: Mf,
+        args: &[Handle<Expression>],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        self.add(
+            Expression::Math {
+                fun,
+                arg: args[0],
+                arg1: args.get(1).copied(),
+                arg2: args.get(2).copied(),
+                arg3: args.get(3).copied(),
+            },
+            span,
+        )
+    }
+
+    fn intrinsic(
+        &mut self,
+        function: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        // Functions on floating point numbers, which convert integers.
+        let float_math = match function.name {
+            "sin" => Some((Mf::Sin, 1)),
+            "cos" => Some((Mf::Cos, 1)),
+            "tan" => Some((Mf::Tan, 1)),
+            "asin" => Some((Mf::Asin, 1)),
+            "acos" => Some((Mf::Acos, 1)),
+            "atan" => Some((Mf::Atan, 1)),
+            "sinh" => Some((Mf::Sinh, 1)),
+            "cosh" => Some((Mf::Cosh, 1)),
+            "tanh" => Some((Mf::Tanh, 1)),
+            "exp" => Some((Mf::Exp, 1)),
+            "exp2" => Some((Mf::Exp2, 1)),
+            "log" => Some((Mf::Log, 1)),
+            "log2" => Some((Mf::Log2, 1)),
+            "sqrt" => Some((Mf::Sqrt, 1)),
+            "rsqrt" => Some((Mf::InverseSqrt, 1)),
+            "ceil" => Some((Mf::Ceil, 1)),
+            "floor" => Some((Mf::Floor, 1)),
+            "round" => Some((Mf::Round, 1)),
+            "trunc" => Some((Mf::Trunc, 1)),
+            "frac" => Some((Mf::Fract, 1)),
+            "saturate" => Some((Mf::Saturate, 1)),
+            "degrees" => Some((Mf::Degrees, 1)),
+            "radians" => Some((Mf::Radians, 1)),
+            "normalize" => Some((Mf::Normalize, 1)),
+            "length" => Some((Mf::Length, 1)),
+            "determinant" => Some((Mf::Determinant, 1)),
+            "transpose" => Some((Mf::Transpose, 1)),
+            "pow" => Some((Mf::Pow, 2)),
+            "atan2" => Some((Mf::Atan2, 2)),
+            "step" => Some((Mf::Step, 2)),
+            "distance" => Some((Mf::Distance, 2)),
+            "cross" => Some((Mf::Cross, 2)),
+            "reflect" => Some((Mf::Reflect, 2)),
+            "lerp" => Some((Mf::Mix, 3)),
+            "smoothstep" => Some((Mf::SmoothStep, 3)),
+            "faceforward" => Some((Mf::FaceForward, 3)),
+            _ => None,
+        };
+        if let Some((fun, count)) = float_math {
+            let args = self.args(function, args, count)?;
+            let args = self.unify(args, true, span)?;
+            return self.math(fun, &args, span).map(Some);
+        }
+
+        // Functions on any numbers.
+        let any_math = match function.name {
+            "abs" => Some((Mf::Abs, 1)),
+            "min" => Some((Mf::Min, 2)),
+            "max" => Some((Mf::Max, 2)),
+            "clamp" => Some((Mf::Clamp, 3)),
+            "countbits" => Some((Mf::CountOneBits, 1)),
+            "reversebits" => Some((Mf::ReverseBits, 1)),
+            "firstbithigh" => Some((Mf::FirstLeadingBit, 1)),
+            "firstbitlow" => Some((Mf::FirstTrailingBit, 1)),
+            _ => None,
+        };
+        if let Some((fun, count)) = any_math {
+            let args = self.args(function, args, count)?;
+            let args = self.unify(args, false, span)?;
+            return self.math(fun, &args, span).map(Some);
+        }
+
+        let value = match function.name {
+            "sign" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, false, span)?;
+                let sign = self.math(Mf::Sign, &args, span)?;
+                self.cast(sign, Scalar::I32, span)?
+            }
+            "dot" => {
+                let args = self.args(function, args, 2)?;
+                let args = self.unify(args, false, span)?;
+                match self.inner(args[0])? {
+                    TypeInner::Vector { .. } => self.math(Mf::Dot, &args, span)?,
+                    _ => self.binary(BinaryOp::Multiply, args[0], args[1], span)?,
+                }
+            }
+            "refract" => {
+                let args = self.args(function, args, 3)?;
+                let mut vectors = self.unify(args[..2].to_vec(), true, span)?;
+                let scalar = self.inner(vectors[0])?.scalar().unwrap();
+                let eta = self.convert_to_inner(args[2], &TypeInner::Scalar(scalar), None, span)?;
+                vectors.push(eta);
+                self.math(Mf::Refract, &vectors, span)?
+            }
+            "fmod" => {
+                let args = self.args(function, args, 2)?;
+                let args = self.unify(args, true, span)?;
+                self.binary(BinaryOp::Modulo, args[0], args[1], span)?
+            }
+            "rcp" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, true, span)?;
+                let one = self.literal_expression(Literal::F32(1.0), span)?;
+                self.binary(BinaryOp::Divide, one, args[0], span)?
+            }
+            "log10" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, true, span)?;
+                let log2 = self.math(Mf::Log2, &args, span)?;
+                let factor =
+                    self.literal_expression(Literal::F32(std::f32::consts::LOG10_2), span)?;
+                self.binary(BinaryOp::Multiply, log2, factor, span)?
+            }
+            "mad" => {
+                let args = self.args(function, args, 3)?;
+                let args = self.unify(args, false, span)?;
+                match self.inner(args[0])?.scalar_kind() {
+                    Some(ScalarKind::Float) => self.math(Mf::Fma, &args, span)?,
+                    _ => {
+                        let product = self.binary(BinaryOp::Multiply, args[0], args[1], span)?;
+                        self.binary(BinaryOp::Add, product, args[2], span)?
+                    }
+                }
+            }
+            "ldexp" => {
+                let args = self.args(function, args, 2)?;
+                let x = self.unify(vec![args[0]], true, span)?[0];
+                let target = with_scalar(&self.inner(x)?, Scalar::I32);
+                let exponent = self.convert_to_inner(args[1], &target, None, span)?;
+                self.math(Mf::Ldexp, &[x, exponent], span)?
+            }
+            "mul" => {
+                let args = self.args(function, args, 2)?;
+                self.mul(args[0], args[1], span)?
+            }
+            "all" | "any" => {
+                let args = self.args(function, args, 1)?;
+                let value = self.cast_to_bool(args[0], span)?;
+                match self.inner(value)? {
+                    TypeInner::Vector { .. } => self.add(
+                        Expression::Relational {
+                            fun: match function.name {
+                                "all" => crate::RelationalFunction::All,
+                                _ => crate::RelationalFunction::Any,
+                            },
+                            argument: value,
+                        },
+                        span,
+                    )?,
+                    TypeInner::Scalar(_) => value,
+                    _ => {
+                        return self.error(
+                            ErrorKind::NotImplemented("`all` and `any` of matrices"),
+                            span,
+                        )
+                    }
+                }
+            }
+            "isnan" | "isinf" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, true, span)?;
+                self.add(
+                    Expression::Relational {
+                        fun: match function.name {
+                            "isnan" => crate::RelationalFunction::IsNan,
+                            _ => crate::RelationalFunction::IsInf,
+                        },
+                        argument: args[0],
+                    },
+                    span,
+                )?
+            }
+            "asfloat" | "asint" | "asuint" => {
+                let args = self.args(function, args, 1)?;
+                let kind = match function.name {
+                    "asfloat" => ScalarKind::Float,
+                    "asint" => ScalarKind::Sint,
+                    _ => ScalarKind::Uint,
+                };
+                match self.inner(args[0])?.scalar_kind() {
+                    Some(source) if source == kind => args[0],
+                    _ => self.add(
+                        Expression::As {
+                            expr: args[0],
+                            kind,
+                            convert: None,
+                        },
+                        span,
+                    )?,
+                }
+            }
+            "ddx" | "ddx_coarse" | "ddx_fine" | "ddy" | "ddy_coarse" | "ddy_fine" | "fwidth" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, true, span)?;
+                let axis = match function.name.as_bytes()[..3] {
+                    [b'd', b'd', b'x'] => crate::DerivativeAxis::X,
+                    [b'd', b'd', b'y'] => crate::DerivativeAxis::Y,
+                    _ => crate::DerivativeAxis::Width,
+                };
+                let ctrl = match function.name.get(3..) {
+                    Some("_coarse") => crate::DerivativeControl::Coarse,
+                    Some("_fine") => crate::DerivativeControl::Fine,
+                    _ => crate::DerivativeControl::None,
+                };
+                self.add(
+                    Expression::Derivative {
+                        axis,
+                        ctrl,
+                        expr: args[0],
+                    },
+                    span,
+                )?
+            }
+            "sincos" => {
+                if args.len() != 3 {
+                    return self.error(
+                        ErrorKind::WrongArgumentCount {
+                            function: function.name.to_string(),
+                            expected: 3,
+                            found: args.len(),
+                        },
+                        span,
+                    );
+                }
+                let x = self.value(&args[0])?;
+                let x = self.unify(vec![x], true, span)?[0];
+                let sin_place = self.place(&args[1])?;
+                let cos_place = self.place(&args[2])?;
+                let sin = self.math(Mf::Sin, &[x], span)?;
+                let cos = self.math(Mf::Cos, &[x], span)?;
+                self.store(sin_place, sin, span)?;
+                self.store(cos_place, cos, span)?;
+                return Ok(None);
+            }
+            "modf" | "frexp" => {
+                if args.len() != 2 {
+                    return self.error(
+                        ErrorKind::WrongArgumentCount {
+                            function: function.name.to_string(),
+                            expected: 2,
+                            found: args.len(),
+                        },
+                        span,
+                    );
+                }
+                let x = self.value(&args[0])?;
+                let x = self.unify(vec![x], true, span)?[0];
+                let place = self.place(&args[1])?;
+                let (size, scalar) = match self.inner(x)? {
+                    TypeInner::Vector { size, scalar } => (Some(size), scalar),
+                    inner => (None, inner.scalar().unwrap()),
+                };
+                let (fun, predeclared) = match function.name {
+                    "modf" => (
+                        Mf::Modf,
+                        crate::PredeclaredType::ModfResult { size, scalar },
+                    ),
+                    _ => (
+                        Mf::Frexp,
+                        crate::PredeclaredType::FrexpResult { size, scalar },
+                    ),
+                };
+                self.lowerer.module.generate_predeclared_type(predeclared);
+                let result = self.math(fun, &[x], span)?;
+                let fract = self.add(
+                    Expression::AccessIndex {
+                        base: result,
+                        index: 0,
+                    },
+                    span,
+                )?;
+                let second = self.add(
+                    Expression::AccessIndex {
+                        base: result,
+                        index: 1,
+                    },
+                    span,
+                )?;
+                self.store(place, second, span)?;
+                fract
+            }
+            "clip" => {
+                let args = self.args(function, args, 1)?;
+                let args = self.unify(args, true, span)?;
+                let zero = self.literal_expression(Literal::F32(0.0), span)?;
+                let negative = self.binary(BinaryOp::Less, args[0], zero, span)?;
+                let condition = match self.inner(negative)? {
+                    TypeInner::Vector { .. } => self.add(
+                        Expression::Relational {
+                            fun: crate::RelationalFunction::Any,
+                            argument: negative,
+                        },
+                        span,
+                    )?,
+                    _ => negative,
+                };
+                self.emit_restart();
+                self.body.push(
+                    Statement::If {
+                        condition,
+                        accept: crate::Block::from_vec(vec![Statement::Kill]),
+                        reject: crate::Block::new(),
+                    },
+                    span,
+                );
+                return Ok(None);
+            }
+            "GroupMemoryBarrier"
+            | "GroupMemoryBarrierWithGroupSync"
+            | "DeviceMemoryBarrier"
+            | "DeviceMemoryBarrierWithGroupSync"
+            | "AllMemoryBarrier"
+            | "AllMemoryBarrierWithGroupSync" => {
+                self.args(function, args, 0)?;
+                let barrier = match function.name.as_bytes()[0] {
+                    b'G' => crate::Barrier::WORK_GROUP,
+                    b'D' => crate::Barrier::STORAGE,
+                    _ => crate::Barrier::STORAGE | crate::Barrier::WORK_GROUP,
+                };
+                self.emit_restart();
+                self.body.push(Statement::Barrier(barrier), span);
+                return Ok(None);
+            }
+            "InterlockedAdd"
+            | "InterlockedAnd"
+            | "InterlockedOr"
+            | "InterlockedXor"
+            | "InterlockedMin"
+            | "InterlockedMax"
+            | "InterlockedExchange"
+            | "InterlockedCompareExchange"
+            | "InterlockedCompareStore" => {
+                self.interlocked(function, args, span)?;
+                return Ok(None);
+            }
+            _ => return self.error(ErrorKind::UnknownFunction(function.name.to_string()), span),
+        };
+        Ok(Some(value))
+    }
+
+    /// Lowers `InterlockedAdd(dest, value, original)` and the other
+    /// `Interlocked*` intrinsics.
+    ///
+    /// `original`, which is assigned the value `dest` had before the
+    /// operation, is optional, except for `InterlockedExchange` and
+    /// `InterlockedCompareExchange`. `InterlockedCompareStore` has none.
+    fn interlocked(
+        &mut self,
+        function: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<()> {
+        let (operands, has_original) = match function.name {
+            "InterlockedCompareExchange" => (2, true),
+            "InterlockedCompareStore" => (2, false),
+            "InterlockedExchange" => (1, true),
+            _ => (1, args.len() == 3),
+        };
+        let expected = 1 + operands + has_original as usize;
+        if args.len() != expected {
+            return self.error(
+                ErrorKind::WrongArgumentCount {
+                    function: function.name.to_string(),
+                    expected,
+                    found: args.len(),
+                },
+                span,
+            );
+        }
+
+        let pointer = match self.place(&args[0])? {
+            Place::Pointer { pointer, .. } => Some(pointer),
+            _ => None,
+        };
+        let scalar = match pointer {
+            Some(pointer) => match self.inner(pointer)? {
+                TypeInner::Pointer { base, .. } => match self.lowerer.module.types[base].inner {
+                    TypeInner::Atomic(scalar) => Some(scalar),
+                    _ => None,
+                },
+                _ => None,
+            },
+            None => None,
+        };
+        let (Some(pointer), Some(scalar)) = (pointer, scalar) else {
+            return self.error(
+                ErrorKind::SemanticError(
+                    "the destination of an `Interlocked*` intrinsic must be an `int` or \
+                     `uint` in a `RWStructuredBuffer` or `groupshared` variable"
+                        .into(),
+                ),
+                args[0].span,
+            );
+        };
+
+        let mut values = Vec::with_capacity(operands);
+        for arg in &args[1..=operands] {
+            let value = self.value(arg)?;
+            values.push(self.convert_to_inner(
+                value,
+                &TypeInner::Scalar(scalar),
+                None,
+                arg.span,
+            )?);
+        }
+        let value = values[operands - 1];
+        let fun = match function.name {
+            "InterlockedAdd" => crate::AtomicFunction::Add,
+            "InterlockedAnd" => crate::AtomicFunction::And,
+            "InterlockedOr" => crate::AtomicFunction::InclusiveOr,
+            "InterlockedXor" => crate::AtomicFunction::ExclusiveOr,
+            "InterlockedMin" => crate::AtomicFunction::Min,
+            "InterlockedMax" => crate::AtomicFunction::Max,
+            "InterlockedExchange" => crate::AtomicFunction::Exchange { compare: None },
+            _ => crate::AtomicFunction::Exchange {
+                compare: Some(values[0]),
+            },
+        };
+
+        // Comparing exchanges always have a result, even if it is unused.
+        let comparison = operands == 2;
+        let result = match (comparison, has_original) {
+            (true, _) => {
+                let ty = self.lowerer.module.generate_predeclared_type(
+                    crate::PredeclaredType::AtomicCompareExchangeWeakResult(scalar),
+                );
+                Some(self.interrupt_emitter(Expression::AtomicResult { ty, comparison }, span)?)
+            }
+            (false, true) => {
+                let ty = self.lowerer.insert_type(TypeInner::Scalar(scalar));
+                Some(self.interrupt_emitter(Expression::AtomicResult { ty, comparison }, span)?)
+            }
+            (false, false) => {
+                self.emit_restart();
+                None
+            }
+        };
+        self.body.push(
+            Statement::Atomic {
+                pointer,
+                fun,
+                value,
+                result,
+            },
+            span,
+        );
+
+        if let (true, Some(result)) = (has_original, result) {
+            let original = self.place(&args[expected - 1])?;
+            let value = match comparison {
+                true => self.add(
+                    Expression::AccessIndex {
+                        base: result,
+                        index: 0,
+                    },
+                    span,
+                )?,
+                false => result,
+            };
+            self.store(original, value, args[expected - 1].span)?;
+        }
+        Ok(())
+    }
+
+    /// Lowers `mul(left, right)`.
+    ///
+    /// An HLSL matrix is the transpose of the naga matrix it is lowered to,
+    /// so the operands of matrix products are swapped.
+    fn mul(
+        &mut self,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let left_inner = self.inner(left)?;
+        let right_inner = self.inner(right)?;
+        let (Some(left_scalar), Some(right_scalar)) = (left_inner.scalar(), right_inner.scalar())
+        else {
+            return self.error(ErrorKind::NotImplemented("`mul` of these types"), span);
+        };
+        let scalar = common_scalar(left_scalar, right_scalar);
+        let left = self.cast(left, scalar, span)?;
+        let right = self.cast(right, scalar, span)?;
+        let product = |ctx: &mut Self, left, right| {
+            ctx.add(
+                Expression::Binary {
+                    op: crate::BinaryOperator::Multiply,
+                    left,
+                    right,
+                },
+                span,
+            )
+        };
+        match (&left_inner, &right_inner) {
+            (&TypeInner::Scalar(_), _) | (_, &TypeInner::Scalar(_)) => {
+                self.binary(BinaryOp::Multiply, left, right, span)
+            }
+            (&TypeInner::Vector { .. }, &TypeInner::Vector { .. }) => {
+                let args = self.unify(vec![left, right], false, span)?;
+                self.math(Mf::Dot, &args, span)
+            }
+            (&TypeInner::Vector { .. }, &TypeInner::Matrix { .. })
+            | (&TypeInner::Matrix { .. }, &TypeInner::Vector { .. })
+            | (&TypeInner::Matrix { .. }, &TypeInner::Matrix { .. }) => product(self, right, left),
+            _ => self.error(ErrorKind::NotImplemented("`mul` of these types"), span),
+        }
+    }
+
+    /// Lowers a method call, like `texture.Sample(sampler, uv)`.
+    pub fn method_call(
+        &mut self,
+        base: &ast::Expr<'a>,
+        method: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let place = self.place(base)?;
+        if let Place::Pointer { pointer, .. } = place {
+            if let TypeInner::Pointer { base: ty, .. } = self.inner(pointer)? {
+                if let TypeInner::Array {
+                    size: crate::ArraySize::Dynamic,
+                    stride,
+                    ..
+                } = self.lowerer.module.types[ty].inner
+                {
+                    return self.buffer_method(place, stride, method, args, span);
+                }
+            }
+        }
+        let image = self.load(place, base.span)?;
+        let TypeInner::Image {
+            dim,
+            arrayed,
+            class,
+        } = self.inner(image)?
+        else {
+            return self.error(
+                ErrorKind::UnknownFunction(method.name.to_string()),
+                method.span,
+            );
+        };
+        let image = ImageInfo {
+            handle: image,
+            dim,
+            arrayed,
+            class,
+        };
+
+        match method.name {
+            "Sample" | "SampleLevel" | "SampleBias" | "SampleGrad" | "SampleCmp"
+            | "SampleCmpLevelZero" | "Gather" | "GatherRed" | "GatherGreen" | "GatherBlue"
+            | "GatherAlpha" | "GatherCmp" => self.sample(&image, method, args, span).map(Some),
+            "Load" => self.image_load(&image, method, args, span).map(Some),
+            "GetDimensions" => {
+                self.image_dimensions(&image, method, args, span)?;
+                Ok(None)
+            }
+            _ => self.error(
+                ErrorKind::UnknownFunction(method.name.to_string()),
+                method.span,
+            ),
+        }
+    }
+
+    /// Lowers a method of a `StructuredBuffer`.
+    fn buffer_method(
+        &mut self,
+        buffer: Place,
+        stride: u32,
+        method: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Option<Handle<Expression>>> {
+        let Place::Pointer {
+            pointer,
+            transposed,
+        } = buffer
+        else {
+            unreachable!()
+        };
+        match method.name {
+            "Load" => {
+                let args = self.args(method, args, 1)?;
+                let index =
+                    self.convert_to_inner(args[0], &TypeInner::Scalar(Scalar::U32), None, span)?;
+                let element = self.add(
+                    Expression::Access {
+                        base: pointer,
+                        index,
+                    },
+                    span,
+                )?;
+                let place = Place::Pointer {
+                    pointer: element,
+                    transposed,
+                };
+                self.load(place, span).map(Some)
+            }
+            "GetDimensions" => {
+                if args.len() != 2 {
+                    return self.error(
+                        ErrorKind::WrongArgumentCount {
+                            function: method.name.to_string(),
+                            expected: 2,
+                            found: args.len(),
+                        },
+                        span,
+                    );
+                }
+                let count_place = self.place(&args[0])?;
+                let stride_place = self.place(&args[1])?;
+                let count = self.add(Expression::ArrayLength(pointer), span)?;
+                let stride = self.literal_expression(Literal::U32(stride), span)?;
+                self.store(count_place, count, span)?;
+                self.store(stride_place, stride, span)?;
+                Ok(None)
+            }
+            _ => self.error(
+                ErrorKind::UnknownFunction(method.name.to_string()),
+                method.span,
+            ),
+        }
+    }
+
+    /// Lowers the coordinate of `image` in `arg`, which has `scalar` components
+    /// and `extra` components after the coordinate and the array index.
+    fn coordinate(
+        &mut self,
+        image: &ImageInfo,
+        arg: &ast::Expr<'a>,
+        scalar: Scalar,
+        extra: u32,
+    ) -> Result<Coordinate> {
+        let span = arg.span;
+        let size = dimension_count(image.dim);
+        let total = size + u32::from(image.arrayed) + extra;
+        let value = self.value(arg)?;
+        let target = match total {
+            1 => TypeInner::Scalar(scalar),
+            total => TypeInner::Vector {
+                size: vector_size(total as usize),
+                scalar,
+            },
+        };
+        let value = self.convert_to_inner(value, &target, None, span)?;
+        let component =
+            |ctx: &mut Self, index| ctx.add(Expression::AccessIndex { base: value, index }, span);
+        let coordinate = match (size, total) {
+            (size, total) if size == total => value,
+            (1, _) => component(self, 0)?,
+            (size, _) => self.add(
+                Expression::Swizzle {
+                    size: vector_size(size as usize),
+                    vector: value,
+                    pattern: crate::SwizzleComponent::XYZW,
+                },
+                span,
+            )?,
+        };
+        let array_index = match image.arrayed {
+            true => {
+                let index = component(self, size)?;
+                let index = match scalar.kind {
+                    ScalarKind::Float => self.math(Mf::Round, &[index], span)?,
+                    _ => index,
+                };
+                Some(self.cast(index, Scalar::I32, span)?)
+            }
+            false => None,
+        };
+        let extra = match extra {
+            0 => None,
+            _ => Some(component(self, total - 1)?),
+        };
+        Ok(Coordinate {
+            coordinate,
+            array_index,
+            extra,
+        })
+    }
+
+    /// Lowers the constant texel offset `arg`.
+    fn offset(&mut self, image: &ImageInfo, arg: &ast::Expr<'a>) -> Result<Handle<Expression>> {
+        let mut ctx = self.as_const();
+        let value = ctx.value(arg)?;
+        let target = int_vector(dimension_count(image.dim));
+        ctx.convert_to_inner(value, &target, None, arg.span)
+    }
+
+    fn sample(
+        &mut self,
+        image: &ImageInfo,
+        method: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        // The number of arguments before the offset.
+        let (count, gather, compare) = match method.name {
+            "Sample" => (2, None, false),
+            "SampleLevel" | "SampleBias" => (3, None, false),
+            "SampleGrad" => (4, None, false),
+            "SampleCmp" | "SampleCmpLevelZero" => (3, None, true),
+            "Gather" | "GatherRed" => (2, Some(crate::SwizzleComponent::X), false),
+            "GatherGreen" => (2, Some(crate::SwizzleComponent::Y), false),
+            "GatherBlue" => (2, Some(crate::SwizzleComponent::Z), false),
+            "GatherAlpha" => (2, Some(crate::SwizzleComponent::W), false),
+            _ => (3, Some(crate::SwizzleComponent::X), true),
+        };
+        if args.len() != count && args.len() != count + 1 {
+            return self.error(
+                ErrorKind::WrongArgumentCount {
+                    function: method.name.to_string(),
+                    expected: count,
+                    found: args.len(),
+                },
+                span,
+            );
+        }
+        let sampler = self.value(&args[0])?;
+        let Coordinate {
+            coordinate,
+            array_index,
+            ..
+        } = self.coordinate(image, &args[1], Scalar::F32, 0)?;
+        let float = |ctx: &mut Self, arg: &ast::Expr<'a>, target: &TypeInner| {
+            let value = ctx.value(arg)?;
+            ctx.convert_to_inner(value, target, None, arg.span)
+        };
+        let f32 = TypeInner::Scalar(Scalar::F32);
+        let level = match method.name {
+            "SampleLevel" => crate::SampleLevel::Exact(float(self, &args[2], &f32)?),
+            "SampleBias" => crate::SampleLevel::Bias(float(self, &args[2], &f32)?),
+            "SampleGrad" => {
+                let size = dimension_count(image.dim);
+                let target = match size {
+                    1 => f32.clone(),
+                    size => TypeInner::Vector {
+                        size: vector_size(size as usize),
+                        scalar: Scalar::F32,
+                    },
+                };
+                crate::SampleLevel::Gradient {
+                    x: float(self, &args[2], &target)?,
+                    y: float(self, &args[3], &target)?,
+                }
+            }
+            "SampleCmpLevelZero" => crate::SampleLevel::Zero,
+            _ if gather.is_some() => crate::SampleLevel::Zero,
+            _ => crate::SampleLevel::Auto,
+        };
+        let depth_ref = match compare {
+            true => Some(float(self, &args[2], &f32)?),
+            false => None,
+        };
+        let offset = match args.get(count) {
+            Some(arg) => Some(self.offset(image, arg)?),
+            None => None,
+        };
+        let value = self.add(
+            Expression::ImageSample {
+                image: image.handle,
+                sampler,
+                gather,
+                coordinate,
+                array_index,
+                offset,
+                level,
+                depth_ref,
+            },
+            span,
+        )?;
+        match (gather, compare) {
+            (None, false) => self.texel(image.handle, value, span),
+            _ => Ok(value),
+        }
+    }
+
+    fn image_load(
+        &mut self,
+        image: &ImageInfo,
+        method: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let (multi, storage) = match image.class {
+            ImageClass::Sampled { multi, .. } | ImageClass::Depth { multi } => (multi, false),
+            ImageClass::Storage { .. } => (false, true),
+        };
+        // Sampled textures take the mip level as the last component of the
+        // coordinate, and multisampled textures take the sample index as a
+        // separate argument.
+        let count = 1 + usize::from(multi);
+        if args.len() != count && args.len() != count + 1 {
+            return self.error(
+                ErrorKind::WrongArgumentCount {
+                    function: method.name.to_string(),
+                    expected: count,
+                    found: args.len(),
+                },
+                span,
+            );
+        }
+        let extra = u32::from(!multi && !storage);
+        let Coordinate {
+            mut coordinate,
+            array_index,
+            extra: level,
+        } = self.coordinate(image, &args[0], Scalar::I32, extra)?;
+        let sample = match multi {
+            true => {
+                let value = self.value(&args[1])?;
+                Some(self.convert_to_inner(value, &TypeInner::Scalar(Scalar::I32), None, span)?)
+            }
+            false => None,
+        };
+        if let Some(offset) = args.get(count) {
+            let value = self.value(offset)?;
+            coordinate = self.binary(BinaryOp::Add, coordinate, value, span)?;
+        }
+        let value = self.add(
+            Expression::ImageLoad {
+                image: image.handle,
+                coordinate,
+                array_index,
+                sample,
+                level,
+            },
+            span,
+        )?;
+        self.texel(image.handle, value, span)
+    }
+
+    /// Lowers `GetDimensions`, which stores the size of the image, and the
+    /// number of layers, levels or samples, in its `out` arguments.
+    fn image_dimensions(
+        &mut self,
+        image: &ImageInfo,
+        method: ast::Ident<'a>,
+        args: &[ast::Expr<'a>],
+        span: Span,
+    ) -> Result<()> {
+        let size = match image.dim {
+            crate::ImageDimension::Cube => 2,
+            dim => dimension_count(dim),
+        };
+        let multi = matches!(
+            image.class,
+            ImageClass::Sampled { multi: true, .. } | ImageClass::Depth { multi: true }
+        );
+        let outputs = size + u32::from(image.arrayed) + u32::from(multi);
+        let (level, args) = match args.len() as u32 {
+            n if n == outputs => (None, args),
+            n if n == outputs + 2 && !multi => {
+                let level = self.value(&args[0])?;
+                let level =
+                    self.convert_to_inner(level, &TypeInner::Scalar(Scalar::U32), None, span)?;
+                (Some(level), &args[1..])
+            }
+            _ => {
+                return self.error(
+                    ErrorKind::WrongArgumentCount {
+                        function: method.name.to_string(),
+                        expected: outputs as usize,
+                        found: args.len(),
+                    },
+                    span,
+                )
+            }
+        };
+        let places = args
+            .iter()
+            .map(|arg| self.place(arg))
+            .collect::<Result<Vec<_>>>()?;
+        let mut places = places.into_iter();
+
+        let dimensions = self.add(
+            Expression::ImageQuery {
+                image: image.handle,
+                query: crate::ImageQuery::Size { level },
+            },
+            span,
+        )?;
+        for index in 0..size {
+            let value = match size {
+                1 => dimensions,
+                _ => self.add(
+                    Expression::AccessIndex {
+                        base: dimensions,
+                        index,
+                    },
+                    span,
+                )?,
+            };
+            self.store(places.next().unwrap(), value, span)?;
+        }
+        let queries = [
+            (image.arrayed, crate::ImageQuery::NumLayers),
+            (multi, crate::ImageQuery::NumSamples),
+            (level.is_some(), crate::ImageQuery::NumLevels),
+        ];
+        for (_, query) in queries.into_iter().filter(|&(used, _)| used) {
+            let value = self.add(
+                Expression::ImageQuery {
+                    image: image.handle,
+                    query,
+                },
+                span,
+            )?;
+            self.store(places.next().unwrap(), value, span)?;
+        }
+        Ok(())
+    }
+}
+
+/// An image a method is called on.
+struct ImageInfo {
+    handle: Handle<Expression>,
+    dim: crate::ImageDimension,
+    arrayed: bool,
+    class: ImageClass,
+}
+
+/// A texture coordinate split into its parts.
+struct Coordinate {
+    coordinate: Handle<Expression>,
+    array_index: Option<Handle<Expression>>,
+    /// The component after the coordinate and the array index, if any.
+    extra: Option<Handle<Expression>>,
+}
@@ -0,0 +1,211 @@
+//! Splitting HLSL source into [`Token`]s.
+
+use super::error::{Error, ErrorKind};
+use crate::Span;
+
+/// A literal number, with the type its suffix gives it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Number {
+    /// An integer without a suffix, or with an `l` suffix.
+    Int(i64),
+    /// An integer with a `u` suffix.
+    Uint(u64),
+    /// A floating point number without a suffix, or with an `f` or `h`
+    /// suffix.
+    ///
+    /// Without `-enable-16bit-types`, `half` is a 32-bit type, so `h` is
+    /// treated the same as `f`.
+    Float(f64),
+    /// A floating point number with an `l` suffix.
+    Double(f64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Token<'a> {
+    Ident(&'a str),
+    Number(Number),
+    String(&'a str),
+    /// An operator or punctuation, like `+=` or `;`.
+    Punct(&'static str),
+    End,
+}
+
+impl std::fmt::Display for Token<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Token::Ident(name) => write!(f, "`{name}`"),
+            Token::Number(_) => write!(f, "number"),
+            Token::String(_) => write!(f, "string"),
+            Token::Punct(punct) => write!(f, "`{punct}`"),
+            Token::End => write!(f, "end of file"),
+        }
+    }
+}
+
+/// Operators and punctuation, longest first so that the first match is the
+/// longest one.
+const PUNCTUATION: &[&str] = &[
+    "<<=", ">>=", "::", "==", "!=", "<=", ">=", "&&", "||", "++", "--", "+=", "-=", "*=", "/=",
+    "%=", "&=", "|=", "^=", "<<", ">>", "+", "-", "*", "/", "%", "&", "|", "^", "~", "!", "<", ">",
+    "=", "?", ":", ";", ",", ".", "(", ")", "[", "]", "{", "}",
+];
+
+/// Splits `source` into tokens, ending with [`Token::End`].
+pub fn tokenize(source: &str) -> Result<Vec<(Token<'_>, Span)>, Error> {
+    let mut tokens = Vec::new();
+    let bytes = source.as_bytes();
+    let mut pos = 0;
+    let mut line_start = true;
+
+    while pos < bytes.len() {
+        let c = bytes[pos];
+        if c == b'\n' {
+            line_start = true;
+            pos += 1;
+            continue;
+        }
+        if c.is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+        if source[pos..].starts_with("//") {
+            pos = source[pos..]
+                .find('\n')
+                .map_or(bytes.len(), |end| pos + end);
+            continue;
+        }
+        if source[pos..].starts_with("/*") {
+            pos = source[pos + 2..]
+                .find("*/")
+                .map_or(bytes.len(), |end| pos + 2 + end + 2);
+            continue;
+        }
+
+        let start = pos;
+        if c == b'#' && line_start {
+            // Preprocessor directives take up the rest of the line, including
+            // escaped line breaks.
+            let mut end = pos;
+            loop {
+                end = source[end..].find('\n').map_or(bytes.len(), |i| end + i);
+                if end > 0 && bytes[end - 1] == b'\\' {
+                    end += 1;
+                    continue;
+                }
+                break;
+            }
+            let directive = source[pos + 1..end].trim_start();
+            if !directive.starts_with("pragma") {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented("preprocessor directives other than `#pragma`"),
+                    span: Span::from(start..end),
+                });
+            }
+            pos = end;
+            continue;
+        }
+        line_start = false;
+
+        let token = if c.is_ascii_alphabetic() || c == b'_' {
+            while pos < bytes.len() && (bytes[pos].is_ascii_alphanumeric() || bytes[pos] == b'_') {
+                pos += 1;
+            }
+            Token::Ident(&source[start..pos])
+        } else if c.is_ascii_digit()
+            || (c == b'.' && bytes.get(pos + 1).is_some_and(u8::is_ascii_digit))
+        {
+            let (number, len) = number(&source[pos..]).ok_or_else(|| Error {
+                kind: ErrorKind::InvalidNumber,
+                span: Span::from(start..pos + 1),
+            })?;
+            pos += len;
+            Token::Number(number)
+        } else if c == b'"' {
+            let end = source[pos + 1..]
+                .find('"')
+                .map(|end| pos + 1 + end)
+                .ok_or_else(|| Error {
+                    kind: ErrorKind::UnexpectedEndOfFile,
+                    span: Span::from(start..bytes.len()),
+                })?;
+            pos = end + 1;
+            Token::String(&source[start + 1..end])
+        } else {
+            let punct = PUNCTUATION
+                .iter()
+                .find(|punct| source[pos..].starts_with(**punct))
+                .ok_or_else(|| Error {
+                    kind: ErrorKind::UnexpectedCharacter(source[pos..].chars().next().unwrap()),
+                    span: Span::from(start..start + 1),
+                })?;
+            pos += punct.len();
+            Token::Punct(punct)
+        };
+        tokens.push((token, Span::from(start..pos)));
+    }
+
+    tokens.push((Token::End, Span::from(bytes.len()..bytes.len())));
+    Ok(tokens)
+}
+
+/// Parses the number at the start of `source`, returning it and its length.
+fn number(source: &str) -> Option<(Number, usize)> {
+    let bytes = source.as_bytes();
+    let digits_end = |from: usize, hex: bool| {
+        from + bytes[from..]
+            .iter()
+            .take_while(|&&b| match hex {
+                true => b.is_ascii_hexdigit(),
+                false => b.is_ascii_digit(),
+            })
+            .count()
+    };
+
+    if source.starts_with("0x") || source.starts_with("0X") {
+        let end = digits_end(2, true);
+        let value = u64::from_str_radix(&source[2..end], 16).ok()?;
+        return int_suffix(source, end, value);
+    }
+
+    let mut end = digits_end(0, false);
+    let mut is_float = false;
+    if bytes.get(end) == Some(&b'.') {
+        is_float = true;
+        end = digits_end(end + 1, false);
+    }
+    if let Some(&(b'e' | b'E')) = bytes.get(end) {
+        let mut exponent = end + 1;
+        if let Some(&(b'+' | b'-')) = bytes.get(exponent) {
+            exponent += 1;
+        }
+        if bytes.get(exponent).is_some_and(u8::is_ascii_digit) {
+            is_float = true;
+            end = digits_end(exponent, false);
+        }
+    }
+
+    // An integer with an `f` or `h` suffix is a floating point number.
+    if !is_float && !matches!(bytes.get(end), Some(&(b'f' | b'F' | b'h' | b'H'))) {
+        let value = source[..end].parse().ok()?;
+        return int_suffix(source, end, value);
+    }
+
+    let value = source[..end].parse().ok()?;
+    Some(match bytes.get(end) {
+        Some(&(b'f' | b'F' | b'h' | b'H')) => (Number::Float(value), end + 1),
+        Some(&(b'l' | b'L')) => (Number::Double(value), end + 1),
+        _ => (Number::Float(value), end),
+    })
+}
+
+fn int_suffix(source: &str, end: usize, value: u64) -> Option<(Number, usize)> {
+    let suffix = source[end..]
+        .bytes()
+        .take_while(|b| matches!(*b, b'u' | b'U' | b'l' | b'L'))
+        .count();
+    let unsigned = source[end..end + suffix].contains(['u', 'U']);
+    Some(match unsigned {
+        true => (Number::Uint(value), end + suffix),
+        false => (Number::Int(i64::try_from(value).ok()?), end + suffix),
+    })
+}
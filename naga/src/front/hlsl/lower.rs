@@ -0,0 +1,2236 @@
+//! Lowering an HLSL [`TranslationUnit`] into a [`Module`].
+//!
+//! [`TranslationUnit`]: ast::TranslationUnit
+
+use std::num::NonZeroU32;
+
+use super::ast::{self, AttributeArg, Direction, ExprKind, StatementKind, TypeKind};
+use super::context::{Context, Outputs, Place};
+use super::error::{Error, ErrorKind};
+use super::lex::Number;
+use super::{Options, Result};
+use crate::front::Typifier;
+use crate::proc::{Alignment, ExpressionKindTracker, Layouter};
+use crate::{
+    AddressSpace, Binding, BuiltIn, Expression, FastHashMap, FastHashSet, GlobalVariable, Handle,
+    ImageClass, Literal, Module, ResourceBinding, Scalar, ScalarKind, ShaderStage, Span, Statement,
+    StorageAccess, StorageFormat, StructMember, Type, TypeInner, VectorSize,
+};
+
+/// What a name declared at global scope refers to.
+#[derive(Clone, Copy, Debug)]
+pub enum GlobalSymbol {
+    Constant(Handle<crate::Constant>),
+    Variable(Handle<GlobalVariable>),
+    /// A member of a `cbuffer`.
+    CBufferMember(Handle<GlobalVariable>, CBufferMember),
+}
+
+/// How a member of a `cbuffer` is stored in the struct naga gives the
+/// `cbuffer`.
+#[derive(Clone, Copy, Debug)]
+pub enum CBufferMember {
+    /// The struct member with the given index.
+    Member(u32),
+    /// A vector that HLSL packs at an offset naga's vectors can't have, like
+    /// a `float3` after a `float`. Each component is a struct member of its
+    /// own, starting with the member with index `first`.
+    Scalarized { first: u32, ty: Handle<Type> },
+}
+
+/// A function that can be called.
+#[derive(Debug)]
+pub struct FunctionInfo {
+    pub handle: Handle<crate::Function>,
+    /// The types of the parameters, without the pointer `out` and `inout`
+    /// parameters are passed by, and their directions.
+    pub params: Vec<(Handle<Type>, Direction)>,
+    pub result: Option<Handle<Type>>,
+}
+
+pub struct Lowerer<'a> {
+    pub module: Module,
+    options: &'a Options,
+    pub global_expression_kind_tracker: ExpressionKindTracker,
+    pub global_typifier: Typifier,
+    pub globals: FastHashMap<&'a str, GlobalSymbol>,
+    structs: FastHashMap<&'a str, Handle<Type>>,
+    pub functions: FastHashMap<&'a str, Vec<FunctionInfo>>,
+    /// Struct members that hold a column-major matrix, or an array of them.
+    ///
+    /// HLSL stores matrices in buffers column by column by default, so a
+    /// `float4x3`, which is four rows of three columns, is stored as three
+    /// columns of four rows. In a struct, it is stored as the naga matrix with
+    /// that layout, which is the transpose of the matrix the HLSL code works
+    /// with, so it is transposed when it is loaded or stored.
+    pub transposed: FastHashSet<(Handle<Type>, u32)>,
+    /// Global variables that hold column-major matrices, like a
+    /// `StructuredBuffer<float4x4>`, which are stored transposed like the
+    /// struct members in [`transposed`].
+    ///
+    /// [`transposed`]: Lowerer::transposed
+    pub transposed_globals: FastHashSet<Handle<GlobalVariable>>,
+    /// The texel types of textures with texels other than `float4`, to
+    /// convert the `vec4` results of sampling them to.
+    pub texel_types: FastHashMap<Handle<GlobalVariable>, Handle<Type>>,
+    layouter: Layouter,
+    /// The resource bindings in use.
+    bindings: FastHashSet<(u32, u32)>,
+    /// The resource bindings given explicitly, which resources without one
+    /// can't be assigned.
+    explicit_bindings: FastHashSet<(u32, u32)>,
+    /// How resources are used, from [`Usage`].
+    usage: Usage<'a>,
+}
+
+impl<'a> Lowerer<'a> {
+    pub fn new(options: &'a Options) -> Self {
+        Lowerer {
+            module: Module::default(),
+            options,
+            global_expression_kind_tracker: ExpressionKindTracker::new(),
+            global_typifier: Typifier::new(),
+            globals: FastHashMap::default(),
+            structs: FastHashMap::default(),
+            functions: FastHashMap::default(),
+            transposed: FastHashSet::default(),
+            transposed_globals: FastHashSet::default(),
+            texel_types: FastHashMap::default(),
+            layouter: Layouter::default(),
+            bindings: FastHashSet::default(),
+            explicit_bindings: FastHashSet::default(),
+            usage: Usage::default(),
+        }
+    }
+
+    pub fn lower(mut self, tu: &ast::TranslationUnit<'a>) -> Result<Module> {
+        let out_functions = out_functions(tu);
+        for decl in tu.decls.iter() {
+            match *decl {
+                ast::GlobalDecl::Function(ref f) => {
+                    self.usage.block(&f.body, &out_functions);
+                }
+                ast::GlobalDecl::CBuffer(ref cbuffer) => {
+                    let binding = self.explicit_binding(&cbuffer.attributes, cbuffer.register, 'b');
+                    self.explicit_bindings.extend(binding);
+                }
+                ast::GlobalDecl::Variable(ref decl) => {
+                    if let Some(class) = register_class(&decl.ty.kind) {
+                        let binding = self.explicit_binding(&decl.attributes, decl.register, class);
+                        self.explicit_bindings.extend(binding);
+                    }
+                }
+                ast::GlobalDecl::Struct(_) => {}
+            }
+        }
+
+        for decl in tu.decls.iter() {
+            match *decl {
+                ast::GlobalDecl::Struct(ref s) => {
+                    if self.structs.contains_key(s.name.name) {
+                        return Err(Error {
+                            kind: ErrorKind::Redefinition(s.name.name.to_string()),
+                            span: s.name.span,
+                        });
+                    }
+                    let ty = self.members(Some(s.name.name), &s.members, None, s.name.span)?;
+                    self.structs.insert(s.name.name, ty);
+                }
+                ast::GlobalDecl::CBuffer(ref cbuffer) => self.cbuffer(cbuffer)?,
+                ast::GlobalDecl::Variable(ref decl) => self.global_variable(decl)?,
+                ast::GlobalDecl::Function(ref f) => self.function(f, &out_functions)?,
+            }
+        }
+
+        for (name, _) in self.options.entry_points.iter() {
+            if !self.module.entry_points.iter().any(|ep| ep.name == *name) {
+                return Err(Error {
+                    kind: ErrorKind::UnknownFunction(name.clone()),
+                    span: Span::UNDEFINED,
+                });
+            }
+        }
+
+        Ok(self.module)
+    }
+
+    pub fn insert_type(&mut self, inner: TypeInner) -> Handle<Type> {
+        self.module
+            .types
+            .insert(Type { name: None, inner }, Span::UNDEFINED)
+    }
+
+    fn define(&mut self, name: ast::Ident<'a>, symbol: GlobalSymbol) -> Result<()> {
+        match self.globals.insert(name.name, symbol) {
+            Some(_) => Err(Error {
+                kind: ErrorKind::Redefinition(name.name.to_string()),
+                span: name.span,
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Types
+
+    /// Returns the naga type of `ty`, with the array sizes in `array_sizes`.
+    ///
+    /// Matrices have the HLSL layout, in which the rows are naga columns.
+    /// Arrays in a `cbuffer` have their stride rounded up to 16 bytes.
+    pub fn ty(
+        &mut self,
+        ty: &ast::Type<'a>,
+        array_sizes: &[Option<ast::Expr<'a>>],
+        in_cbuffer: bool,
+    ) -> Result<Handle<Type>> {
+        let mut base = self.base_ty(ty, false)?;
+        for size in array_sizes.iter().rev() {
+            let size = match *size {
+                Some(ref size) => self.array_size(size)?,
+                None => {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("arrays without a size"),
+                        span: ty.span,
+                    })
+                }
+            };
+            base = self.array(base, crate::ArraySize::Constant(size), in_cbuffer);
+        }
+        Ok(base)
+    }
+
+    /// Returns the naga type of `ty`, without array sizes.
+    ///
+    /// If `transpose` is set, matrices are column-major, and have the layout
+    /// described in [`Lowerer::transposed`].
+    fn base_ty(&mut self, ty: &ast::Type<'a>, transpose: bool) -> Result<Handle<Type>> {
+        let inner = match ty.kind {
+            TypeKind::Void => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError("`void` is not a value type".into()),
+                    span: ty.span,
+                })
+            }
+            TypeKind::Scalar(scalar) => TypeInner::Scalar(scalar),
+            TypeKind::Vector(size, scalar) => TypeInner::Vector { size, scalar },
+            TypeKind::Matrix {
+                rows,
+                columns,
+                scalar,
+            } => {
+                if scalar.kind != ScalarKind::Float {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("matrices of integers or booleans"),
+                        span: ty.span,
+                    });
+                }
+                match transpose {
+                    true => TypeInner::Matrix {
+                        columns,
+                        rows,
+                        scalar,
+                    },
+                    false => TypeInner::Matrix {
+                        columns: rows,
+                        rows: columns,
+                        scalar,
+                    },
+                }
+            }
+            TypeKind::Named(name) => {
+                return self.structs.get(name.name).copied().ok_or(Error {
+                    kind: ErrorKind::UnknownType(name.name.to_string()),
+                    span: name.span,
+                })
+            }
+            TypeKind::Texture { .. } => TypeInner::Image {
+                dim: crate::ImageDimension::D2,
+                arrayed: false,
+                class: ImageClass::Depth { multi: false },
+            },
+            TypeKind::Sampler { comparison } => TypeInner::Sampler { comparison },
+            TypeKind::StructuredBuffer { .. } | TypeKind::ConstantBuffer(_) => {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented("buffers outside of global variables"),
+                    span: ty.span,
+                })
+            }
+        };
+        let inner = match (inner, &ty.kind) {
+            (
+                TypeInner::Image { .. },
+                &TypeKind::Texture {
+                    dim,
+                    arrayed,
+                    ref texel,
+                    ..
+                },
+            ) => TypeInner::Image {
+                dim,
+                arrayed,
+                class: self.image_class(&ty.kind, false, None, true, texel.as_deref())?,
+            },
+            (inner, _) => inner,
+        };
+        Ok(self.insert_type(inner))
+    }
+
+    fn array(
+        &mut self,
+        base: Handle<Type>,
+        size: crate::ArraySize,
+        in_cbuffer: bool,
+    ) -> Handle<Type> {
+        self.layouter.update(self.module.to_ctx()).unwrap();
+        let mut stride = self.layouter[base].to_stride();
+        if in_cbuffer {
+            stride = Alignment::SIXTEEN.round_up(stride);
+        }
+        self.insert_type(TypeInner::Array { base, size, stride })
+    }
+
+    /// Returns `ty` with its integers replaced by atomics, if it is an `int`,
+    /// a `uint` or an array of them.
+    fn atomic(&mut self, ty: Handle<Type>) -> Option<Handle<Type>> {
+        match self.module.types[ty].inner {
+            TypeInner::Scalar(scalar) if scalar == Scalar::I32 || scalar == Scalar::U32 => {
+                Some(self.insert_type(TypeInner::Atomic(scalar)))
+            }
+            TypeInner::Array { base, size, .. } => {
+                let base = self.atomic(base)?;
+                Some(self.array(base, size, false))
+            }
+            _ => None,
+        }
+    }
+
+    fn array_size(&mut self, expr: &ast::Expr<'a>) -> Result<NonZeroU32> {
+        let size = self.const_u32(expr)?;
+        NonZeroU32::new(size).ok_or(Error {
+            kind: ErrorKind::SemanticError("array sizes must be greater than zero".into()),
+            span: expr.span,
+        })
+    }
+
+    /// Evaluates `expr`, which must be a constant `int` or `uint`.
+    fn const_u32(&mut self, expr: &ast::Expr<'a>) -> Result<u32> {
+        // Avoid adding expressions for literals to the module.
+        if let ExprKind::Literal(ast::Literal::Number(Number::Int(value))) = expr.kind {
+            if let Ok(value) = u32::try_from(value) {
+                return Ok(value);
+            }
+        }
+        let mut ctx = Context::new(self, true);
+        let value = ctx.value(expr)?;
+        match ctx.literal(value) {
+            Some(Literal::I32(value)) if value >= 0 => Ok(value as u32),
+            Some(Literal::U32(value)) => Ok(value),
+            _ => Err(Error {
+                kind: ErrorKind::SemanticError("expected a non-negative constant integer".into()),
+                span: expr.span,
+            }),
+        }
+    }
+
+    /// Returns the class of an image of type `texture`, a [`TypeKind::Texture`].
+    ///
+    /// Sampled textures are depth textures if they are `compared` with
+    /// `SampleCmp` or `GatherCmp`. Storage textures have `format` if given,
+    /// and otherwise the one that matches their texel type.
+    fn image_class(
+        &mut self,
+        texture: &TypeKind<'a>,
+        compared: bool,
+        format: Option<StorageFormat>,
+        loaded: bool,
+        texel: Option<&ast::Type<'a>>,
+    ) -> Result<ImageClass> {
+        let TypeKind::Texture {
+            multisampled,
+            storage,
+            ..
+        } = *texture
+        else {
+            unreachable!()
+        };
+        let (size, scalar, span) = match texel.map(|texel| (&texel.kind, texel.span)) {
+            None => (4, Scalar::F32, Span::UNDEFINED),
+            Some((&TypeKind::Scalar(scalar), span)) => (1, scalar, span),
+            Some((&TypeKind::Vector(size, scalar), span)) => (size as u32, scalar, span),
+            Some((_, span)) => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError("texels must be scalars or vectors".into()),
+                    span,
+                })
+            }
+        };
+        if scalar.kind == ScalarKind::Bool {
+            return Err(Error {
+                kind: ErrorKind::SemanticError("texels can't be booleans".into()),
+                span,
+            });
+        }
+
+        if !storage {
+            return Ok(match compared {
+                true => ImageClass::Depth {
+                    multi: multisampled,
+                },
+                false => ImageClass::Sampled {
+                    kind: scalar.kind,
+                    multi: multisampled,
+                },
+            });
+        }
+
+        let format = match format {
+            Some(format) => format,
+            None => {
+                use StorageFormat as Sf;
+                match (size, scalar.kind) {
+                    (1, ScalarKind::Float) => Sf::R32Float,
+                    (1, ScalarKind::Sint) => Sf::R32Sint,
+                    (1, _) => Sf::R32Uint,
+                    (2, ScalarKind::Float) => Sf::Rg32Float,
+                    (2, ScalarKind::Sint) => Sf::Rg32Sint,
+                    (2, _) => Sf::Rg32Uint,
+                    (4, ScalarKind::Float) => Sf::Rgba32Float,
+                    (4, ScalarKind::Sint) => Sf::Rgba32Sint,
+                    (4, _) => Sf::Rgba32Uint,
+                    _ => {
+                        return Err(Error {
+                            kind: ErrorKind::SemanticError(
+                                "there is no storage format for three-component texels, \
+                                 use `[[vk::image_format(...)]]` to give one"
+                                    .into(),
+                            ),
+                            span,
+                        })
+                    }
+                }
+            }
+        };
+        let access = match loaded {
+            true => StorageAccess::LOAD | StorageAccess::STORE,
+            false => StorageAccess::STORE,
+        };
+        Ok(ImageClass::Storage { format, access })
+    }
+
+    /// Returns a struct type with `members`, laid out with WGSL's rules.
+    ///
+    /// Members with semantics get bindings, so that the struct can be the
+    /// input or output of an entry point.
+    ///
+    /// If `cbuffer_members` is given, the members are placed at their offsets
+    /// under HLSL's packing rules instead, and how each is stored is added to
+    /// it. Vectors that naga's rules for uniform buffers don't allow at their
+    /// offset are split into scalars, and it is an error if other members
+    /// can't be placed.
+    fn members(
+        &mut self,
+        name: Option<&str>,
+        members: &[ast::VarDecl<'a>],
+        mut cbuffer_members: Option<&mut Vec<CBufferMember>>,
+        span: Span,
+    ) -> Result<Handle<Type>> {
+        let in_cbuffer = cbuffer_members.is_some();
+        let mut naga_members = Vec::with_capacity(members.len());
+        let mut transposed = Vec::new();
+        let mut offset = 0;
+        let mut cbuffer_end = 0;
+        let mut alignment = Alignment::ONE;
+        let mut next_location = 0;
+        let mut names = FastHashSet::default();
+        for member in members.iter() {
+            if !names.insert(member.name.name) {
+                return Err(Error {
+                    kind: ErrorKind::Redefinition(member.name.name.to_string()),
+                    span: member.name.span,
+                });
+            }
+            if member.init.is_some() {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented("initializers of struct members"),
+                    span: member.name.span,
+                });
+            }
+            let transpose =
+                !member.modifiers.row_major && matches!(member.ty.kind, TypeKind::Matrix { .. });
+            let mut ty = self.base_ty(&member.ty, transpose)?;
+            for size in member.array_sizes.iter().rev() {
+                let size = match *size {
+                    Some(ref size) => self.array_size(size)?,
+                    None => {
+                        return Err(Error {
+                            kind: ErrorKind::NotImplemented("arrays without a size"),
+                            span: member.name.span,
+                        })
+                    }
+                };
+                ty = self.array(ty, crate::ArraySize::Constant(size), in_cbuffer);
+            }
+            let binding = match member.semantic {
+                Some(semantic) => {
+                    let (binding, builtin_ty) =
+                        self.binding(semantic, member, ty, &mut next_location)?;
+                    if let Some(builtin_ty) = builtin_ty {
+                        ty = builtin_ty;
+                    }
+                    Some(binding)
+                }
+                None => None,
+            };
+
+            self.layouter.update(self.module.to_ctx()).unwrap();
+            let layout = self.layouter[ty];
+            let mut member_alignment = layout.alignment;
+            let is_aggregate = matches!(
+                self.module.types[ty].inner,
+                TypeInner::Struct { .. } | TypeInner::Array { .. }
+            );
+            if in_cbuffer && is_aggregate {
+                // Arrays and structs start a new register in a `cbuffer`.
+                member_alignment = Alignment::SIXTEEN;
+            }
+            let previous_end = offset;
+            offset = member_alignment.round_up(offset);
+            if let Some(cbuffer_members) = cbuffer_members.as_deref_mut() {
+                // The member must be where HLSL's packing rules put it, and
+                // naga's layout rules must allow it to be there.
+                let unrepresentable = || Error {
+                    kind: ErrorKind::SemanticError(
+                        format!(
+                            "the HLSL packing of `{}` can not be represented",
+                            member.name.name
+                        )
+                        .into(),
+                    ),
+                    span: member.name.span,
+                };
+                let (hlsl_offset, hlsl_size) = self
+                    .cbuffer_placement(ty, cbuffer_end)
+                    .filter(|&(hlsl_offset, _)| hlsl_offset >= previous_end)
+                    .ok_or_else(unrepresentable)?;
+                cbuffer_end = hlsl_offset + hlsl_size;
+                if !member_alignment.is_aligned(hlsl_offset) {
+                    // Only vectors can be packed at offsets that are too
+                    // small for naga, so store their components instead.
+                    let TypeInner::Vector { size, scalar } = self.module.types[ty].inner else {
+                        return Err(unrepresentable());
+                    };
+                    let scalar_ty = self.module.types.insert(
+                        Type {
+                            name: None,
+                            inner: TypeInner::Scalar(scalar),
+                        },
+                        Span::UNDEFINED,
+                    );
+                    cbuffer_members.push(CBufferMember::Scalarized {
+                        first: naga_members.len() as u32,
+                        ty,
+                    });
+                    for (index, component) in "xyzw".chars().take(size as usize).enumerate() {
+                        naga_members.push(StructMember {
+                            name: Some(format!("{}_{component}", member.name.name)),
+                            ty: scalar_ty,
+                            binding: None,
+                            offset: hlsl_offset + index as u32 * scalar.width as u32,
+                        });
+                    }
+                    alignment = alignment.max(Alignment::from_width(scalar.width));
+                    offset = cbuffer_end;
+                    continue;
+                }
+                offset = hlsl_offset;
+                cbuffer_members.push(CBufferMember::Member(naga_members.len() as u32));
+            }
+            if transpose {
+                transposed.push(naga_members.len() as u32);
+            }
+            alignment = alignment.max(member_alignment);
+            naga_members.push(StructMember {
+                name: Some(member.name.name.to_string()),
+                ty,
+                binding,
+                offset,
+            });
+            offset += layout.size;
+            if in_cbuffer && is_aggregate {
+                offset = Alignment::SIXTEEN.round_up(offset);
+            }
+        }
+
+        let ty = self.module.types.insert(
+            Type {
+                name: name.map(str::to_string),
+                inner: TypeInner::Struct {
+                    members: naga_members,
+                    span: alignment.round_up(offset),
+                },
+            },
+            span,
+        );
+        for index in transposed {
+            self.transposed.insert((ty, index));
+        }
+        Ok(ty)
+    }
+
+    /// Returns the offset and size of a `cbuffer` member of type `ty`
+    /// following members that end at `end`, under HLSL's packing rules.
+    ///
+    /// Scalars and vectors are packed into the current 16-byte register if
+    /// they fit, and other members start a new register.
+    ///
+    /// Returns `None` if naga's layout of `ty` differs from its HLSL layout.
+    fn cbuffer_placement(&self, ty: Handle<Type>, end: u32) -> Option<(u32, u32)> {
+        let size = self.cbuffer_size(ty)?;
+        let offset = match self.module.types[ty].inner {
+            TypeInner::Scalar(scalar) | TypeInner::Vector { scalar, .. } => {
+                let offset = Alignment::new(scalar.width as u32)?.round_up(end);
+                if offset % 16 + size > 16 {
+                    Alignment::SIXTEEN.round_up(offset)
+                } else {
+                    offset
+                }
+            }
+            _ => Alignment::SIXTEEN.round_up(end),
+        };
+        Some((offset, size))
+    }
+
+    /// Returns the size of `ty` in a `cbuffer`, under HLSL's packing rules.
+    ///
+    /// Unlike naga's sizes, this doesn't include the unused part of the last
+    /// register of a matrix, array or struct, which later members can use.
+    ///
+    /// Returns `None` if naga's layout of `ty` differs from its HLSL layout.
+    fn cbuffer_size(&self, ty: Handle<Type>) -> Option<u32> {
+        match self.module.types[ty].inner {
+            TypeInner::Scalar(scalar) => Some(scalar.width as u32),
+            TypeInner::Vector { size, scalar } => Some(size as u32 * scalar.width as u32),
+            TypeInner::Matrix {
+                columns,
+                rows,
+                scalar,
+            } => {
+                // Each naga column is a register.
+                let stride = self.layouter[ty].size / columns as u32;
+                (stride == 16)
+                    .then_some(16 * (columns as u32 - 1) + rows as u32 * scalar.width as u32)
+            }
+            TypeInner::Array {
+                base,
+                size: crate::ArraySize::Constant(size),
+                stride,
+            } => {
+                // Each element starts a new register.
+                let element = self.cbuffer_size(base)?;
+                (stride == Alignment::SIXTEEN.round_up(element))
+                    .then_some(stride * (size.get() - 1) + element)
+            }
+            TypeInner::Struct { ref members, .. } => {
+                let mut end = 0;
+                for member in members {
+                    let (offset, size) = self.cbuffer_placement(member.ty, end)?;
+                    if offset != member.offset {
+                        return None;
+                    }
+                    end = offset + size;
+                }
+                Some(end)
+            }
+            _ => None,
+        }
+    }
+
+    // Global declarations
+
+    fn cbuffer(&mut self, cbuffer: &ast::CBuffer<'a>) -> Result<()> {
+        let mut members = Vec::with_capacity(cbuffer.members.len());
+        let ty = self.members(
+            Some(cbuffer.name.name),
+            &cbuffer.members,
+            Some(&mut members),
+            cbuffer.name.span,
+        )?;
+        let (space, binding) = match find_attribute(&cbuffer.attributes, "vk", "push_constant") {
+            Some(_) => (AddressSpace::PushConstant, None),
+            None => (
+                AddressSpace::Uniform,
+                Some(self.resource_binding(
+                    cbuffer.name,
+                    &cbuffer.attributes,
+                    cbuffer.register,
+                    'b',
+                )?),
+            ),
+        };
+        let variable = self.module.global_variables.append(
+            GlobalVariable {
+                name: Some(cbuffer.name.name.to_string()),
+                space,
+                binding,
+                ty,
+                init: None,
+            },
+            cbuffer.name.span,
+        );
+        for (member, &stored) in cbuffer.members.iter().zip(members.iter()) {
+            self.define(member.name, GlobalSymbol::CBufferMember(variable, stored))?;
+        }
+        Ok(())
+    }
+
+    fn global_variable(&mut self, decl: &ast::VarDecl<'a>) -> Result<()> {
+        let name = decl.name;
+        let span = name.span;
+        if decl.modifiers.is_const {
+            let ty = self.var_ty(decl, false)?;
+            let Some(ref init) = decl.init else {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError("constants must be initialized".into()),
+                    span,
+                });
+            };
+            let mut ctx = Context::new(self, true);
+            let init = ctx.initializer(init, ty)?;
+            let constant = self.module.constants.append(
+                crate::Constant {
+                    name: Some(name.name.to_string()),
+                    ty,
+                    init,
+                },
+                span,
+            );
+            return self.define(name, GlobalSymbol::Constant(constant));
+        }
+
+        let space = match decl.ty.kind {
+            _ if decl.modifiers.groupshared => AddressSpace::WorkGroup,
+            _ if decl.modifiers.is_static => AddressSpace::Private,
+            TypeKind::Texture { .. } | TypeKind::Sampler { .. } => AddressSpace::Handle,
+            TypeKind::StructuredBuffer { read_write, .. } => AddressSpace::Storage {
+                access: match read_write {
+                    true => StorageAccess::LOAD | StorageAccess::STORE,
+                    false => StorageAccess::LOAD,
+                },
+            },
+            TypeKind::ConstantBuffer(_) => {
+                match find_attribute(&decl.attributes, "vk", "push_constant") {
+                    Some(_) => AddressSpace::PushConstant,
+                    None => AddressSpace::Uniform,
+                }
+            }
+            _ => {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented(
+                        "global variables outside a `cbuffer`, which are not `static`, \
+                         `groupshared` or resources",
+                    ),
+                    span,
+                })
+            }
+        };
+
+        let mut texel = None;
+        let mut transposed = false;
+        let ty = match decl.ty.kind {
+            TypeKind::Texture {
+                dim,
+                arrayed,
+                texel: ref texel_ty,
+                ..
+            } => {
+                let format = match find_attribute(&decl.attributes, "vk", "image_format") {
+                    Some(attribute) => Some(image_format(attribute)?),
+                    None => None,
+                };
+                let class = self.image_class(
+                    &decl.ty.kind,
+                    self.usage.compared.contains(name.name),
+                    format,
+                    self.usage.loaded.contains(name.name),
+                    texel_ty.as_deref(),
+                )?;
+                if let Some(ref texel_ty) = *texel_ty {
+                    texel = Some(self.base_ty(texel_ty, false)?);
+                }
+                let ty = self.insert_type(TypeInner::Image {
+                    dim,
+                    arrayed,
+                    class,
+                });
+                self.binding_array(ty, decl)?
+            }
+            TypeKind::Sampler { .. } => {
+                let ty = self.base_ty(&decl.ty, false)?;
+                self.binding_array(ty, decl)?
+            }
+            TypeKind::StructuredBuffer { ref element, .. } => {
+                if !decl.array_sizes.is_empty() {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("arrays of buffers"),
+                        span,
+                    });
+                }
+                transposed = matches!(element.kind, TypeKind::Matrix { .. });
+                let element = self.base_ty(element, transposed)?;
+                self.array(element, crate::ArraySize::Dynamic, false)
+            }
+            TypeKind::ConstantBuffer(ref element) => {
+                if !decl.array_sizes.is_empty() {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("arrays of buffers"),
+                        span,
+                    });
+                }
+                let ty = self.base_ty(element, false)?;
+                if !matches!(self.module.types[ty].inner, TypeInner::Struct { .. }) {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "the type of a `ConstantBuffer` must be a struct".into(),
+                        ),
+                        span: element.span,
+                    });
+                }
+                ty
+            }
+            _ => self.var_ty(decl, false)?,
+        };
+        let ty = match space {
+            AddressSpace::Storage { .. } | AddressSpace::WorkGroup
+                if self.usage.atomic.contains(name.name) =>
+            {
+                self.atomic(ty).unwrap_or(ty)
+            }
+            _ => ty,
+        };
+
+        let binding = match space {
+            AddressSpace::Handle | AddressSpace::Uniform | AddressSpace::Storage { .. } => {
+                let Some(class) = register_class(&decl.ty.kind) else {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "only resources can be declared in this address space".into(),
+                        ),
+                        span,
+                    });
+                };
+                Some(self.resource_binding(name, &decl.attributes, decl.register, class)?)
+            }
+            _ => {
+                if let Some(register) = decl.register {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "only resources can be bound to registers".into(),
+                        ),
+                        span: register.span,
+                    });
+                }
+                None
+            }
+        };
+
+        let init = match decl.init {
+            Some(ref init) if space == AddressSpace::Private => {
+                let mut ctx = Context::new(self, true);
+                Some(ctx.initializer(init, ty)?)
+            }
+            Some(ref init) => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "only `static` global variables can be initialized".into(),
+                    ),
+                    span: init.span,
+                })
+            }
+            None => None,
+        };
+
+        let variable = self.module.global_variables.append(
+            GlobalVariable {
+                name: Some(name.name.to_string()),
+                space,
+                binding,
+                ty,
+                init,
+            },
+            span,
+        );
+        if let Some(texel) = texel {
+            self.texel_types.insert(variable, texel);
+        }
+        if transposed {
+            self.transposed_globals.insert(variable);
+        }
+        self.define(name, GlobalSymbol::Variable(variable))
+    }
+
+    /// Returns the type of `decl`, taking the size of an array without one
+    /// from its initializer.
+    fn var_ty(&mut self, decl: &ast::VarDecl<'a>, in_cbuffer: bool) -> Result<Handle<Type>> {
+        if let (Some(&None), Some(&ExprKind::InitList(ref items))) = (
+            decl.array_sizes.first(),
+            decl.init.as_ref().map(|init| &init.kind),
+        ) {
+            let mut ty = self.ty(&decl.ty, &decl.array_sizes[1..], in_cbuffer)?;
+            let size = NonZeroU32::new(items.len() as u32).ok_or(Error {
+                kind: ErrorKind::SemanticError("array sizes must be greater than zero".into()),
+                span: decl.name.span,
+            })?;
+            ty = self.array(ty, crate::ArraySize::Constant(size), in_cbuffer);
+            return Ok(ty);
+        }
+        self.ty(&decl.ty, &decl.array_sizes, in_cbuffer)
+    }
+
+    /// Wraps `ty`, a resource type, in a binding array if `decl` declares an
+    /// array.
+    fn binding_array(
+        &mut self,
+        mut ty: Handle<Type>,
+        decl: &ast::VarDecl<'a>,
+    ) -> Result<Handle<Type>> {
+        for size in decl.array_sizes.iter().rev() {
+            let size = match *size {
+                Some(ref size) => crate::ArraySize::Constant(self.array_size(size)?),
+                None => crate::ArraySize::Dynamic,
+            };
+            ty = self.insert_type(TypeInner::BindingArray { base: ty, size });
+        }
+        Ok(ty)
+    }
+
+    /// Returns the binding of a resource given with `[[vk::binding]]` or a
+    /// register of `class`, if it has one.
+    fn explicit_binding(
+        &mut self,
+        attributes: &[ast::Attribute<'a>],
+        register: Option<ast::Register>,
+        class: char,
+    ) -> Option<(u32, u32)> {
+        if let Some(attribute) = find_attribute(attributes, "vk", "binding") {
+            let binding = attribute_u32(attribute.args.first()?).ok()?;
+            let group = match attribute.args.get(1) {
+                Some(arg) => attribute_u32(arg).ok()?,
+                None => 0,
+            };
+            return Some((group, binding));
+        }
+        let register = register?;
+        let shifts = &self.options.register_shifts;
+        let shift = match class {
+            'b' => shifts.constant_buffer,
+            't' => shifts.texture,
+            's' => shifts.sampler,
+            _ => shifts.unordered_access,
+        };
+        (register.class == class).then_some((register.space, register.index + shift))
+    }
+
+    /// Returns the binding of the resource `name`.
+    ///
+    /// The binding is given by `[[vk::binding(binding, group)]]`, or by a
+    /// register of `class`, as the register's index plus the shift in
+    /// [`Options::register_shifts`] in the group of the register's space.
+    /// Resources without either get the first binding in group 0 that isn't
+    /// given to any other resource explicitly.
+    fn resource_binding(
+        &mut self,
+        name: ast::Ident<'a>,
+        attributes: &[ast::Attribute<'a>],
+        register: Option<ast::Register>,
+        class: char,
+    ) -> Result<ResourceBinding> {
+        if let Some(register) = register {
+            if register.class != class && find_attribute(attributes, "vk", "binding").is_none() {
+                return Err(Error {
+                    kind: ErrorKind::InvalidRegister(format!(
+                        "{}{}: this resource needs a `{class}` register",
+                        register.class, register.index
+                    )),
+                    span: register.span,
+                });
+            }
+        }
+        let (group, binding) = match self.explicit_binding(attributes, register, class) {
+            Some(binding) => binding,
+            None if find_attribute(attributes, "vk", "binding").is_some() => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "`vk::binding` expects a constant binding and an optional group".into(),
+                    ),
+                    span: name.span,
+                })
+            }
+            None => {
+                let binding = (0..)
+                    .find(|&binding| {
+                        !self.explicit_bindings.contains(&(0, binding))
+                            && !self.bindings.contains(&(0, binding))
+                    })
+                    .unwrap();
+                (0, binding)
+            }
+        };
+        if !self.bindings.insert((group, binding)) {
+            return Err(Error {
+                kind: ErrorKind::BindingCollision(name.name.to_string(), group, binding),
+                span: name.span,
+            });
+        }
+        Ok(ResourceBinding { group, binding })
+    }
+
+    // Entry point interfaces
+
+    /// Returns the binding for a value with `semantic`, and the type the value
+    /// must have if it is a built-in.
+    ///
+    /// Semantics that aren't system values get the location given by
+    /// `[[vk::location]]`, or otherwise `next_location`, which is incremented.
+    fn binding(
+        &mut self,
+        semantic: ast::Ident<'a>,
+        decl: &ast::VarDecl<'a>,
+        ty: Handle<Type>,
+        next_location: &mut u32,
+    ) -> Result<(Binding, Option<Handle<Type>>)> {
+        let upper = semantic.name.to_ascii_uppercase();
+        if let Some(index) = upper.strip_prefix("SV_TARGET") {
+            let location = match index {
+                "" => 0,
+                index => index.parse().map_err(|_| Error {
+                    kind: ErrorKind::UnknownSemantic(semantic.name.to_string()),
+                    span: semantic.span,
+                })?,
+            };
+            return Ok((self.location(location, decl, ty), None));
+        }
+        if upper.starts_with("SV_") {
+            let (built_in, inner) = builtin(&upper).ok_or(Error {
+                kind: ErrorKind::UnknownSemantic(semantic.name.to_string()),
+                span: semantic.span,
+            })?;
+            let builtin_ty = self.insert_type(inner);
+            return Ok((Binding::BuiltIn(built_in), Some(builtin_ty)));
+        }
+        let location = match find_attribute(&decl.attributes, "vk", "location") {
+            Some(attribute) => match attribute.args.first() {
+                Some(arg) => attribute_u32(arg)?,
+                None => {
+                    return Err(Error {
+                        kind: ErrorKind::WrongArgumentCount {
+                            function: "vk::location".to_string(),
+                            expected: 1,
+                            found: 0,
+                        },
+                        span: attribute.name.span,
+                    })
+                }
+            },
+            None => {
+                *next_location += 1;
+                *next_location - 1
+            }
+        };
+        Ok((self.location(location, decl, ty), None))
+    }
+
+    fn location(&self, location: u32, decl: &ast::VarDecl<'a>, ty: Handle<Type>) -> Binding {
+        let mut binding = Binding::Location {
+            location,
+            second_blend_source: false,
+            interpolation: decl.modifiers.interpolation,
+            sampling: decl.modifiers.sampling,
+        };
+        binding.apply_default_interpolation(&self.module.types[ty].inner);
+        binding
+    }
+
+    // Functions
+
+    /// Returns the stage of `f` if it is an entry point.
+    fn stage(&self, f: &ast::Function<'a>) -> Result<Option<ShaderStage>> {
+        if let Some(&stage) = self.options.entry_points.get(f.name.name) {
+            return Ok(Some(stage));
+        }
+        if let Some(attribute) = find_attribute(&f.attributes, "", "shader") {
+            return match attribute.args.first() {
+                Some(&AttributeArg::String("vertex", _)) => Ok(Some(ShaderStage::Vertex)),
+                Some(&AttributeArg::String("pixel", _)) => Ok(Some(ShaderStage::Fragment)),
+                Some(&AttributeArg::String("compute", _)) => Ok(Some(ShaderStage::Compute)),
+                _ => Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "expected `\"vertex\"`, `\"pixel\"` or `\"compute\"`".into(),
+                    ),
+                    span: attribute.name.span,
+                }),
+            };
+        }
+        Ok(find_attribute(&f.attributes, "", "numthreads").map(|_| ShaderStage::Compute))
+    }
+
+    fn function(
+        &mut self,
+        f: &ast::Function<'a>,
+        out_functions: &FastHashSet<&'a str>,
+    ) -> Result<()> {
+        let stage = self.stage(f)?;
+        let mut usage = Usage::default();
+        usage.block(&f.body, out_functions);
+
+        let result = match f.result.kind {
+            TypeKind::Void => None,
+            _ => Some(self.ty(&f.result, &[], false)?),
+        };
+
+        let mut ctx = Context::new(self, false);
+        ctx.symbols.push_scope();
+        let mut params = Vec::new();
+        let mut next_input = 0;
+        let mut next_output = 0;
+        let mut outputs = Vec::new();
+
+        for param in f.params.iter() {
+            let name = param.name;
+            let ty = ctx.lowerer.var_ty(param, false)?;
+            if param.init.is_some() {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented("default arguments"),
+                    span: name.span,
+                });
+            }
+            let direction = param.modifiers.direction;
+            params.push((ty, direction));
+
+            if stage.is_some() && direction != Direction::In {
+                if direction == Direction::InOut {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("`inout` parameters of entry points"),
+                        span: name.span,
+                    });
+                }
+                // Outputs are written to locals, which are returned together
+                // in a struct.
+                let local = ctx.locals.append(
+                    crate::LocalVariable {
+                        name: Some(name.name.to_string()),
+                        ty,
+                        init: None,
+                    },
+                    name.span,
+                );
+                let pointer = ctx.add(Expression::LocalVariable(local), name.span)?;
+                ctx.symbols.add(
+                    name.name,
+                    Place::Pointer {
+                        pointer,
+                        transposed: false,
+                    },
+                );
+                outputs.push((param, ty, local));
+                continue;
+            }
+
+            let (arg_ty, binding) = match (stage, param.semantic) {
+                (Some(_), Some(semantic)) if !is_struct(&ctx.lowerer.module, ty) => {
+                    let (binding, builtin_ty) =
+                        ctx.lowerer.binding(semantic, param, ty, &mut next_input)?;
+                    (builtin_ty.unwrap_or(ty), Some(binding))
+                }
+                (Some(_), _) if !is_struct(&ctx.lowerer.module, ty) => {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            format!("the entry point parameter `{}` needs a semantic", name.name)
+                                .into(),
+                        ),
+                        span: name.span,
+                    })
+                }
+                (_, _) if direction == Direction::In => (ty, None),
+                (_, _) => (
+                    ctx.lowerer.insert_type(TypeInner::Pointer {
+                        base: ty,
+                        space: AddressSpace::Function,
+                    }),
+                    None,
+                ),
+            };
+            let index = ctx.arguments.len() as u32;
+            ctx.arguments.push(crate::FunctionArgument {
+                name: Some(name.name.to_string()),
+                ty: arg_ty,
+                binding,
+            });
+            let value = ctx.add(Expression::FunctionArgument(index), name.span)?;
+
+            let place = match direction {
+                Direction::In => {
+                    let value = ctx.convert(value, ty, name.span)?;
+                    match usage.assigned.contains(name.name) {
+                        // Parameters that are assigned to are copied to a local.
+                        true => {
+                            let local = ctx.locals.append(
+                                crate::LocalVariable {
+                                    name: Some(name.name.to_string()),
+                                    ty,
+                                    init: None,
+                                },
+                                name.span,
+                            );
+                            let pointer = ctx.add(Expression::LocalVariable(local), name.span)?;
+                            ctx.emit_restart();
+                            ctx.body
+                                .push(Statement::Store { pointer, value }, name.span);
+                            Place::Pointer {
+                                pointer,
+                                transposed: false,
+                            }
+                        }
+                        false => Place::Value(value),
+                    }
+                }
+                Direction::Out | Direction::InOut => Place::Pointer {
+                    pointer: value,
+                    transposed: false,
+                },
+            };
+            ctx.symbols.add(name.name, place);
+        }
+
+        // The result of an entry point, with its binding.
+        let mut result_binding = None;
+        if let (Some(_), Some(ty)) = (stage, result) {
+            if !is_struct(&ctx.lowerer.module, ty) {
+                let Some(semantic) = f.semantic else {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "the result of an entry point needs a semantic".into(),
+                        ),
+                        span: f.result.span,
+                    });
+                };
+                let decl = ast::VarDecl {
+                    attributes: Vec::new(),
+                    modifiers: Default::default(),
+                    ty: f.result.clone(),
+                    name: f.name,
+                    array_sizes: Vec::new(),
+                    semantic: None,
+                    register: None,
+                    init: None,
+                };
+                result_binding =
+                    Some(ctx.lowerer.binding(semantic, &decl, ty, &mut next_output)?);
+            }
+        }
+
+        if !outputs.is_empty() {
+            ctx.outputs = Some(ctx.lowerer.outputs(
+                f,
+                result,
+                result_binding.take(),
+                &outputs,
+                &mut next_output,
+            )?);
+        }
+        ctx.result = result;
+
+        ctx.block(&f.body)?;
+        if let Some(ref outputs) = ctx.outputs {
+            if !matches!(
+                ctx.body.last(),
+                Some(&(Statement::Return { .. } | Statement::Kill))
+            ) {
+                let locals = outputs.locals.clone();
+                let ty = outputs.ty;
+                let value = ctx.compose_outputs(ty, None, &locals, f.name.span)?;
+                ctx.emit_restart();
+                ctx.body
+                    .push(Statement::Return { value: Some(value) }, f.name.span);
+            }
+        }
+        ctx.symbols.pop_scope();
+        let ctx = ctx.finish();
+
+        let naga_result = match (ctx.outputs.as_ref(), result, result_binding) {
+            (Some(outputs), _, _) => Some(crate::FunctionResult {
+                ty: outputs.ty,
+                binding: None,
+            }),
+            (None, Some(ty), Some((binding, builtin_ty))) => Some(crate::FunctionResult {
+                ty: builtin_ty.unwrap_or(ty),
+                binding: Some(binding),
+            }),
+            (None, Some(ty), None) => Some(crate::FunctionResult { ty, binding: None }),
+            (None, None, _) => None,
+        };
+        let mut function = crate::Function {
+            name: Some(f.name.name.to_string()),
+            arguments: ctx.arguments,
+            result: naga_result,
+            local_variables: ctx.locals,
+            expressions: ctx.expressions,
+            named_expressions: ctx.named_expressions,
+            body: ctx.body,
+            diagnostic_filter_leaf: None,
+        };
+        if function.result.is_none() {
+            crate::proc::ensure_block_returns(&mut function.body);
+        }
+
+        let Some(stage) = stage else {
+            let handle = self.module.functions.append(function, f.name.span);
+            self.functions
+                .entry(f.name.name)
+                .or_default()
+                .push(FunctionInfo {
+                    handle,
+                    params,
+                    result,
+                });
+            return Ok(());
+        };
+
+        let workgroup_size = match find_attribute(&f.attributes, "", "numthreads") {
+            Some(attribute) => {
+                if stage != ShaderStage::Compute {
+                    return Err(Error {
+                        kind: ErrorKind::SemanticError(
+                            "only compute shaders can have `numthreads`".into(),
+                        ),
+                        span: attribute.name.span,
+                    });
+                }
+                if attribute.args.len() != 3 {
+                    return Err(Error {
+                        kind: ErrorKind::WrongArgumentCount {
+                            function: "numthreads".to_string(),
+                            expected: 3,
+                            found: attribute.args.len(),
+                        },
+                        span: attribute.name.span,
+                    });
+                }
+                let mut size = [0; 3];
+                for (size, arg) in size.iter_mut().zip(attribute.args.iter()) {
+                    *size = match *arg {
+                        AttributeArg::Expr(ref expr) => self.const_u32(expr)?,
+                        AttributeArg::String(_, span) => {
+                            return Err(Error {
+                                kind: ErrorKind::NotConstant,
+                                span,
+                            })
+                        }
+                    };
+                }
+                size
+            }
+            None if stage == ShaderStage::Compute => {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        "compute shaders need a `numthreads` attribute".into(),
+                    ),
+                    span: f.name.span,
+                })
+            }
+            None => [0; 3],
+        };
+        let early_depth_test = find_attribute(&f.attributes, "", "earlydepthstencil")
+            .map(|_| crate::EarlyDepthTest { conservative: None });
+
+        self.module.entry_points.push(crate::EntryPoint {
+            name: f.name.name.to_string(),
+            stage,
+            early_depth_test,
+            workgroup_size,
+            workgroup_size_overrides: None,
+            function,
+        });
+        Ok(())
+    }
+
+    /// Returns the struct an entry point with `out` parameters returns.
+    fn outputs(
+        &mut self,
+        f: &ast::Function<'a>,
+        result: Option<Handle<Type>>,
+        result_binding: Option<(Binding, Option<Handle<Type>>)>,
+        outputs: &[(
+            &ast::VarDecl<'a>,
+            Handle<Type>,
+            Handle<crate::LocalVariable>,
+        )],
+        next_location: &mut u32,
+    ) -> Result<Outputs> {
+        let mut members = Vec::new();
+        let has_value = result.is_some();
+        if let Some(ty) = result {
+            let Some((binding, builtin_ty)) = result_binding else {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented(
+                        "entry points returning a struct and with `out` parameters",
+                    ),
+                    span: f.result.span,
+                });
+            };
+            members.push(("value".to_string(), builtin_ty.unwrap_or(ty), binding));
+        }
+        for &(param, ty, _) in outputs {
+            let Some(semantic) = param.semantic else {
+                return Err(Error {
+                    kind: ErrorKind::SemanticError(
+                        format!(
+                            "the entry point parameter `{}` needs a semantic",
+                            param.name.name
+                        )
+                        .into(),
+                    ),
+                    span: param.name.span,
+                });
+            };
+            let (binding, builtin_ty) = self.binding(semantic, param, ty, next_location)?;
+            members.push((
+                param.name.name.to_string(),
+                builtin_ty.unwrap_or(ty),
+                binding,
+            ));
+        }
+
+        self.layouter.update(self.module.to_ctx()).unwrap();
+        let mut offset = 0;
+        let mut alignment = Alignment::ONE;
+        let members = members
+            .into_iter()
+            .map(|(name, ty, binding)| {
+                let layout = self.layouter[ty];
+                offset = layout.alignment.round_up(offset);
+                alignment = alignment.max(layout.alignment);
+                let member = StructMember {
+                    name: Some(name),
+                    ty,
+                    binding: Some(binding),
+                    offset,
+                };
+                offset += layout.size;
+                member
+            })
+            .collect();
+        let ty = self.module.types.insert(
+            Type {
+                name: Some(format!("{}Output", f.name.name)),
+                inner: TypeInner::Struct {
+                    members,
+                    span: alignment.round_up(offset),
+                },
+            },
+            f.name.span,
+        );
+        Ok(Outputs {
+            ty,
+            has_value,
+            locals: outputs.iter().map(|&(_, _, local)| local).collect(),
+        })
+    }
+}
+
+// Statements
+
+impl<'a> Context<'_, 'a> {
+    pub fn block(&mut self, block: &[ast::Statement<'a>]) -> Result<()> {
+        self.symbols.push_scope();
+        for statement in block {
+            self.statement(statement)?;
+            // Statements after `return` and the like are never run.
+            if matches!(
+                self.body.last(),
+                Some(
+                    &(Statement::Return { .. }
+                        | Statement::Kill
+                        | Statement::Break
+                        | Statement::Continue)
+                )
+            ) {
+                break;
+            }
+        }
+        self.symbols.pop_scope();
+        Ok(())
+    }
+
+    /// Lowers `statement` into a new block.
+    fn sub_block(&mut self, statement: &ast::Statement<'a>) -> Result<crate::Block> {
+        self.with_body(|ctx| {
+            ctx.symbols.push_scope();
+            let result = ctx.statement(statement);
+            ctx.symbols.pop_scope();
+            result
+        })
+    }
+
+    fn statement(&mut self, statement: &ast::Statement<'a>) -> Result<()> {
+        let span = statement.span;
+        match statement.kind {
+            StatementKind::Empty => {}
+            StatementKind::Block(ref block) => {
+                let block = self.with_body(|ctx| ctx.block(block))?;
+                self.body.push(Statement::Block(block), span);
+            }
+            StatementKind::Local(ref decls) => {
+                for decl in decls {
+                    self.local(decl)?;
+                }
+            }
+            StatementKind::Expr(ref expr) => self.statement_expr(expr)?,
+            StatementKind::If {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.condition(condition)?;
+                let accept = self.sub_block(accept)?;
+                let reject = match *reject {
+                    Some(ref reject) => self.sub_block(reject)?,
+                    None => crate::Block::new(),
+                };
+                self.body.push(
+                    Statement::If {
+                        condition,
+                        accept,
+                        reject,
+                    },
+                    span,
+                );
+            }
+            StatementKind::For {
+                ref init,
+                ref condition,
+                ref update,
+                body: ref loop_body,
+            } => {
+                self.symbols.push_scope();
+                if let Some(ref init) = *init {
+                    self.statement(init)?;
+                }
+                self.lower_loop(condition.as_ref(), update.as_ref(), loop_body, span)?;
+                self.symbols.pop_scope();
+            }
+            StatementKind::While {
+                ref condition,
+                body: ref loop_body,
+            } => self.lower_loop(Some(condition), None, loop_body, span)?,
+            StatementKind::DoWhile {
+                body: ref loop_body,
+                ref condition,
+            } => {
+                self.loop_depth += 1;
+                let body = self.sub_block(loop_body)?;
+                let mut break_if = None;
+                let continuing = self.with_body(|ctx| {
+                    let condition = ctx.condition(condition)?;
+                    break_if = Some(ctx.add(
+                        Expression::Unary {
+                            op: crate::UnaryOperator::LogicalNot,
+                            expr: condition,
+                        },
+                        span,
+                    )?);
+                    Ok(())
+                })?;
+                self.loop_depth -= 1;
+                self.body.push(
+                    Statement::Loop {
+                        body,
+                        continuing,
+                        break_if,
+                    },
+                    span,
+                );
+            }
+            StatementKind::Switch {
+                ref selector,
+                ref cases,
+            } => self.switch(selector, cases, span)?,
+            StatementKind::Break => self.body.push(Statement::Break, span),
+            StatementKind::Continue => self.body.push(Statement::Continue, span),
+            StatementKind::Return(ref value) => {
+                let value = match (value.as_ref(), self.result) {
+                    (Some(value), Some(ty)) => {
+                        let value = self.value(value)?;
+                        Some(self.convert(value, ty, span)?)
+                    }
+                    (None, None) => None,
+                    (Some(_), None) => {
+                        return self.error(
+                            ErrorKind::SemanticError(
+                                "a `void` function can't return a value".into(),
+                            ),
+                            span,
+                        )
+                    }
+                    (None, Some(_)) => {
+                        return self.error(
+                            ErrorKind::SemanticError("expected a value to return".into()),
+                            span,
+                        )
+                    }
+                };
+                let value = match self.outputs {
+                    Some(ref outputs) => {
+                        let ty = outputs.ty;
+                        let locals = outputs.locals.clone();
+                        Some(self.compose_outputs(ty, value, &locals, span)?)
+                    }
+                    None => value,
+                };
+                self.emit_restart();
+                self.body.push(Statement::Return { value }, span);
+            }
+            StatementKind::Discard => {
+                self.emit_restart();
+                self.body.push(Statement::Kill, span);
+            }
+        }
+        Ok(())
+    }
+
+    /// Lowers an expression whose value is unused.
+    fn statement_expr(&mut self, expr: &ast::Expr<'a>) -> Result<()> {
+        let span = expr.span;
+        match expr.kind {
+            ExprKind::Assign {
+                op: None,
+                ref target,
+                ref value,
+            } => {
+                let place = self.place(target)?;
+                let value = self.value(value)?;
+                self.store(place, value, span)
+            }
+            ExprKind::Call { function, ref args } => {
+                self.call(function, args, span)?;
+                Ok(())
+            }
+            ExprKind::MethodCall {
+                ref base,
+                method,
+                ref args,
+            } => {
+                self.method_call(base, method, args, span)?;
+                Ok(())
+            }
+            _ => {
+                self.value(expr)?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Lowers a `for` or `while` loop.
+    fn lower_loop(
+        &mut self,
+        condition: Option<&ast::Expr<'a>>,
+        update: Option<&ast::Expr<'a>>,
+        loop_body: &ast::Statement<'a>,
+        span: Span,
+    ) -> Result<()> {
+        self.loop_depth += 1;
+        let body = self.with_body(|ctx| {
+            if let Some(condition) = condition {
+                let condition = ctx.condition(condition)?;
+                ctx.emit_restart();
+                ctx.body.push(
+                    Statement::If {
+                        condition,
+                        accept: crate::Block::new(),
+                        reject: crate::Block::from_vec(vec![Statement::Break]),
+                    },
+                    span,
+                );
+            }
+            let body = ctx.sub_block(loop_body)?;
+            ctx.body.push(Statement::Block(body), span);
+            Ok(())
+        })?;
+        let continuing = self.with_body(|ctx| match update {
+            Some(update) => ctx.statement_expr(update),
+            None => Ok(()),
+        })?;
+        self.loop_depth -= 1;
+        self.body.push(
+            Statement::Loop {
+                body,
+                continuing,
+                break_if: None,
+            },
+            span,
+        );
+        Ok(())
+    }
+
+    fn switch(
+        &mut self,
+        selector: &ast::Expr<'a>,
+        cases: &[ast::SwitchCase<'a>],
+        span: Span,
+    ) -> Result<()> {
+        let selector_span = selector.span;
+        let selector = self.value(selector)?;
+        let scalar = match self.inner(selector)?.scalar_kind() {
+            Some(ScalarKind::Uint) => Scalar::U32,
+            _ => Scalar::I32,
+        };
+        let selector =
+            self.convert_to_inner(selector, &TypeInner::Scalar(scalar), None, selector_span)?;
+
+        let mut naga_cases = Vec::with_capacity(cases.len() + 1);
+        for case in cases {
+            let value = match case.value {
+                Some(ref value) => {
+                    let handle = self.value(value)?;
+                    let literal = match self.literal(handle) {
+                        Some(Literal::I32(v)) if scalar == Scalar::U32 => {
+                            u32::try_from(v).ok().map(crate::SwitchValue::U32)
+                        }
+                        Some(Literal::U32(v)) if scalar == Scalar::I32 => {
+                            i32::try_from(v).ok().map(crate::SwitchValue::I32)
+                        }
+                        Some(Literal::I32(v)) => Some(crate::SwitchValue::I32(v)),
+                        Some(Literal::U32(v)) => Some(crate::SwitchValue::U32(v)),
+                        _ => None,
+                    };
+                    literal.ok_or(Error {
+                        kind: ErrorKind::SemanticError(
+                            "case labels must be constant integers".into(),
+                        ),
+                        span: value.span,
+                    })?
+                }
+                None => crate::SwitchValue::Default,
+            };
+            // A `break` at the end of a case ends the case, which otherwise
+            // falls through to the next one.
+            let (statements, fall_through) = match case.body.split_last() {
+                Some((last, rest)) if matches!(last.kind, StatementKind::Break) => (rest, false),
+                _ => (&case.body[..], true),
+            };
+            let body = self.with_body(|ctx| ctx.block(statements))?;
+            let fall_through = fall_through
+                && !matches!(
+                    body.last(),
+                    Some(&(Statement::Return { .. } | Statement::Kill | Statement::Continue))
+                );
+            naga_cases.push(crate::SwitchCase {
+                value,
+                body,
+                fall_through,
+            });
+        }
+        if !naga_cases
+            .iter()
+            .any(|case| case.value == crate::SwitchValue::Default)
+        {
+            naga_cases.push(crate::SwitchCase {
+                value: crate::SwitchValue::Default,
+                body: crate::Block::new(),
+                fall_through: false,
+            });
+        }
+        if let Some(last) = naga_cases.last_mut() {
+            last.fall_through = false;
+        }
+        self.emit_restart();
+        self.body.push(
+            Statement::Switch {
+                selector,
+                cases: naga_cases,
+            },
+            span,
+        );
+        Ok(())
+    }
+
+    fn local(&mut self, decl: &ast::VarDecl<'a>) -> Result<()> {
+        let name = decl.name;
+        let span = name.span;
+        if decl.modifiers.is_static && !decl.modifiers.is_const {
+            return self.error(ErrorKind::NotImplemented("`static` local variables"), span);
+        }
+        if decl.modifiers.groupshared || decl.register.is_some() {
+            return self.error(
+                ErrorKind::SemanticError(
+                    "local variables can't be `groupshared` or bound to registers".into(),
+                ),
+                span,
+            );
+        }
+        let ty = self.lowerer.var_ty(decl, false)?;
+        let init = match decl.init {
+            Some(ref init) => Some(self.initializer(init, ty)?),
+            None => None,
+        };
+
+        let place = match (decl.modifiers.is_const, init) {
+            (true, Some(value)) => {
+                self.named_expressions
+                    .entry(value)
+                    .or_insert_with(|| name.name.to_string());
+                Place::Value(value)
+            }
+            (true, None) => {
+                return self.error(
+                    ErrorKind::SemanticError("constants must be initialized".into()),
+                    span,
+                )
+            }
+            (false, init) => {
+                let const_init =
+                    init.filter(|&init| self.loop_depth == 0 && self.is_const_expression(init));
+                let local = self.locals.append(
+                    crate::LocalVariable {
+                        name: Some(name.name.to_string()),
+                        ty,
+                        init: const_init,
+                    },
+                    span,
+                );
+                let pointer = self.add(Expression::LocalVariable(local), span)?;
+                if let (Some(value), None) = (init, const_init) {
+                    self.emit_restart();
+                    self.body.push(Statement::Store { pointer, value }, span);
+                }
+                Place::Pointer {
+                    pointer,
+                    transposed: false,
+                }
+            }
+        };
+        self.symbols.add(name.name, place);
+        Ok(())
+    }
+
+    /// Returns the output struct of an entry point, with `value`, the value
+    /// returned, and the values of the `out` parameters in `locals`.
+    fn compose_outputs(
+        &mut self,
+        ty: Handle<Type>,
+        value: Option<Handle<Expression>>,
+        locals: &[Handle<crate::LocalVariable>],
+        span: Span,
+    ) -> Result<Handle<Expression>> {
+        let mut components = Vec::with_capacity(locals.len() + 1);
+        let has_value = self
+            .outputs
+            .as_ref()
+            .is_some_and(|outputs| outputs.has_value);
+        let TypeInner::Struct { ref members, .. } = self.lowerer.module.types[ty].inner else {
+            unreachable!()
+        };
+        let member_types = members.iter().map(|m| m.ty).collect::<Vec<_>>();
+        let mut member_types = member_types.into_iter();
+        if has_value {
+            let value = match value {
+                Some(value) => value,
+                None => {
+                    return self.error(
+                        ErrorKind::SemanticError("expected a value to return".into()),
+                        span,
+                    )
+                }
+            };
+            let member_ty = member_types.next().unwrap();
+            components.push(self.convert(value, member_ty, span)?);
+        }
+        for (&local, member_ty) in locals.iter().zip(member_types) {
+            let pointer = self.add(Expression::LocalVariable(local), span)?;
+            let value = self.add(Expression::Load { pointer }, span)?;
+            components.push(self.convert(value, member_ty, span)?);
+        }
+        self.add(Expression::Compose { ty, components }, span)
+    }
+}
+
+fn is_struct(module: &Module, ty: Handle<Type>) -> bool {
+    matches!(module.types[ty].inner, TypeInner::Struct { .. })
+}
+
+/// Returns the built-in for the system value semantic `upper`, in upper case,
+/// and the type naga gives it.
+fn builtin(upper: &str) -> Option<(BuiltIn, TypeInner)> {
+    let u32 = TypeInner::Scalar(Scalar::U32);
+    let uvec3 = TypeInner::Vector {
+        size: VectorSize::Tri,
+        scalar: Scalar::U32,
+    };
+    Some(match upper {
+        "SV_POSITION" => (
+            BuiltIn::Position { invariant: false },
+            TypeInner::Vector {
+                size: VectorSize::Quad,
+                scalar: Scalar::F32,
+            },
+        ),
+        "SV_DEPTH" | "SV_DEPTHGREATEREQUAL" | "SV_DEPTHLESSEQUAL" => {
+            (BuiltIn::FragDepth, TypeInner::Scalar(Scalar::F32))
+        }
+        "SV_VERTEXID" => (BuiltIn::VertexIndex, u32),
+        "SV_INSTANCEID" => (BuiltIn::InstanceIndex, u32),
+        "SV_ISFRONTFACE" => (BuiltIn::FrontFacing, TypeInner::Scalar(Scalar::BOOL)),
+        "SV_SAMPLEINDEX" => (BuiltIn::SampleIndex, u32),
+        "SV_COVERAGE" => (BuiltIn::SampleMask, u32),
+        "SV_PRIMITIVEID" => (BuiltIn::PrimitiveIndex, u32),
+        "SV_VIEWID" => (BuiltIn::ViewIndex, TypeInner::Scalar(Scalar::I32)),
+        "SV_DISPATCHTHREADID" => (BuiltIn::GlobalInvocationId, uvec3),
+        "SV_GROUPID" => (BuiltIn::WorkGroupId, uvec3),
+        "SV_GROUPTHREADID" => (BuiltIn::LocalInvocationId, uvec3),
+        "SV_GROUPINDEX" => (BuiltIn::LocalInvocationIndex, u32),
+        _ => return None,
+    })
+}
+
+/// Returns the register class of resources of type `kind`.
+const fn register_class(kind: &TypeKind) -> Option<char> {
+    Some(match *kind {
+        TypeKind::Texture { storage: true, .. }
+        | TypeKind::StructuredBuffer {
+            read_write: true, ..
+        } => 'u',
+        TypeKind::Texture { .. } | TypeKind::StructuredBuffer { .. } => 't',
+        TypeKind::Sampler { .. } => 's',
+        TypeKind::ConstantBuffer(_) => 'b',
+        _ => return None,
+    })
+}
+
+/// Returns the attribute named `namespace::name`, or just `name` if
+/// `namespace` is empty.
+fn find_attribute<'b, 'a>(
+    attributes: &'b [ast::Attribute<'a>],
+    namespace: &str,
+    name: &str,
+) -> Option<&'b ast::Attribute<'a>> {
+    attributes.iter().find(|attribute| {
+        attribute.name.name == name
+            && attribute.namespace.map_or("", |namespace| namespace.name) == namespace
+    })
+}
+
+/// Returns the value of `arg`, which must be an integer literal.
+fn attribute_u32(arg: &AttributeArg) -> Result<u32> {
+    match *arg {
+        AttributeArg::Expr(ast::Expr {
+            kind: ExprKind::Literal(ast::Literal::Number(Number::Int(value))),
+            span,
+        }) => u32::try_from(value).map_err(|_| Error {
+            kind: ErrorKind::InvalidNumber,
+            span,
+        }),
+        AttributeArg::Expr(ast::Expr {
+            kind: ExprKind::Literal(ast::Literal::Number(Number::Uint(value))),
+            span,
+        }) => u32::try_from(value).map_err(|_| Error {
+            kind: ErrorKind::InvalidNumber,
+            span,
+        }),
+        AttributeArg::Expr(ast::Expr { span, .. }) | AttributeArg::String(_, span) => Err(Error {
+            kind: ErrorKind::SemanticError("expected an integer literal".into()),
+            span,
+        }),
+    }
+}
+
+/// Returns the format given by `[[vk::image_format(...)]]`.
+fn image_format(attribute: &ast::Attribute) -> Result<StorageFormat> {
+    use StorageFormat as Sf;
+    let (name, span) = match attribute.args.first() {
+        Some(&AttributeArg::String(name, span)) => (name, span),
+        _ => {
+            return Err(Error {
+                kind: ErrorKind::SemanticError("expected the name of a format".into()),
+                span: attribute.name.span,
+            })
+        }
+    };
+    Ok(match name {
+        "rgba32f" => Sf::Rgba32Float,
+        "rgba16f" => Sf::Rgba16Float,
+        "rg32f" => Sf::Rg32Float,
+        "rg16f" => Sf::Rg16Float,
+        "r11g11b10f" => Sf::Rg11b10Ufloat,
+        "r32f" => Sf::R32Float,
+        "r16f" => Sf::R16Float,
+        "rgba16" => Sf::Rgba16Unorm,
+        "rgb10a2" => Sf::Rgb10a2Unorm,
+        "rgba8" => Sf::Rgba8Unorm,
+        "rg16" => Sf::Rg16Unorm,
+        "rg8" => Sf::Rg8Unorm,
+        "r16" => Sf::R16Unorm,
+        "r8" => Sf::R8Unorm,
+        "rgba16snorm" => Sf::Rgba16Snorm,
+        "rgba8snorm" => Sf::Rgba8Snorm,
+        "rg16snorm" => Sf::Rg16Snorm,
+        "rg8snorm" => Sf::Rg8Snorm,
+        "r16snorm" => Sf::R16Snorm,
+        "r8snorm" => Sf::R8Snorm,
+        "rgba32i" => Sf::Rgba32Sint,
+        "rgba16i" => Sf::Rgba16Sint,
+        "rgba8i" => Sf::Rgba8Sint,
+        "rg32i" => Sf::Rg32Sint,
+        "rg16i" => Sf::Rg16Sint,
+        "rg8i" => Sf::Rg8Sint,
+        "r32i" => Sf::R32Sint,
+        "r16i" => Sf::R16Sint,
+        "r8i" => Sf::R8Sint,
+        "rgba32ui" => Sf::Rgba32Uint,
+        "rgba16ui" => Sf::Rgba16Uint,
+        "rgba8ui" => Sf::Rgba8Uint,
+        "rgb10a2ui" => Sf::Rgb10a2Uint,
+        "rg32ui" => Sf::Rg32Uint,
+        "rg16ui" => Sf::Rg16Uint,
+        "rg8ui" => Sf::Rg8Uint,
+        "r32ui" => Sf::R32Uint,
+        "r16ui" => Sf::R16Uint,
+        "r8ui" => Sf::R8Uint,
+        "r64ui" => Sf::R64Uint,
+        _ => {
+            return Err(Error {
+                kind: ErrorKind::SemanticError(format!("unknown image format `{name}`").into()),
+                span,
+            })
+        }
+    })
+}
+
+/// The `Interlocked*` intrinsics, whose first argument must be an atomic.
+const INTERLOCKED: &[&str] = &[
+    "InterlockedAdd",
+    "InterlockedAnd",
+    "InterlockedOr",
+    "InterlockedXor",
+    "InterlockedMin",
+    "InterlockedMax",
+    "InterlockedExchange",
+    "InterlockedCompareExchange",
+    "InterlockedCompareStore",
+];
+
+/// Returns the names of the functions that have `out` or `inout`
+/// parameters, including intrinsics.
+fn out_functions<'a>(tu: &ast::TranslationUnit<'a>) -> FastHashSet<&'a str> {
+    let mut names = FastHashSet::from_iter(["sincos", "modf", "frexp"]);
+    names.extend(INTERLOCKED);
+    for decl in tu.decls.iter() {
+        if let ast::GlobalDecl::Function(ref f) = *decl {
+            if f.params
+                .iter()
+                .any(|p| p.modifiers.direction != Direction::In)
+            {
+                names.insert(f.name.name);
+            }
+        }
+    }
+    names
+}
+
+/// How names are used in function bodies.
+#[derive(Default)]
+struct Usage<'a> {
+    /// Textures that are compared with `SampleCmp` or `GatherCmp`, which are
+    /// depth textures.
+    compared: FastHashSet<&'a str>,
+    /// Textures that are read by indexing or `Load`.
+    loaded: FastHashSet<&'a str>,
+    /// Names that are assigned to, or passed to a function that might assign
+    /// to them.
+    assigned: FastHashSet<&'a str>,
+    /// Variables that are operated on with `Interlocked*` intrinsics, which
+    /// hold atomics.
+    atomic: FastHashSet<&'a str>,
+}
+
+impl<'a> Usage<'a> {
+    fn block(&mut self, block: &[ast::Statement<'a>], out_functions: &FastHashSet<&'a str>) {
+        for statement in block {
+            self.statement(statement, out_functions);
+        }
+    }
+
+    fn statement(&mut self, statement: &ast::Statement<'a>, out_functions: &FastHashSet<&'a str>) {
+        match statement.kind {
+            StatementKind::Empty
+            | StatementKind::Break
+            | StatementKind::Continue
+            | StatementKind::Discard
+            | StatementKind::Return(None) => {}
+            StatementKind::Block(ref block) => self.block(block, out_functions),
+            StatementKind::Local(ref decls) => {
+                for decl in decls {
+                    if let Some(ref init) = decl.init {
+                        self.expr(init, out_functions);
+                    }
+                }
+            }
+            StatementKind::Expr(ref expr) | StatementKind::Return(Some(ref expr)) => {
+                self.expr(expr, out_functions)
+            }
+            StatementKind::If {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                self.expr(condition, out_functions);
+                self.statement(accept, out_functions);
+                if let Some(ref reject) = *reject {
+                    self.statement(reject, out_functions);
+                }
+            }
+            StatementKind::For {
+                ref init,
+                ref condition,
+                ref update,
+                ref body,
+            } => {
+                if let Some(ref init) = *init {
+                    self.statement(init, out_functions);
+                }
+                for expr in condition.iter().chain(update.iter()) {
+                    self.expr(expr, out_functions);
+                }
+                self.statement(body, out_functions);
+            }
+            StatementKind::While {
+                ref condition,
+                ref body,
+            }
+            | StatementKind::DoWhile {
+                ref body,
+                ref condition,
+            } => {
+                self.expr(condition, out_functions);
+                self.statement(body, out_functions);
+            }
+            StatementKind::Switch {
+                ref selector,
+                ref cases,
+            } => {
+                self.expr(selector, out_functions);
+                for case in cases {
+                    self.block(&case.body, out_functions);
+                }
+            }
+        }
+    }
+
+    fn expr(&mut self, expr: &ast::Expr<'a>, out_functions: &FastHashSet<&'a str>) {
+        match expr.kind {
+            ExprKind::Literal(_) | ExprKind::Ident(_) => {}
+            ExprKind::Unary { ref expr, .. }
+            | ExprKind::Cast { ref expr, .. }
+            | ExprKind::Member { base: ref expr, .. } => self.expr(expr, out_functions),
+            ExprKind::Binary {
+                ref left,
+                ref right,
+                ..
+            } => {
+                self.expr(left, out_functions);
+                self.expr(right, out_functions);
+            }
+            ExprKind::Assign {
+                op,
+                ref target,
+                ref value,
+            } => {
+                self.assigned.extend(root(target));
+                match (op, &target.kind) {
+                    // Storing to a texel doesn't read the texture.
+                    (
+                        None,
+                        &ExprKind::Index {
+                            ref base,
+                            ref index,
+                        },
+                    ) => {
+                        if !matches!(base.kind, ExprKind::Ident(_)) {
+                            self.expr(base, out_functions);
+                        }
+                        self.expr(index, out_functions);
+                    }
+                    _ => self.expr(target, out_functions),
+                }
+                self.expr(value, out_functions);
+            }
+            ExprKind::IncDec { ref target, .. } => {
+                self.assigned.extend(root(target));
+                self.expr(target, out_functions);
+            }
+            ExprKind::Ternary {
+                ref condition,
+                ref accept,
+                ref reject,
+            } => {
+                self.expr(condition, out_functions);
+                self.expr(accept, out_functions);
+                self.expr(reject, out_functions);
+            }
+            ExprKind::Call { function, ref args } => {
+                if INTERLOCKED.contains(&function.name) {
+                    self.atomic.extend(args.first().and_then(root));
+                }
+                for arg in args {
+                    if out_functions.contains(function.name) {
+                        self.assigned.extend(root(arg));
+                    }
+                    self.expr(arg, out_functions);
+                }
+            }
+            ExprKind::Construct { ref args, .. } | ExprKind::InitList(ref args) => {
+                for arg in args {
+                    self.expr(arg, out_functions);
+                }
+            }
+            ExprKind::Index {
+                ref base,
+                ref index,
+            } => {
+                if let ExprKind::Ident(name) = base.kind {
+                    self.loaded.insert(name);
+                }
+                self.expr(base, out_functions);
+                self.expr(index, out_functions);
+            }
+            ExprKind::MethodCall {
+                ref base,
+                method,
+                ref args,
+            } => {
+                if let ExprKind::Ident(name) = base.kind {
+                    match method.name {
+                        "SampleCmp" | "SampleCmpLevelZero" | "SampleCmpLevel" | "GatherCmp"
+                        | "GatherCmpRed" => {
+                            self.compared.insert(name);
+                        }
+                        "Load" => {
+                            self.loaded.insert(name);
+                        }
+                        _ => {}
+                    }
+                }
+                self.expr(base, out_functions);
+                for arg in args {
+                    self.expr(arg, out_functions);
+                }
+            }
+        }
+    }
+}
+
+/// Returns the variable `expr` is a part of, if any.
+fn root<'a>(expr: &ast::Expr<'a>) -> Option<&'a str> {
+    match expr.kind {
+        ExprKind::Ident(name) => Some(name),
+        ExprKind::Member { ref base, .. } | ExprKind::Index { ref base, .. } => root(base),
+        _ => None,
+    }
+}
@@ -0,0 +1,193 @@
+/*!
+Frontend for [HLSL][hlsl] (High-Level Shading Language).
+
+To begin, take a look at the documentation for the [`Frontend`].
+
+# Supported subset
+
+The frontend accepts a practical subset of Shader Model 5 and 6:
+
+- `struct`s, `cbuffer`s and `ConstantBuffer<T>`, `StructuredBuffer<T>` and
+  `RWStructuredBuffer<T>`, `Texture1D`, `Texture2D`, `Texture3D`,
+  `TextureCube`, their `Array` and `MS` variants, `RWTexture*`,
+  `SamplerState` and `SamplerComparisonState`, `static` and `groupshared`
+  variables, and constants.
+- Functions, with `in`, `out` and `inout` parameters and overloads.
+- The usual statements and operators, with HLSL's implicit conversions.
+- Common intrinsics, including `mul`, the texture methods `Sample*`,
+  `Gather*`, `Load` and `GetDimensions`, and the group barriers.
+
+Entry points are the functions named in [`Options::entry_points`], and the
+functions with a `[shader("...")]` attribute, or a `[numthreads(...)]`
+attribute for compute shaders.
+
+# Bindings
+
+A resource bound to `register(t3, space1)` is given group 1 and binding 3,
+plus the shift for its register class in [`Options::register_shifts`].
+`[[vk::binding(binding, group)]]` gives the binding directly. Resources
+without either get the first binding in group 0 that no other resource uses.
+As with DXC, resources with registers of different classes but the same
+index and space get the same binding, which is an error, unless the shifts
+tell them apart.
+
+Semantics starting with `SV_` are mapped to built-ins, `SV_Target{N}` to
+location `N`, and other semantics get locations in the order they are
+declared in, unless they have a `[[vk::location(N)]]` attribute. This
+matches what DXC does when compiling to SPIR-V.
+
+# Limitations
+
+- There is no preprocessor: `#pragma` is ignored, and other directives are
+  errors.
+- Matrices are stored column by column unless they are `row_major`, as HLSL
+  does. Members of `cbuffer`s are placed at the offsets HLSL's packing
+  rules give them. A vector HLSL packs at an offset naga's vectors can't
+  have, like a `float3` after a `float`, is stored as one member per
+  component, named after the vector and the component. It is an error for
+  a member to be packed into the unused part of the last register of an
+  array, matrix or `struct`, or to have a `struct` type whose members WGSL
+  would lay out differently. `struct`s outside of `cbuffer`s use WGSL's
+  layout. `packoffset` is not supported.
+- Globals that are not resources, `static` or `groupshared`, which HLSL
+  places in an implicit `$Globals` constant buffer, are not supported.
+- `Interlocked*` intrinsics only work on `RWStructuredBuffer`s and
+  `groupshared` variables of `int`s or `uint`s, or arrays of them, which
+  become arrays of atomics. They can't operate on members of `struct`s.
+- `half` and `min16float` are 32-bit floating point types.
+
+[hlsl]: https://learn.microsoft.com/en-us/windows/win32/direct3dhlsl/dx-graphics-hlsl
+*/
+
+pub use error::{Error, ErrorKind};
+
+use crate::{FastHashMap, Module, ShaderStage, TypeInner, UniqueArena};
+
+mod ast;
+mod context;
+mod error;
+mod intrinsics;
+mod lex;
+mod lower;
+mod parse;
+#[cfg(test)]
+mod tests;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// The amounts added to the indices of registers of each class to get
+/// binding numbers.
+///
+/// These work like DXC's `-fvk-b-shift`, `-fvk-t-shift`, `-fvk-s-shift` and
+/// `-fvk-u-shift` options, which are used to keep resources in different
+/// register classes from getting the same binding.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+pub struct RegisterShifts {
+    /// The shift for `b` registers, used by constant buffers.
+    pub constant_buffer: u32,
+    /// The shift for `t` registers, used by textures and read-only buffers.
+    pub texture: u32,
+    /// The shift for `s` registers, used by samplers.
+    pub sampler: u32,
+    /// The shift for `u` registers, used by read-write textures and buffers.
+    pub unordered_access: u32,
+}
+
+/// Per-shader options passed to [`parse`](Frontend::parse).
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+pub struct Options {
+    /// Functions that are entry points of the given stages, in addition to
+    /// the functions marked with a `[shader("...")]` or `[numthreads(...)]`
+    /// attribute.
+    ///
+    /// HLSL shaders usually give the entry point and its stage on the
+    /// compiler's command line rather than in the source.
+    pub entry_points: FastHashMap<String, ShaderStage>,
+    /// The shifts added to register indices to get binding numbers.
+    pub register_shifts: RegisterShifts,
+}
+
+impl Options {
+    /// Returns options with the single entry point `name` of `stage`.
+    pub fn with_entry_point(name: &str, stage: ShaderStage) -> Self {
+        Options {
+            entry_points: [(name.to_string(), stage)].into_iter().collect(),
+            ..Default::default()
+        }
+    }
+}
+
+/// The `Frontend` parses HLSL source into a [`Module`].
+///
+/// # Example usage
+/// ```rust
+/// use naga::ShaderStage;
+/// use naga::front::hlsl::{Frontend, Options};
+///
+/// let hlsl = r#"
+///     float4 main(float4 position : POSITION) : SV_Position {
+///         return position;
+///     }
+/// "#;
+///
+/// let options = Options::with_entry_point("main", ShaderStage::Vertex);
+/// let module = Frontend::new().parse(&options, hlsl).unwrap();
+/// assert_eq!(module.entry_points.len(), 1);
+/// ```
+#[derive(Debug, Default)]
+pub struct Frontend {}
+
+impl Frontend {
+    pub const fn new() -> Self {
+        Frontend {}
+    }
+
+    /// Parses `source`, returning a [`Module`] or the first error found.
+    pub fn parse(&mut self, options: &Options, source: &str) -> Result<Module> {
+        let tokens = lex::tokenize(source)?;
+        let tu = parse::Parser::new(tokens).parse()?;
+        lower::Lowerer::new(options).lower(&tu)
+    }
+}
+
+/// Returns the HLSL name of the type `inner`, for error messages.
+fn type_name(inner: &TypeInner, types: &UniqueArena<crate::Type>) -> String {
+    let scalar_name = |scalar: crate::Scalar| match (scalar.kind, scalar.width) {
+        (crate::ScalarKind::Bool, _) => "bool",
+        (crate::ScalarKind::Sint, 8) => "int64_t",
+        (crate::ScalarKind::Sint, _) => "int",
+        (crate::ScalarKind::Uint, 8) => "uint64_t",
+        (crate::ScalarKind::Uint, _) => "uint",
+        (crate::ScalarKind::Float, 8) => "double",
+        (crate::ScalarKind::Float, 2) => "float16_t",
+        (crate::ScalarKind::Float, _) => "float",
+        (crate::ScalarKind::AbstractInt, _) => "int",
+        (crate::ScalarKind::AbstractFloat, _) => "float",
+    };
+    match *inner {
+        TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => scalar_name(scalar).to_string(),
+        TypeInner::Vector { size, scalar } => format!("{}{}", scalar_name(scalar), size as u8),
+        // An HLSL matrix is the transpose of the naga matrix.
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => format!("{}{}x{}", scalar_name(scalar), columns as u8, rows as u8),
+        TypeInner::Array { base, size, .. } | TypeInner::BindingArray { base, size } => {
+            let base = type_name(&types[base].inner, types);
+            match size {
+                crate::ArraySize::Constant(size) => format!("{base}[{size}]"),
+                _ => format!("{base}[]"),
+            }
+        }
+        TypeInner::Struct { .. } => "struct".to_string(),
+        TypeInner::Pointer { base, .. } => type_name(&types[base].inner, types),
+        TypeInner::Image { .. } => "texture".to_string(),
+        TypeInner::Sampler { .. } => "sampler".to_string(),
+        _ => "value".to_string(),
+    }
+}
@@ -0,0 +1,1174 @@
+//! Parsing [`Token`]s into a [`TranslationUnit`].
+
+use super::ast::*;
+use super::error::{Error, ErrorKind};
+use super::lex::{Number, Token};
+use crate::{FastHashSet, ImageDimension, Scalar, Span, VectorSize};
+use std::borrow::Cow;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Keywords that can precede the type of a declaration.
+const MODIFIERS: &[&str] = &[
+    "static",
+    "const",
+    "groupshared",
+    "uniform",
+    "extern",
+    "precise",
+    "volatile",
+    "globallycoherent",
+    "row_major",
+    "column_major",
+    "nointerpolation",
+    "linear",
+    "noperspective",
+    "centroid",
+    "sample",
+    "in",
+    "out",
+    "inout",
+];
+
+/// Returns the scalar type named `name`.
+pub fn scalar_from_name(name: &str) -> Option<Scalar> {
+    Some(match name {
+        "bool" => Scalar::BOOL,
+        "int" | "int32_t" | "min16int" | "min12int" => Scalar::I32,
+        "uint" | "dword" | "uint32_t" | "min16uint" => Scalar::U32,
+        // Without `-enable-16bit-types`, `half` is a 32-bit type.
+        "float" | "float32_t" | "half" | "min16float" | "min10float" => Scalar::F32,
+        "double" | "float64_t" => Scalar::F64,
+        "float16_t" => Scalar::F16,
+        "int64_t" => Scalar::I64,
+        "uint64_t" => Scalar::U64,
+        _ => return None,
+    })
+}
+
+/// The names of scalar types that vector and matrix type names start with,
+/// longest first.
+const SCALAR_PREFIXES: &[&str] = &[
+    "min16float",
+    "min10float",
+    "float16_t",
+    "float32_t",
+    "float64_t",
+    "min16uint",
+    "min16int",
+    "min12int",
+    "uint32_t",
+    "uint64_t",
+    "int32_t",
+    "int64_t",
+    "double",
+    "float",
+    "dword",
+    "bool",
+    "half",
+    "uint",
+    "int",
+];
+
+const fn vector_size(n: u8) -> Option<VectorSize> {
+    match n {
+        b'2' => Some(VectorSize::Bi),
+        b'3' => Some(VectorSize::Tri),
+        b'4' => Some(VectorSize::Quad),
+        _ => None,
+    }
+}
+
+/// Returns the type named `name`, if it is a scalar, vector or matrix type
+/// spelled like `float`, `float3` or `float4x4`.
+fn numeric_type_from_name<'a>(name: &str) -> Option<TypeKind<'a>> {
+    if let Some(scalar) = scalar_from_name(name) {
+        return Some(TypeKind::Scalar(scalar));
+    }
+    SCALAR_PREFIXES.iter().find_map(|&prefix| {
+        let rest = name.strip_prefix(prefix)?.as_bytes();
+        let scalar = scalar_from_name(prefix)?;
+        match *rest {
+            [b'1'] => Some(TypeKind::Scalar(scalar)),
+            [n] => Some(TypeKind::Vector(vector_size(n)?, scalar)),
+            [rows, b'x', columns] => Some(TypeKind::Matrix {
+                rows: vector_size(rows)?,
+                columns: vector_size(columns)?,
+                scalar,
+            }),
+            _ => None,
+        }
+    })
+}
+
+/// Returns the texture type named `name`, without its template arguments.
+fn texture_from_name(name: &str) -> Option<(ImageDimension, bool, bool, bool)> {
+    let (storage, name) = match name.strip_prefix("RW") {
+        Some(name) => (true, name),
+        None => (false, name),
+    };
+    let (dim, arrayed, multisampled) = match name {
+        "Texture1D" => (ImageDimension::D1, false, false),
+        "Texture1DArray" => (ImageDimension::D1, true, false),
+        "Texture2D" => (ImageDimension::D2, false, false),
+        "Texture2DArray" => (ImageDimension::D2, true, false),
+        "Texture2DMS" if !storage => (ImageDimension::D2, false, true),
+        "Texture2DMSArray" if !storage => (ImageDimension::D2, true, true),
+        "Texture3D" => (ImageDimension::D3, false, false),
+        "TextureCube" if !storage => (ImageDimension::Cube, false, false),
+        "TextureCubeArray" if !storage => (ImageDimension::Cube, true, false),
+        _ => return None,
+    };
+    Some((dim, arrayed, multisampled, storage))
+}
+
+pub struct Parser<'a> {
+    tokens: Vec<(Token<'a>, Span)>,
+    pos: usize,
+    /// The names of the structs declared so far, which are types.
+    struct_names: FastHashSet<&'a str>,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<(Token<'a>, Span)>) -> Self {
+        Parser {
+            tokens,
+            pos: 0,
+            struct_names: FastHashSet::default(),
+        }
+    }
+
+    fn peek(&self) -> Token<'a> {
+        self.tokens[self.pos].0
+    }
+
+    fn peek_at(&self, offset: usize) -> Token<'a> {
+        self.tokens
+            .get(self.pos + offset)
+            .map_or(Token::End, |&(token, _)| token)
+    }
+
+    fn span(&self) -> Span {
+        self.tokens[self.pos].1
+    }
+
+    /// Returns the span from `start` to the end of the last token.
+    fn span_from(&self, start: Span) -> Span {
+        let end = self.tokens[self.pos.saturating_sub(1)].1;
+        start.until(&end)
+    }
+
+    fn next(&mut self) -> (Token<'a>, Span) {
+        let token = self.tokens[self.pos];
+        if token.0 != Token::End {
+            self.pos += 1;
+        }
+        token
+    }
+
+    fn error<T>(&self, expected: impl Into<Cow<'static, str>>) -> Result<T> {
+        let (token, span) = self.tokens[self.pos];
+        Err(Error {
+            kind: match token {
+                Token::End => ErrorKind::UnexpectedEndOfFile,
+                _ => ErrorKind::UnexpectedToken {
+                    expected: expected.into(),
+                    found: token.to_string(),
+                },
+            },
+            span,
+        })
+    }
+
+    fn skip(&mut self, punct: &'static str) -> bool {
+        if self.peek() == Token::Punct(punct) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_ident(&mut self, name: &str) -> bool {
+        if self.peek() == Token::Ident(name) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punct: &'static str) -> Result<Span> {
+        let span = self.span();
+        match self.skip(punct) {
+            true => Ok(span),
+            false => self.error(format!("`{punct}`")),
+        }
+    }
+
+    fn ident(&mut self) -> Result<Ident<'a>> {
+        match self.peek() {
+            Token::Ident(name) => {
+                let span = self.next().1;
+                Ok(Ident { name, span })
+            }
+            _ => self.error("identifier"),
+        }
+    }
+
+    /// Expects the `>` closing a template argument list, splitting a `>>`
+    /// in two.
+    fn expect_template_close(&mut self) -> Result<()> {
+        match self.peek() {
+            Token::Punct(">") => {
+                self.pos += 1;
+                Ok(())
+            }
+            Token::Punct(">>") | Token::Punct(">=") | Token::Punct(">>=") => {
+                let (token, span) = self.tokens[self.pos];
+                let rest = match token {
+                    Token::Punct(">>") => ">",
+                    Token::Punct(">=") => "=",
+                    _ => ">=",
+                };
+                let range = span.to_range().unwrap_or_default();
+                let span = Span::new(range.start as u32 + 1, range.end as u32);
+                self.tokens[self.pos] = (Token::Punct(rest), span);
+                Ok(())
+            }
+            _ => self.error("`>`"),
+        }
+    }
+
+    /// Parses a whole translation unit.
+    pub fn parse(&mut self) -> Result<TranslationUnit<'a>> {
+        let mut tu = TranslationUnit::default();
+        while self.peek() != Token::End {
+            if self.skip(";") {
+                continue;
+            }
+            self.global_decl(&mut tu)?;
+        }
+        Ok(tu)
+    }
+
+    fn global_decl(&mut self, tu: &mut TranslationUnit<'a>) -> Result<()> {
+        let attributes = self.attributes()?;
+        match self.peek() {
+            Token::Ident("struct") => {
+                self.next();
+                let name = self.ident()?;
+                let members = self.members()?;
+                self.expect(";")?;
+                self.struct_names.insert(name.name);
+                tu.decls.push(GlobalDecl::Struct(Struct { name, members }));
+            }
+            Token::Ident("cbuffer") => {
+                self.next();
+                let name = self.ident()?;
+                let mut register = None;
+                while self.skip(":") {
+                    register = Some(self.register()?);
+                }
+                let members = self.members()?;
+                self.skip(";");
+                tu.decls.push(GlobalDecl::CBuffer(CBuffer {
+                    attributes,
+                    name,
+                    register,
+                    members,
+                }));
+            }
+            Token::Ident("typedef" | "tbuffer" | "namespace" | "template" | "class") => {
+                return Err(Error {
+                    kind: ErrorKind::NotImplemented(
+                        "`typedef`, `tbuffer`, `namespace`, `template` and `class`",
+                    ),
+                    span: self.span(),
+                });
+            }
+            _ => {
+                let modifiers = self.modifiers()?;
+                let ty = self.ty()?;
+                let name = self.ident()?;
+                if self.peek() == Token::Punct("(") {
+                    let function = self.function(attributes, ty, name)?;
+                    tu.decls.push(GlobalDecl::Function(function));
+                } else {
+                    let decls = self.var_decls(attributes, modifiers, ty, name)?;
+                    self.expect(";")?;
+                    tu.decls.extend(decls.into_iter().map(GlobalDecl::Variable));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Parses any attributes, like `[numthreads(8, 8, 1)]` or
+    /// `[[vk::binding(0)]]`.
+    fn attributes(&mut self) -> Result<Vec<Attribute<'a>>> {
+        let mut attributes = Vec::new();
+        while self.peek() == Token::Punct("[") {
+            self.next();
+            let double = self.skip("[");
+            loop {
+                let mut namespace = None;
+                let mut name = self.ident()?;
+                if self.skip("::") {
+                    namespace = Some(name);
+                    name = self.ident()?;
+                }
+                let mut args = Vec::new();
+                if self.skip("(") {
+                    while !self.skip(")") {
+                        if let Token::String(string) = self.peek() {
+                            let span = self.next().1;
+                            args.push(AttributeArg::String(string, span));
+                        } else {
+                            args.push(AttributeArg::Expr(self.assignment_expr()?));
+                        }
+                        if !self.skip(",") {
+                            self.expect(")")?;
+                            break;
+                        }
+                    }
+                }
+                attributes.push(Attribute {
+                    namespace,
+                    name,
+                    args,
+                });
+                if !double || !self.skip(",") {
+                    break;
+                }
+            }
+            self.expect("]")?;
+            if double {
+                self.expect("]")?;
+            }
+        }
+        Ok(attributes)
+    }
+
+    fn modifiers(&mut self) -> Result<Modifiers> {
+        let mut modifiers = Modifiers::default();
+        while let Token::Ident(name) = self.peek() {
+            if !self.at_modifier() {
+                break;
+            }
+            self.next();
+            match name {
+                "static" => modifiers.is_static = true,
+                "const" => modifiers.is_const = true,
+                "groupshared" => modifiers.groupshared = true,
+                "row_major" => modifiers.row_major = true,
+                "column_major" => modifiers.row_major = false,
+                "nointerpolation" => modifiers.interpolation = Some(crate::Interpolation::Flat),
+                "linear" => modifiers.interpolation = Some(crate::Interpolation::Perspective),
+                "noperspective" => modifiers.interpolation = Some(crate::Interpolation::Linear),
+                "centroid" => modifiers.sampling = Some(crate::Sampling::Centroid),
+                "sample" => modifiers.sampling = Some(crate::Sampling::Sample),
+                "in" => modifiers.direction = Direction::In,
+                "out" => modifiers.direction = Direction::Out,
+                "inout" => modifiers.direction = Direction::InOut,
+                _ => {}
+            }
+        }
+        Ok(modifiers)
+    }
+
+    /// Returns whether the next token is a modifier keyword.
+    ///
+    /// Some modifiers, like `sample`, are also commonly used as names, so
+    /// they are only taken as modifiers if another name follows.
+    fn at_modifier(&self) -> bool {
+        match self.peek() {
+            Token::Ident(name) => {
+                MODIFIERS.contains(&name) && matches!(self.peek_at(1), Token::Ident(_))
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns whether the next tokens are a type followed by a name, which
+    /// starts a declaration rather than an expression.
+    fn at_declaration(&mut self) -> bool {
+        let start = self.pos;
+        while self.at_modifier() {
+            self.pos += 1;
+        }
+        let is_declaration = self.ty().is_ok() && matches!(self.peek(), Token::Ident(_));
+        self.pos = start;
+        is_declaration
+    }
+
+    /// Returns whether the next token is the name of a type.
+    fn at_type(&self) -> bool {
+        match self.peek() {
+            Token::Ident(name) => {
+                self.struct_names.contains(name)
+                    || numeric_type_from_name(name).is_some()
+                    || texture_from_name(name).is_some()
+                    || matches!(
+                        name,
+                        "void"
+                            | "vector"
+                            | "matrix"
+                            | "SamplerState"
+                            | "sampler"
+                            | "SamplerComparisonState"
+                            | "StructuredBuffer"
+                            | "RWStructuredBuffer"
+                            | "ConstantBuffer"
+                    )
+            }
+            _ => false,
+        }
+    }
+
+    fn ty(&mut self) -> Result<Type<'a>> {
+        if !self.at_type() {
+            return match self.peek() {
+                Token::Ident(name) => Err(Error {
+                    kind: ErrorKind::UnknownType(name.to_string()),
+                    span: self.span(),
+                }),
+                _ => self.error("a type"),
+            };
+        }
+        let ident = self.ident()?;
+        let kind = match ident.name {
+            "void" => TypeKind::Void,
+            "SamplerState" | "sampler" => TypeKind::Sampler { comparison: false },
+            "SamplerComparisonState" => TypeKind::Sampler { comparison: true },
+            "vector" => {
+                if self.skip("<") {
+                    let scalar = self.scalar_arg()?;
+                    self.expect(",")?;
+                    let size = self.size_arg()?;
+                    self.expect_template_close()?;
+                    match size {
+                        None => TypeKind::Scalar(scalar),
+                        Some(size) => TypeKind::Vector(size, scalar),
+                    }
+                } else {
+                    TypeKind::Vector(VectorSize::Quad, Scalar::F32)
+                }
+            }
+            "matrix" => {
+                if self.skip("<") {
+                    let scalar = self.scalar_arg()?;
+                    self.expect(",")?;
+                    let rows = self.size_arg()?;
+                    self.expect(",")?;
+                    let columns = self.size_arg()?;
+                    self.expect_template_close()?;
+                    match (rows, columns) {
+                        (Some(rows), Some(columns)) => TypeKind::Matrix {
+                            rows,
+                            columns,
+                            scalar,
+                        },
+                        _ => {
+                            return Err(Error {
+                                kind: ErrorKind::NotImplemented(
+                                    "matrices with a single row or column",
+                                ),
+                                span: self.span_from(ident.span),
+                            })
+                        }
+                    }
+                } else {
+                    TypeKind::Matrix {
+                        rows: VectorSize::Quad,
+                        columns: VectorSize::Quad,
+                        scalar: Scalar::F32,
+                    }
+                }
+            }
+            "StructuredBuffer" | "RWStructuredBuffer" | "ConstantBuffer" => {
+                self.expect("<")?;
+                let element = Box::new(self.ty()?);
+                self.expect_template_close()?;
+                match ident.name {
+                    "ConstantBuffer" => TypeKind::ConstantBuffer(element),
+                    name => TypeKind::StructuredBuffer {
+                        element,
+                        read_write: name.starts_with("RW"),
+                    },
+                }
+            }
+            name if self.struct_names.contains(name) => TypeKind::Named(ident),
+            name => {
+                if let Some(kind) = numeric_type_from_name(name) {
+                    kind
+                } else if let Some((dim, arrayed, multisampled, storage)) = texture_from_name(name)
+                {
+                    let mut texel = None;
+                    if self.skip("<") {
+                        // `unorm` and `snorm` don't change how texels are
+                        // read or written.
+                        let _ = self.skip_ident("unorm") || self.skip_ident("snorm");
+                        texel = Some(Box::new(self.ty()?));
+                        // The sample count of a `Texture2DMS`
+                        if self.skip(",") {
+                            self.expr()?;
+                        }
+                        self.expect_template_close()?;
+                    }
+                    TypeKind::Texture {
+                        dim,
+                        arrayed,
+                        multisampled,
+                        storage,
+                        texel,
+                    }
+                } else {
+                    return Err(Error {
+                        kind: ErrorKind::UnknownType(name.to_string()),
+                        span: ident.span,
+                    });
+                }
+            }
+        };
+        Ok(Type {
+            kind,
+            span: self.span_from(ident.span),
+        })
+    }
+
+    fn scalar_arg(&mut self) -> Result<Scalar> {
+        let ident = self.ident()?;
+        scalar_from_name(ident.name).ok_or(Error {
+            kind: ErrorKind::UnknownType(ident.name.to_string()),
+            span: ident.span,
+        })
+    }
+
+    /// Parses the size of a vector or matrix template, returning `None` for
+    /// a size of 1.
+    fn size_arg(&mut self) -> Result<Option<VectorSize>> {
+        let span = self.span();
+        match self.next().0 {
+            Token::Number(Number::Int(1) | Number::Uint(1)) => Ok(None),
+            Token::Number(Number::Int(2) | Number::Uint(2)) => Ok(Some(VectorSize::Bi)),
+            Token::Number(Number::Int(3) | Number::Uint(3)) => Ok(Some(VectorSize::Tri)),
+            Token::Number(Number::Int(4) | Number::Uint(4)) => Ok(Some(VectorSize::Quad)),
+            _ => Err(Error {
+                kind: ErrorKind::UnexpectedToken {
+                    expected: "a size from 1 to 4".into(),
+                    found: self.tokens[self.pos - 1].0.to_string(),
+                },
+                span,
+            }),
+        }
+    }
+
+    /// Parses the `{ ... }` members of a struct or cbuffer.
+    fn members(&mut self) -> Result<Vec<VarDecl<'a>>> {
+        self.expect("{")?;
+        let mut members = Vec::new();
+        while !self.skip("}") {
+            let attributes = self.attributes()?;
+            let modifiers = self.modifiers()?;
+            let ty = self.ty()?;
+            let name = self.ident()?;
+            members.extend(self.var_decls(attributes, modifiers, ty, name)?);
+            self.expect(";")?;
+        }
+        Ok(members)
+    }
+
+    /// Parses the rest of a declaration of one or more variables, after the
+    /// first name.
+    fn var_decls(
+        &mut self,
+        attributes: Vec<Attribute<'a>>,
+        modifiers: Modifiers,
+        ty: Type<'a>,
+        name: Ident<'a>,
+    ) -> Result<Vec<VarDecl<'a>>> {
+        let mut decls = vec![self.var_decl(attributes, modifiers, ty, name)?];
+        while self.skip(",") {
+            let name = self.ident()?;
+            // Later declarators share the type of the first.
+            let ty = decls[0].ty.clone();
+            decls.push(self.var_decl(Vec::new(), modifiers, ty, name)?);
+        }
+        Ok(decls)
+    }
+
+    fn var_decl(
+        &mut self,
+        attributes: Vec<Attribute<'a>>,
+        modifiers: Modifiers,
+        ty: Type<'a>,
+        name: Ident<'a>,
+    ) -> Result<VarDecl<'a>> {
+        let mut array_sizes = Vec::new();
+        while self.skip("[") {
+            if self.skip("]") {
+                array_sizes.push(None);
+            } else {
+                array_sizes.push(Some(self.expr()?));
+                self.expect("]")?;
+            }
+        }
+        let (semantic, register) = self.annotations()?;
+        let init = match self.skip("=") {
+            true => Some(self.assignment_expr()?),
+            false => None,
+        };
+        Ok(VarDecl {
+            attributes,
+            modifiers,
+            ty,
+            name,
+            array_sizes,
+            semantic,
+            register,
+            init,
+        })
+    }
+
+    /// Parses the `: SEMANTIC` and `: register(...)` annotations of a
+    /// declaration.
+    fn annotations(&mut self) -> Result<(Option<Ident<'a>>, Option<Register>)> {
+        let mut semantic = None;
+        let mut register = None;
+        while self.skip(":") {
+            match self.peek() {
+                Token::Ident("register") => register = Some(self.register()?),
+                Token::Ident("packoffset") => {
+                    return Err(Error {
+                        kind: ErrorKind::NotImplemented("`packoffset`"),
+                        span: self.span(),
+                    })
+                }
+                _ => semantic = Some(self.ident()?),
+            }
+        }
+        Ok((semantic, register))
+    }
+
+    /// Parses `register(t0, space1)`.
+    fn register(&mut self) -> Result<Register> {
+        let start = self.span();
+        if !self.skip_ident("register") {
+            return self.error("`register`");
+        }
+        self.expect("(")?;
+        let slot = self.ident()?;
+        let invalid = |span| Error {
+            kind: ErrorKind::InvalidRegister(slot.name.to_string()),
+            span,
+        };
+        let mut chars = slot.name.chars();
+        let class = chars
+            .next()
+            .map(|c| c.to_ascii_lowercase())
+            .filter(|c| matches!(*c, 'b' | 't' | 's' | 'u'))
+            .ok_or_else(|| invalid(slot.span))?;
+        let index = chars.as_str().parse().map_err(|_| invalid(slot.span))?;
+        let mut space = 0;
+        if self.skip(",") {
+            let space_ident = self.ident()?;
+            space = space_ident
+                .name
+                .strip_prefix("space")
+                .and_then(|space| space.parse().ok())
+                .ok_or(Error {
+                    kind: ErrorKind::InvalidRegister(space_ident.name.to_string()),
+                    span: space_ident.span,
+                })?;
+        }
+        self.expect(")")?;
+        Ok(Register {
+            class,
+            index,
+            space,
+            span: self.span_from(start),
+        })
+    }
+
+    fn function(
+        &mut self,
+        attributes: Vec<Attribute<'a>>,
+        result: Type<'a>,
+        name: Ident<'a>,
+    ) -> Result<Function<'a>> {
+        self.expect("(")?;
+        let mut params = Vec::new();
+        if !self.skip(")") {
+            if self.peek() == Token::Ident("void") && self.peek_at(1) == Token::Punct(")") {
+                self.pos += 2;
+            } else {
+                loop {
+                    let attributes = self.attributes()?;
+                    let modifiers = self.modifiers()?;
+                    let ty = self.ty()?;
+                    let name = self.ident()?;
+                    params.push(self.var_decl(attributes, modifiers, ty, name)?);
+                    if !self.skip(",") {
+                        break;
+                    }
+                }
+                self.expect(")")?;
+            }
+        }
+        let (semantic, _) = self.annotations()?;
+        if self.peek() == Token::Punct(";") {
+            return Err(Error {
+                kind: ErrorKind::NotImplemented("function declarations without a body"),
+                span: self.span_from(name.span),
+            });
+        }
+        let body = self.block()?;
+        Ok(Function {
+            attributes,
+            result,
+            name,
+            params,
+            semantic,
+            body,
+        })
+    }
+
+    fn block(&mut self) -> Result<Block<'a>> {
+        self.expect("{")?;
+        let mut statements = Vec::new();
+        while !self.skip("}") {
+            statements.push(self.statement()?);
+        }
+        Ok(statements)
+    }
+
+    fn statement(&mut self) -> Result<Statement<'a>> {
+        // Attributes like `[unroll]` or `[branch]` are hints we don't need.
+        self.attributes()?;
+        let start = self.span();
+        let kind = match self.peek() {
+            Token::Punct("{") => StatementKind::Block(self.block()?),
+            Token::Punct(";") => {
+                self.next();
+                StatementKind::Empty
+            }
+            Token::Ident("if") => {
+                self.next();
+                self.expect("(")?;
+                let condition = self.expr()?;
+                self.expect(")")?;
+                let accept = Box::new(self.statement()?);
+                let reject = match self.skip_ident("else") {
+                    true => Some(Box::new(self.statement()?)),
+                    false => None,
+                };
+                StatementKind::If {
+                    condition,
+                    accept,
+                    reject,
+                }
+            }
+            Token::Ident("for") => {
+                self.next();
+                self.expect("(")?;
+                let init = match self.peek() {
+                    Token::Punct(";") => {
+                        self.next();
+                        None
+                    }
+                    _ => Some(Box::new(self.simple_statement()?)),
+                };
+                let condition = match self.peek() {
+                    Token::Punct(";") => None,
+                    _ => Some(self.expr()?),
+                };
+                self.expect(";")?;
+                let update = match self.peek() {
+                    Token::Punct(")") => None,
+                    _ => Some(self.expr()?),
+                };
+                self.expect(")")?;
+                let body = Box::new(self.statement()?);
+                StatementKind::For {
+                    init,
+                    condition,
+                    update,
+                    body,
+                }
+            }
+            Token::Ident("while") => {
+                self.next();
+                self.expect("(")?;
+                let condition = self.expr()?;
+                self.expect(")")?;
+                let body = Box::new(self.statement()?);
+                StatementKind::While { condition, body }
+            }
+            Token::Ident("do") => {
+                self.next();
+                let body = Box::new(self.statement()?);
+                if !self.skip_ident("while") {
+                    return self.error("`while`");
+                }
+                self.expect("(")?;
+                let condition = self.expr()?;
+                self.expect(")")?;
+                self.expect(";")?;
+                StatementKind::DoWhile { body, condition }
+            }
+            Token::Ident("switch") => {
+                self.next();
+                self.expect("(")?;
+                let selector = self.expr()?;
+                self.expect(")")?;
+                self.expect("{")?;
+                let mut cases = Vec::new();
+                while !self.skip("}") {
+                    let value = if self.skip_ident("case") {
+                        Some(self.expr()?)
+                    } else if self.skip_ident("default") {
+                        None
+                    } else {
+                        return self.error("`case` or `default`");
+                    };
+                    self.expect(":")?;
+                    let mut body = Vec::new();
+                    while !matches!(
+                        self.peek(),
+                        Token::Ident("case" | "default") | Token::Punct("}")
+                    ) {
+                        body.push(self.statement()?);
+                    }
+                    cases.push(SwitchCase { value, body });
+                }
+                StatementKind::Switch { selector, cases }
+            }
+            Token::Ident("break") => {
+                self.next();
+                self.expect(";")?;
+                StatementKind::Break
+            }
+            Token::Ident("continue") => {
+                self.next();
+                self.expect(";")?;
+                StatementKind::Continue
+            }
+            Token::Ident("discard") => {
+                self.next();
+                self.expect(";")?;
+                StatementKind::Discard
+            }
+            Token::Ident("return") => {
+                self.next();
+                let value = match self.peek() {
+                    Token::Punct(";") => None,
+                    _ => Some(self.expr()?),
+                };
+                self.expect(";")?;
+                StatementKind::Return(value)
+            }
+            _ => return self.simple_statement(),
+        };
+        Ok(Statement {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses a declaration or expression statement, with its `;`.
+    fn simple_statement(&mut self) -> Result<Statement<'a>> {
+        let start = self.span();
+        let kind = if self.at_declaration() {
+            let modifiers = self.modifiers()?;
+            let ty = self.ty()?;
+            let name = self.ident()?;
+            StatementKind::Local(self.var_decls(Vec::new(), modifiers, ty, name)?)
+        } else {
+            StatementKind::Expr(self.expr()?)
+        };
+        self.expect(";")?;
+        Ok(Statement {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Parses an expression, including assignments.
+    pub fn expr(&mut self) -> Result<Expr<'a>> {
+        self.assignment_expr()
+    }
+
+    fn assignment_expr(&mut self) -> Result<Expr<'a>> {
+        let target = self.ternary_expr()?;
+        let op = match self.peek() {
+            Token::Punct("=") => None,
+            Token::Punct("+=") => Some(BinaryOp::Add),
+            Token::Punct("-=") => Some(BinaryOp::Subtract),
+            Token::Punct("*=") => Some(BinaryOp::Multiply),
+            Token::Punct("/=") => Some(BinaryOp::Divide),
+            Token::Punct("%=") => Some(BinaryOp::Modulo),
+            Token::Punct("&=") => Some(BinaryOp::And),
+            Token::Punct("|=") => Some(BinaryOp::InclusiveOr),
+            Token::Punct("^=") => Some(BinaryOp::ExclusiveOr),
+            Token::Punct("<<=") => Some(BinaryOp::ShiftLeft),
+            Token::Punct(">>=") => Some(BinaryOp::ShiftRight),
+            _ => return Ok(target),
+        };
+        self.next();
+        let value = self.assignment_expr()?;
+        Ok(Expr {
+            span: target.span.until(&value.span),
+            kind: ExprKind::Assign {
+                op,
+                target: Box::new(target),
+                value: Box::new(value),
+            },
+        })
+    }
+
+    fn ternary_expr(&mut self) -> Result<Expr<'a>> {
+        let condition = self.binary_expr(0)?;
+        if !self.skip("?") {
+            return Ok(condition);
+        }
+        let accept = self.assignment_expr()?;
+        self.expect(":")?;
+        let reject = self.assignment_expr()?;
+        Ok(Expr {
+            span: condition.span.until(&reject.span),
+            kind: ExprKind::Ternary {
+                condition: Box::new(condition),
+                accept: Box::new(accept),
+                reject: Box::new(reject),
+            },
+        })
+    }
+
+    /// Parses binary operators binding at least as tightly as `precedence`.
+    fn binary_expr(&mut self, precedence: u8) -> Result<Expr<'a>> {
+        let mut left = self.unary_expr()?;
+        loop {
+            let (op, op_precedence) = match self.peek() {
+                Token::Punct("||") => (BinaryOp::LogicalOr, 0),
+                Token::Punct("&&") => (BinaryOp::LogicalAnd, 1),
+                Token::Punct("|") => (BinaryOp::InclusiveOr, 2),
+                Token::Punct("^") => (BinaryOp::ExclusiveOr, 3),
+                Token::Punct("&") => (BinaryOp::And, 4),
+                Token::Punct("==") => (BinaryOp::Equal, 5),
+                Token::Punct("!=") => (BinaryOp::NotEqual, 5),
+                Token::Punct("<") => (BinaryOp::Less, 6),
+                Token::Punct("<=") => (BinaryOp::LessEqual, 6),
+                Token::Punct(">") => (BinaryOp::Greater, 6),
+                Token::Punct(">=") => (BinaryOp::GreaterEqual, 6),
+                Token::Punct("<<") => (BinaryOp::ShiftLeft, 7),
+                Token::Punct(">>") => (BinaryOp::ShiftRight, 7),
+                Token::Punct("+") => (BinaryOp::Add, 8),
+                Token::Punct("-") => (BinaryOp::Subtract, 8),
+                Token::Punct("*") => (BinaryOp::Multiply, 9),
+                Token::Punct("/") => (BinaryOp::Divide, 9),
+                Token::Punct("%") => (BinaryOp::Modulo, 9),
+                _ => return Ok(left),
+            };
+            if op_precedence < precedence {
+                return Ok(left);
+            }
+            self.next();
+            let right = self.binary_expr(op_precedence + 1)?;
+            left = Expr {
+                span: left.span.until(&right.span),
+                kind: ExprKind::Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                },
+            };
+        }
+    }
+
+    fn unary_expr(&mut self) -> Result<Expr<'a>> {
+        let start = self.span();
+        let op = match self.peek() {
+            Token::Punct("-") => UnaryOp::Negate,
+            Token::Punct("+") => UnaryOp::Plus,
+            Token::Punct("!") => UnaryOp::LogicalNot,
+            Token::Punct("~") => UnaryOp::BitwiseNot,
+            Token::Punct(punct @ ("++" | "--")) => {
+                self.next();
+                let target = self.unary_expr()?;
+                return Ok(Expr {
+                    span: start.until(&target.span),
+                    kind: ExprKind::IncDec {
+                        increment: punct == "++",
+                        prefix: true,
+                        target: Box::new(target),
+                    },
+                });
+            }
+            Token::Punct("(") if self.is_cast() => {
+                self.next();
+                let ty = self.ty()?;
+                self.expect(")")?;
+                let expr = self.unary_expr()?;
+                return Ok(Expr {
+                    span: start.until(&expr.span),
+                    kind: ExprKind::Cast {
+                        ty,
+                        expr: Box::new(expr),
+                    },
+                });
+            }
+            _ => return self.postfix_expr(),
+        };
+        self.next();
+        let expr = self.unary_expr()?;
+        Ok(Expr {
+            span: start.until(&expr.span),
+            kind: ExprKind::Unary {
+                op,
+                expr: Box::new(expr),
+            },
+        })
+    }
+
+    /// Returns whether the `(` at the current position starts a cast.
+    fn is_cast(&mut self) -> bool {
+        let start = self.pos;
+        self.pos += 1;
+        let is_cast = self.at_type() && self.ty().is_ok() && self.peek() == Token::Punct(")");
+        self.pos = start;
+        is_cast
+    }
+
+    fn postfix_expr(&mut self) -> Result<Expr<'a>> {
+        let mut expr = self.primary_expr()?;
+        loop {
+            match self.peek() {
+                Token::Punct(".") => {
+                    self.next();
+                    let member = self.ident()?;
+                    if self.skip("(") {
+                        let args = self.args()?;
+                        expr = Expr {
+                            span: self.span_from(expr.span),
+                            kind: ExprKind::MethodCall {
+                                base: Box::new(expr),
+                                method: member,
+                                args,
+                            },
+                        };
+                    } else {
+                        expr = Expr {
+                            span: expr.span.until(&member.span),
+                            kind: ExprKind::Member {
+                                base: Box::new(expr),
+                                member,
+                            },
+                        };
+                    }
+                }
+                Token::Punct("[") => {
+                    self.next();
+                    let index = self.expr()?;
+                    self.expect("]")?;
+                    expr = Expr {
+                        span: self.span_from(expr.span),
+                        kind: ExprKind::Index {
+                            base: Box::new(expr),
+                            index: Box::new(index),
+                        },
+                    };
+                }
+                Token::Punct(punct @ ("++" | "--")) => {
+                    self.next();
+                    expr = Expr {
+                        span: self.span_from(expr.span),
+                        kind: ExprKind::IncDec {
+                            increment: punct == "++",
+                            prefix: false,
+                            target: Box::new(expr),
+                        },
+                    };
+                }
+                _ => return Ok(expr),
+            }
+        }
+    }
+
+    /// Parses call arguments, after the `(`.
+    fn args(&mut self) -> Result<Vec<Expr<'a>>> {
+        let mut args = Vec::new();
+        if self.skip(")") {
+            return Ok(args);
+        }
+        loop {
+            args.push(self.assignment_expr()?);
+            if !self.skip(",") {
+                break;
+            }
+        }
+        self.expect(")")?;
+        Ok(args)
+    }
+
+    fn primary_expr(&mut self) -> Result<Expr<'a>> {
+        let start = self.span();
+        let kind = match self.peek() {
+            Token::Number(number) => {
+                self.next();
+                ExprKind::Literal(Literal::Number(number))
+            }
+            Token::Ident("true") => {
+                self.next();
+                ExprKind::Literal(Literal::Bool(true))
+            }
+            Token::Ident("false") => {
+                self.next();
+                ExprKind::Literal(Literal::Bool(false))
+            }
+            Token::Punct("(") => {
+                self.next();
+                let expr = self.expr()?;
+                self.expect(")")?;
+                return Ok(Expr {
+                    kind: expr.kind,
+                    span: self.span_from(start),
+                });
+            }
+            Token::Punct("{") => {
+                self.next();
+                let mut items = Vec::new();
+                while !self.skip("}") {
+                    items.push(self.assignment_expr()?);
+                    if !self.skip(",") {
+                        self.expect("}")?;
+                        break;
+                    }
+                }
+                ExprKind::InitList(items)
+            }
+            Token::Ident(_) if self.at_type() => {
+                let ty = self.ty()?;
+                self.expect("(")?;
+                let args = self.args()?;
+                ExprKind::Construct { ty, args }
+            }
+            Token::Ident(name) => {
+                let function = self.ident()?;
+                if self.skip("(") {
+                    ExprKind::Call {
+                        function,
+                        args: self.args()?,
+                    }
+                } else {
+                    ExprKind::Ident(name)
+                }
+            }
+            _ => return self.error("expression"),
+        };
+        Ok(Expr {
+            kind,
+            span: self.span_from(start),
+        })
+    }
+}
@@ -0,0 +1,556 @@
+use super::{Error, ErrorKind, Frontend, Options, RegisterShifts};
+use crate::{
+    valid::{Capabilities, ValidationFlags, Validator},
+    AddressSpace, Binding, BuiltIn, ImageClass, Module, ResourceBinding, ShaderStage, Span,
+    StorageAccess, TypeInner,
+};
+
+fn parse(options: &Options, source: &str) -> Result<Module, Error> {
+    Frontend::new().parse(options, source)
+}
+
+/// Parses and validates `source`, with `main` as an entry point of `stage`.
+fn parse_valid(stage: ShaderStage, source: &str) -> Module {
+    let options = Options::with_entry_point("main", stage);
+    let module = parse(&options, source).unwrap_or_else(|error| {
+        panic!("{}", error.emit_to_string(source));
+    });
+    Validator::new(ValidationFlags::all(), Capabilities::default())
+        .validate(&module)
+        .unwrap_or_else(|error| panic!("{error:?}\n{module:#?}"));
+    module
+}
+
+fn parse_err(source: &str) -> ErrorKind {
+    let options = Options::with_entry_point("main", ShaderStage::Fragment);
+    parse(&options, source).unwrap_err().kind
+}
+
+fn binding(module: &Module, name: &str) -> Option<ResourceBinding> {
+    module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some(name))
+        .and_then(|(_, var)| var.binding.clone())
+}
+
+#[test]
+fn vertex_shader() {
+    let module = parse_valid(
+        ShaderStage::Vertex,
+        "
+        cbuffer Camera : register(b0) {
+            float4x4 view_projection;
+            float time;
+        };
+
+        struct VSInput {
+            float3 position : POSITION;
+            float2 uv : TEXCOORD0;
+        };
+
+        struct VSOutput {
+            float4 position : SV_Position;
+            float2 uv : TEXCOORD0;
+        };
+
+        VSOutput main(VSInput input, uint id : SV_VertexID) {
+            VSOutput output;
+            output.position = mul(view_projection, float4(input.position, 1.0));
+            output.uv = input.uv + time * id;
+            return output;
+        }
+        ",
+    );
+
+    let ep = &module.entry_points[0];
+    assert_eq!(ep.stage, ShaderStage::Vertex);
+    assert_eq!(
+        ep.function.arguments[1].binding,
+        Some(Binding::BuiltIn(BuiltIn::VertexIndex))
+    );
+    let result = ep.function.result.as_ref().unwrap();
+    let TypeInner::Struct { ref members, .. } = module.types[result.ty].inner else {
+        panic!("expected a struct");
+    };
+    assert_eq!(
+        members[0].binding,
+        Some(Binding::BuiltIn(BuiltIn::Position { invariant: false }))
+    );
+    assert!(matches!(
+        members[1].binding,
+        Some(Binding::Location { location: 0, .. })
+    ));
+    assert_eq!(
+        binding(&module, "Camera"),
+        Some(ResourceBinding {
+            group: 0,
+            binding: 0
+        })
+    );
+}
+
+#[test]
+fn pixel_shader() {
+    let module = parse_valid(
+        ShaderStage::Fragment,
+        "
+        Texture2D<float4> albedo : register(t1, space2);
+        SamplerState linear_sampler : register(s3, space2);
+        Texture2D<float> shadow_map : register(t2, space2);
+        SamplerComparisonState shadow_sampler : register(s4, space2);
+
+        float4 main(float4 position : SV_Position, float2 uv : TEXCOORD0) : SV_Target {
+            float4 color = albedo.Sample(linear_sampler, uv);
+            float shadow = shadow_map.SampleCmpLevelZero(shadow_sampler, uv, position.z);
+            clip(color.a - 0.5);
+            return float4(color.rgb * shadow, 1);
+        }
+        ",
+    );
+
+    assert_eq!(
+        binding(&module, "albedo"),
+        Some(ResourceBinding {
+            group: 2,
+            binding: 1
+        })
+    );
+    let (_, shadow_map) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("shadow_map"))
+        .unwrap();
+    assert!(matches!(
+        module.types[shadow_map.ty].inner,
+        TypeInner::Image {
+            class: ImageClass::Depth { multi: false },
+            ..
+        }
+    ));
+    let result = module.entry_points[0].function.result.as_ref().unwrap();
+    assert!(matches!(
+        result.binding,
+        Some(Binding::Location { location: 0, .. })
+    ));
+}
+
+#[test]
+fn compute_shader() {
+    let module = parse_valid(
+        ShaderStage::Compute,
+        "
+        struct Particle {
+            float3 position;
+            float3 velocity;
+        };
+
+        StructuredBuffer<float> masses : register(t0);
+        RWStructuredBuffer<Particle> particles : register(u1);
+        RWTexture2D<float4> image : register(u2);
+        groupshared float shared_mass[64];
+
+        [numthreads(64, 1, 1)]
+        void main(uint3 id : SV_DispatchThreadID, uint index : SV_GroupIndex) {
+            uint count, stride;
+            particles.GetDimensions(count, stride);
+            if (id.x >= count) {
+                return;
+            }
+            shared_mass[index] = masses[id.x];
+            GroupMemoryBarrierWithGroupSync();
+            Particle p = particles[id.x];
+            p.position += p.velocity * shared_mass[index];
+            particles[id.x] = p;
+            image[id.xy] = float4(p.position, 1);
+        }
+        ",
+    );
+
+    let ep = &module.entry_points[0];
+    assert_eq!(ep.stage, ShaderStage::Compute);
+    assert_eq!(ep.workgroup_size, [64, 1, 1]);
+    let (_, particles) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("particles"))
+        .unwrap();
+    assert_eq!(
+        particles.space,
+        AddressSpace::Storage {
+            access: StorageAccess::LOAD | StorageAccess::STORE
+        }
+    );
+    let (_, image) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("image"))
+        .unwrap();
+    assert!(matches!(
+        module.types[image.ty].inner,
+        TypeInner::Image {
+            class: ImageClass::Storage {
+                format: crate::StorageFormat::Rgba32Float,
+                access: StorageAccess::STORE
+            },
+            ..
+        }
+    ));
+}
+
+#[test]
+fn shader_attribute() {
+    let source = "
+        [shader(\"vertex\")]
+        float4 vs_main(float4 position : POSITION) : SV_Position { return position; }
+
+        [shader(\"pixel\")]
+        float4 ps_main() : SV_Target0 { return 1; }
+    ";
+    let module = parse(&Options::default(), source).unwrap();
+    let stages = module
+        .entry_points
+        .iter()
+        .map(|ep| (ep.name.as_str(), ep.stage))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        stages,
+        [
+            ("vs_main", ShaderStage::Vertex),
+            ("ps_main", ShaderStage::Fragment)
+        ]
+    );
+}
+
+#[test]
+fn bindings() {
+    let source = "
+        Texture2D a;
+        Texture2D b : register(t0);
+        SamplerState c : register(s0);
+        [[vk::binding(5, 1)]] Texture2D d;
+        cbuffer Params : register(b0) { float4 e; };
+
+        float4 main(float4 position : SV_Position) : SV_Target {
+            return a.Sample(c, position.xy) + b.Sample(c, position.xy)
+                + d.Sample(c, position.xy) + e;
+        }
+    ";
+    let options = Options {
+        register_shifts: RegisterShifts {
+            constant_buffer: 10,
+            texture: 20,
+            sampler: 30,
+            unordered_access: 40,
+        },
+        ..Options::with_entry_point("main", ShaderStage::Fragment)
+    };
+    let module = parse(&options, source).unwrap();
+    let get = |name| {
+        let binding = binding(&module, name).unwrap();
+        (binding.group, binding.binding)
+    };
+    assert_eq!(get("a"), (0, 0));
+    assert_eq!(get("b"), (0, 20));
+    assert_eq!(get("c"), (0, 30));
+    assert_eq!(get("d"), (1, 5));
+    assert_eq!(get("Params"), (0, 10));
+
+    assert_eq!(
+        parse_err(
+            "
+            Texture2D a : register(t0);
+            Texture2D b : register(t0);
+            "
+        ),
+        ErrorKind::BindingCollision("b".to_string(), 0, 0)
+    );
+    assert!(matches!(
+        parse_err("Texture2D a : register(s0);"),
+        ErrorKind::InvalidRegister(_)
+    ));
+}
+
+#[test]
+fn statements() {
+    parse_valid(
+        ShaderStage::Fragment,
+        "
+        static const float weights[3] = { 0.25, 0.5, 0.25 };
+
+        int classify(int value, out bool negative) {
+            negative = value < 0;
+            switch (value) {
+                case 0:
+                    return 0;
+                case 1:
+                case 2:
+                    value *= 2;
+                    break;
+                default:
+                    value = -1;
+                    break;
+            }
+            return value;
+        }
+
+        float4 main(float4 position : SV_Position) : SV_Target {
+            float sum = 0;
+            for (int i = 0; i < 3; i++) {
+                if (i == 1) {
+                    continue;
+                }
+                sum += weights[i];
+            }
+            int j = 0;
+            while (j < 4) {
+                j++;
+            }
+            do {
+                j--;
+            } while (j > 0);
+            bool negative;
+            int kind = classify(int(position.x), negative);
+            float3 v = position.xyz;
+            v.xy = v.yx;
+            v.z = negative ? 1.0 : 0.0;
+            return float4(v * sum, kind);
+        }
+        ",
+    );
+}
+
+#[test]
+fn matrices() {
+    let module = parse_valid(
+        ShaderStage::Vertex,
+        "
+        cbuffer Transforms {
+            float4x3 column_major_matrix;
+            row_major float4x3 row_major_matrix;
+        };
+
+        float4 main(float3 position : POSITION) : SV_Position {
+            float3x3 m = float3x3(1, 0, 0, 0, 1, 0, 0, 0, 1);
+            float3 p = mul(m, position);
+            float4 q = mul(column_major_matrix, p) + mul(row_major_matrix, p);
+            return float4(q.xyz, column_major_matrix._m00 + row_major_matrix[1][2]);
+        }
+        ",
+    );
+
+    let (_, transforms) = module.global_variables.iter().next().unwrap();
+    let TypeInner::Struct { ref members, .. } = module.types[transforms.ty].inner else {
+        panic!("expected a struct");
+    };
+    // A column-major `float4x3` is stored as three columns of four rows.
+    assert!(matches!(
+        module.types[members[0].ty].inner,
+        TypeInner::Matrix {
+            columns: crate::VectorSize::Tri,
+            rows: crate::VectorSize::Quad,
+            ..
+        }
+    ));
+    assert!(matches!(
+        module.types[members[1].ty].inner,
+        TypeInner::Matrix {
+            columns: crate::VectorSize::Quad,
+            rows: crate::VectorSize::Tri,
+            ..
+        }
+    ));
+}
+
+/// Returns the offsets of the members of the `cbuffer` in `source`.
+fn cbuffer_offsets(source: &str) -> Vec<u32> {
+    let module = parse_valid(ShaderStage::Fragment, source);
+    let (_, cbuffer) = module.global_variables.iter().next().unwrap();
+    let TypeInner::Struct { ref members, .. } = module.types[cbuffer.ty].inner else {
+        panic!("expected a struct");
+    };
+    members.iter().map(|member| member.offset).collect()
+}
+
+#[test]
+fn cbuffer_packing() {
+    // Scalars and vectors share a register while they fit.
+    assert_eq!(
+        cbuffer_offsets(
+            "
+            cbuffer C { float3 a; float b; float2 c; float2 d; float3 e; float f; };
+            float4 main() : SV_Target { return a.x + b + c.x + d.x + e.x + f; }
+            "
+        ),
+        [0, 12, 16, 24, 32, 44]
+    );
+    // Arrays, structs and matrices start a new register, and every array
+    // element is in a register of its own.
+    assert_eq!(
+        cbuffer_offsets(
+            "
+            struct S { float4 v; float3 w; float x; };
+            cbuffer C { float a; float4 b[2]; S s; float4x4 m; float c; };
+            float4 main() : SV_Target { return a + b[1] + s.x + m[0] + c; }
+            "
+        ),
+        [0, 16, 48, 80, 144]
+    );
+
+    // HLSL packs `b` at offset 4 and `d` at offset 20, which are not
+    // aligned for naga's vectors, so their components are stored instead.
+    assert_eq!(
+        cbuffer_offsets(
+            "
+            cbuffer C { float a; float3 b; float c; float2 d; };
+            float4 main() : SV_Target { return float4(b, c) + d.y; }
+            "
+        ),
+        [0, 4, 8, 12, 16, 20, 24]
+    );
+    // HLSL packs `c` into the last register of `arr`, but naga's arrays use
+    // all of their last element's register.
+    assert_eq!(
+        parse_err("cbuffer C { float arr[2]; float c; }; float4 main() : SV_Target { return c; }"),
+        ErrorKind::SemanticError("the HLSL packing of `c` can not be represented".into())
+    );
+    // HLSL doesn't pad `x` to 16 bytes in the struct.
+    assert_eq!(
+        parse_err(
+            "
+            struct S { float x; float3 y; };
+            cbuffer C { S s; };
+            float4 main() : SV_Target { return s.x; }
+            "
+        ),
+        ErrorKind::SemanticError("the HLSL packing of `s` can not be represented".into())
+    );
+}
+
+#[test]
+fn out_parameters() {
+    let module = parse_valid(
+        ShaderStage::Vertex,
+        "
+        void main(float4 position : POSITION, out float4 clip : SV_Position, out float2 uv : TEXCOORD) {
+            clip = position;
+            uv = position.xy;
+        }
+        ",
+    );
+    let result = module.entry_points[0].function.result.as_ref().unwrap();
+    let TypeInner::Struct { ref members, .. } = module.types[result.ty].inner else {
+        panic!("expected a struct");
+    };
+    assert_eq!(members.len(), 2);
+}
+
+#[test]
+fn intrinsics() {
+    parse_valid(
+        ShaderStage::Fragment,
+        "
+        Texture2DArray<float4> layers : register(t0);
+        SamplerState s : register(s1);
+
+        float4 main(float4 position : SV_Position) : SV_Target {
+            float2 uv = position.xy;
+            float4 color = layers.SampleLevel(s, float3(uv, 1), 0, int2(1, 1));
+            color += layers.Load(int4(1, 2, 0, 0));
+            color += layers.GatherRed(s, float3(uv, 0));
+            uint width, height, elements, levels;
+            layers.GetDimensions(0, width, height, elements, levels);
+            float sine, cosine;
+            sincos(position.x, sine, cosine);
+            float whole;
+            float fraction = modf(position.y, whole);
+            float3 n = normalize(cross(float3(1, 0, 0), position.xyz));
+            color.rgb *= saturate(dot(n, float3(0, 1, 0)));
+            color.a = lerp(sine, cosine, frac(fraction)) + rcp(width) + fmod(whole, 2);
+            color.r += ddx(position.x) + fwidth(position.y) + log10(height);
+            color.g = max(abs(color.g), 0.5) * sign(position.x) + asfloat(asuint(1.0f));
+            return all(color > 0) ? color : mad(color, 2, 1);
+        }
+        ",
+    );
+}
+
+#[test]
+fn interlocked() {
+    let module = parse_valid(
+        ShaderStage::Compute,
+        "
+        RWStructuredBuffer<uint> counter : register(u0);
+        RWStructuredBuffer<int> values : register(u1);
+        groupshared uint total;
+
+        [numthreads(64, 1, 1)]
+        void main(uint3 id : SV_DispatchThreadID) {
+            uint previous;
+            InterlockedAdd(counter[0], 1, previous);
+            InterlockedMax(values[previous], id.x);
+            InterlockedOr(total, 1u << id.x);
+            uint original;
+            InterlockedCompareExchange(counter[1], 0, previous, original);
+            InterlockedCompareStore(counter[2], original, 1);
+            InterlockedExchange(total, 0, original);
+            values[id.x] = counter[1] + total;
+        }
+        ",
+    );
+
+    let (_, counter) = module
+        .global_variables
+        .iter()
+        .find(|&(_, var)| var.name.as_deref() == Some("counter"))
+        .unwrap();
+    let TypeInner::Array { base, .. } = module.types[counter.ty].inner else {
+        panic!("{:?}", module.types[counter.ty]);
+    };
+    assert!(matches!(module.types[base].inner, TypeInner::Atomic(_)));
+
+    let source = "
+        RWStructuredBuffer<float> values : register(u0);
+        float4 main() : SV_Target { InterlockedAdd(values[0], 1); return 0; }
+    ";
+    assert!(matches!(parse_err(source), ErrorKind::SemanticError(_)));
+    let source = "
+        RWStructuredBuffer<uint> values : register(u0);
+        float4 main() : SV_Target { InterlockedExchange(values[0], 1); return 0; }
+    ";
+    assert!(matches!(
+        parse_err(source),
+        ErrorKind::WrongArgumentCount { expected: 3, .. }
+    ));
+}
+
+#[test]
+fn errors() {
+    let source = "float4 main() : SV_Target { return undefined; }";
+    let options = Options::with_entry_point("main", ShaderStage::Fragment);
+    let error = parse(&options, source).unwrap_err();
+    assert_eq!(
+        error,
+        Error {
+            kind: ErrorKind::UnknownIdentifier("undefined".to_string()),
+            span: Span::new(35, 44),
+        }
+    );
+
+    assert_eq!(
+        parse_err("#include \"common.hlsl\"\n"),
+        ErrorKind::NotImplemented("preprocessor directives other than `#pragma`")
+    );
+    assert!(matches!(
+        parse_err("float4 main() : SV_Target { float3x3 m = 1; return m; }"),
+        ErrorKind::TypeMismatch { .. }
+    ));
+    assert_eq!(
+        parse_err("float4 main() : SV_Bogus { return 0; }"),
+        ErrorKind::UnknownSemantic("SV_Bogus".to_string())
+    );
+    assert!(matches!(
+        parse_err("float4 main() : SV_Target { return 0 }"),
+        ErrorKind::UnexpectedToken { .. }
+    ));
+}
@@ -9,6 +9,8 @@ mod type_gen;
 pub mod atomic_upgrade;
 #[cfg(feature = "glsl-in")]
 pub mod glsl;
+#[cfg(feature = "hlsl-in")]
+pub mod hlsl;
 #[cfg(feature = "spv-in")]
 pub mod spv;
 #[cfg(feature = "wgsl-in")]
@@ -0,0 +1,301 @@
+/*!
+Recovering source code and source locations from debug information.
+
+Compilers like glslang and DXC can embed a shader's source code in the
+SPIR-V module they produce, either with `OpSource` or with the
+`DebugSource` instruction of the `NonSemantic.Shader.DebugInfo.100`
+instruction set. Instructions are then attributed to lines of that source
+with `OpLine` or `DebugLine`.
+
+If the module embeds source code, the spans in the [`Module`] we produce
+refer to that source code, rather than to the SPIR-V binary. Only one
+source file is recovered: the first one whose text the module includes.
+Instructions attributed to other files, like included headers, get
+undefined spans.
+
+[`Module`]: crate::Module
+*/
+
+use super::{Error, Instruction, LookupHelper as _};
+use crate::{FastHashMap, Span};
+
+/// The `DebugSource` instruction of `NonSemantic.Shader.DebugInfo.100`.
+const DEBUG_SOURCE: u32 = 35;
+/// The `DebugSourceContinued` instruction of `NonSemantic.Shader.DebugInfo.100`.
+const DEBUG_SOURCE_CONTINUED: u32 = 102;
+/// The `DebugLine` instruction of `NonSemantic.Shader.DebugInfo.100`.
+const DEBUG_LINE: u32 = 103;
+/// The `DebugNoLine` instruction of `NonSemantic.Shader.DebugInfo.100`.
+const DEBUG_NO_LINE: u32 = 104;
+
+/// Source code embedded in a SPIR-V module's debug information.
+///
+/// The spans in a module parsed with [`Frontend::parse_with_source`] are
+/// byte ranges in [`source_code`].
+///
+/// [`Frontend::parse_with_source`]: super::Frontend::parse_with_source
+/// [`source_code`]: DebugSource::source_code
+#[derive(Clone, Debug)]
+pub struct DebugSource {
+    /// The name of the source file, if the module gives one.
+    pub file_name: Option<String>,
+    /// The language of the source code, as given by `OpSource`.
+    pub language: spirv::SourceLanguage,
+    /// The text of the source file.
+    pub source_code: String,
+}
+
+/// Debug information gathered while parsing a module.
+#[derive(Default)]
+pub(super) struct SourceMap {
+    /// `OpString` literals, by result id.
+    strings: FastHashMap<spirv::Word, String>,
+
+    /// The file names of `DebugSource` instructions, by result id.
+    debug_source_files: FastHashMap<spirv::Word, String>,
+
+    /// The language given by the first `OpSource` instruction.
+    language: Option<spirv::SourceLanguage>,
+
+    /// The source file that spans refer to.
+    source: Option<DebugSource>,
+
+    /// Whether `OpSourceContinued` and `DebugSourceContinued` instructions
+    /// currently extend `source`.
+    continuing: bool,
+
+    /// The byte offset of the start of each line of `source`.
+    ///
+    /// This is computed on demand, and cleared when `source` grows.
+    line_starts: Vec<usize>,
+
+    /// Whether spans refer to `source` rather than to the SPIR-V binary.
+    ///
+    /// This is set as soon as we know the module has debug information,
+    /// even if we haven't seen the source code yet, so that spans are
+    /// consistent throughout the module.
+    active: bool,
+
+    /// The span of the source attributed to the current instruction.
+    line: Span,
+}
+
+impl SourceMap {
+    pub(super) const fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub(super) const fn current_line(&self) -> Span {
+        self.line
+    }
+
+    pub(super) fn clear_line(&mut self) {
+        self.line = Span::UNDEFINED;
+    }
+
+    pub(super) fn activate(&mut self) {
+        self.active = true;
+    }
+
+    pub(super) fn take_source(&mut self) -> Option<DebugSource> {
+        self.source.take()
+    }
+
+    /// Record a source file, whose text may follow in continuations.
+    fn add_source(&mut self, file_name: Option<String>, text: Option<String>) {
+        self.continuing = false;
+        let Some(source_code) = text else {
+            return;
+        };
+        self.active = true;
+        if self.source.is_none() {
+            self.source = Some(DebugSource {
+                file_name,
+                language: self.language.unwrap_or(spirv::SourceLanguage::Unknown),
+                source_code,
+            });
+            self.continuing = true;
+        }
+    }
+
+    fn continue_source(&mut self, text: &str) {
+        if let Some(ref mut source) = self.source {
+            if self.continuing {
+                source.source_code.push_str(text);
+                self.line_starts.clear();
+            }
+        }
+    }
+
+    /// Return the byte offset of `column` in `line`, both one-based.
+    ///
+    /// Columns past the end of the line are clamped to its end.
+    fn offset(&mut self, line: u32, column: u32) -> Option<usize> {
+        let text = &self.source.as_ref()?.source_code;
+        if self.line_starts.is_empty() {
+            self.line_starts.push(0);
+            self.line_starts
+                .extend(text.match_indices('\n').map(|(i, _)| i + 1));
+        }
+        let start = *self.line_starts.get(line.checked_sub(1)? as usize)?;
+        let end = text[start..].find('\n').map_or(text.len(), |i| start + i);
+        let end = start + text[start..end].trim_end_matches('\r').len();
+        let mut offset = (start + column.saturating_sub(1) as usize).min(end);
+        while !text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        Some(offset)
+    }
+
+    /// Attribute the following instructions to `file_name`, starting at
+    /// `line` and `column`.
+    ///
+    /// If `end` is `None`, the span extends to the end of the line.
+    fn set_line(
+        &mut self,
+        file_name: Option<&str>,
+        (line, column): (u32, u32),
+        end: Option<(u32, u32)>,
+    ) {
+        self.line = Span::UNDEFINED;
+        let Some(ref source) = self.source else {
+            return;
+        };
+        if source.file_name.is_some() && source.file_name.as_deref() != file_name {
+            return;
+        }
+        let Some(start) = self.offset(line, column) else {
+            return;
+        };
+        let end = end
+            .and_then(|(line, column)| self.offset(line, column))
+            .filter(|&end| end > start)
+            .or_else(|| self.offset(line, u32::MAX))
+            .unwrap_or(start);
+        self.line = Span::from(start..end);
+    }
+}
+
+impl<I: Iterator<Item = u32>> super::Frontend<I> {
+    pub(super) fn parse_string(&mut self, inst: Instruction) -> Result<(), Error> {
+        self.switch(super::ModuleState::Source, inst.op)?;
+        inst.expect_at_least(3)?;
+        let id = self.next()?;
+        let (string, _) = self.next_string(inst.wc - 2)?;
+        self.source_map.strings.insert(id, string);
+        Ok(())
+    }
+
+    pub(super) fn parse_source(&mut self, inst: Instruction) -> Result<(), Error> {
+        self.switch(super::ModuleState::Source, inst.op)?;
+        inst.expect_at_least(3)?;
+        let language = self.next()?;
+        let _version = self.next()?;
+        let file_name = if inst.wc > 3 {
+            let file_id = self.next()?;
+            Some(self.source_map.strings.lookup(file_id)?.clone())
+        } else {
+            None
+        };
+        let text = if inst.wc > 4 {
+            let (text, _) = self.next_string(inst.wc - 4)?;
+            Some(text)
+        } else {
+            None
+        };
+
+        let map = &mut self.source_map;
+        if map.language.is_none() {
+            map.language = spirv::SourceLanguage::from_u32(language);
+        }
+        map.add_source(file_name, text);
+        Ok(())
+    }
+
+    pub(super) fn parse_source_continued(&mut self, inst: Instruction) -> Result<(), Error> {
+        self.switch(super::ModuleState::Source, inst.op)?;
+        inst.expect_at_least(2)?;
+        let (text, _) = self.next_string(inst.wc - 1)?;
+        self.source_map.continue_source(&text);
+        Ok(())
+    }
+
+    pub(super) fn parse_line(&mut self, inst: Instruction) -> Result<(), Error> {
+        inst.expect(4)?;
+        let file_id = self.next()?;
+        let line = self.next()?;
+        let column = self.next()?;
+        let map = &mut self.source_map;
+        let file_name = map.strings.get(&file_id).cloned();
+        map.set_line(file_name.as_deref(), (line, column), None);
+        Ok(())
+    }
+
+    pub(super) fn parse_no_line(&mut self, inst: Instruction) -> Result<(), Error> {
+        inst.expect(1)?;
+        self.source_map.clear_line();
+        Ok(())
+    }
+
+    /// Parse an `OpExtInst` from a `NonSemantic.*` instruction set.
+    ///
+    /// We only care about the instructions of
+    /// `NonSemantic.Shader.DebugInfo.100` that locate the source. Everything
+    /// else can be skipped without changing the module's meaning.
+    pub(super) fn parse_non_semantic_ext_inst(
+        &mut self,
+        inst: Instruction,
+        result_id: spirv::Word,
+        set_id: spirv::Word,
+        gctx: crate::proc::GlobalCtx,
+    ) -> Result<(), Error> {
+        let inst_id = self.next()?;
+        let mut operands = Vec::with_capacity(inst.wc.saturating_sub(5) as usize);
+        for _ in 5..inst.wc {
+            operands.push(self.next()?);
+        }
+        if Some(set_id) != self.ext_debug_info_id {
+            return Ok(());
+        }
+
+        let map = &mut self.source_map;
+        match (inst_id, operands.as_slice()) {
+            (DEBUG_SOURCE, &[file_id, ref text_id @ ..]) => {
+                let file_name = map.strings.lookup(file_id)?.clone();
+                let text = match *text_id {
+                    [text_id] => Some(map.strings.lookup(text_id)?.clone()),
+                    _ => None,
+                };
+                map.debug_source_files.insert(result_id, file_name.clone());
+                map.add_source(Some(file_name), text);
+            }
+            (DEBUG_SOURCE_CONTINUED, &[text_id]) => {
+                let text = map.strings.lookup(text_id)?.clone();
+                map.continue_source(&text);
+            }
+            (DEBUG_LINE, &[source_id, line_start, line_end, column_start, column_end]) => {
+                let mut values = [0; 4];
+                for (value, id) in
+                    values
+                        .iter_mut()
+                        .zip([line_start, line_end, column_start, column_end])
+                {
+                    let constant = self.lookup_constant.lookup(id)?;
+                    *value = super::resolve_constant(gctx, &constant.inner)
+                        .ok_or(Error::InvalidId(id))?;
+                }
+                let [line_start, line_end, column_start, column_end] = values;
+                let map = &mut self.source_map;
+                let file_name = map.debug_source_files.get(&source_id).cloned();
+                map.set_line(
+                    file_name.as_deref(),
+                    (line_start, column_start),
+                    Some((line_end, column_end)),
+                );
+            }
+            (DEBUG_NO_LINE, _) => map.clear_line(),
+            _ => {}
+        }
+        Ok(())
+    }
+}
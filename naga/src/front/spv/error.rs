@@ -132,6 +132,12 @@ pub enum Error {
     InvalidBarrierScope(spirv::Word),
     #[error("invalid barrier memory semantics %{0}")]
     InvalidBarrierMemorySemantics(spirv::Word),
+    #[error("invalid gather component %{0}")]
+    InvalidGatherComponent(spirv::Word),
+    #[error("unsupported gather offset operand {0:?}, only constant offsets are supported")]
+    UnsupportedGatherOffset(spirv::ImageOperands),
+    #[error("invalid ray query intersection %{0}")]
+    InvalidRayQueryIntersection(spirv::Word),
     #[error(
         "arrays of images / samplers are supported only through bindings for \
          now (i.e. you can't create an array of images or samplers that doesn't \
@@ -140,6 +146,10 @@ pub enum Error {
     NonBindingArrayOfImageOrSamplers,
     #[error("naga only supports specialization constant IDs up to 65535 but was given {0}")]
     SpecIdTooHigh(u32),
+    #[error("unsupported specialization constant operation {0:?}")]
+    UnsupportedSpecConstantOp(spirv::Op),
+    #[error("invalid specialization constant operation: {0}")]
+    InvalidSpecConstantOp(crate::proc::ConstantEvaluatorError),
 
     #[error("atomic upgrade error: {0}")]
     AtomicUpgradeError(atomic_upgrade::Error),
@@ -35,6 +35,12 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
         let _fun_control = self.next()?;
         let fun_type_id = self.next()?;
 
+        let (expressions, override_copies) = self.make_expression_storage(
+            &module.global_variables,
+            &module.constants,
+            &module.overrides,
+            &module.global_expressions,
+        );
         let mut fun = {
             let ft = self.lookup_function_type.lookup(fun_type_id)?;
             if ft.return_type_id != result_type_id {
@@ -53,11 +59,7 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
                     })
                 },
                 local_variables: Arena::new(),
-                expressions: self.make_expression_storage(
-                    &module.global_variables,
-                    &module.constants,
-                    &module.overrides,
-                ),
+                expressions,
                 named_expressions: crate::NamedExpressions::default(),
                 body: crate::Block::new(),
                 diagnostic_filter_leaf: None,
@@ -140,12 +142,8 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             let fun_inst = self.next_inst()?;
             log::debug!("{:?}", fun_inst.op);
             match fun_inst.op {
-                spirv::Op::Line => {
-                    fun_inst.expect(4)?;
-                    let _file_id = self.next()?;
-                    let _row_id = self.next()?;
-                    let _col_id = self.next()?;
-                }
+                spirv::Op::Line => self.parse_line(fun_inst)?,
+                spirv::Op::NoLine => self.parse_no_line(fun_inst)?,
                 spirv::Op::Label => {
                     // Read the label ID
                     fun_inst.expect(2)?;
@@ -277,7 +275,11 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             }
         }
 
-        fun.body = block_ctx.lower();
+        // Values computed from specialization constants must be emitted
+        // before anything uses them.
+        let mut body = block_ctx.lower();
+        fun.body = super::spec_constant::emit_override_expressions(override_copies, &fun, &body);
+        fun.body.append(&mut body);
 
         // done
         let fun_handle = module.functions.append(fun, self.span_from_with_op(start));
@@ -563,12 +565,13 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             }
         }
 
+        let (workgroup_size, workgroup_size_overrides) = self.workgroup_size(module, &ep)?;
         module.entry_points.push(crate::EntryPoint {
             name: ep.name,
             stage: ep.stage,
             early_depth_test: ep.early_depth_test,
-            workgroup_size: ep.workgroup_size,
-            workgroup_size_overrides: None,
+            workgroup_size,
+            workgroup_size_overrides,
             function,
         });
 
@@ -46,6 +46,10 @@ pub struct SamplingOptions {
     pub project: bool,
     /// Depth comparison sampling with a reference value.
     pub compare: bool,
+    /// Gathering a single component from the four texels that would be
+    /// used for bilinear filtering, as `OpImageGather` and
+    /// `OpImageDrefGather` do.
+    pub gather: bool,
 }
 
 enum ExtraCoordinate {
@@ -459,6 +463,12 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
         let result_id = self.next()?;
         let sampled_image_id = self.next()?;
         let coordinate_id = self.next()?;
+        // `OpImageDrefGather` takes a reference value instead of a component.
+        let component_id = if options.gather && !options.compare {
+            Some(self.next()?)
+        } else {
+            None
+        };
         let dref_id = if options.compare {
             Some(self.next()?)
         } else {
@@ -466,6 +476,25 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
         };
         let span = self.span_from_with_op(start);
 
+        let gather = if options.gather {
+            Some(match component_id {
+                Some(id) => {
+                    let component_const = self.lookup_constant.lookup(id)?;
+                    match super::resolve_constant(ctx.gctx(), &component_const.inner) {
+                        Some(0) => crate::SwizzleComponent::X,
+                        Some(1) => crate::SwizzleComponent::Y,
+                        Some(2) => crate::SwizzleComponent::Z,
+                        Some(3) => crate::SwizzleComponent::W,
+                        _ => return Err(Error::InvalidGatherComponent(id)),
+                    }
+                }
+                // Depth comparisons always gather the depth.
+                None => crate::SwizzleComponent::X,
+            })
+        } else {
+            None
+        };
+
         let mut image_ops = if words_left != 0 {
             words_left -= 1;
             self.next()?
@@ -473,7 +502,12 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             0
         };
 
-        let mut level = crate::SampleLevel::Auto;
+        // Gathers have no level of detail operands, and always use the base level.
+        let mut level = if options.gather {
+            crate::SampleLevel::Zero
+        } else {
+            crate::SampleLevel::Auto
+        };
         let mut offset = None;
         while image_ops != 0 {
             let bit = 1 << image_ops.trailing_zeros();
@@ -562,18 +596,33 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
                 }
                 spirv::ImageOperands::CONST_OFFSET => {
                     let offset_constant = self.next()?;
-                    let offset_expr = self
+                    let offset_handle = self
                         .lookup_constant
                         .lookup(offset_constant)?
                         .inner
-                        .to_expr();
-                    let offset_handle = ctx
-                        .module
-                        .global_expressions
-                        .append(offset_expr, Default::default());
+                        .to_global_expr(&mut ctx.module.global_expressions, Default::default());
                     offset = Some(offset_handle);
                     words_left -= 1;
                 }
+                // Naga IR gathers take a single constant offset, so accept
+                // an `Offset` operand only if it happens to be constant.
+                spirv::ImageOperands::OFFSET if options.gather => {
+                    let offset_id = self.next()?;
+                    let offset_constant = self
+                        .lookup_constant
+                        .get(&offset_id)
+                        .ok_or(Error::UnsupportedGatherOffset(spirv::ImageOperands::OFFSET))?;
+                    let offset_handle = offset_constant
+                        .inner
+                        .to_global_expr(&mut ctx.module.global_expressions, Default::default());
+                    offset = Some(offset_handle);
+                    words_left -= 1;
+                }
+                spirv::ImageOperands::CONST_OFFSETS if options.gather => {
+                    return Err(Error::UnsupportedGatherOffset(
+                        spirv::ImageOperands::CONST_OFFSETS,
+                    ));
+                }
                 other => {
                     log::warn!("Unknown image sample operand {:?}", other);
                     for _ in 0..words_left {
@@ -707,7 +756,7 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
         let expr = crate::Expression::ImageSample {
             image: si_lexp.image,
             sampler: si_lexp.sampler,
-            gather: None, //TODO
+            gather,
             coordinate,
             array_index,
             offset,
@@ -715,7 +764,9 @@ impl<I: Iterator<Item = u32>> super::Frontend<I> {
             depth_ref,
         };
         let image_sample_handle = ctx.expressions.append(expr, self.span_from_with_op(start));
-        let handle = if is_depth && depth_ref.is_none() {
+        // Sampling a depth image without a comparison produces a single value,
+        // but SPIR-V always expects a vector. Gathers produce vectors already.
+        let handle = if is_depth && depth_ref.is_none() && gather.is_none() {
             let splat_expr = crate::Expression::Splat {
                 size: crate::VectorSize::Quad,
                 value: image_sample_handle,
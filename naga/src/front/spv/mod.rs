@@ -28,12 +28,16 @@ This value then gets used instead of `OpLoad` result later on.
 */
 
 mod convert;
+mod debug;
 mod error;
 mod function;
 mod image;
 mod null;
+mod ray;
+mod spec_constant;
 
 use convert::*;
+pub use debug::DebugSource;
 pub use error::Error;
 use function::*;
 
@@ -44,7 +48,7 @@ use crate::{
 };
 
 use petgraph::graphmap::GraphMap;
-use std::{convert::TryInto, mem, num::NonZeroU32, path::PathBuf};
+use std::{convert::TryInto, mem, num::NonZeroU32, ops::Range, path::PathBuf};
 
 use super::atomic_upgrade::Upgrades;
 
@@ -57,6 +61,7 @@ pub const SUPPORTED_CAPABILITIES: &[spirv::Capability] = &[
     spirv::Capability::DerivativeControl,
     spirv::Capability::Matrix,
     spirv::Capability::ImageQuery,
+    spirv::Capability::ImageGatherExtended,
     spirv::Capability::Sampled1D,
     spirv::Capability::Image1D,
     spirv::Capability::SampledCubeArray,
@@ -71,6 +76,7 @@ pub const SUPPORTED_CAPABILITIES: &[spirv::Capability] = &[
     spirv::Capability::Float64,
     spirv::Capability::Geometry,
     spirv::Capability::MultiView,
+    spirv::Capability::RayQueryKHR,
     // tricky ones
     spirv::Capability::UniformBufferArrayDynamicIndexing,
     spirv::Capability::StorageBufferArrayDynamicIndexing,
@@ -80,6 +86,8 @@ pub const SUPPORTED_EXTENSIONS: &[&str] = &[
     "SPV_KHR_vulkan_memory_model",
     "SPV_KHR_multiview",
     "SPV_EXT_shader_atomic_float_add",
+    "SPV_KHR_ray_query",
+    "SPV_KHR_non_semantic_info",
 ];
 pub const SUPPORTED_EXT_SETS: &[&str] = &["GLSL.std.450"];
 
@@ -274,6 +282,8 @@ struct EntryPoint {
     name: String,
     early_depth_test: Option<crate::EarlyDepthTest>,
     workgroup_size: [u32; 3],
+    /// The constants given by a `LocalSizeId` execution mode.
+    workgroup_size_ids: Option<[spirv::Word; 3]>,
     variable_ids: Vec<spirv::Word>,
 }
 
@@ -287,13 +297,39 @@ struct LookupType {
 enum Constant {
     Constant(Handle<crate::Constant>),
     Override(Handle<crate::Override>),
+    /// An override-expression in [`Module::global_expressions`] that isn't
+    /// an override itself, like the result of an `OpSpecConstantOp` that
+    /// uses a specialization constant, or a composite of specialization
+    /// constants.
+    ///
+    /// Each function gets its own copy of the expression, made by
+    /// [`Frontend::make_expression_storage`], which it only emits if it
+    /// uses it.
+    ///
+    /// [`Module::global_expressions`]: crate::Module::global_expressions
+    Expression(Handle<crate::Expression>),
 }
 
 impl Constant {
-    const fn to_expr(&self) -> crate::Expression {
+    /// Return a handle to this constant's value in `global_expressions`.
+    fn to_global_expr(
+        &self,
+        global_expressions: &mut Arena<crate::Expression>,
+        span: crate::Span,
+    ) -> Handle<crate::Expression> {
         match *self {
-            Self::Constant(c) => crate::Expression::Constant(c),
-            Self::Override(o) => crate::Expression::Override(o),
+            Self::Constant(c) => global_expressions.append(crate::Expression::Constant(c), span),
+            Self::Override(o) => global_expressions.append(crate::Expression::Override(o), span),
+            Self::Expression(expr) => expr,
+        }
+    }
+
+    /// Return true if this constant's value depends on a specialization
+    /// constant.
+    const fn is_override_expression(&self) -> bool {
+        match *self {
+            Self::Constant(_) => false,
+            Self::Override(_) | Self::Expression(_) => true,
         }
     }
 }
@@ -572,6 +608,13 @@ pub struct Frontend<I> {
     layouter: Layouter,
     temp_bytes: Vec<u8>,
     ext_glsl_id: Option<spirv::Word>,
+    /// The ids of all imported `NonSemantic.*` instruction sets.
+    ext_non_semantic_ids: FastHashSet<spirv::Word>,
+    /// The id of the `NonSemantic.Shader.DebugInfo.100` instruction set.
+    ext_debug_info_id: Option<spirv::Word>,
+    /// Embedded source code, and the source location of the current
+    /// instruction.
+    source_map: debug::SourceMap,
     future_decor: FastHashMap<spirv::Word, Decoration>,
     future_member_decor: FastHashMap<(spirv::Word, MemberIndex), Decoration>,
     lookup_member: FastHashMap<(Handle<crate::Type>, MemberIndex), LookupMember>,
@@ -595,6 +638,9 @@ pub struct Frontend<I> {
     lookup_function_type: FastHashMap<spirv::Word, LookupFunctionType>,
     lookup_function: FastHashMap<spirv::Word, LookupFunction>,
     lookup_entry_point: FastHashMap<spirv::Word, EntryPoint>,
+    /// The value of the constant decorated with the `WorkgroupSize` built-in,
+    /// which takes precedence over every entry point's `LocalSize`.
+    workgroup_size_builtin: Option<Handle<crate::Expression>>,
     // When parsing functions, each entry point function gets an entry here so that additional
     // processing for them can be performed after all function parsing.
     deferred_entry_points: Vec<(EntryPoint, spirv::Word)>,
@@ -634,6 +680,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             layouter: Layouter::default(),
             temp_bytes: Vec::new(),
             ext_glsl_id: None,
+            ext_non_semantic_ids: FastHashSet::default(),
+            ext_debug_info_id: None,
+            source_map: debug::SourceMap::default(),
             future_decor: FastHashMap::default(),
             future_member_decor: FastHashMap::default(),
             handle_sampling: FastHashMap::default(),
@@ -650,6 +699,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             lookup_function_type: FastHashMap::default(),
             lookup_function: FastHashMap::default(),
             lookup_entry_point: FastHashMap::default(),
+            workgroup_size_builtin: None,
             deferred_entry_points: Vec::default(),
             deferred_function_calls: Vec::default(),
             dummy_functions: Arena::new(),
@@ -661,11 +711,23 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
     }
 
     fn span_from(&self, from: usize) -> crate::Span {
-        crate::Span::from(from..self.data_offset)
+        self.span_for(from..self.data_offset)
     }
 
     fn span_from_with_op(&self, from: usize) -> crate::Span {
-        crate::Span::from((from - 4)..self.data_offset)
+        self.span_for((from - 4)..self.data_offset)
+    }
+
+    /// Return the span of the instruction at `range` in the module's binary.
+    ///
+    /// If the module has debug information, this is instead the span of
+    /// the source code that the current `OpLine` refers to.
+    fn span_for(&self, range: Range<usize>) -> crate::Span {
+        if self.source_map.is_active() {
+            self.source_map.current_line()
+        } else {
+            crate::Span::from(range)
+        }
     }
 
     fn next(&mut self) -> Result<u32, Error> {
@@ -1471,6 +1533,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             })
         }
 
+        // `OpLine` only applies until the end of the block.
+        self.source_map.clear_line();
+
         let mut emitter = crate::proc::Emitter::default();
         emitter.start(ctx.expressions);
 
@@ -1533,17 +1598,12 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             use spirv::Op;
             let start = self.data_offset;
             let inst = self.next_inst()?;
-            let span = crate::Span::from(start..(start + 4 * (inst.wc as usize)));
+            let span = self.span_for(start..(start + 4 * (inst.wc as usize)));
             log::debug!("\t\t{:?} [{}]", inst.op, inst.wc);
 
             match inst.op {
-                Op::Line => {
-                    inst.expect(4)?;
-                    let _file_id = self.next()?;
-                    let _row_id = self.next()?;
-                    let _col_id = self.next()?;
-                }
-                Op::NoLine => inst.expect(1)?,
+                Op::Line => self.parse_line(inst)?,
+                Op::NoLine => self.parse_no_line(inst)?,
                 Op::Undef => {
                     inst.expect(3)?;
                     let type_id = self.next()?;
@@ -1573,7 +1633,19 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                         inst.expect(5)?;
                         let init_id = self.next()?;
                         let lconst = self.lookup_constant.lookup(init_id)?;
-                        Some(ctx.expressions.append(lconst.inner.to_expr(), span))
+                        Some(match lconst.inner {
+                            Constant::Constant(c) => {
+                                ctx.expressions.append(crate::Expression::Constant(c), span)
+                            }
+                            Constant::Override(o) => {
+                                ctx.expressions.append(crate::Expression::Override(o), span)
+                            }
+                            // `make_expression_storage` already copied the
+                            // expression into this function.
+                            Constant::Expression(_) => {
+                                self.lookup_expression.lookup(init_id)?.handle
+                            }
+                        })
                     } else {
                         None
                     };
@@ -2177,9 +2249,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let base_handle = get_expr_handle!(pointer_id, base_lexp);
                     let type_lookup = self.lookup_type.lookup(base_lexp.type_id)?;
                     let handle = match ctx.module.types[type_lookup.handle].inner {
-                        crate::TypeInner::Image { .. } | crate::TypeInner::Sampler { .. } => {
-                            base_handle
-                        }
+                        crate::TypeInner::Image { .. }
+                        | crate::TypeInner::Sampler { .. }
+                        | crate::TypeInner::AccelerationStructure => base_handle,
                         _ => match self.lookup_load_override.get(&pointer_id) {
                             Some(&LookupLoadOverride::Loaded(handle)) => handle,
                             //Note: we aren't handling `LookupLoadOverride::Pending` properly here
@@ -2783,6 +2855,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let options = image::SamplingOptions {
                         compare: false,
                         project: false,
+                        gather: false,
                     };
                     self.parse_image_sample(
                         extra,
@@ -2799,6 +2872,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let options = image::SamplingOptions {
                         compare: false,
                         project: true,
+                        gather: false,
                     };
                     self.parse_image_sample(
                         extra,
@@ -2815,6 +2889,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let options = image::SamplingOptions {
                         compare: true,
                         project: false,
+                        gather: false,
                     };
                     self.parse_image_sample(
                         extra,
@@ -2831,6 +2906,41 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let options = image::SamplingOptions {
                         compare: true,
                         project: true,
+                        gather: false,
+                    };
+                    self.parse_image_sample(
+                        extra,
+                        options,
+                        ctx,
+                        &mut emitter,
+                        &mut block,
+                        block_id,
+                        body_idx,
+                    )?;
+                }
+                Op::ImageGather => {
+                    let extra = inst.expect_at_least(6)?;
+                    let options = image::SamplingOptions {
+                        compare: false,
+                        project: false,
+                        gather: true,
+                    };
+                    self.parse_image_sample(
+                        extra,
+                        options,
+                        ctx,
+                        &mut emitter,
+                        &mut block,
+                        block_id,
+                        body_idx,
+                    )?;
+                }
+                Op::ImageDrefGather => {
+                    let extra = inst.expect_at_least(6)?;
+                    let options = image::SamplingOptions {
+                        compare: true,
+                        project: false,
+                        gather: true,
                     };
                     self.parse_image_sample(
                         extra,
@@ -2842,6 +2952,38 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                         body_idx,
                     )?;
                 }
+                Op::RayQueryInitializeKHR => {
+                    inst.expect(9)?;
+                    self.parse_ray_query_initialize(ctx, &mut emitter, &mut block, body_idx)?;
+                }
+                Op::RayQueryProceedKHR => {
+                    inst.expect(4)?;
+                    self.parse_ray_query_proceed(ctx, &mut emitter, &mut block, block_id)?;
+                }
+                Op::RayQueryTerminateKHR => {
+                    inst.expect(2)?;
+                    self.parse_ray_query_terminate(ctx, &mut emitter, &mut block)?;
+                }
+                Op::RayQueryGetIntersectionTypeKHR
+                | Op::RayQueryGetIntersectionTKHR
+                | Op::RayQueryGetIntersectionInstanceCustomIndexKHR
+                | Op::RayQueryGetIntersectionInstanceIdKHR
+                | Op::RayQueryGetIntersectionInstanceShaderBindingTableRecordOffsetKHR
+                | Op::RayQueryGetIntersectionGeometryIndexKHR
+                | Op::RayQueryGetIntersectionPrimitiveIndexKHR
+                | Op::RayQueryGetIntersectionBarycentricsKHR
+                | Op::RayQueryGetIntersectionFrontFaceKHR
+                | Op::RayQueryGetIntersectionObjectToWorldKHR
+                | Op::RayQueryGetIntersectionWorldToObjectKHR => {
+                    inst.expect(5)?;
+                    self.parse_ray_query_get_intersection(
+                        inst.op,
+                        ctx,
+                        &mut emitter,
+                        &mut block,
+                        block_id,
+                    )?;
+                }
                 Op::ImageQuerySize => {
                     inst.expect(4)?;
                     self.parse_image_query_size(
@@ -3088,6 +3230,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     let result_type_id = self.next()?;
                     let result_id = self.next()?;
                     let set_id = self.next()?;
+                    if self.ext_non_semantic_ids.contains(&set_id) {
+                        self.parse_non_semantic_ext_inst(inst, result_id, set_id, ctx.gctx())?;
+                        continue;
+                    }
                     if Some(set_id) != self.ext_glsl_id {
                         return Err(Error::UnsupportedExtInstSet(set_id));
                     }
@@ -3846,6 +3992,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                                     .bits()
                                 != 0,
                         );
+                        flags.set(
+                            crate::Barrier::TEXTURE,
+                            semantics & spirv::MemorySemantics::IMAGE_MEMORY.bits() != 0,
+                        );
                         block.push(crate::Statement::Barrier(flags), span);
                     } else {
                         log::warn!("Unsupported barrier execution scope: {}", exec_scope);
@@ -3891,7 +4041,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                                 ctx.gctx().global_expressions[ctx.gctx().constants[constant].init],
                                 crate::Expression::Literal(crate::Literal::Bool(true)),
                             ),
-                            Constant::Override(_) => false,
+                            Constant::Override(_) | Constant::Expression(_) => false,
                         })
                         .is_some()
                     {
@@ -4042,7 +4192,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 | Op::GroupNonUniformShuffle
                 | Op::GroupNonUniformShuffleDown
                 | Op::GroupNonUniformShuffleUp
-                | Op::GroupNonUniformShuffleXor => {
+                | Op::GroupNonUniformShuffleXor
+                | Op::GroupNonUniformQuadBroadcast
+                | Op::GroupNonUniformQuadSwap => {
                     inst.expect(if matches!(inst.op, Op::GroupNonUniformBroadcastFirst) {
                         5
                     } else {
@@ -4064,6 +4216,16 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
 
                     let mode = if matches!(inst.op, Op::GroupNonUniformBroadcastFirst) {
                         crate::GatherMode::BroadcastFirst
+                    } else if matches!(inst.op, Op::GroupNonUniformQuadSwap) {
+                        let direction_id = self.next()?;
+                        let direction_const = self.lookup_constant.lookup(direction_id)?;
+                        let direction = match resolve_constant(ctx.gctx(), &direction_const.inner) {
+                            Some(0) => crate::Direction::X,
+                            Some(1) => crate::Direction::Y,
+                            Some(2) => crate::Direction::Diagonal,
+                            _ => return Err(Error::InvalidOperand),
+                        };
+                        crate::GatherMode::QuadSwap(direction)
                     } else {
                         let index_id = self.next()?;
                         let index_lookup = self.lookup_expression.lookup(index_id)?;
@@ -4082,6 +4244,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                             Op::GroupNonUniformShuffleXor => {
                                 crate::GatherMode::ShuffleXor(index_handle)
                             }
+                            Op::GroupNonUniformQuadBroadcast => {
+                                crate::GatherMode::QuadBroadcast(index_handle)
+                            }
                             _ => unreachable!(),
                         }
                     };
@@ -4383,12 +4548,22 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
+    /// Create a function's expression arena, holding its uses of global
+    /// variables and constants.
+    ///
+    /// Also return the range of override-expression copies that
+    /// [`spec_constant::emit_override_expressions`] must consider emitting
+    /// at the start of the function's body.
     fn make_expression_storage(
         &mut self,
         globals: &Arena<crate::GlobalVariable>,
         constants: &Arena<crate::Constant>,
         overrides: &Arena<crate::Override>,
-    ) -> Arena<crate::Expression> {
+        global_expressions: &Arena<crate::Expression>,
+    ) -> (
+        Arena<crate::Expression>,
+        crate::arena::Range<crate::Expression>,
+    ) {
         let mut expressions = Arena::new();
         #[allow(clippy::panic)]
         {
@@ -4415,6 +4590,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             let (expr, span) = match con.inner {
                 Constant::Constant(c) => (crate::Expression::Constant(c), constants.get_span(c)),
                 Constant::Override(o) => (crate::Expression::Override(o), overrides.get_span(o)),
+                Constant::Expression(_) => continue,
             };
             let handle = expressions.append(expr, span);
             self.lookup_expression.insert(
@@ -4429,8 +4605,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 },
             );
         }
+        let copies = self.copy_override_expressions(global_expressions, &mut expressions);
         // done
-        expressions
+        (expressions, copies)
     }
 
     fn switch(&mut self, state: ModuleState, op: spirv::Op) -> Result<(), Error> {
@@ -4569,6 +4746,20 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
     }
 
     pub fn parse(mut self) -> Result<crate::Module, Error> {
+        self.parse_module()
+    }
+
+    /// Parse the module, and return it along with the source code embedded
+    /// in its debug information, if any.
+    ///
+    /// If the module embeds its source code, the spans in the returned
+    /// module refer to that source code. See [`DebugSource`].
+    pub fn parse_with_source(mut self) -> Result<(crate::Module, Option<DebugSource>), Error> {
+        let module = self.parse_module()?;
+        Ok((module, self.source_map.take_source()))
+    }
+
+    fn parse_module(&mut self) -> Result<crate::Module, Error> {
         let mut module = {
             if self.next()? != spirv::MAGIC_NUMBER {
                 return Err(Error::InvalidHeader);
@@ -4603,8 +4794,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 Op::MemoryModel => self.parse_memory_model(inst),
                 Op::EntryPoint => self.parse_entry_point(inst),
                 Op::ExecutionMode => self.parse_execution_mode(inst),
+                Op::ExecutionModeId => self.parse_execution_mode_id(inst),
                 Op::String => self.parse_string(inst),
                 Op::Source => self.parse_source(inst),
+                Op::SourceContinued => self.parse_source_continued(inst),
                 Op::SourceExtension => self.parse_source_extension(inst),
                 Op::Name => self.parse_name(inst),
                 Op::MemberName => self.parse_member_name(inst),
@@ -4625,10 +4818,15 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                 Op::TypeImage => self.parse_type_image(inst, &mut module),
                 Op::TypeSampledImage => self.parse_type_sampled_image(inst),
                 Op::TypeSampler => self.parse_type_sampler(inst, &mut module),
+                Op::TypeAccelerationStructureKHR => {
+                    self.parse_type_acceleration_structure(inst, &mut module)
+                }
+                Op::TypeRayQueryKHR => self.parse_type_ray_query(inst, &mut module),
                 Op::Constant | Op::SpecConstant => self.parse_constant(inst, &mut module),
                 Op::ConstantComposite | Op::SpecConstantComposite => {
                     self.parse_composite_constant(inst, &mut module)
                 }
+                Op::SpecConstantOp => self.parse_spec_constant_op(inst, &mut module),
                 Op::ConstantNull | Op::Undef => self.parse_null_constant(inst, &mut module),
                 Op::ConstantTrue | Op::SpecConstantTrue => {
                     self.parse_bool_constant(inst, true, &mut module)
@@ -4637,6 +4835,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
                     self.parse_bool_constant(inst, false, &mut module)
                 }
                 Op::Variable => self.parse_global_variable(inst, &mut module),
+                Op::ExtInst => self.parse_global_ext_inst(inst, &module),
+                Op::Line => self.parse_line(inst),
+                Op::NoLine => self.parse_no_line(inst),
                 Op::Function => {
                     self.switch(ModuleState::Function, inst.op)?;
                     inst.expect(5)?;
@@ -4745,6 +4946,14 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         if left != 0 {
             return Err(Error::InvalidOperand);
         }
+        if name.starts_with("NonSemantic.") {
+            self.ext_non_semantic_ids.insert(result_id);
+            if name == "NonSemantic.Shader.DebugInfo.100" {
+                self.ext_debug_info_id = Some(result_id);
+                self.source_map.activate();
+            }
+            return Ok(());
+        }
         if !SUPPORTED_EXT_SETS.contains(&name.as_str()) {
             return Err(Error::UnsupportedExtSet(name));
         }
@@ -4778,6 +4987,7 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             name,
             early_depth_test: None,
             workgroup_size: [0; 3],
+            workgroup_size_ids: None,
             variable_ids: self.data.by_ref().take(left as usize).collect(),
         };
         self.lookup_entry_point.insert(function_id, ep);
@@ -4839,19 +5049,34 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
-    fn parse_string(&mut self, inst: Instruction) -> Result<(), Error> {
-        self.switch(ModuleState::Source, inst.op)?;
+    fn parse_execution_mode_id(&mut self, inst: Instruction) -> Result<(), Error> {
+        use spirv::ExecutionMode;
+
+        self.switch(ModuleState::ExecutionMode, inst.op)?;
         inst.expect_at_least(3)?;
-        let _id = self.next()?;
-        let (_name, _) = self.next_string(inst.wc - 2)?;
-        Ok(())
-    }
 
-    fn parse_source(&mut self, inst: Instruction) -> Result<(), Error> {
-        self.switch(ModuleState::Source, inst.op)?;
-        for _ in 1..inst.wc {
-            let _ = self.next()?;
+        let ep_id = self.next()?;
+        let mode_id = self.next()?;
+        let args: Vec<spirv::Word> = self.data.by_ref().take(inst.wc as usize - 3).collect();
+
+        let ep = self
+            .lookup_entry_point
+            .get_mut(&ep_id)
+            .ok_or(Error::InvalidId(ep_id))?;
+        let mode =
+            ExecutionMode::from_u32(mode_id).ok_or(Error::UnsupportedExecutionMode(mode_id))?;
+
+        match mode {
+            ExecutionMode::LocalSizeId => {
+                inst.expect(6)?;
+                // The constants these refer to aren't known yet.
+                ep.workgroup_size_ids = Some([args[0], args[1], args[2]]);
+            }
+            _ => {
+                return Err(Error::UnsupportedExecutionMode(mode_id));
+            }
         }
+
         Ok(())
     }
 
@@ -5548,6 +5773,60 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
+    fn parse_type_acceleration_structure(
+        &mut self,
+        inst: Instruction,
+        module: &mut crate::Module,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect(2)?;
+        let id = self.next()?;
+        let decor = self.future_decor.remove(&id).unwrap_or_default();
+        let handle = module.types.insert(
+            crate::Type {
+                name: decor.name,
+                inner: crate::TypeInner::AccelerationStructure,
+            },
+            self.span_from_with_op(start),
+        );
+        self.lookup_type.insert(
+            id,
+            LookupType {
+                handle,
+                base_id: None,
+            },
+        );
+        Ok(())
+    }
+
+    fn parse_type_ray_query(
+        &mut self,
+        inst: Instruction,
+        module: &mut crate::Module,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect(2)?;
+        let id = self.next()?;
+        let decor = self.future_decor.remove(&id).unwrap_or_default();
+        let handle = module.types.insert(
+            crate::Type {
+                name: decor.name,
+                inner: crate::TypeInner::RayQuery,
+            },
+            self.span_from_with_op(start),
+        );
+        self.lookup_type.insert(
+            id,
+            LookupType {
+                handle,
+                base_id: None,
+            },
+        );
+        Ok(())
+    }
+
     fn parse_constant(
         &mut self,
         inst: Instruction,
@@ -5636,14 +5915,16 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         let ty = type_lookup.handle;
 
         let mut components = Vec::with_capacity(inst.wc as usize - 3);
+        let mut is_override_expression = false;
         for _ in 0..components.capacity() {
             let start = self.data_offset;
             let component_id = self.next()?;
             let span = self.span_from_with_op(start);
             let constant = self.lookup_constant.lookup(component_id)?;
-            let expr = module
-                .global_expressions
-                .append(constant.inner.to_expr(), span);
+            is_override_expression |= constant.inner.is_override_expression();
+            let expr = constant
+                .inner
+                .to_global_expr(&mut module.global_expressions, span);
             components.push(expr);
         }
 
@@ -5653,6 +5934,10 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             .global_expressions
             .append(crate::Expression::Compose { ty, components }, span);
 
+        if is_override_expression {
+            self.insert_override_expression(id, type_id, init);
+            return Ok(());
+        }
         self.insert_parsed_constant(module, id, type_id, ty, init, span)
     }
 
@@ -5712,6 +5997,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         span: crate::Span,
     ) -> Result<(), Error> {
         let decor = self.future_decor.remove(&id).unwrap_or_default();
+        if decor.built_in == Some(spirv::BuiltIn::WorkgroupSize as u32) {
+            self.workgroup_size_builtin = Some(init);
+        }
 
         let inner = if let Some(id) = decor.specialization_constant_id {
             let o = crate::Override {
@@ -5735,6 +6023,46 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
         Ok(())
     }
 
+    /// Record `init`, an override-expression that isn't an override itself,
+    /// as the value of the constant `id`.
+    ///
+    /// See [`Constant::Expression`].
+    fn insert_override_expression(
+        &mut self,
+        id: spirv::Word,
+        type_id: spirv::Word,
+        init: Handle<crate::Expression>,
+    ) {
+        let decor = self.future_decor.remove(&id).unwrap_or_default();
+        if decor.built_in == Some(spirv::BuiltIn::WorkgroupSize as u32) {
+            self.workgroup_size_builtin = Some(init);
+        }
+
+        self.lookup_constant.insert(
+            id,
+            LookupConstant {
+                inner: Constant::Expression(init),
+                type_id,
+            },
+        );
+    }
+
+    fn parse_global_ext_inst(
+        &mut self,
+        inst: Instruction,
+        module: &crate::Module,
+    ) -> Result<(), Error> {
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect_at_least(5)?;
+        let _result_type_id = self.next()?;
+        let result_id = self.next()?;
+        let set_id = self.next()?;
+        if !self.ext_non_semantic_ids.contains(&set_id) {
+            return Err(Error::UnsupportedExtInstSet(set_id));
+        }
+        self.parse_non_semantic_ext_inst(inst, result_id, set_id, module.to_ctx())
+    }
+
     fn parse_global_variable(
         &mut self,
         inst: Instruction,
@@ -5752,9 +6080,9 @@ impl<I: Iterator<Item = u32>> Frontend<I> {
             let init_id = self.next()?;
             let span = self.span_from_with_op(start);
             let lconst = self.lookup_constant.lookup(init_id)?;
-            let expr = module
-                .global_expressions
-                .append(lconst.inner.to_expr(), span);
+            let expr = lconst
+                .inner
+                .to_global_expr(&mut module.global_expressions, span);
             Some(expr)
         } else {
             None
@@ -6025,7 +6353,7 @@ fn make_index_literal(
 fn resolve_constant(gctx: crate::proc::GlobalCtx, constant: &Constant) -> Option<u32> {
     let constant = match *constant {
         Constant::Constant(constant) => constant,
-        Constant::Override(_) => return None,
+        Constant::Override(_) | Constant::Expression(_) => return None,
     };
     match gctx.global_expressions[gctx.constants[constant].init] {
         crate::Expression::Literal(crate::Literal::U32(id)) => Some(id),
@@ -6045,6 +6373,24 @@ pub fn parse_u8_slice(data: &[u8], options: &Options) -> Result<crate::Module, E
     Frontend::new(words, options).parse()
 }
 
+/// Parse a module, and return it along with the source code embedded in
+/// its debug information, if any.
+///
+/// See [`Frontend::parse_with_source`].
+pub fn parse_u8_slice_with_source(
+    data: &[u8],
+    options: &Options,
+) -> Result<(crate::Module, Option<DebugSource>), Error> {
+    if data.len() % 4 != 0 {
+        return Err(Error::IncompleteData);
+    }
+
+    let words = data
+        .chunks(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()));
+    Frontend::new(words, options).parse_with_source()
+}
+
 /// Helper function to check if `child` is in the scope of `parent`
 fn is_parent(mut child: usize, parent: usize, block_ctx: &BlockContext) -> bool {
     loop {
@@ -6075,4 +6421,85 @@ mod test {
         ];
         let _ = super::parse_u8_slice(&bin, &Default::default()).unwrap();
     }
+
+    /// Check that spans in `bin` refer to its embedded source code.
+    fn check_source_spans(bin: &[u8]) {
+        let (module, source) = super::parse_u8_slice_with_source(bin, &Default::default()).unwrap();
+        let source = source.expect("module should embed its source");
+        assert_eq!(source.file_name.as_deref(), Some("data.comp"));
+        assert_eq!(source.language, spirv::SourceLanguage::GLSL);
+        assert!(source.source_code.ends_with("    b = x * 2.0;\n}\n"));
+
+        // The entry point's body is a wrapper that calls the SPIR-V function.
+        let (_, function) = module.functions.iter().next().unwrap();
+        let text = |span: crate::Span| &source.source_code[span.to_range().unwrap()];
+        let load = function
+            .expressions
+            .iter()
+            .find(|&(_, expr)| matches!(*expr, crate::Expression::Load { .. }))
+            .unwrap()
+            .0;
+        assert!(text(function.expressions.get_span(load)).starts_with("float x = a"));
+        let (_, &store_span) = function
+            .body
+            .span_iter()
+            .find(|&(statement, _)| matches!(*statement, crate::Statement::Store { .. }))
+            .unwrap();
+        assert!(text(store_span).starts_with("b = x * 2.0"));
+    }
+
+    /// Return `gather.spv` with the image operands of its first
+    /// `OpImageGather` that has any replaced with `operands` and `offset`.
+    fn patch_gather_offset(operands: spirv::ImageOperands, offset: spirv::Word) -> Vec<u8> {
+        let bin = include_bytes!("../../../tests/in/spv/gather.spv");
+        let mut words: Vec<u32> = bin
+            .chunks(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        let mut index = 5;
+        loop {
+            let word_count = (words[index] >> 16) as usize;
+            let opcode = words[index] & 0xffff;
+            if opcode == spirv::Op::ImageGather as u32 && word_count == 8 {
+                words[index + 6] = operands.bits();
+                words[index + 7] = offset;
+                break;
+            }
+            index += word_count;
+        }
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    #[test]
+    fn gather_offsets() {
+        // %22 is a constant `vec2<i32>`, and %33 is the loaded `uv` input.
+        let bin = patch_gather_offset(spirv::ImageOperands::OFFSET, 22);
+        super::parse_u8_slice(&bin, &Default::default()).unwrap();
+
+        let bin = patch_gather_offset(spirv::ImageOperands::OFFSET, 33);
+        let error = super::parse_u8_slice(&bin, &Default::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            super::Error::UnsupportedGatherOffset(spirv::ImageOperands::OFFSET)
+        ));
+
+        let bin = patch_gather_offset(spirv::ImageOperands::CONST_OFFSETS, 22);
+        let error = super::parse_u8_slice(&bin, &Default::default()).unwrap_err();
+        assert!(matches!(
+            error,
+            super::Error::UnsupportedGatherOffset(spirv::ImageOperands::CONST_OFFSETS)
+        ));
+    }
+
+    #[test]
+    fn op_line_spans() {
+        check_source_spans(include_bytes!("../../../tests/in/spv/debug-info-line.spv"));
+    }
+
+    #[test]
+    fn debug_line_spans() {
+        check_source_spans(include_bytes!(
+            "../../../tests/in/spv/debug-info-non-semantic.spv"
+        ));
+    }
 }
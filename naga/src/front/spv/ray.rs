@@ -0,0 +1,237 @@
+/*!
+Parsing `SPV_KHR_ray_query` instructions.
+*/
+
+use super::{Error, LookupExpression, LookupHelper as _};
+
+impl<I: Iterator<Item = u32>> super::Frontend<I> {
+    /// Parse `OpRayQueryInitializeKHR`.
+    ///
+    /// The separate ray parameters are gathered into a struct of the
+    /// [`SpecialTypes::ray_desc`] type, as [`RayQueryFunction::Initialize`]
+    /// expects.
+    ///
+    /// [`SpecialTypes::ray_desc`]: crate::SpecialTypes::ray_desc
+    /// [`RayQueryFunction::Initialize`]: crate::RayQueryFunction::Initialize
+    pub(super) fn parse_ray_query_initialize(
+        &mut self,
+        ctx: &mut super::BlockContext,
+        emitter: &mut crate::proc::Emitter,
+        block: &mut crate::Block,
+        body_idx: usize,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        let query_id = self.next()?;
+        let acceleration_structure_id = self.next()?;
+        let flags_id = self.next()?;
+        let cull_mask_id = self.next()?;
+        let origin_id = self.next()?;
+        let tmin_id = self.next()?;
+        let dir_id = self.next()?;
+        let tmax_id = self.next()?;
+        let span = self.span_from_with_op(start);
+
+        // Ray queries are always local variables, and acceleration
+        // structures are always globals, so both are in the root scope.
+        let query = self.lookup_expression.lookup(query_id)?.handle;
+        let acceleration_structure = self
+            .lookup_expression
+            .lookup(acceleration_structure_id)?
+            .handle;
+
+        //Note: the order of components must match `generate_ray_desc_type`
+        let mut components = Vec::with_capacity(6);
+        for id in [flags_id, cull_mask_id, tmin_id, tmax_id, origin_id, dir_id] {
+            let lexp = self.lookup_expression.lookup(id)?;
+            components.push(self.get_expr_handle(id, lexp, ctx, emitter, block, body_idx));
+        }
+        let ty = ctx.module.generate_ray_desc_type();
+        let descriptor = ctx
+            .expressions
+            .append(crate::Expression::Compose { ty, components }, span);
+
+        block.extend(emitter.finish(ctx.expressions));
+        block.push(
+            crate::Statement::RayQuery {
+                query,
+                fun: crate::RayQueryFunction::Initialize {
+                    acceleration_structure,
+                    descriptor,
+                },
+            },
+            span,
+        );
+        emitter.start(ctx.expressions);
+        Ok(())
+    }
+
+    /// Parse `OpRayQueryProceedKHR`.
+    pub(super) fn parse_ray_query_proceed(
+        &mut self,
+        ctx: &mut super::BlockContext,
+        emitter: &mut crate::proc::Emitter,
+        block: &mut crate::Block,
+        block_id: spirv::Word,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        let result_type_id = self.next()?;
+        let result_id = self.next()?;
+        let query_id = self.next()?;
+        let span = self.span_from_with_op(start);
+
+        let query = self.lookup_expression.lookup(query_id)?.handle;
+
+        block.extend(emitter.finish(ctx.expressions));
+        let result = ctx
+            .expressions
+            .append(crate::Expression::RayQueryProceedResult, span);
+        self.lookup_expression.insert(
+            result_id,
+            LookupExpression {
+                handle: result,
+                type_id: result_type_id,
+                block_id,
+            },
+        );
+        block.push(
+            crate::Statement::RayQuery {
+                query,
+                fun: crate::RayQueryFunction::Proceed { result },
+            },
+            span,
+        );
+        emitter.start(ctx.expressions);
+        Ok(())
+    }
+
+    /// Parse `OpRayQueryTerminateKHR`.
+    pub(super) fn parse_ray_query_terminate(
+        &mut self,
+        ctx: &mut super::BlockContext,
+        emitter: &mut crate::proc::Emitter,
+        block: &mut crate::Block,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        let query_id = self.next()?;
+        let span = self.span_from_with_op(start);
+
+        let query = self.lookup_expression.lookup(query_id)?.handle;
+
+        block.extend(emitter.finish(ctx.expressions));
+        block.push(
+            crate::Statement::RayQuery {
+                query,
+                fun: crate::RayQueryFunction::Terminate,
+            },
+            span,
+        );
+        emitter.start(ctx.expressions);
+        Ok(())
+    }
+
+    /// Parse one of the `OpRayQueryGetIntersection*KHR` instructions that
+    /// read a member of the [`SpecialTypes::ray_intersection`] struct.
+    ///
+    /// [`SpecialTypes::ray_intersection`]: crate::SpecialTypes::ray_intersection
+    pub(super) fn parse_ray_query_get_intersection(
+        &mut self,
+        op: spirv::Op,
+        ctx: &mut super::BlockContext,
+        emitter: &mut crate::proc::Emitter,
+        block: &mut crate::Block,
+        block_id: spirv::Word,
+    ) -> Result<(), Error> {
+        let start = self.data_offset;
+        let result_type_id = self.next()?;
+        let result_id = self.next()?;
+        let query_id = self.next()?;
+        let intersection_id = self.next()?;
+        let span = self.span_from_with_op(start);
+
+        //Note: the indices must match `generate_ray_intersection_type`
+        let member = match op {
+            spirv::Op::RayQueryGetIntersectionTypeKHR => 0,
+            spirv::Op::RayQueryGetIntersectionTKHR => 1,
+            spirv::Op::RayQueryGetIntersectionInstanceCustomIndexKHR => 2,
+            spirv::Op::RayQueryGetIntersectionInstanceIdKHR => 3,
+            spirv::Op::RayQueryGetIntersectionInstanceShaderBindingTableRecordOffsetKHR => 4,
+            spirv::Op::RayQueryGetIntersectionGeometryIndexKHR => 5,
+            spirv::Op::RayQueryGetIntersectionPrimitiveIndexKHR => 6,
+            spirv::Op::RayQueryGetIntersectionBarycentricsKHR => 7,
+            spirv::Op::RayQueryGetIntersectionFrontFaceKHR => 8,
+            spirv::Op::RayQueryGetIntersectionObjectToWorldKHR => 9,
+            spirv::Op::RayQueryGetIntersectionWorldToObjectKHR => 10,
+            _ => return Err(Error::UnsupportedInstruction(self.state, op)),
+        };
+
+        let query = self.lookup_expression.lookup(query_id)?.handle;
+        let intersection_const = self.lookup_constant.lookup(intersection_id)?;
+        let committed = match super::resolve_constant(ctx.gctx(), &intersection_const.inner) {
+            Some(0) => false,
+            Some(1) => true,
+            _ => return Err(Error::InvalidRayQueryIntersection(intersection_id)),
+        };
+
+        ctx.module.generate_ray_intersection_type();
+        let intersection = ctx.expressions.append(
+            crate::Expression::RayQueryGetIntersection { query, committed },
+            span,
+        );
+        let mut handle = ctx.expressions.append(
+            crate::Expression::AccessIndex {
+                base: intersection,
+                index: member,
+            },
+            span,
+        );
+
+        // The IR intersection kind matches SPIR-V's committed intersection
+        // types, but candidates use their own enumeration, where triangles
+        // are 0 and AABBs are 1.
+        if op == spirv::Op::RayQueryGetIntersectionTypeKHR && !committed {
+            // Literals must not be covered by an `Emit` statement.
+            block.extend(emitter.finish(ctx.expressions));
+            let mut literal = |value: u32| {
+                ctx.expressions
+                    .append(crate::Expression::Literal(crate::Literal::U32(value)), span)
+            };
+            let triangle = literal(crate::RayQueryIntersection::Triangle as u32);
+            let candidate_triangle = literal(
+                spirv::RayQueryCandidateIntersectionType::RayQueryCandidateIntersectionTriangleKHR
+                    as u32,
+            );
+            let candidate_aabb = literal(
+                spirv::RayQueryCandidateIntersectionType::RayQueryCandidateIntersectionAABBKHR
+                    as u32,
+            );
+            emitter.start(ctx.expressions);
+
+            let is_triangle = ctx.expressions.append(
+                crate::Expression::Binary {
+                    op: crate::BinaryOperator::Equal,
+                    left: handle,
+                    right: triangle,
+                },
+                span,
+            );
+            handle = ctx.expressions.append(
+                crate::Expression::Select {
+                    condition: is_triangle,
+                    accept: candidate_triangle,
+                    reject: candidate_aabb,
+                },
+                span,
+            );
+        }
+
+        self.lookup_expression.insert(
+            result_id,
+            LookupExpression {
+                handle,
+                type_id: result_type_id,
+                block_id,
+            },
+        );
+        Ok(())
+    }
+}
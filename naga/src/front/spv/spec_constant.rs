@@ -0,0 +1,650 @@
+/*!
+Parsing `OpSpecConstantOp`, and using override-expressions in functions.
+
+Naga represents SPIR-V specialization constants decorated with `SpecId` as
+[`Override`]s whose [`id`] is the `SpecId`, so pipelines can specialize them
+the same way they specialize WGSL `override` declarations.
+
+Values computed from specialization constants, by `OpSpecConstantOp` or
+`OpSpecConstantComposite`, become override-expressions in
+[`Module::global_expressions`]. Naga's IR has no way to refer to such an
+expression from a function, so, just as every constant and global variable
+is registered in every function, every function gets its own copy of these
+expressions. Once the function has been parsed, the copies it uses are
+emitted at the start of its body by [`Emit`] statements; like the
+[`Constant`] expressions of constants it doesn't use, the rest are left
+alone for compaction to remove.
+
+[`Override`]: crate::Override
+[`Emit`]: crate::Statement::Emit
+[`Constant`]: crate::Expression::Constant
+[`id`]: crate::Override::id
+[`Module::global_expressions`]: crate::Module::global_expressions
+*/
+
+use super::{Constant, Error, Instruction, LookupExpression, LookupHelper as _, ModuleState};
+use crate::{
+    arena::{Arena, Handle, HandleSet, Range},
+    proc::{
+        operands::{for_each_expression_operand, for_each_statement_operand},
+        ConstantEvaluator, ExpressionKindTracker,
+    },
+    BinaryOperator, Block, Expression, FastHashMap, ScalarKind, UnaryOperator,
+};
+
+impl<I: Iterator<Item = u32>> super::Frontend<I> {
+    /// Parse `OpSpecConstantOp`.
+    ///
+    /// If none of the operands depend on specialization constants, the
+    /// result is evaluated now and becomes an ordinary [`Constant`].
+    ///
+    /// [`Constant`]: crate::Constant
+    pub(super) fn parse_spec_constant_op(
+        &mut self,
+        inst: Instruction,
+        module: &mut crate::Module,
+    ) -> Result<(), Error> {
+        use spirv::Op;
+
+        let start = self.data_offset;
+        self.switch(ModuleState::Type, inst.op)?;
+        inst.expect_at_least(5)?;
+        let type_id = self.next()?;
+        let id = self.next()?;
+        let opcode = self.next()?;
+        let mut words = Vec::with_capacity(inst.wc as usize - 4);
+        for _ in 4..inst.wc {
+            words.push(self.next()?);
+        }
+        let span = self.span_from_with_op(start);
+
+        let op = Op::from_u32(opcode).ok_or(Error::UnknownInstruction(opcode as u16))?;
+        let ty = self.lookup_type.lookup(type_id)?.handle;
+        let result_scalar = module.types[ty].inner.scalar();
+        let result_kind = result_scalar.map(|scalar| scalar.kind);
+
+        // `OpCompositeExtract` and `OpVectorShuffle` take literal operands
+        // after their ids.
+        let id_count = match op {
+            Op::CompositeExtract => 1,
+            Op::VectorShuffle => 2,
+            _ => words.len(),
+        };
+        if words.len() < id_count {
+            return Err(Error::InvalidOperand);
+        }
+        let (ids, literals) = words.split_at(id_count);
+
+        let mut is_override_expression = false;
+        let mut operands = Vec::with_capacity(ids.len());
+        for &operand_id in ids {
+            let constant = self.lookup_constant.lookup(operand_id)?;
+            is_override_expression |= constant.inner.is_override_expression();
+            let operand_ty = self.lookup_type.lookup(constant.type_id)?.handle;
+            let handle = constant
+                .inner
+                .to_global_expr(&mut module.global_expressions, span);
+            operands.push(Operand {
+                handle,
+                kind: module.types[operand_ty].inner.scalar_kind(),
+                size: match module.types[operand_ty].inner {
+                    crate::TypeInner::Vector { size, .. } => size as u32,
+                    _ => 1,
+                },
+            });
+        }
+
+        let mut tracker = ExpressionKindTracker::from_arena(&module.global_expressions);
+        let mut ctx = SpecConstantContext {
+            evaluator: ConstantEvaluator::for_wgsl_module(module, &mut tracker, true),
+            span,
+        };
+
+        let binary = |op| match op {
+            Op::IAdd => BinaryOperator::Add,
+            Op::ISub => BinaryOperator::Subtract,
+            Op::IMul => BinaryOperator::Multiply,
+            Op::UDiv | Op::SDiv => BinaryOperator::Divide,
+            Op::UMod | Op::SRem => BinaryOperator::Modulo,
+            Op::BitwiseOr => BinaryOperator::InclusiveOr,
+            Op::BitwiseXor => BinaryOperator::ExclusiveOr,
+            Op::BitwiseAnd => BinaryOperator::And,
+            Op::ShiftLeftLogical => BinaryOperator::ShiftLeft,
+            Op::ShiftRightLogical | Op::ShiftRightArithmetic => BinaryOperator::ShiftRight,
+            Op::LogicalOr => BinaryOperator::LogicalOr,
+            Op::LogicalAnd => BinaryOperator::LogicalAnd,
+            Op::LogicalEqual | Op::IEqual => BinaryOperator::Equal,
+            Op::LogicalNotEqual | Op::INotEqual => BinaryOperator::NotEqual,
+            Op::ULessThan | Op::SLessThan => BinaryOperator::Less,
+            Op::UGreaterThan | Op::SGreaterThan => BinaryOperator::Greater,
+            Op::ULessThanEqual | Op::SLessThanEqual => BinaryOperator::LessEqual,
+            // `OpUGreaterThanEqual` and `OpSGreaterThanEqual`.
+            _ => BinaryOperator::GreaterEqual,
+        };
+
+        let operand = |index: usize| operands.get(index).ok_or(Error::InvalidOperand);
+        let init = match op {
+            Op::SConvert | Op::UConvert | Op::FConvert => {
+                let value = operand(0)?;
+                let source_kind = match op {
+                    Op::SConvert => Some(ScalarKind::Sint),
+                    Op::UConvert => Some(ScalarKind::Uint),
+                    _ => value.kind,
+                };
+                let value = ctx.cast(value, source_kind)?;
+                let scalar = result_scalar.ok_or(Error::UnsupportedType(ty))?;
+                ctx.append(Expression::As {
+                    expr: value,
+                    kind: scalar.kind,
+                    convert: Some(scalar.width),
+                })?
+            }
+            Op::SNegate => {
+                let value = ctx.cast(operand(0)?, Some(ScalarKind::Sint))?;
+                let negated = ctx.append(Expression::Unary {
+                    op: UnaryOperator::Negate,
+                    expr: value,
+                })?;
+                ctx.cast_handle(negated, Some(ScalarKind::Sint), result_kind)?
+            }
+            Op::Not | Op::LogicalNot => {
+                let value = ctx.cast(operand(0)?, result_kind)?;
+                ctx.append(Expression::Unary {
+                    op: if op == Op::Not {
+                        UnaryOperator::BitwiseNot
+                    } else {
+                        UnaryOperator::LogicalNot
+                    },
+                    expr: value,
+                })?
+            }
+            Op::IAdd
+            | Op::ISub
+            | Op::IMul
+            | Op::BitwiseOr
+            | Op::BitwiseXor
+            | Op::BitwiseAnd
+            | Op::LogicalOr
+            | Op::LogicalAnd
+            | Op::LogicalEqual
+            | Op::LogicalNotEqual => {
+                let left = ctx.cast(operand(0)?, result_kind)?;
+                let right = ctx.cast(operand(1)?, result_kind)?;
+                ctx.append(Expression::Binary {
+                    op: binary(op),
+                    left,
+                    right,
+                })?
+            }
+            Op::UDiv | Op::UMod | Op::SDiv | Op::SRem => {
+                let kind = match op {
+                    Op::UDiv | Op::UMod => Some(ScalarKind::Uint),
+                    _ => Some(ScalarKind::Sint),
+                };
+                let left = ctx.cast(operand(0)?, kind)?;
+                let right = ctx.cast(operand(1)?, kind)?;
+                let result = ctx.append(Expression::Binary {
+                    op: binary(op),
+                    left,
+                    right,
+                })?;
+                ctx.cast_handle(result, kind, result_kind)?
+            }
+            Op::SMod => {
+                // The result takes the sign of the divisor:
+                // `((x % y) + y) % y`.
+                let kind = Some(ScalarKind::Sint);
+                let left = ctx.cast(operand(0)?, kind)?;
+                let right = ctx.cast(operand(1)?, kind)?;
+                let mut result = left;
+                for op in [BinaryOperator::Modulo, BinaryOperator::Add] {
+                    result = ctx.append(Expression::Binary {
+                        op,
+                        left: result,
+                        right,
+                    })?;
+                }
+                let result = ctx.append(Expression::Binary {
+                    op: BinaryOperator::Modulo,
+                    left: result,
+                    right,
+                })?;
+                ctx.cast_handle(result, kind, result_kind)?
+            }
+            Op::ShiftLeftLogical | Op::ShiftRightLogical | Op::ShiftRightArithmetic => {
+                // Naga's right shifts are arithmetic exactly when the
+                // operand is signed.
+                let kind = match op {
+                    Op::ShiftLeftLogical => result_kind,
+                    Op::ShiftRightLogical => Some(ScalarKind::Uint),
+                    _ => Some(ScalarKind::Sint),
+                };
+                let left = ctx.cast(operand(0)?, kind)?;
+                let right = ctx.cast(operand(1)?, Some(ScalarKind::Uint))?;
+                let result = ctx.append(Expression::Binary {
+                    op: binary(op),
+                    left,
+                    right,
+                })?;
+                ctx.cast_handle(result, kind, result_kind)?
+            }
+            Op::IEqual | Op::INotEqual => {
+                let left = operand(0)?;
+                let right = ctx.cast(operand(1)?, left.kind)?;
+                ctx.append(Expression::Binary {
+                    op: binary(op),
+                    left: left.handle,
+                    right,
+                })?
+            }
+            Op::ULessThan
+            | Op::UGreaterThan
+            | Op::ULessThanEqual
+            | Op::UGreaterThanEqual
+            | Op::SLessThan
+            | Op::SGreaterThan
+            | Op::SLessThanEqual
+            | Op::SGreaterThanEqual => {
+                let kind = match op {
+                    Op::ULessThan
+                    | Op::UGreaterThan
+                    | Op::ULessThanEqual
+                    | Op::UGreaterThanEqual => Some(ScalarKind::Uint),
+                    _ => Some(ScalarKind::Sint),
+                };
+                let left = ctx.cast(operand(0)?, kind)?;
+                let right = ctx.cast(operand(1)?, kind)?;
+                ctx.append(Expression::Binary {
+                    op: binary(op),
+                    left,
+                    right,
+                })?
+            }
+            Op::Select => ctx.append(Expression::Select {
+                condition: operand(0)?.handle,
+                accept: operand(1)?.handle,
+                reject: operand(2)?.handle,
+            })?,
+            Op::CompositeExtract => {
+                let mut result = operand(0)?.handle;
+                for &index in literals {
+                    result = ctx.append(Expression::AccessIndex {
+                        base: result,
+                        index,
+                    })?;
+                }
+                result
+            }
+            Op::VectorShuffle => {
+                let (first, second) = (operand(0)?, operand(1)?);
+                let mut components = Vec::with_capacity(literals.len());
+                for &index in literals {
+                    // An index of `0xFFFFFFFF` means the component is
+                    // undefined, so any component will do.
+                    let (base, index) = match index.checked_sub(first.size) {
+                        Some(index) if index < second.size => (second.handle, index),
+                        Some(_) => (first.handle, 0),
+                        None => (first.handle, index),
+                    };
+                    components.push(ctx.append(Expression::AccessIndex { base, index })?);
+                }
+                ctx.append(Expression::Compose { ty, components })?
+            }
+            _ => return Err(Error::UnsupportedSpecConstantOp(op)),
+        };
+
+        if is_override_expression {
+            self.insert_override_expression(id, type_id, init);
+            Ok(())
+        } else {
+            self.insert_parsed_constant(module, id, type_id, ty, init, span)
+        }
+    }
+
+    /// Return the workgroup size of `ep`, and the override-expressions
+    /// for the dimensions that depend on specialization constants.
+    ///
+    /// The `WorkgroupSize` built-in takes precedence over the `LocalSizeId`
+    /// execution mode, which takes precedence over `LocalSize`.
+    pub(super) fn workgroup_size(
+        &self,
+        module: &mut crate::Module,
+        ep: &super::EntryPoint,
+    ) -> Result<(WorkgroupSize, Option<WorkgroupSizeOverrides>), Error> {
+        let mut workgroup_size = ep.workgroup_size;
+        let components = match (self.workgroup_size_builtin, ep.workgroup_size_ids) {
+            (Some(builtin), _) => match module.global_expressions[builtin] {
+                Expression::Compose { ref components, .. } if components.len() == 3 => {
+                    [components[0], components[1], components[2]]
+                }
+                // Like `OpConstantNull`, which isn't a valid size anyway.
+                _ => return Ok((workgroup_size, None)),
+            },
+            (None, Some(ids)) => {
+                let mut components = Vec::with_capacity(ids.len());
+                for id in ids {
+                    let component = self
+                        .lookup_constant
+                        .lookup(id)?
+                        .inner
+                        .to_global_expr(&mut module.global_expressions, Default::default());
+                    components.push(component);
+                }
+                [components[0], components[1], components[2]]
+            }
+            (None, None) => return Ok((workgroup_size, None)),
+        };
+
+        let mut overrides = [None; 3];
+        for ((size, size_override), component) in workgroup_size
+            .iter_mut()
+            .zip(overrides.iter_mut())
+            .zip(components)
+        {
+            match module.to_ctx().eval_expr_to_u32(component) {
+                Ok(value) => *size = value,
+                Err(_) => *size_override = Some(component),
+            }
+        }
+        let overrides = overrides.iter().any(Option::is_some).then_some(overrides);
+        Ok((workgroup_size, overrides))
+    }
+
+    /// Copy the override-expressions behind all [`Constant::Expression`]
+    /// constants, and the expressions they use, into a function's
+    /// `expressions` arena, and register them in `lookup_expression`.
+    ///
+    /// Return the range of copies that need an [`Emit`] statement. Once
+    /// the function has been parsed, [`emit_override_expressions`] emits
+    /// the ones it actually uses.
+    ///
+    /// [`Emit`]: crate::Statement::Emit
+    pub(super) fn copy_override_expressions(
+        &mut self,
+        global_expressions: &Arena<Expression>,
+        expressions: &mut Arena<Expression>,
+    ) -> Range<Expression> {
+        let roots: Vec<_> = self
+            .lookup_constant
+            .iter()
+            .filter_map(|(&id, constant)| match constant.inner {
+                Constant::Expression(expr) => Some((id, constant.type_id, expr)),
+                Constant::Constant(_) | Constant::Override(_) => None,
+            })
+            .collect();
+
+        // Find all the global expressions the roots use. Since operands
+        // always precede their users in the arena, visiting them in handle
+        // order visits operands first.
+        let mut used = HandleSet::for_arena(global_expressions);
+        let mut stack: Vec<_> = roots.iter().map(|&(_, _, expr)| expr).collect();
+        while let Some(expr) = stack.pop() {
+            if used.insert(expr) {
+                map_operands(&global_expressions[expr], |operand| {
+                    stack.push(operand);
+                    operand
+                });
+            }
+        }
+
+        // Expressions that need no `Emit` statement must not be covered by
+        // one, so copy those first.
+        let mut copies = FastHashMap::default();
+        let mut start = 0;
+        for pre_emit in [true, false] {
+            if !pre_emit {
+                start = expressions.len();
+            }
+            for expr in used.iter() {
+                let global = &global_expressions[expr];
+                if global.needs_pre_emit() != pre_emit {
+                    continue;
+                }
+                let copy = map_operands(global, |operand| copies[&operand]);
+                let span = global_expressions.get_span(expr);
+                copies.insert(expr, expressions.append(copy, span));
+            }
+        }
+
+        for (id, type_id, expr) in roots {
+            self.lookup_expression.insert(
+                id,
+                LookupExpression {
+                    handle: copies[&expr],
+                    type_id,
+                    // Like other constants, these are available throughout
+                    // the function.
+                    block_id: 0,
+                },
+            );
+        }
+
+        expressions.range_from(start)
+    }
+}
+
+/// Return [`Emit`] statements for the copies in `copies` that `function`
+/// uses, directly or through other copies.
+///
+/// `copies` is the range [`Frontend::copy_override_expressions`] returned
+/// for `function`, and `body` is the function's body. Copies the function
+/// doesn't use are left unemitted, like the [`Constant`] expressions of
+/// constants it doesn't use.
+///
+/// [`Emit`]: crate::Statement::Emit
+/// [`Frontend::copy_override_expressions`]: super::Frontend::copy_override_expressions
+/// [`Constant`]: crate::Expression::Constant
+pub(super) fn emit_override_expressions(
+    copies: Range<Expression>,
+    function: &crate::Function,
+    body: &Block,
+) -> Block {
+    let mut block = Block::new();
+    let copies: Vec<_> = copies.collect();
+    let (Some(&first), Some(&last)) = (copies.first(), copies.last()) else {
+        return block;
+    };
+    let is_copy = |expr: Handle<Expression>| (first.index()..=last.index()).contains(&expr.index());
+
+    let mut used = HandleSet::for_arena(&function.expressions);
+    for (handle, expr) in function.expressions.iter() {
+        if !is_copy(handle) {
+            for_each_expression_operand(expr, |operand| {
+                used.insert(operand);
+            });
+        }
+    }
+    used.insert_iter(
+        function
+            .local_variables
+            .iter()
+            .filter_map(|(_, var)| var.init),
+    );
+    mark_statement_operands(body, &mut used);
+    for &copy in copies.iter().rev() {
+        if used.contains(copy) {
+            for_each_expression_operand(&function.expressions[copy], |operand| {
+                used.insert(operand);
+            });
+        }
+    }
+
+    // `Emit` ranges are contiguous, so each run of used copies gets its own.
+    let mut run: Option<(Handle<Expression>, Handle<Expression>)> = None;
+    for copy in copies {
+        if used.contains(copy) {
+            run = Some((run.map_or(copy, |(start, _)| start), copy));
+        } else if let Some((start, end)) = run.take() {
+            push_emit(&mut block, &function.expressions, start, end);
+        }
+    }
+    if let Some((start, end)) = run {
+        push_emit(&mut block, &function.expressions, start, end);
+    }
+    block
+}
+
+/// Add every expression handle the statements in `block` use as operands,
+/// including those in nested blocks, to `used`.
+fn mark_statement_operands(block: &Block, used: &mut HandleSet<Expression>) {
+    for statement in block.iter() {
+        match *statement {
+            crate::Statement::Block(ref block) => mark_statement_operands(block, used),
+            crate::Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                mark_statement_operands(accept, used);
+                mark_statement_operands(reject, used);
+            }
+            crate::Statement::Switch { ref cases, .. } => {
+                for case in cases.iter() {
+                    mark_statement_operands(&case.body, used);
+                }
+            }
+            crate::Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                mark_statement_operands(body, used);
+                mark_statement_operands(continuing, used);
+            }
+            _ => {}
+        }
+        for_each_statement_operand(statement, |operand| {
+            used.insert(operand);
+        });
+    }
+}
+
+fn push_emit(
+    block: &mut Block,
+    expressions: &Arena<Expression>,
+    start: Handle<Expression>,
+    end: Handle<Expression>,
+) {
+    let range = Range::new_from_bounds(start, end);
+    let mut span = crate::Span::default();
+    for handle in range.clone() {
+        span.subsume(expressions.get_span(handle));
+    }
+    block.push(crate::Statement::Emit(range), span);
+}
+
+type WorkgroupSize = [u32; 3];
+
+/// See [`EntryPoint::workgroup_size_overrides`].
+///
+/// [`EntryPoint::workgroup_size_overrides`]: crate::EntryPoint::workgroup_size_overrides
+type WorkgroupSizeOverrides = [Option<Handle<Expression>>; 3];
+
+/// An operand of `OpSpecConstantOp`.
+struct Operand {
+    handle: Handle<Expression>,
+    kind: Option<ScalarKind>,
+    /// The number of components, for `OpVectorShuffle`.
+    size: u32,
+}
+
+struct SpecConstantContext<'a> {
+    evaluator: ConstantEvaluator<'a>,
+    span: crate::Span,
+}
+
+impl SpecConstantContext<'_> {
+    fn append(&mut self, expr: Expression) -> Result<Handle<Expression>, Error> {
+        self.evaluator
+            .try_eval_and_append(expr, self.span)
+            .map_err(Error::InvalidSpecConstantOp)
+    }
+
+    /// Reinterpret `operand` as a value of scalar kind `kind`, if it isn't
+    /// one already.
+    fn cast(
+        &mut self,
+        operand: &Operand,
+        kind: Option<ScalarKind>,
+    ) -> Result<Handle<Expression>, Error> {
+        self.cast_handle(operand.handle, operand.kind, kind)
+    }
+
+    fn cast_handle(
+        &mut self,
+        expr: Handle<Expression>,
+        from: Option<ScalarKind>,
+        to: Option<ScalarKind>,
+    ) -> Result<Handle<Expression>, Error> {
+        match to {
+            Some(kind) if from != to => self.append(Expression::As {
+                expr,
+                kind,
+                convert: None,
+            }),
+            _ => Ok(expr),
+        }
+    }
+}
+
+/// Return a copy of `expr` with `f` applied to each of its operands.
+///
+/// This only supports the expressions that can appear in the
+/// override-expressions we build for specialization constants.
+fn map_operands(
+    expr: &Expression,
+    mut f: impl FnMut(Handle<Expression>) -> Handle<Expression>,
+) -> Expression {
+    match *expr {
+        Expression::Compose { ty, ref components } => Expression::Compose {
+            ty,
+            components: components.iter().map(|&component| f(component)).collect(),
+        },
+        Expression::Splat { size, value } => Expression::Splat {
+            size,
+            value: f(value),
+        },
+        Expression::Swizzle {
+            size,
+            vector,
+            pattern,
+        } => Expression::Swizzle {
+            size,
+            vector: f(vector),
+            pattern,
+        },
+        Expression::AccessIndex { base, index } => Expression::AccessIndex {
+            base: f(base),
+            index,
+        },
+        Expression::Access { base, index } => Expression::Access {
+            base: f(base),
+            index: f(index),
+        },
+        Expression::Unary { op, expr } => Expression::Unary { op, expr: f(expr) },
+        Expression::Binary { op, left, right } => Expression::Binary {
+            op,
+            left: f(left),
+            right: f(right),
+        },
+        Expression::Select {
+            condition,
+            accept,
+            reject,
+        } => Expression::Select {
+            condition: f(condition),
+            accept: f(accept),
+            reject: f(reject),
+        },
+        Expression::As {
+            expr,
+            kind,
+            convert,
+        } => Expression::As {
+            expr: f(expr),
+            kind,
+            convert,
+        },
+        ref other => other.clone(),
+    }
+}
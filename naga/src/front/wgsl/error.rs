@@ -1,4 +1,4 @@
-use crate::diagnostic_filter::ConflictingDiagnosticRuleError;
+use crate::diagnostic_filter::{ConflictingDiagnosticRuleError, Severity};
 use crate::front::wgsl::parse::directive::enable_extension::{
     EnableExtension, UnimplementedEnableExtension,
 };
@@ -6,11 +6,11 @@ use crate::front::wgsl::parse::directive::language_extension::{
     LanguageExtension, UnimplementedLanguageExtension,
 };
 use crate::front::wgsl::parse::lexer::Token;
-use crate::front::wgsl::Scalar;
+use crate::front::wgsl::{Linker, Scalar};
 use crate::proc::{Alignment, ConstantEvaluatorError, ResolveError};
 use crate::{SourceLocation, Span};
 use codespan_reporting::diagnostic::{Diagnostic, Label};
-use codespan_reporting::files::SimpleFile;
+use codespan_reporting::files::{SimpleFile, SimpleFiles};
 use codespan_reporting::term;
 use std::borrow::Cow;
 use std::ops::Range;
@@ -26,6 +26,14 @@ pub struct ParseError {
     // The first span should be the primary span, and the other ones should be complementary.
     labels: Vec<(Span, Cow<'static, str>)>,
     notes: Vec<String>,
+    code: &'static str,
+}
+
+/// The parts of a [`ParseError`] that [`Error::describe`] produces.
+struct Description {
+    message: String,
+    labels: Vec<(Span, Cow<'static, str>)>,
+    notes: Vec<String>,
 }
 
 impl ParseError {
@@ -39,15 +47,36 @@ impl ParseError {
         &self.message
     }
 
+    pub fn notes(&self) -> impl ExactSizeIterator<Item = &str> + '_ {
+        self.notes.iter().map(String::as_str)
+    }
+
+    /// Returns a short identifier for the kind of error, like `unknown-ident`.
+    ///
+    /// Unlike the message, this does not depend on the source code, and does
+    /// not change between releases, so tools can use it to recognize errors.
+    pub const fn code(&self) -> &'static str {
+        self.code
+    }
+
     fn diagnostic(&self) -> Diagnostic<()> {
+        self.diagnostic_with(|span| span.to_range().map(|range| ((), range)))
+    }
+
+    /// Builds the diagnostic, using `locate` to find the file and range
+    /// each label's span refers to.
+    fn diagnostic_with<FileId>(
+        &self,
+        locate: impl Fn(Span) -> Option<(FileId, Range<usize>)>,
+    ) -> Diagnostic<FileId> {
         let diagnostic = Diagnostic::error()
             .with_message(self.message.to_string())
             .with_labels(
                 self.labels
                     .iter()
-                    .filter_map(|label| label.0.to_range().map(|range| (label, range)))
-                    .map(|(label, range)| {
-                        Label::primary((), range).with_message(label.1.to_string())
+                    .filter_map(|label| locate(label.0).map(|location| (label, location)))
+                    .map(|(label, (file, range))| {
+                        Label::primary(file, range).with_message(label.1.to_string())
                     })
                     .collect(),
             )
@@ -96,10 +125,42 @@ impl ParseError {
         String::from_utf8(writer.into_inner()).unwrap()
     }
 
+    /// Emits a summary of an error returned by [`Linker::link`] to a string,
+    /// pointing into the modules the error came from.
+    pub fn emit_to_string_with_linker(&self, linker: &Linker) -> String {
+        let mut files = SimpleFiles::new();
+        for index in 0..linker.module_count() {
+            let (name, source) = linker.module(index).unwrap();
+            files.add(name, source);
+        }
+        let diagnostic = self.diagnostic_with(|span| linker.locate(span));
+        let config = term::Config::default();
+        let mut writer = NoColor::new(Vec::new());
+        term::emit(&mut writer, &config, &files, &diagnostic).expect("cannot write error");
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+
     /// Returns a [`SourceLocation`] for the first label in the error message.
     pub fn location(&self, source: &str) -> Option<SourceLocation> {
         self.labels.first().map(|label| label.0.location(source))
     }
+
+    /// Turns the error into a non-fatal diagnostic at `severity`.
+    pub(crate) fn into_diagnostic(
+        self,
+        severity: Severity,
+    ) -> crate::diagnostic_filter::Diagnostic {
+        crate::diagnostic_filter::Diagnostic {
+            severity,
+            message: self.message,
+            labels: self
+                .labels
+                .into_iter()
+                .map(|(span, msg)| (span, msg.into_owned()))
+                .collect(),
+            notes: self.notes,
+        }
+    }
 }
 
 impl std::fmt::Display for ParseError {
@@ -146,8 +207,6 @@ pub enum NumberError {
     Invalid,
     #[error("numeric literal not representable by target type")]
     NotRepresentable,
-    #[error("unimplemented f16 type")]
-    UnimplementedF16,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -197,10 +256,19 @@ pub(crate) enum Error<'a> {
     UnknownEnableExtension(Span, &'a str),
     UnknownLanguageExtension(Span, &'a str),
     UnknownDiagnosticRuleName(Span),
+    UnusedLocal {
+        kind: &'static str,
+        name: Span,
+    },
+    UnreachableCode {
+        statement: Span,
+        terminator: Span,
+    },
     SizeAttributeTooLow(Span, u32),
     AlignAttributeTooLow(Span, Alignment),
     NonPowerOfTwoAlignAttribute(Span),
     InconsistentBinding(Span),
+    InvalidBlendSrc(Span),
     TypeNotConstructible(Span),
     TypeNotInferable(Span),
     InitializationTypeMismatch {
@@ -213,7 +281,6 @@ pub(crate) enum Error<'a> {
     InvalidAtomicPointer(Span),
     InvalidAtomicOperandType(Span),
     InvalidRayQueryPointer(Span),
-    Pointer(&'static str, Span),
     NotPointer(Span),
     NotReference(&'static str, Span),
     InvalidAssignment {
@@ -352,9 +419,118 @@ pub(crate) struct ConcretizationFailedError {
 }
 
 impl<'a> Error<'a> {
+    /// Return the stable identifier for this kind of error.
+    ///
+    /// See [`ParseError::code`].
+    const fn code(&self) -> &'static str {
+        match *self {
+            Error::Unexpected(..) => "unexpected-token",
+            Error::UnexpectedComponents(..) => "unexpected-components",
+            Error::UnexpectedOperationInConstContext(..) => "unexpected-operation-in-const-context",
+            Error::BadNumber(..) => "bad-number",
+            Error::BadMatrixScalarKind(..) => "bad-matrix-scalar-kind",
+            Error::BadAccessor(..) => "bad-accessor",
+            Error::BadTexture(..) => "bad-texture",
+            Error::BadTypeCast { .. } => "bad-type-cast",
+            Error::BadTextureSampleType { .. } => "bad-texture-sample-type",
+            Error::BadIncrDecrReferenceType(..) => "bad-incr-decr-reference-type",
+            Error::InvalidResolve(..) => "invalid-resolve",
+            Error::InvalidForInitializer(..) => "invalid-for-initializer",
+            Error::InvalidBreakIf(..) => "invalid-break-if",
+            Error::InvalidGatherComponent(..) => "invalid-gather-component",
+            Error::InvalidConstructorComponentType(..) => "invalid-constructor-component-type",
+            Error::InvalidIdentifierUnderscore(..) => "invalid-identifier-underscore",
+            Error::ReservedIdentifierPrefix(..) => "reserved-identifier-prefix",
+            Error::UnknownAddressSpace(..) => "unknown-address-space",
+            Error::RepeatedAttribute(..) => "repeated-attribute",
+            Error::UnknownAttribute(..) => "unknown-attribute",
+            Error::UnknownBuiltin(..) => "unknown-builtin",
+            Error::UnknownAccess(..) => "unknown-access",
+            Error::UnknownIdent(..) => "unknown-ident",
+            Error::UnknownScalarType(..) => "unknown-scalar-type",
+            Error::UnknownType(..) => "unknown-type",
+            Error::UnknownStorageFormat(..) => "unknown-storage-format",
+            Error::UnknownConservativeDepth(..) => "unknown-conservative-depth",
+            Error::UnknownEnableExtension(..) => "unknown-enable-extension",
+            Error::UnknownLanguageExtension(..) => "unknown-language-extension",
+            Error::UnknownDiagnosticRuleName(..) => "unknown-diagnostic-rule-name",
+            Error::UnusedLocal { .. } => "unused-local",
+            Error::UnreachableCode { .. } => "unreachable-code",
+            Error::SizeAttributeTooLow(..) => "size-attribute-too-low",
+            Error::AlignAttributeTooLow(..) => "align-attribute-too-low",
+            Error::NonPowerOfTwoAlignAttribute(..) => "non-power-of-two-align-attribute",
+            Error::InconsistentBinding(..) => "inconsistent-binding",
+            Error::InvalidBlendSrc(..) => "invalid-blend-src",
+            Error::TypeNotConstructible(..) => "type-not-constructible",
+            Error::TypeNotInferable(..) => "type-not-inferable",
+            Error::InitializationTypeMismatch { .. } => "initialization-type-mismatch",
+            Error::DeclMissingTypeAndInit(..) => "decl-missing-type-and-init",
+            Error::MissingAttribute(..) => "missing-attribute",
+            Error::InvalidAtomicPointer(..) => "invalid-atomic-pointer",
+            Error::InvalidAtomicOperandType(..) => "invalid-atomic-operand-type",
+            Error::InvalidRayQueryPointer(..) => "invalid-ray-query-pointer",
+            Error::NotPointer(..) => "not-pointer",
+            Error::NotReference(..) => "not-reference",
+            Error::InvalidAssignment { .. } => "invalid-assignment",
+            Error::ReservedKeyword(..) => "reserved-keyword",
+            Error::Redefinition { .. } => "redefinition",
+            Error::RecursiveDeclaration { .. } => "recursive-declaration",
+            Error::CyclicDeclaration { .. } => "cyclic-declaration",
+            Error::InvalidSwitchValue { .. } => "invalid-switch-value",
+            Error::CalledEntryPoint(..) => "called-entry-point",
+            Error::WrongArgumentCount { .. } => "wrong-argument-count",
+            Error::FunctionReturnsVoid(..) => "function-returns-void",
+            Error::InvalidWorkGroupUniformLoad(..) => "invalid-work-group-uniform-load",
+            Error::Internal(..) => "internal",
+            Error::ExpectedConstExprConcreteIntegerScalar(..) => {
+                "expected-const-expr-concrete-integer-scalar"
+            }
+            Error::ExpectedNonNegative(..) => "expected-non-negative",
+            Error::ExpectedPositiveArrayLength(..) => "expected-positive-array-length",
+            Error::MissingWorkgroupSize(..) => "missing-workgroup-size",
+            Error::ConstantEvaluatorError(..) => "constant-evaluator-error",
+            Error::AutoConversion(..) => "auto-conversion",
+            Error::AutoConversionLeafScalar(..) => "auto-conversion-leaf-scalar",
+            Error::ConcretizationFailed(..) => "concretization-failed",
+            Error::ExceededLimitForNestedBraces { .. } => "exceeded-limit-for-nested-braces",
+            Error::PipelineConstantIDValue(..) => "pipeline-constant-id-value",
+            Error::NotBool(..) => "not-bool",
+            Error::ConstAssertFailed(..) => "const-assert-failed",
+            Error::DirectiveAfterFirstGlobalDecl { .. } => "directive-after-first-global-decl",
+            Error::EnableExtensionNotYetImplemented { .. } => {
+                "enable-extension-not-yet-implemented"
+            }
+            Error::EnableExtensionNotEnabled { .. } => "enable-extension-not-enabled",
+            Error::LanguageExtensionNotYetImplemented { .. } => {
+                "language-extension-not-yet-implemented"
+            }
+            Error::DiagnosticInvalidSeverity { .. } => "diagnostic-invalid-severity",
+            Error::DiagnosticDuplicateTriggeringRule(..) => "diagnostic-duplicate-triggering-rule",
+            Error::DiagnosticAttributeNotYetImplementedAtParseSite { .. } => {
+                "diagnostic-attribute-not-yet-implemented-at-parse-site"
+            }
+            Error::DiagnosticAttributeNotSupported { .. } => "diagnostic-attribute-not-supported",
+        }
+    }
+
     #[cold]
     #[inline(never)]
     pub(crate) fn as_parse_error(&self, source: &'a str) -> ParseError {
+        let Description {
+            message,
+            labels,
+            notes,
+        } = self.describe(source);
+        ParseError {
+            message,
+            labels,
+            notes,
+            code: self.code(),
+        }
+    }
+
+    /// Describe this error, in terms of the `source` it was found in.
+    fn describe(&self, source: &'a str) -> Description {
         match *self {
             Error::Unexpected(unexpected_span, expected) => {
                 let expected_str = match expected {
@@ -407,7 +583,7 @@ impl<'a> Error<'a> {
                         "the `diagnostic` attribute identifier".to_string()
                     }
                 };
-                ParseError {
+                Description {
                     message: format!(
                         "expected {}, found {:?}",
                         expected_str, &source[unexpected_span],
@@ -416,22 +592,22 @@ impl<'a> Error<'a> {
                     notes: vec![],
                 }
             }
-            Error::UnexpectedComponents(bad_span) => ParseError {
+            Error::UnexpectedComponents(bad_span) => Description {
                 message: "unexpected components".to_string(),
                 labels: vec![(bad_span, "unexpected components".into())],
                 notes: vec![],
             },
-            Error::UnexpectedOperationInConstContext(span) => ParseError {
+            Error::UnexpectedOperationInConstContext(span) => Description {
                 message: "this operation is not supported in a const context".to_string(),
                 labels: vec![(span, "operation not supported here".into())],
                 notes: vec![],
             },
-            Error::BadNumber(bad_span, ref err) => ParseError {
+            Error::BadNumber(bad_span, ref err) => Description {
                 message: format!("{}: `{}`", err, &source[bad_span],),
                 labels: vec![(bad_span, err.to_string().into())],
                 notes: vec![],
             },
-            Error::BadMatrixScalarKind(span, scalar) => ParseError {
+            Error::BadMatrixScalarKind(span, scalar) => Description {
                 message: format!(
                     "matrix scalar type must be floating-point, but found `{}`",
                     scalar.to_wgsl()
@@ -439,22 +615,22 @@ impl<'a> Error<'a> {
                 labels: vec![(span, "must be floating-point (e.g. `f32`)".into())],
                 notes: vec![],
             },
-            Error::BadAccessor(accessor_span) => ParseError {
+            Error::BadAccessor(accessor_span) => Description {
                 message: format!("invalid field accessor `{}`", &source[accessor_span],),
                 labels: vec![(accessor_span, "invalid accessor".into())],
                 notes: vec![],
             },
-            Error::UnknownIdent(ident_span, ident) => ParseError {
+            Error::UnknownIdent(ident_span, ident) => Description {
                 message: format!("no definition in scope for identifier: `{ident}`"),
                 labels: vec![(ident_span, "unknown identifier".into())],
                 notes: vec![],
             },
-            Error::UnknownScalarType(bad_span) => ParseError {
+            Error::UnknownScalarType(bad_span) => Description {
                 message: format!("unknown scalar type: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown scalar type".into())],
                 notes: vec!["Valid scalar types are f32, f64, i32, u32, bool".into()],
             },
-            Error::BadTextureSampleType { span, scalar } => ParseError {
+            Error::BadTextureSampleType { span, scalar } => Description {
                 message: format!(
                     "texture sample type must be one of f32, i32 or u32, but found {}",
                     scalar.to_wgsl()
@@ -462,7 +638,7 @@ impl<'a> Error<'a> {
                 labels: vec![(span, "must be one of f32, i32 or u32".into())],
                 notes: vec![],
             },
-            Error::BadIncrDecrReferenceType(span) => ParseError {
+            Error::BadIncrDecrReferenceType(span) => Description {
                 message: concat!(
                     "increment/decrement operation requires ",
                     "reference type to be one of i32 or u32"
@@ -471,7 +647,7 @@ impl<'a> Error<'a> {
                 labels: vec![(span, "must be a reference type of i32 or u32".into())],
                 notes: vec![],
             },
-            Error::BadTexture(bad_span) => ParseError {
+            Error::BadTexture(bad_span) => Description {
                 message: format!(
                     "expected an image, but found `{}` which is not an image",
                     &source[bad_span]
@@ -485,18 +661,18 @@ impl<'a> Error<'a> {
                 ref to_type,
             } => {
                 let msg = format!("cannot cast a {from_type} to a {to_type}");
-                ParseError {
+                Description {
                     message: msg.clone(),
                     labels: vec![(span, msg.into())],
                     notes: vec![],
                 }
             }
-            Error::InvalidResolve(ref resolve_error) => ParseError {
+            Error::InvalidResolve(ref resolve_error) => Description {
                 message: resolve_error.to_string(),
                 labels: vec![],
                 notes: vec![],
             },
-            Error::InvalidForInitializer(bad_span) => ParseError {
+            Error::InvalidForInitializer(bad_span) => Description {
                 message: format!(
                     "for(;;) initializer is not an assignment or a function call: `{}`",
                     &source[bad_span]
@@ -504,12 +680,12 @@ impl<'a> Error<'a> {
                 labels: vec![(bad_span, "not an assignment or function call".into())],
                 notes: vec![],
             },
-            Error::InvalidBreakIf(bad_span) => ParseError {
+            Error::InvalidBreakIf(bad_span) => Description {
                 message: "A break if is only allowed in a continuing block".to_string(),
                 labels: vec![(bad_span, "not in a continuing block".into())],
                 notes: vec![],
             },
-            Error::InvalidGatherComponent(bad_span) => ParseError {
+            Error::InvalidGatherComponent(bad_span) => Description {
                 message: format!(
                     "textureGather component `{}` doesn't exist, must be 0, 1, 2, or 3",
                     &source[bad_span]
@@ -517,12 +693,12 @@ impl<'a> Error<'a> {
                 labels: vec![(bad_span, "invalid component".into())],
                 notes: vec![],
             },
-            Error::InvalidConstructorComponentType(bad_span, component) => ParseError {
+            Error::InvalidConstructorComponentType(bad_span, component) => Description {
                 message: format!("invalid type for constructor component at index [{component}]"),
                 labels: vec![(bad_span, "invalid component type".into())],
                 notes: vec![],
             },
-            Error::InvalidIdentifierUnderscore(bad_span) => ParseError {
+            Error::InvalidIdentifierUnderscore(bad_span) => Description {
                 message: "Identifier can't be `_`".to_string(),
                 labels: vec![(bad_span, "invalid identifier".into())],
                 notes: vec![
@@ -530,7 +706,7 @@ impl<'a> Error<'a> {
                         .to_string(),
                 ],
             },
-            Error::ReservedIdentifierPrefix(bad_span) => ParseError {
+            Error::ReservedIdentifierPrefix(bad_span) => Description {
                 message: format!(
                     "Identifier starts with a reserved prefix: `{}`",
                     &source[bad_span]
@@ -538,47 +714,47 @@ impl<'a> Error<'a> {
                 labels: vec![(bad_span, "invalid identifier".into())],
                 notes: vec![],
             },
-            Error::UnknownAddressSpace(bad_span) => ParseError {
+            Error::UnknownAddressSpace(bad_span) => Description {
                 message: format!("unknown address space: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown address space".into())],
                 notes: vec![],
             },
-            Error::RepeatedAttribute(bad_span) => ParseError {
+            Error::RepeatedAttribute(bad_span) => Description {
                 message: format!("repeated attribute: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "repeated attribute".into())],
                 notes: vec![],
             },
-            Error::UnknownAttribute(bad_span) => ParseError {
+            Error::UnknownAttribute(bad_span) => Description {
                 message: format!("unknown attribute: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown attribute".into())],
                 notes: vec![],
             },
-            Error::UnknownBuiltin(bad_span) => ParseError {
+            Error::UnknownBuiltin(bad_span) => Description {
                 message: format!("unknown builtin: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown builtin".into())],
                 notes: vec![],
             },
-            Error::UnknownAccess(bad_span) => ParseError {
+            Error::UnknownAccess(bad_span) => Description {
                 message: format!("unknown access: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown access".into())],
                 notes: vec![],
             },
-            Error::UnknownStorageFormat(bad_span) => ParseError {
+            Error::UnknownStorageFormat(bad_span) => Description {
                 message: format!("unknown storage format: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown storage format".into())],
                 notes: vec![],
             },
-            Error::UnknownConservativeDepth(bad_span) => ParseError {
+            Error::UnknownConservativeDepth(bad_span) => Description {
                 message: format!("unknown conservative depth: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown conservative depth".into())],
                 notes: vec![],
             },
-            Error::UnknownType(bad_span) => ParseError {
+            Error::UnknownType(bad_span) => Description {
                 message: format!("unknown type: `{}`", &source[bad_span]),
                 labels: vec![(bad_span, "unknown type".into())],
                 notes: vec![],
             },
-            Error::UnknownEnableExtension(span, word) => ParseError {
+            Error::UnknownEnableExtension(span, word) => Description {
                 message: format!("unknown enable-extension `{}`", word),
                 labels: vec![(span, "".into())],
                 notes: vec![
@@ -586,7 +762,7 @@ impl<'a> Error<'a> {
                         .into(),
                 ],
             },
-            Error::UnknownLanguageExtension(span, name) => ParseError {
+            Error::UnknownLanguageExtension(span, name) => Description {
                 message: format!("unknown language extension `{name}`"),
                 labels: vec![(span, "".into())],
                 notes: vec![concat!(
@@ -595,7 +771,29 @@ impl<'a> Error<'a> {
                 )
                 .into()],
             },
-            Error::UnknownDiagnosticRuleName(span) => ParseError {
+            Error::UnusedLocal { kind, name } => Description {
+                message: format!("unused {kind} `{}`", &source[name]),
+                labels: vec![(name, "declared here".into())],
+                notes: vec![format!(
+                    "if this is intentional, prefix it with an underscore: `_{}`",
+                    &source[name]
+                )],
+            },
+            Error::UnreachableCode {
+                statement,
+                terminator,
+            } => Description {
+                message: "unreachable code".to_string(),
+                labels: vec![
+                    (statement, "unreachable statement".into()),
+                    (
+                        terminator,
+                        "any code following this statement is unreachable".into(),
+                    ),
+                ],
+                notes: vec![],
+            },
+            Error::UnknownDiagnosticRuleName(span) => Description {
                 message: format!("unknown `diagnostic(…)` rule name `{}`", &source[span]),
                 labels: vec![(span, "not a valid diagnostic rule name".into())],
                 notes: vec![concat!(
@@ -604,32 +802,37 @@ impl<'a> Error<'a> {
                 )
                 .into()],
             },
-            Error::SizeAttributeTooLow(bad_span, min_size) => ParseError {
+            Error::SizeAttributeTooLow(bad_span, min_size) => Description {
                 message: format!("struct member size must be at least {min_size}"),
                 labels: vec![(bad_span, format!("must be at least {min_size}").into())],
                 notes: vec![],
             },
-            Error::AlignAttributeTooLow(bad_span, min_align) => ParseError {
+            Error::AlignAttributeTooLow(bad_span, min_align) => Description {
                 message: format!("struct member alignment must be at least {min_align}"),
                 labels: vec![(bad_span, format!("must be at least {min_align}").into())],
                 notes: vec![],
             },
-            Error::NonPowerOfTwoAlignAttribute(bad_span) => ParseError {
+            Error::NonPowerOfTwoAlignAttribute(bad_span) => Description {
                 message: "struct member alignment must be a power of 2".to_string(),
                 labels: vec![(bad_span, "must be a power of 2".into())],
                 notes: vec![],
             },
-            Error::InconsistentBinding(span) => ParseError {
+            Error::InconsistentBinding(span) => Description {
                 message: "input/output binding is not consistent".to_string(),
                 labels: vec![(span, "input/output binding is not consistent".into())],
                 notes: vec![],
             },
-            Error::TypeNotConstructible(span) => ParseError {
+            Error::InvalidBlendSrc(span) => Description {
+                message: "`blend_src` must be either 0 or 1".to_string(),
+                labels: vec![(span, "must be either 0 or 1".into())],
+                notes: vec![],
+            },
+            Error::TypeNotConstructible(span) => Description {
                 message: format!("type `{}` is not constructible", &source[span]),
                 labels: vec![(span, "type is not constructible".into())],
                 notes: vec![],
             },
-            Error::TypeNotInferable(span) => ParseError {
+            Error::TypeNotInferable(span) => Description {
                 message: "type can't be inferred".to_string(),
                 labels: vec![(span, "type can't be inferred".into())],
                 notes: vec![],
@@ -638,7 +841,7 @@ impl<'a> Error<'a> {
                 name,
                 ref expected,
                 ref got,
-            } => ParseError {
+            } => Description {
                 message: format!(
                     "the type of `{}` is expected to be `{}`, but got `{}`",
                     &source[name], expected, got,
@@ -646,7 +849,7 @@ impl<'a> Error<'a> {
                 labels: vec![(name, format!("definition of `{}`", &source[name]).into())],
                 notes: vec![],
             },
-            Error::DeclMissingTypeAndInit(name_span) => ParseError {
+            Error::DeclMissingTypeAndInit(name_span) => Description {
                 message: format!(
                     "declaration of `{}` needs a type specifier or initializer",
                     &source[name_span]
@@ -654,7 +857,7 @@ impl<'a> Error<'a> {
                 labels: vec![(name_span, "needs a type specifier or initializer".into())],
                 notes: vec![],
             },
-            Error::MissingAttribute(name, name_span) => ParseError {
+            Error::MissingAttribute(name, name_span) => Description {
                 message: format!(
                     "variable `{}` needs a '{}' attribute",
                     &source[name_span], name
@@ -665,27 +868,27 @@ impl<'a> Error<'a> {
                 )],
                 notes: vec![],
             },
-            Error::InvalidAtomicPointer(span) => ParseError {
+            Error::InvalidAtomicPointer(span) => Description {
                 message: "atomic operation is done on a pointer to a non-atomic".to_string(),
                 labels: vec![(span, "atomic pointer is invalid".into())],
                 notes: vec![],
             },
-            Error::InvalidAtomicOperandType(span) => ParseError {
+            Error::InvalidAtomicOperandType(span) => Description {
                 message: "atomic operand type is inconsistent with the operation".to_string(),
                 labels: vec![(span, "atomic operand type is invalid".into())],
                 notes: vec![],
             },
-            Error::InvalidRayQueryPointer(span) => ParseError {
+            Error::InvalidRayQueryPointer(span) => Description {
                 message: "ray query operation is done on a pointer to a non-ray-query".to_string(),
                 labels: vec![(span, "ray query pointer is invalid".into())],
                 notes: vec![],
             },
-            Error::NotPointer(span) => ParseError {
+            Error::NotPointer(span) => Description {
                 message: "the operand of the `*` operator must be a pointer".to_string(),
                 labels: vec![(span, "expression is not a pointer".into())],
                 notes: vec![],
             },
-            Error::NotReference(what, span) => ParseError {
+            Error::NotReference(what, span) => Description {
                 message: format!("{what} must be a reference"),
                 labels: vec![(span, "expression is not a reference".into())],
                 notes: vec![],
@@ -709,7 +912,7 @@ impl<'a> Error<'a> {
                     InvalidAssignmentType::Other => (None, vec![]),
                 };
 
-                ParseError {
+                Description {
                     message: "invalid left-hand side of assignment".into(),
                     labels: std::iter::once((span, "cannot assign to this expression".into()))
                         .chain(extra_label)
@@ -717,12 +920,7 @@ impl<'a> Error<'a> {
                     notes,
                 }
             }
-            Error::Pointer(what, span) => ParseError {
-                message: format!("{what} must not be a pointer"),
-                labels: vec![(span, "expression is a pointer".into())],
-                notes: vec![],
-            },
-            Error::ReservedKeyword(name_span) => ParseError {
+            Error::ReservedKeyword(name_span) => Description {
                 message: format!("name `{}` is a reserved keyword", &source[name_span]),
                 labels: vec![(
                     name_span,
@@ -730,7 +928,7 @@ impl<'a> Error<'a> {
                 )],
                 notes: vec![],
             },
-            Error::Redefinition { previous, current } => ParseError {
+            Error::Redefinition { previous, current } => Description {
                 message: format!("redefinition of `{}`", &source[current]),
                 labels: vec![
                     (
@@ -744,12 +942,12 @@ impl<'a> Error<'a> {
                 ],
                 notes: vec![],
             },
-            Error::RecursiveDeclaration { ident, usage } => ParseError {
+            Error::RecursiveDeclaration { ident, usage } => Description {
                 message: format!("declaration of `{}` is recursive", &source[ident]),
                 labels: vec![(ident, "".into()), (usage, "uses itself here".into())],
                 notes: vec![],
             },
-            Error::CyclicDeclaration { ident, ref path } => ParseError {
+            Error::CyclicDeclaration { ident, ref path } => Description {
                 message: format!("declaration of `{}` is cyclic", &source[ident]),
                 labels: path
                     .iter()
@@ -770,7 +968,7 @@ impl<'a> Error<'a> {
                     .collect(),
                 notes: vec![],
             },
-            Error::InvalidSwitchValue { uint, span } => ParseError {
+            Error::InvalidSwitchValue { uint, span } => Description {
                 message: "invalid switch value".to_string(),
                 labels: vec![(
                     span,
@@ -791,7 +989,7 @@ impl<'a> Error<'a> {
                     )
                 }],
             },
-            Error::CalledEntryPoint(span) => ParseError {
+            Error::CalledEntryPoint(span) => Description {
                 message: "entry point cannot be called".to_string(),
                 labels: vec![(span, "entry point cannot be called".into())],
                 notes: vec![],
@@ -800,7 +998,7 @@ impl<'a> Error<'a> {
                 span,
                 ref expected,
                 found,
-            } => ParseError {
+            } => Description {
                 message: format!(
                     "wrong number of arguments: expected {}, found {}",
                     if expected.len() < 2 {
@@ -813,24 +1011,24 @@ impl<'a> Error<'a> {
                 labels: vec![(span, "wrong number of arguments".into())],
                 notes: vec![],
             },
-            Error::FunctionReturnsVoid(span) => ParseError {
+            Error::FunctionReturnsVoid(span) => Description {
                 message: "function does not return any value".to_string(),
                 labels: vec![(span, "".into())],
                 notes: vec![
                     "perhaps you meant to call the function in a separate statement?".into(),
                 ],
             },
-            Error::InvalidWorkGroupUniformLoad(span) => ParseError {
+            Error::InvalidWorkGroupUniformLoad(span) => Description {
                 message: "incorrect type passed to workgroupUniformLoad".into(),
                 labels: vec![(span, "".into())],
                 notes: vec!["passed type must be a workgroup pointer".into()],
             },
-            Error::Internal(message) => ParseError {
+            Error::Internal(message) => Description {
                 message: "internal WGSL front end error".to_string(),
                 labels: vec![],
                 notes: vec![message.into()],
             },
-            Error::ExpectedConstExprConcreteIntegerScalar(span) => ParseError {
+            Error::ExpectedConstExprConcreteIntegerScalar(span) => Description {
                 message: concat!(
                     "must be a const-expression that ",
                     "resolves to a concrete integer scalar (`u32` or `i32`)"
@@ -839,22 +1037,22 @@ impl<'a> Error<'a> {
                 labels: vec![(span, "must resolve to `u32` or `i32`".into())],
                 notes: vec![],
             },
-            Error::ExpectedNonNegative(span) => ParseError {
+            Error::ExpectedNonNegative(span) => Description {
                 message: "must be non-negative (>= 0)".to_string(),
                 labels: vec![(span, "must be non-negative".into())],
                 notes: vec![],
             },
-            Error::ExpectedPositiveArrayLength(span) => ParseError {
+            Error::ExpectedPositiveArrayLength(span) => Description {
                 message: "array element count must be positive (> 0)".to_string(),
                 labels: vec![(span, "must be positive".into())],
                 notes: vec![],
             },
-            Error::ConstantEvaluatorError(ref e, span) => ParseError {
+            Error::ConstantEvaluatorError(ref e, span) => Description {
                 message: e.to_string(),
                 labels: vec![(span, "see msg".into())],
                 notes: vec![],
             },
-            Error::MissingWorkgroupSize(span) => ParseError {
+            Error::MissingWorkgroupSize(span) => Description {
                 message: "workgroup size is missing on compute shader entry point".to_string(),
                 labels: vec![(
                     span,
@@ -870,7 +1068,7 @@ impl<'a> Error<'a> {
                     source_span,
                     ref source_type,
                 } = **error;
-                ParseError {
+                Description {
                     message: format!(
                         "automatic conversions cannot convert `{}` to `{}`",
                         source_type, dest_type
@@ -895,7 +1093,7 @@ impl<'a> Error<'a> {
                     source_span,
                     ref source_type,
                 } = **error;
-                ParseError {
+                Description {
                     message: format!(
                         "automatic conversions cannot convert elements of `{}` to `{}`",
                         source_type, dest_scalar
@@ -924,7 +1122,7 @@ impl<'a> Error<'a> {
                     ref scalar,
                     ref inner,
                 } = **error;
-                ParseError {
+                Description {
                     message: format!("failed to convert expression to a concrete type: {inner}"),
                     labels: vec![(
                         expr_span,
@@ -936,27 +1134,27 @@ impl<'a> Error<'a> {
                     )],
                 }
             }
-            Error::ExceededLimitForNestedBraces { span, limit } => ParseError {
+            Error::ExceededLimitForNestedBraces { span, limit } => Description {
                 message: "brace nesting limit reached".into(),
                 labels: vec![(span, "limit reached at this brace".into())],
                 notes: vec![format!("nesting limit is currently set to {limit}")],
             },
-            Error::PipelineConstantIDValue(span) => ParseError {
+            Error::PipelineConstantIDValue(span) => Description {
                 message: "pipeline constant ID must be between 0 and 65535 inclusive".to_string(),
                 labels: vec![(span, "must be between 0 and 65535 inclusive".into())],
                 notes: vec![],
             },
-            Error::NotBool(span) => ParseError {
+            Error::NotBool(span) => Description {
                 message: "must be a const-expression that resolves to a `bool`".to_string(),
                 labels: vec![(span, "must resolve to `bool`".into())],
                 notes: vec![],
             },
-            Error::ConstAssertFailed(span) => ParseError {
+            Error::ConstAssertFailed(span) => Description {
                 message: "`const_assert` failure".to_string(),
                 labels: vec![(span, "evaluates to `false`".into())],
                 notes: vec![],
             },
-            Error::DirectiveAfterFirstGlobalDecl { directive_span } => ParseError {
+            Error::DirectiveAfterFirstGlobalDecl { directive_span } => Description {
                 message: "expected global declaration, but found a global directive".into(),
                 labels: vec![(
                     directive_span,
@@ -968,7 +1166,7 @@ impl<'a> Error<'a> {
                 )
                 .into()],
             },
-            Error::EnableExtensionNotYetImplemented { kind, span } => ParseError {
+            Error::EnableExtensionNotYetImplemented { kind, span } => Description {
                 message: format!(
                     "the `{}` enable-extension is not yet supported",
                     EnableExtension::Unimplemented(kind).to_ident()
@@ -990,7 +1188,7 @@ impl<'a> Error<'a> {
                     kind.tracking_issue_num()
                 )],
             },
-            Error::EnableExtensionNotEnabled { kind, span } => ParseError {
+            Error::EnableExtensionNotEnabled { kind, span } => Description {
                 message: format!("`{}` enable-extension is not enabled", kind.to_ident()),
                 labels: vec![(
                     span,
@@ -1018,7 +1216,7 @@ impl<'a> Error<'a> {
                     vec![]
                 },
             },
-            Error::LanguageExtensionNotYetImplemented { kind, span } => ParseError {
+            Error::LanguageExtensionNotYetImplemented { kind, span } => Description {
                 message: format!(
                     "the `{}` language extension is not yet supported",
                     LanguageExtension::Unimplemented(kind).to_ident()
@@ -1035,7 +1233,7 @@ impl<'a> Error<'a> {
             },
             Error::DiagnosticInvalidSeverity {
                 severity_control_name_span,
-            } => ParseError {
+            } => Description {
                 message: "invalid `diagnostic(…)` severity".into(),
                 labels: vec![(
                     severity_control_name_span,
@@ -1051,7 +1249,7 @@ impl<'a> Error<'a> {
                 triggering_rule_spans,
             }) => {
                 let [first_span, second_span] = triggering_rule_spans;
-                ParseError {
+                Description {
                     message: "found conflicting `diagnostic(…)` rule(s)".into(),
                     labels: vec![
                         (first_span, "first rule".into()),
@@ -1070,7 +1268,7 @@ impl<'a> Error<'a> {
             Error::DiagnosticAttributeNotYetImplementedAtParseSite {
                 site_name_plural,
                 ref spans,
-            } => ParseError {
+            } => Description {
                 message: "`@diagnostic(…)` attribute(s) not yet implemented".into(),
                 labels: {
                     let mut spans = spans.iter().cloned();
@@ -1108,7 +1306,7 @@ impl<'a> Error<'a> {
                         display_plural
                     }
                 };
-                ParseError {
+                Description {
                     message: format!(
                         "`@diagnostic(…)` attribute(s) on {on_what_plural} are not supported",
                     ),
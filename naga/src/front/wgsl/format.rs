@@ -0,0 +1,705 @@
+/*!
+Formatting WGSL source code.
+
+[`format_str`] rewrites a WGSL translation unit in a canonical layout, the way
+`rustfmt` does for Rust. Unlike [`back::wgsl`], which regenerates WGSL from
+Naga IR, the formatter works from the source text itself, so comments, type
+aliases, constant expressions, parentheses and identifiers all come through
+unchanged: only whitespace is rewritten.
+
+The input must parse successfully; it is checked with the same parser the
+front end uses, but it is not lowered or validated, so the formatter also
+accepts modules with type errors and the like. The output is then produced
+from the lexer's token stream, with comments recovered from the trivia the
+parser skips:
+
+- Statements, declarations and struct members each go on their own line,
+  indented by four spaces per block.
+
+- Attributes on module-scope declarations go on a line of their own, before
+  the declaration. Other attributes stay inline.
+
+- Parenthesized and bracketed lists are kept on one line, unless the source
+  already broke them over several lines, in which case each item gets its
+  own line.
+
+- Blank lines between statements and declarations are kept, but runs of
+  them are collapsed to one, and blank lines at the start and end of blocks
+  are removed.
+
+- Comments stay where they were: at the end of a line, on their own line,
+  or inline.
+
+Long lines are not rewrapped.
+
+[`back::wgsl`]: crate::back::wgsl
+*/
+
+use crate::front::wgsl::error::ParseError;
+use crate::front::wgsl::parse::lexer::{consume_token, Token};
+use crate::front::wgsl::parse::Parser;
+
+const INDENT: &str = "    ";
+
+/// Words after which an expression starts, rather than ends.
+///
+/// An operator following one of these is unary, and a parenthesized
+/// expression following one is separated from it by a space.
+const EXPRESSION_KEYWORDS: &[&str] = &[
+    "break",
+    "case",
+    "const_assert",
+    "for",
+    "if",
+    "return",
+    "switch",
+    "while",
+];
+
+/// Format the WGSL source code `source`.
+///
+/// Return an error if `source` does not parse.
+pub fn format_str(source: &str) -> Result<String, ParseError> {
+    Parser::new()
+        .parse(source)
+        .map_err(|error| error.as_parse_error(source))?;
+
+    let tokens = tokenize(source);
+    Ok(Printer::new(&tokens).print())
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Kind {
+    Word,
+    Number,
+    LineComment,
+    BlockComment,
+    /// `(`, `[` or `{`.
+    Open(char),
+    /// `)`, `]` or `}`.
+    Close(char),
+    /// The `<` starting a template list, as in `vec3<f32>`.
+    TemplateOpen,
+    /// The `>` ending a template list.
+    TemplateClose,
+    /// The `@` starting an attribute.
+    Attribute,
+    /// `:`, `;`, `,` or `.`.
+    Separator(char),
+    Operator,
+    Arrow,
+    /// `++` or `--`.
+    Postfix,
+}
+
+#[derive(Debug)]
+struct Tok<'a> {
+    kind: Kind,
+    text: &'a str,
+
+    /// The number of line breaks between the previous token and this one.
+    newlines_before: usize,
+}
+
+/// Split `source` into tokens, including comments.
+///
+/// The lexer can't tell a `<` starting a template list from a less-than
+/// operator on its own, so we apply the template list discovery algorithm
+/// from [§3.9 Template Lists] of the WGSL specification as we go.
+///
+/// [§3.9 Template Lists]: https://gpuweb.github.io/gpuweb/wgsl/#template-lists-sec
+fn tokenize(source: &str) -> Vec<Tok<'_>> {
+    let mut tokens: Vec<Tok<'_>> = Vec::new();
+    let mut input = source;
+    let mut newlines = 0;
+
+    // Candidate template list starts: the index of the `<` token, and the
+    // bracket nesting depth at which it appeared.
+    let mut pending: Vec<(usize, usize)> = Vec::new();
+    let mut depth = 0;
+
+    loop {
+        if input.starts_with('>') && pending.last().is_some_and(|&(_, d)| d == depth) {
+            let (open, _) = pending.pop().unwrap();
+            tokens[open].kind = Kind::TemplateOpen;
+            tokens.push(Tok {
+                kind: Kind::TemplateClose,
+                text: &input[..1],
+                newlines_before: std::mem::take(&mut newlines),
+            });
+            input = &input[1..];
+            continue;
+        }
+
+        let (token, rest) = consume_token(input, false);
+        let text = &input[..input.len() - rest.len()];
+        input = rest;
+
+        let kind = match token {
+            Token::End => break,
+            Token::Trivia if text.starts_with("//") => {
+                // Line comments include the line break that ends them.
+                let comment = text.trim_end();
+                tokens.push(Tok {
+                    kind: Kind::LineComment,
+                    text: comment,
+                    newlines_before: std::mem::take(&mut newlines),
+                });
+                newlines += text[comment.len()..].matches('\n').count();
+                continue;
+            }
+            Token::Trivia if text.starts_with("/*") => Kind::BlockComment,
+            Token::Trivia => {
+                newlines += text.matches('\n').count();
+                continue;
+            }
+            Token::Word(_) => Kind::Word,
+            Token::Number(_) => Kind::Number,
+            Token::Separator(c) => Kind::Separator(c),
+            Token::Paren(c @ ('(' | '[' | '{')) => Kind::Open(c),
+            Token::Paren(c @ (')' | ']' | '}')) => Kind::Close(c),
+            Token::Attribute => Kind::Attribute,
+            Token::Arrow => Kind::Arrow,
+            Token::IncrementOperation | Token::DecrementOperation => Kind::Postfix,
+            Token::Paren(_)
+            | Token::Operation(_)
+            | Token::LogicalOperation(_)
+            | Token::ShiftOperation(_)
+            | Token::AssignmentOperation(_)
+            | Token::Unknown(_) => Kind::Operator,
+        };
+
+        match token {
+            Token::Paren('<') => {
+                let after_word = tokens
+                    .iter()
+                    .rev()
+                    .find(|tok| !is_comment(tok.kind))
+                    .is_some_and(|tok| tok.kind == Kind::Word);
+                if after_word {
+                    pending.push((tokens.len(), depth));
+                }
+            }
+            Token::Paren('(' | '[') => depth += 1,
+            Token::Paren(')' | ']') | Token::LogicalOperation('&' | '|') => {
+                while pending.last().is_some_and(|&(_, d)| d >= depth) {
+                    pending.pop();
+                }
+                if let Token::Paren(_) = token {
+                    depth = depth.saturating_sub(1);
+                }
+            }
+            Token::Separator(';' | ':')
+            | Token::Paren('{')
+            | Token::Operation('=')
+            | Token::AssignmentOperation(_) => {
+                depth = 0;
+                pending.clear();
+            }
+            _ => {}
+        }
+
+        tokens.push(Tok {
+            kind,
+            text,
+            newlines_before: std::mem::take(&mut newlines),
+        });
+    }
+
+    tokens
+}
+
+const fn is_comment(kind: Kind) -> bool {
+    matches!(kind, Kind::LineComment | Kind::BlockComment)
+}
+
+/// A bracketed region of the output.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Group {
+    /// A block of statements.
+    Block,
+    /// The members of a struct.
+    Struct,
+    /// A parenthesized or bracketed list, with each item on its own line if
+    /// `vertical` is set.
+    List {
+        vertical: bool,
+    },
+    Template,
+}
+
+struct Printer<'t, 'a> {
+    tokens: &'t [Tok<'a>],
+    out: String,
+    indent: usize,
+    groups: Vec<Group>,
+
+    /// The index of the last token written, if any.
+    prev: Option<usize>,
+
+    /// A line break is owed before the next token.
+    line_break: bool,
+
+    /// The next `{` starts a struct's members.
+    struct_next: bool,
+
+    /// The index of the last token of the module-scope attribute being
+    /// written, if any.
+    attribute_end: Option<usize>,
+
+    /// We are writing the attributes of a module-scope declaration.
+    in_declaration_attributes: bool,
+}
+
+impl<'t, 'a> Printer<'t, 'a> {
+    const fn new(tokens: &'t [Tok<'a>]) -> Self {
+        Printer {
+            tokens,
+            out: String::new(),
+            indent: 0,
+            groups: Vec::new(),
+            prev: None,
+            line_break: false,
+            struct_next: false,
+            attribute_end: None,
+            in_declaration_attributes: false,
+        }
+    }
+
+    fn print(mut self) -> String {
+        for (index, tok) in self.tokens.iter().enumerate() {
+            match tok.kind {
+                Kind::LineComment => {
+                    if tok.newlines_before > 0 || self.prev.is_none() {
+                        self.line_break = true;
+                        self.write(index);
+                    } else {
+                        // Keep the comment at the end of the line it was on.
+                        self.out.push(' ');
+                        self.out.push_str(tok.text);
+                        self.prev = Some(index);
+                    }
+                    self.line_break = true;
+                }
+                Kind::BlockComment => {
+                    if tok.newlines_before > 0 {
+                        self.line_break = true;
+                    }
+                    self.write(index);
+                    if self
+                        .next(index)
+                        .is_some_and(|next| next.newlines_before > 0)
+                    {
+                        self.line_break = true;
+                    }
+                }
+                Kind::Open('{') => {
+                    let group = if std::mem::take(&mut self.struct_next) {
+                        Group::Struct
+                    } else {
+                        Group::Block
+                    };
+                    self.write(index);
+                    self.groups.push(group);
+                    self.indent += 1;
+                    self.line_break = true;
+                }
+                Kind::Close('}') => {
+                    self.groups.pop();
+                    self.indent -= 1;
+                    // Write empty blocks as `{}`.
+                    self.line_break = !self
+                        .prev
+                        .is_some_and(|prev| self.tokens[prev].kind == Kind::Open('{'));
+                    self.write(index);
+                    match self.next(index) {
+                        Some(next) if next.kind == Kind::Word && next.text == "else" => {}
+                        Some(next) if matches!(next.kind, Kind::Separator(_)) => {}
+                        _ => self.line_break = true,
+                    }
+                }
+                Kind::Open(_) => {
+                    self.write(index);
+                    let vertical = self.is_vertical(index);
+                    self.groups.push(Group::List { vertical });
+                    if vertical {
+                        self.indent += 1;
+                        self.line_break = true;
+                    }
+                }
+                Kind::Close(_) => {
+                    if let Some(Group::List { vertical: true }) = self.groups.pop() {
+                        self.indent -= 1;
+                        self.line_break = true;
+                    }
+                    self.write(index);
+                }
+                Kind::TemplateOpen => {
+                    self.write(index);
+                    self.groups.push(Group::Template);
+                }
+                Kind::TemplateClose => {
+                    self.groups.pop();
+                    self.write(index);
+                }
+                Kind::Separator(',') => {
+                    self.write(index);
+                    if let Some(&Group::Struct | &Group::List { vertical: true }) =
+                        self.groups.last()
+                    {
+                        self.line_break = true;
+                    }
+                }
+                Kind::Separator(';') => {
+                    self.write(index);
+                    if let None | Some(&Group::Block) = self.groups.last() {
+                        self.line_break = true;
+                    }
+                }
+                Kind::Word if tok.text == "struct" && self.groups.is_empty() => {
+                    self.struct_next = true;
+                    self.write(index);
+                }
+                Kind::Attribute => {
+                    let starts_declaration = self.groups.is_empty()
+                        && (self.in_declaration_attributes
+                            || self.prev.map_or(true, |prev| {
+                                matches!(
+                                    self.tokens[prev].kind,
+                                    Kind::Separator(';') | Kind::Close('}')
+                                )
+                            }));
+                    self.write(index);
+                    if starts_declaration {
+                        self.attribute_end = Some(self.attribute_end(index));
+                        self.in_declaration_attributes = true;
+                    }
+                }
+                _ => self.write(index),
+            }
+
+            if self.attribute_end == Some(index) {
+                self.attribute_end = None;
+                if !self
+                    .next_significant(index)
+                    .is_some_and(|next| next.kind == Kind::Attribute)
+                {
+                    self.in_declaration_attributes = false;
+                    self.line_break = true;
+                }
+            }
+        }
+
+        if !self.out.is_empty() {
+            self.out.push('\n');
+        }
+        self.out
+    }
+
+    fn next(&self, index: usize) -> Option<&'t Tok<'a>> {
+        self.tokens.get(index + 1)
+    }
+
+    fn next_significant(&self, index: usize) -> Option<&'t Tok<'a>> {
+        self.tokens[index + 1..]
+            .iter()
+            .find(|tok| !is_comment(tok.kind))
+    }
+
+    /// Write the token at `index`, preceded by a line break or a space as
+    /// appropriate.
+    fn write(&mut self, index: usize) {
+        let tok = &self.tokens[index];
+        if let Some(prev) = self.prev {
+            if self.line_break {
+                self.out.push('\n');
+                // Keep a single blank line between items, but not at the
+                // start or end of a block.
+                if tok.newlines_before > 1
+                    && tok.kind != Kind::Close('}')
+                    && self.tokens[prev].kind != Kind::Open('{')
+                {
+                    self.out.push('\n');
+                }
+                for _ in 0..self.indent {
+                    self.out.push_str(INDENT);
+                }
+            } else if self.needs_space(prev, index) {
+                self.out.push(' ');
+            }
+        }
+        self.line_break = false;
+        self.out.push_str(tok.text);
+        self.prev = Some(index);
+    }
+
+    /// Return true if the tokens at `prev` and `next` should be separated by
+    /// a space, when on the same line.
+    fn needs_space(&self, prev: usize, next: usize) -> bool {
+        let (prev_tok, next_tok) = (&self.tokens[prev], &self.tokens[next]);
+        match (prev_tok.kind, next_tok.kind) {
+            (_, Kind::Separator(_) | Kind::Close(')' | ']'))
+            | (_, Kind::TemplateOpen | Kind::TemplateClose | Kind::Postfix)
+            | (Kind::Separator('.'), _)
+            | (Kind::Open('(' | '['), _)
+            | (Kind::Open('{'), Kind::Close('}'))
+            | (Kind::TemplateOpen | Kind::Attribute, _) => false,
+            // Don't turn `- -x` into `--x`, or `& &x` into `&&x`.
+            (Kind::Operator, _) if self.is_unary(prev) => {
+                next_tok.text.starts_with(prev_tok.text) && matches!(prev_tok.text, "-" | "&")
+            }
+            (Kind::Word, Kind::Open('(')) => EXPRESSION_KEYWORDS.contains(&prev_tok.text),
+            (Kind::TemplateClose | Kind::Close(')' | ']'), Kind::Open('('))
+            | (
+                Kind::Word | Kind::Number | Kind::TemplateClose | Kind::Close(')' | ']'),
+                Kind::Open('['),
+            ) => false,
+            _ => true,
+        }
+    }
+
+    /// Return true if the operator at `index` is a prefix operator.
+    fn is_unary(&self, index: usize) -> bool {
+        if !matches!(self.tokens[index].text, "-" | "!" | "~" | "*" | "&") {
+            return false;
+        }
+        let Some(prev) = self.tokens[..index]
+            .iter()
+            .rev()
+            .find(|tok| !is_comment(tok.kind))
+        else {
+            return true;
+        };
+        match prev.kind {
+            Kind::Word => EXPRESSION_KEYWORDS.contains(&prev.text),
+            Kind::Number | Kind::Close(')' | ']') | Kind::TemplateClose => false,
+            _ => true,
+        }
+    }
+
+    /// Return true if the list opened by the `(` or `[` at `open` should
+    /// have each of its items on its own line.
+    ///
+    /// That is the case if the list has more than one item, and the source
+    /// already spread it across several lines.
+    fn is_vertical(&self, open: usize) -> bool {
+        let mut depth = 0;
+        let mut has_commas = false;
+        let mut has_line_breaks = false;
+        for tok in &self.tokens[open + 1..] {
+            if depth == 0 && tok.newlines_before > 0 {
+                has_line_breaks = true;
+            }
+            match tok.kind {
+                Kind::Open(_) | Kind::TemplateOpen => depth += 1,
+                Kind::Close(_) | Kind::TemplateClose => {
+                    if depth == 0 {
+                        break;
+                    }
+                    depth -= 1;
+                }
+                Kind::Separator(',') if depth == 0 => has_commas = true,
+                _ => {}
+            }
+        }
+        has_commas && has_line_breaks
+    }
+
+    /// Return the index of the last token of the attribute whose `@` is at
+    /// `at`.
+    fn attribute_end(&self, at: usize) -> usize {
+        let name = at + 1;
+        match self.tokens.get(name + 1) {
+            Some(tok) if tok.kind == Kind::Open('(') => {
+                let mut depth = 0;
+                for (index, tok) in self.tokens.iter().enumerate().skip(name + 1) {
+                    match tok.kind {
+                        Kind::Open(_) => depth += 1,
+                        Kind::Close(_) => {
+                            depth -= 1;
+                            if depth == 0 {
+                                return index;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                self.tokens.len() - 1
+            }
+            _ => name,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_str, tokenize};
+
+    #[track_caller]
+    fn check(input: &str, expected: &str) {
+        let output = format_str(input).unwrap();
+        if output != expected {
+            for diff in diff::lines(expected, &output) {
+                match diff {
+                    diff::Result::Left(l) => println!("-{l}"),
+                    diff::Result::Both(l, _) => println!(" {l}"),
+                    diff::Result::Right(r) => println!("+{r}"),
+                }
+            }
+            panic!("formatted output doesn't match");
+        }
+
+        // Formatting must only change whitespace, and must be idempotent.
+        let tokens = |source| {
+            tokenize(source)
+                .into_iter()
+                .map(|tok| (tok.kind, tok.text))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(tokens(input), tokens(&output));
+        assert_eq!(format_str(&output).unwrap(), output);
+    }
+
+    #[test]
+    fn declarations() {
+        check(
+            "enable f16;
+            alias  Float=f32 ;
+            struct S{a:vec4<Float>,@size(16) b : array<u32,4>}
+            const   c=vec2(1,2)*3;
+            @group(0)
+            @binding(0) var<storage,read_write> buf:array<S>;
+            @compute @workgroup_size(64,1) fn main(@builtin(global_invocation_id) id:vec3<u32>){buf[id.x].a=vec4<f32>(0.0);}",
+            "enable f16;
+alias Float = f32;
+struct S {
+    a: vec4<Float>,
+    @size(16) b: array<u32, 4>
+}
+const c = vec2(1, 2) * 3;
+@group(0) @binding(0)
+var<storage, read_write> buf: array<S>;
+@compute @workgroup_size(64, 1)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    buf[id.x].a = vec4<f32>(0.0);
+}
+",
+        );
+    }
+
+    #[test]
+    fn statements() {
+        check(
+            "fn f(x: i32) -> i32 {
+                var a = -x;   var b: array<vec2<f32>,2>; let n = - -a;
+                if (a<x) && !(x>=2) { a++; } else if x > a >> 1 { a -= 1; }
+                else {}
+                for (var i = 0; i < 4; i++) { a += i; }
+                switch a { case 1, 2: { return 0; } default: {} }
+                loop { a = a * 2; continuing { break if a > 10; } }
+                let p = &a; *p = a <<2;
+                return a;
+            }",
+            "fn f(x: i32) -> i32 {
+    var a = -x;
+    var b: array<vec2<f32>, 2>;
+    let n = - -a;
+    if (a < x) && !(x >= 2) {
+        a++;
+    } else if x > a >> 1 {
+        a -= 1;
+    } else {}
+    for (var i = 0; i < 4; i++) {
+        a += i;
+    }
+    switch a {
+        case 1, 2: {
+            return 0;
+        }
+        default: {}
+    }
+    loop {
+        a = a * 2;
+        continuing {
+            break if a > 10;
+        }
+    }
+    let p = &a;
+    *p = a << 2;
+    return a;
+}
+",
+        );
+    }
+
+    #[test]
+    fn comments_and_blank_lines() {
+        check(
+            "// A module.
+
+
+/* Constants. */ const a = 1; // trailing
+const b = 2;
+
+
+
+fn f() {
+
+    // Leading.
+    let x = a + /* inline */ b;
+
+    let y = x;
+}
+",
+            "// A module.
+
+/* Constants. */ const a = 1; // trailing
+const b = 2;
+
+fn f() {
+    // Leading.
+    let x = a + /* inline */ b;
+
+    let y = x;
+}
+",
+        );
+    }
+
+    #[test]
+    fn vertical_lists() {
+        check(
+            "fn f(
+                a: f32,
+                b: f32
+            ) -> f32 {
+                return max(a,
+                    b);
+            }
+            fn g(a: f32,
+                 b: f32) -> f32 { return (a
+                   + b); }",
+            "fn f(
+    a: f32,
+    b: f32
+) -> f32 {
+    return max(
+        a,
+        b
+    );
+}
+fn g(
+    a: f32,
+    b: f32
+) -> f32 {
+    return (a + b);
+}
+",
+        );
+    }
+
+    #[test]
+    fn parse_error() {
+        assert!(format_str("fn f( {").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+/*!
+Combining several WGSL modules into one [`Module`].
+
+The modules given to a [`Linker`] share one namespace: a declaration in one
+module can be used from any other, regardless of the order in which the
+modules were added, and declaring the same name in two modules is an error.
+Directives, like `enable` and `diagnostic`, only apply to the module they
+appear in. Since no one module's global diagnostic filters apply to the
+whole [`Module`], its [`diagnostic_filter_leaf`] is `None`; each function's
+filters start from those of the module that declares it.
+
+The modules are laid out one after the other in a combined source, which
+the spans in the [`Module`] and in errors refer to. [`Linker::locate`] maps
+those spans back to the module they came from.
+
+[`Module`]: crate::Module
+[`diagnostic_filter_leaf`]: crate::Module::diagnostic_filter_leaf
+*/
+
+use super::{index, parse::Parser, Lowerer, ParseError};
+use crate::{diagnostic_filter::Diagnostic, Span};
+use std::ops::Range;
+
+/// A module added to a [`Linker`].
+#[derive(Clone, Debug)]
+struct LinkedModule {
+    /// The name of the module, used in error messages.
+    name: String,
+    /// The range of the combined source holding the module's text.
+    range: Range<usize>,
+}
+
+/// Combines several WGSL modules into one [`Module`].
+///
+/// ```
+/// # use naga::front::wgsl::Linker;
+/// let mut linker = Linker::new();
+/// linker.add_module("common.wgsl", "fn double(x: f32) -> f32 { return x * 2.0; }");
+/// linker.add_module("main.wgsl", "@compute @workgroup_size(1) fn main() { _ = double(1.0); }");
+/// let module = linker.link().unwrap();
+/// assert_eq!(module.functions.len(), 1);
+/// ```
+///
+/// [`Module`]: crate::Module
+#[derive(Debug, Default)]
+pub struct Linker {
+    source: String,
+    modules: Vec<LinkedModule>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Linker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a module named `name` with the text `source`.
+    pub fn add_module(&mut self, name: impl Into<String>, source: &str) -> &mut Self {
+        if !self.source.is_empty() && !self.source.ends_with('\n') {
+            self.source.push('\n');
+        }
+        let start = self.source.len();
+        self.source.push_str(source);
+        self.modules.push(LinkedModule {
+            name: name.into(),
+            range: start..self.source.len(),
+        });
+        self
+    }
+
+    /// Parses all the modules added so far and combines them into one.
+    pub fn link(&mut self) -> Result<crate::Module, ParseError> {
+        let modules = self
+            .modules
+            .iter()
+            .map(|module| module.range.clone())
+            .collect::<Vec<_>>();
+        let mut parser = Parser::new();
+        let result = parser.parse_modules(&self.source, &modules).and_then(|tu| {
+            let index = index::Index::generate(&tu)?;
+            Lowerer::new(&index).lower(&tu)
+        });
+        self.diagnostics = parser.diagnostics().to_vec();
+        result.map_err(|error| error.as_parse_error(&self.source))
+    }
+
+    /// Returns the diagnostics reported by the last call to [`link`] that
+    /// didn't make it fail, like warnings about unused variables.
+    ///
+    /// Their spans refer to the combined [`source`], and can be mapped back
+    /// to the module they came from with [`locate`], or reported there with
+    /// [`Diagnostic::emit_to_string_with_linker`].
+    ///
+    /// [`link`]: Linker::link
+    /// [`source`]: Linker::source
+    /// [`locate`]: Linker::locate
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Returns the combined source of all modules, which spans refer to.
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Returns the number of modules added so far.
+    pub fn module_count(&self) -> usize {
+        self.modules.len()
+    }
+
+    /// Returns the name and text of the module at `index`, in the order the
+    /// modules were added.
+    pub fn module(&self, index: usize) -> Option<(&str, &str)> {
+        let module = self.modules.get(index)?;
+        Some((&module.name, &self.source[module.range.clone()]))
+    }
+
+    /// Maps `span`, a range of the combined source, to the module it came
+    /// from.
+    ///
+    /// Returns the index of the module and the range of its text. Spans that
+    /// cross into another module are cut short at the end of the first one.
+    pub fn locate(&self, span: Span) -> Option<(usize, Range<usize>)> {
+        let range = span.to_range()?;
+        let index = self
+            .modules
+            .partition_point(|module| module.range.start <= range.start)
+            .checked_sub(1)?;
+        let module = &self.modules[index];
+        let end = range.end.min(module.range.end).max(range.start);
+        Some((
+            index,
+            range.start - module.range.start..end - module.range.start,
+        ))
+    }
+}
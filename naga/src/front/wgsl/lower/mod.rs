@@ -980,6 +980,8 @@ enum SubgroupGather {
     ShuffleDown,
     ShuffleUp,
     ShuffleXor,
+    QuadBroadcast,
+    QuadSwap(crate::Direction),
 }
 
 impl SubgroupGather {
@@ -991,6 +993,10 @@ impl SubgroupGather {
             "subgroupShuffleDown" => Self::ShuffleDown,
             "subgroupShuffleUp" => Self::ShuffleUp,
             "subgroupShuffleXor" => Self::ShuffleXor,
+            "quadBroadcast" => Self::QuadBroadcast,
+            "quadSwapX" => Self::QuadSwap(crate::Direction::X),
+            "quadSwapY" => Self::QuadSwap(crate::Direction::Y),
+            "quadSwapDiagonal" => Self::QuadSwap(crate::Direction::Diagonal),
             _ => return None,
         })
     }
@@ -1871,6 +1877,26 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         ctx.apply_load_rule(expr)
     }
 
+    /// Lower `expr`, the base of a `[]` or `.member` expression.
+    ///
+    /// WGSL's `pointer_composite_access` language extension lets a
+    /// pointer be used directly as the base of a component access, as if
+    /// it had been explicitly dereferenced. So if `expr` is a pointer
+    /// value, return it as a reference.
+    fn pointer_composite_base(
+        &mut self,
+        expr: Handle<ast::Expression<'source>>,
+        ctx: &mut ExpressionContext<'source, '_, '_>,
+    ) -> Result<Typed<Handle<crate::Expression>>, Error<'source>> {
+        let lowered = self.expression_for_reference(expr, ctx)?;
+        if let Typed::Plain(handle) = lowered {
+            if resolve_inner!(ctx, handle).pointer_space().is_some() {
+                return Ok(Typed::Reference(handle));
+            }
+        }
+        Ok(lowered)
+    }
+
     fn expression_for_reference(
         &mut self,
         expr: Handle<ast::Expression<'source>>,
@@ -1883,6 +1909,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             ast::Expression::Literal(literal) => {
                 let literal = match literal {
                     ast::Literal::Number(Number::F32(f)) => crate::Literal::F32(f),
+                    ast::Literal::Number(Number::F16(f)) => crate::Literal::F16(f),
                     ast::Literal::Number(Number::I32(i)) => crate::Literal::I32(i),
                     ast::Literal::Number(Number::U32(u)) => crate::Literal::U32(u),
                     ast::Literal::Number(Number::I64(i)) => crate::Literal::I64(i),
@@ -1977,25 +2004,16 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                 return Ok(Typed::Plain(handle));
             }
             ast::Expression::Index { base, index } => {
-                let lowered_base = self.expression_for_reference(base, ctx)?;
+                let lowered_base = self.pointer_composite_base(base, ctx)?;
                 let index = self.expression(index, ctx)?;
 
-                if let Typed::Plain(handle) = lowered_base {
-                    if resolve_inner!(ctx, handle).pointer_space().is_some() {
-                        return Err(Error::Pointer(
-                            "the value indexed by a `[]` subscripting expression",
-                            ctx.ast_expressions.get_span(base),
-                        ));
-                    }
-                }
-
                 lowered_base.map(|base| match ctx.const_access(index) {
                     Some(index) => crate::Expression::AccessIndex { base, index },
                     None => crate::Expression::Access { base, index },
                 })
             }
             ast::Expression::Member { base, ref field } => {
-                let lowered_base = self.expression_for_reference(base, ctx)?;
+                let lowered_base = self.pointer_composite_base(base, ctx)?;
 
                 let temp_inner;
                 let composite_type: &crate::TypeInner = match lowered_base {
@@ -2023,18 +2041,7 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                         }
                     }
 
-                    Typed::Plain(handle) => {
-                        let inner = resolve_inner!(ctx, handle);
-                        if let crate::TypeInner::Pointer { .. }
-                        | crate::TypeInner::ValuePointer { .. } = *inner
-                        {
-                            return Err(Error::Pointer(
-                                "the value accessed by a `.member` expression",
-                                ctx.ast_expressions.get_span(base),
-                            ));
-                        }
-                        inner
-                    }
+                    Typed::Plain(handle) => resolve_inner!(ctx, handle),
                 };
 
                 let access = match *composite_type {
@@ -2472,6 +2479,14 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
                                 .push(crate::Statement::Barrier(crate::Barrier::WORK_GROUP), span);
                             return Ok(None);
                         }
+                        "textureBarrier" => {
+                            ctx.prepare_args(arguments, 0, span).finish()?;
+
+                            let rctx = ctx.runtime_expression_ctx(span)?;
+                            rctx.block
+                                .push(crate::Statement::Barrier(crate::Barrier::TEXTURE), span);
+                            return Ok(None);
+                        }
                         "subgroupBarrier" => {
                             ctx.prepare_args(arguments, 0, span).finish()?;
 
@@ -2958,22 +2973,39 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
         arguments: &[Handle<ast::Expression<'source>>],
         ctx: &mut ExpressionContext<'source, '_, '_>,
     ) -> Result<Handle<crate::Expression>, Error<'source>> {
-        let mut args = ctx.prepare_args(arguments, 2, span);
+        use SubgroupGather as Sg;
+        let arg_count = match mode {
+            Sg::BroadcastFirst | Sg::QuadSwap(_) => 1,
+            _ => 2,
+        };
+        let mut args = ctx.prepare_args(arguments, arg_count, span);
 
         let argument = self.expression(args.next()?, ctx)?;
 
-        use SubgroupGather as Sg;
-        let mode = if let Sg::BroadcastFirst = mode {
-            crate::GatherMode::BroadcastFirst
-        } else {
-            let index = self.expression(args.next()?, ctx)?;
-            match mode {
-                Sg::Broadcast => crate::GatherMode::Broadcast(index),
-                Sg::Shuffle => crate::GatherMode::Shuffle(index),
-                Sg::ShuffleDown => crate::GatherMode::ShuffleDown(index),
-                Sg::ShuffleUp => crate::GatherMode::ShuffleUp(index),
-                Sg::ShuffleXor => crate::GatherMode::ShuffleXor(index),
-                Sg::BroadcastFirst => unreachable!(),
+        let mode = match mode {
+            Sg::BroadcastFirst => crate::GatherMode::BroadcastFirst,
+            Sg::QuadSwap(direction) => crate::GatherMode::QuadSwap(direction),
+            _ => {
+                let index_expr = args.next()?;
+                let index = self.expression(index_expr, ctx)?;
+                match mode {
+                    Sg::Broadcast => crate::GatherMode::Broadcast(index),
+                    Sg::Shuffle => crate::GatherMode::Shuffle(index),
+                    Sg::ShuffleDown => crate::GatherMode::ShuffleDown(index),
+                    Sg::ShuffleUp => crate::GatherMode::ShuffleUp(index),
+                    Sg::ShuffleXor => crate::GatherMode::ShuffleXor(index),
+                    Sg::QuadBroadcast => {
+                        // The lane to read from must be a const-expression.
+                        let rctx = ctx.runtime_expression_ctx(span)?;
+                        if !rctx.local_expression_kind_tracker.is_const(index) {
+                            return Err(Error::ExpectedConstExprConcreteIntegerScalar(
+                                ctx.ast_expressions.get_span(index_expr),
+                            ));
+                        }
+                        crate::GatherMode::QuadBroadcast(index)
+                    }
+                    Sg::BroadcastFirst | Sg::QuadSwap(_) => unreachable!(),
+                }
             }
         };
 
@@ -3274,10 +3306,18 @@ impl<'source, 'temp> Lowerer<'source, 'temp> {
             Some(ast::Binding::BuiltIn(b)) => Some(crate::Binding::BuiltIn(b)),
             Some(ast::Binding::Location {
                 location,
-                second_blend_source,
+                blend_src,
                 interpolation,
                 sampling,
             }) => {
+                let second_blend_source = match blend_src {
+                    Some(blend_src) => match self.const_u32(blend_src, &mut ctx.as_const())? {
+                        (0, _) => false,
+                        (1, _) => true,
+                        (_, span) => return Err(Error::InvalidBlendSrc(span)),
+                    },
+                    None => false,
+                };
                 let mut binding = crate::Binding::Location {
                     location: self.const_u32(location, &mut ctx.as_const())?.0,
                     second_blend_source,
@@ -5,7 +5,9 @@ Frontend for [WGSL][wgsl] (WebGPU Shading Language).
 */
 
 mod error;
+mod format;
 mod index;
+mod link;
 mod lower;
 mod parse;
 #[cfg(test)]
@@ -17,6 +19,8 @@ use crate::front::wgsl::parse::Parser;
 use thiserror::Error;
 
 pub use crate::front::wgsl::error::ParseError;
+pub use crate::front::wgsl::format::format_str;
+pub use crate::front::wgsl::link::Linker;
 use crate::front::wgsl::lower::Lowerer;
 use crate::Scalar;
 
@@ -39,6 +43,16 @@ impl Frontend {
         self.inner(source).map_err(|x| x.as_parse_error(source))
     }
 
+    /// Returns the diagnostics reported by the last call to [`parse`] that
+    /// didn't make it fail, like warnings about unused variables.
+    ///
+    /// Their spans refer to the source passed to [`parse`].
+    ///
+    /// [`parse`]: Frontend::parse
+    pub fn diagnostics(&self) -> &[crate::diagnostic_filter::Diagnostic] {
+        self.parser.diagnostics()
+    }
+
     fn inner<'a>(&mut self, source: &'a str) -> Result<crate::Module, Error<'a>> {
         let tu = self.parser.parse(source)?;
         let index = index::Index::generate(&tu)?;
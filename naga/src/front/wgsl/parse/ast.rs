@@ -141,7 +141,7 @@ pub enum Binding<'a> {
     BuiltIn(crate::BuiltIn),
     Location {
         location: Handle<Expression<'a>>,
-        second_blend_source: bool,
+        blend_src: Option<Handle<Expression<'a>>>,
         interpolation: Option<crate::Interpolation>,
         sampling: Option<crate::Sampling>,
     },
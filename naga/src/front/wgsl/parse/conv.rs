@@ -1,4 +1,7 @@
 use super::Error;
+use crate::front::wgsl::parse::directive::enable_extension::{
+    EnableExtensions, ImplementedEnableExtension,
+};
 use crate::front::wgsl::Scalar;
 use crate::Span;
 
@@ -23,6 +26,7 @@ pub fn map_built_in(word: &str, span: Span) -> Result<crate::BuiltIn, Error<'_>>
         "vertex_index" => crate::BuiltIn::VertexIndex,
         "instance_index" => crate::BuiltIn::InstanceIndex,
         "view_index" => crate::BuiltIn::ViewIndex,
+        "clip_distances" => crate::BuiltIn::ClipDistance,
         // fragment
         "front_facing" => crate::BuiltIn::FrontFacing,
         "frag_depth" => crate::BuiltIn::FragDepth,
@@ -112,10 +116,21 @@ pub fn map_storage_format(word: &str, span: Span) -> Result<crate::StorageFormat
     })
 }
 
-pub fn get_scalar_type(word: &str) -> Option<Scalar> {
+/// Map a WGSL scalar type name to its [`Scalar`], if it is one.
+///
+/// Return an error if `word` names a type whose enable-extension is missing
+/// from `enable_extensions`. `span` should cover `word`.
+pub fn get_scalar_type(
+    enable_extensions: &EnableExtensions,
+    span: Span,
+    word: &str,
+) -> Result<Option<Scalar>, Error<'static>> {
     use crate::ScalarKind as Sk;
-    match word {
-        // "f16" => Some(Scalar { kind: Sk::Float, width: 2 }),
+    let scalar = match word {
+        "f16" => Some(Scalar {
+            kind: Sk::Float,
+            width: 2,
+        }),
         "f32" => Some(Scalar {
             kind: Sk::Float,
             width: 4,
@@ -145,7 +160,16 @@ pub fn get_scalar_type(word: &str) -> Option<Scalar> {
             width: crate::BOOL_WIDTH,
         }),
         _ => None,
+    };
+
+    if scalar == Some(Scalar::F16) && !enable_extensions.contains(ImplementedEnableExtension::F16) {
+        return Err(Error::EnableExtensionNotEnabled {
+            kind: ImplementedEnableExtension::F16.into(),
+            span,
+        });
     }
+
+    Ok(scalar)
 }
 
 pub fn map_derivative(word: &str) -> Option<(crate::DerivativeAxis, crate::DerivativeControl)> {
@@ -214,6 +238,8 @@ pub fn map_standard_fun(word: &str) -> Option<crate::MathFunction> {
         "pow" => Mf::Pow,
         // geometry
         "dot" => Mf::Dot,
+        "dot4I8Packed" => Mf::Dot4I8Packed,
+        "dot4U8Packed" => Mf::Dot4U8Packed,
         "cross" => Mf::Cross,
         "distance" => Mf::Distance,
         "length" => Mf::Length,
@@ -249,6 +275,8 @@ pub fn map_standard_fun(word: &str) -> Option<crate::MathFunction> {
         "pack2x16float" => Mf::Pack2x16float,
         "pack4xI8" => Mf::Pack4xI8,
         "pack4xU8" => Mf::Pack4xU8,
+        "pack4xI8Clamp" => Mf::Pack4xI8Clamp,
+        "pack4xU8Clamp" => Mf::Pack4xU8Clamp,
         // data unpacking
         "unpack4x8snorm" => Mf::Unpack4x8snorm,
         "unpack4x8unorm" => Mf::Unpack4x8unorm,
@@ -39,10 +39,10 @@ impl crate::diagnostic_filter::Severity {
         self,
         err: crate::front::wgsl::error::Error<'a>,
         source: &str,
+        diagnostics: &mut Vec<crate::diagnostic_filter::Diagnostic>,
     ) -> Result<(), crate::front::wgsl::error::Error<'a>> {
-        self.report_diag(err, |e, level| {
-            let e = e.as_parse_error(source);
-            log::log!(level, "{}", e.emit_to_string(source));
+        self.report_diag(err, diagnostics, |e, severity| {
+            e.as_parse_error(source).into_diagnostic(severity)
         })
     }
 }
@@ -5,24 +5,48 @@ use crate::{front::wgsl::error::Error, Span};
 
 /// Tracks the status of every enable-extension known to Naga.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct EnableExtensions {}
+pub struct EnableExtensions {
+    /// Whether `enable f16;` was written earlier in the shader module.
+    f16: bool,
+    /// Whether `enable clip_distances;` was written earlier in the shader module.
+    clip_distances: bool,
+    /// Whether `enable dual_source_blending;` was written earlier in the shader module.
+    dual_source_blending: bool,
+}
 
 impl EnableExtensions {
     pub(crate) const fn empty() -> Self {
-        Self {}
+        Self {
+            f16: false,
+            clip_distances: false,
+            dual_source_blending: false,
+        }
     }
 
     /// Add an enable-extension to the set requested by a module.
-    #[allow(unreachable_code)]
     pub(crate) fn add(&mut self, ext: ImplementedEnableExtension) {
-        let _field: &mut bool = match ext {};
-        *_field = true;
+        let field = match ext {
+            ImplementedEnableExtension::F16 => &mut self.f16,
+            ImplementedEnableExtension::ClipDistances => &mut self.clip_distances,
+            ImplementedEnableExtension::DualSourceBlending => &mut self.dual_source_blending,
+        };
+        *field = true;
+    }
+
+    /// Add all the enable-extensions requested in `other`.
+    pub(crate) fn extend(&mut self, other: &Self) {
+        self.f16 |= other.f16;
+        self.clip_distances |= other.clip_distances;
+        self.dual_source_blending |= other.dual_source_blending;
     }
 
     /// Query whether an enable-extension tracked here has been requested.
-    #[allow(unused)]
     pub(crate) const fn contains(&self, ext: ImplementedEnableExtension) -> bool {
-        match ext {}
+        match ext {
+            ImplementedEnableExtension::F16 => self.f16,
+            ImplementedEnableExtension::ClipDistances => self.clip_distances,
+            ImplementedEnableExtension::DualSourceBlending => self.dual_source_blending,
+        }
     }
 }
 
@@ -37,7 +61,6 @@ impl Default for EnableExtensions {
 /// WGSL spec.: <https://www.w3.org/TR/WGSL/#enable-extensions-sec>
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
 pub enum EnableExtension {
-    #[allow(unused)]
     Implemented(ImplementedEnableExtension),
     Unimplemented(UnimplementedEnableExtension),
 }
@@ -50,12 +73,10 @@ impl EnableExtension {
     /// Convert from a sentinel word in WGSL into its associated [`EnableExtension`], if possible.
     pub(crate) fn from_ident(word: &str, span: Span) -> Result<Self, Error<'_>> {
         Ok(match word {
-            Self::F16 => Self::Unimplemented(UnimplementedEnableExtension::F16),
-            Self::CLIP_DISTANCES => {
-                Self::Unimplemented(UnimplementedEnableExtension::ClipDistances)
-            }
+            Self::F16 => Self::Implemented(ImplementedEnableExtension::F16),
+            Self::CLIP_DISTANCES => Self::Implemented(ImplementedEnableExtension::ClipDistances),
             Self::DUAL_SOURCE_BLENDING => {
-                Self::Unimplemented(UnimplementedEnableExtension::DualSourceBlending)
+                Self::Implemented(ImplementedEnableExtension::DualSourceBlending)
             }
             _ => return Err(Error::UnknownEnableExtension(span, word)),
         })
@@ -64,23 +85,19 @@ impl EnableExtension {
     /// Maps this [`EnableExtension`] into the sentinel word associated with it in WGSL.
     pub const fn to_ident(self) -> &'static str {
         match self {
-            Self::Implemented(kind) => match kind {},
-            Self::Unimplemented(kind) => match kind {
-                UnimplementedEnableExtension::F16 => Self::F16,
-                UnimplementedEnableExtension::ClipDistances => Self::CLIP_DISTANCES,
-                UnimplementedEnableExtension::DualSourceBlending => Self::DUAL_SOURCE_BLENDING,
+            Self::Implemented(kind) => match kind {
+                ImplementedEnableExtension::F16 => Self::F16,
+                ImplementedEnableExtension::ClipDistances => Self::CLIP_DISTANCES,
+                ImplementedEnableExtension::DualSourceBlending => Self::DUAL_SOURCE_BLENDING,
             },
+            Self::Unimplemented(kind) => match kind {},
         }
     }
 }
 
 /// A variant of [`EnableExtension::Implemented`].
 #[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
-pub enum ImplementedEnableExtension {}
-
-/// A variant of [`EnableExtension::Unimplemented`].
-#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
-pub enum UnimplementedEnableExtension {
+pub enum ImplementedEnableExtension {
     /// Enables `f16`/`half` primitive support in all shader languages.
     ///
     /// In the WGSL standard, this corresponds to [`enable f16;`].
@@ -91,22 +108,28 @@ pub enum UnimplementedEnableExtension {
     ///
     /// In the WGSL standard, this corresponds to [`enable clip_distances;`].
     ///
-    /// [`enable clip_distances;`]: https://www.w3.org/TR/WGSL/#extension-f16
+    /// [`enable clip_distances;`]: https://www.w3.org/TR/WGSL/#extension-clip_distances
     ClipDistances,
     /// Enables the `blend_src` attribute in WGSL.
     ///
     /// In the WGSL standard, this corresponds to [`enable dual_source_blending;`].
     ///
-    /// [`enable dual_source_blending;`]: https://www.w3.org/TR/WGSL/#extension-f16
+    /// [`enable dual_source_blending;`]: https://www.w3.org/TR/WGSL/#extension-dual_source_blending
     DualSourceBlending,
 }
 
+impl From<ImplementedEnableExtension> for EnableExtension {
+    fn from(value: ImplementedEnableExtension) -> Self {
+        Self::Implemented(value)
+    }
+}
+
+/// A variant of [`EnableExtension::Unimplemented`].
+#[derive(Clone, Copy, Debug, Hash, Eq, PartialEq)]
+pub enum UnimplementedEnableExtension {}
+
 impl UnimplementedEnableExtension {
     pub(crate) const fn tracking_issue_num(self) -> u16 {
-        match self {
-            Self::F16 => 4384,
-            Self::ClipDistances => 6236,
-            Self::DualSourceBlending => 6402,
-        }
+        match self {}
     }
 }
@@ -9,7 +9,6 @@ use strum::VariantArray;
 /// WGSL spec.: <https://www.w3.org/TR/WGSL/#language-extensions-sec>
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub enum LanguageExtension {
-    #[allow(unused)]
     Implemented(ImplementedLanguageExtension),
     Unimplemented(UnimplementedLanguageExtension),
 }
@@ -24,17 +23,17 @@ impl LanguageExtension {
     /// Convert from a sentinel word in WGSL into its associated [`LanguageExtension`], if possible.
     pub fn from_ident(s: &str) -> Option<Self> {
         Some(match s {
-            Self::READONLY_AND_READWRITE_STORAGE_TEXTURES => Self::Unimplemented(
-                UnimplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures,
-            ),
+            Self::READONLY_AND_READWRITE_STORAGE_TEXTURES => {
+                Self::Implemented(ImplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures)
+            }
             Self::PACKED4X8_INTEGER_DOT_PRODUCT => {
-                Self::Unimplemented(UnimplementedLanguageExtension::Packed4x8IntegerDotProduct)
+                Self::Implemented(ImplementedLanguageExtension::Packed4x8IntegerDotProduct)
             }
             Self::UNRESTRICTED_POINTER_PARAMETERS => {
-                Self::Unimplemented(UnimplementedLanguageExtension::UnrestrictedPointerParameters)
+                Self::Implemented(ImplementedLanguageExtension::UnrestrictedPointerParameters)
             }
             Self::POINTER_COMPOSITE_ACCESS => {
-                Self::Unimplemented(UnimplementedLanguageExtension::PointerCompositeAccess)
+                Self::Implemented(ImplementedLanguageExtension::PointerCompositeAccess)
             }
             _ => return None,
         })
@@ -44,27 +43,19 @@ impl LanguageExtension {
     pub const fn to_ident(self) -> &'static str {
         match self {
             Self::Implemented(kind) => kind.to_ident(),
-            Self::Unimplemented(kind) => match kind {
-                UnimplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures => {
-                    Self::READONLY_AND_READWRITE_STORAGE_TEXTURES
-                }
-                UnimplementedLanguageExtension::Packed4x8IntegerDotProduct => {
-                    Self::PACKED4X8_INTEGER_DOT_PRODUCT
-                }
-                UnimplementedLanguageExtension::UnrestrictedPointerParameters => {
-                    Self::UNRESTRICTED_POINTER_PARAMETERS
-                }
-                UnimplementedLanguageExtension::PointerCompositeAccess => {
-                    Self::POINTER_COMPOSITE_ACCESS
-                }
-            },
+            Self::Unimplemented(kind) => match kind {},
         }
     }
 }
 
 /// A variant of [`LanguageExtension::Implemented`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, VariantArray)]
-pub enum ImplementedLanguageExtension {}
+pub enum ImplementedLanguageExtension {
+    ReadOnlyAndReadWriteStorageTextures,
+    Packed4x8IntegerDotProduct,
+    UnrestrictedPointerParameters,
+    PointerCompositeAccess,
+}
 
 impl ImplementedLanguageExtension {
     /// Returns slice of all variants of [`ImplementedLanguageExtension`].
@@ -74,26 +65,25 @@ impl ImplementedLanguageExtension {
 
     /// Maps this [`ImplementedLanguageExtension`] into the sentinel word associated with it in WGSL.
     pub const fn to_ident(self) -> &'static str {
-        match self {}
+        match self {
+            Self::ReadOnlyAndReadWriteStorageTextures => {
+                LanguageExtension::READONLY_AND_READWRITE_STORAGE_TEXTURES
+            }
+            Self::Packed4x8IntegerDotProduct => LanguageExtension::PACKED4X8_INTEGER_DOT_PRODUCT,
+            Self::UnrestrictedPointerParameters => {
+                LanguageExtension::UNRESTRICTED_POINTER_PARAMETERS
+            }
+            Self::PointerCompositeAccess => LanguageExtension::POINTER_COMPOSITE_ACCESS,
+        }
     }
 }
 
 /// A variant of [`LanguageExtension::Unimplemented`].
 #[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
-pub enum UnimplementedLanguageExtension {
-    ReadOnlyAndReadWriteStorageTextures,
-    Packed4x8IntegerDotProduct,
-    UnrestrictedPointerParameters,
-    PointerCompositeAccess,
-}
+pub enum UnimplementedLanguageExtension {}
 
 impl UnimplementedLanguageExtension {
     pub(crate) const fn tracking_issue_num(self) -> u16 {
-        match self {
-            Self::ReadOnlyAndReadWriteStorageTextures => 6204,
-            Self::Packed4x8IntegerDotProduct => 6445,
-            Self::UnrestrictedPointerParameters => 5158,
-            Self::PointerCompositeAccess => 6192,
-        }
+        match self {}
     }
 }
@@ -1,6 +1,8 @@
 use super::{number::consume_number, Error, ExpectedToken};
 use crate::front::wgsl::error::NumberError;
-use crate::front::wgsl::parse::directive::enable_extension::EnableExtensions;
+use crate::front::wgsl::parse::directive::enable_extension::{
+    EnableExtensions, ImplementedEnableExtension,
+};
 use crate::front::wgsl::parse::{conv, Number};
 use crate::front::wgsl::Scalar;
 use crate::Span;
@@ -46,7 +48,7 @@ fn consume_any(input: &str, what: impl Fn(char) -> bool) -> (&str, &str) {
 ///     `Token::LogicalOperation` tokens.
 ///
 /// [§3.1 Parsing]: https://gpuweb.github.io/gpuweb/wgsl/#parsing
-fn consume_token(input: &str, generic: bool) -> (Token<'_>, &str) {
+pub(in crate::front::wgsl) fn consume_token(input: &str, generic: bool) -> (Token<'_>, &str) {
     let mut chars = input.chars();
     let cur = match chars.next() {
         Some(c) => c,
@@ -218,7 +220,6 @@ pub(in crate::front::wgsl) struct Lexer<'a> {
     /// statements.
     last_end_offset: usize,
 
-    #[allow(dead_code)]
     pub(in crate::front::wgsl) enable_extensions: EnableExtensions,
 }
 
@@ -232,6 +233,17 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Create a lexer for the part of `source` that starts at `offset`.
+    ///
+    /// Spans are still offsets into the whole of `source`.
+    pub(in crate::front::wgsl) fn new_at(source: &'a str, offset: usize) -> Self {
+        Lexer {
+            input: &source[offset..],
+            last_end_offset: offset,
+            ..Self::new(source)
+        }
+    }
+
     /// Calls the function with a lexer and returns the result of the function as well as the span for everything the function parsed
     ///
     /// # Examples
@@ -405,12 +417,31 @@ impl<'a> Lexer<'a> {
         }
     }
 
+    /// Return an error if `extension` was not enabled by an `enable` directive.
+    ///
+    /// `span` should cover the use of the functionality requiring it.
+    pub(in crate::front::wgsl) fn require_enable_extension(
+        &self,
+        extension: ImplementedEnableExtension,
+        span: Span,
+    ) -> Result<(), Error<'static>> {
+        if self.enable_extensions.contains(extension) {
+            Ok(())
+        } else {
+            Err(Error::EnableExtensionNotEnabled {
+                kind: extension.into(),
+                span,
+            })
+        }
+    }
+
     /// Parses a generic scalar type, for example `<f32>`.
     pub(in crate::front::wgsl) fn next_scalar_generic(&mut self) -> Result<Scalar, Error<'a>> {
         self.expect_generic_paren('<')?;
         let pair = match self.next() {
             (Token::Word(word), span) => {
-                conv::get_scalar_type(word).ok_or(Error::UnknownScalarType(span))
+                conv::get_scalar_type(&self.enable_extensions, span, word)?
+                    .ok_or(Error::UnknownScalarType(span))
             }
             (_, span) => Err(Error::UnknownScalarType(span)),
         }?;
@@ -426,9 +457,11 @@ impl<'a> Lexer<'a> {
     ) -> Result<(Scalar, Span), Error<'a>> {
         self.expect_generic_paren('<')?;
         let pair = match self.next() {
-            (Token::Word(word), span) => conv::get_scalar_type(word)
-                .map(|scalar| (scalar, span))
-                .ok_or(Error::UnknownScalarType(span)),
+            (Token::Word(word), span) => {
+                conv::get_scalar_type(&self.enable_extensions, span, word)?
+                    .map(|scalar| (scalar, span))
+                    .ok_or(Error::UnknownScalarType(span))
+            }
             (_, span) => Err(Error::UnknownScalarType(span)),
         }?;
         self.expect_generic_paren('>')?;
@@ -517,14 +550,14 @@ fn test_numbers() {
             Token::Number(Ok(Number::AbstractFloat(0.01))),
             Token::Number(Ok(Number::AbstractFloat(12.34))),
             Token::Number(Ok(Number::F32(0.))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(0.)))),
             Token::Number(Ok(Number::AbstractFloat(0.001))),
             Token::Number(Ok(Number::AbstractFloat(43.75))),
             Token::Number(Ok(Number::F32(16.))),
             Token::Number(Ok(Number::AbstractFloat(0.1875))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(12.)))),
             Token::Number(Ok(Number::AbstractFloat(0.12109375))),
-            Token::Number(Err(NumberError::UnimplementedF16)),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(12.5)))),
         ],
     );
 
@@ -657,6 +690,27 @@ fn double_floats() {
     )
 }
 
+#[test]
+fn half_floats() {
+    sub_test(
+        "65504h 0x1p-24h 0x1.ffcp15h 1.5h",
+        &[
+            Token::Number(Ok(Number::F16(half::f16::MAX))),
+            Token::Number(Ok(Number::F16(half::f16::MIN_POSITIVE_SUBNORMAL))),
+            Token::Number(Ok(Number::F16(half::f16::MAX))),
+            Token::Number(Ok(Number::F16(half::f16::from_f32(1.5)))),
+        ],
+    );
+    sub_test(
+        "65520h 0x1p16h 0x1.001p0h",
+        &[
+            Token::Number(Err(NumberError::NotRepresentable)),
+            Token::Number(Err(NumberError::NotRepresentable)),
+            Token::Number(Err(NumberError::NotRepresentable)),
+        ],
+    );
+}
+
 #[test]
 fn test_tokens() {
     sub_test("id123_OK", &[Token::Word("id123_OK")]);
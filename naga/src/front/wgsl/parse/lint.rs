@@ -0,0 +1,152 @@
+//! Warnings about suspicious but valid code in function bodies.
+//!
+//! These run on the AST of each function right after it is parsed, and add
+//! [`Diagnostic`]s at [`Severity::Warning`] rather than failing:
+//!
+//! - local `let`, `var` and `const` declarations that are never referred to,
+//!   unless their name starts with an underscore, and
+//!
+//! - statements following a `return`, `break`, `continue` or `discard` in
+//!   the same block, which can never run.
+//!
+//! WGSL leaves two-part triggering rule names to implementations, so these
+//! lints are controlled by the `naga.unused_variable` and
+//! `naga.unreachable_code` rules. A `diagnostic(…)` directive or function
+//! attribute can turn them off, or make them errors.
+
+use super::ast;
+use crate::diagnostic_filter::{
+    Diagnostic, DiagnosticFilterNode, FilterableTriggeringRule, Severity,
+};
+use crate::front::wgsl::error::Error;
+use crate::{Arena, FastHashSet, Handle};
+
+/// The triggering rule for unused local declarations.
+const UNUSED_VARIABLE: [&str; 2] = ["naga", "unused_variable"];
+
+/// The triggering rule for unreachable statements.
+const UNREACHABLE_CODE: [&str; 2] = ["naga", "unreachable_code"];
+
+/// Lints the body of `function`.
+///
+/// The expressions of `function` are those of `expressions` from
+/// `first_expression` on. Its diagnostic filters are in
+/// `diagnostic_filters`.
+pub(super) fn lint_function<'a>(
+    function: &ast::Function<'a>,
+    expressions: &Arena<ast::Expression<'a>>,
+    first_expression: usize,
+    diagnostic_filters: &Arena<DiagnosticFilterNode>,
+    source: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Result<(), Error<'a>> {
+    let used = expressions
+        .iter()
+        .skip(first_expression)
+        .filter_map(|(_, expression)| match *expression {
+            ast::Expression::Ident(ast::IdentExpr::Local(local)) => Some(local),
+            _ => None,
+        })
+        .collect();
+    let severity = |[namespace, name]: [&str; 2]| {
+        let rule = FilterableTriggeringRule::User(Box::new([namespace.into(), name.into()]));
+        DiagnosticFilterNode::search_rule(
+            function.diagnostic_filter_leaf,
+            diagnostic_filters,
+            &rule,
+        )
+        .unwrap_or(Severity::Warning)
+    };
+    let mut linter = Linter {
+        used,
+        unused_variable: severity(UNUSED_VARIABLE),
+        unreachable_code: severity(UNREACHABLE_CODE),
+        source,
+        diagnostics,
+    };
+    linter.block(&function.body)
+}
+
+struct Linter<'s> {
+    used: FastHashSet<Handle<ast::Local>>,
+    unused_variable: Severity,
+    unreachable_code: Severity,
+    source: &'s str,
+    diagnostics: &'s mut Vec<Diagnostic>,
+}
+
+impl Linter<'_> {
+    fn block<'a>(&mut self, block: &ast::Block<'a>) -> Result<(), Error<'a>> {
+        let mut terminator = None;
+        for statement in block.stmts.iter() {
+            if let Some(terminator) = terminator.take() {
+                self.unreachable_code.report_wgsl_parse_diag(
+                    Error::UnreachableCode {
+                        statement: statement.span,
+                        terminator,
+                    },
+                    self.source,
+                    self.diagnostics,
+                )?;
+                // Report each block only once.
+                break;
+            }
+
+            match statement.kind {
+                ast::StatementKind::LocalDecl(ref decl) => self.local(decl)?,
+                ast::StatementKind::Block(ref block) => self.block(block)?,
+                ast::StatementKind::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.block(accept)?;
+                    self.block(reject)?;
+                }
+                ast::StatementKind::Switch { ref cases, .. } => {
+                    for case in cases {
+                        self.block(&case.body)?;
+                    }
+                }
+                ast::StatementKind::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.block(body)?;
+                    self.block(continuing)?;
+                }
+                ast::StatementKind::Break
+                | ast::StatementKind::Continue
+                | ast::StatementKind::Return { .. }
+                | ast::StatementKind::Kill => terminator = Some(statement.span),
+                ast::StatementKind::Call { .. }
+                | ast::StatementKind::Assign { .. }
+                | ast::StatementKind::Increment(_)
+                | ast::StatementKind::Decrement(_)
+                | ast::StatementKind::Phony(_)
+                | ast::StatementKind::ConstAssert(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn local<'a>(&mut self, decl: &ast::LocalDecl<'a>) -> Result<(), Error<'a>> {
+        let (kind, name, handle) = match *decl {
+            ast::LocalDecl::Var(ref var) => ("variable", var.name, var.handle),
+            ast::LocalDecl::Let(ref l) => ("variable", l.name, l.handle),
+            ast::LocalDecl::Const(ref c) => ("constant", c.name, c.handle),
+        };
+        if self.used.contains(&handle) || name.name.starts_with('_') {
+            return Ok(());
+        }
+        self.unused_variable.report_wgsl_parse_diag(
+            Error::UnusedLocal {
+                kind,
+                name: name.span,
+            },
+            self.source,
+            self.diagnostics,
+        )
+    }
+}
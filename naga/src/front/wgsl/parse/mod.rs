@@ -1,10 +1,10 @@
 use crate::diagnostic_filter::{
-    self, DiagnosticFilter, DiagnosticFilterMap, DiagnosticFilterNode, FilterableTriggeringRule,
-    ShouldConflictOnFullDuplicate, StandardFilterableTriggeringRule,
+    self, Diagnostic, DiagnosticFilter, DiagnosticFilterMap, DiagnosticFilterNode,
+    FilterableTriggeringRule, ShouldConflictOnFullDuplicate, StandardFilterableTriggeringRule,
 };
 use crate::front::wgsl::error::{DiagnosticAttributeNotSupportedPosition, Error, ExpectedToken};
 use crate::front::wgsl::parse::directive::enable_extension::{
-    EnableExtension, EnableExtensions, UnimplementedEnableExtension,
+    EnableExtension, EnableExtensions, ImplementedEnableExtension,
 };
 use crate::front::wgsl::parse::directive::language_extension::LanguageExtension;
 use crate::front::wgsl::parse::directive::DirectiveKind;
@@ -13,11 +13,13 @@ use crate::front::wgsl::parse::number::Number;
 use crate::front::wgsl::Scalar;
 use crate::front::SymbolTable;
 use crate::{Arena, FastIndexSet, Handle, ShaderStage, Span};
+use std::ops::Range;
 
 pub mod ast;
 pub mod conv;
 pub mod directive;
 pub mod lexer;
+mod lint;
 pub mod number;
 
 /// State for constructing an AST expression.
@@ -174,7 +176,7 @@ impl<T> ParsedAttribute<T> {
 #[derive(Default)]
 struct BindingParser<'a> {
     location: ParsedAttribute<Handle<ast::Expression<'a>>>,
-    second_blend_source: ParsedAttribute<bool>,
+    blend_src: ParsedAttribute<Handle<ast::Expression<'a>>>,
     built_in: ParsedAttribute<crate::BuiltIn>,
     interpolation: ParsedAttribute<crate::Interpolation>,
     sampling: ParsedAttribute<crate::Sampling>,
@@ -200,8 +202,14 @@ impl<'a> BindingParser<'a> {
             "builtin" => {
                 lexer.expect(Token::Paren('('))?;
                 let (raw, span) = lexer.next_ident_with_span()?;
-                self.built_in
-                    .set(conv::map_built_in(raw, span)?, name_span)?;
+                let built_in = conv::map_built_in(raw, span)?;
+                if built_in == crate::BuiltIn::ClipDistance {
+                    lexer.require_enable_extension(
+                        ImplementedEnableExtension::ClipDistances,
+                        span,
+                    )?;
+                }
+                self.built_in.set(built_in, name_span)?;
                 lexer.expect(Token::Paren(')'))?;
             }
             "interpolate" => {
@@ -216,8 +224,15 @@ impl<'a> BindingParser<'a> {
                 }
                 lexer.expect(Token::Paren(')'))?;
             }
-            "second_blend_source" => {
-                self.second_blend_source.set(true, name_span)?;
+            "blend_src" => {
+                lexer.require_enable_extension(
+                    ImplementedEnableExtension::DualSourceBlending,
+                    name_span,
+                )?;
+                lexer.expect(Token::Paren('('))?;
+                self.blend_src
+                    .set(parser.general_expression(lexer, ctx)?, name_span)?;
+                lexer.expect(Token::Paren(')'))?;
             }
             "invariant" => {
                 self.invariant.set(true, name_span)?;
@@ -234,9 +249,10 @@ impl<'a> BindingParser<'a> {
             self.interpolation.value,
             self.sampling.value,
             self.invariant.value.unwrap_or_default(),
+            self.blend_src.value,
         ) {
-            (None, None, None, None, false) => Ok(None),
-            (Some(location), None, interpolation, sampling, false) => {
+            (None, None, None, None, false, None) => Ok(None),
+            (Some(location), None, interpolation, sampling, false, blend_src) => {
                 // Before handing over the completed `Module`, we call
                 // `apply_default_interpolation` to ensure that the interpolation and
                 // sampling have been explicitly specified on all vertex shader output and fragment
@@ -245,27 +261,43 @@ impl<'a> BindingParser<'a> {
                     location,
                     interpolation,
                     sampling,
-                    second_blend_source: self.second_blend_source.value.unwrap_or(false),
+                    blend_src,
                 }))
             }
-            (None, Some(crate::BuiltIn::Position { .. }), None, None, invariant) => {
+            (None, Some(crate::BuiltIn::Position { .. }), None, None, invariant, None) => {
                 Ok(Some(ast::Binding::BuiltIn(crate::BuiltIn::Position {
                     invariant,
                 })))
             }
-            (None, Some(built_in), None, None, false) => Ok(Some(ast::Binding::BuiltIn(built_in))),
-            (_, _, _, _, _) => Err(Error::InconsistentBinding(span)),
+            (None, Some(built_in), None, None, false, None) => {
+                Ok(Some(ast::Binding::BuiltIn(built_in)))
+            }
+            (_, _, _, _, _, _) => Err(Error::InconsistentBinding(span)),
         }
     }
 }
 
 pub struct Parser {
     rules: Vec<(Rule, usize)>,
+    /// Diagnostics at lesser severities than [`Severity::Error`], collected
+    /// over the whole translation unit.
+    ///
+    /// [`Severity::Error`]: diagnostic_filter::Severity::Error
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl Parser {
     pub const fn new() -> Self {
-        Parser { rules: Vec::new() }
+        Parser {
+            rules: Vec::new(),
+            diagnostics: Vec::new(),
+        }
+    }
+
+    /// Returns the diagnostics reported by the last parse that didn't make it
+    /// fail, like warnings.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
     }
 
     fn reset(&mut self) {
@@ -328,7 +360,7 @@ impl Parser {
         span: Span,
         ctx: &mut ExpressionContext<'a, '_, '_>,
     ) -> Result<Option<ast::ConstructorType<'a>>, Error<'a>> {
-        if let Some(scalar) = conv::get_scalar_type(word) {
+        if let Some(scalar) = conv::get_scalar_type(&lexer.enable_extensions, span, word)? {
             return Ok(Some(ast::ConstructorType::Scalar(scalar)));
         }
 
@@ -357,6 +389,14 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "vec2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Vector {
+                    size: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "vec3" => ast::ConstructorType::PartialVector {
                 size: crate::VectorSize::Tri,
             },
@@ -381,6 +421,14 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "vec3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Vector {
+                    size: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "vec4" => ast::ConstructorType::PartialVector {
                 size: crate::VectorSize::Quad,
             },
@@ -405,6 +453,14 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "vec4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Vector {
+                    size: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat2x2" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Bi,
                 rows: crate::VectorSize::Bi,
@@ -417,6 +473,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat2x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat2x3" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Bi,
                 rows: crate::VectorSize::Tri,
@@ -429,6 +494,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat2x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat2x4" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Bi,
                 rows: crate::VectorSize::Quad,
@@ -441,6 +515,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat2x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat3x2" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Tri,
                 rows: crate::VectorSize::Bi,
@@ -453,6 +536,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat3x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat3x3" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Tri,
                 rows: crate::VectorSize::Tri,
@@ -465,6 +557,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat3x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat3x4" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Tri,
                 rows: crate::VectorSize::Quad,
@@ -477,6 +578,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat3x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat4x2" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Quad,
                 rows: crate::VectorSize::Bi,
@@ -489,6 +599,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat4x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat4x3" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Quad,
                 rows: crate::VectorSize::Tri,
@@ -501,6 +620,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat4x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "mat4x4" => ast::ConstructorType::PartialMatrix {
                 columns: crate::VectorSize::Quad,
                 rows: crate::VectorSize::Quad,
@@ -513,6 +641,15 @@ impl Parser {
                     ty_span: Span::UNDEFINED,
                 }))
             }
+            "mat4x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                return Ok(Some(ast::ConstructorType::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }));
+            }
             "array" => ast::ConstructorType::PartialArray,
             "atomic"
             | "binding_array"
@@ -707,15 +844,12 @@ impl Parser {
             }
             (Token::Number(res), span) => {
                 let _ = lexer.next();
-                let num = res.map_err(|err| match err {
-                    super::error::NumberError::UnimplementedF16 => {
-                        Error::EnableExtensionNotEnabled {
-                            kind: EnableExtension::Unimplemented(UnimplementedEnableExtension::F16),
-                            span,
-                        }
-                    }
-                    err => Error::BadNumber(span, err),
-                })?;
+                let num = res.map_err(|err| Error::BadNumber(span, err))?;
+
+                if let Number::F16(_) = num {
+                    lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                }
+
                 ast::Expression::Literal(ast::Literal::Number(num))
             }
             (Token::Word("RAY_FLAG_NONE"), _) => {
@@ -1236,9 +1370,10 @@ impl Parser {
         &mut self,
         lexer: &mut Lexer<'a>,
         word: &'a str,
+        span: Span,
         ctx: &mut ExpressionContext<'a, '_, '_>,
     ) -> Result<Option<ast::Type<'a>>, Error<'a>> {
-        if let Some(scalar) = conv::get_scalar_type(word) {
+        if let Some(scalar) = conv::get_scalar_type(&lexer.enable_extensions, span, word)? {
             return Ok(Some(ast::Type::Scalar(scalar)));
         }
 
@@ -1266,6 +1401,14 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "vec2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Vector {
+                    size: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "vec3" => {
                 let (ty, ty_span) = self.singular_generic(lexer, ctx)?;
                 ast::Type::Vector {
@@ -1289,6 +1432,14 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "vec3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Vector {
+                    size: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "vec4" => {
                 let (ty, ty_span) = self.singular_generic(lexer, ctx)?;
                 ast::Type::Vector {
@@ -1312,6 +1463,14 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "vec4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Vector {
+                    size: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat2x2" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Bi, crate::VectorSize::Bi)?
             }
@@ -1321,6 +1480,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat2x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat2x3" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Bi, crate::VectorSize::Tri)?
             }
@@ -1330,6 +1498,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat2x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat2x4" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Bi, crate::VectorSize::Quad)?
             }
@@ -1339,6 +1516,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat2x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Bi,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat3x2" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Tri, crate::VectorSize::Bi)?
             }
@@ -1348,6 +1534,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat3x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat3x3" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Tri, crate::VectorSize::Tri)?
             }
@@ -1357,6 +1552,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat3x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat3x4" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Tri, crate::VectorSize::Quad)?
             }
@@ -1366,6 +1570,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat3x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Tri,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat4x2" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Quad, crate::VectorSize::Bi)?
             }
@@ -1375,6 +1588,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat4x2h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Bi,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat4x3" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Quad, crate::VectorSize::Tri)?
             }
@@ -1384,6 +1606,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat4x3h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Tri,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "mat4x4" => {
                 self.matrix_with_type(lexer, ctx, crate::VectorSize::Quad, crate::VectorSize::Quad)?
             }
@@ -1393,6 +1624,15 @@ impl Parser {
                 ty: ctx.new_scalar(Scalar::F32),
                 ty_span: Span::UNDEFINED,
             },
+            "mat4x4h" => {
+                lexer.require_enable_extension(ImplementedEnableExtension::F16, span)?;
+                ast::Type::Matrix {
+                    columns: crate::VectorSize::Quad,
+                    rows: crate::VectorSize::Quad,
+                    ty: ctx.new_scalar(Scalar::F16),
+                    ty_span: Span::UNDEFINED,
+                }
+            }
             "atomic" => {
                 let scalar = lexer.next_scalar_generic()?;
                 ast::Type::Atomic(scalar)
@@ -1648,7 +1888,7 @@ impl Parser {
 
         let (name, span) = lexer.next_ident_with_span()?;
 
-        let ty = match self.type_decl_impl(lexer, name, ctx)? {
+        let ty = match self.type_decl_impl(lexer, name, span, ctx)? {
             Some(ty) => ty,
             None => {
                 ctx.unresolved.insert(ast::Dependency {
@@ -2339,6 +2579,7 @@ impl Parser {
         // read function name
         let fun_name = lexer.next_ident()?;
 
+        let first_expression = out.expressions.len();
         let mut locals = Arena::new();
 
         let mut ctx = ExpressionContext {
@@ -2406,6 +2647,14 @@ impl Parser {
             body,
             diagnostic_filter_leaf,
         };
+        lint::lint_function(
+            &fun,
+            &out.expressions,
+            first_expression,
+            &out.diagnostic_filters,
+            lexer.source,
+            &mut self.diagnostics,
+        )?;
 
         // done
         self.pop_rule_span(lexer);
@@ -2702,22 +2951,80 @@ impl Parser {
     }
 
     pub fn parse<'a>(&mut self, source: &'a str) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
-        self.reset();
+        let whole = 0..source.len();
+        self.parse_modules(source, std::slice::from_ref(&whole))
+    }
 
-        let mut lexer = Lexer::new(source);
+    /// Parse several modules into a single translation unit.
+    ///
+    /// Each of the `modules` ranges of `source` is parsed as a module of its
+    /// own, with its own directives, but the global declarations of all the
+    /// modules share one namespace. Spans are offsets into `source`.
+    ///
+    /// A module's global diagnostic filters only apply to its own
+    /// declarations, so unless there is just one module, the translation
+    /// unit itself has none. Its enable-extensions are those of all the
+    /// modules together.
+    pub fn parse_modules<'a>(
+        &mut self,
+        source: &'a str,
+        modules: &[Range<usize>],
+    ) -> Result<ast::TranslationUnit<'a>, Error<'a>> {
+        self.diagnostics.clear();
         let mut tu = ast::TranslationUnit::default();
         let mut enable_extensions = EnableExtensions::empty();
-        let mut diagnostic_filters = DiagnosticFilterMap::new();
 
-        // Parse directives.
+        // Parse the directives of all modules first, so that declarations
+        // can use any module's enable-extensions.
+        let mut lexers = Vec::with_capacity(modules.len());
+        for module in modules {
+            self.reset();
+            let mut lexer = Lexer::new_at(&source[..module.end], module.start);
+            let mut diagnostic_filters = DiagnosticFilterMap::new();
+            lexer.enable_extensions = self.directives(&mut lexer, &mut diagnostic_filters)?;
+            enable_extensions.extend(&lexer.enable_extensions);
+            let diagnostic_filter_leaf = Self::write_diagnostic_filters(
+                &mut tu.diagnostic_filters,
+                diagnostic_filters,
+                None,
+            );
+            lexers.push((lexer, diagnostic_filter_leaf));
+        }
+
+        tu.enable_extensions = enable_extensions;
+
+        for (mut lexer, diagnostic_filter_leaf) in lexers {
+            self.reset();
+            // Declarations take their module's filters from here.
+            tu.diagnostic_filter_leaf = diagnostic_filter_leaf;
+            while lexer.peek().0 != Token::End {
+                self.global_decl(&mut lexer, &mut tu)?;
+            }
+        }
+        if modules.len() != 1 {
+            tu.diagnostic_filter_leaf = None;
+        }
+
+        Ok(tu)
+    }
+
+    /// Parse the directives at the start of a module, returning the
+    /// enable-extensions they request.
+    fn directives<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+        diagnostic_filters: &mut DiagnosticFilterMap,
+    ) -> Result<EnableExtensions, Error<'a>> {
+        let mut enable_extensions = EnableExtensions::empty();
+
         while let Ok((ident, _directive_ident_span)) = lexer.peek_ident_with_span() {
             if let Some(kind) = DirectiveKind::from_ident(ident) {
-                self.push_rule_span(Rule::Directive, &mut lexer);
+                self.push_rule_span(Rule::Directive, lexer);
                 let _ = lexer.next_ident_with_span().unwrap();
                 match kind {
                     DirectiveKind::Diagnostic => {
-                        let diagnostic_filter = self.diagnostic_filter(&mut lexer)?;
-                        let span = self.peek_rule_span(&lexer);
+                        let diagnostic_filter = self.diagnostic_filter(lexer)?;
+                        let span = self.peek_rule_span(lexer);
                         diagnostic_filters.add(
                             diagnostic_filter,
                             span,
@@ -2726,7 +3033,7 @@ impl Parser {
                         lexer.expect(Token::Separator(';'))?;
                     }
                     DirectiveKind::Enable => {
-                        self.directive_ident_list(&mut lexer, |ident, span| {
+                        self.directive_ident_list(lexer, |ident, span| {
                             let kind = EnableExtension::from_ident(ident, span)?;
                             let extension = match kind {
                                 EnableExtension::Implemented(kind) => kind,
@@ -2742,7 +3049,7 @@ impl Parser {
                         })?;
                     }
                     DirectiveKind::Requires => {
-                        self.directive_ident_list(&mut lexer, |ident, span| {
+                        self.directive_ident_list(lexer, |ident, span| {
                             match LanguageExtension::from_ident(ident) {
                                 Some(LanguageExtension::Implemented(_kind)) => {
                                     // NOTE: No further validation is needed for an extension, so
@@ -2759,29 +3066,13 @@ impl Parser {
                         })?;
                     }
                 }
-                self.pop_rule_span(&lexer);
+                self.pop_rule_span(lexer);
             } else {
                 break;
             }
         }
 
-        lexer.enable_extensions = enable_extensions.clone();
-        tu.enable_extensions = enable_extensions;
-        tu.diagnostic_filter_leaf =
-            Self::write_diagnostic_filters(&mut tu.diagnostic_filters, diagnostic_filters, None);
-
-        loop {
-            match self.global_decl(&mut lexer, &mut tu) {
-                Err(error) => return Err(error),
-                Ok(()) => {
-                    if lexer.peek().0 == Token::End {
-                        break;
-                    }
-                }
-            }
-        }
-
-        Ok(tu)
+        Ok(enable_extensions)
     }
 
     const fn increase_brace_nesting(
@@ -2810,7 +3101,10 @@ impl Parser {
         Ok(brace_nesting_level + 1)
     }
 
-    fn diagnostic_filter<'a>(&self, lexer: &mut Lexer<'a>) -> Result<DiagnosticFilter, Error<'a>> {
+    fn diagnostic_filter<'a>(
+        &mut self,
+        lexer: &mut Lexer<'a>,
+    ) -> Result<DiagnosticFilter, Error<'a>> {
         lexer.expect(Token::Paren('('))?;
 
         let (severity_control_name, severity_control_name_span) = lexer.next_ident_with_span()?;
@@ -2836,6 +3130,7 @@ impl Parser {
                 diagnostic_filter::Severity::Warning.report_wgsl_parse_diag(
                     Error::UnknownDiagnosticRuleName(diagnostic_rule_name_span),
                     lexer.source,
+                    &mut self.diagnostics,
                 )?;
                 FilterableTriggeringRule::Unknown(diagnostic_rule_name.into())
             }
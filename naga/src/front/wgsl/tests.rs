@@ -827,3 +827,321 @@ error: found conflicting `diagnostic(…)` rule(s)
         }
     }
 }
+
+mod link {
+    use crate::diagnostic_filter::Severity;
+    use crate::front::wgsl::Linker;
+
+    #[test]
+    fn cross_module_references() {
+        let mut linker = Linker::new();
+        linker
+            .add_module(
+                "main.wgsl",
+                "
+@group(0) @binding(0) var<storage, read_write> params: Params;
+@compute @workgroup_size(1)
+fn main() { params.value = twice(params.value); }",
+            )
+            .add_module(
+                "common.wgsl",
+                "
+const SCALE: f32 = 2.0;
+fn twice(x: f32) -> f32 { return x * SCALE; }
+struct Params { value: f32 }",
+            );
+        let module = linker.link().unwrap();
+        assert_eq!(module.functions.len(), 1);
+        assert_eq!(module.entry_points.len(), 1);
+
+        let (handle, _) = module.functions.iter().next().unwrap();
+        let span = module.functions.get_span(handle);
+        let (index, range) = linker.locate(span).unwrap();
+        let (name, source) = linker.module(index).unwrap();
+        assert_eq!(name, "common.wgsl");
+        assert!(source[range].starts_with("fn twice"));
+    }
+
+    #[test]
+    fn enable_extensions_are_per_module() {
+        let mut linker = Linker::new();
+        linker
+            .add_module("a.wgsl", "enable f16; fn half() -> f16 { return 1.0h; }")
+            .add_module("b.wgsl", "fn other() -> f16 { return half(); }");
+        let error = linker.link().unwrap_err();
+        assert_eq!(error.code(), "enable-extension-not-enabled");
+        let (index, _) = linker.locate(error.labels().next().unwrap().0).unwrap();
+        assert_eq!(linker.module(index).unwrap().0, "b.wgsl");
+    }
+
+    #[test]
+    fn diagnostic_filters_are_per_module() {
+        let mut linker = Linker::new();
+        linker
+            .add_module(
+                "a.wgsl",
+                "diagnostic(off, derivative_uniformity); fn a() {}",
+            )
+            .add_module("b.wgsl", "fn b() {}");
+        let module = linker.link().unwrap();
+        assert_eq!(module.diagnostic_filter_leaf, None);
+        for (_, function) in module.functions.iter() {
+            let leaf = function.diagnostic_filter_leaf;
+            match function.name.as_deref() {
+                Some("a") => assert!(leaf.is_some()),
+                Some("b") => assert_eq!(leaf, None),
+                _ => unreachable!(),
+            }
+        }
+    }
+
+    #[test]
+    fn diagnostic_filters_do_not_conflict_across_modules() {
+        let mut linker = Linker::new();
+        linker
+            .add_module(
+                "a.wgsl",
+                "diagnostic(off, derivative_uniformity); fn a() {}",
+            )
+            .add_module(
+                "b.wgsl",
+                "diagnostic(error, derivative_uniformity); fn b() {}",
+            );
+        let module = linker.link().unwrap();
+        let severities: Vec<_> = module
+            .functions
+            .iter()
+            .map(|(_, function)| {
+                let leaf = function.diagnostic_filter_leaf.unwrap();
+                module.diagnostic_filters[leaf].inner.new_severity
+            })
+            .collect();
+        assert_eq!(severities, [Severity::Off, Severity::Error]);
+    }
+
+    #[test]
+    fn diagnostics() {
+        let mut linker = Linker::new();
+        linker
+            .add_module(
+                "a.wgsl",
+                "diagnostic(off, naga.unused_variable); fn f() { let x = 1; }",
+            )
+            .add_module("b.wgsl", "fn g() {\n    let a = 1;\n}");
+        linker.link().unwrap();
+        assert_eq!(linker.diagnostics().len(), 1);
+        assert_eq!(
+            linker.diagnostics()[0].emit_to_string_with_linker(&linker),
+            "\
+warning: unused variable `a`
+  ┌─ b.wgsl:2:9
+  │
+2 │     let a = 1;
+  │         ^ declared here
+  │
+  = note: if this is intentional, prefix it with an underscore: `_a`
+
+"
+        );
+    }
+
+    #[test]
+    fn redefinition() {
+        let mut linker = Linker::new();
+        linker
+            .add_module("a.wgsl", "fn f() {}")
+            .add_module("b.wgsl", "const x = 1;\nfn f() {}");
+        let error = linker.link().unwrap_err();
+        assert_eq!(
+            error.emit_to_string_with_linker(&linker),
+            "\
+error: redefinition of `f`
+  ┌─ b.wgsl:2:4
+  │
+2 │ fn f() {}
+  │    ^ redefinition of `f`
+  │
+  ┌─ a.wgsl:1:4
+  │
+1 │ fn f() {}
+  │    ^ previous definition of `f`
+
+"
+        );
+    }
+}
+
+mod diagnostics {
+    use crate::diagnostic_filter::Severity;
+    use crate::front::wgsl::Frontend;
+
+    /// Parses `source`, which must succeed, and returns its diagnostics.
+    #[track_caller]
+    fn warnings(source: &str) -> Vec<String> {
+        let mut frontend = Frontend::new();
+        frontend.parse(source).unwrap();
+        frontend
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                assert_eq!(diagnostic.severity(), Severity::Warning);
+                diagnostic.emit_to_string(source)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn unused_locals() {
+        let source = "
+fn f(unused_argument: i32) -> i32 {
+    let a = 1;
+    var b = 2;
+    const c = 3;
+    let _d = 4;
+    return b;
+}
+";
+        assert_eq!(
+            warnings(source),
+            [
+                "\
+warning: unused variable `a`
+  ┌─ wgsl:3:9
+  │
+3 │     let a = 1;
+  │         ^ declared here
+  │
+  = note: if this is intentional, prefix it with an underscore: `_a`
+
+",
+                "\
+warning: unused constant `c`
+  ┌─ wgsl:5:11
+  │
+5 │     const c = 3;
+  │           ^ declared here
+  │
+  = note: if this is intentional, prefix it with an underscore: `_c`
+
+"
+            ]
+        );
+    }
+
+    #[test]
+    fn unreachable_code() {
+        let source = "
+fn f(x: i32) -> i32 {
+    loop {
+        break;
+        _ = x;
+    }
+    return x;
+    _ = x + 1;
+    _ = x + 2;
+}
+";
+        assert_eq!(
+            warnings(source),
+            [
+                "\
+warning: unreachable code
+  ┌─ wgsl:4:9
+  │
+4 │         break;
+  │         ^^^^^^ any code following this statement is unreachable
+5 │         _ = x;
+  │         ^^^^^^ unreachable statement
+
+",
+                "\
+warning: unreachable code
+  ┌─ wgsl:7:5
+  │
+7 │     return x;
+  │     ^^^^^^^^^ any code following this statement is unreachable
+8 │     _ = x + 1;
+  │     ^^^^^^^^^^ unreachable statement
+
+"
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_diagnostic_rule() {
+        let source = "diagnostic(off, wat_is_this);";
+        assert_eq!(
+            warnings(source),
+            ["\
+warning: unknown `diagnostic(…)` rule name `wat_is_this`
+  ┌─ wgsl:1:17
+  │
+1 │ diagnostic(off, wat_is_this);
+  │                 ^^^^^^^^^^^ not a valid diagnostic rule name
+  │
+  = note: See available trigger rules at <https://www.w3.org/TR/WGSL/#filterable-triggering-rules>.
+
+"]
+        );
+    }
+
+    #[test]
+    fn lint_filters() {
+        let source = "
+diagnostic(off, naga.unused_variable);
+
+fn f() {
+    let a = 1;
+}
+
+@diagnostic(off, naga.unreachable_code)
+fn g() {
+    return;
+    _ = 1;
+}
+
+@diagnostic(warning, naga.unused_variable)
+fn h() {
+    let b = 2;
+    return;
+    _ = 3;
+}
+";
+        let warnings = warnings(source);
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with("warning: unused variable `b`"));
+        assert_eq!(
+            warnings[1],
+            "\
+warning: unreachable code
+   ┌─ wgsl:17:5
+   │
+17 │     return;
+   │     ^^^^^^^ any code following this statement is unreachable
+18 │     _ = 3;
+   │     ^^^^^^ unreachable statement
+
+"
+        );
+
+        let source = "
+diagnostic(error, naga.unused_variable);
+
+fn f() {
+    let a = 1;
+}
+";
+        let error = Frontend::new().parse(source).unwrap_err();
+        assert_eq!(error.message(), "unused variable `a`");
+    }
+
+    #[test]
+    fn cleared_between_parses() {
+        let mut frontend = Frontend::new();
+        frontend.parse("fn f() { let a = 1; }").unwrap();
+        assert_eq!(frontend.diagnostics().len(), 1);
+        frontend.parse("fn f() { let a = 1; _ = a; }").unwrap();
+        assert!(frontend.diagnostics().is_empty());
+    }
+}
@@ -0,0 +1,1003 @@
+//! Running an invocation, statement by statement.
+
+use super::{
+    image, math, ops,
+    value::{self, column_stride, Memory, Pointer, Value},
+    Error, Resource, Resources, Result, Texture,
+};
+use crate::{
+    valid::{FunctionInfo, ModuleInfo},
+    AddressSpace, ArraySize, AtomicFunction, BinaryOperator, Block, Expression, Function, Handle,
+    ImageClass, ImageDimension, ImageQuery, Literal, Module, RelationalFunction, Statement,
+    SwitchCase, SwitchValue, TypeInner,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(super) enum Status {
+    Running,
+    /// Waiting at a barrier for the rest of the workgroup.
+    Barrier,
+    Finished,
+}
+
+/// The state of one invocation of an entry point.
+pub(super) struct Invocation<'a> {
+    /// The memory of private variables, indexed by handle.
+    private: Vec<Vec<u8>>,
+    /// The call stack. The entry point is at the bottom.
+    frames: Vec<Frame<'a>>,
+    /// Whether the invocation is between the two barriers of a
+    /// [`WorkGroupUniformLoad`](Statement::WorkGroupUniformLoad) statement.
+    uniform_load: bool,
+    /// The number of steps the invocation has taken, which is limited by
+    /// [`Executor::step_limit`].
+    steps: u64,
+    pub status: Status,
+}
+
+impl Invocation<'_> {
+    pub const fn new(private: Vec<Vec<u8>>) -> Self {
+        Invocation {
+            private,
+            frames: Vec::new(),
+            uniform_load: false,
+            steps: 0,
+            status: Status::Running,
+        }
+    }
+}
+
+/// A call to a function.
+struct Frame<'a> {
+    function: &'a Function,
+    info: &'a FunctionInfo,
+    arguments: Vec<Value>,
+    /// The memory of local variables, indexed by handle.
+    locals: Vec<Vec<u8>>,
+    /// The values of the function's expressions, indexed by handle.
+    ///
+    /// An `Emit` statement evaluates its expressions again, which keeps this
+    /// up to date in loops.
+    values: Vec<Option<Value>>,
+    /// The blocks being run, innermost last.
+    blocks: Vec<Cursor<'a>>,
+    /// The caller's expression that receives the value this call returns.
+    result: Option<Handle<Expression>>,
+}
+
+/// A position in a block being run.
+struct Cursor<'a> {
+    statements: &'a [Statement],
+    /// The index of the next statement to run.
+    next: usize,
+    kind: BlockKind<'a>,
+}
+
+#[derive(Clone, Copy)]
+enum BlockKind<'a> {
+    /// A function body, a nested block, or a branch of an `If`.
+    Plain,
+    /// The body or continuing block of a `Loop`.
+    Loop {
+        body: &'a Block,
+        continuing: &'a Block,
+        break_if: Option<Handle<Expression>>,
+        in_continuing: bool,
+    },
+    /// A case of a `Switch`, which may fall through to the next one.
+    Switch {
+        cases: &'a [SwitchCase],
+        index: usize,
+    },
+}
+
+/// Runs an invocation, with access to the memory it shares with others.
+pub(super) struct Executor<'a, 'i, 'r> {
+    pub module: &'a Module,
+    pub info: &'a ModuleInfo,
+    pub invocation: &'i mut Invocation<'a>,
+    /// The memory of workgroup variables, indexed by handle.
+    pub workgroup: &'i mut [Vec<u8>],
+    pub resources: &'i mut Resources<'r>,
+    /// The number of steps an invocation may take before it is stopped with
+    /// [`Error::StepLimit`].
+    pub step_limit: u64,
+}
+
+impl<'a, 'r> Executor<'a, '_, 'r> {
+    /// Initializes private variables and calls the entry point `function`.
+    pub fn start(
+        &mut self,
+        function: &'a Function,
+        info: &'a FunctionInfo,
+        arguments: Vec<Value>,
+    ) -> Result<()> {
+        let types = &self.module.types;
+        for (handle, var) in self.module.global_variables.iter() {
+            if let (AddressSpace::Private, Some(init)) = (var.space, var.init) {
+                let value = self.eval_global(init)?;
+                let memory = &mut self.invocation.private[handle.index()];
+                value::write(memory, 0, &types[var.ty].inner, types, &value)?;
+            }
+        }
+        self.call(function, info, arguments, None)
+    }
+
+    /// Runs the invocation until it reaches a barrier or finishes.
+    pub fn run(&mut self) -> Result<()> {
+        while self.invocation.status == Status::Running {
+            self.step()?;
+        }
+        Ok(())
+    }
+
+    fn frame(&self) -> &Frame<'a> {
+        self.invocation.frames.last().unwrap()
+    }
+
+    fn frame_mut(&mut self) -> &mut Frame<'a> {
+        self.invocation.frames.last_mut().unwrap()
+    }
+
+    fn cursor_mut(&mut self) -> &mut Cursor<'a> {
+        self.frame_mut().blocks.last_mut().unwrap()
+    }
+
+    fn push_block(&mut self, statements: &'a [Statement], kind: BlockKind<'a>) {
+        self.frame_mut().blocks.push(Cursor {
+            statements,
+            next: 0,
+            kind,
+        });
+    }
+
+    fn call(
+        &mut self,
+        function: &'a Function,
+        info: &'a FunctionInfo,
+        arguments: Vec<Value>,
+        result: Option<Handle<Expression>>,
+    ) -> Result<()> {
+        let types = &self.module.types;
+        let locals = function
+            .local_variables
+            .iter()
+            .map(|(_, local)| vec![0; types[local.ty].inner.size(self.module.to_ctx()) as usize])
+            .collect();
+        self.invocation.frames.push(Frame {
+            function,
+            info,
+            arguments,
+            locals,
+            values: vec![None; function.expressions.len()],
+            blocks: Vec::new(),
+            result,
+        });
+        self.push_block(&function.body, BlockKind::Plain);
+
+        for (handle, local) in function.local_variables.iter() {
+            if let Some(init) = local.init {
+                let value = self.eval(init)?;
+                let memory = &mut self.frame_mut().locals[handle.index()];
+                value::write(memory, 0, &types[local.ty].inner, types, &value)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns from the current function with `value`.
+    fn finish_call(&mut self, value: Option<Value>) {
+        let frame = self.invocation.frames.pop().unwrap();
+        match self.invocation.frames.last_mut() {
+            Some(caller) => {
+                if let (Some(result), Some(value)) = (frame.result, value) {
+                    caller.values[result.index()] = Some(value);
+                }
+            }
+            None => self.invocation.status = Status::Finished,
+        }
+    }
+
+    fn step(&mut self) -> Result<()> {
+        if self.invocation.steps == self.step_limit {
+            return Err(Error::StepLimit(self.step_limit));
+        }
+        self.invocation.steps += 1;
+        let Some(cursor) = self.frame_mut().blocks.last_mut() else {
+            // The end of a function without a `Return` statement.
+            self.finish_call(None);
+            return Ok(());
+        };
+        let statements = cursor.statements;
+        match statements.get(cursor.next) {
+            Some(statement) => {
+                cursor.next += 1;
+                self.execute(statement)
+            }
+            None => self.end_block(),
+        }
+    }
+
+    fn end_block(&mut self) -> Result<()> {
+        let cursor = self.cursor_mut();
+        match cursor.kind {
+            BlockKind::Plain => {
+                self.frame_mut().blocks.pop();
+            }
+            BlockKind::Loop {
+                body,
+                continuing,
+                break_if,
+                in_continuing: false,
+            } => {
+                cursor.statements = continuing;
+                cursor.next = 0;
+                cursor.kind = BlockKind::Loop {
+                    body,
+                    continuing,
+                    break_if,
+                    in_continuing: true,
+                };
+            }
+            BlockKind::Loop {
+                body,
+                continuing,
+                break_if,
+                in_continuing: true,
+            } => {
+                let stop = match break_if {
+                    Some(condition) => self.eval(condition)?.scalar() == Literal::Bool(true),
+                    None => false,
+                };
+                let cursor = self.cursor_mut();
+                if stop {
+                    self.frame_mut().blocks.pop();
+                } else {
+                    cursor.statements = body;
+                    cursor.next = 0;
+                    cursor.kind = BlockKind::Loop {
+                        body,
+                        continuing,
+                        break_if,
+                        in_continuing: false,
+                    };
+                }
+            }
+            BlockKind::Switch { cases, index } => {
+                if cases[index].fall_through && index + 1 < cases.len() {
+                    cursor.statements = &cases[index + 1].body;
+                    cursor.next = 0;
+                    cursor.kind = BlockKind::Switch {
+                        cases,
+                        index: index + 1,
+                    };
+                } else {
+                    self.frame_mut().blocks.pop();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, statement: &'a Statement) -> Result<()> {
+        match *statement {
+            Statement::Emit(ref range) => {
+                for handle in range.clone() {
+                    self.frame_mut().values[handle.index()] = None;
+                    self.eval(handle)?;
+                }
+            }
+            Statement::Block(ref block) => self.push_block(block, BlockKind::Plain),
+            Statement::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => {
+                let block = if self.eval(condition)?.scalar() == Literal::Bool(true) {
+                    accept
+                } else {
+                    reject
+                };
+                self.push_block(block, BlockKind::Plain);
+            }
+            Statement::Switch {
+                selector,
+                ref cases,
+            } => {
+                let selector = self.eval(selector)?.scalar();
+                let matches = |case: &SwitchCase| match case.value {
+                    SwitchValue::I32(value) => selector == Literal::I32(value),
+                    SwitchValue::U32(value) => selector == Literal::U32(value),
+                    SwitchValue::Default => false,
+                };
+                let index = cases.iter().position(matches).or_else(|| {
+                    cases
+                        .iter()
+                        .position(|case| case.value == SwitchValue::Default)
+                });
+                if let Some(index) = index {
+                    self.push_block(&cases[index].body, BlockKind::Switch { cases, index });
+                }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+            } => self.push_block(
+                body,
+                BlockKind::Loop {
+                    body,
+                    continuing,
+                    break_if,
+                    in_continuing: false,
+                },
+            ),
+            Statement::Break => {
+                let blocks = &mut self.frame_mut().blocks;
+                while let Some(cursor) = blocks.pop() {
+                    if let BlockKind::Loop { .. } | BlockKind::Switch { .. } = cursor.kind {
+                        break;
+                    }
+                }
+            }
+            Statement::Continue => {
+                let blocks = &mut self.frame_mut().blocks;
+                while let Some(cursor) = blocks.last_mut() {
+                    if let BlockKind::Loop {
+                        body,
+                        continuing,
+                        break_if,
+                        ..
+                    } = cursor.kind
+                    {
+                        cursor.statements = continuing;
+                        cursor.next = 0;
+                        cursor.kind = BlockKind::Loop {
+                            body,
+                            continuing,
+                            break_if,
+                            in_continuing: true,
+                        };
+                        break;
+                    }
+                    blocks.pop();
+                }
+            }
+            Statement::Return { value } => {
+                let value = value.map(|value| self.eval(value)).transpose()?;
+                self.finish_call(value);
+            }
+            Statement::Kill => return Err(Error::Unsupported("`discard`")),
+            Statement::Barrier(_) => self.invocation.status = Status::Barrier,
+            Statement::Store { pointer, value } => {
+                let pointer = self.eval(pointer)?.pointer();
+                let inner = self.ty(value);
+                let value = self.eval(value)?;
+                let types = &self.module.types;
+                value::write(
+                    self.memory(pointer.memory)?,
+                    pointer.offset,
+                    inner,
+                    types,
+                    &value,
+                )?;
+            }
+            Statement::ImageStore {
+                image,
+                coordinate,
+                array_index,
+                value,
+            } => {
+                let coordinate = coordinate_of(&self.eval(coordinate)?)?;
+                let layer = self.layer(array_index)?;
+                let value = texel_of(self.eval(value)?);
+                let (texture, dim, _) = self.texture(image)?;
+                image::store(texture, dim, coordinate, layer, value)?;
+            }
+            Statement::Atomic {
+                pointer,
+                ref fun,
+                value,
+                result,
+            } => {
+                let pointer = self.eval(pointer)?.pointer();
+                let value = self.eval(value)?.scalar();
+                let compare = self.compare(fun)?;
+                let memory = self.memory(pointer.memory)?;
+                let old = value::read_scalar(memory, pointer.offset, value.scalar())?;
+                let (new, exchanged) = atomic(fun, old, value, compare)?;
+                value::write_scalar(memory, pointer.offset, new)?;
+                if let Some(result) = result {
+                    let result_value = match compare {
+                        Some(_) => Value::Composite(vec![
+                            Value::Scalar(old),
+                            Value::Scalar(Literal::Bool(exchanged)),
+                        ]),
+                        None => Value::Scalar(old),
+                    };
+                    self.frame_mut().values[result.index()] = Some(result_value);
+                }
+            }
+            Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                ref fun,
+                value,
+            } => {
+                let coordinate = coordinate_of(&self.eval(coordinate)?)?;
+                let layer = self.layer(array_index)?;
+                let value = self.eval(value)?.scalar();
+                let compare = self.compare(fun)?;
+                let (texture, dim, _) = self.texture(image)?;
+                let mut texel = image::load(texture, dim, coordinate, layer)?;
+                texel[0] = atomic(fun, texel[0], value, compare)?.0;
+                image::store(texture, dim, coordinate, layer, texel)?;
+            }
+            Statement::WorkGroupUniformLoad { pointer, result } => {
+                // This is a barrier, a load, and another barrier. Run the
+                // statement again after the first barrier to do the rest.
+                if self.invocation.uniform_load {
+                    self.invocation.uniform_load = false;
+                    let pointer = self.eval(pointer)?.pointer();
+                    let value = self.load(pointer, self.ty(result))?;
+                    self.frame_mut().values[result.index()] = Some(value);
+                } else {
+                    self.invocation.uniform_load = true;
+                    self.cursor_mut().next -= 1;
+                }
+                self.invocation.status = Status::Barrier;
+            }
+            Statement::Call {
+                function,
+                ref arguments,
+                result,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|&argument| self.eval(argument))
+                    .collect::<Result<_>>()?;
+                let info = &self.info[function];
+                self.call(&self.module.functions[function], info, arguments, result)?;
+            }
+            Statement::RayQuery { .. } => return Err(Error::Unsupported("ray queries")),
+            Statement::SubgroupBallot { .. }
+            | Statement::SubgroupGather { .. }
+            | Statement::SubgroupCollectiveOperation { .. } => {
+                return Err(Error::Unsupported("subgroup operations"))
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the type of `expr` in the current function.
+    fn ty(&self, expr: Handle<Expression>) -> &'a TypeInner {
+        let info: &'a FunctionInfo = self.frame().info;
+        info[expr].ty.inner_with(&self.module.types)
+    }
+
+    fn memory(&mut self, memory: Memory) -> Result<&mut [u8]> {
+        let module = self.module;
+        Ok(match memory {
+            Memory::Local { frame, variable } => {
+                &mut self.invocation.frames[frame].locals[variable.index()]
+            }
+            Memory::Private(var) => &mut self.invocation.private[var.index()],
+            Memory::WorkGroup(var) => &mut self.workgroup[var.index()],
+            Memory::Buffer(var) => match self.resource(var)? {
+                &mut Resource::Buffer(ref mut data) => data,
+                _ => return Err(mismatch(module, var, "buffer")),
+            },
+        })
+    }
+
+    fn resource(&mut self, var: Handle<crate::GlobalVariable>) -> Result<&mut Resource<'r>> {
+        let binding = self.module.global_variables[var]
+            .binding
+            .as_ref()
+            .ok_or(Error::Unsupported("resources without bindings"))?;
+        self.resources
+            .get_mut(binding)
+            .ok_or_else(|| Error::MissingResource(binding.clone()))
+    }
+
+    fn load(&mut self, pointer: Pointer, inner: &TypeInner) -> Result<Value> {
+        let types = &self.module.types;
+        value::read(self.memory(pointer.memory)?, pointer.offset, inner, types)
+    }
+
+    /// Returns the texture that `image` refers to, and its dimension and class.
+    fn texture(
+        &mut self,
+        image: Handle<Expression>,
+    ) -> Result<(&mut Texture<'r>, ImageDimension, ImageClass)> {
+        let Value::Image(var) = self.eval(image)? else {
+            return Err(Error::Unsupported("binding arrays"));
+        };
+        let TypeInner::Image { dim, class, .. } =
+            self.module.types[self.module.global_variables[var].ty].inner
+        else {
+            return Err(Error::Unsupported("binding arrays"));
+        };
+        let module = self.module;
+        match self.resource(var)? {
+            &mut Resource::Texture(ref mut texture) => Ok((texture, dim, class)),
+            _ => Err(mismatch(module, var, "texture")),
+        }
+    }
+
+    fn layer(&mut self, array_index: Option<Handle<Expression>>) -> Result<i64> {
+        match array_index {
+            Some(index) => integer(self.eval(index)?.scalar()),
+            None => Ok(0),
+        }
+    }
+
+    /// Evaluates the value an atomic exchange compares with, if any.
+    fn compare(&mut self, fun: &AtomicFunction) -> Result<Option<Literal>> {
+        match *fun {
+            AtomicFunction::Exchange {
+                compare: Some(compare),
+            } => Ok(Some(self.eval(compare)?.scalar())),
+            _ => Ok(None),
+        }
+    }
+
+    /// Evaluates an expression in [`Module::global_expressions`].
+    fn eval_global(&self, handle: Handle<Expression>) -> Result<Value> {
+        let types = &self.module.types;
+        Ok(match self.module.global_expressions[handle] {
+            Expression::Literal(literal) => Value::Scalar(literal),
+            Expression::Constant(constant) => {
+                self.eval_global(self.module.constants[constant].init)?
+            }
+            Expression::Override(_) => return Err(Error::Override),
+            Expression::ZeroValue(ty) => value::zero(&types[ty].inner, types)?,
+            Expression::Compose { ty, ref components } => {
+                let components = components
+                    .iter()
+                    .map(|&component| self.eval_global(component))
+                    .collect::<Result<_>>()?;
+                compose(&types[ty].inner, components)
+            }
+            Expression::Splat { size, value } => {
+                Value::Composite(vec![self.eval_global(value)?; size as usize])
+            }
+            _ => return Err(Error::Unsupported("this constant expression")),
+        })
+    }
+
+    /// Evaluates an expression in the current function.
+    fn eval(&mut self, handle: Handle<Expression>) -> Result<Value> {
+        let frame = self.frame();
+        if let Some(ref value) = frame.values[handle.index()] {
+            return Ok(value.clone());
+        }
+        let function: &'a Function = frame.function;
+        let value = self.eval_uncached(&function.expressions[handle], handle)?;
+        self.frame_mut().values[handle.index()] = Some(value.clone());
+        Ok(value)
+    }
+
+    fn eval_uncached(
+        &mut self,
+        expression: &Expression,
+        handle: Handle<Expression>,
+    ) -> Result<Value> {
+        let types = &self.module.types;
+        Ok(match *expression {
+            Expression::Literal(literal) => Value::Scalar(literal),
+            Expression::Constant(constant) => {
+                self.eval_global(self.module.constants[constant].init)?
+            }
+            Expression::Override(_) => return Err(Error::Override),
+            Expression::ZeroValue(ty) => value::zero(&types[ty].inner, types)?,
+            Expression::Compose { ty, ref components } => {
+                let components = components
+                    .iter()
+                    .map(|&component| self.eval(component))
+                    .collect::<Result<_>>()?;
+                compose(&types[ty].inner, components)
+            }
+            Expression::Access { base, index } => {
+                let index = self.eval(index)?.scalar();
+                let index = u32::try_from(integer(index)?).map_err(|_| Error::OutOfBounds)?;
+                self.access(base, index)?
+            }
+            Expression::AccessIndex { base, index } => self.access(base, index)?,
+            Expression::Splat { size, value } => {
+                Value::Composite(vec![self.eval(value)?; size as usize])
+            }
+            Expression::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let components = self.eval(vector)?.components();
+                Value::Composite(
+                    pattern[..size as usize]
+                        .iter()
+                        .map(|&component| components[component as usize].clone())
+                        .collect(),
+                )
+            }
+            Expression::FunctionArgument(index) => self.frame().arguments[index as usize].clone(),
+            Expression::GlobalVariable(var) => self.global(var)?,
+            Expression::LocalVariable(variable) => Value::Pointer(Pointer {
+                memory: Memory::Local {
+                    frame: self.invocation.frames.len() - 1,
+                    variable,
+                },
+                offset: 0,
+            }),
+            Expression::Load { pointer } => {
+                let pointer = self.eval(pointer)?.pointer();
+                self.load(pointer, self.ty(handle))?
+            }
+            Expression::ImageSample { .. } => return Err(Error::Unsupported("texture sampling")),
+            Expression::ImageLoad {
+                image,
+                coordinate,
+                array_index,
+                sample,
+                level,
+            } => {
+                let coordinate = coordinate_of(&self.eval(coordinate)?)?;
+                let layer = self.layer(array_index)?;
+                // Only the first sample of the first level exists.
+                for index in [sample, level].into_iter().flatten() {
+                    if integer(self.eval(index)?.scalar())? != 0 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+                let (texture, dim, class) = self.texture(image)?;
+                let texel = image::load(texture, dim, coordinate, layer)?;
+                match class {
+                    ImageClass::Depth { .. } => Value::Scalar(texel[0]),
+                    _ => Value::Composite(texel.map(Value::Scalar).to_vec()),
+                }
+            }
+            Expression::ImageQuery { image, query } => {
+                if let ImageQuery::Size { level: Some(level) } = query {
+                    if integer(self.eval(level)?.scalar())? != 0 {
+                        return Err(Error::OutOfBounds);
+                    }
+                }
+                let (texture, dim, _) = self.texture(image)?;
+                let [width, height, depth] = texture.size;
+                let uints = |values: &[u32]| {
+                    Value::Composite(
+                        values
+                            .iter()
+                            .map(|&v| Value::Scalar(Literal::U32(v)))
+                            .collect(),
+                    )
+                };
+                match query {
+                    ImageQuery::Size { .. } => match dim {
+                        ImageDimension::D1 => Value::Scalar(Literal::U32(width)),
+                        ImageDimension::D2 | ImageDimension::Cube => uints(&[width, height]),
+                        ImageDimension::D3 => uints(&[width, height, depth]),
+                    },
+                    ImageQuery::NumLevels | ImageQuery::NumSamples => {
+                        Value::Scalar(Literal::U32(1))
+                    }
+                    ImageQuery::NumLayers => Value::Scalar(Literal::U32(match dim {
+                        ImageDimension::Cube => depth / 6,
+                        _ => depth,
+                    })),
+                }
+            }
+            Expression::Unary { op, expr } => self.eval(expr)?.map(|v| ops::unary(op, v))?,
+            Expression::Binary { op, left, right } => self.binary(op, left, right)?,
+            Expression::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let args = [
+                    self.eval(condition)?,
+                    self.eval(accept)?,
+                    self.eval(reject)?,
+                ];
+                Value::zip_all(&args, &mut |x| {
+                    Ok(if x[0] == Literal::Bool(true) {
+                        x[1]
+                    } else {
+                        x[2]
+                    })
+                })?
+            }
+            Expression::Derivative { .. } => return Err(Error::Unsupported("derivatives")),
+            Expression::Relational { fun, argument } => {
+                let argument = self.eval(argument)?;
+                match fun {
+                    RelationalFunction::All | RelationalFunction::Any => {
+                        let mut values = argument
+                            .components()
+                            .into_iter()
+                            .map(|c| c.scalar() == Literal::Bool(true));
+                        let result = if fun == RelationalFunction::All {
+                            values.all(|v| v)
+                        } else {
+                            values.any(|v| v)
+                        };
+                        Value::Scalar(Literal::Bool(result))
+                    }
+                    RelationalFunction::IsNan | RelationalFunction::IsInf => argument.map(|v| {
+                        let ops::Wide::Float(v) = ops::widen(v)? else {
+                            return Ok(Literal::Bool(false));
+                        };
+                        Ok(Literal::Bool(if fun == RelationalFunction::IsNan {
+                            v.is_nan()
+                        } else {
+                            v.is_infinite()
+                        }))
+                    })?,
+                }
+            }
+            Expression::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let args = [Some(arg), arg1, arg2, arg3]
+                    .into_iter()
+                    .flatten()
+                    .map(|arg| self.eval(arg))
+                    .collect::<Result<Vec<_>>>()?;
+                math::math(fun, &args)?
+            }
+            Expression::As {
+                expr,
+                kind,
+                convert,
+            } => {
+                let value = self.eval(expr)?;
+                match convert {
+                    Some(width) => value.map(|v| ops::convert(v, crate::Scalar { kind, width }))?,
+                    None => {
+                        // Bitcast through memory, which also handles casts
+                        // between vectors of different sizes.
+                        let (from, to) = (self.ty(expr), self.ty(handle));
+                        let mut bytes = vec![0; from.size(self.module.to_ctx()) as usize];
+                        value::write(&mut bytes, 0, from, types, &value)?;
+                        value::read(&bytes, 0, to, types)?
+                    }
+                }
+            }
+            Expression::ArrayLength(pointer) => {
+                let pointer_value = self.eval(pointer)?.pointer();
+                let stride = match *self.ty(pointer) {
+                    TypeInner::Pointer { base, .. } => match types[base].inner {
+                        TypeInner::Array { stride, .. } => stride,
+                        _ => return Err(Error::Unsupported("binding arrays")),
+                    },
+                    _ => return Err(Error::Unsupported("binding arrays")),
+                };
+                let size = self.memory(pointer_value.memory)?.len() as u32;
+                Value::Scalar(Literal::U32(
+                    size.saturating_sub(pointer_value.offset) / stride,
+                ))
+            }
+            Expression::RayQueryGetIntersection { .. } => {
+                return Err(Error::Unsupported("ray queries"))
+            }
+            Expression::CallResult(_)
+            | Expression::AtomicResult { .. }
+            | Expression::WorkGroupUniformLoadResult { .. }
+            | Expression::RayQueryProceedResult
+            | Expression::SubgroupBallotResult
+            | Expression::SubgroupOperationResult { .. } => {
+                unreachable!("{expression:?} is set by the statement that produces it")
+            }
+        })
+    }
+
+    fn global(&self, var: Handle<crate::GlobalVariable>) -> Result<Value> {
+        let global = &self.module.global_variables[var];
+        let memory = match global.space {
+            AddressSpace::Handle => {
+                return match self.module.types[global.ty].inner {
+                    TypeInner::Image { .. } => Ok(Value::Image(var)),
+                    TypeInner::Sampler { .. } => Ok(Value::Sampler),
+                    _ => Err(Error::Unsupported("binding arrays")),
+                }
+            }
+            AddressSpace::Private => Memory::Private(var),
+            AddressSpace::WorkGroup => Memory::WorkGroup(var),
+            AddressSpace::Uniform | AddressSpace::Storage { .. } => Memory::Buffer(var),
+            AddressSpace::PushConstant => return Err(Error::Unsupported("push constants")),
+            AddressSpace::Function => unreachable!("global variable in the function space"),
+        };
+        Ok(Value::Pointer(Pointer { memory, offset: 0 }))
+    }
+
+    /// Accesses the `index`th component of `base`, or of what it points to.
+    fn access(&mut self, base: Handle<Expression>, index: u32) -> Result<Value> {
+        let pointer = match self.eval(base)? {
+            Value::Pointer(pointer) => pointer,
+            Value::Composite(components) => {
+                return components
+                    .into_iter()
+                    .nth(index as usize)
+                    .ok_or(Error::OutOfBounds)
+            }
+            _ => return Err(Error::Unsupported("binding arrays")),
+        };
+        let check = |count: u32| {
+            if index < count {
+                Ok(())
+            } else {
+                Err(Error::OutOfBounds)
+            }
+        };
+        let offset = match *self.ty(base) {
+            TypeInner::Pointer { base, .. } => match self.module.types[base].inner {
+                TypeInner::Array { size, stride, .. } => {
+                    if let ArraySize::Constant(count) = size {
+                        check(count.get())?;
+                    }
+                    // Elements of runtime-sized arrays are checked against the
+                    // size of the buffer when they are accessed.
+                    index * stride
+                }
+                TypeInner::Vector { size, scalar } => {
+                    check(size as u32)?;
+                    index * scalar.width as u32
+                }
+                TypeInner::Matrix {
+                    columns,
+                    rows,
+                    scalar,
+                } => {
+                    check(columns as u32)?;
+                    index * column_stride(rows, scalar)
+                }
+                TypeInner::Struct { ref members, .. } => members[index as usize].offset,
+                _ => return Err(Error::Unsupported("binding arrays")),
+            },
+            TypeInner::ValuePointer {
+                size: Some(size),
+                scalar,
+                ..
+            } => {
+                check(size as u32)?;
+                index * scalar.width as u32
+            }
+            _ => return Err(Error::Unsupported("accesses through this pointer")),
+        };
+        Ok(Value::Pointer(Pointer {
+            memory: pointer.memory,
+            offset: pointer.offset + offset,
+        }))
+    }
+
+    fn binary(
+        &mut self,
+        op: BinaryOperator,
+        left: Handle<Expression>,
+        right: Handle<Expression>,
+    ) -> Result<Value> {
+        let (left_ty, right_ty) = (self.ty(left), self.ty(right));
+        let (left, right) = (self.eval(left)?, self.eval(right)?);
+        if op == BinaryOperator::Multiply {
+            match (left_ty, right_ty) {
+                (&TypeInner::Matrix { .. }, &TypeInner::Matrix { .. }) => {
+                    return right
+                        .components()
+                        .iter()
+                        .map(|column| matrix_times_vector(&left, column))
+                        .collect::<Result<_>>()
+                        .map(Value::Composite);
+                }
+                (&TypeInner::Matrix { .. }, &TypeInner::Vector { .. }) => {
+                    return matrix_times_vector(&left, &right);
+                }
+                (&TypeInner::Vector { .. }, &TypeInner::Matrix { .. }) => {
+                    return right
+                        .components()
+                        .iter()
+                        .map(|column| math::dot(&left, column).map(Value::Scalar))
+                        .collect::<Result<_>>()
+                        .map(Value::Composite);
+                }
+                _ => {}
+            }
+        }
+        left.zip(right, |a, b| ops::binary(op, a, b))
+    }
+}
+
+fn mismatch(module: &Module, var: Handle<crate::GlobalVariable>, expected: &'static str) -> Error {
+    let binding = module.global_variables[var].binding.clone();
+    Error::ResourceMismatch(binding.unwrap(), expected)
+}
+
+/// Builds a value of type `inner` from `components`.
+fn compose(inner: &TypeInner, components: Vec<Value>) -> Value {
+    match *inner {
+        // Vectors can be built from smaller vectors.
+        TypeInner::Vector { .. } => {
+            Value::Composite(components.into_iter().flat_map(Value::components).collect())
+        }
+        _ => Value::Composite(components),
+    }
+}
+
+fn matrix_times_vector(matrix: &Value, vector: &Value) -> Result<Value> {
+    let mut sum: Option<Value> = None;
+    for (column, factor) in matrix
+        .clone()
+        .components()
+        .into_iter()
+        .zip(vector.clone().components())
+    {
+        let term = column.zip(factor, |a, b| ops::binary(BinaryOperator::Multiply, a, b))?;
+        sum = Some(match sum {
+            Some(sum) => sum.zip(term, |a, b| ops::binary(BinaryOperator::Add, a, b))?,
+            None => term,
+        });
+    }
+    sum.ok_or(Error::Unsupported("empty matrices"))
+}
+
+/// Applies the atomic function `fun` to the value `old` in memory, returning
+/// the new value and, for a compare-exchange, whether it exchanged.
+fn atomic(
+    fun: &AtomicFunction,
+    old: Literal,
+    value: Literal,
+    compare: Option<Literal>,
+) -> Result<(Literal, bool)> {
+    let op = match *fun {
+        AtomicFunction::Add => BinaryOperator::Add,
+        AtomicFunction::Subtract => BinaryOperator::Subtract,
+        AtomicFunction::And => BinaryOperator::And,
+        AtomicFunction::ExclusiveOr => BinaryOperator::ExclusiveOr,
+        AtomicFunction::InclusiveOr => BinaryOperator::InclusiveOr,
+        AtomicFunction::Min | AtomicFunction::Max => {
+            let less = ops::binary(BinaryOperator::Less, value, old)? == Literal::Bool(true);
+            let new = if less == matches!(*fun, AtomicFunction::Min) {
+                value
+            } else {
+                old
+            };
+            return Ok((new, false));
+        }
+        AtomicFunction::Exchange { .. } => {
+            return Ok(match compare {
+                Some(compare) if old != compare => (old, false),
+                Some(_) => (value, true),
+                None => (value, false),
+            });
+        }
+    };
+    Ok((ops::binary(op, old, value)?, false))
+}
+
+fn integer(literal: Literal) -> Result<i64> {
+    match ops::widen(literal)? {
+        ops::Wide::Int(v) => i64::try_from(v).map_err(|_| Error::OutOfBounds),
+        _ => Err(Error::Unsupported("non-integer indices")),
+    }
+}
+
+fn coordinate_of(value: &Value) -> Result<[i64; 3]> {
+    let mut coordinate = [0; 3];
+    for (c, component) in coordinate.iter_mut().zip(value.clone().components()) {
+        *c = integer(component.scalar())?;
+    }
+    Ok(coordinate)
+}
+
+fn texel_of(value: Value) -> [Literal; 4] {
+    let components = value.components();
+    let mut texel = [Literal::U32(0); 4];
+    for (t, component) in texel.iter_mut().zip(components) {
+        *t = component.scalar();
+    }
+    texel
+}
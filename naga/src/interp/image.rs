@@ -0,0 +1,305 @@
+//! Texel formats and addressing.
+
+use super::{Error, Result, Texture};
+use crate::{ImageDimension, Literal, StorageFormat as Sf};
+
+/// How one channel of a texel is stored.
+#[derive(Clone, Copy)]
+enum Channel {
+    Unorm8,
+    Snorm8,
+    Uint8,
+    Sint8,
+    Unorm16,
+    Snorm16,
+    Uint16,
+    Sint16,
+    Float16,
+    Uint32,
+    Sint32,
+    Float32,
+    Uint64,
+}
+
+impl Channel {
+    const fn size(self) -> usize {
+        match self {
+            Self::Unorm8 | Self::Snorm8 | Self::Uint8 | Self::Sint8 => 1,
+            Self::Unorm16 | Self::Snorm16 | Self::Uint16 | Self::Sint16 | Self::Float16 => 2,
+            Self::Uint32 | Self::Sint32 | Self::Float32 => 4,
+            Self::Uint64 => 8,
+        }
+    }
+
+    /// The value of a channel the format doesn't have: zero, or one for
+    /// alpha.
+    const fn default(self, alpha: bool) -> Literal {
+        match self {
+            Self::Uint8 | Self::Uint16 | Self::Uint32 => Literal::U32(alpha as u32),
+            Self::Sint8 | Self::Sint16 | Self::Sint32 => Literal::I32(alpha as i32),
+            Self::Uint64 => Literal::U64(alpha as u64),
+            _ => Literal::F32(alpha as u8 as f32),
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> Literal {
+        let mut raw = [0; 8];
+        raw[..bytes.len()].copy_from_slice(bytes);
+        let raw = u64::from_le_bytes(raw);
+        match self {
+            Self::Unorm8 => Literal::F32(raw as u8 as f32 / 255.0),
+            Self::Snorm8 => Literal::F32((raw as u8 as i8 as f32 / 127.0).max(-1.0)),
+            Self::Uint8 | Self::Uint16 | Self::Uint32 => Literal::U32(raw as u32),
+            Self::Sint8 => Literal::I32(raw as u8 as i8 as i32),
+            Self::Unorm16 => Literal::F32(raw as u16 as f32 / 65535.0),
+            Self::Snorm16 => Literal::F32((raw as u16 as i16 as f32 / 32767.0).max(-1.0)),
+            Self::Sint16 => Literal::I32(raw as u16 as i16 as i32),
+            Self::Float16 => Literal::F32(half::f16::from_bits(raw as u16).to_f32()),
+            Self::Sint32 => Literal::I32(raw as u32 as i32),
+            Self::Float32 => Literal::F32(f32::from_bits(raw as u32)),
+            Self::Uint64 => Literal::U64(raw),
+        }
+    }
+
+    fn encode(self, value: Literal, bytes: &mut [u8]) -> Result<()> {
+        let raw = match (self, value) {
+            (Self::Unorm8, Literal::F32(v)) => unorm(v, 255.0),
+            (Self::Snorm8, Literal::F32(v)) => snorm(v, 127.0),
+            (Self::Unorm16, Literal::F32(v)) => unorm(v, 65535.0),
+            (Self::Snorm16, Literal::F32(v)) => snorm(v, 32767.0),
+            (Self::Float16, Literal::F32(v)) => half::f16::from_f32(v).to_bits() as u64,
+            (Self::Float32, Literal::F32(v)) => v.to_bits() as u64,
+            (Self::Uint8 | Self::Uint16 | Self::Uint32, Literal::U32(v)) => v as u64,
+            (Self::Sint8 | Self::Sint16 | Self::Sint32, Literal::I32(v)) => v as u32 as u64,
+            (Self::Uint64, Literal::U64(v)) => v,
+            _ => return Err(Error::Unsupported("texel values of this type")),
+        };
+        bytes.copy_from_slice(&raw.to_le_bytes()[..bytes.len()]);
+        Ok(())
+    }
+}
+
+fn unorm(v: f32, max: f32) -> u64 {
+    (v.clamp(0.0, 1.0) * max).round() as u64
+}
+
+fn snorm(v: f32, max: f32) -> u64 {
+    (v.clamp(-1.0, 1.0) * max).round() as i64 as u64
+}
+
+/// The way texels of a format are stored.
+enum Layout {
+    Channels(Channel, usize),
+    Bgra8Unorm,
+    Rgb10a2Uint,
+    Rgb10a2Unorm,
+    Rg11b10Ufloat,
+}
+
+const fn layout(format: Sf) -> Layout {
+    use Channel as C;
+    match format {
+        Sf::R8Unorm => Layout::Channels(C::Unorm8, 1),
+        Sf::R8Snorm => Layout::Channels(C::Snorm8, 1),
+        Sf::R8Uint => Layout::Channels(C::Uint8, 1),
+        Sf::R8Sint => Layout::Channels(C::Sint8, 1),
+        Sf::R16Uint => Layout::Channels(C::Uint16, 1),
+        Sf::R16Sint => Layout::Channels(C::Sint16, 1),
+        Sf::R16Float => Layout::Channels(C::Float16, 1),
+        Sf::R16Unorm => Layout::Channels(C::Unorm16, 1),
+        Sf::R16Snorm => Layout::Channels(C::Snorm16, 1),
+        Sf::Rg8Unorm => Layout::Channels(C::Unorm8, 2),
+        Sf::Rg8Snorm => Layout::Channels(C::Snorm8, 2),
+        Sf::Rg8Uint => Layout::Channels(C::Uint8, 2),
+        Sf::Rg8Sint => Layout::Channels(C::Sint8, 2),
+        Sf::R32Uint => Layout::Channels(C::Uint32, 1),
+        Sf::R32Sint => Layout::Channels(C::Sint32, 1),
+        Sf::R32Float => Layout::Channels(C::Float32, 1),
+        Sf::Rg16Uint => Layout::Channels(C::Uint16, 2),
+        Sf::Rg16Sint => Layout::Channels(C::Sint16, 2),
+        Sf::Rg16Float => Layout::Channels(C::Float16, 2),
+        Sf::Rg16Unorm => Layout::Channels(C::Unorm16, 2),
+        Sf::Rg16Snorm => Layout::Channels(C::Snorm16, 2),
+        Sf::Rgba8Unorm => Layout::Channels(C::Unorm8, 4),
+        Sf::Rgba8Snorm => Layout::Channels(C::Snorm8, 4),
+        Sf::Rgba8Uint => Layout::Channels(C::Uint8, 4),
+        Sf::Rgba8Sint => Layout::Channels(C::Sint8, 4),
+        Sf::Bgra8Unorm => Layout::Bgra8Unorm,
+        Sf::Rgb10a2Uint => Layout::Rgb10a2Uint,
+        Sf::Rgb10a2Unorm => Layout::Rgb10a2Unorm,
+        Sf::Rg11b10Ufloat => Layout::Rg11b10Ufloat,
+        Sf::R64Uint => Layout::Channels(C::Uint64, 1),
+        Sf::Rg32Uint => Layout::Channels(C::Uint32, 2),
+        Sf::Rg32Sint => Layout::Channels(C::Sint32, 2),
+        Sf::Rg32Float => Layout::Channels(C::Float32, 2),
+        Sf::Rgba16Uint => Layout::Channels(C::Uint16, 4),
+        Sf::Rgba16Sint => Layout::Channels(C::Sint16, 4),
+        Sf::Rgba16Float => Layout::Channels(C::Float16, 4),
+        Sf::Rgba16Unorm => Layout::Channels(C::Unorm16, 4),
+        Sf::Rgba16Snorm => Layout::Channels(C::Snorm16, 4),
+        Sf::Rgba32Uint => Layout::Channels(C::Uint32, 4),
+        Sf::Rgba32Sint => Layout::Channels(C::Sint32, 4),
+        Sf::Rgba32Float => Layout::Channels(C::Float32, 4),
+    }
+}
+
+const fn texel_size(format: Sf) -> usize {
+    match layout(format) {
+        Layout::Channels(channel, count) => channel.size() * count,
+        Layout::Bgra8Unorm | Layout::Rgb10a2Uint | Layout::Rgb10a2Unorm | Layout::Rg11b10Ufloat => {
+            4
+        }
+    }
+}
+
+/// Decodes a texel into the four components a texture load returns.
+fn decode(format: Sf, bytes: &[u8]) -> [Literal; 4] {
+    let packed = || u32::from_le_bytes(bytes[..4].try_into().unwrap());
+    match layout(format) {
+        Layout::Channels(channel, count) => {
+            let size = channel.size();
+            std::array::from_fn(|i| {
+                if i < count {
+                    channel.decode(&bytes[i * size..][..size])
+                } else {
+                    channel.default(i == 3)
+                }
+            })
+        }
+        Layout::Bgra8Unorm => {
+            let [b, g, r, a] = std::array::from_fn(|i| Channel::Unorm8.decode(&bytes[i..i + 1]));
+            [r, g, b, a]
+        }
+        Layout::Rgb10a2Uint => {
+            let v = packed();
+            [(0, 10), (10, 10), (20, 10), (30, 2)]
+                .map(|(shift, bits)| Literal::U32(field(v, shift, bits)))
+        }
+        Layout::Rgb10a2Unorm => {
+            let v = packed();
+            [(0, 10), (10, 10), (20, 10), (30, 2)].map(|(shift, bits)| {
+                Literal::F32(field(v, shift, bits) as f32 / ((1 << bits) - 1) as f32)
+            })
+        }
+        Layout::Rg11b10Ufloat => {
+            let v = packed();
+            // These small floats are `f16`s without the sign bit, and with
+            // fewer mantissa bits.
+            let r = half::f16::from_bits(((v & 0x7ff) << 4) as u16);
+            let g = half::f16::from_bits((((v >> 11) & 0x7ff) << 4) as u16);
+            let b = half::f16::from_bits((((v >> 22) & 0x3ff) << 5) as u16);
+            [r, g, b, half::f16::ONE].map(|c| Literal::F32(c.to_f32()))
+        }
+    }
+}
+
+/// Returns the `bits` bits of `v` starting at bit `shift`.
+const fn field(v: u32, shift: u32, bits: u32) -> u32 {
+    (v >> shift) & ((1 << bits) - 1)
+}
+
+/// Encodes the four components of a texture store as a texel.
+fn encode(format: Sf, value: [Literal; 4], bytes: &mut [u8]) -> Result<()> {
+    let floats = || {
+        value.map(|c| match c {
+            Literal::F32(v) => Ok(v),
+            _ => Err(Error::Unsupported("texel values of this type")),
+        })
+    };
+    let packed = match layout(format) {
+        Layout::Channels(channel, count) => {
+            let size = channel.size();
+            for (i, &component) in value.iter().enumerate().take(count) {
+                channel.encode(component, &mut bytes[i * size..][..size])?;
+            }
+            return Ok(());
+        }
+        Layout::Bgra8Unorm => {
+            let [r, g, b, a] = value;
+            for (i, component) in [b, g, r, a].into_iter().enumerate() {
+                Channel::Unorm8.encode(component, &mut bytes[i..i + 1])?;
+            }
+            return Ok(());
+        }
+        Layout::Rgb10a2Uint => {
+            let mut packed = 0;
+            for (i, component) in value.into_iter().enumerate() {
+                let Literal::U32(c) = component else {
+                    return Err(Error::Unsupported("texel values of this type"));
+                };
+                let bits = if i == 3 { 3 } else { 0x3ff };
+                packed |= (c.min(bits)) << (i * 10);
+            }
+            packed
+        }
+        Layout::Rgb10a2Unorm => {
+            let [r, g, b, a] = floats();
+            let (r, g, b, a) = (r?, g?, b?, a?);
+            unorm(r, 1023.0) as u32
+                | (unorm(g, 1023.0) as u32) << 10
+                | (unorm(b, 1023.0) as u32) << 20
+                | (unorm(a, 3.0) as u32) << 30
+        }
+        Layout::Rg11b10Ufloat => {
+            let [r, g, b, _] = floats();
+            let small =
+                |v: f32, shift: u32| half::f16::from_f32(v.max(0.0)).to_bits() as u32 >> shift;
+            small(r?, 4) | small(g?, 4) << 11 | small(b?, 5) << 22
+        }
+    };
+    bytes.copy_from_slice(&packed.to_le_bytes());
+    Ok(())
+}
+
+/// Returns the byte offset of the texel at `coordinate` in `layer`, or an
+/// error if it is out of bounds.
+fn texel_offset(
+    texture: &Texture,
+    dim: ImageDimension,
+    coordinate: [i64; 3],
+    layer: i64,
+) -> Result<usize> {
+    let [width, height, depth] = texture.size.map(i64::from);
+    let (x, y, z) = match dim {
+        ImageDimension::D1 => (coordinate[0], 0, layer),
+        ImageDimension::D2 | ImageDimension::Cube => (coordinate[0], coordinate[1], layer),
+        ImageDimension::D3 => (coordinate[0], coordinate[1], coordinate[2]),
+    };
+    if !(0..width).contains(&x) || !(0..height).contains(&y) || !(0..depth).contains(&z) {
+        return Err(Error::OutOfBounds);
+    }
+    let index = (z * height + y) * width + x;
+    let size = texel_size(texture.format);
+    let offset = index as usize * size;
+    if offset + size > texture.data.len() {
+        return Err(Error::OutOfBounds);
+    }
+    Ok(offset)
+}
+
+pub(super) fn load(
+    texture: &Texture,
+    dim: ImageDimension,
+    coordinate: [i64; 3],
+    layer: i64,
+) -> Result<[Literal; 4]> {
+    let offset = texel_offset(texture, dim, coordinate, layer)?;
+    let size = texel_size(texture.format);
+    Ok(decode(texture.format, &texture.data[offset..offset + size]))
+}
+
+pub(super) fn store(
+    texture: &mut Texture,
+    dim: ImageDimension,
+    coordinate: [i64; 3],
+    layer: i64,
+    value: [Literal; 4],
+) -> Result<()> {
+    let offset = texel_offset(texture, dim, coordinate, layer)?;
+    let size = texel_size(texture.format);
+    encode(
+        texture.format,
+        value,
+        &mut texture.data[offset..offset + size],
+    )
+}
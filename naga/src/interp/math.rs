@@ -0,0 +1,487 @@
+//! Built-in math functions.
+
+use super::{
+    ops::{self, narrow, widen, Wide},
+    value::Value,
+    Error, Result,
+};
+use crate::{BinaryOperator, Literal, MathFunction as Mf, Scalar};
+
+pub(super) fn math(fun: Mf, args: &[Value]) -> Result<Value> {
+    match fun {
+        Mf::Abs => numeric(args, |x| x[0].abs(), |x| x[0].abs()),
+        Mf::Min => numeric(args, |x| x[0].min(x[1]), |x| x[0].min(x[1])),
+        Mf::Max => numeric(args, |x| x[0].max(x[1]), |x| x[0].max(x[1])),
+        Mf::Clamp => numeric(
+            args,
+            |x| x[0].max(x[1]).min(x[2]),
+            |x| x[0].max(x[1]).min(x[2]),
+        ),
+        Mf::Saturate => float(args, |x| x[0].clamp(0.0, 1.0)),
+        Mf::Cos => float(args, |x| x[0].cos()),
+        Mf::Cosh => float(args, |x| x[0].cosh()),
+        Mf::Sin => float(args, |x| x[0].sin()),
+        Mf::Sinh => float(args, |x| x[0].sinh()),
+        Mf::Tan => float(args, |x| x[0].tan()),
+        Mf::Tanh => float(args, |x| x[0].tanh()),
+        Mf::Acos => float(args, |x| x[0].acos()),
+        Mf::Asin => float(args, |x| x[0].asin()),
+        Mf::Atan => float(args, |x| x[0].atan()),
+        Mf::Atan2 => float(args, |x| x[0].atan2(x[1])),
+        Mf::Asinh => float(args, |x| x[0].asinh()),
+        Mf::Acosh => float(args, |x| x[0].acosh()),
+        Mf::Atanh => float(args, |x| x[0].atanh()),
+        Mf::Radians => float(args, |x| x[0].to_radians()),
+        Mf::Degrees => float(args, |x| x[0].to_degrees()),
+        Mf::Ceil => float(args, |x| x[0].ceil()),
+        Mf::Floor => float(args, |x| x[0].floor()),
+        Mf::Round => float(args, |x| round_ties_even(x[0])),
+        Mf::Fract => float(args, |x| x[0] - x[0].floor()),
+        Mf::Trunc => float(args, |x| x[0].trunc()),
+        Mf::Modf => Ok(Value::Composite(vec![
+            float(args, |x| x[0] - x[0].trunc())?,
+            float(args, |x| x[0].trunc())?,
+        ])),
+        Mf::Frexp => Ok(Value::Composite(vec![
+            float(args, |x| frexp(x[0]).0)?,
+            args[0]
+                .clone()
+                .map(|x| Ok(Literal::I32(frexp(to_float(x)?).1)))?,
+        ])),
+        Mf::Ldexp => Value::zip_all(args, &mut |x| {
+            let Wide::Int(exponent) = widen(x[1])? else {
+                return Err(Error::Unsupported("this `ldexp` exponent"));
+            };
+            let value = to_float(x[0])? * 2f64.powi(exponent as i32);
+            narrow(Wide::Float(value), x[0].scalar())
+        }),
+        Mf::Exp => float(args, |x| x[0].exp()),
+        Mf::Exp2 => float(args, |x| x[0].exp2()),
+        Mf::Log => float(args, |x| x[0].ln()),
+        Mf::Log2 => float(args, |x| x[0].log2()),
+        Mf::Pow => float(args, |x| x[0].powf(x[1])),
+        Mf::Dot => dot(&args[0], &args[1]).map(Value::Scalar),
+        Mf::Dot4I8Packed | Mf::Dot4U8Packed => {
+            let (a, b) = (packed(&args[0])?, packed(&args[1])?);
+            Ok(Value::Scalar(if fun == Mf::Dot4I8Packed {
+                let sum = (0..4)
+                    .map(|i| (a >> (8 * i)) as i8 as i32 * (b >> (8 * i)) as i8 as i32)
+                    .sum();
+                Literal::I32(sum)
+            } else {
+                let sum = (0..4)
+                    .map(|i| (a >> (8 * i)) as u8 as u32 * (b >> (8 * i)) as u8 as u32)
+                    .sum();
+                Literal::U32(sum)
+            }))
+        }
+        Mf::Outer => {
+            let (a, scalar) = floats(&args[0])?;
+            let (b, _) = floats(&args[1])?;
+            b.iter()
+                .map(|&y| vector(a.iter().map(|&x| x * y), scalar))
+                .collect::<Result<_>>()
+                .map(Value::Composite)
+        }
+        Mf::Cross => {
+            let (a, scalar) = floats(&args[0])?;
+            let (b, _) = floats(&args[1])?;
+            vector(
+                [
+                    a[1] * b[2] - a[2] * b[1],
+                    a[2] * b[0] - a[0] * b[2],
+                    a[0] * b[1] - a[1] * b[0],
+                ],
+                scalar,
+            )
+        }
+        Mf::Distance => {
+            let (a, scalar) = floats(&args[0])?;
+            let (b, _) = floats(&args[1])?;
+            let difference: Vec<_> = a.iter().zip(b).map(|(x, y)| x - y).collect();
+            narrow(Wide::Float(length(&difference)), scalar).map(Value::Scalar)
+        }
+        Mf::Length => {
+            let (a, scalar) = floats(&args[0])?;
+            narrow(Wide::Float(length(&a)), scalar).map(Value::Scalar)
+        }
+        Mf::Normalize => {
+            let (a, scalar) = floats(&args[0])?;
+            let length = length(&a);
+            vector(a.iter().map(|x| x / length), scalar)
+        }
+        Mf::FaceForward => {
+            let (n, scalar) = floats(&args[0])?;
+            let (i, _) = floats(&args[1])?;
+            let (n_ref, _) = floats(&args[2])?;
+            let sign = if dot_floats(&n_ref, &i) < 0.0 {
+                1.0
+            } else {
+                -1.0
+            };
+            vector(n.iter().map(|x| x * sign), scalar)
+        }
+        Mf::Reflect => {
+            let (i, scalar) = floats(&args[0])?;
+            let (n, _) = floats(&args[1])?;
+            let d = dot_floats(&n, &i);
+            vector(i.iter().zip(&n).map(|(i, n)| i - 2.0 * d * n), scalar)
+        }
+        Mf::Refract => {
+            let (i, scalar) = floats(&args[0])?;
+            let (n, _) = floats(&args[1])?;
+            let eta = to_float(args[2].scalar())?;
+            let d = dot_floats(&n, &i);
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            if k < 0.0 {
+                vector(i.iter().map(|_| 0.0), scalar)
+            } else {
+                let factor = eta * d + k.sqrt();
+                vector(i.iter().zip(&n).map(|(i, n)| eta * i - factor * n), scalar)
+            }
+        }
+        Mf::Sign => numeric(
+            args,
+            |x| x[0].signum(),
+            |x| {
+                if x[0] == 0.0 {
+                    x[0]
+                } else {
+                    x[0].signum()
+                }
+            },
+        ),
+        Mf::Fma => float(args, |x| x[0].mul_add(x[1], x[2])),
+        Mf::Mix => float(args, |x| x[0] * (1.0 - x[2]) + x[1] * x[2]),
+        Mf::Step => float(args, |x| if x[0] <= x[1] { 1.0 } else { 0.0 }),
+        Mf::SmoothStep => float(args, |x| {
+            let t = ((x[2] - x[0]) / (x[1] - x[0])).clamp(0.0, 1.0);
+            t * t * (3.0 - 2.0 * t)
+        }),
+        Mf::Sqrt => float(args, |x| x[0].sqrt()),
+        Mf::InverseSqrt => float(args, |x| 1.0 / x[0].sqrt()),
+        Mf::Transpose => {
+            let columns: Vec<_> = args[0].clone().components();
+            let rows = columns.first().map_or(0, |c| c.clone().components().len());
+            Ok(Value::Composite(
+                (0..rows)
+                    .map(|row| {
+                        Value::Composite(
+                            columns
+                                .iter()
+                                .map(|column| column.clone().components()[row].clone())
+                                .collect(),
+                        )
+                    })
+                    .collect(),
+            ))
+        }
+        Mf::Determinant => {
+            let mut scalar = None;
+            let columns = args[0]
+                .clone()
+                .components()
+                .iter()
+                .map(|column| {
+                    let (column, column_scalar) = floats(column)?;
+                    scalar = Some(column_scalar);
+                    Ok(column)
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let scalar = scalar.ok_or(Error::Unsupported("empty matrices"))?;
+            narrow(Wide::Float(determinant(&columns)), scalar).map(Value::Scalar)
+        }
+        Mf::QuantizeToF16 => float(args, |x| half::f16::from_f64(x[0]).to_f64()),
+        Mf::CountTrailingZeros => bits(args, |v, w| (v.trailing_zeros().min(w)) as u64),
+        Mf::CountLeadingZeros => bits(args, |v, w| (v.leading_zeros() - (64 - w)) as u64),
+        Mf::CountOneBits => bits(args, |v, _| v.count_ones() as u64),
+        Mf::ReverseBits => bits(args, |v, w| v.reverse_bits() >> (64 - w)),
+        Mf::FirstTrailingBit => bits(args, |v, _| {
+            if v == 0 {
+                u64::MAX
+            } else {
+                v.trailing_zeros() as u64
+            }
+        }),
+        Mf::FirstLeadingBit => Value::zip_all(args, &mut |x| {
+            let (v, w) = to_bits(x[0])?;
+            let signed = matches!(x[0], Literal::I32(_) | Literal::I64(_));
+            // For negative signed values, look for the first zero bit instead.
+            let v = if signed && v >> (w - 1) & 1 == 1 {
+                !v & mask(w)
+            } else {
+                v
+            };
+            let index = if v == 0 {
+                u64::MAX
+            } else {
+                63 - v.leading_zeros() as u64
+            };
+            Ok(from_bits(x[0], index))
+        }),
+        Mf::ExtractBits => Value::zip_all(args, &mut |x| {
+            let (v, w) = to_bits(x[0])?;
+            let (offset, count) = offset_and_count(x[1], x[2], w)?;
+            if count == 0 {
+                return Ok(from_bits(x[0], 0));
+            }
+            let mut result = (v >> offset) & mask(count);
+            let signed = matches!(x[0], Literal::I32(_) | Literal::I64(_));
+            if signed && result >> (count - 1) & 1 == 1 {
+                result |= !mask(count);
+            }
+            Ok(from_bits(x[0], result))
+        }),
+        Mf::InsertBits => Value::zip_all(args, &mut |x| {
+            let (v, w) = to_bits(x[0])?;
+            let (new_bits, _) = to_bits(x[1])?;
+            let (offset, count) = offset_and_count(x[2], x[3], w)?;
+            if count == 0 {
+                return Ok(x[0]);
+            }
+            let mask = mask(count) << offset;
+            Ok(from_bits(x[0], (v & !mask) | ((new_bits << offset) & mask)))
+        }),
+        Mf::Pack4x8snorm => pack(&args[0], 8, |x| {
+            Ok((0.5 + 127.0 * to_float(x)?.clamp(-1.0, 1.0)).floor() as i32 as u32)
+        }),
+        Mf::Pack4x8unorm => pack(&args[0], 8, |x| {
+            Ok((0.5 + 255.0 * to_float(x)?.clamp(0.0, 1.0)).floor() as u32)
+        }),
+        Mf::Pack2x16snorm => pack(&args[0], 16, |x| {
+            Ok((0.5 + 32767.0 * to_float(x)?.clamp(-1.0, 1.0)).floor() as i32 as u32)
+        }),
+        Mf::Pack2x16unorm => pack(&args[0], 16, |x| {
+            Ok((0.5 + 65535.0 * to_float(x)?.clamp(0.0, 1.0)).floor() as u32)
+        }),
+        Mf::Pack2x16float => pack(&args[0], 16, |x| {
+            Ok(half::f16::from_f64(to_float(x)?).to_bits() as u32)
+        }),
+        Mf::Pack4xI8 | Mf::Pack4xU8 => pack(&args[0], 8, |x| Ok(to_bits(x)?.0 as u32)),
+        Mf::Pack4xI8Clamp => pack(&args[0], 8, |x| match x {
+            Literal::I32(v) => Ok(v.clamp(-128, 127) as u32),
+            _ => Err(Error::Unsupported("this `pack4xI8Clamp` argument")),
+        }),
+        Mf::Pack4xU8Clamp => pack(&args[0], 8, |x| match x {
+            Literal::U32(v) => Ok(v.min(255)),
+            _ => Err(Error::Unsupported("this `pack4xU8Clamp` argument")),
+        }),
+        Mf::Unpack4x8snorm => unpack(&args[0], 8, |v| {
+            Literal::F32((v as u8 as i8 as f32 / 127.0).max(-1.0))
+        }),
+        Mf::Unpack4x8unorm => unpack(&args[0], 8, |v| Literal::F32(v as u8 as f32 / 255.0)),
+        Mf::Unpack2x16snorm => unpack(&args[0], 16, |v| {
+            Literal::F32((v as u16 as i16 as f32 / 32767.0).max(-1.0))
+        }),
+        Mf::Unpack2x16unorm => unpack(&args[0], 16, |v| Literal::F32(v as u16 as f32 / 65535.0)),
+        Mf::Unpack2x16float => unpack(&args[0], 16, |v| {
+            Literal::F32(half::f16::from_bits(v as u16).to_f32())
+        }),
+        Mf::Unpack4xI8 => unpack(&args[0], 8, |v| Literal::I32(v as u8 as i8 as i32)),
+        Mf::Unpack4xU8 => unpack(&args[0], 8, |v| Literal::U32(v & 0xff)),
+        Mf::Inverse => Err(Error::Unsupported("`inverse`")),
+    }
+}
+
+/// Applies `f` to each floating-point scalar of `args`.
+fn float(args: &[Value], f: impl Fn(&[f64]) -> f64) -> Result<Value> {
+    numeric(args, |_| 0, f)
+}
+
+/// Applies `int` to each integer scalar of `args`, or `float` to each
+/// floating-point one.
+fn numeric(
+    args: &[Value],
+    int: impl Fn(&[i128]) -> i128,
+    float: impl Fn(&[f64]) -> f64,
+) -> Result<Value> {
+    Value::zip_all(args, &mut |x| {
+        let wide = x.iter().map(|&x| widen(x)).collect::<Result<Vec<_>>>()?;
+        let ints: Option<Vec<_>> = wide
+            .iter()
+            .map(|w| match *w {
+                Wide::Int(v) => Some(v),
+                _ => None,
+            })
+            .collect();
+        let result = match ints {
+            Some(ints) if x[0].scalar().kind != crate::ScalarKind::Float => Wide::Int(int(&ints)),
+            _ => {
+                let floats = x.iter().map(|&x| to_float(x)).collect::<Result<Vec<_>>>()?;
+                Wide::Float(float(&floats))
+            }
+        };
+        narrow(result, x[0].scalar())
+    })
+}
+
+fn to_float(literal: Literal) -> Result<f64> {
+    match widen(literal)? {
+        Wide::Float(v) => Ok(v),
+        _ => Err(Error::Unsupported("integer arguments to this function")),
+    }
+}
+
+/// Returns the components of the floating-point vector `value`, and their
+/// type.
+fn floats(value: &Value) -> Result<(Vec<f64>, Scalar)> {
+    let components = value.clone().components();
+    let scalar = components
+        .first()
+        .map(|c| c.scalar().scalar())
+        .ok_or(Error::Unsupported("empty vectors"))?;
+    let floats = components
+        .iter()
+        .map(|c| to_float(c.scalar()))
+        .collect::<Result<_>>()?;
+    Ok((floats, scalar))
+}
+
+fn vector(components: impl IntoIterator<Item = f64>, scalar: Scalar) -> Result<Value> {
+    components
+        .into_iter()
+        .map(|v| narrow(Wide::Float(v), scalar).map(Value::Scalar))
+        .collect::<Result<_>>()
+        .map(Value::Composite)
+}
+
+pub(super) fn dot(a: &Value, b: &Value) -> Result<Literal> {
+    let products = a.clone().zip(b.clone(), |x, y| {
+        ops::binary(BinaryOperator::Multiply, x, y)
+    })?;
+    let mut products = products.components().into_iter().map(|p| p.scalar());
+    let first = products.next().ok_or(Error::Unsupported("empty vectors"))?;
+    products.try_fold(first, |sum, p| ops::binary(BinaryOperator::Add, sum, p))
+}
+
+fn dot_floats(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+fn length(a: &[f64]) -> f64 {
+    dot_floats(a, a).sqrt()
+}
+
+fn determinant(columns: &[Vec<f64>]) -> f64 {
+    if columns.len() == 1 {
+        return columns[0][0];
+    }
+    // Expand along the first row.
+    (0..columns.len())
+        .map(|i| {
+            let minor: Vec<Vec<f64>> = columns
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(_, column)| column[1..].to_vec())
+                .collect();
+            let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+            sign * columns[i][0] * determinant(&minor)
+        })
+        .sum()
+}
+
+/// Rounds `x` to the nearest integer, and halfway cases to the even one.
+fn round_ties_even(x: f64) -> f64 {
+    let rounded = x.round();
+    if (x - x.trunc()).abs() == 0.5 {
+        2.0 * (x / 2.0).round()
+    } else {
+        rounded
+    }
+}
+
+/// Splits `x` into a fraction with a magnitude in `[0.5, 1)` and a power of
+/// two.
+fn frexp(x: f64) -> (f64, i32) {
+    if x == 0.0 || !x.is_finite() {
+        return (x, 0);
+    }
+    let mut exponent = x.abs().log2().floor() as i32 + 1;
+    let mut fraction = x / 2f64.powi(exponent);
+    if fraction.abs() >= 1.0 {
+        fraction /= 2.0;
+        exponent += 1;
+    } else if fraction.abs() < 0.5 {
+        fraction *= 2.0;
+        exponent -= 1;
+    }
+    (fraction, exponent)
+}
+
+/// Returns the bits of the integer `literal`, and how many there are.
+const fn to_bits(literal: Literal) -> Result<(u64, u32)> {
+    Ok(match literal {
+        Literal::I32(v) => (v as u32 as u64, 32),
+        Literal::U32(v) => (v as u64, 32),
+        Literal::I64(v) => (v as u64, 64),
+        Literal::U64(v) => (v, 64),
+        _ => return Err(Error::Unsupported("non-integer arguments to this function")),
+    })
+}
+
+/// Returns an integer of the same type as `template` with the low bits of
+/// `bits`.
+const fn from_bits(template: Literal, bits: u64) -> Literal {
+    match template {
+        Literal::I32(_) => Literal::I32(bits as u32 as i32),
+        Literal::I64(_) => Literal::I64(bits as i64),
+        Literal::U64(_) => Literal::U64(bits),
+        _ => Literal::U32(bits as u32),
+    }
+}
+
+const fn mask(count: u32) -> u64 {
+    if count >= 64 {
+        u64::MAX
+    } else {
+        (1 << count) - 1
+    }
+}
+
+/// Applies `f` to the bits of each integer scalar of `args`, and how many
+/// there are.
+fn bits(args: &[Value], f: impl Fn(u64, u32) -> u64) -> Result<Value> {
+    args[0].clone().map(|x| {
+        let (v, w) = to_bits(x)?;
+        Ok(from_bits(x, f(v, w)))
+    })
+}
+
+/// Clamps the `offset` and `count` arguments of `extractBits` and
+/// `insertBits` to the width `w` of the other arguments.
+fn offset_and_count(offset: Literal, count: Literal, w: u32) -> Result<(u32, u32)> {
+    let (Literal::U32(offset), Literal::U32(count)) = (offset, count) else {
+        return Err(Error::Unsupported("these bit offsets and counts"));
+    };
+    let offset = offset.min(w);
+    Ok((offset, count.min(w - offset)))
+}
+
+fn packed(value: &Value) -> Result<u32> {
+    match value.scalar() {
+        Literal::U32(v) => Ok(v),
+        _ => Err(Error::Unsupported("this packed argument")),
+    }
+}
+
+/// Packs the components of `value` into a `u32`, `bits` bits each, after
+/// converting them with `f`.
+fn pack(value: &Value, bits: u32, f: impl Fn(Literal) -> Result<u32>) -> Result<Value> {
+    let mut packed = 0;
+    for (i, component) in value.clone().components().iter().enumerate() {
+        packed |= (f(component.scalar())? & mask(bits) as u32) << (i as u32 * bits);
+    }
+    Ok(Value::Scalar(Literal::U32(packed)))
+}
+
+/// Unpacks the `u32` `value` into a vector of `32 / bits` components,
+/// converting each group of `bits` bits with `f`.
+fn unpack(value: &Value, bits: u32, f: impl Fn(u32) -> Literal) -> Result<Value> {
+    let packed = packed(value)?;
+    Ok(Value::Composite(
+        (0..32 / bits)
+            .map(|i| Value::Scalar(f(packed >> (i * bits))))
+            .collect(),
+    ))
+}
@@ -0,0 +1,364 @@
+/*!
+A CPU interpreter for compute shaders.
+
+[`Interpreter::dispatch`] runs a compute entry point of a validated
+[`Module`] on the CPU, reading and writing the buffers and textures in a
+[`Resources`] map. This makes it possible to check what a shader computes in
+an ordinary `cargo test`, without a GPU.
+
+```rust
+# #[cfg(feature = "wgsl-in")] {
+use naga::interp::{Interpreter, Resource, Resources};
+
+let module = naga::front::wgsl::parse_str("
+    @group(0) @binding(0) var<storage, read_write> data: array<u32>;
+
+    @compute @workgroup_size(4)
+    fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+        data[id.x] = data[id.x] * 2u;
+    }
+").unwrap();
+let info = naga::valid::Validator::new(Default::default(), Default::default())
+    .validate(&module)
+    .unwrap();
+
+let mut bytes: Vec<u8> = [1u32, 2, 3, 4].iter().flat_map(|x| x.to_le_bytes()).collect();
+let mut resources = Resources::default();
+let binding = naga::ResourceBinding { group: 0, binding: 0 };
+resources.insert(binding, Resource::Buffer(&mut bytes));
+
+Interpreter::new(&module, &info)
+    .dispatch("main", [1, 1, 1], &mut resources)
+    .unwrap();
+assert_eq!(bytes[4..8], 4u32.to_le_bytes());
+# }
+```
+
+# Execution
+
+Workgroups run one after another, in order of their [`WorkGroupId`]'s `x`,
+`y` and `z` components. Within a workgroup, each invocation runs in order of
+its [`LocalInvocationIndex`] until it reaches a barrier or finishes, and then
+the next one gets to run. Once every invocation has stopped, those waiting at
+a barrier continue. So the results of a dispatch, including the values
+returned by atomic operations, are the same from one run to the next.
+
+Values are laid out in memory as the module's types describe, so buffer
+contents have the same layout as on a GPU. Integer arithmetic wraps, and
+integer division by zero follows WGSL's rules. Floating-point arithmetic is
+done with Rust's operations, so the results of functions like `sin` may differ
+in the last bits from a GPU's.
+
+# Limitations
+
+The interpreter doesn't support:
+
+- Overrides. Use `back::pipeline_constants::process_overrides` to replace
+  them with constants first.
+- Texture sampling, derivatives, subgroup operations, ray queries and
+  binding arrays.
+- Push constants.
+- Textures with more than one mip level or sample.
+
+Accessing memory outside of a variable or resource is an error, rather than
+being clamped or discarded. An invocation that takes more steps than
+[`Interpreter::with_step_limit`] allows is stopped with [`Error::StepLimit`],
+so a shader that never terminates fails instead of hanging.
+
+[`WorkGroupId`]: crate::BuiltIn::WorkGroupId
+[`LocalInvocationIndex`]: crate::BuiltIn::LocalInvocationIndex
+*/
+
+mod exec;
+mod image;
+mod math;
+mod ops;
+mod value;
+
+use crate::{
+    valid::ModuleInfo, AddressSpace, BuiltIn, FastHashMap, Module, ResourceBinding, ShaderStage,
+    StorageFormat,
+};
+
+use exec::{Executor, Invocation, Status};
+use value::Value;
+
+/// A resource that a shader's global variables can be bound to.
+#[derive(Debug)]
+pub enum Resource<'a> {
+    /// The contents of a storage or uniform buffer.
+    Buffer(&'a mut [u8]),
+    /// A texture, for sampled, depth and storage textures.
+    Texture(Texture<'a>),
+    /// A sampler. Since the interpreter doesn't sample textures, samplers have
+    /// no state.
+    Sampler,
+}
+
+/// The texels of a texture.
+///
+/// Texels are stored row by row, with no padding between rows, and each
+/// array layer or depth slice follows the previous one. Only a single mip
+/// level and a single sample are supported.
+#[derive(Debug)]
+pub struct Texture<'a> {
+    /// The texels.
+    pub data: &'a mut [u8],
+    /// The format of the texels.
+    ///
+    /// This is used for sampled and depth textures as well. Depth textures
+    /// usually use [`StorageFormat::R32Float`].
+    pub format: StorageFormat,
+    /// The width, height, and depth or number of array layers.
+    ///
+    /// For cube textures, the number of layers is six times the number of
+    /// cubes.
+    pub size: [u32; 3],
+}
+
+/// The resources a dispatch can access, by binding.
+pub type Resources<'a> = FastHashMap<ResourceBinding, Resource<'a>>;
+
+/// An error that stopped a dispatch.
+#[derive(Clone, Debug, thiserror::Error)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum Error {
+    #[error("There is no entry point named `{0}`")]
+    MissingEntryPoint(String),
+    #[error("Entry point `{0}` is not a compute shader")]
+    NotCompute(String),
+    #[error("No resource is bound to group {}, binding {}", .0.group, .0.binding)]
+    MissingResource(ResourceBinding),
+    #[error(
+        "The resource bound to group {group}, binding {binding} is not a {expected}",
+        group = .0.group,
+        binding = .0.binding,
+        expected = .1
+    )]
+    ResourceMismatch(ResourceBinding, &'static str),
+    #[error("The module uses overrides, which must be replaced with constants first")]
+    Override,
+    #[error("Memory was accessed out of bounds")]
+    OutOfBounds,
+    #[error("The interpreter doesn't support {0}")]
+    Unsupported(&'static str),
+    #[error("An invocation took more than {0} steps without finishing")]
+    StepLimit(u64),
+}
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Runs compute entry points of a module on the CPU.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Copy, Debug)]
+pub struct Interpreter<'a> {
+    module: &'a Module,
+    info: &'a ModuleInfo,
+    step_limit: u64,
+}
+
+impl<'a> Interpreter<'a> {
+    /// The number of steps an invocation may take, unless
+    /// [`with_step_limit`](Self::with_step_limit) sets another limit.
+    pub const DEFAULT_STEP_LIMIT: u64 = 1 << 24;
+
+    /// Creates an interpreter for `module`, which must have been validated
+    /// with the result `info`.
+    pub const fn new(module: &'a Module, info: &'a ModuleInfo) -> Self {
+        Interpreter {
+            module,
+            info,
+            step_limit: Self::DEFAULT_STEP_LIMIT,
+        }
+    }
+
+    /// Limits each invocation to `step_limit` steps, after which the
+    /// dispatch stops with [`Error::StepLimit`].
+    ///
+    /// A step runs one statement, or moves past the end of a block. The
+    /// limit keeps shaders that loop forever from hanging the caller.
+    pub const fn with_step_limit(self, step_limit: u64) -> Self {
+        Interpreter { step_limit, ..self }
+    }
+
+    /// Runs the compute entry point named `entry_point` for the given number
+    /// of workgroups in each dimension, with access to `resources`.
+    ///
+    /// Buffers and storage textures in `resources` hold the shader's writes
+    /// when this returns, including if it returns an error partway through.
+    pub fn dispatch(
+        &self,
+        entry_point: &str,
+        workgroups: [u32; 3],
+        resources: &mut Resources,
+    ) -> Result<()> {
+        let (index, ep) = self
+            .module
+            .entry_points
+            .iter()
+            .enumerate()
+            .find(|&(_, ep)| ep.name == entry_point)
+            .ok_or_else(|| Error::MissingEntryPoint(entry_point.to_string()))?;
+        if ep.stage != ShaderStage::Compute {
+            return Err(Error::NotCompute(entry_point.to_string()));
+        }
+        if ep.workgroup_size_overrides.is_some() {
+            return Err(Error::Override);
+        }
+        let ep_info = self.info.get_entry_point(index);
+
+        for z in 0..workgroups[2] {
+            for y in 0..workgroups[1] {
+                for x in 0..workgroups[0] {
+                    self.run_workgroup(ep, ep_info, [x, y, z], workgroups, resources)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn run_workgroup(
+        &self,
+        ep: &'a crate::EntryPoint,
+        ep_info: &'a crate::valid::FunctionInfo,
+        workgroup_id: [u32; 3],
+        num_workgroups: [u32; 3],
+        resources: &mut Resources,
+    ) -> Result<()> {
+        let [size_x, size_y, size_z] = ep.workgroup_size;
+        let mut workgroup = self.allocate_globals(AddressSpace::WorkGroup);
+
+        let mut invocations = Vec::new();
+        for z in 0..size_z {
+            for y in 0..size_y {
+                for x in 0..size_x {
+                    let builtins = Builtins {
+                        global_id: [
+                            workgroup_id[0] * size_x + x,
+                            workgroup_id[1] * size_y + y,
+                            workgroup_id[2] * size_z + z,
+                        ],
+                        local_id: [x, y, z],
+                        local_index: (z * size_y + y) * size_x + x,
+                        workgroup_id,
+                        num_workgroups,
+                    };
+                    let arguments = ep
+                        .function
+                        .arguments
+                        .iter()
+                        .map(|arg| builtins.argument(self.module, arg))
+                        .collect::<Result<_>>()?;
+                    let mut invocation =
+                        Invocation::new(self.allocate_globals(AddressSpace::Private));
+                    Executor {
+                        module: self.module,
+                        info: self.info,
+                        invocation: &mut invocation,
+                        workgroup: &mut workgroup,
+                        resources,
+                        step_limit: self.step_limit,
+                    }
+                    .start(&ep.function, ep_info, arguments)?;
+                    invocations.push(invocation);
+                }
+            }
+        }
+
+        // Run every invocation until it reaches a barrier or finishes, and
+        // repeat until none are waiting at a barrier.
+        loop {
+            let mut waiting = false;
+            for invocation in invocations.iter_mut() {
+                if invocation.status == Status::Running {
+                    Executor {
+                        module: self.module,
+                        info: self.info,
+                        invocation,
+                        workgroup: &mut workgroup,
+                        resources,
+                        step_limit: self.step_limit,
+                    }
+                    .run()?;
+                }
+                waiting |= invocation.status == Status::Barrier;
+            }
+            if !waiting {
+                return Ok(());
+            }
+            for invocation in invocations.iter_mut() {
+                if invocation.status == Status::Barrier {
+                    invocation.status = Status::Running;
+                }
+            }
+        }
+    }
+
+    /// Returns zeroed memory for the global variables in `space`, indexed by
+    /// handle. Other variables get no memory.
+    ///
+    /// Private variables with an initializer are initialized when an
+    /// invocation starts.
+    fn allocate_globals(&self, space: AddressSpace) -> Vec<Vec<u8>> {
+        self.module
+            .global_variables
+            .iter()
+            .map(|(_, var)| {
+                if var.space == space {
+                    let size = self.module.types[var.ty].inner.size(self.module.to_ctx());
+                    vec![0; size as usize]
+                } else {
+                    Vec::new()
+                }
+            })
+            .collect()
+    }
+}
+
+/// The values of the built-in inputs of a compute shader invocation.
+struct Builtins {
+    global_id: [u32; 3],
+    local_id: [u32; 3],
+    local_index: u32,
+    workgroup_id: [u32; 3],
+    num_workgroups: [u32; 3],
+}
+
+impl Builtins {
+    fn argument(&self, module: &Module, arg: &crate::FunctionArgument) -> Result<Value> {
+        match arg.binding {
+            Some(crate::Binding::BuiltIn(built_in)) => self.value(built_in),
+            Some(crate::Binding::Location { .. }) => Err(Error::Unsupported("user-defined inputs")),
+            None => match module.types[arg.ty].inner {
+                crate::TypeInner::Struct { ref members, .. } => members
+                    .iter()
+                    .map(|member| match member.binding {
+                        Some(crate::Binding::BuiltIn(built_in)) => self.value(built_in),
+                        _ => Err(Error::Unsupported("user-defined inputs")),
+                    })
+                    .collect::<Result<_>>()
+                    .map(Value::Composite),
+                _ => Err(Error::Unsupported("user-defined inputs")),
+            },
+        }
+    }
+
+    fn value(&self, built_in: BuiltIn) -> Result<Value> {
+        let vec3 = |v: [u32; 3]| {
+            Value::Composite(
+                v.iter()
+                    .map(|&c| Value::Scalar(crate::Literal::U32(c)))
+                    .collect(),
+            )
+        };
+        Ok(match built_in {
+            BuiltIn::GlobalInvocationId => vec3(self.global_id),
+            BuiltIn::LocalInvocationId => vec3(self.local_id),
+            BuiltIn::LocalInvocationIndex => Value::Scalar(crate::Literal::U32(self.local_index)),
+            BuiltIn::WorkGroupId => vec3(self.workgroup_id),
+            BuiltIn::NumWorkGroups => vec3(self.num_workgroups),
+            _ => return Err(Error::Unsupported("this built-in input")),
+        })
+    }
+}
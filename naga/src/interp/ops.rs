@@ -0,0 +1,177 @@
+//! Operators and conversions on scalars.
+
+use num_traits::{CheckedRem, Float, PrimInt, WrappingAdd, WrappingMul, WrappingSub};
+
+use super::{Error, Result};
+use crate::{BinaryOperator as Bo, Literal, Scalar, ScalarKind, UnaryOperator};
+
+pub(super) fn unary(op: UnaryOperator, value: Literal) -> Result<Literal> {
+    Ok(match (op, value) {
+        (UnaryOperator::Negate, Literal::I32(v)) => Literal::I32(v.wrapping_neg()),
+        (UnaryOperator::Negate, Literal::I64(v)) => Literal::I64(v.wrapping_neg()),
+        (UnaryOperator::Negate, Literal::F16(v)) => Literal::F16(-v),
+        (UnaryOperator::Negate, Literal::F32(v)) => Literal::F32(-v),
+        (UnaryOperator::Negate, Literal::F64(v)) => Literal::F64(-v),
+        (UnaryOperator::LogicalNot | UnaryOperator::BitwiseNot, Literal::Bool(v)) => {
+            Literal::Bool(!v)
+        }
+        (UnaryOperator::BitwiseNot, Literal::I32(v)) => Literal::I32(!v),
+        (UnaryOperator::BitwiseNot, Literal::U32(v)) => Literal::U32(!v),
+        (UnaryOperator::BitwiseNot, Literal::I64(v)) => Literal::I64(!v),
+        (UnaryOperator::BitwiseNot, Literal::U64(v)) => Literal::U64(!v),
+        _ => return Err(Error::Unsupported("this unary operation")),
+    })
+}
+
+pub(super) fn binary(op: Bo, left: Literal, right: Literal) -> Result<Literal> {
+    if let Bo::ShiftLeft | Bo::ShiftRight = op {
+        // The shift amount is always a `u32`, whatever the type shifted.
+        let Literal::U32(amount) = right else {
+            return Err(Error::Unsupported("this shift operation"));
+        };
+        let left_shift = op == Bo::ShiftLeft;
+        return Ok(match left {
+            Literal::I32(v) if left_shift => Literal::I32(v.wrapping_shl(amount)),
+            Literal::I32(v) => Literal::I32(v.wrapping_shr(amount)),
+            Literal::U32(v) if left_shift => Literal::U32(v.wrapping_shl(amount)),
+            Literal::U32(v) => Literal::U32(v.wrapping_shr(amount)),
+            Literal::I64(v) if left_shift => Literal::I64(v.wrapping_shl(amount)),
+            Literal::I64(v) => Literal::I64(v.wrapping_shr(amount)),
+            Literal::U64(v) if left_shift => Literal::U64(v.wrapping_shl(amount)),
+            Literal::U64(v) => Literal::U64(v.wrapping_shr(amount)),
+            _ => return Err(Error::Unsupported("this shift operation")),
+        });
+    }
+
+    match (left, right) {
+        (Literal::I32(a), Literal::I32(b)) => integer(op, a, b, Literal::I32),
+        (Literal::U32(a), Literal::U32(b)) => integer(op, a, b, Literal::U32),
+        (Literal::I64(a), Literal::I64(b)) => integer(op, a, b, Literal::I64),
+        (Literal::U64(a), Literal::U64(b)) => integer(op, a, b, Literal::U64),
+        (Literal::F16(a), Literal::F16(b)) => float(op, a, b, Literal::F16),
+        (Literal::F32(a), Literal::F32(b)) => float(op, a, b, Literal::F32),
+        (Literal::F64(a), Literal::F64(b)) => float(op, a, b, Literal::F64),
+        (Literal::Bool(a), Literal::Bool(b)) => Ok(Literal::Bool(match op {
+            Bo::Equal => a == b,
+            Bo::NotEqual => a != b,
+            Bo::And | Bo::LogicalAnd => a & b,
+            Bo::InclusiveOr | Bo::LogicalOr => a | b,
+            Bo::ExclusiveOr => a ^ b,
+            _ => return Err(Error::Unsupported("this boolean operation")),
+        })),
+        _ => Err(Error::Unsupported("operands of these types")),
+    }
+}
+
+fn integer<T>(op: Bo, a: T, b: T, wrap: fn(T) -> Literal) -> Result<Literal>
+where
+    T: PrimInt + CheckedRem + WrappingAdd + WrappingSub + WrappingMul,
+{
+    Ok(match op {
+        Bo::Add => wrap(a.wrapping_add(&b)),
+        Bo::Subtract => wrap(a.wrapping_sub(&b)),
+        Bo::Multiply => wrap(a.wrapping_mul(&b)),
+        // WGSL defines division by zero, and the overflowing division of the
+        // most negative value by -1, to produce the left operand, and the
+        // remainder in both cases to be zero.
+        Bo::Divide => wrap(a.checked_div(&b).unwrap_or(a)),
+        Bo::Modulo => wrap(a.checked_rem(&b).unwrap_or_else(T::zero)),
+        Bo::Equal => Literal::Bool(a == b),
+        Bo::NotEqual => Literal::Bool(a != b),
+        Bo::Less => Literal::Bool(a < b),
+        Bo::LessEqual => Literal::Bool(a <= b),
+        Bo::Greater => Literal::Bool(a > b),
+        Bo::GreaterEqual => Literal::Bool(a >= b),
+        Bo::And => wrap(a & b),
+        Bo::ExclusiveOr => wrap(a ^ b),
+        Bo::InclusiveOr => wrap(a | b),
+        Bo::LogicalAnd | Bo::LogicalOr | Bo::ShiftLeft | Bo::ShiftRight => {
+            return Err(Error::Unsupported("this integer operation"))
+        }
+    })
+}
+
+fn float<T: Float>(op: Bo, a: T, b: T, wrap: fn(T) -> Literal) -> Result<Literal> {
+    Ok(match op {
+        Bo::Add => wrap(a + b),
+        Bo::Subtract => wrap(a - b),
+        Bo::Multiply => wrap(a * b),
+        Bo::Divide => wrap(a / b),
+        Bo::Modulo => wrap(a % b),
+        Bo::Equal => Literal::Bool(a == b),
+        Bo::NotEqual => Literal::Bool(a != b),
+        Bo::Less => Literal::Bool(a < b),
+        Bo::LessEqual => Literal::Bool(a <= b),
+        Bo::Greater => Literal::Bool(a > b),
+        Bo::GreaterEqual => Literal::Bool(a >= b),
+        _ => return Err(Error::Unsupported("this floating-point operation")),
+    })
+}
+
+/// A scalar widened to the largest type of its kind, to implement functions
+/// once for every width.
+#[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
+pub(super) enum Wide {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+}
+
+pub(super) fn widen(literal: Literal) -> Result<Wide> {
+    Ok(match literal {
+        Literal::Bool(v) => Wide::Bool(v),
+        Literal::I32(v) => Wide::Int(v.into()),
+        Literal::U32(v) => Wide::Int(v.into()),
+        Literal::I64(v) => Wide::Int(v.into()),
+        Literal::U64(v) => Wide::Int(v.into()),
+        Literal::F16(v) => Wide::Float(v.into()),
+        Literal::F32(v) => Wide::Float(v.into()),
+        Literal::F64(v) => Wide::Float(v),
+        Literal::AbstractInt(_) | Literal::AbstractFloat(_) => {
+            return Err(Error::Unsupported("abstract values"))
+        }
+    })
+}
+
+/// Narrows `wide` to a scalar of type `scalar`.
+///
+/// Integers wrap around, and floating-point values convert to integers by
+/// rounding toward zero and clamping, as WGSL's conversions do.
+pub(super) fn narrow(wide: Wide, scalar: Scalar) -> Result<Literal> {
+    let int = match wide {
+        Wide::Bool(v) => v as i128,
+        Wide::Int(v) => v,
+        Wide::Float(v) => match (scalar.kind, scalar.width) {
+            (ScalarKind::Sint, 4) => (v as i32).into(),
+            (ScalarKind::Uint, 4) => (v as u32).into(),
+            (ScalarKind::Sint, 8) => (v as i64).into(),
+            (ScalarKind::Uint, 8) => (v as u64).into(),
+            _ => 0,
+        },
+    };
+    let float = match wide {
+        Wide::Bool(v) => v as u8 as f64,
+        Wide::Int(v) => v as f64,
+        Wide::Float(v) => v,
+    };
+    Ok(match (scalar.kind, scalar.width) {
+        (ScalarKind::Bool, _) => Literal::Bool(match wide {
+            Wide::Bool(v) => v,
+            Wide::Int(v) => v != 0,
+            Wide::Float(v) => v != 0.0,
+        }),
+        (ScalarKind::Sint, 4) => Literal::I32(int as i32),
+        (ScalarKind::Uint, 4) => Literal::U32(int as u32),
+        (ScalarKind::Sint, 8) => Literal::I64(int as i64),
+        (ScalarKind::Uint, 8) => Literal::U64(int as u64),
+        (ScalarKind::Float, 2) => Literal::F16(half::f16::from_f64(float)),
+        (ScalarKind::Float, 4) => Literal::F32(float as f32),
+        (ScalarKind::Float, 8) => Literal::F64(float),
+        _ => return Err(Error::Unsupported("abstract or unusually sized scalars")),
+    })
+}
+
+/// Converts `literal` to a scalar of type `scalar`, as an `As` expression does.
+pub(super) fn convert(literal: Literal, scalar: Scalar) -> Result<Literal> {
+    narrow(widen(literal)?, scalar)
+}
@@ -0,0 +1,301 @@
+//! Values, and how they are laid out in memory.
+
+use super::{Error, Result};
+use crate::{
+    proc::Alignment, ArraySize, GlobalVariable, Handle, Literal, LocalVariable, Scalar, ScalarKind,
+    Type, TypeInner, UniqueArena,
+};
+
+/// The value of an expression.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) enum Value {
+    Scalar(Literal),
+    /// A vector, matrix, array or struct. The components of a matrix are its
+    /// columns.
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+    /// An image, identified by the global variable it is bound to.
+    Image(Handle<GlobalVariable>),
+    Sampler,
+}
+
+/// A pointer to a value somewhere in memory.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) struct Pointer {
+    pub memory: Memory,
+    /// The offset of the value in bytes from the start of `memory`.
+    pub offset: u32,
+}
+
+/// A piece of memory that a [`Pointer`] can point into.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(super) enum Memory {
+    /// A local variable of the function running in the given frame of the
+    /// invocation's call stack.
+    Local {
+        frame: usize,
+        variable: Handle<LocalVariable>,
+    },
+    /// A private variable of the invocation.
+    Private(Handle<GlobalVariable>),
+    /// A workgroup variable of the invocation's workgroup.
+    WorkGroup(Handle<GlobalVariable>),
+    /// The buffer bound to a uniform or storage variable.
+    Buffer(Handle<GlobalVariable>),
+}
+
+impl Value {
+    /// Returns the scalar this value holds.
+    ///
+    /// Validation guarantees that the operands that this is used on are
+    /// scalars.
+    pub fn scalar(&self) -> Literal {
+        match *self {
+            Value::Scalar(literal) => literal,
+            _ => unreachable!("expected a scalar, found {self:?}"),
+        }
+    }
+
+    pub fn pointer(&self) -> Pointer {
+        match *self {
+            Value::Pointer(pointer) => pointer,
+            _ => unreachable!("expected a pointer, found {self:?}"),
+        }
+    }
+
+    pub fn components(self) -> Vec<Value> {
+        match self {
+            Value::Composite(components) => components,
+            other => vec![other],
+        }
+    }
+
+    /// Returns the value of `self` to combine with the `index`th component of
+    /// another operand: the component of a composite, or a scalar itself.
+    fn lane(&self, index: usize) -> Value {
+        match *self {
+            Value::Composite(ref components) => components[index].clone(),
+            ref other => other.clone(),
+        }
+    }
+
+    /// Applies `f` to each scalar in `self`.
+    pub fn map(self, mut f: impl FnMut(Literal) -> Result<Literal>) -> Result<Value> {
+        Self::zip_all(&[self], &mut |args| f(args[0]))
+    }
+
+    /// Applies `f` to each pair of scalars in `self` and `other`. If one of
+    /// them is a scalar and the other isn't, the scalar is paired with every
+    /// scalar of the other.
+    pub fn zip(
+        self,
+        other: Value,
+        mut f: impl FnMut(Literal, Literal) -> Result<Literal>,
+    ) -> Result<Value> {
+        Self::zip_all(&[self, other], &mut |args| f(args[0], args[1]))
+    }
+
+    /// Like [`zip`](Value::zip), for any number of operands.
+    pub fn zip_all(
+        args: &[Value],
+        f: &mut impl FnMut(&[Literal]) -> Result<Literal>,
+    ) -> Result<Value> {
+        let len = args.iter().find_map(|arg| match *arg {
+            Value::Composite(ref components) => Some(components.len()),
+            _ => None,
+        });
+        match len {
+            Some(len) => (0..len)
+                .map(|index| {
+                    let lanes: Vec<_> = args.iter().map(|arg| arg.lane(index)).collect();
+                    Self::zip_all(&lanes, f)
+                })
+                .collect::<Result<_>>()
+                .map(Value::Composite),
+            None => {
+                let scalars: Vec<_> = args.iter().map(Value::scalar).collect();
+                f(&scalars).map(Value::Scalar)
+            }
+        }
+    }
+}
+
+/// Returns the distance in bytes between the columns of a matrix.
+pub(super) fn column_stride(rows: crate::VectorSize, scalar: Scalar) -> u32 {
+    Alignment::from(rows) * scalar.width as u32
+}
+
+/// Returns the bytes of `memory` at `offset` for a value of `size` bytes.
+fn bytes(memory: &[u8], offset: u32, size: u8) -> Result<&[u8]> {
+    let start = offset as usize;
+    memory
+        .get(start..start + size as usize)
+        .ok_or(Error::OutOfBounds)
+}
+
+fn bytes_mut(memory: &mut [u8], offset: u32, size: u8) -> Result<&mut [u8]> {
+    let start = offset as usize;
+    memory
+        .get_mut(start..start + size as usize)
+        .ok_or(Error::OutOfBounds)
+}
+
+pub(super) fn read_scalar(memory: &[u8], offset: u32, scalar: Scalar) -> Result<Literal> {
+    let bytes = bytes(memory, offset, scalar.width)?;
+    Ok(match (scalar.kind, scalar.width) {
+        (ScalarKind::Bool, _) => Literal::Bool(bytes.iter().any(|&b| b != 0)),
+        (ScalarKind::Sint, 4) => Literal::I32(i32::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Sint, 8) => Literal::I64(i64::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Uint, 4) => Literal::U32(u32::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Uint, 8) => Literal::U64(u64::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Float, 2) => Literal::F16(half::f16::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Float, 4) => Literal::F32(f32::from_le_bytes(bytes.try_into().unwrap())),
+        (ScalarKind::Float, 8) => Literal::F64(f64::from_le_bytes(bytes.try_into().unwrap())),
+        _ => return Err(Error::Unsupported("abstract or unusually sized scalars")),
+    })
+}
+
+pub(super) fn write_scalar(memory: &mut [u8], offset: u32, literal: Literal) -> Result<()> {
+    let bytes = bytes_mut(memory, offset, literal.width())?;
+    match literal {
+        Literal::Bool(b) => bytes.fill(b as u8),
+        Literal::I32(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::U32(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::I64(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::U64(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::F16(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::F32(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::F64(v) => bytes.copy_from_slice(&v.to_le_bytes()),
+        Literal::AbstractInt(_) | Literal::AbstractFloat(_) => {
+            return Err(Error::Unsupported("abstract values"))
+        }
+    }
+    Ok(())
+}
+
+/// Reads a value of type `inner` from `memory` at `offset`.
+pub(super) fn read(
+    memory: &[u8],
+    offset: u32,
+    inner: &TypeInner,
+    types: &UniqueArena<Type>,
+) -> Result<Value> {
+    Ok(match *inner {
+        TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => {
+            Value::Scalar(read_scalar(memory, offset, scalar)?)
+        }
+        TypeInner::Vector { size, scalar } => Value::Composite(
+            (0..size as u32)
+                .map(|i| read_scalar(memory, offset + i * scalar.width as u32, scalar))
+                .map(|literal| literal.map(Value::Scalar))
+                .collect::<Result<_>>()?,
+        ),
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => {
+            let column = TypeInner::Vector { size: rows, scalar };
+            let stride = column_stride(rows, scalar);
+            Value::Composite(
+                (0..columns as u32)
+                    .map(|i| read(memory, offset + i * stride, &column, types))
+                    .collect::<Result<_>>()?,
+            )
+        }
+        TypeInner::Array {
+            base,
+            size: ArraySize::Constant(count),
+            stride,
+        } => Value::Composite(
+            (0..count.get())
+                .map(|i| read(memory, offset + i * stride, &types[base].inner, types))
+                .collect::<Result<_>>()?,
+        ),
+        TypeInner::Struct { ref members, .. } => Value::Composite(
+            members
+                .iter()
+                .map(|member| {
+                    read(
+                        memory,
+                        offset + member.offset,
+                        &types[member.ty].inner,
+                        types,
+                    )
+                })
+                .collect::<Result<_>>()?,
+        ),
+        _ => return Err(Error::Unsupported("loading values of this type")),
+    })
+}
+
+/// Writes `value`, of type `inner`, to `memory` at `offset`.
+pub(super) fn write(
+    memory: &mut [u8],
+    offset: u32,
+    inner: &TypeInner,
+    types: &UniqueArena<Type>,
+    value: &Value,
+) -> Result<()> {
+    let components = match *value {
+        Value::Scalar(literal) => return write_scalar(memory, offset, literal),
+        Value::Composite(ref components) => components,
+        _ => return Err(Error::Unsupported("storing values of this type")),
+    };
+    match *inner {
+        TypeInner::Vector { scalar, .. } => {
+            for (i, component) in components.iter().enumerate() {
+                let offset = offset + i as u32 * scalar.width as u32;
+                write_scalar(memory, offset, component.scalar())?;
+            }
+        }
+        TypeInner::Matrix { rows, scalar, .. } => {
+            let column = TypeInner::Vector { size: rows, scalar };
+            let stride = column_stride(rows, scalar);
+            for (i, component) in components.iter().enumerate() {
+                write(
+                    memory,
+                    offset + i as u32 * stride,
+                    &column,
+                    types,
+                    component,
+                )?;
+            }
+        }
+        TypeInner::Array { base, stride, .. } => {
+            for (i, component) in components.iter().enumerate() {
+                let offset = offset + i as u32 * stride;
+                write(memory, offset, &types[base].inner, types, component)?;
+            }
+        }
+        TypeInner::Struct { ref members, .. } => {
+            for (member, component) in members.iter().zip(components) {
+                let offset = offset + member.offset;
+                write(memory, offset, &types[member.ty].inner, types, component)?;
+            }
+        }
+        _ => return Err(Error::Unsupported("storing values of this type")),
+    }
+    Ok(())
+}
+
+/// Returns the zero value of type `inner`.
+pub(super) fn zero(inner: &TypeInner, types: &UniqueArena<Type>) -> Result<Value> {
+    let size = match *inner {
+        TypeInner::Struct { span, .. } => span,
+        TypeInner::Array {
+            size: ArraySize::Constant(count),
+            stride,
+            ..
+        } => count.get() * stride,
+        TypeInner::Matrix {
+            columns,
+            rows,
+            scalar,
+        } => columns as u32 * column_stride(rows, scalar),
+        TypeInner::Vector { size, scalar } => size as u32 * scalar.width as u32,
+        TypeInner::Scalar(scalar) | TypeInner::Atomic(scalar) => scalar.width as u32,
+        _ => return Err(Error::Unsupported("zero values of this type")),
+    };
+    read(&vec![0; size as usize], 0, inner, types)
+}
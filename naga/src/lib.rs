@@ -259,8 +259,12 @@ pub mod compact;
 pub mod diagnostic_filter;
 pub mod error;
 pub mod front;
+#[cfg(feature = "interp")]
+pub mod interp;
 pub mod keywords;
 mod non_max_u32;
+#[cfg(feature = "opt")]
+pub mod opt;
 pub mod proc;
 mod span;
 pub mod valid;
@@ -888,6 +892,8 @@ pub enum Literal {
     F64(f64),
     /// May not be NaN or infinity.
     F32(f32),
+    /// May not be NaN or infinity.
+    F16(#[cfg_attr(feature = "arbitrary", arbitrary(with = arbitrary_f16))] half::f16),
     U32(u32),
     I32(i32),
     U64(u64),
@@ -897,6 +903,12 @@ pub enum Literal {
     AbstractFloat(f64),
 }
 
+/// Generate an arbitrary [`half::f16`], which doesn't implement [`Arbitrary`] itself.
+#[cfg(feature = "arbitrary")]
+fn arbitrary_f16(u: &mut arbitrary::Unstructured) -> arbitrary::Result<half::f16> {
+    Ok(half::f16::from_bits(u.arbitrary()?))
+}
+
 /// Pipeline-overridable constant.
 #[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serialize", derive(Serialize))]
@@ -1196,6 +1208,8 @@ pub enum MathFunction {
     Pow,
     // geometry
     Dot,
+    Dot4I8Packed,
+    Dot4U8Packed,
     Outer,
     Cross,
     Distance,
@@ -1233,6 +1247,8 @@ pub enum MathFunction {
     Pack2x16float,
     Pack4xI8,
     Pack4xU8,
+    Pack4xI8Clamp,
+    Pack4xU8Clamp,
     // data unpacking
     Unpack4x8snorm,
     Unpack4x8unorm,
@@ -1323,6 +1339,27 @@ pub enum GatherMode {
     ShuffleUp(Handle<Expression>),
     /// Each gathers from their lane xored with the given by the expression
     ShuffleXor(Handle<Expression>),
+    /// All in a quad gather from the lane of the quad at the index given by the expression
+    QuadBroadcast(Handle<Expression>),
+    /// Each gathers from the neighboring lane of its quad in the given direction
+    QuadSwap(Direction),
+}
+
+/// The neighbor a [`GatherMode::QuadSwap`] exchanges values with.
+///
+/// The lanes of a quad are arranged as a 2x2 grid, with lane 0 at the top left,
+/// lane 1 at the top right, lane 2 at the bottom left and lane 3 at the bottom right.
+#[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+#[cfg_attr(feature = "deserialize", derive(Deserialize))]
+#[cfg_attr(feature = "arbitrary", derive(Arbitrary))]
+pub enum Direction {
+    /// The lane in the same row.
+    X = 0,
+    /// The lane in the same column.
+    Y = 1,
+    /// The lane in the other row and column.
+    Diagonal = 2,
 }
 
 #[derive(Clone, Copy, Debug, Hash, Eq, Ord, PartialEq, PartialOrd)]
@@ -1364,6 +1401,8 @@ bitflags::bitflags! {
         const WORK_GROUP = 1 << 1;
         /// Barrier synchronizes execution across all invocations within a subgroup that execute this instruction.
         const SUB_GROUP = 1 << 2;
+        /// Barrier affects all storage texture accesses, in the [`AddressSpace::Handle`] space.
+        const TEXTURE = 1 << 3;
     }
 }
 
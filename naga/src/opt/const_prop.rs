@@ -0,0 +1,574 @@
+//! Propagating constant values through local variables.
+//!
+//! A local variable that is only ever given one constant value might as
+//! well be that constant. This pass looks for local variables that are
+//! either:
+//!
+//! - never assigned, so that they always hold their initializer, or
+//!
+//! - assigned exactly once, by a [`Store`] of a constant expression that
+//!   every [`Load`] from the variable must follow,
+//!
+//! and replaces loads from them with the value itself. If every load from a
+//! variable can be replaced, the variable and its [`Store`] are removed.
+//!
+//! Loads of individual components through [`AccessIndex`] expressions are
+//! replaced with the corresponding component of the value. Loads through
+//! dynamically indexed [`Access`] expressions are left alone.
+//!
+//! Expressions whose operands are all constant once loads have been
+//! replaced, like the `1f * 3f` left by propagating `3f` into `x * 3f`, are
+//! evaluated with the [`ConstantEvaluator`], following WGSL's rules for
+//! constant expressions. Those the evaluator can't handle, or that would be
+//! errors in a WGSL constant expression, are left for run time.
+//!
+//! [`Store`]: crate::Statement::Store
+//! [`Load`]: crate::Expression::Load
+//! [`Access`]: crate::Expression::Access
+//! [`AccessIndex`]: crate::Expression::AccessIndex
+//! [`ConstantEvaluator`]: crate::proc::ConstantEvaluator
+
+use super::{
+    locals::LocalUses,
+    rebuild::{rebuild, Pass, Rebuilder},
+};
+use crate::{
+    proc::{
+        operands::{adjust_expression, for_each_expression_operand},
+        ConstantEvaluator, Emitter, ExpressionKindTracker,
+    },
+    Arena, Block, Expression, Function, Handle, LocalVariable, Module, Span, Statement, TypeInner,
+};
+
+pub(super) fn propagate_constants(module: &Module, function: &Function) -> Function {
+    let uses = LocalUses::new(function);
+    let mut finder = Finder {
+        function,
+        uses: &uses,
+        kinds: ExpressionKindTracker::from_arena(&function.expressions),
+        stores: vec![None; function.local_variables.len()],
+        loads: vec![Vec::new(); function.local_variables.len()],
+        path: Vec::new(),
+        next_block: 0,
+    };
+    finder.block(&function.body);
+
+    let mut values = Vec::with_capacity(function.local_variables.len());
+    let mut removed = Vec::with_capacity(function.local_variables.len());
+    for (local, variable) in function.local_variables.iter() {
+        let local_use = uses.get(local);
+        let value = if local_use.escapes || local_use.partially_stored {
+            None
+        } else if local_use.stores == 0 {
+            Some(Value::Init(variable.init))
+        } else {
+            finder.stored_value(local)
+        };
+        removed.push(value.is_some() && finder.all_loads_replaceable(local));
+        values.push(value);
+    }
+
+    rebuild(
+        module,
+        function,
+        &mut ConstantPropagation {
+            uses,
+            values,
+            removed,
+            folder: Folder::new(module),
+        },
+    )
+}
+
+/// The value every load from a local variable produces.
+#[derive(Clone, Copy)]
+enum Value {
+    /// The variable's initializer, or zero if it has none.
+    Init(Option<Handle<Expression>>),
+
+    /// The constant expression assigned by the variable's only [`Store`].
+    ///
+    /// [`Store`]: Statement::Store
+    Stored(Handle<Expression>),
+}
+
+/// A position in a function's body: a block, and a statement index within
+/// it.
+///
+/// Blocks are numbered in the order [`Finder`] visits them.
+type Position = (usize, usize);
+
+/// Finds the stores and loads of each local variable.
+struct Finder<'a> {
+    function: &'a Function,
+    uses: &'a LocalUses,
+    kinds: ExpressionKindTracker,
+
+    /// For each local variable, the position and value of the last
+    /// constant [`Store`] assigning the whole variable.
+    ///
+    /// [`Store`]: Statement::Store
+    stores: Vec<Option<(Position, Handle<Expression>)>>,
+
+    /// For each local variable, the positions of the statements enclosing
+    /// each [`Emit`] of a [`Load`] from it, innermost last, and that load's
+    /// pointer operand.
+    ///
+    /// [`Emit`]: Statement::Emit
+    /// [`Load`]: Expression::Load
+    loads: Vec<Vec<(Vec<Position>, Handle<Expression>)>>,
+
+    /// The positions enclosing the statement being visited.
+    path: Vec<Position>,
+
+    next_block: usize,
+}
+
+impl Finder<'_> {
+    fn block(&mut self, block: &Block) {
+        let id = self.next_block;
+        self.next_block += 1;
+        for (index, statement) in block.iter().enumerate() {
+            self.path.push((id, index));
+            match *statement {
+                Statement::Emit(ref range) => {
+                    for handle in range.clone() {
+                        if let Expression::Load { pointer } = self.function.expressions[handle] {
+                            if let Some(local) = self.uses.root(pointer) {
+                                self.loads[local.index()].push((self.path.clone(), pointer));
+                            }
+                        }
+                    }
+                }
+                Statement::Block(ref block) => self.block(block),
+                Statement::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.block(accept);
+                    self.block(reject);
+                }
+                Statement::Switch { ref cases, .. } => {
+                    for case in cases.iter() {
+                        self.block(&case.body);
+                    }
+                }
+                Statement::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.block(body);
+                    self.block(continuing);
+                }
+                Statement::Store { pointer, value } => {
+                    if let Expression::LocalVariable(local) = self.function.expressions[pointer] {
+                        if self.kinds.is_const_or_override(value) {
+                            self.stores[local.index()] = Some(((id, index), value));
+                        }
+                    }
+                }
+                _ => {}
+            }
+            self.path.pop();
+        }
+    }
+
+    /// If `local`'s only [`Store`] assigns a constant, and every load from
+    /// `local` follows it, return that constant.
+    ///
+    /// Every path from the start of the function to a load that is nested
+    /// within a later statement of the store's block passes through the
+    /// store, so those loads always see the stored value.
+    ///
+    /// [`Store`]: Statement::Store
+    fn stored_value(&self, local: Handle<LocalVariable>) -> Option<Value> {
+        if self.uses.get(local).stores != 1 {
+            return None;
+        }
+        let ((block, index), value) = self.stores[local.index()]?;
+        let dominated = self.loads[local.index()].iter().all(|&(ref path, _)| {
+            path.iter()
+                .any(|&(load_block, load_index)| load_block == block && load_index > index)
+        });
+        dominated.then_some(Value::Stored(value))
+    }
+
+    /// Return true if every load from `local` only uses constant indices.
+    fn all_loads_replaceable(&self, local: Handle<LocalVariable>) -> bool {
+        self.loads[local.index()]
+            .iter()
+            .all(|&(_, pointer)| constant_path(self.function, pointer).is_some())
+    }
+}
+
+/// If `pointer` is a [`LocalVariable`] followed by zero or more
+/// [`AccessIndex`] expressions, return the variable and the indices,
+/// outermost first.
+///
+/// [`LocalVariable`]: Expression::LocalVariable
+/// [`AccessIndex`]: Expression::AccessIndex
+fn constant_path(
+    function: &Function,
+    mut pointer: Handle<Expression>,
+) -> Option<(Handle<LocalVariable>, Vec<u32>)> {
+    let mut indices = Vec::new();
+    loop {
+        match function.expressions[pointer] {
+            Expression::AccessIndex { base, index } => {
+                indices.push(index);
+                pointer = base;
+            }
+            Expression::LocalVariable(local) => {
+                indices.reverse();
+                return Some((local, indices));
+            }
+            _ => return None,
+        }
+    }
+}
+
+struct ConstantPropagation {
+    uses: LocalUses,
+
+    /// For each local variable, the value loads from it produce, if known.
+    values: Vec<Option<Value>>,
+
+    /// For each local variable, true if all loads from it will be replaced,
+    /// so it can be removed entirely.
+    removed: Vec<bool>,
+
+    folder: Folder,
+}
+
+impl ConstantPropagation {
+    fn is_removed(&self, pointer: Handle<Expression>) -> bool {
+        self.uses
+            .root(pointer)
+            .is_some_and(|local| self.removed[local.index()])
+    }
+
+    /// Return the value a [`Load`] from `pointer` produces, if known.
+    ///
+    /// [`Load`]: Expression::Load
+    fn load(
+        &self,
+        rb: &mut Rebuilder<'_, '_>,
+        pointer: Handle<Expression>,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        let (local, indices) = constant_path(rb.source, pointer)?;
+        let value = self.values[local.index()]?;
+        let span = rb.source.expressions.get_span(pointer);
+
+        let mut value = match value {
+            Value::Init(Some(init)) => rb.copy_tree(init, out),
+            Value::Init(None) => {
+                let ty = rb.source.local_variables[local].ty;
+                rb.append(Expression::ZeroValue(ty), span, out)
+            }
+            Value::Stored(value) => rb.lookup(value, out),
+        };
+        for index in indices {
+            value = component(rb, value, index, span, out);
+        }
+        Some(value)
+    }
+}
+
+/// Return an expression for component `index` of `value`.
+///
+/// Where `value` is built from its components, return the component directly.
+fn component(
+    rb: &mut Rebuilder<'_, '_>,
+    value: Handle<Expression>,
+    index: u32,
+    span: Span,
+    out: &mut Block,
+) -> Handle<Expression> {
+    match rb.target.expressions[value] {
+        Expression::Compose { ty, ref components } => {
+            let count = match rb.module.types[ty].inner {
+                TypeInner::Array { .. } | TypeInner::Struct { .. } => components.len(),
+                TypeInner::Vector { size, .. } => size as usize,
+                TypeInner::Matrix { columns, .. } => columns as usize,
+                _ => 0,
+            };
+            if components.len() == count {
+                return components[index as usize];
+            }
+        }
+        Expression::Splat { value, .. } => return value,
+        _ => {}
+    }
+    rb.append(Expression::AccessIndex { base: value, index }, span, out)
+}
+
+impl Pass for ConstantPropagation {
+    fn expression(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        old: Handle<Expression>,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        let source = rb.source;
+        match source.expressions[old] {
+            Expression::Load { pointer } => {
+                if let Some(value) = self.load(rb, pointer, out) {
+                    return Some(value);
+                }
+            }
+            Expression::Access { .. } | Expression::AccessIndex { .. } if self.is_removed(old) => {
+                return None;
+            }
+            _ => {}
+        }
+
+        let span = source.expressions.get_span(old);
+        let expr = rb.remap(old, out);
+        if let Some(value) = self.folder.fold(rb, &expr, span, out) {
+            return Some(value);
+        }
+        Some(rb.append(expr, span, out))
+    }
+
+    fn statement(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        statement: &Statement,
+        span: Span,
+        _rest: &[Statement],
+        out: &mut Block,
+    ) {
+        match *statement {
+            Statement::Store { pointer, .. } if self.is_removed(pointer) => {}
+            _ => rb.copy_statement(self, statement, span, out),
+        }
+    }
+}
+
+/// Evaluates expressions whose operands are all constant.
+///
+/// The [`ConstantEvaluator`] may need to add types to the module, so it
+/// works on a scratch copy of the module's types and constants. Values that
+/// refer to types the real module doesn't have are thrown away.
+struct Folder {
+    scratch: Module,
+}
+
+impl Folder {
+    fn new(module: &Module) -> Self {
+        Folder {
+            scratch: Module {
+                types: module.types.clone(),
+                constants: module.constants.clone(),
+                overrides: module.overrides.clone(),
+                global_expressions: module.global_expressions.clone(),
+                ..Module::default()
+            },
+        }
+    }
+
+    /// If `expr`, whose operands are expressions in `rb`'s target, can be
+    /// evaluated now, append its value to the target and return it.
+    fn fold(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        expr: &Expression,
+        span: Span,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        if !is_foldable(expr) {
+            return None;
+        }
+
+        let mut expressions = Arena::new();
+        let mut expr = expr.clone();
+        let mut constant = true;
+        adjust_expression(&mut expr, &mut |operand| match copy_constant(
+            &rb.target.expressions,
+            *operand,
+            &mut expressions,
+        ) {
+            Some(copy) => *operand = copy,
+            None => constant = false,
+        });
+        if !constant {
+            return None;
+        }
+
+        let mut kinds = ExpressionKindTracker::from_arena(&expressions);
+        let mut emitter = Emitter::default();
+        let mut block = Block::new();
+        let value = ConstantEvaluator::for_wgsl_function(
+            &mut self.scratch,
+            &mut expressions,
+            &mut kinds,
+            &mut emitter,
+            &mut block,
+            true,
+        )
+        .try_eval_and_append(expr, span)
+        .ok()?;
+
+        if uses_new_types(&expressions, value, rb.module.types.len()) {
+            return None;
+        }
+        Some(copy_value(rb, &expressions, value, out))
+    }
+}
+
+/// Return true if `expr` is worth evaluating when its operands are constant.
+const fn is_foldable(expr: &Expression) -> bool {
+    matches!(
+        *expr,
+        Expression::Access { .. }
+            | Expression::AccessIndex { .. }
+            | Expression::Swizzle { .. }
+            | Expression::Unary { .. }
+            | Expression::Binary { .. }
+            | Expression::Select { .. }
+            | Expression::Relational { .. }
+            | Expression::Math { .. }
+            | Expression::As { .. }
+    )
+}
+
+/// If `handle` in `source` is a constant built from literals, constants,
+/// and zero values, copy it to `target`.
+fn copy_constant(
+    source: &Arena<Expression>,
+    handle: Handle<Expression>,
+    target: &mut Arena<Expression>,
+) -> Option<Handle<Expression>> {
+    let mut expr = source[handle].clone();
+    match expr {
+        Expression::Literal(_) | Expression::Constant(_) | Expression::ZeroValue(_) => {}
+        Expression::Compose { .. } | Expression::Splat { .. } => {
+            let mut constant = true;
+            adjust_expression(&mut expr, &mut |operand| match copy_constant(
+                source, *operand, target,
+            ) {
+                Some(copy) => *operand = copy,
+                None => constant = false,
+            });
+            if !constant {
+                return None;
+            }
+        }
+        _ => return None,
+    }
+    Some(target.append(expr, source.get_span(handle)))
+}
+
+/// Return true if the value `handle` in `arena` refers to a type whose index
+/// is `type_count` or more.
+fn uses_new_types(
+    arena: &Arena<Expression>,
+    handle: Handle<Expression>,
+    type_count: usize,
+) -> bool {
+    let expr = &arena[handle];
+    let new_type = match *expr {
+        Expression::ZeroValue(ty) | Expression::Compose { ty, .. } => ty.index() >= type_count,
+        _ => false,
+    };
+    let mut new_operand_type = false;
+    for_each_expression_operand(expr, |operand| {
+        new_operand_type |= uses_new_types(arena, operand, type_count);
+    });
+    new_type || new_operand_type
+}
+
+/// Copy the value `handle` in `arena` into `rb`'s target.
+fn copy_value(
+    rb: &mut Rebuilder<'_, '_>,
+    arena: &Arena<Expression>,
+    handle: Handle<Expression>,
+    out: &mut Block,
+) -> Handle<Expression> {
+    let mut expr = arena[handle].clone();
+    adjust_expression(&mut expr, &mut |operand| {
+        *operand = copy_value(rb, arena, *operand, out);
+    });
+    rb.append(expr, arena.get_span(handle), out)
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::super::testing::{apply, count_emitted, function};
+    use crate::{Expression, Literal, Statement};
+
+    fn loads(module: &crate::Module, name: &str) -> usize {
+        count_emitted(function(module, name), |expr| {
+            matches!(*expr, Expression::Load { .. })
+        })
+    }
+
+    #[test]
+    fn fold() {
+        let (_, after) = apply(
+            "
+fn f() -> f32 {
+    var origin = vec2(1.0, 2.0);
+    let k = 3.0;
+    return origin.x * k;
+}
+",
+            super::propagate_constants,
+        );
+        let f = function(&after, "f");
+        assert_eq!(loads(&after, "f"), 0);
+        let value = f
+            .body
+            .iter()
+            .find_map(|statement| match *statement {
+                Statement::Return { value } => value,
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(f.expressions[value], Expression::Literal(Literal::F32(3.0)));
+    }
+
+    #[test]
+    fn store_in_loop() {
+        // The load of `x` sees `1.0` on the first iteration, and `2.0` on
+        // the rest.
+        let (before, after) = apply(
+            "
+fn f(n: u32) -> f32 {
+    var x = 1.0;
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += x;
+        x = 2.0;
+    }
+    return total;
+}
+",
+            super::propagate_constants,
+        );
+        assert_eq!(loads(&after, "f"), loads(&before, "f"));
+    }
+
+    #[test]
+    fn escaping_local() {
+        let (before, after) = apply(
+            "
+fn write(p: ptr<function, f32>) {
+    *p = 2.0;
+}
+
+fn f() -> f32 {
+    var x = 1.0;
+    write(&x);
+    return x;
+}
+",
+            super::propagate_constants,
+        );
+        assert_eq!(loads(&before, "f"), 1);
+        assert_eq!(loads(&after, "f"), 1);
+    }
+}
@@ -0,0 +1,193 @@
+//! Eliminating common subexpressions.
+//!
+//! If a function computes the same pure expression from the same operands
+//! twice, and the first computation is still in scope at the second, the
+//! second can just reuse the first. Front ends produce this sort of
+//! repetition routinely: each mention of `a.b[i]` in WGSL source becomes a
+//! fresh chain of [`Access`] expressions, for example.
+//!
+//! Only expressions whose value depends on nothing but their operands are
+//! candidates. [`Load`]s, image sampling and loading, and derivatives are
+//! never combined.
+//!
+//! [`Access`]: crate::Expression::Access
+//! [`Load`]: crate::Expression::Load
+
+use super::rebuild::{rebuild, Pass, Rebuilder};
+use crate::{Block, Expression, FastHashMap, Function, Handle, Module};
+use std::mem::{discriminant, Discriminant};
+
+pub(super) fn eliminate_common_subexpressions(module: &Module, function: &Function) -> Function {
+    rebuild(module, function, &mut CommonSubexpressions::default())
+}
+
+/// The kind of an expression and its first operand.
+///
+/// Candidates for reuse are grouped by this key, and then compared in full.
+type Key = (Discriminant<Expression>, Handle<Expression>);
+
+#[derive(Default)]
+struct CommonSubexpressions {
+    /// Expressions in the rebuilt function that are in scope, grouped by
+    /// [`Key`].
+    available: FastHashMap<Key, Vec<Handle<Expression>>>,
+
+    /// The keys of the expressions added to `available`, in order.
+    added: Vec<Key>,
+
+    /// The length of `added` at the start of each enclosing block.
+    scopes: Vec<usize>,
+}
+
+/// If `expr` is a candidate for reuse, return its [`Key`].
+fn key(expr: &Expression) -> Option<Key> {
+    let first = match *expr {
+        Expression::Compose { ref components, .. } => *components.first()?,
+        Expression::Access { base, .. } | Expression::AccessIndex { base, .. } => base,
+        Expression::Splat { value, .. } => value,
+        Expression::Swizzle { vector, .. } => vector,
+        Expression::ImageQuery { image, .. } => image,
+        Expression::Unary { expr, .. } => expr,
+        Expression::Binary { left, .. } => left,
+        Expression::Select { condition, .. } => condition,
+        Expression::Relational { argument, .. } => argument,
+        Expression::Math { arg, .. } => arg,
+        Expression::As { expr, .. } => expr,
+        Expression::ArrayLength(expr) => expr,
+        Expression::Literal(_)
+        | Expression::Constant(_)
+        | Expression::Override(_)
+        | Expression::ZeroValue(_)
+        | Expression::FunctionArgument(_)
+        | Expression::GlobalVariable(_)
+        | Expression::LocalVariable(_)
+        | Expression::Load { .. }
+        | Expression::ImageSample { .. }
+        | Expression::ImageLoad { .. }
+        | Expression::Derivative { .. }
+        | Expression::CallResult(_)
+        | Expression::AtomicResult { .. }
+        | Expression::WorkGroupUniformLoadResult { .. }
+        | Expression::RayQueryProceedResult
+        | Expression::RayQueryGetIntersection { .. }
+        | Expression::SubgroupBallotResult
+        | Expression::SubgroupOperationResult { .. } => return None,
+    };
+    Some((discriminant(expr), first))
+}
+
+impl Pass for CommonSubexpressions {
+    fn expression(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        old: Handle<Expression>,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        let expr = rb.remap(old, out);
+        let Some(key) = key(&expr) else {
+            let span = rb.source.expressions.get_span(old);
+            return Some(rb.append(expr, span, out));
+        };
+
+        let candidates = self.available.entry(key).or_default();
+        if let Some(&existing) = candidates
+            .iter()
+            .find(|&&candidate| rb.target.expressions[candidate] == expr)
+        {
+            return Some(existing);
+        }
+
+        let span = rb.source.expressions.get_span(old);
+        let new = rb.append(expr, span, out);
+        candidates.push(new);
+        self.added.push(key);
+        Some(new)
+    }
+
+    fn enter_block(&mut self) {
+        self.scopes.push(self.added.len());
+    }
+
+    fn leave_block(&mut self) {
+        let start = self.scopes.pop().unwrap();
+        for key in self.added.drain(start..) {
+            if let Some(candidates) = self.available.get_mut(&key) {
+                candidates.pop();
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::super::testing::{apply, count_emitted, function};
+    use crate::{BinaryOperator, Expression};
+
+    fn adds(module: &crate::Module, name: &str) -> usize {
+        count_emitted(function(module, name), |expr| {
+            matches!(
+                *expr,
+                Expression::Binary {
+                    op: BinaryOperator::Add,
+                    ..
+                }
+            )
+        })
+    }
+
+    #[test]
+    fn repeated() {
+        let (before, after) = apply(
+            "
+fn f(v: vec2<f32>) -> f32 {
+    let a = v.x + v.y;
+    let b = v.x + v.y;
+    return a * b;
+}
+",
+            super::eliminate_common_subexpressions,
+        );
+        assert_eq!(adds(&before, "f"), 2);
+        assert_eq!(adds(&after, "f"), 1);
+    }
+
+    #[test]
+    fn loads() {
+        let (_, after) = apply(
+            "
+var<private> g: f32;
+
+fn f() -> f32 {
+    let a = g;
+    g = 2.0;
+    let b = g;
+    return a + b;
+}
+",
+            super::eliminate_common_subexpressions,
+        );
+        let loads = count_emitted(function(&after, "f"), |expr| {
+            matches!(*expr, Expression::Load { .. })
+        });
+        assert_eq!(loads, 2);
+    }
+
+    #[test]
+    fn out_of_scope() {
+        // The sum computed in the `if` isn't available after it.
+        let (before, after) = apply(
+            "
+fn f(v: vec2<f32>, c: bool) -> f32 {
+    var r = 0.0;
+    if c {
+        r = v.x + v.y;
+    }
+    return r + (v.x + v.y);
+}
+",
+            super::eliminate_common_subexpressions,
+        );
+        assert_eq!(adds(&before, "f"), 3);
+        assert_eq!(adds(&after, "f"), 3);
+    }
+}
@@ -0,0 +1,248 @@
+//! Eliminating stores to local variables that are never read.
+//!
+//! A [`Store`] to a local variable is dead if no [`Load`] can observe the
+//! value it stores. This pass removes two kinds of dead stores:
+//!
+//! - stores to variables that are never read at all, and
+//!
+//! - stores to a whole variable that are always followed by another store
+//!   to the whole variable, or by the end of the function, before any load.
+//!
+//! Variables whose address is passed to a function or used by any other
+//! statement are left alone. Once their stores are gone, variables that are
+//! never read disappear entirely.
+//!
+//! [`Store`]: crate::Statement::Store
+//! [`Load`]: crate::Expression::Load
+
+use super::{
+    locals::LocalUses,
+    rebuild::{rebuild, Pass, Rebuilder},
+};
+use crate::{Block, Expression, Function, Handle, LocalVariable, Module, Span, Statement};
+
+pub(super) fn eliminate_dead_stores(module: &Module, function: &Function) -> Function {
+    let uses = LocalUses::new(function);
+    rebuild(module, function, &mut DeadStores { function, uses })
+}
+
+struct DeadStores<'a> {
+    function: &'a Function,
+    uses: LocalUses,
+}
+
+impl DeadStores<'_> {
+    /// If `pointer` points into a local variable that is never read, return
+    /// that variable.
+    fn write_only_root(&self, pointer: Handle<Expression>) -> Option<Handle<LocalVariable>> {
+        self.uses
+            .root(pointer)
+            .filter(|&local| self.uses.get(local).is_write_only())
+    }
+
+    /// Return true if the whole-variable store to `local` followed by `rest`
+    /// is dead.
+    ///
+    /// If `end_is_dead` is true, reaching the end of `rest` ends the
+    /// function.
+    fn overwritten(
+        &self,
+        local: Handle<LocalVariable>,
+        rest: &[Statement],
+        end_is_dead: bool,
+    ) -> bool {
+        for statement in rest {
+            match *statement {
+                Statement::Emit(_) if self.reads(local, statement) => return false,
+                Statement::Emit(_) => {}
+                Statement::Store { pointer, .. } => {
+                    if let Expression::LocalVariable(other) = self.function.expressions[pointer] {
+                        if other == local {
+                            return true;
+                        }
+                    }
+                }
+                Statement::Return { .. } | Statement::Kill => return true,
+                Statement::Break | Statement::Continue => return false,
+                Statement::Block(_)
+                | Statement::If { .. }
+                | Statement::Switch { .. }
+                | Statement::Loop { .. }
+                    if self.reads(local, statement) || may_jump(statement) =>
+                {
+                    return false;
+                }
+                // The variable doesn't escape, so nothing else can read it.
+                _ => {}
+            }
+        }
+        end_is_dead
+    }
+
+    /// Return true if `statement`, or any statement nested within it, loads
+    /// from `local`.
+    fn reads(&self, local: Handle<LocalVariable>, statement: &Statement) -> bool {
+        let block_reads = |block: &Block| block.iter().any(|s| self.reads(local, s));
+        match *statement {
+            Statement::Emit(ref range) => range.clone().any(|handle| {
+                matches!(
+                    self.function.expressions[handle],
+                    Expression::Load { pointer } if self.uses.root(pointer) == Some(local)
+                )
+            }),
+            Statement::Block(ref block) => block_reads(block),
+            Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => block_reads(accept) || block_reads(reject),
+            Statement::Switch { ref cases, .. } => cases.iter().any(|case| block_reads(&case.body)),
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => block_reads(body) || block_reads(continuing),
+            _ => false,
+        }
+    }
+}
+
+/// Return true if `statement` contains a [`Break`] or [`Continue`] that may
+/// transfer control out of it.
+///
+/// [`Break`]: Statement::Break
+/// [`Continue`]: Statement::Continue
+fn may_jump(statement: &Statement) -> bool {
+    let block_jumps = |block: &Block| block.iter().any(may_jump);
+    match *statement {
+        Statement::Break | Statement::Continue => true,
+        Statement::Block(ref block) => block_jumps(block),
+        Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => block_jumps(accept) || block_jumps(reject),
+        Statement::Switch { ref cases, .. } => cases.iter().any(|case| block_jumps(&case.body)),
+        Statement::Loop {
+            ref body,
+            ref continuing,
+            ..
+        } => block_jumps(body) || block_jumps(continuing),
+        _ => false,
+    }
+}
+
+impl Pass for DeadStores<'_> {
+    fn expression(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        old: Handle<Expression>,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        match rb.source.expressions[old] {
+            Expression::Access { .. } | Expression::AccessIndex { .. }
+                if self.write_only_root(old).is_some() =>
+            {
+                None
+            }
+            _ => Some(rb.copy_expression(old, out)),
+        }
+    }
+
+    fn statement(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        statement: &Statement,
+        span: Span,
+        rest: &[Statement],
+        out: &mut Block,
+    ) {
+        if let Statement::Store { pointer, .. } = *statement {
+            if self.write_only_root(pointer).is_some() {
+                return;
+            }
+            if let Expression::LocalVariable(local) = self.function.expressions[pointer] {
+                if !self.uses.get(local).escapes && self.overwritten(local, rest, rb.at_top_level())
+                {
+                    return;
+                }
+            }
+        }
+        rb.copy_statement(self, statement, span, out);
+    }
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::super::testing::{apply, count_statements, function};
+    use crate::Statement;
+
+    fn stores(module: &crate::Module, name: &str) -> usize {
+        count_statements(function(module, name), |statement| {
+            matches!(*statement, Statement::Store { .. })
+        })
+    }
+
+    #[test]
+    fn overwritten() {
+        let (before, after) = apply(
+            "
+fn f() -> f32 {
+    var x = 1.0;
+    var unread: f32;
+    x = 2.0;
+    unread = 5.0;
+    x = 3.0;
+    return x;
+}
+",
+            super::eliminate_dead_stores,
+        );
+        assert_eq!(stores(&before, "f"), 3);
+        assert_eq!(stores(&after, "f"), 1);
+    }
+
+    #[test]
+    fn store_in_loop() {
+        // The store at the end of the loop body is read by the next
+        // iteration.
+        let (before, after) = apply(
+            "
+fn f(n: u32) -> f32 {
+    var x = 0.0;
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += x;
+        x = f32(i);
+    }
+    return total;
+}
+",
+            super::eliminate_dead_stores,
+        );
+        assert_eq!(stores(&after, "f"), stores(&before, "f"));
+    }
+
+    #[test]
+    fn escaping_local() {
+        // `read` can see the first store to `x`.
+        let (before, after) = apply(
+            "
+fn read(p: ptr<function, f32>) -> f32 {
+    return *p;
+}
+
+fn f() -> f32 {
+    var x: f32;
+    x = 2.0;
+    let a = read(&x);
+    x = 3.0;
+    return a + x;
+}
+",
+            super::eliminate_dead_stores,
+        );
+        assert_eq!(stores(&before, "f"), 2);
+        assert_eq!(stores(&after, "f"), 2);
+    }
+}
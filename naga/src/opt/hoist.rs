@@ -0,0 +1,191 @@
+//! Hoisting loop-invariant expressions out of loops.
+//!
+//! An expression in a loop's body whose operands are all computed before
+//! the loop produces the same value on every iteration, so it can be
+//! computed once, just before the loop, instead.
+//!
+//! Only pure expressions evaluated unconditionally by the loop's body are
+//! moved. Since hoisted expressions are evaluated even if the loop's body
+//! never runs, expressions that index by a dynamic value are left in place,
+//! in case the index is only in bounds when the loop runs.
+
+use super::rebuild::{rebuild, Pass, Rebuilder};
+use crate::{
+    proc::operands::for_each_expression_operand, Block, Expression, Function, Handle, Module, Span,
+    Statement,
+};
+
+pub(super) fn hoist_loop_invariants(module: &Module, function: &Function) -> Function {
+    rebuild(module, function, &mut LoopInvariants)
+}
+
+struct LoopInvariants;
+
+/// Return true if `expr` may be evaluated before the loop containing it.
+const fn is_hoistable(expr: &Expression) -> bool {
+    matches!(
+        *expr,
+        Expression::Compose { .. }
+            | Expression::AccessIndex { .. }
+            | Expression::Splat { .. }
+            | Expression::Swizzle { .. }
+            | Expression::ImageQuery { .. }
+            | Expression::Unary { .. }
+            | Expression::Binary { .. }
+            | Expression::Select { .. }
+            | Expression::Relational { .. }
+            | Expression::Math { .. }
+            | Expression::As { .. }
+            | Expression::ArrayLength(_)
+    )
+}
+
+/// Add the loop-invariant expressions emitted in `block` to `invariant`.
+///
+/// Nested [`Block`] statements are always executed, so search them too.
+///
+/// [`Block`]: Statement::Block
+fn find_invariants(rb: &Rebuilder<'_, '_>, block: &Block, invariant: &mut Vec<Handle<Expression>>) {
+    for statement in block.iter() {
+        let range = match *statement {
+            Statement::Emit(ref range) => range,
+            Statement::Block(ref block) => {
+                find_invariants(rb, block, invariant);
+                continue;
+            }
+            _ => continue,
+        };
+        for handle in range.clone() {
+            let expr = &rb.source.expressions[handle];
+            if !is_hoistable(expr) {
+                continue;
+            }
+            let mut operands_invariant = true;
+            for_each_expression_operand(expr, |operand| {
+                operands_invariant &= rb.source.expressions[operand].needs_pre_emit()
+                    || rb.get(operand).is_some()
+                    || invariant.contains(&operand);
+            });
+            if operands_invariant {
+                invariant.push(handle);
+            }
+        }
+    }
+}
+
+impl Pass for LoopInvariants {
+    fn statement(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        statement: &Statement,
+        span: Span,
+        _rest: &[Statement],
+        out: &mut Block,
+    ) {
+        if let Statement::Loop { ref body, .. } = *statement {
+            // Anything the loop uses that was computed before it has
+            // already been rebuilt, and anything computed inside it hasn't.
+            let mut invariant = Vec::new();
+            find_invariants(rb, body, &mut invariant);
+
+            for handle in invariant {
+                let new = rb.copy_expression(handle, out);
+                rb.define(handle, new);
+            }
+        }
+
+        rb.copy_statement(self, statement, span, out);
+    }
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::super::testing::{apply, emitted, function};
+    use crate::{Expression, Module};
+
+    /// Return the loop depths of the expressions emitted in `name` for
+    /// which `predicate` is true.
+    fn depths(module: &Module, name: &str, predicate: impl Fn(&Expression) -> bool) -> Vec<usize> {
+        let f = function(module, name);
+        emitted(f)
+            .into_iter()
+            .filter(|&(handle, _)| predicate(&f.expressions[handle]))
+            .map(|(_, depth)| depth)
+            .collect()
+    }
+
+    #[test]
+    fn invariant() {
+        let (before, after) = apply(
+            "
+fn f(n: u32, a: f32, b: f32) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += a * b;
+    }
+    return total;
+}
+",
+            super::hoist_loop_invariants,
+        );
+        let multiply = |expr: &Expression| {
+            matches!(
+                *expr,
+                Expression::Binary {
+                    op: crate::BinaryOperator::Multiply,
+                    ..
+                }
+            )
+        };
+        assert_eq!(depths(&before, "f", multiply), [1]);
+        assert_eq!(depths(&after, "f", multiply), [0]);
+    }
+
+    #[test]
+    fn dynamic_access() {
+        // `j` may only be in bounds when the loop runs.
+        let (_, after) = apply(
+            "
+fn f(n: u32, j: u32, v: vec4<f32>) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += v[j];
+    }
+    return total;
+}
+",
+            super::hoist_loop_invariants,
+        );
+        let access = |expr: &Expression| matches!(*expr, Expression::Access { .. });
+        assert_eq!(depths(&after, "f", access), [1]);
+    }
+
+    #[test]
+    fn load() {
+        let (_, after) = apply(
+            "
+var<private> g: f32;
+
+fn f(n: u32) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += g * 2.0;
+        g += 1.0;
+    }
+    return total;
+}
+",
+            super::hoist_loop_invariants,
+        );
+        // Neither the loads of `g` nor the product that uses one may move.
+        let f = function(&after, "f");
+        let dependent = |expr: &Expression| match *expr {
+            Expression::Load { pointer } => {
+                matches!(f.expressions[pointer], Expression::GlobalVariable(_))
+            }
+            Expression::Binary { op, .. } => op == crate::BinaryOperator::Multiply,
+            _ => false,
+        };
+        assert_eq!(depths(&after, "f", dependent), [1, 1, 1]);
+    }
+}
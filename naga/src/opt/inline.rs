@@ -0,0 +1,471 @@
+//! Inlining function calls.
+//!
+//! This pass replaces [`Call`] statements with a copy of the called
+//! function's body, and removes functions that are no longer called from
+//! anywhere. Functions that were never called to begin with are kept, since
+//! they may be part of a library's interface.
+//!
+//! The callee's arguments become the caller's argument expressions, and
+//! its local variables are added to the caller. Since naga IR has no way to
+//! jump out of the middle of a block, only functions whose [`Return`]
+//! statements all appear in tail position are inlined: that is, those where
+//! control always reaches the end of the function right after returning.
+//! If the callee returns a value from anywhere other than the end of its
+//! outermost block, the value is passed back through a new local variable.
+//!
+//! [`Call`]: crate::Statement::Call
+//! [`Return`]: crate::Statement::Return
+
+use super::rebuild::{rebuild, Pass, Rebuilder};
+use crate::{
+    Arena, Block, Expression, Function, Handle, LocalVariable, Module, Span, Statement, TypeInner,
+};
+
+pub(super) fn inline_functions(module: &mut Module) {
+    let inlinable: Vec<bool> = module
+        .functions
+        .iter()
+        .map(|(_, function)| is_inlinable(module, function))
+        .collect();
+    if !inlinable.contains(&true) {
+        return;
+    }
+
+    let mut called_before = vec![false; module.functions.len()];
+    for function in all_functions(module) {
+        for_each_call(&function.body, &mut |callee| {
+            called_before[callee.index()] = true
+        });
+    }
+
+    let mut inliner = Inliner {
+        inlinable,
+        frames: Vec::new(),
+    };
+    let handles: Vec<_> = module.functions.iter().map(|(handle, _)| handle).collect();
+    for handle in handles {
+        if inliner.calls_inlinable(&module.functions[handle].body) {
+            let function = rebuild(module, &module.functions[handle], &mut inliner);
+            module.functions[handle] = function;
+        }
+    }
+    for index in 0..module.entry_points.len() {
+        if inliner.calls_inlinable(&module.entry_points[index].function.body) {
+            let function = rebuild(module, &module.entry_points[index].function, &mut inliner);
+            module.entry_points[index].function = function;
+        }
+    }
+
+    remove_uncalled_functions(module, &called_before);
+}
+
+/// Return true if calls to `function` can be inlined.
+fn is_inlinable(module: &Module, function: &Function) -> bool {
+    let has_ray_query = function
+        .local_variables
+        .iter()
+        .any(|(_, local)| matches!(module.types[local.ty].inner, TypeInner::RayQuery));
+    !has_ray_query && returns_only_in_tail_position(&function.body, true)
+}
+
+/// Return true if every [`Return`] in `block` is in tail position.
+///
+/// If `tail` is true, control reaches the end of the function right after
+/// `block` finishes.
+///
+/// [`Return`]: Statement::Return
+fn returns_only_in_tail_position(block: &Block, tail: bool) -> bool {
+    block.iter().enumerate().all(|(index, statement)| {
+        let tail = tail && index + 1 == block.len();
+        match *statement {
+            Statement::Return { .. } => tail,
+            Statement::Block(ref block) => returns_only_in_tail_position(block, tail),
+            Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                returns_only_in_tail_position(accept, tail)
+                    && returns_only_in_tail_position(reject, tail)
+            }
+            Statement::Switch { ref cases, .. } => cases
+                .iter()
+                .all(|case| returns_only_in_tail_position(&case.body, tail && !case.fall_through)),
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                returns_only_in_tail_position(body, false)
+                    && returns_only_in_tail_position(continuing, false)
+            }
+            _ => true,
+        }
+    })
+}
+
+fn all_functions(module: &Module) -> impl Iterator<Item = &Function> {
+    module
+        .functions
+        .iter()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter().map(|ep| &ep.function))
+}
+
+/// Call `f` on the callee of every [`Call`] statement in `block`.
+///
+/// [`Call`]: Statement::Call
+fn for_each_call(block: &Block, f: &mut impl FnMut(Handle<Function>)) {
+    for statement in block.iter() {
+        match *statement {
+            Statement::Block(ref block) => for_each_call(block, f),
+            Statement::If {
+                ref accept,
+                ref reject,
+                ..
+            } => {
+                for_each_call(accept, f);
+                for_each_call(reject, f);
+            }
+            Statement::Switch { ref cases, .. } => {
+                for case in cases.iter() {
+                    for_each_call(&case.body, f);
+                }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                ..
+            } => {
+                for_each_call(body, f);
+                for_each_call(continuing, f);
+            }
+            Statement::Call { function, .. } => f(function),
+            _ => {}
+        }
+    }
+}
+
+/// Like [`for_each_call`], but let `f` change the callee.
+fn for_each_call_mut(block: &mut Block, f: &mut impl FnMut(&mut Handle<Function>)) {
+    for statement in block.iter_mut() {
+        match *statement {
+            Statement::Block(ref mut block) => for_each_call_mut(block, f),
+            Statement::If {
+                ref mut accept,
+                ref mut reject,
+                ..
+            } => {
+                for_each_call_mut(accept, f);
+                for_each_call_mut(reject, f);
+            }
+            Statement::Switch { ref mut cases, .. } => {
+                for case in cases.iter_mut() {
+                    for_each_call_mut(&mut case.body, f);
+                }
+            }
+            Statement::Loop {
+                ref mut body,
+                ref mut continuing,
+                ..
+            } => {
+                for_each_call_mut(body, f);
+                for_each_call_mut(continuing, f);
+            }
+            Statement::Call {
+                ref mut function, ..
+            } => f(function),
+            _ => {}
+        }
+    }
+}
+
+/// Remove functions that were called before inlining, but aren't any more.
+fn remove_uncalled_functions(module: &mut Module, called_before: &[bool]) {
+    // Everything reachable from an entry point or a function nobody ever
+    // called stays.
+    let mut live = vec![false; module.functions.len()];
+    let mut worklist: Vec<Handle<Function>> = module
+        .functions
+        .iter()
+        .map(|(handle, _)| handle)
+        .filter(|handle| !called_before[handle.index()])
+        .collect();
+    for ep in module.entry_points.iter() {
+        for_each_call(&ep.function.body, &mut |callee| worklist.push(callee));
+    }
+    while let Some(handle) = worklist.pop() {
+        if !live[handle.index()] {
+            live[handle.index()] = true;
+            for_each_call(&module.functions[handle].body, &mut |callee| {
+                worklist.push(callee)
+            });
+        }
+    }
+    if !live.contains(&false) {
+        return;
+    }
+
+    let mut new_handles = vec![None; live.len()];
+    let mut functions = Arena::new();
+    for (handle, function, span) in module.functions.drain() {
+        if live[handle.index()] {
+            new_handles[handle.index()] = Some(functions.append(function, span));
+        }
+    }
+    module.functions = functions;
+
+    let mut adjust = |callee: &mut Handle<Function>| {
+        *callee = new_handles[callee.index()].unwrap();
+    };
+    let functions = module
+        .functions
+        .iter_mut()
+        .map(|(_, function)| function)
+        .chain(module.entry_points.iter_mut().map(|ep| &mut ep.function));
+    for function in functions {
+        for (_, expr) in function.expressions.iter_mut() {
+            if let Expression::CallResult(ref mut callee) = *expr {
+                adjust(callee);
+            }
+        }
+        for_each_call_mut(&mut function.body, &mut adjust);
+    }
+}
+
+struct Inliner {
+    /// For each function, true if calls to it can be inlined.
+    inlinable: Vec<bool>,
+
+    /// The functions currently being inlined, innermost last.
+    frames: Vec<Frame>,
+}
+
+/// A function being inlined.
+struct Frame {
+    /// The local variable that receives the function's return value, if it
+    /// needs one.
+    result: Option<Handle<LocalVariable>>,
+
+    /// The value returned at the end of the function's outermost block.
+    value: Option<Handle<Expression>>,
+}
+
+impl Inliner {
+    fn calls_inlinable(&self, block: &Block) -> bool {
+        let mut found = false;
+        for_each_call(block, &mut |callee| found |= self.inlinable[callee.index()]);
+        found
+    }
+
+    /// Append the body of `callee`, applied to `arguments`, to `out`.
+    ///
+    /// Return the value the function returns, if any.
+    fn inline(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        callee: Handle<Function>,
+        arguments: &[Handle<Expression>],
+        span: Span,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        let module = rb.module;
+        let function = &module.functions[callee];
+        let arguments = arguments
+            .iter()
+            .map(|&argument| rb.lookup(argument, out))
+            .collect();
+        rb.flush(out);
+
+        let mut inlined = Rebuilder::new(module, function, &mut *rb.target, Some(arguments));
+        for (local, variable) in function.local_variables.iter() {
+            // A local variable's initializer is applied each time its
+            // function is called. If the call is in a loop, or the
+            // initializer is more than a single expression that can be
+            // hoisted to the top of the caller, assign it explicitly.
+            let needs_store = match variable.init {
+                Some(init) => !function.expressions[init].needs_pre_emit(),
+                None => false,
+            } || inlined.target.loop_depth > 0;
+            if needs_store {
+                let new = inlined.declare_local(local, None);
+                let value = match variable.init {
+                    Some(init) => inlined.copy_tree(init, out),
+                    None => inlined.append(Expression::ZeroValue(variable.ty), span, out),
+                };
+                let pointer = inlined.append_unemitted(Expression::LocalVariable(new), span, out);
+                inlined.flush(out);
+                out.push(Statement::Store { pointer, value }, span);
+            } else {
+                let init = variable.init.map(|init| inlined.lookup(init, out));
+                inlined.declare_local(local, init);
+            }
+        }
+
+        let result = match function.result {
+            Some(ref result)
+                if !matches!(function.body.last(), Some(&Statement::Return { .. })) =>
+            {
+                let name = function.name.as_deref().unwrap_or("function");
+                Some(inlined.target.local_variables.append(
+                    LocalVariable {
+                        name: Some(format!("{name}_result")),
+                        ty: result.ty,
+                        init: None,
+                    },
+                    span,
+                ))
+            }
+            _ => None,
+        };
+
+        self.frames.push(Frame {
+            result,
+            value: None,
+        });
+        let body = inlined.block(self, &function.body);
+        out.extend_block(body);
+        let frame = self.frames.pop().unwrap();
+
+        match frame.result {
+            Some(local) => {
+                let pointer = rb.append_unemitted(Expression::LocalVariable(local), span, out);
+                Some(rb.append(Expression::Load { pointer }, span, out))
+            }
+            None => frame.value,
+        }
+    }
+}
+
+impl Pass for Inliner {
+    fn statement(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        statement: &Statement,
+        span: Span,
+        rest: &[Statement],
+        out: &mut Block,
+    ) {
+        match *statement {
+            Statement::Call {
+                function,
+                ref arguments,
+                result,
+            } if self.inlinable[function.index()] => {
+                let value = self.inline(rb, function, arguments, span, out);
+                if let Some(result) = result {
+                    rb.define(result, value.unwrap());
+                }
+            }
+            Statement::Return { value } if !self.frames.is_empty() => {
+                let Some(value) = value else {
+                    return;
+                };
+                let value = rb.lookup(value, out);
+                let frame = self.frames.last_mut().unwrap();
+                match frame.result {
+                    Some(local) => {
+                        let pointer =
+                            rb.append_unemitted(Expression::LocalVariable(local), span, out);
+                        out.push(Statement::Store { pointer, value }, span);
+                    }
+                    None => {
+                        debug_assert!(rest.is_empty() && rb.at_top_level());
+                        frame.value = Some(value);
+                    }
+                }
+            }
+            _ => rb.copy_statement(self, statement, span, out),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::super::testing::{count_statements, function, parse, statements, validate};
+    use crate::{Expression, Literal, Module, Statement};
+
+    fn inline(source: &str) -> Module {
+        let mut module = parse(source);
+        super::inline_functions(&mut module);
+        validate(&module);
+        module
+    }
+
+    fn calls(module: &Module, name: &str) -> usize {
+        count_statements(function(module, name), |statement| {
+            matches!(*statement, Statement::Call { .. })
+        })
+    }
+
+    #[test]
+    fn tail_return() {
+        let module = inline(
+            "
+fn double(x: f32) -> f32 {
+    return x * 2.0;
+}
+
+fn f(a: f32) -> f32 {
+    return double(a);
+}
+",
+        );
+        assert_eq!(calls(&module, "f"), 0);
+        assert!(module
+            .functions
+            .iter()
+            .all(|(_, function)| function.name.as_deref() != Some("double")));
+    }
+
+    #[test]
+    fn loop_body() {
+        // Each iteration must start `y` over at `1.0`.
+        let module = inline(
+            "
+fn g(x: f32) -> f32 {
+    var y = 1.0;
+    y += x;
+    return y;
+}
+
+fn f(n: u32) -> f32 {
+    var total = 0.0;
+    for (var i = 0u; i < n; i++) {
+        total += g(f32(i));
+    }
+    return total;
+}
+",
+        );
+        let f = function(&module, "f");
+        assert_eq!(calls(&module, "f"), 0);
+        let initialized = statements(f).into_iter().any(|(statement, depth)| {
+            matches!(*statement, Statement::Store { value, .. }
+                if depth == 1 && f.expressions[value] == Expression::Literal(Literal::F32(1.0)))
+        });
+        assert!(initialized);
+    }
+
+    #[test]
+    fn return_in_loop() {
+        let module = inline(
+            "
+fn find(n: u32) -> u32 {
+    for (var i = 0u; i < n; i++) {
+        if i * i > n {
+            return i;
+        }
+    }
+    return n;
+}
+
+fn f(n: u32) -> u32 {
+    return find(n);
+}
+",
+        );
+        assert_eq!(calls(&module, "f"), 1);
+        assert_eq!(module.functions.len(), 2);
+    }
+}
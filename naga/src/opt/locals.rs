@@ -0,0 +1,140 @@
+//! Finding out how a function uses its local variables.
+
+use crate::{
+    proc::operands::{for_each_expression_operand, for_each_statement_operand},
+    Block, Expression, Function, Handle, LocalVariable, Statement,
+};
+
+/// How a function uses one of its local variables.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct LocalUse {
+    /// Some [`Load`] reads all or part of the variable.
+    ///
+    /// [`Load`]: Expression::Load
+    pub loaded: bool,
+
+    /// The number of [`Store`] statements that assign the variable as a
+    /// whole.
+    ///
+    /// [`Store`]: Statement::Store
+    pub stores: usize,
+
+    /// Some [`Store`] statement assigns part of the variable.
+    ///
+    /// [`Store`]: Statement::Store
+    pub partially_stored: bool,
+
+    /// A pointer to the variable is used in some way other than loading
+    /// or storing through it: passed to a function, used by an atomic
+    /// operation, and so on.
+    pub escapes: bool,
+}
+
+impl LocalUse {
+    /// Return true if nothing ever reads the variable's value.
+    pub const fn is_write_only(&self) -> bool {
+        !self.loaded && !self.escapes
+    }
+}
+
+/// Information about how a function uses its local variables.
+pub(super) struct LocalUses {
+    /// For each expression, the local variable it points into, if any.
+    roots: Vec<Option<Handle<LocalVariable>>>,
+
+    /// How each local variable is used.
+    uses: Vec<LocalUse>,
+}
+
+impl LocalUses {
+    pub fn new(function: &Function) -> Self {
+        let mut this = LocalUses {
+            roots: Vec::with_capacity(function.expressions.len()),
+            uses: vec![LocalUse::default(); function.local_variables.len()],
+        };
+
+        for (_, expr) in function.expressions.iter() {
+            let root = match *expr {
+                Expression::LocalVariable(local) => Some(local),
+                Expression::Access { base, .. } | Expression::AccessIndex { base, .. } => {
+                    this.roots[base.index()]
+                }
+                _ => None,
+            };
+            this.roots.push(root);
+
+            match *expr {
+                Expression::Access { index, .. } => this.escape(index),
+                Expression::AccessIndex { .. } => {}
+                Expression::Load { pointer } => {
+                    if let Some(local) = this.root(pointer) {
+                        this.uses[local.index()].loaded = true;
+                    }
+                }
+                _ => for_each_expression_operand(expr, |operand| this.escape(operand)),
+            }
+        }
+
+        this.trace_block(function, &function.body);
+        this
+    }
+
+    fn trace_block(&mut self, function: &Function, block: &Block) {
+        for statement in block.iter() {
+            match *statement {
+                Statement::Block(ref block) => self.trace_block(function, block),
+                Statement::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.trace_block(function, accept);
+                    self.trace_block(function, reject);
+                }
+                Statement::Switch { ref cases, .. } => {
+                    for case in cases.iter() {
+                        self.trace_block(function, &case.body);
+                    }
+                }
+                Statement::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.trace_block(function, body);
+                    self.trace_block(function, continuing);
+                }
+                Statement::Store { pointer, value } => {
+                    if let Some(local) = self.root(pointer) {
+                        let local_use = &mut self.uses[local.index()];
+                        match function.expressions[pointer] {
+                            Expression::LocalVariable(_) => local_use.stores += 1,
+                            _ => local_use.partially_stored = true,
+                        }
+                    }
+                    self.escape(value);
+                    continue;
+                }
+                _ => {}
+            }
+            for_each_statement_operand(statement, |operand| self.escape(operand));
+        }
+    }
+
+    /// Note that `expr` is used in a way that lets its pointer escape.
+    fn escape(&mut self, expr: Handle<Expression>) {
+        if let Some(local) = self.root(expr) {
+            self.uses[local.index()].escapes = true;
+        }
+    }
+
+    /// Return the local variable that `expr` points into, if any.
+    pub fn root(&self, expr: Handle<Expression>) -> Option<Handle<LocalVariable>> {
+        self.roots[expr.index()]
+    }
+
+    /// Return how the function uses `local`.
+    pub fn get(&self, local: Handle<LocalVariable>) -> LocalUse {
+        self.uses[local.index()]
+    }
+}
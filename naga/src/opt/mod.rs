@@ -0,0 +1,237 @@
+/*!
+Optimization passes for naga IR.
+
+Front ends translate source code fairly literally, and leave it to the
+driver consuming the backend's output to clean up after them. When that
+output is going somewhere that doesn't optimize, or doesn't optimize well,
+it can help to simplify the module before writing it out.
+
+[`optimize`] applies the passes selected in an [`Options`] to every
+function and entry point in a module. Every pass is opt-in, and each one
+produces a module that behaves exactly like its input. Passes never
+change a module's interface: global variables, constants, and entry points
+are left alone, as are functions that nothing calls.
+
+Running [`compact`] after optimizing removes any types and constants the
+optimized module no longer uses.
+
+[`compact`]: crate::compact::compact
+*/
+
+mod const_prop;
+mod cse;
+mod dead_stores;
+mod hoist;
+mod inline;
+mod locals;
+mod rebuild;
+
+use crate::{Function, Module};
+
+/// Which optimization passes [`optimize`] should apply.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(serde::Serialize))]
+#[cfg_attr(feature = "deserialize", derive(serde::Deserialize))]
+#[cfg_attr(feature = "deserialize", serde(default))]
+pub struct Options {
+    /// Replace calls to functions with copies of their bodies, and remove
+    /// functions that are no longer called.
+    ///
+    /// Functions that return from anywhere but the end of their body, in
+    /// the middle of a loop for example, are not inlined.
+    pub inline_functions: bool,
+
+    /// Replace loads from local variables that only ever hold a single
+    /// constant value with that value, and evaluate expressions whose
+    /// operands are then all constant.
+    pub propagate_constants: bool,
+
+    /// Reuse the value of an earlier pure expression rather than
+    /// computing it again.
+    pub eliminate_common_subexpressions: bool,
+
+    /// Move pure expressions whose operands don't change from one
+    /// iteration of a loop to the next out of the loop.
+    pub hoist_loop_invariants: bool,
+
+    /// Remove stores to local variables whose value is never read.
+    pub eliminate_dead_stores: bool,
+}
+
+impl Options {
+    /// Enable every optimization pass.
+    pub const fn all() -> Self {
+        Self {
+            inline_functions: true,
+            propagate_constants: true,
+            eliminate_common_subexpressions: true,
+            hoist_loop_invariants: true,
+            eliminate_dead_stores: true,
+        }
+    }
+}
+
+/// Apply the optimization passes enabled in `options` to `module`.
+///
+/// Passes run in the order [`Options`]' fields are listed, so that each
+/// can take advantage of the previous ones' work: inlining exposes
+/// constants to propagate, constant propagation makes expressions
+/// identical, and so on.
+///
+/// # Panics
+///
+/// If `module` has not passed validation, this may panic.
+pub fn optimize(module: &mut Module, options: &Options) {
+    if options.inline_functions {
+        inline::inline_functions(module);
+    }
+    if options.propagate_constants {
+        for_each_function(module, const_prop::propagate_constants);
+    }
+    if options.eliminate_common_subexpressions {
+        for_each_function(module, cse::eliminate_common_subexpressions);
+    }
+    if options.hoist_loop_invariants {
+        for_each_function(module, hoist::hoist_loop_invariants);
+    }
+    if options.eliminate_dead_stores {
+        for_each_function(module, dead_stores::eliminate_dead_stores);
+    }
+}
+
+/// Replace each function and entry point in `module` with the result of
+/// applying `pass` to it.
+fn for_each_function(module: &mut Module, pass: fn(&Module, &Function) -> Function) {
+    let handles: Vec<_> = module.functions.iter().map(|(handle, _)| handle).collect();
+    for handle in handles {
+        let function = pass(module, &module.functions[handle]);
+        module.functions[handle] = function;
+    }
+    for index in 0..module.entry_points.len() {
+        let function = pass(module, &module.entry_points[index].function);
+        module.entry_points[index].function = function;
+    }
+}
+
+/// Helpers for the passes' unit tests.
+#[cfg(all(test, feature = "wgsl-in"))]
+mod testing {
+    use crate::{Block, Expression, Function, Handle, Module, Statement};
+
+    /// Parse and validate the WGSL `source`.
+    pub(super) fn parse(source: &str) -> Module {
+        let module = crate::front::wgsl::parse_str(source).unwrap();
+        validate(&module);
+        module
+    }
+
+    pub(super) fn validate(module: &Module) {
+        crate::valid::Validator::new(
+            crate::valid::ValidationFlags::all(),
+            crate::valid::Capabilities::all(),
+        )
+        .validate(module)
+        .unwrap();
+    }
+
+    /// Apply `pass` to every function in the WGSL `source`, and return the
+    /// module before and after.
+    pub(super) fn apply(
+        source: &str,
+        pass: fn(&Module, &Function) -> Function,
+    ) -> (Module, Module) {
+        let module = parse(source);
+        let mut optimized = module.clone();
+        super::for_each_function(&mut optimized, pass);
+        validate(&optimized);
+        (module, optimized)
+    }
+
+    /// Return the function or entry point in `module` named `name`.
+    pub(super) fn function<'a>(module: &'a Module, name: &str) -> &'a Function {
+        module
+            .functions
+            .iter()
+            .map(|(_, function)| function)
+            .chain(module.entry_points.iter().map(|ep| &ep.function))
+            .find(|function| function.name.as_deref() == Some(name))
+            .unwrap()
+    }
+
+    /// Return every statement in `function`, with the number of loops it
+    /// is nested in.
+    pub(super) fn statements(function: &Function) -> Vec<(&Statement, usize)> {
+        fn walk<'a>(block: &'a Block, depth: usize, out: &mut Vec<(&'a Statement, usize)>) {
+            for statement in block.iter() {
+                out.push((statement, depth));
+                match *statement {
+                    Statement::Block(ref block) => walk(block, depth, out),
+                    Statement::If {
+                        ref accept,
+                        ref reject,
+                        ..
+                    } => {
+                        walk(accept, depth, out);
+                        walk(reject, depth, out);
+                    }
+                    Statement::Switch { ref cases, .. } => {
+                        for case in cases {
+                            walk(&case.body, depth, out);
+                        }
+                    }
+                    Statement::Loop {
+                        ref body,
+                        ref continuing,
+                        ..
+                    } => {
+                        walk(body, depth + 1, out);
+                        walk(continuing, depth + 1, out);
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        let mut out = Vec::new();
+        walk(&function.body, 0, &mut out);
+        out
+    }
+
+    /// Return every expression `function` emits, with the number of loops
+    /// it is emitted in.
+    pub(super) fn emitted(function: &Function) -> Vec<(Handle<Expression>, usize)> {
+        statements(function)
+            .into_iter()
+            .filter_map(|(statement, depth)| match *statement {
+                Statement::Emit(ref range) => {
+                    Some(range.clone().map(move |handle| (handle, depth)))
+                }
+                _ => None,
+            })
+            .flatten()
+            .collect()
+    }
+
+    /// Count the statements in `function` for which `predicate` is true.
+    pub(super) fn count_statements(
+        function: &Function,
+        predicate: impl Fn(&Statement) -> bool,
+    ) -> usize {
+        statements(function)
+            .into_iter()
+            .filter(|&(statement, _)| predicate(statement))
+            .count()
+    }
+
+    /// Count the expressions emitted in `function` for which `predicate` is
+    /// true.
+    pub(super) fn count_emitted(
+        function: &Function,
+        predicate: impl Fn(&Expression) -> bool,
+    ) -> usize {
+        emitted(function)
+            .into_iter()
+            .filter(|&(handle, _)| predicate(&function.expressions[handle]))
+            .count()
+    }
+}
@@ -0,0 +1,631 @@
+//! Copying a function into fresh arenas, one statement at a time.
+//!
+//! Every pass in this module works by rebuilding each function from scratch:
+//! a [`Rebuilder`] walks the original function's body in order, copying each
+//! statement and each emitted expression into a new [`Target`]. A [`Pass`]
+//! can intercept each step to drop, replace, or add expressions and
+//! statements.
+//!
+//! Since new expressions are appended in the order they are evaluated, the
+//! rebuilt function always satisfies the validator's requirements that
+//! expressions only refer to earlier expressions, and that [`Emit`]
+//! statements cover contiguous ranges. This is what lets passes move code
+//! around without renumbering anything by hand.
+//!
+//! Expressions that are never emitted, like [`Literal`] and
+//! [`LocalVariable`], are only copied when something uses them, and local
+//! variables are only created when something refers to them, so anything
+//! a pass leaves unused disappears from the rebuilt function.
+//!
+//! [`Emit`]: Statement::Emit
+//! [`Literal`]: Expression::Literal
+//! [`LocalVariable`]: Expression::LocalVariable
+
+use crate::{
+    proc::{operands::adjust_expression, Emitter},
+    Arena, Block, Expression, Function, Handle, LocalVariable, Module, NamedExpressions, Span,
+    Statement, SwitchCase,
+};
+
+/// A transformation applied by [`rebuild`].
+///
+/// Each method's default implementation copies its input unchanged.
+pub(super) trait Pass {
+    /// Rebuild `old`, an expression covered by an [`Emit`] statement.
+    ///
+    /// Return the rebuilt function's counterpart of `old`, or `None` if
+    /// nothing in the rebuilt function will refer to `old`.
+    ///
+    /// [`Emit`]: Statement::Emit
+    fn expression(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        old: Handle<Expression>,
+        out: &mut Block,
+    ) -> Option<Handle<Expression>> {
+        Some(rb.copy_expression(old, out))
+    }
+
+    /// Rebuild `statement`, appending the result to `out`.
+    ///
+    /// The `rest` slice holds the statements following `statement` in its
+    /// block.
+    fn statement(
+        &mut self,
+        rb: &mut Rebuilder<'_, '_>,
+        statement: &Statement,
+        span: Span,
+        rest: &[Statement],
+        out: &mut Block,
+    ) {
+        let _ = rest;
+        rb.copy_statement(self, statement, span, out);
+    }
+
+    /// Note that the rebuilder is entering a nested block.
+    ///
+    /// A [`Loop`]'s `continuing` block is nested within its `body`.
+    ///
+    /// [`Loop`]: Statement::Loop
+    fn enter_block(&mut self) {}
+
+    /// Note that the rebuilder is leaving the block most recently entered.
+    fn leave_block(&mut self) {}
+}
+
+/// The parts of a function under construction that are shared with inlined
+/// callees.
+#[derive(Default)]
+pub(super) struct Target {
+    pub expressions: Arena<Expression>,
+    pub local_variables: Arena<LocalVariable>,
+    pub named_expressions: NamedExpressions,
+
+    /// The range of expressions appended since the last [`Emit`] statement.
+    ///
+    /// [`Emit`]: Statement::Emit
+    emitter: Emitter,
+
+    /// The number of loops enclosing the statement being rebuilt.
+    pub loop_depth: usize,
+}
+
+/// State for copying one function's body into a [`Target`].
+pub(super) struct Rebuilder<'a, 't> {
+    pub module: &'a Module,
+
+    /// The function whose body we're copying.
+    pub source: &'a Function,
+
+    pub target: &'t mut Target,
+
+    /// If `source` is being inlined, the expressions its arguments refer to.
+    arguments: Option<Vec<Handle<Expression>>>,
+
+    /// The rebuilt counterpart of each of `source`'s expressions.
+    expressions: Vec<Option<Handle<Expression>>>,
+
+    /// The rebuilt counterpart of each of `source`'s local variables.
+    locals: Vec<Option<Handle<LocalVariable>>>,
+
+    /// Local variables whose initializers still need to be copied, along
+    /// with the initializer in `source`.
+    pending_inits: Vec<(Handle<LocalVariable>, Handle<Expression>)>,
+
+    /// The number of blocks enclosing the statement being rebuilt.
+    depth: usize,
+}
+
+/// Rebuild `function`, transformed by `pass`.
+pub(super) fn rebuild<P: Pass>(module: &Module, function: &Function, pass: &mut P) -> Function {
+    let mut target = Target::default();
+    let mut rb = Rebuilder::new(module, function, &mut target, None);
+    let body = rb.block(pass, &function.body);
+    rb.finish_locals();
+
+    Function {
+        name: function.name.clone(),
+        arguments: function.arguments.clone(),
+        result: function.result.clone(),
+        local_variables: target.local_variables,
+        expressions: target.expressions,
+        named_expressions: target.named_expressions,
+        body,
+        diagnostic_filter_leaf: function.diagnostic_filter_leaf,
+    }
+}
+
+impl<'a, 't> Rebuilder<'a, 't> {
+    /// Prepare to copy `source` into `target`.
+    ///
+    /// If `arguments` is `Some`, `source` is being inlined, and its
+    /// arguments are the given expressions in `target`. The caller must use
+    /// [`declare_local`] to create `source`'s local variables.
+    ///
+    /// [`declare_local`]: Rebuilder::declare_local
+    pub fn new(
+        module: &'a Module,
+        source: &'a Function,
+        target: &'t mut Target,
+        arguments: Option<Vec<Handle<Expression>>>,
+    ) -> Self {
+        Self {
+            module,
+            source,
+            target,
+            arguments,
+            expressions: vec![None; source.expressions.len()],
+            locals: vec![None; source.local_variables.len()],
+            pending_inits: Vec::new(),
+            depth: 0,
+        }
+    }
+
+    /// Return true if the statement being rebuilt is in `source`'s
+    /// outermost block.
+    pub const fn at_top_level(&self) -> bool {
+        self.depth == 1
+    }
+
+    /// Return the rebuilt counterpart of `old`, if it has one yet.
+    pub fn get(&self, old: Handle<Expression>) -> Option<Handle<Expression>> {
+        self.expressions[old.index()]
+    }
+
+    /// Record `new` as the rebuilt counterpart of `old`.
+    pub fn define(&mut self, old: Handle<Expression>, new: Handle<Expression>) {
+        self.expressions[old.index()] = Some(new);
+        if let Some(name) = self.source.named_expressions.get(&old) {
+            // Names are only meaningful for expressions that get emitted.
+            if !self.target.expressions[new].needs_pre_emit() {
+                self.target
+                    .named_expressions
+                    .entry(new)
+                    .or_insert_with(|| name.clone());
+            }
+        }
+    }
+
+    /// Return the rebuilt counterpart of `old`, which `source` uses as an
+    /// operand.
+    ///
+    /// Expressions that don't need to be emitted, or that `source` never
+    /// emits, are copied on first use.
+    pub fn lookup(&mut self, old: Handle<Expression>, out: &mut Block) -> Handle<Expression> {
+        if let Some(new) = self.expressions[old.index()] {
+            return new;
+        }
+
+        let source = self.source;
+        let new = match source.expressions[old] {
+            Expression::FunctionArgument(index) if self.arguments.is_some() => {
+                self.arguments.as_ref().unwrap()[index as usize]
+            }
+            Expression::LocalVariable(local) => {
+                let local = self.local(local);
+                let span = source.expressions.get_span(old);
+                self.append_unemitted(Expression::LocalVariable(local), span, out)
+            }
+            ref expr if expr.needs_pre_emit() => {
+                let span = source.expressions.get_span(old);
+                self.append_unemitted(expr.clone(), span, out)
+            }
+            // The validator only checks that statements' operands have been
+            // emitted, so an expression used only by other expressions may
+            // never be emitted at all. Evaluate it here, as backends do.
+            _ => self.copy_expression(old, out),
+        };
+        self.expressions[old.index()] = Some(new);
+        new
+    }
+
+    /// Return the rebuilt counterpart of the local variable `old`,
+    /// creating it if necessary.
+    fn local(&mut self, old: Handle<LocalVariable>) -> Handle<LocalVariable> {
+        if let Some(new) = self.locals[old.index()] {
+            return new;
+        }
+
+        let local = &self.source.local_variables[old];
+        let span = self.source.local_variables.get_span(old);
+        let new = self.target.local_variables.append(
+            LocalVariable {
+                name: local.name.clone(),
+                ty: local.ty,
+                init: None,
+            },
+            span,
+        );
+        if let Some(init) = local.init {
+            self.pending_inits.push((new, init));
+        }
+        self.locals[old.index()] = Some(new);
+        new
+    }
+
+    /// Create the rebuilt counterpart of the local variable `old`, with the
+    /// given initializer.
+    pub fn declare_local(
+        &mut self,
+        old: Handle<LocalVariable>,
+        init: Option<Handle<Expression>>,
+    ) -> Handle<LocalVariable> {
+        let local = &self.source.local_variables[old];
+        let span = self.source.local_variables.get_span(old);
+        let new = self.target.local_variables.append(
+            LocalVariable {
+                name: local.name.clone(),
+                ty: local.ty,
+                init,
+            },
+            span,
+        );
+        self.locals[old.index()] = Some(new);
+        new
+    }
+
+    /// Copy the initializers of the local variables created so far.
+    fn finish_locals(&mut self) {
+        // Initializers are constant expressions, so they needn't be emitted.
+        let mut scratch = Block::new();
+        for (local, init) in std::mem::take(&mut self.pending_inits) {
+            let init = match self.get(init) {
+                Some(new) => new,
+                None => self.copy_tree(init, &mut scratch),
+            };
+            self.target.local_variables.get_mut(local).init = Some(init);
+        }
+        self.flush(&mut scratch);
+    }
+
+    /// Append `expr` to the target, emitting it if necessary.
+    pub fn append(&mut self, expr: Expression, span: Span, out: &mut Block) -> Handle<Expression> {
+        if expr.needs_pre_emit() {
+            return self.append_unemitted(expr, span, out);
+        }
+        if !self.target.emitter.is_running() {
+            self.target.emitter.start(&self.target.expressions);
+        }
+        self.target.expressions.append(expr, span)
+    }
+
+    /// Append `expr` to the target, without emitting it.
+    ///
+    /// This is for expressions that don't need to be emitted, and for
+    /// the results of statements like [`Call`].
+    ///
+    /// [`Call`]: Statement::Call
+    pub fn append_unemitted(
+        &mut self,
+        expr: Expression,
+        span: Span,
+        out: &mut Block,
+    ) -> Handle<Expression> {
+        self.flush(out);
+        self.target.expressions.append(expr, span)
+    }
+
+    /// Push an [`Emit`] statement to `out` for any expressions appended
+    /// since the last one.
+    ///
+    /// This must be called before pushing any other statement to `out`, and
+    /// before starting on a nested block.
+    ///
+    /// [`Emit`]: Statement::Emit
+    pub fn flush(&mut self, out: &mut Block) {
+        if self.target.emitter.is_running() {
+            out.extend(self.target.emitter.finish(&self.target.expressions));
+        }
+    }
+
+    /// Return a copy of `old` whose operands refer to rebuilt expressions.
+    pub fn remap(&mut self, old: Handle<Expression>, out: &mut Block) -> Expression {
+        let mut expr = self.source.expressions[old].clone();
+        adjust_expression(&mut expr, &mut |operand| {
+            *operand = self.lookup(*operand, out);
+        });
+        expr
+    }
+
+    /// Copy the emitted expression `old` into the target.
+    pub fn copy_expression(
+        &mut self,
+        old: Handle<Expression>,
+        out: &mut Block,
+    ) -> Handle<Expression> {
+        let expr = self.remap(old, out);
+        let span = self.source.expressions.get_span(old);
+        self.append(expr, span, out)
+    }
+
+    /// Copy `old` and all the emitted expressions it uses into the target,
+    /// without reusing any earlier copies.
+    ///
+    /// This is for constant expressions, whose operands may have been
+    /// emitted somewhere that is not in scope at this point.
+    pub fn copy_tree(&mut self, old: Handle<Expression>, out: &mut Block) -> Handle<Expression> {
+        if self.source.expressions[old].needs_pre_emit() {
+            return self.lookup(old, out);
+        }
+        let mut expr = self.source.expressions[old].clone();
+        adjust_expression(&mut expr, &mut |operand| {
+            *operand = self.copy_tree(*operand, out);
+        });
+        let span = self.source.expressions.get_span(old);
+        self.append(expr, span, out)
+    }
+
+    /// Copy the result expression `old` defined by some statement.
+    fn copy_result(&mut self, old: Handle<Expression>, out: &mut Block) -> Handle<Expression> {
+        let span = self.source.expressions.get_span(old);
+        let new = self.append_unemitted(self.source.expressions[old].clone(), span, out);
+        self.define(old, new);
+        new
+    }
+
+    /// Rebuild the nested block `block`.
+    ///
+    /// The caller must [`flush`] its own block first.
+    ///
+    /// [`flush`]: Rebuilder::flush
+    pub fn block<P: Pass + ?Sized>(&mut self, pass: &mut P, block: &Block) -> Block {
+        let mut out = Block::new();
+        self.depth += 1;
+        pass.enter_block();
+        self.statements(pass, block, &mut out);
+        self.flush(&mut out);
+        pass.leave_block();
+        self.depth -= 1;
+        out
+    }
+
+    fn statements<P: Pass + ?Sized>(&mut self, pass: &mut P, block: &Block, out: &mut Block) {
+        for (index, (statement, &span)) in block.span_iter().enumerate() {
+            pass.statement(self, statement, span, &block[index + 1..], out);
+        }
+    }
+
+    /// Copy `statement` into `out`, using `pass` to rebuild its contents.
+    pub fn copy_statement<P: Pass + ?Sized>(
+        &mut self,
+        pass: &mut P,
+        statement: &Statement,
+        span: Span,
+        out: &mut Block,
+    ) {
+        let statement = match *statement {
+            Statement::Emit(ref range) => {
+                for old in range.clone() {
+                    // A pass may have rebuilt this expression early.
+                    if self.get(old).is_some() {
+                        continue;
+                    }
+                    if let Some(new) = pass.expression(self, old, out) {
+                        self.define(old, new);
+                    }
+                }
+                return;
+            }
+            Statement::Block(ref block) => {
+                self.flush(out);
+                Statement::Block(self.block(pass, block))
+            }
+            Statement::If {
+                condition,
+                ref accept,
+                ref reject,
+            } => {
+                let condition = self.lookup(condition, out);
+                self.flush(out);
+                Statement::If {
+                    condition,
+                    accept: self.block(pass, accept),
+                    reject: self.block(pass, reject),
+                }
+            }
+            Statement::Switch {
+                selector,
+                ref cases,
+            } => {
+                let selector = self.lookup(selector, out);
+                self.flush(out);
+                let cases = cases
+                    .iter()
+                    .map(|case| SwitchCase {
+                        value: case.value,
+                        body: self.block(pass, &case.body),
+                        fall_through: case.fall_through,
+                    })
+                    .collect();
+                Statement::Switch { selector, cases }
+            }
+            Statement::Loop {
+                ref body,
+                ref continuing,
+                break_if,
+            } => {
+                self.flush(out);
+                self.target.loop_depth += 1;
+
+                // The `continuing` block and `break_if` can see the
+                // expressions emitted by `body`, so treat them as nested
+                // within it.
+                self.depth += 1;
+                pass.enter_block();
+                let mut new_body = Block::new();
+                self.statements(pass, body, &mut new_body);
+                self.flush(&mut new_body);
+                let mut continuing = self.block(pass, continuing);
+                let break_if = break_if.map(|condition| self.lookup(condition, &mut continuing));
+                pass.leave_block();
+                self.depth -= 1;
+
+                self.target.loop_depth -= 1;
+                Statement::Loop {
+                    body: new_body,
+                    continuing,
+                    break_if,
+                }
+            }
+            Statement::Break => Statement::Break,
+            Statement::Continue => Statement::Continue,
+            Statement::Return { value } => Statement::Return {
+                value: value.map(|value| self.lookup(value, out)),
+            },
+            Statement::Kill => Statement::Kill,
+            Statement::Barrier(barrier) => Statement::Barrier(barrier),
+            Statement::Store { pointer, value } => Statement::Store {
+                pointer: self.lookup(pointer, out),
+                value: self.lookup(value, out),
+            },
+            Statement::ImageStore {
+                image,
+                coordinate,
+                array_index,
+                value,
+            } => Statement::ImageStore {
+                image: self.lookup(image, out),
+                coordinate: self.lookup(coordinate, out),
+                array_index: array_index.map(|index| self.lookup(index, out)),
+                value: self.lookup(value, out),
+            },
+            Statement::Atomic {
+                pointer,
+                ref fun,
+                value,
+                result,
+            } => {
+                let pointer = self.lookup(pointer, out);
+                let fun = match *fun {
+                    crate::AtomicFunction::Exchange {
+                        compare: Some(compare),
+                    } => crate::AtomicFunction::Exchange {
+                        compare: Some(self.lookup(compare, out)),
+                    },
+                    other => other,
+                };
+                let value = self.lookup(value, out);
+                let result = result.map(|result| self.copy_result(result, out));
+                Statement::Atomic {
+                    pointer,
+                    fun,
+                    value,
+                    result,
+                }
+            }
+            Statement::ImageAtomic {
+                image,
+                coordinate,
+                array_index,
+                fun,
+                value,
+            } => Statement::ImageAtomic {
+                image: self.lookup(image, out),
+                coordinate: self.lookup(coordinate, out),
+                array_index: array_index.map(|index| self.lookup(index, out)),
+                fun,
+                value: self.lookup(value, out),
+            },
+            Statement::WorkGroupUniformLoad { pointer, result } => {
+                let pointer = self.lookup(pointer, out);
+                Statement::WorkGroupUniformLoad {
+                    pointer,
+                    result: self.copy_result(result, out),
+                }
+            }
+            Statement::Call {
+                function,
+                ref arguments,
+                result,
+            } => {
+                let arguments = arguments
+                    .iter()
+                    .map(|&argument| self.lookup(argument, out))
+                    .collect();
+                Statement::Call {
+                    function,
+                    arguments,
+                    result: result.map(|result| self.copy_result(result, out)),
+                }
+            }
+            Statement::RayQuery { query, ref fun } => {
+                let query = self.lookup(query, out);
+                let fun = match *fun {
+                    crate::RayQueryFunction::Initialize {
+                        acceleration_structure,
+                        descriptor,
+                    } => crate::RayQueryFunction::Initialize {
+                        acceleration_structure: self.lookup(acceleration_structure, out),
+                        descriptor: self.lookup(descriptor, out),
+                    },
+                    crate::RayQueryFunction::Proceed { result } => {
+                        crate::RayQueryFunction::Proceed {
+                            result: self.copy_result(result, out),
+                        }
+                    }
+                    crate::RayQueryFunction::Terminate => crate::RayQueryFunction::Terminate,
+                };
+                Statement::RayQuery { query, fun }
+            }
+            Statement::SubgroupBallot { result, predicate } => {
+                let predicate = predicate.map(|predicate| self.lookup(predicate, out));
+                Statement::SubgroupBallot {
+                    result: self.copy_result(result, out),
+                    predicate,
+                }
+            }
+            Statement::SubgroupGather {
+                mode,
+                argument,
+                result,
+            } => {
+                let mode = match mode {
+                    crate::GatherMode::BroadcastFirst => crate::GatherMode::BroadcastFirst,
+                    crate::GatherMode::Broadcast(index) => {
+                        crate::GatherMode::Broadcast(self.lookup(index, out))
+                    }
+                    crate::GatherMode::Shuffle(index) => {
+                        crate::GatherMode::Shuffle(self.lookup(index, out))
+                    }
+                    crate::GatherMode::ShuffleDown(index) => {
+                        crate::GatherMode::ShuffleDown(self.lookup(index, out))
+                    }
+                    crate::GatherMode::ShuffleUp(index) => {
+                        crate::GatherMode::ShuffleUp(self.lookup(index, out))
+                    }
+                    crate::GatherMode::ShuffleXor(index) => {
+                        crate::GatherMode::ShuffleXor(self.lookup(index, out))
+                    }
+                    crate::GatherMode::QuadBroadcast(index) => {
+                        crate::GatherMode::QuadBroadcast(self.lookup(index, out))
+                    }
+                    crate::GatherMode::QuadSwap(direction) => {
+                        crate::GatherMode::QuadSwap(direction)
+                    }
+                };
+                let argument = self.lookup(argument, out);
+                Statement::SubgroupGather {
+                    mode,
+                    argument,
+                    result: self.copy_result(result, out),
+                }
+            }
+            Statement::SubgroupCollectiveOperation {
+                op,
+                collective_op,
+                argument,
+                result,
+            } => {
+                let argument = self.lookup(argument, out);
+                Statement::SubgroupCollectiveOperation {
+                    op,
+                    collective_op,
+                    argument,
+                    result: self.copy_result(result, out),
+                }
+            }
+        };
+        self.flush(out);
+        out.push(statement, span);
+    }
+}
@@ -1,6 +1,8 @@
 use std::iter;
 
 use arrayvec::ArrayVec;
+use half::f16;
+use num_traits::{real::Real, FromPrimitive, One, Zero};
 
 use crate::{
     arena::{Arena, Handle, HandleVec, UniqueArena},
@@ -199,6 +201,7 @@ gen_component_wise_extractor! {
     literals: [
         AbstractFloat => AbstractFloat: f64,
         F32 => F32: f32,
+        F16 => F16: f16,
         AbstractInt => AbstractInt: i64,
         U32 => U32: u32,
         I32 => I32: i32,
@@ -219,6 +222,7 @@ gen_component_wise_extractor! {
     literals: [
         AbstractFloat => Abstract: f64,
         F32 => F32: f32,
+        F16 => F16: f16,
     ],
     scalar_kinds: [
         Float,
@@ -244,6 +248,7 @@ gen_component_wise_extractor! {
         AbstractFloat => AbstractFloat: f64,
         AbstractInt => AbstractInt: i64,
         F32 => F32: f32,
+        F16 => F16: f16,
         I32 => I32: i32,
     ],
     scalar_kinds: [
@@ -570,6 +575,11 @@ pub enum ConstantEvaluatorError {
     },
     #[error("abstract floating-point values cannot be automatically converted to integers")]
     AutomaticConversionFloatToInt { to_type: &'static str },
+    #[error("the value `{value}` is out of range for the concrete type `{to_type}`")]
+    ConversionOutOfRange {
+        value: String,
+        to_type: &'static str,
+    },
     #[error("Division by zero")]
     DivisionByZero,
     #[error("Remainder by zero")]
@@ -1090,6 +1100,7 @@ impl<'a> ConstantEvaluator<'a> {
                 component_wise_scalar(self, span, [arg], |args| match args {
                     Scalar::AbstractFloat([e]) => Ok(Scalar::AbstractFloat([e.abs()])),
                     Scalar::F32([e]) => Ok(Scalar::F32([e.abs()])),
+                    Scalar::F16([e]) => Ok(Scalar::F16([e.abs()])),
                     Scalar::AbstractInt([e]) => Ok(Scalar::AbstractInt([e.abs()])),
                     Scalar::I32([e]) => Ok(Scalar::I32([e.wrapping_abs()])),
                     Scalar::U32([e]) => Ok(Scalar::U32([e])), // TODO: just re-use the expression, ezpz
@@ -1122,7 +1133,9 @@ impl<'a> ConstantEvaluator<'a> {
                 )
             }
             crate::MathFunction::Saturate => {
-                component_wise_float!(self, span, [arg], |e| { Ok([e.clamp(0., 1.)]) })
+                component_wise_float!(self, span, [arg], |e| {
+                    Ok([e.clamp(Zero::zero(), One::one())])
+                })
             }
 
             // trigonometry
@@ -1200,6 +1213,9 @@ impl<'a> ConstantEvaluator<'a> {
                 component_wise_float(self, span, [arg], |e| match e {
                     Float::Abstract([e]) => Ok(Float::Abstract([round_ties_even(e)])),
                     Float::F32([e]) => Ok(Float::F32([(round_ties_even(e as f64) as f32)])),
+                    Float::F16([e]) => {
+                        Ok(Float::F16([f16::from_f64(round_ties_even(f64::from(e)))]))
+                    }
                 })
             }
             crate::MathFunction::Fract => {
@@ -1246,14 +1262,14 @@ impl<'a> ConstantEvaluator<'a> {
             }
             crate::MathFunction::Step => {
                 component_wise_float!(self, span, [arg, arg1.unwrap()], |edge, x| {
-                    Ok([if edge <= x { 1.0 } else { 0.0 }])
+                    Ok([if edge <= x { One::one() } else { Zero::zero() }])
                 })
             }
             crate::MathFunction::Sqrt => {
                 component_wise_float!(self, span, [arg], |e| { Ok([e.sqrt()]) })
             }
             crate::MathFunction::InverseSqrt => {
-                component_wise_float!(self, span, [arg], |e| { Ok([1. / e.sqrt()]) })
+                component_wise_float!(self, span, [arg], |e| { Ok([e.sqrt().recip()]) })
             }
 
             // bits
@@ -1532,6 +1548,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v,
                         Literal::U32(v) => v as i32,
                         Literal::F32(v) => v as i32,
+                        Literal::F16(v) => v.to_f32() as i32,
                         Literal::Bool(v) => v as i32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1543,6 +1560,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as u32,
                         Literal::U32(v) => v,
                         Literal::F32(v) => v as u32,
+                        Literal::F16(v) => v.to_f32() as u32,
                         Literal::Bool(v) => v as u32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1554,6 +1572,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as i64,
                         Literal::U32(v) => v as i64,
                         Literal::F32(v) => v as i64,
+                        Literal::F16(v) => v.to_f32() as i64,
                         Literal::Bool(v) => v as i64,
                         Literal::F64(v) => v as i64,
                         Literal::I64(v) => v,
@@ -1565,6 +1584,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as u64,
                         Literal::U32(v) => v as u64,
                         Literal::F32(v) => v as u64,
+                        Literal::F16(v) => v.to_f32() as u64,
                         Literal::Bool(v) => v as u64,
                         Literal::F64(v) => v as u64,
                         Literal::I64(v) => v as u64,
@@ -1576,6 +1596,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v as f32,
                         Literal::U32(v) => v as f32,
                         Literal::F32(v) => v,
+                        Literal::F16(v) => v.to_f32(),
                         Literal::Bool(v) => v as u32 as f32,
                         Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
                             return make_error();
@@ -1583,10 +1604,23 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::AbstractInt(v) => f32::try_from_abstract(v)?,
                         Literal::AbstractFloat(v) => f32::try_from_abstract(v)?,
                     }),
+                    Sc::F16 => Literal::F16(match literal {
+                        Literal::I32(v) => f16_from_concrete(v as f32)?,
+                        Literal::U32(v) => f16_from_concrete(v as f32)?,
+                        Literal::F32(v) => f16_from_concrete(v)?,
+                        Literal::F16(v) => v,
+                        Literal::Bool(v) => f16::from_f32(v as u32 as f32),
+                        Literal::F64(_) | Literal::I64(_) | Literal::U64(_) => {
+                            return make_error();
+                        }
+                        Literal::AbstractInt(v) => f16::try_from_abstract(v)?,
+                        Literal::AbstractFloat(v) => f16::try_from_abstract(v)?,
+                    }),
                     Sc::F64 => Literal::F64(match literal {
                         Literal::I32(v) => v as f64,
                         Literal::U32(v) => v as f64,
                         Literal::F32(v) => v as f64,
+                        Literal::F16(v) => v.to_f64(),
                         Literal::F64(v) => v,
                         Literal::Bool(v) => v as u32 as f64,
                         Literal::I64(_) | Literal::U64(_) => return make_error(),
@@ -1597,6 +1631,7 @@ impl<'a> ConstantEvaluator<'a> {
                         Literal::I32(v) => v != 0,
                         Literal::U32(v) => v != 0,
                         Literal::F32(v) => v != 0.0,
+                        Literal::F16(v) => v != f16::ZERO,
                         Literal::Bool(v) => v,
                         Literal::F64(_)
                         | Literal::I64(_)
@@ -1747,6 +1782,7 @@ impl<'a> ConstantEvaluator<'a> {
                     Literal::I32(v) => Literal::I32(v.wrapping_neg()),
                     Literal::I64(v) => Literal::I64(v.wrapping_neg()),
                     Literal::F32(v) => Literal::F32(-v),
+                    Literal::F16(v) => Literal::F16(-v),
                     Literal::AbstractInt(v) => Literal::AbstractInt(v.wrapping_neg()),
                     Literal::AbstractFloat(v) => Literal::AbstractFloat(-v),
                     _ => return Err(ConstantEvaluatorError::InvalidUnaryOpArg),
@@ -1881,6 +1917,14 @@ impl<'a> ConstantEvaluator<'a> {
                             BinaryOperator::Modulo => a % b,
                             _ => return Err(ConstantEvaluatorError::InvalidBinaryOpArgs),
                         }),
+                        (Literal::F16(a), Literal::F16(b)) => Literal::F16(match op {
+                            BinaryOperator::Add => a + b,
+                            BinaryOperator::Subtract => a - b,
+                            BinaryOperator::Multiply => a * b,
+                            BinaryOperator::Divide => a / b,
+                            BinaryOperator::Modulo => a % b,
+                            _ => return Err(ConstantEvaluatorError::InvalidBinaryOpArgs),
+                        }),
                         (Literal::AbstractInt(a), Literal::U32(b)) => {
                             Literal::AbstractInt(match op {
                                 BinaryOperator::ShiftLeft => {
@@ -2424,6 +2468,47 @@ impl TryFromAbstract<f64> for f32 {
     }
 }
 
+impl TryFromAbstract<i64> for f16 {
+    fn try_from_abstract(value: i64) -> Result<Self, ConstantEvaluatorError> {
+        let f = f16::from_i64(value).unwrap_or(f16::INFINITY);
+        if f.is_infinite() {
+            return Err(ConstantEvaluatorError::AutomaticConversionLossy {
+                value: format!("{value:?}"),
+                to_type: "f16",
+            });
+        }
+        Ok(f)
+    }
+}
+
+impl TryFromAbstract<f64> for f16 {
+    fn try_from_abstract(value: f64) -> Result<f16, ConstantEvaluatorError> {
+        let f = f16::from_f64(value);
+        if f.is_infinite() {
+            return Err(ConstantEvaluatorError::AutomaticConversionLossy {
+                value: format!("{value:?}"),
+                to_type: "f16",
+            });
+        }
+        Ok(f)
+    }
+}
+
+/// Convert a concrete numeric value to `f16`.
+///
+/// Unlike conversions to `f32`, these can overflow, which WGSL treats as
+/// an error in constant and override expressions.
+fn f16_from_concrete(value: f32) -> Result<f16, ConstantEvaluatorError> {
+    let f = f16::from_f32(value);
+    if f.is_infinite() {
+        return Err(ConstantEvaluatorError::ConversionOutOfRange {
+            value: format!("{value:?}"),
+            to_type: "f16",
+        });
+    }
+    Ok(f)
+}
+
 impl TryFromAbstract<i64> for f64 {
     fn try_from_abstract(value: i64) -> Result<Self, ConstantEvaluatorError> {
         let f = value as f64;
@@ -0,0 +1,301 @@
+//! Visiting the expression handles that expressions and statements refer to.
+
+use crate::{Expression, Handle, Statement};
+
+/// Apply `adjust` to every expression handle `expr` refers to.
+pub(crate) fn adjust_expression(
+    expr: &mut Expression,
+    adjust: &mut impl FnMut(&mut Handle<Expression>),
+) {
+    match *expr {
+        Expression::Compose {
+            ref mut components,
+            ty: _,
+        } => {
+            for component in components.iter_mut() {
+                adjust(component);
+            }
+        }
+        Expression::Access {
+            ref mut base,
+            ref mut index,
+        } => {
+            adjust(base);
+            adjust(index);
+        }
+        Expression::AccessIndex {
+            ref mut base,
+            index: _,
+        } => adjust(base),
+        Expression::Splat {
+            ref mut value,
+            size: _,
+        } => adjust(value),
+        Expression::Swizzle {
+            ref mut vector,
+            size: _,
+            pattern: _,
+        } => adjust(vector),
+        Expression::Load { ref mut pointer } => adjust(pointer),
+        Expression::ImageSample {
+            ref mut image,
+            ref mut sampler,
+            gather: _,
+            ref mut coordinate,
+            ref mut array_index,
+            // This refers to a global expression, not one of the function's.
+            offset: _,
+            ref mut level,
+            ref mut depth_ref,
+        } => {
+            adjust(image);
+            adjust(sampler);
+            adjust(coordinate);
+            if let Some(ref mut array_index) = *array_index {
+                adjust(array_index);
+            }
+            match *level {
+                crate::SampleLevel::Auto | crate::SampleLevel::Zero => {}
+                crate::SampleLevel::Exact(ref mut expr)
+                | crate::SampleLevel::Bias(ref mut expr) => adjust(expr),
+                crate::SampleLevel::Gradient {
+                    ref mut x,
+                    ref mut y,
+                } => {
+                    adjust(x);
+                    adjust(y);
+                }
+            }
+            if let Some(ref mut depth_ref) = *depth_ref {
+                adjust(depth_ref);
+            }
+        }
+        Expression::ImageLoad {
+            ref mut image,
+            ref mut coordinate,
+            ref mut array_index,
+            ref mut sample,
+            ref mut level,
+        } => {
+            adjust(image);
+            adjust(coordinate);
+            for expr in [array_index, sample, level].into_iter().flatten() {
+                adjust(expr);
+            }
+        }
+        Expression::ImageQuery {
+            ref mut image,
+            ref mut query,
+        } => {
+            adjust(image);
+            match *query {
+                crate::ImageQuery::Size {
+                    level: Some(ref mut level),
+                } => adjust(level),
+                crate::ImageQuery::Size { level: None }
+                | crate::ImageQuery::NumLevels
+                | crate::ImageQuery::NumLayers
+                | crate::ImageQuery::NumSamples => {}
+            }
+        }
+        Expression::Unary {
+            op: _,
+            ref mut expr,
+        } => adjust(expr),
+        Expression::Binary {
+            op: _,
+            ref mut left,
+            ref mut right,
+        } => {
+            adjust(left);
+            adjust(right);
+        }
+        Expression::Select {
+            ref mut condition,
+            ref mut accept,
+            ref mut reject,
+        } => {
+            adjust(condition);
+            adjust(accept);
+            adjust(reject);
+        }
+        Expression::Derivative {
+            axis: _,
+            ctrl: _,
+            ref mut expr,
+        } => adjust(expr),
+        Expression::Relational {
+            fun: _,
+            ref mut argument,
+        } => adjust(argument),
+        Expression::Math {
+            fun: _,
+            ref mut arg,
+            ref mut arg1,
+            ref mut arg2,
+            ref mut arg3,
+        } => {
+            adjust(arg);
+            for arg in [arg1, arg2, arg3].into_iter().flatten() {
+                adjust(arg);
+            }
+        }
+        Expression::As {
+            ref mut expr,
+            kind: _,
+            convert: _,
+        } => adjust(expr),
+        Expression::ArrayLength(ref mut expr) => adjust(expr),
+        Expression::RayQueryGetIntersection {
+            ref mut query,
+            committed: _,
+        } => adjust(query),
+        Expression::Literal(_)
+        | Expression::Constant(_)
+        | Expression::Override(_)
+        | Expression::ZeroValue(_)
+        | Expression::FunctionArgument(_)
+        | Expression::GlobalVariable(_)
+        | Expression::LocalVariable(_)
+        | Expression::CallResult(_)
+        | Expression::AtomicResult { .. }
+        | Expression::WorkGroupUniformLoadResult { .. }
+        | Expression::RayQueryProceedResult
+        | Expression::SubgroupBallotResult
+        | Expression::SubgroupOperationResult { .. } => {}
+    }
+}
+
+/// Call `f` on every expression handle `expr` refers to.
+pub(crate) fn for_each_expression_operand(
+    expr: &Expression,
+    mut f: impl FnMut(Handle<Expression>),
+) {
+    let mut expr = expr.clone();
+    adjust_expression(&mut expr, &mut |&mut operand| f(operand));
+}
+
+/// Call `f` on every expression handle `statement` uses as an operand.
+///
+/// This doesn't visit nested blocks, the expressions covered by
+/// [`Statement::Emit`], or the result expressions that statements like
+/// [`Statement::Call`] define.
+pub(crate) fn for_each_statement_operand(
+    statement: &Statement,
+    mut f: impl FnMut(Handle<Expression>),
+) {
+    match *statement {
+        Statement::Emit(_)
+        | Statement::Block(_)
+        | Statement::Loop { break_if: None, .. }
+        | Statement::Break
+        | Statement::Continue
+        | Statement::Return { value: None }
+        | Statement::Kill
+        | Statement::Barrier(_) => {}
+        Statement::If { condition, .. } => f(condition),
+        Statement::Switch { selector, .. } => f(selector),
+        Statement::Loop {
+            break_if: Some(break_if),
+            ..
+        } => f(break_if),
+        Statement::Return { value: Some(value) } => f(value),
+        Statement::Store { pointer, value } => {
+            f(pointer);
+            f(value);
+        }
+        Statement::ImageStore {
+            image,
+            coordinate,
+            array_index,
+            value,
+        } => {
+            f(image);
+            f(coordinate);
+            if let Some(array_index) = array_index {
+                f(array_index);
+            }
+            f(value);
+        }
+        Statement::Atomic {
+            pointer,
+            ref fun,
+            value,
+            result: _,
+        } => {
+            f(pointer);
+            if let crate::AtomicFunction::Exchange {
+                compare: Some(compare),
+            } = *fun
+            {
+                f(compare);
+            }
+            f(value);
+        }
+        Statement::ImageAtomic {
+            image,
+            coordinate,
+            array_index,
+            fun: _,
+            value,
+        } => {
+            f(image);
+            f(coordinate);
+            if let Some(array_index) = array_index {
+                f(array_index);
+            }
+            f(value);
+        }
+        Statement::WorkGroupUniformLoad { pointer, result: _ } => f(pointer),
+        Statement::Call {
+            function: _,
+            ref arguments,
+            result: _,
+        } => {
+            for &argument in arguments {
+                f(argument);
+            }
+        }
+        Statement::RayQuery { query, ref fun } => {
+            f(query);
+            if let crate::RayQueryFunction::Initialize {
+                acceleration_structure,
+                descriptor,
+            } = *fun
+            {
+                f(acceleration_structure);
+                f(descriptor);
+            }
+        }
+        Statement::SubgroupBallot {
+            result: _,
+            predicate,
+        } => {
+            if let Some(predicate) = predicate {
+                f(predicate);
+            }
+        }
+        Statement::SubgroupGather {
+            ref mode,
+            argument,
+            result: _,
+        } => {
+            match *mode {
+                crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
+                crate::GatherMode::Broadcast(index)
+                | crate::GatherMode::Shuffle(index)
+                | crate::GatherMode::ShuffleDown(index)
+                | crate::GatherMode::ShuffleUp(index)
+                | crate::GatherMode::ShuffleXor(index)
+                | crate::GatherMode::QuadBroadcast(index) => f(index),
+            }
+            f(argument);
+        }
+        Statement::SubgroupCollectiveOperation {
+            op: _,
+            collective_op: _,
+            argument,
+            result: _,
+        } => f(argument),
+    }
+}
@@ -0,0 +1,642 @@
+/*!
+Reflection of the interface of a [`Module`]'s entry points.
+
+[`reflect`] lists, for each entry point, the resources it uses together with
+the kind of binding each one needs, the inputs and outputs it passes
+between pipeline stages, and its workgroup size, as well as the module's
+pipeline-overridable constants. This is what is needed to build bind group
+and vertex buffer layouts for a shader.
+
+The binding types mirror the ones of the WebGPU API, as found in the
+`wgpu-types` crate, so that they can be translated one to one.
+
+[`Module`]: crate::Module
+*/
+
+use crate::valid::ModuleInfo;
+use crate::{Handle, Scalar, ScalarKind, ShaderStage, VectorSize};
+use std::num::NonZeroU32;
+
+#[cfg(feature = "serialize")]
+use serde::Serialize;
+
+/// The interface of a [`Module`], as returned by [`reflect`].
+///
+/// [`Module`]: crate::Module
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Reflection {
+    /// The entry points of the module, in the order of
+    /// [`Module::entry_points`].
+    ///
+    /// [`Module::entry_points`]: crate::Module::entry_points
+    pub entry_points: Vec<EntryPoint>,
+    /// The pipeline-overridable constants of the module.
+    pub overrides: Vec<Override>,
+}
+
+/// The interface of an entry point.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct EntryPoint {
+    pub name: String,
+    pub stage: ShaderStage,
+    /// The resources the entry point uses, sorted by group and binding.
+    pub resources: Vec<Resource>,
+    /// The inputs of the entry point, with struct arguments flattened into
+    /// their members.
+    pub inputs: Vec<Varying>,
+    /// The outputs of the entry point, with a struct result flattened into
+    /// its members.
+    pub outputs: Vec<Varying>,
+    /// The workgroup size, for compute shaders.
+    ///
+    /// The dimensions given by [`workgroup_size_overrides`] are only known
+    /// once the overrides are given values.
+    ///
+    /// [`workgroup_size_overrides`]: EntryPoint::workgroup_size_overrides
+    pub workgroup_size: [u32; 3],
+    /// The dimensions of the workgroup size that are given by
+    /// pipeline-overridable expressions.
+    pub workgroup_size_overrides: [Option<WorkgroupSizeOverride>; 3],
+}
+
+/// A dimension of the workgroup size given by a pipeline-overridable
+/// expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum WorkgroupSizeOverride {
+    /// The value of the override at this index of [`Reflection::overrides`].
+    Override(usize),
+    /// An expression using overrides.
+    Expression,
+}
+
+/// A resource bound to a bind group.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Resource {
+    pub name: Option<String>,
+    pub group: u32,
+    pub binding: u32,
+    pub ty: BindingType,
+    /// The number of elements of a binding array, if the resource is one and
+    /// its size is known.
+    pub count: Option<NonZeroU32>,
+}
+
+/// The kind of binding a [`Resource`] needs, like `wgt::BindingType`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum BindingType {
+    Buffer {
+        ty: BufferBindingType,
+        /// The size of the buffer's type, or of its fixed-size part and a
+        /// single element of its runtime-sized array.
+        min_binding_size: u64,
+    },
+    Sampler(SamplerBindingType),
+    Texture {
+        sample_type: TextureSampleType,
+        view_dimension: TextureViewDimension,
+        multisampled: bool,
+    },
+    StorageTexture {
+        access: StorageTextureAccess,
+        format: crate::StorageFormat,
+        view_dimension: TextureViewDimension,
+    },
+    AccelerationStructure,
+}
+
+/// Like `wgt::BufferBindingType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum BufferBindingType {
+    Uniform,
+    Storage { read_only: bool },
+}
+
+/// Like `wgt::SamplerBindingType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum SamplerBindingType {
+    Filtering,
+    Comparison,
+}
+
+/// Like `wgt::TextureSampleType`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum TextureSampleType {
+    /// A float texture, which is filterable if the entry point samples it
+    /// with a sampler.
+    Float {
+        filterable: bool,
+    },
+    Depth,
+    Sint,
+    Uint,
+}
+
+/// Like `wgt::TextureViewDimension`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum TextureViewDimension {
+    D1,
+    D2,
+    D2Array,
+    Cube,
+    CubeArray,
+    D3,
+}
+
+/// Like `wgt::StorageTextureAccess`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum StorageTextureAccess {
+    WriteOnly,
+    ReadOnly,
+    ReadWrite,
+    Atomic,
+}
+
+/// An input or output of an entry point.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Varying {
+    /// The name of the argument or struct member.
+    pub name: Option<String>,
+    pub binding: crate::Binding,
+    /// The vertex format whose shader type is the type of the varying, if
+    /// there is one.
+    ///
+    /// For the inputs of a vertex shader, this is the format to use for the
+    /// vertex attribute.
+    pub format: Option<VertexFormat>,
+}
+
+/// Like the `wgt::VertexFormat`s whose shader types are 16, 32 or 64 bit
+/// scalars and vectors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub enum VertexFormat {
+    Uint32,
+    Uint32x2,
+    Uint32x3,
+    Uint32x4,
+    Sint32,
+    Sint32x2,
+    Sint32x3,
+    Sint32x4,
+    Float16,
+    Float16x2,
+    Float16x4,
+    Float32,
+    Float32x2,
+    Float32x3,
+    Float32x4,
+    Float64,
+    Float64x2,
+    Float64x3,
+    Float64x4,
+}
+
+impl VertexFormat {
+    /// Returns the vertex format whose shader type is a `scalar`, or a vector
+    /// of `size` of them.
+    pub const fn from_type(scalar: Scalar, size: Option<VectorSize>) -> Option<Self> {
+        use {ScalarKind as Sk, VectorSize as Vs};
+        Some(match (scalar.kind, scalar.width, size) {
+            (Sk::Uint, 4, None) => Self::Uint32,
+            (Sk::Uint, 4, Some(Vs::Bi)) => Self::Uint32x2,
+            (Sk::Uint, 4, Some(Vs::Tri)) => Self::Uint32x3,
+            (Sk::Uint, 4, Some(Vs::Quad)) => Self::Uint32x4,
+            (Sk::Sint, 4, None) => Self::Sint32,
+            (Sk::Sint, 4, Some(Vs::Bi)) => Self::Sint32x2,
+            (Sk::Sint, 4, Some(Vs::Tri)) => Self::Sint32x3,
+            (Sk::Sint, 4, Some(Vs::Quad)) => Self::Sint32x4,
+            (Sk::Float, 2, None) => Self::Float16,
+            (Sk::Float, 2, Some(Vs::Bi)) => Self::Float16x2,
+            (Sk::Float, 2, Some(Vs::Quad)) => Self::Float16x4,
+            (Sk::Float, 4, None) => Self::Float32,
+            (Sk::Float, 4, Some(Vs::Bi)) => Self::Float32x2,
+            (Sk::Float, 4, Some(Vs::Tri)) => Self::Float32x3,
+            (Sk::Float, 4, Some(Vs::Quad)) => Self::Float32x4,
+            (Sk::Float, 8, None) => Self::Float64,
+            (Sk::Float, 8, Some(Vs::Bi)) => Self::Float64x2,
+            (Sk::Float, 8, Some(Vs::Tri)) => Self::Float64x3,
+            (Sk::Float, 8, Some(Vs::Quad)) => Self::Float64x4,
+            _ => return None,
+        })
+    }
+}
+
+/// A pipeline-overridable constant.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serialize", derive(Serialize))]
+pub struct Override {
+    pub name: Option<String>,
+    /// The numeric identifier given with `@id`, if any.
+    pub id: Option<u16>,
+    /// The type of the override, if it is a scalar.
+    pub scalar: Option<Scalar>,
+    /// The default value, if it is given as a literal.
+    pub default: Option<crate::Literal>,
+    /// Whether the override has a default value at all.
+    pub has_default: bool,
+}
+
+/// Reflects the interface of `module`, which `info` is the validation result
+/// of.
+pub fn reflect(module: &crate::Module, info: &ModuleInfo) -> Reflection {
+    let entry_points = module
+        .entry_points
+        .iter()
+        .enumerate()
+        .map(|(index, entry_point)| {
+            let info = info.get_entry_point(index);
+            let mut resources = module
+                .global_variables
+                .iter()
+                .filter(|&(handle, _)| !info[handle].is_empty())
+                .filter_map(|(handle, var)| {
+                    let binding = var.binding.as_ref()?;
+                    let filterable = info.sampling_set.iter().any(|key| key.image == handle);
+                    let (ty, count) = binding_type(module, var, filterable)?;
+                    Some(Resource {
+                        name: var.name.clone(),
+                        group: binding.group,
+                        binding: binding.binding,
+                        ty,
+                        count,
+                    })
+                })
+                .collect::<Vec<_>>();
+            resources.sort_by_key(|resource| (resource.group, resource.binding));
+
+            let function = &entry_point.function;
+            let mut inputs = Vec::new();
+            for argument in function.arguments.iter() {
+                varyings(
+                    module,
+                    argument.name.as_deref(),
+                    argument.binding.as_ref(),
+                    argument.ty,
+                    &mut inputs,
+                );
+            }
+            let mut outputs = Vec::new();
+            if let Some(ref result) = function.result {
+                varyings(
+                    module,
+                    None,
+                    result.binding.as_ref(),
+                    result.ty,
+                    &mut outputs,
+                );
+            }
+
+            let workgroup_size_overrides = entry_point
+                .workgroup_size_overrides
+                .unwrap_or_default()
+                .map(|size| {
+                    size.map(|expr| match module.global_expressions[expr] {
+                        crate::Expression::Override(o) => {
+                            WorkgroupSizeOverride::Override(o.index())
+                        }
+                        _ => WorkgroupSizeOverride::Expression,
+                    })
+                });
+
+            EntryPoint {
+                name: entry_point.name.clone(),
+                stage: entry_point.stage,
+                resources,
+                inputs,
+                outputs,
+                workgroup_size: entry_point.workgroup_size,
+                workgroup_size_overrides,
+            }
+        })
+        .collect();
+
+    let overrides = module
+        .overrides
+        .iter()
+        .map(|(_, o)| Override {
+            name: o.name.clone(),
+            id: o.id,
+            scalar: module.types[o.ty].inner.scalar(),
+            default: o
+                .init
+                .and_then(|init| match module.global_expressions[init] {
+                    crate::Expression::Literal(literal) => Some(literal),
+                    _ => None,
+                }),
+            has_default: o.init.is_some(),
+        })
+        .collect();
+
+    Reflection {
+        entry_points,
+        overrides,
+    }
+}
+
+/// Returns the binding type of the resource `var`, and the number of
+/// elements if it is a binding array.
+fn binding_type(
+    module: &crate::Module,
+    var: &crate::GlobalVariable,
+    filterable: bool,
+) -> Option<(BindingType, Option<NonZeroU32>)> {
+    use crate::{ImageClass as Ic, ImageDimension as Id, StorageAccess as Sa, TypeInner as Ti};
+
+    let (inner, count) = match module.types[var.ty].inner {
+        Ti::BindingArray { base, size } => {
+            let count = match size {
+                crate::ArraySize::Constant(count) => Some(count),
+                _ => None,
+            };
+            (&module.types[base].inner, count)
+        }
+        ref inner => (inner, None),
+    };
+
+    let ty = match *inner {
+        Ti::Image {
+            dim,
+            arrayed,
+            class,
+        } => {
+            let view_dimension = match dim {
+                Id::D1 => TextureViewDimension::D1,
+                Id::D2 if arrayed => TextureViewDimension::D2Array,
+                Id::D2 => TextureViewDimension::D2,
+                Id::D3 => TextureViewDimension::D3,
+                Id::Cube if arrayed => TextureViewDimension::CubeArray,
+                Id::Cube => TextureViewDimension::Cube,
+            };
+            match class {
+                Ic::Sampled { kind, multi } => BindingType::Texture {
+                    sample_type: match kind {
+                        ScalarKind::Float => TextureSampleType::Float { filterable },
+                        ScalarKind::Sint => TextureSampleType::Sint,
+                        ScalarKind::Uint => TextureSampleType::Uint,
+                        ScalarKind::Bool | ScalarKind::AbstractInt | ScalarKind::AbstractFloat => {
+                            return None
+                        }
+                    },
+                    view_dimension,
+                    multisampled: multi,
+                },
+                Ic::Depth { multi } => BindingType::Texture {
+                    sample_type: TextureSampleType::Depth,
+                    view_dimension,
+                    multisampled: multi,
+                },
+                Ic::Storage { format, access } => BindingType::StorageTexture {
+                    access: if access.contains(Sa::ATOMIC) {
+                        StorageTextureAccess::Atomic
+                    } else if access.contains(Sa::LOAD | Sa::STORE) {
+                        StorageTextureAccess::ReadWrite
+                    } else if access.contains(Sa::STORE) {
+                        StorageTextureAccess::WriteOnly
+                    } else {
+                        StorageTextureAccess::ReadOnly
+                    },
+                    format,
+                    view_dimension,
+                },
+            }
+        }
+        Ti::Sampler { comparison } => BindingType::Sampler(match comparison {
+            true => SamplerBindingType::Comparison,
+            false => SamplerBindingType::Filtering,
+        }),
+        Ti::AccelerationStructure => BindingType::AccelerationStructure,
+        ref other => BindingType::Buffer {
+            ty: match var.space {
+                crate::AddressSpace::Uniform => BufferBindingType::Uniform,
+                crate::AddressSpace::Storage { access } => BufferBindingType::Storage {
+                    read_only: !access.contains(Sa::STORE),
+                },
+                _ => return None,
+            },
+            min_binding_size: u64::from(other.size(module.to_ctx())),
+        },
+    };
+    Some((ty, count))
+}
+
+/// Adds the varyings passed through `binding` and `ty` to `list`, flattening
+/// structs into their members.
+fn varyings(
+    module: &crate::Module,
+    name: Option<&str>,
+    binding: Option<&crate::Binding>,
+    ty: Handle<crate::Type>,
+    list: &mut Vec<Varying>,
+) {
+    let format = match module.types[ty].inner {
+        crate::TypeInner::Struct { ref members, .. } => {
+            for member in members {
+                varyings(
+                    module,
+                    member.name.as_deref(),
+                    member.binding.as_ref(),
+                    member.ty,
+                    list,
+                );
+            }
+            return;
+        }
+        crate::TypeInner::Scalar(scalar) => VertexFormat::from_type(scalar, None),
+        crate::TypeInner::Vector { size, scalar } => VertexFormat::from_type(scalar, Some(size)),
+        _ => None,
+    };
+    if let Some(binding) = binding {
+        list.push(Varying {
+            name: name.map(str::to_string),
+            binding: binding.clone(),
+            format,
+        });
+    }
+}
+
+#[cfg(all(test, feature = "wgsl-in"))]
+mod tests {
+    use super::*;
+
+    fn reflect_wgsl(source: &str) -> Reflection {
+        let module = crate::front::wgsl::parse_str(source).unwrap();
+        let info = crate::valid::Validator::new(
+            crate::valid::ValidationFlags::all(),
+            crate::valid::Capabilities::all(),
+        )
+        .validate(&module)
+        .unwrap();
+        reflect(&module, &info)
+    }
+
+    #[test]
+    fn resources() {
+        let reflection = reflect_wgsl(
+            "
+struct Params { scale: vec4<f32>, count: u32 }
+@group(1) @binding(0) var<uniform> params: Params;
+@group(0) @binding(2) var<storage, read> input: array<f32>;
+@group(0) @binding(1) var t: texture_2d<f32>;
+@group(0) @binding(0) var s: sampler;
+@group(0) @binding(3) var depth: texture_depth_2d_array;
+@group(0) @binding(4) var out: texture_storage_2d<rgba8unorm, write>;
+@group(2) @binding(0) var unused: texture_2d<u32>;
+
+@fragment
+fn main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let c = textureSample(t, s, position.xy) * params.scale;
+    textureStore(out, vec2(0), c);
+    return c * input[params.count] * textureLoad(depth, vec2(0), 0, 0);
+}
+",
+        );
+        let [ref entry_point] = reflection.entry_points[..] else {
+            panic!("expected one entry point");
+        };
+        let resources = entry_point
+            .resources
+            .iter()
+            .map(|r| (r.name.as_deref().unwrap(), r.group, r.binding, r.ty))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            resources,
+            [
+                (
+                    "s",
+                    0,
+                    0,
+                    BindingType::Sampler(SamplerBindingType::Filtering)
+                ),
+                (
+                    "t",
+                    0,
+                    1,
+                    BindingType::Texture {
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    }
+                ),
+                (
+                    "input",
+                    0,
+                    2,
+                    BindingType::Buffer {
+                        ty: BufferBindingType::Storage { read_only: true },
+                        min_binding_size: 4,
+                    }
+                ),
+                (
+                    "depth",
+                    0,
+                    3,
+                    BindingType::Texture {
+                        sample_type: TextureSampleType::Depth,
+                        view_dimension: TextureViewDimension::D2Array,
+                        multisampled: false,
+                    }
+                ),
+                (
+                    "out",
+                    0,
+                    4,
+                    BindingType::StorageTexture {
+                        access: StorageTextureAccess::WriteOnly,
+                        format: crate::StorageFormat::Rgba8Unorm,
+                        view_dimension: TextureViewDimension::D2,
+                    }
+                ),
+                (
+                    "params",
+                    1,
+                    0,
+                    BindingType::Buffer {
+                        ty: BufferBindingType::Uniform,
+                        min_binding_size: 32,
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn interface() {
+        let reflection = reflect_wgsl(
+            "
+override scale: f32 = 2.0;
+@id(7) override count: u32;
+
+struct VertexInput {
+    @location(0) position: vec3<f32>,
+    @location(1) @interpolate(flat) index: u32,
+}
+
+@vertex
+fn vs(input: VertexInput, @location(2) uv: vec2<f32>) -> @builtin(position) vec4<f32> {
+    return vec4(input.position * scale, f32(input.index + count)) + uv.xyxy;
+}
+
+@compute @workgroup_size(8, 4)
+fn cs() {}
+
+override size: u32 = 16;
+
+@compute @workgroup_size(size, size * 2, 2)
+fn cs_overridden() {}
+",
+        );
+        let vs = &reflection.entry_points[0];
+        let inputs = vs
+            .inputs
+            .iter()
+            .map(|v| (v.name.as_deref().unwrap(), v.format))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            inputs,
+            [
+                ("position", Some(VertexFormat::Float32x3)),
+                ("index", Some(VertexFormat::Uint32)),
+                ("uv", Some(VertexFormat::Float32x2)),
+            ]
+        );
+        assert_eq!(
+            vs.outputs[0].binding,
+            crate::Binding::BuiltIn(crate::BuiltIn::Position { invariant: false })
+        );
+
+        assert_eq!(reflection.entry_points[1].workgroup_size, [8, 4, 1]);
+        assert_eq!(
+            reflection.entry_points[1].workgroup_size_overrides,
+            [None; 3]
+        );
+        assert_eq!(reflection.entry_points[2].workgroup_size[2], 2);
+        assert_eq!(
+            reflection.entry_points[2].workgroup_size_overrides,
+            [
+                Some(WorkgroupSizeOverride::Override(2)),
+                Some(WorkgroupSizeOverride::Expression),
+                None,
+            ]
+        );
+
+        let [ref scale, ref count, _] = reflection.overrides[..] else {
+            panic!("expected three overrides");
+        };
+        assert_eq!(scale.name.as_deref(), Some("scale"));
+        assert_eq!(scale.default, Some(crate::Literal::F32(2.0)));
+        assert_eq!(count.id, Some(7));
+        assert_eq!(count.scalar, Some(Scalar::U32));
+        assert!(!count.has_default);
+    }
+}
@@ -696,6 +696,8 @@ impl<'a> ResolveContext<'a> {
                             )))
                         }
                     },
+                    Mf::Dot4I8Packed => TypeResolution::Value(Ti::Scalar(crate::Scalar::I32)),
+                    Mf::Dot4U8Packed => TypeResolution::Value(Ti::Scalar(crate::Scalar::U32)),
                     Mf::Outer => {
                         let arg1 = arg1.ok_or_else(|| {
                             ResolveError::IncompatibleOperands(format!("{fun:?}(_, None)"))
@@ -815,7 +817,9 @@ impl<'a> ResolveContext<'a> {
                     | Mf::Pack2x16unorm
                     | Mf::Pack2x16float
                     | Mf::Pack4xI8
-                    | Mf::Pack4xU8 => TypeResolution::Value(Ti::Scalar(crate::Scalar::U32)),
+                    | Mf::Pack4xU8
+                    | Mf::Pack4xI8Clamp
+                    | Mf::Pack4xU8Clamp => TypeResolution::Value(Ti::Scalar(crate::Scalar::U32)),
                     // data unpacking
                     Mf::Unpack4x8snorm | Mf::Unpack4x8unorm => TypeResolution::Value(Ti::Vector {
                         size: crate::VectorSize::Quad,
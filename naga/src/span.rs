@@ -239,6 +239,30 @@ impl<E> WithSpan<E> {
         Some(self.spans[0].0.location(source))
     }
 
+    /// Turns the error into a non-fatal [`Diagnostic`] at `severity`.
+    ///
+    /// [`Diagnostic`]: crate::diagnostic_filter::Diagnostic
+    pub(crate) fn into_diagnostic(
+        self,
+        severity: crate::diagnostic_filter::Severity,
+    ) -> crate::diagnostic_filter::Diagnostic
+    where
+        E: Error,
+    {
+        let mut notes = Vec::new();
+        let mut source: &dyn Error = &self.inner;
+        while let Some(next) = Error::source(source) {
+            notes.push(next.to_string());
+            source = next;
+        }
+        crate::diagnostic_filter::Diagnostic {
+            severity,
+            message: self.inner.to_string(),
+            labels: self.spans,
+            notes,
+        }
+    }
+
     pub(crate) fn diagnostic(&self) -> codespan_reporting::diagnostic::Diagnostic<()>
     where
         E: Error,
@@ -6,7 +6,9 @@
 //! - expression reference counts
 
 use super::{ExpressionError, FunctionError, ModuleInfo, ShaderStages, ValidationFlags};
-use crate::diagnostic_filter::{DiagnosticFilterNode, StandardFilterableTriggeringRule};
+use crate::diagnostic_filter::{
+    Diagnostic, DiagnosticFilterNode, StandardFilterableTriggeringRule,
+};
 use crate::span::{AddSpan as _, WithSpan};
 use crate::{
     arena::{Arena, Handle},
@@ -297,6 +299,14 @@ pub struct FunctionInfo {
     /// See [`DiagnosticFilterNode`] for details on how the tree is represented and used in
     /// validation.
     diagnostic_filter_leaf: Option<Handle<DiagnosticFilterNode>>,
+
+    /// Diagnostics reported for this function at lesser severities than
+    /// [`Severity::Error`], like uniformity violations that a diagnostic filter
+    /// turned into warnings.
+    ///
+    /// [`Severity::Error`]: crate::diagnostic_filter::Severity::Error
+    #[cfg_attr(any(feature = "serialize", feature = "deserialize"), serde(skip))]
+    diagnostics: Vec<Diagnostic>,
 }
 
 impl FunctionInfo {
@@ -306,6 +316,10 @@ impl FunctionInfo {
     pub const fn expression_count(&self) -> usize {
         self.expressions.len()
     }
+    /// Returns the non-fatal diagnostics reported for this function.
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
     pub fn dominates_global_use(&self, other: &Self) -> bool {
         for (self_global_uses, other_global_uses) in
             self.global_uses.iter().zip(other.global_uses.iter())
@@ -859,12 +873,8 @@ impl FunctionInfo {
                                 severity.report_diag(
                                     FunctionError::NonUniformControlFlow(req, expr, cause)
                                         .with_span_handle(expr, expression_arena),
-                                    // TODO: Yes, this isn't contextualized with source, because
-                                    // the user is supposed to render what would normally be an
-                                    // error here. Once we actually support warning-level
-                                    // diagnostic items, then we won't need this non-compliant hack:
-                                    // <https://github.com/gfx-rs/wgpu/issues/6458>
-                                    |e, level| log::log!(level, "{e}"),
+                                    &mut self.diagnostics,
+                                    WithSpan::into_diagnostic,
                                 )?;
                             }
                         }
@@ -1044,7 +1054,9 @@ impl FunctionInfo {
                     result: _,
                 } => {
                     for &argument in arguments {
-                        let _ = self.add_ref(argument);
+                        let global_use =
+                            pointer_argument_use(&self.expressions[argument.index()].ty);
+                        let _ = self.add_ref_impl(argument, global_use);
                     }
                     let info = &other_functions[function.index()];
                     //Note: the result is validated by the Validator, not here
@@ -1115,12 +1127,13 @@ impl FunctionInfo {
                 } => {
                     let _ = self.add_ref(argument);
                     match mode {
-                        crate::GatherMode::BroadcastFirst => {}
+                        crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
                         crate::GatherMode::Broadcast(index)
                         | crate::GatherMode::Shuffle(index)
                         | crate::GatherMode::ShuffleDown(index)
                         | crate::GatherMode::ShuffleUp(index)
-                        | crate::GatherMode::ShuffleXor(index) => {
+                        | crate::GatherMode::ShuffleXor(index)
+                        | crate::GatherMode::QuadBroadcast(index) => {
                             let _ = self.add_ref(index);
                         }
                     }
@@ -1135,6 +1148,37 @@ impl FunctionInfo {
     }
 }
 
+/// Return the use a callee may make of a global passed to it by pointer.
+///
+/// The callee may write through pointers in the [`Storage`] and
+/// [`WorkGroup`] address spaces, so assume it does whatever the pointer
+/// type permits. Arguments of other types are only read.
+///
+/// [`Storage`]: crate::AddressSpace::Storage
+/// [`WorkGroup`]: crate::AddressSpace::WorkGroup
+fn pointer_argument_use(ty: &TypeResolution) -> GlobalUse {
+    let space = match *ty {
+        TypeResolution::Value(
+            crate::TypeInner::Pointer { space, .. } | crate::TypeInner::ValuePointer { space, .. },
+        ) => space,
+        _ => return GlobalUse::READ,
+    };
+    match space {
+        crate::AddressSpace::Storage { access } => {
+            let mut global_use = GlobalUse::empty();
+            if access.contains(crate::StorageAccess::LOAD) {
+                global_use |= GlobalUse::READ;
+            }
+            if access.contains(crate::StorageAccess::STORE) {
+                global_use |= GlobalUse::WRITE;
+            }
+            global_use
+        }
+        crate::AddressSpace::WorkGroup => GlobalUse::READ | GlobalUse::WRITE,
+        _ => GlobalUse::READ,
+    }
+}
+
 impl ModuleInfo {
     /// Populates `self.const_expression_types`
     pub(super) fn process_const_expression(
@@ -1168,6 +1212,7 @@ impl ModuleInfo {
             sampling: crate::FastHashSet::default(),
             dual_source_blending: false,
             diagnostic_filter_leaf: fun.diagnostic_filter_leaf,
+            diagnostics: Vec::new(),
         };
         let resolve_context =
             ResolveContext::with_locals(module, &fun.local_variables, &fun.arguments);
@@ -1207,6 +1252,20 @@ impl ModuleInfo {
     pub fn get_entry_point(&self, index: usize) -> &FunctionInfo {
         &self.entry_points[index]
     }
+
+    /// Returns the non-fatal diagnostics reported while validating the module,
+    /// for its functions first and then its entry points.
+    ///
+    /// Validation only fails for diagnostics at [`Severity::Error`]; the ones
+    /// at lesser severities are collected here instead.
+    ///
+    /// [`Severity::Error`]: crate::diagnostic_filter::Severity::Error
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> + '_ {
+        self.functions
+            .iter()
+            .chain(self.entry_points.iter())
+            .flat_map(|info| info.diagnostics.iter())
+    }
 }
 
 #[test]
@@ -1288,6 +1347,7 @@ fn uniform_control_flow() {
         sampling: crate::FastHashSet::default(),
         dual_source_blending: false,
         diagnostic_filter_leaf: None,
+        diagnostics: Vec::new(),
     };
     let resolve_context = ResolveContext {
         constants: &Arena::new(),
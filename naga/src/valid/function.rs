@@ -74,6 +74,8 @@ pub enum SubgroupError {
     UnsupportedOperation(super::SubgroupOperationSet),
     #[error("Unknown operation")]
     UnknownOperation,
+    #[error("Quad broadcast index {0:?} is not a const-expression")]
+    NonConstQuadIndex(Handle<crate::Expression>),
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -222,6 +224,7 @@ struct BlockContext<'a> {
     abilities: ControlFlowAbility,
     info: &'a FunctionInfo,
     expressions: &'a Arena<crate::Expression>,
+    local_expr_kind: &'a crate::proc::ExpressionKindTracker,
     types: &'a UniqueArena<crate::Type>,
     local_vars: &'a Arena<crate::LocalVariable>,
     global_vars: &'a Arena<crate::GlobalVariable>,
@@ -237,11 +240,13 @@ impl<'a> BlockContext<'a> {
         module: &'a crate::Module,
         info: &'a FunctionInfo,
         prev_infos: &'a [FunctionInfo],
+        local_expr_kind: &'a crate::proc::ExpressionKindTracker,
     ) -> Self {
         Self {
             abilities: ControlFlowAbility::RETURN,
             info,
             expressions: &fun.expressions,
+            local_expr_kind,
             types: &module.types,
             local_vars: &fun.local_variables,
             global_vars: &module.global_variables,
@@ -669,12 +674,13 @@ impl super::Validator {
         context: &BlockContext,
     ) -> Result<(), WithSpan<FunctionError>> {
         match *mode {
-            crate::GatherMode::BroadcastFirst => {}
+            crate::GatherMode::BroadcastFirst | crate::GatherMode::QuadSwap(_) => {}
             crate::GatherMode::Broadcast(index)
             | crate::GatherMode::Shuffle(index)
             | crate::GatherMode::ShuffleDown(index)
             | crate::GatherMode::ShuffleUp(index)
-            | crate::GatherMode::ShuffleXor(index) => {
+            | crate::GatherMode::ShuffleXor(index)
+            | crate::GatherMode::QuadBroadcast(index) => {
                 let index_ty = context.resolve_type(index, &self.valid_expression_set)?;
                 match *index_ty {
                     crate::TypeInner::Scalar(crate::Scalar::U32) => {}
@@ -688,6 +694,15 @@ impl super::Validator {
                             .into_other());
                     }
                 }
+                // SPIR-V and the WGSL spec require the lane of a quad
+                // broadcast to be known before the shader runs.
+                if let crate::GatherMode::QuadBroadcast(_) = *mode {
+                    if !context.local_expr_kind.is_const_or_override(index) {
+                        return Err(SubgroupError::NonConstQuadIndex(index)
+                            .with_span_handle(index, context.expressions)
+                            .into_other());
+                    }
+                }
             }
         }
         let argument_inner = context.resolve_type(argument, &self.valid_expression_set)?;
@@ -1534,6 +1549,20 @@ impl super::Validator {
                         )
                         .with_span_static(span, "missing capability for this operation"));
                     }
+                    if let crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_) =
+                        *mode
+                    {
+                        if !self
+                            .capabilities
+                            .contains(super::Capabilities::SUBGROUP_QUAD)
+                        {
+                            return Err(FunctionError::MissingCapability(
+                                super::Capabilities::SUBGROUP_QUAD,
+                            )
+                            .with_span_static(span, "missing capability for this operation"));
+                        }
+                        stages &= super::ShaderStages::FRAGMENT | super::ShaderStages::COMPUTE;
+                    }
                     let operation = mode.required_operations();
                     if !self.subgroup_operations.contains(operation) {
                         return Err(FunctionError::InvalidSubgroup(
@@ -1619,7 +1648,14 @@ impl super::Validator {
 
         for (index, argument) in fun.arguments.iter().enumerate() {
             match module.types[argument.ty].inner.pointer_space() {
-                Some(crate::AddressSpace::Private | crate::AddressSpace::Function) | None => {}
+                Some(
+                    crate::AddressSpace::Private
+                    | crate::AddressSpace::Function
+                    | crate::AddressSpace::Uniform
+                    | crate::AddressSpace::Storage { .. }
+                    | crate::AddressSpace::WorkGroup,
+                )
+                | None => {}
                 Some(other) => {
                     return Err(FunctionError::InvalidArgumentPointerSpace {
                         index,
@@ -1702,7 +1738,7 @@ impl super::Validator {
             let stages = self
                 .validate_block(
                     &fun.body,
-                    &BlockContext::new(fun, module, &info, &mod_info.functions),
+                    &BlockContext::new(fun, module, &info, &mod_info.functions, &local_expr_kind),
                 )?
                 .stages;
             info.available_stages &= stages;
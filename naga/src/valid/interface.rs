@@ -8,6 +8,8 @@ use crate::span::{AddSpan as _, MapErrWithSpan as _, SpanProvider as _, WithSpan
 use bit_set::BitSet;
 
 const MAX_WORKGROUP_SIZE: u32 = 0x4000;
+/// The most clip distances a vertex shader can write, as in WGSL.
+const MAX_CLIP_DISTANCES: u32 = 8;
 
 #[derive(Clone, Debug, thiserror::Error)]
 #[cfg_attr(test, derive(PartialEq))]
@@ -84,6 +86,8 @@ pub enum VaryingError {
     },
     #[error("Workgroup size is multi dimensional, @builtin(subgroup_id) and @builtin(subgroup_invocation_id) are not supported.")]
     InvalidMultiDimensionalSubgroupBuiltIn,
+    #[error("At most {MAX_CLIP_DISTANCES} clip distances can be written, not {0}")]
+    TooManyClipDistances(u32),
 }
 
 #[derive(Clone, Debug, thiserror::Error)]
@@ -196,6 +200,19 @@ impl VaryingContext<'_> {
                     return Err(VaryingError::InvalidMultiDimensionalSubgroupBuiltIn);
                 }
 
+                if let (
+                    Bi::ClipDistance,
+                    &Ti::Array {
+                        size: crate::ArraySize::Constant(size),
+                        ..
+                    },
+                ) = (built_in, ty_inner)
+                {
+                    if size.get() > MAX_CLIP_DISTANCES {
+                        return Err(VaryingError::TooManyClipDistances(size.get()));
+                    }
+                }
+
                 let (visible, type_good) = match built_in {
                     Bi::BaseInstance
                     | Bi::BaseVertex
@@ -8,6 +8,7 @@ mod expression;
 mod function;
 mod handles;
 mod interface;
+mod requirements;
 mod r#type;
 
 use crate::{
@@ -29,6 +30,7 @@ pub use expression::{ConstExpressionError, ExpressionError};
 pub use function::{CallError, FunctionError, LocalVariableError};
 pub use interface::{EntryPointError, GlobalVariableError, VaryingError};
 pub use r#type::{Disalignment, TypeError, TypeFlags, WidthError};
+pub use requirements::{required_capabilities, Requirement, Requirements};
 
 use self::handles::InvalidHandleError;
 
@@ -156,6 +158,14 @@ bitflags::bitflags! {
         const TEXTURE_ATOMIC = 1 << 22;
         /// Support for atomic operations on 64-bit images.
         const TEXTURE_INT64_ATOMIC = 1 << 23;
+        /// Support for 16-bit floating-point types.
+        const SHADER_FLOAT16 = 1 << 24;
+        /// Support for quad operations, [`GatherMode::QuadBroadcast`] and
+        /// [`GatherMode::QuadSwap`], in the fragment and compute stages.
+        ///
+        /// [`GatherMode::QuadBroadcast`]: crate::GatherMode::QuadBroadcast
+        /// [`GatherMode::QuadSwap`]: crate::GatherMode::QuadSwap
+        const SUBGROUP_QUAD = 1 << 25;
     }
 }
 
@@ -183,11 +193,11 @@ bitflags::bitflags! {
         const SHUFFLE = 1 << 4;
         /// shuffle up, down
         const SHUFFLE_RELATIVE = 1 << 5;
+        /// quad broadcast, quad swap, in the fragment and compute stages
+        const QUAD_FRAGMENT_COMPUTE = 1 << 7;
         // We don't support these operations yet
         // /// Clustered
         // const CLUSTERED = 1 << 6;
-        // /// Quad supported
-        // const QUAD_FRAGMENT_COMPUTE = 1 << 7;
         // /// Quad supported in all stages
         // const QUAD_ALL_STAGES = 1 << 8;
     }
@@ -212,6 +222,7 @@ impl super::GatherMode {
             Self::BroadcastFirst | Self::Broadcast(_) => S::BALLOT,
             Self::Shuffle(_) | Self::ShuffleXor(_) => S::SHUFFLE,
             Self::ShuffleUp(_) | Self::ShuffleDown(_) => S::SHUFFLE_RELATIVE,
+            Self::QuadBroadcast(_) | Self::QuadSwap(_) => S::QUAD_FRAGMENT_COMPUTE,
         }
     }
 }
@@ -435,12 +446,15 @@ impl crate::TypeInner {
 impl Validator {
     /// Construct a new validator instance.
     pub fn new(flags: ValidationFlags, capabilities: Capabilities) -> Self {
-        let subgroup_operations = if capabilities.contains(Capabilities::SUBGROUP) {
+        let mut subgroup_operations = if capabilities.contains(Capabilities::SUBGROUP) {
             use SubgroupOperationSet as S;
             S::BASIC | S::VOTE | S::ARITHMETIC | S::BALLOT | S::SHUFFLE | S::SHUFFLE_RELATIVE
         } else {
             SubgroupOperationSet::empty()
         };
+        if capabilities.contains(Capabilities::SUBGROUP_QUAD) {
+            subgroup_operations |= SubgroupOperationSet::QUAD_FRAGMENT_COMPUTE;
+        }
         let subgroup_stages = {
             let mut stages = ShaderStages::empty();
             if capabilities.contains(Capabilities::SUBGROUP_VERTEX_STAGE) {
@@ -0,0 +1,525 @@
+/*!
+Inference of the [`Capabilities`] a [`Module`] needs.
+
+[`Validator`] checks a module against a given set of capabilities, and stops
+at the first one it is missing. [`required_capabilities`] goes the other way:
+it walks a module and collects the smallest set of capabilities, subgroup
+stages and subgroup operations that it validates with, along with the span of
+each construct that needs them. This is useful to check shaders against what a
+device supports ahead of time.
+
+[`Module`]: crate::Module
+[`Validator`]: super::Validator
+*/
+
+use super::{Capabilities, FunctionInfo, ModuleInfo, ShaderStages, SubgroupOperationSet};
+use crate::{Handle, Span};
+
+/// What a [`Module`] needs from the [`Validator`], as returned by
+/// [`required_capabilities`].
+///
+/// A [`Validator`] created with [`Validator::new`] from [`capabilities`]
+/// supports [`subgroup_stages`] and [`subgroup_operations`], so the
+/// capabilities alone are enough to validate the module.
+///
+/// [`Module`]: crate::Module
+/// [`Validator`]: super::Validator
+/// [`Validator::new`]: super::Validator::new
+/// [`capabilities`]: Requirements::capabilities
+/// [`subgroup_stages`]: Requirements::subgroup_stages
+/// [`subgroup_operations`]: Requirements::subgroup_operations
+#[derive(Clone, Debug, PartialEq)]
+pub struct Requirements {
+    /// The capabilities the module needs.
+    pub capabilities: Capabilities,
+    /// The stages of the entry points that use subgroup operations.
+    pub subgroup_stages: ShaderStages,
+    /// The subgroup operations the module uses.
+    pub subgroup_operations: SubgroupOperationSet,
+    /// Each construct that needs a capability or subgroup operation, in the
+    /// order they were found.
+    pub uses: Vec<Requirement>,
+}
+
+/// A construct in a [`Module`] that needs capabilities or subgroup
+/// operations.
+///
+/// [`Module`]: crate::Module
+#[derive(Clone, Debug, PartialEq)]
+pub struct Requirement {
+    pub capabilities: Capabilities,
+    pub subgroup_operations: SubgroupOperationSet,
+    /// The span of the construct.
+    ///
+    /// Entry point arguments and results have no span of their own, so this
+    /// is undefined for the built-ins and attributes on them.
+    pub span: Span,
+}
+
+/// Returns what `module` needs to validate.
+///
+/// `info` must be the result of validating `module`, typically with
+/// [`Capabilities::all`]: the uniformity analysis it holds tells which
+/// binding arrays are indexed non-uniformly.
+pub fn required_capabilities(module: &crate::Module, info: &ModuleInfo) -> Requirements {
+    let mut walker = Walker {
+        module,
+        requirements: Requirements {
+            capabilities: Capabilities::empty(),
+            subgroup_stages: ShaderStages::empty(),
+            subgroup_operations: SubgroupOperationSet::empty(),
+            uses: Vec::new(),
+        },
+    };
+
+    walker.types();
+    for (handle, var) in module.global_variables.iter() {
+        walker.global_variable(var, module.global_variables.get_span(handle));
+    }
+    for (handle, expr) in module.global_expressions.iter() {
+        let span = module.global_expressions.get_span(handle);
+        walker.literal(expr, span);
+    }
+
+    // The span of the first subgroup operation each function performs,
+    // directly or through the functions it calls. Functions only call
+    // functions that come before them in the arena.
+    let mut subgroup_uses = Vec::with_capacity(module.functions.len());
+    for (handle, function) in module.functions.iter() {
+        let first = walker.function(function, &info[handle], &subgroup_uses);
+        subgroup_uses.push(first);
+    }
+
+    for (index, ep) in module.entry_points.iter().enumerate() {
+        let first = walker.function(&ep.function, info.get_entry_point(index), &subgroup_uses);
+        walker.entry_point(ep);
+        if let Some(span) = first {
+            let stage = match ep.stage {
+                crate::ShaderStage::Vertex => {
+                    walker.require(Capabilities::SUBGROUP_VERTEX_STAGE, span);
+                    ShaderStages::VERTEX
+                }
+                crate::ShaderStage::Fragment => ShaderStages::FRAGMENT,
+                crate::ShaderStage::Compute => ShaderStages::COMPUTE,
+            };
+            walker.requirements.subgroup_stages |= stage;
+        }
+    }
+
+    let mut requirements = walker.requirements;
+    // `SHADER_INT64_ATOMIC_ALL_OPS` allows everything that
+    // `SHADER_INT64_ATOMIC_MIN_MAX` does.
+    if requirements
+        .capabilities
+        .contains(Capabilities::SHADER_INT64_ATOMIC_ALL_OPS)
+    {
+        requirements
+            .capabilities
+            .remove(Capabilities::SHADER_INT64_ATOMIC_MIN_MAX);
+    }
+    requirements
+}
+
+struct Walker<'a> {
+    module: &'a crate::Module,
+    requirements: Requirements,
+}
+
+impl Walker<'_> {
+    fn require(&mut self, capabilities: Capabilities, span: Span) {
+        self.require_subgroup(capabilities, SubgroupOperationSet::empty(), span);
+    }
+
+    fn require_subgroup(
+        &mut self,
+        capabilities: Capabilities,
+        subgroup_operations: SubgroupOperationSet,
+        span: Span,
+    ) {
+        if capabilities.is_empty() && subgroup_operations.is_empty() {
+            return;
+        }
+        self.requirements.capabilities |= capabilities;
+        self.requirements.subgroup_operations |= subgroup_operations;
+        self.requirements.uses.push(Requirement {
+            capabilities,
+            subgroup_operations,
+            span,
+        });
+    }
+
+    /// Returns the capability that values of `scalar` need, like
+    /// `Validator::check_width`.
+    const fn width(scalar: crate::Scalar) -> Capabilities {
+        use crate::ScalarKind as Sk;
+        match (scalar.kind, scalar.width) {
+            (Sk::Float, 8) => Capabilities::FLOAT64,
+            (Sk::Float, 2) => Capabilities::SHADER_FLOAT16,
+            (Sk::Sint | Sk::Uint, 8) => Capabilities::SHADER_INT64,
+            _ => Capabilities::empty(),
+        }
+    }
+
+    /// Returns the capability that `inner` itself needs, like
+    /// `Validator::validate_type`.
+    const fn type_capability(inner: &crate::TypeInner) -> Capabilities {
+        use crate::TypeInner as Ti;
+
+        match *inner {
+            Ti::Scalar(scalar)
+            | Ti::Vector { scalar, .. }
+            | Ti::Matrix { scalar, .. }
+            | Ti::ValuePointer { scalar, .. } => Self::width(scalar),
+            Ti::Atomic(scalar) => match scalar {
+                crate::Scalar::I64 | crate::Scalar::U64 => {
+                    Capabilities::SHADER_INT64_ATOMIC_MIN_MAX
+                }
+                crate::Scalar::F32 => Capabilities::SHADER_FLOAT32_ATOMIC,
+                _ => Capabilities::empty(),
+            },
+            Ti::Image {
+                dim: crate::ImageDimension::Cube,
+                arrayed: true,
+                ..
+            } => Capabilities::CUBE_ARRAY_TEXTURES,
+            Ti::AccelerationStructure | Ti::RayQuery => Capabilities::RAY_QUERY,
+            _ => Capabilities::empty(),
+        }
+    }
+
+    fn types(&mut self) {
+        for (handle, ty) in self.module.types.iter() {
+            let span = self.module.types.get_span(handle);
+            self.require(Self::type_capability(&ty.inner), span);
+        }
+    }
+
+    fn global_variable(&mut self, var: &crate::GlobalVariable, span: Span) {
+        use crate::StorageFormat as Sf;
+
+        // Front ends don't give predeclared types like `texture_cube_array`
+        // a span, so point at the variable instead.
+        let mut ty = var.ty;
+        if let crate::TypeInner::BindingArray { base, .. } = self.module.types[ty].inner {
+            ty = base;
+        }
+        if !self.module.types.get_span(ty).is_defined() {
+            self.require(Self::type_capability(&self.module.types[ty].inner), span);
+        }
+
+        let required = match var.space {
+            crate::AddressSpace::PushConstant => Capabilities::PUSH_CONSTANT,
+            crate::AddressSpace::Handle => match self.module.types[var.ty].inner {
+                crate::TypeInner::Image {
+                    class:
+                        crate::ImageClass::Storage {
+                            format:
+                                Sf::R16Unorm
+                                | Sf::R16Snorm
+                                | Sf::Rg16Unorm
+                                | Sf::Rg16Snorm
+                                | Sf::Rgba16Unorm
+                                | Sf::Rgba16Snorm,
+                            ..
+                        },
+                    ..
+                } => Capabilities::STORAGE_TEXTURE_16BIT_NORM_FORMATS,
+                _ => Capabilities::empty(),
+            },
+            _ => Capabilities::empty(),
+        };
+        self.require(required, span);
+    }
+
+    fn literal(&mut self, expr: &crate::Expression, span: Span) {
+        if let crate::Expression::Literal(literal) = *expr {
+            self.require(Self::width(literal.scalar()), span);
+        }
+    }
+
+    /// Walks `function`, and returns the span of the first subgroup operation
+    /// it performs, if any.
+    fn function(
+        &mut self,
+        function: &crate::Function,
+        info: &FunctionInfo,
+        subgroup_uses: &[Option<Span>],
+    ) -> Option<Span> {
+        for (handle, expr) in function.expressions.iter() {
+            let span = function.expressions.get_span(handle);
+            self.literal(expr, span);
+            match *expr {
+                crate::Expression::As {
+                    convert: Some(_), ..
+                } => {
+                    let ty = info[handle].ty.inner_with(&self.module.types);
+                    if let Some(scalar) = ty.scalar() {
+                        self.require(Self::width(scalar), span);
+                    }
+                }
+                crate::Expression::Access { base, index }
+                    if info[index].uniformity.non_uniform_result.is_some() =>
+                {
+                    let required = self.non_uniform_indexing(function, info, base);
+                    self.require(required, span);
+                }
+                _ => {}
+            }
+        }
+
+        let mut context = BlockContext {
+            info,
+            subgroup_uses,
+            first_subgroup_use: None,
+        };
+        self.block(&function.body, &mut context);
+        context.first_subgroup_use
+    }
+
+    /// Returns the capability needed to index `base` with a non-uniform
+    /// index, like the uniformity analysis does.
+    fn non_uniform_indexing(
+        &self,
+        function: &crate::Function,
+        info: &FunctionInfo,
+        base: Handle<crate::Expression>,
+    ) -> Capabilities {
+        let crate::TypeInner::BindingArray { base: element, .. } =
+            *info[base].ty.inner_with(&self.module.types)
+        else {
+            return Capabilities::empty();
+        };
+        match self.module.types[element].inner {
+            crate::TypeInner::Image {
+                class: crate::ImageClass::Storage { .. },
+                ..
+            } => Capabilities::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            crate::TypeInner::Image { .. } => {
+                Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING
+            }
+            crate::TypeInner::Sampler { .. } => Capabilities::SAMPLER_NON_UNIFORM_INDEXING,
+            _ => match function.expressions[base] {
+                crate::Expression::GlobalVariable(var) => {
+                    match self.module.global_variables[var].space {
+                        crate::AddressSpace::Uniform => {
+                            Capabilities::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING
+                        }
+                        _ => Capabilities::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+                    }
+                }
+                _ => Capabilities::empty(),
+            },
+        }
+    }
+
+    fn block(&mut self, block: &crate::Block, context: &mut BlockContext) {
+        use crate::Statement as S;
+
+        for (statement, &span) in block.span_iter() {
+            match *statement {
+                S::Block(ref block) => self.block(block, context),
+                S::If {
+                    ref accept,
+                    ref reject,
+                    ..
+                } => {
+                    self.block(accept, context);
+                    self.block(reject, context);
+                }
+                S::Switch { ref cases, .. } => {
+                    for case in cases {
+                        self.block(&case.body, context);
+                    }
+                }
+                S::Loop {
+                    ref body,
+                    ref continuing,
+                    ..
+                } => {
+                    self.block(body, context);
+                    self.block(continuing, context);
+                }
+                S::Call { function, .. } => {
+                    if context.subgroup_uses[function.index()].is_some() {
+                        context.first_subgroup_use.get_or_insert(span);
+                    }
+                }
+                S::Atomic {
+                    pointer,
+                    ref fun,
+                    result,
+                    ..
+                } => {
+                    let required = self.atomic(context, pointer, fun, result.is_some());
+                    self.require(required, span);
+                }
+                S::ImageAtomic { image, .. } => {
+                    let ty = context.info[image].ty.inner_with(&self.module.types);
+                    let required = match *ty {
+                        crate::TypeInner::Image {
+                            class:
+                                crate::ImageClass::Storage {
+                                    format: crate::StorageFormat::R64Uint,
+                                    ..
+                                },
+                            ..
+                        } => Capabilities::TEXTURE_INT64_ATOMIC,
+                        _ => Capabilities::TEXTURE_ATOMIC,
+                    };
+                    self.require(required, span);
+                }
+                S::Barrier(barrier) => {
+                    if barrier.contains(crate::Barrier::SUB_GROUP) {
+                        self.require_subgroup(
+                            Capabilities::SUBGROUP | Capabilities::SUBGROUP_BARRIER,
+                            SubgroupOperationSet::BASIC,
+                            span,
+                        );
+                        context.first_subgroup_use.get_or_insert(span);
+                    }
+                }
+                S::SubgroupBallot { .. } => {
+                    self.require_subgroup(
+                        Capabilities::SUBGROUP,
+                        SubgroupOperationSet::BALLOT,
+                        span,
+                    );
+                    context.first_subgroup_use.get_or_insert(span);
+                }
+                S::SubgroupCollectiveOperation { ref op, .. } => {
+                    self.require_subgroup(Capabilities::SUBGROUP, op.required_operations(), span);
+                    context.first_subgroup_use.get_or_insert(span);
+                }
+                S::SubgroupGather { ref mode, .. } => {
+                    let mut required = Capabilities::SUBGROUP;
+                    if let crate::GatherMode::QuadBroadcast(_) | crate::GatherMode::QuadSwap(_) =
+                        *mode
+                    {
+                        required |= Capabilities::SUBGROUP_QUAD;
+                    }
+                    self.require_subgroup(required, mode.required_operations(), span);
+                    context.first_subgroup_use.get_or_insert(span);
+                }
+                S::Emit(_)
+                | S::Break
+                | S::Continue
+                | S::Return { .. }
+                | S::Kill
+                | S::Store { .. }
+                | S::ImageStore { .. }
+                | S::WorkGroupUniformLoad { .. }
+                | S::RayQuery { .. } => {}
+            }
+        }
+    }
+
+    /// Returns the capability an [`Atomic`] statement needs, like
+    /// `Validator::validate_atomic`.
+    ///
+    /// [`Atomic`]: crate::Statement::Atomic
+    fn atomic(
+        &self,
+        context: &BlockContext,
+        pointer: Handle<crate::Expression>,
+        fun: &crate::AtomicFunction,
+        has_result: bool,
+    ) -> Capabilities {
+        let types = &self.module.types;
+        let crate::TypeInner::Pointer { base, space } = *context.info[pointer].ty.inner_with(types)
+        else {
+            return Capabilities::empty();
+        };
+        let crate::TypeInner::Atomic(scalar) = types[base].inner else {
+            return Capabilities::empty();
+        };
+        match scalar {
+            crate::Scalar::I64 | crate::Scalar::U64 => {
+                if matches!(
+                    *fun,
+                    crate::AtomicFunction::Min | crate::AtomicFunction::Max
+                ) && matches!(space, crate::AddressSpace::Storage { .. })
+                    && !has_result
+                {
+                    Capabilities::SHADER_INT64_ATOMIC_MIN_MAX
+                } else {
+                    Capabilities::SHADER_INT64_ATOMIC_ALL_OPS
+                }
+            }
+            crate::Scalar::F32 => Capabilities::SHADER_FLOAT32_ATOMIC,
+            _ => Capabilities::empty(),
+        }
+    }
+
+    fn entry_point(&mut self, ep: &crate::EntryPoint) {
+        if ep.early_depth_test.is_some() {
+            self.require(Capabilities::EARLY_DEPTH_TEST, Span::UNDEFINED);
+        }
+        let arguments = ep
+            .function
+            .arguments
+            .iter()
+            .map(|arg| (arg.ty, arg.binding.as_ref()));
+        let result = ep
+            .function
+            .result
+            .as_ref()
+            .map(|result| (result.ty, result.binding.as_ref()));
+        for (ty, binding) in arguments.chain(result) {
+            match binding {
+                Some(binding) => self.binding(binding, Span::UNDEFINED),
+                None => {
+                    // The members of a struct have the bindings.
+                    if let crate::TypeInner::Struct { ref members, .. } =
+                        self.module.types[ty].inner
+                    {
+                        let span = self.module.types.get_span(ty);
+                        for binding in members.iter().filter_map(|m| m.binding.as_ref()) {
+                            self.binding(binding, span);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Requires the capability `binding` needs, like `VaryingContext::validate_impl`.
+    fn binding(&mut self, binding: &crate::Binding, span: Span) {
+        use crate::BuiltIn as Bi;
+
+        let required = match *binding {
+            crate::Binding::BuiltIn(built_in) => match built_in {
+                Bi::ClipDistance => Capabilities::CLIP_DISTANCE,
+                Bi::CullDistance => Capabilities::CULL_DISTANCE,
+                Bi::PrimitiveIndex => Capabilities::PRIMITIVE_INDEX,
+                Bi::ViewIndex => Capabilities::MULTIVIEW,
+                Bi::SampleIndex => Capabilities::MULTISAMPLED_SHADING,
+                Bi::NumSubgroups | Bi::SubgroupId | Bi::SubgroupSize | Bi::SubgroupInvocationId => {
+                    Capabilities::SUBGROUP
+                }
+                _ => Capabilities::empty(),
+            },
+            crate::Binding::Location {
+                sampling,
+                second_blend_source,
+                ..
+            } => {
+                let mut required = Capabilities::empty();
+                if second_blend_source {
+                    required |= Capabilities::DUAL_SOURCE_BLENDING;
+                }
+                if sampling == Some(crate::Sampling::Sample) {
+                    required |= Capabilities::MULTISAMPLED_SHADING;
+                }
+                required
+            }
+        };
+        self.require(required, span);
+    }
+}
+
+struct BlockContext<'a> {
+    info: &'a FunctionInfo,
+    subgroup_uses: &'a [Option<Span>],
+    /// The span of the first subgroup operation found so far.
+    first_subgroup_use: Option<Span>,
+}
@@ -203,14 +203,16 @@ fn check_member_layout(
 /// `TypeFlags::empty()`.
 ///
 /// Pointers passed as arguments to user-defined functions must be in the
-/// `Function` or `Private` address space.
+/// `Function`, `Private`, `Uniform`, `Storage`, or `WorkGroup` address
+/// space. The last three are permitted by WGSL's
+/// `unrestricted_pointer_parameters` language extension.
 const fn ptr_space_argument_flag(space: crate::AddressSpace) -> TypeFlags {
     use crate::AddressSpace as As;
     match space {
-        As::Function | As::Private => TypeFlags::ARGUMENT,
-        As::Uniform | As::Storage { .. } | As::Handle | As::PushConstant | As::WorkGroup => {
-            TypeFlags::empty()
+        As::Function | As::Private | As::Uniform | As::Storage { .. } | As::WorkGroup => {
+            TypeFlags::ARGUMENT
         }
+        As::Handle | As::PushConstant => TypeFlags::empty(),
     }
 }
 
@@ -260,6 +262,14 @@ impl super::Validator {
                         });
                     }
                     true
+                } else if scalar.width == 2 {
+                    if !self.capabilities.contains(Capabilities::SHADER_FLOAT16) {
+                        return Err(WidthError::MissingCapability {
+                            name: "f16",
+                            flag: "SHADER_FLOAT16",
+                        });
+                    }
+                    true
                 } else {
                     scalar.width == 4
                 }
@@ -0,0 +1,21 @@
+requires packed_4x8_integer_dot_product;
+
+fn test_packed_integer_dot_product() -> u32 {
+    let a_signed = 1u;
+    let b_signed = 2u;
+    let c_signed: i32 = dot4I8Packed(a_signed, b_signed);
+
+    let a_unsigned = 3u;
+    let b_unsigned = 4u;
+    let c_unsigned: u32 = dot4U8Packed(a_unsigned, b_unsigned);
+
+    // test baking of arguments
+    let c_signed_baked: i32 = dot4I8Packed(5u + c_unsigned, 6u + c_unsigned);
+    let c_unsigned_baked: u32 = dot4U8Packed(7u + c_unsigned, 8u + c_unsigned);
+    return c_unsigned_baked;
+}
+
+@compute @workgroup_size(1)
+fn main() {
+    let c = test_packed_integer_dot_product();
+}
@@ -0,0 +1,20 @@
+// The descriptor heap indices come from a `ByteAddressBuffer`, see the
+// `Buffer` index source in `hlsl-descriptor-heap-buffer.param.ron`.
+
+struct Material {
+    tint: vec4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> material: Material;
+@group(0) @binding(1)
+var samp: sampler;
+@group(1) @binding(0)
+var textures: binding_array<texture_2d<f32>, 8>;
+
+@fragment
+fn main(@location(0) uv: vec2<f32>, @location(1) @interpolate(flat) index: u32) -> @location(0) vec4<f32> {
+    let first = textureSample(textures[0], samp, uv);
+    let layer = textureSample(textures[index], samp, uv);
+    return (first + layer) * material.tint;
+}
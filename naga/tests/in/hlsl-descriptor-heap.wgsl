@@ -0,0 +1,44 @@
+struct Params {
+    scale: f32,
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> input: array<f32>;
+@group(0) @binding(2)
+var<storage, read_write> output: array<f32>;
+
+@group(1) @binding(0)
+var tex: texture_2d<f32>;
+@group(1) @binding(1)
+var samp: sampler;
+@group(1) @binding(2)
+var textures: binding_array<texture_2d<f32>, 4>;
+@group(1) @binding(3)
+var samplers: binding_array<sampler, 4>;
+
+fn scaled(index: u32) -> f32 {
+    return input[index] * params.scale;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.count {
+        output[id.x] = scaled(id.x);
+    }
+}
+
+struct FragmentIn {
+    @location(0) uv: vec2<f32>,
+    @location(1) @interpolate(flat) index: u32,
+}
+
+@fragment
+fn fs_main(in: FragmentIn) -> @location(0) vec4<f32> {
+    let base = textureSample(tex, samp, in.uv);
+    let first = textureSample(textures[0], samplers[0], in.uv);
+    let layer = textureSample(textures[in.index], samplers[in.index], in.uv);
+    return base + first + layer;
+}
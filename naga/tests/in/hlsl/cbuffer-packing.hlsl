@@ -0,0 +1,36 @@
+// Members of a `cbuffer` are at the offsets HLSL's packing rules give them.
+
+struct Light {
+    float3 direction;
+    float intensity;
+    float4 color;
+};
+
+cbuffer Scene : register(b0) {
+    float4x4 view_projection;
+    float3 camera_position;
+    float time;
+    float2 jitter;
+    float2 resolution;
+    Light lights[2];
+    float exposure;
+    row_major float4x3 model;
+    float3 tint;
+};
+
+[shader("vertex")]
+float4 vs_main(float3 position : POSITION) : SV_Position {
+    float3 world = mul(float4(position, 1), model);
+    float4 clip = mul(view_projection, float4(world - camera_position, 1));
+    return clip + float4(jitter / resolution, 0, 0) * time;
+}
+
+[shader("pixel")]
+float4 ps_main(float3 normal : NORMAL) : SV_Target {
+    float3 color = 0;
+    for (int i = 0; i < 2; i++) {
+        float light = saturate(dot(normal, -lights[i].direction));
+        color += lights[i].color.rgb * light * lights[i].intensity;
+    }
+    return float4(color * tint * exposure, 1);
+}
@@ -0,0 +1,12 @@
+// A vector that HLSL packs at an offset naga's vectors can't have, like a
+// `float3` after a `float`, is stored as one member per component.
+
+cbuffer Fog : register(b0) {
+    float density;
+    float3 color;
+};
+
+[shader("pixel")]
+float4 main() : SV_Target {
+    return float4(color, density);
+}
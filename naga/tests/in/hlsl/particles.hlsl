@@ -0,0 +1,24 @@
+struct Particle {
+    float3 position;
+    float3 velocity;
+};
+
+StructuredBuffer<float> masses : register(t0);
+RWStructuredBuffer<Particle> particles : register(u1);
+RWTexture2D<float4> image : register(u2);
+groupshared float shared_mass[64];
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID, uint index : SV_GroupIndex) {
+    uint count, stride;
+    particles.GetDimensions(count, stride);
+    if (id.x >= count) {
+        return;
+    }
+    shared_mass[index] = masses[id.x];
+    GroupMemoryBarrierWithGroupSync();
+    Particle p = particles[id.x];
+    p.position += p.velocity * shared_mass[index];
+    particles[id.x] = p;
+    image[id.xy] = float4(p.position, 1);
+}
@@ -0,0 +1,41 @@
+struct Params {
+    scale: f32,
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+@group(0) @binding(1)
+var<storage, read> input: array<f32>;
+@group(0) @binding(2)
+var<storage, read_write> output: array<f32>;
+@group(0) @binding(3)
+var tex: texture_2d<f32>;
+@group(0) @binding(4)
+var samp: sampler;
+@group(0) @binding(5)
+var textures: binding_array<texture_2d<f32>, 4>;
+
+@group(1) @binding(0)
+var<uniform> tint: vec4<f32>;
+
+fn scaled(index: u32) -> f32 {
+    return input[index] * params.scale;
+}
+
+fn layer(index: u32, uv: vec2<f32>) -> vec4<f32> {
+    return textureSampleLevel(textures[index], samp, uv, 0.0);
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    if id.x < params.count {
+        output[id.x] = scaled(id.x);
+    }
+}
+
+@fragment
+fn fs_main(@location(0) uv: vec2<f32>, @location(1) @interpolate(flat) index: u32) -> @location(0) vec4<f32> {
+    let base = textureSample(tex, samp, uv);
+    return (base + layer(index, uv)) * tint;
+}
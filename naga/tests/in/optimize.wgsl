@@ -0,0 +1,74 @@
+// Exercises the passes in `naga::opt`.
+
+struct Params {
+    scale: f32,
+    offset: vec2<f32>,
+    count: u32,
+}
+
+@group(0) @binding(0)
+var<uniform> params: Params;
+
+@group(0) @binding(1)
+var<storage, read_write> output: array<vec4<f32>>;
+
+// Inlined, returning from its tail.
+fn transform(p: vec2<f32>) -> vec2<f32> {
+    return p * params.scale + params.offset;
+}
+
+// Inlined, returning through a new local variable.
+fn select_axis(v: vec2<f32>, x: bool) -> f32 {
+    if x {
+        return v.x;
+    } else {
+        return v.y;
+    }
+}
+
+// Not inlined, since it returns from inside a loop.
+fn find_first(threshold: f32) -> u32 {
+    for (var i = 0u; i < params.count; i++) {
+        if output[i].x > threshold {
+            return i;
+        }
+    }
+    return params.count;
+}
+
+// Never called, so kept as-is.
+fn unused(a: f32) -> f32 {
+    let b = a * 2.0;
+    return b;
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    // Constant propagation: `k` and `origin` always hold the same value, so
+    // `origin.x * k` folds to `3.0`.
+    let k = 3.0;
+    var origin = vec2(1.0, 2.0);
+    var scale = 0.5;
+
+    // Dead stores: the first store to `total` is overwritten before being
+    // read, and `unread` is never read at all.
+    var total = 0.0;
+    var unread = 7.0;
+    total = origin.x * k;
+    unread = total;
+
+    // Common subexpressions: `output[id.x]` is computed once.
+    let a = output[id.x].x + output[id.x].y;
+
+    // Loop-invariant hoisting: `f32(id.y) * scale` doesn't change. Only the
+    // SPIR-V output shows this, since the WGSL writer bakes hoisted
+    // expressions at their uses, putting this one back inside the loop.
+    for (var i = 0u; i < 4u; i++) {
+        total += f32(id.y) * scale + f32(i);
+    }
+
+    let p = transform(origin);
+    let axis = select_axis(p, a > 0.0);
+    let first = find_first(axis);
+    output[id.x] = vec4(p, total, f32(first));
+}
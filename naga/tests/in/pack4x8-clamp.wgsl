@@ -0,0 +1,8 @@
+@compute @workgroup_size(1)
+fn main() {
+    var i4 = vec4<i32>(0);
+    var u = 0u;
+    var u4 = vec4<u32>(0u);
+    u = pack4xI8Clamp(i4);
+    u = pack4xU8Clamp(u4);
+}
@@ -0,0 +1,22 @@
+requires readonly_and_readwrite_storage_textures;
+
+@group(0) @binding(0) var s_r_r: texture_storage_2d<r32float, read>;
+@group(0) @binding(1) var s_rgba_r: texture_storage_2d<rgba32float, read>;
+@group(0) @binding(2) var s_r_rw: texture_storage_2d<r32float, read_write>;
+@group(0) @binding(3) var s_rgba_w: texture_storage_2d<rgba32float, write>;
+
+@compute @workgroup_size(1)
+fn csLoad(@builtin(local_invocation_id) id: vec3<u32>) {
+    let coords = vec2<i32>(id.xy);
+    let a = textureLoad(s_r_r, coords);
+    let b = textureLoad(s_rgba_r, coords);
+    textureStore(s_rgba_w, coords, a + b);
+}
+
+@compute @workgroup_size(1)
+fn csReadWrite(@builtin(local_invocation_id) id: vec3<u32>) {
+    let coords = vec2<i32>(id.xy);
+    let value = textureLoad(s_r_rw, coords);
+    textureBarrier();
+    textureStore(s_r_rw, coords, value * 2.0);
+}
@@ -0,0 +1,16 @@
+@group(0) @binding(0)
+var<storage, read_write> derivatives: array<vec2<f32>, 64>;
+
+@compute @workgroup_size(64)
+fn main(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(subgroup_invocation_id) subgroup_invocation_id: u32,
+) {
+    let value = f32(global_id.x * global_id.x);
+    let dx = quadSwapX(value) - value;
+    let dy = quadSwapY(value) - value;
+    let diagonal = quadSwapDiagonal(value);
+    let first = quadBroadcast(value, 0u);
+    quadBroadcast(subgroup_invocation_id, 3u);
+    derivatives[global_id.x % 64u] = vec2(dx, dy) + (diagonal - first);
+}
@@ -0,0 +1,60 @@
+requires unrestricted_pointer_parameters, pointer_composite_access;
+
+struct Particle {
+    position: vec4<f32>,
+    velocity: vec4<f32>,
+}
+
+struct Particles {
+    count: u32,
+    items: array<Particle, 8>,
+}
+
+struct Params {
+    scale: vec4<f32>,
+    offsets: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0) var<storage, read_write> particles: Particles;
+@group(0) @binding(1) var<storage, read> source: Particles;
+@group(0) @binding(2) var<uniform> params: Params;
+
+var<workgroup> counter: atomic<u32>;
+var<workgroup> scratch: array<u32, 64>;
+
+fn advance(p: ptr<storage, Particle, read_write>, offset: ptr<uniform, vec4<f32>>) {
+    p.position += p.velocity * offset.w + *offset;
+}
+
+fn read_count(p: ptr<storage, Particles, read>) -> u32 {
+    return p.count;
+}
+
+fn advance_all(p: ptr<storage, Particles, read_write>, i: u32) {
+    advance(&p.items[i], &params.offsets[i % 4]);
+}
+
+fn bump(c: ptr<workgroup, atomic<u32>>) -> u32 {
+    return atomicAdd(c, 1u);
+}
+
+fn store(slots: ptr<workgroup, array<u32, 64>>, index: u32, value: u32) {
+    slots[index] = value;
+}
+
+fn local_sum(p: ptr<function, array<u32, 4>>) -> u32 {
+    return p[0] + p[1] + p[2] + p[3];
+}
+
+@compute @workgroup_size(64)
+fn main(@builtin(local_invocation_index) index: u32) {
+    var local = array<u32, 4>(1u, 2u, 3u, 4u);
+    let n = read_count(&source) + local_sum(&local);
+    if index < n {
+        advance_all(&particles, index);
+        advance(&particles.items[0], &params.offsets[1]);
+    }
+    let slot = bump(&counter);
+    store(&scratch, slot, index);
+    particles.count = read_count(&source);
+}
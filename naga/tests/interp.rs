@@ -0,0 +1,378 @@
+/*!
+Tests for the compute shader interpreter.
+*/
+
+#![cfg(all(feature = "wgsl-in", feature = "interp"))]
+
+use naga::interp::{Error, Interpreter, Resource, Resources, Texture};
+use naga::{valid, ResourceBinding, StorageFormat};
+
+const fn binding(binding: u32) -> ResourceBinding {
+    ResourceBinding { group: 0, binding }
+}
+
+fn dispatch(source: &str, workgroups: [u32; 3], resources: &mut Resources) -> Result<(), Error> {
+    let module = naga::front::wgsl::parse_str(source).unwrap_or_else(|e| {
+        panic!(
+            "expected WGSL to parse successfully:\n{}",
+            e.emit_to_string(source)
+        );
+    });
+    let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .unwrap_or_else(|e| panic!("expected WGSL to validate:\n{}", e.emit_to_string(source)));
+    Interpreter::new(&module, &info).dispatch("main", workgroups, resources)
+}
+
+fn to_bytes(values: &[u32]) -> Vec<u8> {
+    values.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn from_bytes(bytes: &[u8]) -> Vec<u32> {
+    bytes
+        .chunks_exact(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect()
+}
+
+/// Runs `source` with a single storage buffer of `len` `u32`s at binding 0,
+/// and returns its contents.
+fn run_with_output(source: &str, workgroups: [u32; 3], len: usize) -> Vec<u32> {
+    let mut output = vec![0; len * 4];
+    let mut resources = Resources::default();
+    resources.insert(binding(0), Resource::Buffer(&mut output));
+    dispatch(source, workgroups, &mut resources).unwrap();
+    drop(resources);
+    from_bytes(&output)
+}
+
+#[test]
+fn workgroup_reduction() {
+    // Each workgroup sums its slice of the input with a tree reduction in
+    // workgroup memory, which only works if the barriers do.
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> sums: array<u32>;
+        @group(0) @binding(1) var<storage> input: array<u32>;
+
+        var<workgroup> partial: array<u32, 8>;
+
+        @compute @workgroup_size(8)
+        fn main(
+            @builtin(local_invocation_index) local: u32,
+            @builtin(workgroup_id) group: vec3<u32>,
+            @builtin(global_invocation_id) global: vec3<u32>,
+        ) {
+            partial[local] = input[global.x];
+            workgroupBarrier();
+            for (var stride = 4u; stride > 0u; stride /= 2u) {
+                if local < stride {
+                    partial[local] += partial[local + stride];
+                }
+                workgroupBarrier();
+            }
+            if local == 0u {
+                sums[group.x] = partial[0];
+            }
+        }
+    ";
+    let mut sums = vec![0; 2 * 4];
+    let mut input = to_bytes(&(1..=16).collect::<Vec<_>>());
+    let mut resources = Resources::default();
+    resources.insert(binding(0), Resource::Buffer(&mut sums));
+    resources.insert(binding(1), Resource::Buffer(&mut input));
+    dispatch(source, [2, 1, 1], &mut resources).unwrap();
+    drop(resources);
+    assert_eq!(from_bytes(&sums), [36, 100]);
+}
+
+#[test]
+fn atomics() {
+    let source = "
+        struct Output {
+            counter: atomic<u32>,
+            max: atomic<i32>,
+            exchanged: atomic<u32>,
+            order: array<u32, 8>,
+        }
+        @group(0) @binding(0) var<storage, read_write> output: Output;
+
+        @compute @workgroup_size(4)
+        fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+            let old = atomicAdd(&output.counter, 1u);
+            output.order[old] = id.x;
+            atomicMax(&output.max, i32(id.x) - 3);
+            let result = atomicCompareExchangeWeak(&output.exchanged, 0u, id.x + 10u);
+            if result.exchanged {
+                atomicOr(&output.exchanged, 0x100u);
+            }
+        }
+    ";
+    let output = run_with_output(source, [2, 1, 1], 11);
+    assert_eq!(output[..3], [8, 4, 0x100 | 10]);
+    // Invocations run in a fixed order, so the values returned by atomics are
+    // deterministic.
+    assert_eq!(output[3..], [0, 1, 2, 3, 4, 5, 6, 7]);
+}
+
+#[test]
+fn workgroup_uniform_load() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32, 4>;
+        var<workgroup> value: u32;
+
+        @compute @workgroup_size(4)
+        fn main(@builtin(local_invocation_index) index: u32) {
+            if index == 3u {
+                value = 42u;
+            }
+            output[index] = workgroupUniformLoad(&value) + index;
+        }
+    ";
+    assert_eq!(run_with_output(source, [1, 1, 1], 4), [42, 43, 44, 45]);
+}
+
+#[test]
+fn control_flow_and_functions() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32, 6>;
+
+        fn collatz_steps(start: u32) -> u32 {
+            var n = start;
+            var steps = 0u;
+            loop {
+                if n == 1u {
+                    break;
+                }
+                if n % 2u == 0u {
+                    n /= 2u;
+                    continue;
+                }
+                n = 3u * n + 1u;
+                continuing {
+                    steps += 1u;
+                }
+            }
+            return steps;
+        }
+
+        fn accumulate(total: ptr<function, u32>, value: u32) {
+            *total += value;
+        }
+
+        fn classify(x: u32) -> u32 {
+            switch x {
+                case 0u, 1u: { return 10u; }
+                case 2u: { return 20u; }
+                default: { return 30u; }
+            }
+        }
+
+        @compute @workgroup_size(1)
+        fn main() {
+            var total = 0u;
+            for (var i = 1u; i <= 4u; i++) {
+                accumulate(&total, i);
+            }
+            output[0] = total;
+            output[1] = collatz_steps(6u);
+            output[2] = classify(1u);
+            output[3] = classify(2u);
+            output[4] = classify(7u);
+            var count = 0u;
+            while count < 100u {
+                count += 7u;
+            }
+            output[5] = count;
+        }
+    ";
+    assert_eq!(
+        run_with_output(source, [1, 1, 1], 6),
+        [10, 8, 10, 20, 30, 105]
+    );
+}
+
+#[test]
+fn arithmetic() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32, 12>;
+
+        struct Params {
+            scale: mat2x2<f32>,
+            offset: vec2<f32>,
+        }
+        @group(0) @binding(1) var<uniform> params: Params;
+
+        var<private> seed: i32 = -7;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            let zero = u32(seed + 7);
+            output[0] = 5u / zero;
+            output[1] = 5u % zero;
+            output[2] = bitcast<u32>(seed / 2);
+            output[3] = countOneBits(0xF0F0u);
+            output[4] = reverseBits(1u);
+            output[5] = bitcast<u32>(extractBits(seed, 1u, 3u));
+            output[6] = pack4x8unorm(vec4(0.0, 1.0, 0.5, 1.0));
+            let v = params.scale * vec2(1.0, 2.0) + params.offset;
+            output[7] = u32(v.x);
+            output[8] = u32(v.y);
+            output[9] = u32(dot(vec3(1, 2, 3), vec3(4, 5, 6)));
+            output[10] = u32(round(2.5) + round(3.5));
+            output[11] = select(1u, 2u, all(vec2(seed < 0, true)));
+        }
+    ";
+    let mut output = vec![0; 12 * 4];
+    let mut params: Vec<u8> = [1.0f32, 2.0, 3.0, 4.0, 0.5, 100.0]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect();
+    let mut resources = Resources::default();
+    resources.insert(binding(0), Resource::Buffer(&mut output));
+    resources.insert(binding(1), Resource::Buffer(&mut params));
+    dispatch(source, [1, 1, 1], &mut resources).unwrap();
+    drop(resources);
+    assert_eq!(
+        from_bytes(&output),
+        [
+            5,
+            0,
+            -3i32 as u32,
+            8,
+            0x8000_0000,
+            -4i32 as u32,
+            0xFF80_FF00,
+            7,
+            110,
+            32,
+            6,
+            2,
+        ]
+    );
+}
+
+#[test]
+fn textures() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32, 3>;
+        @group(0) @binding(1) var input: texture_2d<f32>;
+        @group(0) @binding(2) var result: texture_storage_2d<r32uint, write>;
+
+        @compute @workgroup_size(2, 2)
+        fn main(@builtin(local_invocation_id) id: vec3<u32>) {
+            let texel = textureLoad(input, id.xy, 0);
+            textureStore(result, id.xy, vec4(u32(round(texel.r * 255.0)) + u32(texel.a)));
+            if all(id == vec3(0u)) {
+                let size = textureDimensions(input);
+                output[0] = size.x;
+                output[1] = size.y;
+                output[2] = textureNumLevels(input);
+            }
+        }
+    ";
+    let mut output = vec![0; 3 * 4];
+    let mut input = [10u8, 0, 0, 255, 20, 0, 0, 255, 30, 0, 0, 255, 40, 0, 0, 0];
+    let mut result = vec![0; 4 * 4];
+    let mut resources = Resources::default();
+    resources.insert(binding(0), Resource::Buffer(&mut output));
+    resources.insert(
+        binding(1),
+        Resource::Texture(Texture {
+            data: &mut input,
+            format: StorageFormat::Rgba8Unorm,
+            size: [2, 2, 1],
+        }),
+    );
+    resources.insert(
+        binding(2),
+        Resource::Texture(Texture {
+            data: &mut result,
+            format: StorageFormat::R32Uint,
+            size: [2, 2, 1],
+        }),
+    );
+    dispatch(source, [1, 1, 1], &mut resources).unwrap();
+    drop(resources);
+    assert_eq!(from_bytes(&output), [2, 2, 1]);
+    assert_eq!(from_bytes(&result), [11, 21, 31, 40]);
+}
+
+#[test]
+fn errors() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            output[4] = 1u;
+        }
+
+        @fragment
+        fn fragment() {}
+    ";
+    let mut output = vec![0; 4 * 4];
+    let mut resources = Resources::default();
+    let error = dispatch(source, [1, 1, 1], &mut resources).unwrap_err();
+    assert!(matches!(error, Error::MissingResource(b) if b == binding(0)));
+    resources.insert(binding(0), Resource::Sampler);
+    let error = dispatch(source, [1, 1, 1], &mut resources).unwrap_err();
+    assert!(matches!(error, Error::ResourceMismatch(b, "buffer") if b == binding(0)));
+    resources.insert(binding(0), Resource::Buffer(&mut output));
+    let error = dispatch(source, [1, 1, 1], &mut resources).unwrap_err();
+    assert!(matches!(error, Error::OutOfBounds));
+
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .unwrap();
+    let interpreter = Interpreter::new(&module, &info);
+    let error = interpreter
+        .dispatch("fragment", [1, 1, 1], &mut resources)
+        .unwrap_err();
+    assert!(matches!(error, Error::NotCompute(name) if name == "fragment"));
+    let error = interpreter
+        .dispatch("missing", [1, 1, 1], &mut resources)
+        .unwrap_err();
+    assert!(matches!(error, Error::MissingEntryPoint(name) if name == "missing"));
+}
+
+#[test]
+fn step_limit() {
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> output: array<u32>;
+
+        @compute @workgroup_size(1)
+        fn main() {
+            for (var i = 0u; i < 100u; i++) {
+                output[0] += 1u;
+            }
+        }
+
+        @compute @workgroup_size(1)
+        fn forever() {
+            loop {}
+        }
+    ";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .unwrap();
+    let mut output = vec![0; 4];
+    let mut resources = Resources::default();
+    resources.insert(binding(0), Resource::Buffer(&mut output));
+
+    let interpreter = Interpreter::new(&module, &info);
+    let error = interpreter
+        .with_step_limit(1000)
+        .dispatch("forever", [1, 1, 1], &mut resources)
+        .unwrap_err();
+    assert!(matches!(error, Error::StepLimit(1000)));
+    let error = interpreter
+        .with_step_limit(50)
+        .dispatch("main", [1, 1, 1], &mut resources)
+        .unwrap_err();
+    assert!(matches!(error, Error::StepLimit(50)));
+    interpreter
+        .dispatch("main", [1, 1, 1], &mut resources)
+        .unwrap();
+}
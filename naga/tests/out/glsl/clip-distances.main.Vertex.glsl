@@ -0,0 +1,15 @@
+#version 330 core
+struct VertexOutput {
+    vec4 position;
+    float clip_distances[1];
+};
+
+void main() {
+    VertexOutput out_ = VertexOutput(vec4(0.0), float[1](0.0));
+    out_.clip_distances[0] = 0.5;
+    VertexOutput _e4 = out_;
+    gl_Position = _e4.position;
+    gl_ClipDistance = _e4.clip_distances;
+    return;
+}
+
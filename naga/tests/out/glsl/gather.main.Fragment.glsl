@@ -0,0 +1,38 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+uniform highp sampler2D _group_0_binding_0_fs;
+
+uniform highp sampler2DShadow _group_0_binding_1_fs;
+
+uniform highp sampler2DShadow _group_0_binding_2_fs;
+
+vec2 uv_1 = vec2(0.0);
+
+vec4 color = vec4(0.0);
+
+layout(location = 0) smooth in vec2 _vs2fs_location0;
+layout(location = 0) out vec4 _fs2p_location0;
+
+void main_1() {
+    vec2 _e8 = uv_1;
+    vec4 _e9 = textureGather(_group_0_binding_0_fs, vec2(_e8), 1);
+    vec4 _e10 = textureGatherOffset(_group_0_binding_0_fs, vec2(_e8), ivec2(1, -1), 3);
+    vec4 _e11 = textureGather(_group_0_binding_1_fs, vec2(_e8), 0.5);
+    vec4 _e12 = textureGatherOffset(_group_0_binding_1_fs, vec2(_e8), 0.5, ivec2(1, -1));
+    vec4 _e13 = textureGather(_group_0_binding_2_fs, vec2(_e8), 0);
+    color = ((((_e9 + _e10) + _e11) + _e12) + _e13);
+    return;
+}
+
+void main() {
+    vec2 uv = _vs2fs_location0;
+    uv_1 = uv;
+    main_1();
+    vec4 _e3 = color;
+    _fs2p_location0 = _e3;
+    return;
+}
+
@@ -0,0 +1,92 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+struct Params {
+    float scale;
+    vec2 offset;
+    uint count;
+};
+uniform Params_block_0Compute { Params _group_0_binding_0_cs; };
+
+layout(std430) buffer type_4_block_1Compute { vec4 _group_0_binding_1_cs[]; };
+
+
+uint find_first(float threshold) {
+    uint i_1 = 0u;
+    bool loop_init = true;
+    while(true) {
+        if (!loop_init) {
+            uint _e14 = i_1;
+            i_1 = (_e14 + 1u);
+        }
+        loop_init = false;
+        uint _e3 = i_1;
+        uint _e4 = _group_0_binding_0_cs.count;
+        if ((_e3 < _e4)) {
+        } else {
+            break;
+        }
+        {
+            uint _e6 = i_1;
+            float _e10 = _group_0_binding_1_cs[_e6].x;
+            if ((_e10 > threshold)) {
+                uint _e13 = i_1;
+                return _e13;
+            }
+        }
+    }
+    uint _e19 = _group_0_binding_0_cs.count;
+    return _e19;
+}
+
+float unused(float a) {
+    float b = (a * 2.0);
+    return b;
+}
+
+void main() {
+    uvec3 id = gl_GlobalInvocationID;
+    float total = 0.0;
+    uint i = 0u;
+    float select_axis_result = 0.0;
+    total = 3.0;
+    float _e10 = _group_0_binding_1_cs[id.x].x;
+    float _e14 = _group_0_binding_1_cs[id.x].y;
+    float a_1 = (_e10 + _e14);
+    bool loop_init_1 = true;
+    while(true) {
+        if (!loop_init_1) {
+            uint _e29 = i;
+            i = (_e29 + 1u);
+        }
+        loop_init_1 = false;
+        uint _e21 = i;
+        if ((_e21 < 4u)) {
+        } else {
+            break;
+        }
+        {
+            uint _e24 = i;
+            float _e27 = total;
+            total = (_e27 + ((float(id.y) * 0.5) + float(_e24)));
+        }
+    }
+    float _e34 = _group_0_binding_0_cs.scale;
+    vec2 _e38 = _group_0_binding_0_cs.offset;
+    vec2 p = ((vec2(1.0, 2.0) * _e34) + _e38);
+    if ((a_1 > 0.0)) {
+        select_axis_result = p.x;
+    } else {
+        select_axis_result = p.y;
+    }
+    float axis = select_axis_result;
+    uint _e48 = find_first(axis);
+    float _e51 = total;
+    _group_0_binding_1_cs[id.x] = vec4(p, _e51, float(_e48));
+    return;
+}
+
@@ -0,0 +1,29 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+
+uint naga_pack4xI8Clamp(ivec4 arg) {
+    ivec4 clamped = clamp(arg, -128, 127);
+    return uint((clamped[0] & 0xFF) | ((clamped[1] & 0xFF) << 8) | ((clamped[2] & 0xFF) << 16) | ((clamped[3] & 0xFF) << 24));
+}
+
+uint naga_pack4xU8Clamp(uvec4 arg) {
+    uvec4 clamped = clamp(arg, 0u, 255u);
+    return (clamped[0] & 0xFFu) | ((clamped[1] & 0xFFu) << 8) | ((clamped[2] & 0xFFu) << 16) | ((clamped[3] & 0xFFu) << 24);
+}
+
+void main() {
+    ivec4 i4_ = ivec4(0);
+    uint u = 0u;
+    uvec4 u4_ = uvec4(0u);
+    ivec4 _e8 = i4_;
+    u = naga_pack4xI8Clamp(_e8);
+    uvec4 _e10 = u4_;
+    u = naga_pack4xU8Clamp(_e10);
+    return;
+}
+
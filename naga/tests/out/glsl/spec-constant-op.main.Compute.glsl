@@ -0,0 +1,35 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 32, local_size_y = 1, local_size_z = 1) in;
+
+struct Data {
+    uint shifted;
+    uint picked;
+    int negated;
+    uint sum;
+};
+const uint folded = 12u;
+const uint a = 10u;
+const int b = 3;
+const bool flag = true;
+const uint wg_x = 32u;
+
+layout(std430) buffer Data_block_0Compute { Data _group_0_binding_0_cs; };
+
+
+void main_1() {
+    uint _e14 = (a + uint(b));
+    _group_0_binding_0_cs.shifted = (((_e14 > 4u) ? _e14 : 1u) << 2u);
+    _group_0_binding_0_cs.picked = (false ? 12u : folded);
+    _group_0_binding_0_cs.negated = -3;
+    _group_0_binding_0_cs.sum = (_e14 + a);
+    return;
+}
+
+void main() {
+    main_1();
+}
+
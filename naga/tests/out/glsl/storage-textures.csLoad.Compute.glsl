@@ -0,0 +1,20 @@
+#version 430 core
+#extension GL_ARB_compute_shader : require
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+layout(r32f) readonly uniform image2D _group_0_binding_0_cs;
+
+layout(rgba32f) readonly uniform image2D _group_0_binding_1_cs;
+
+layout(rgba32f) writeonly uniform image2D _group_0_binding_3_cs;
+
+
+void main() {
+    uvec3 id = gl_LocalInvocationID;
+    ivec2 coords = ivec2(id.xy);
+    vec4 a = imageLoad(_group_0_binding_0_cs, coords);
+    vec4 b = imageLoad(_group_0_binding_1_cs, coords);
+    imageStore(_group_0_binding_3_cs, coords, (a + b));
+    return;
+}
+
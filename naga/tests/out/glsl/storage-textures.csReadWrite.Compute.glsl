@@ -0,0 +1,17 @@
+#version 430 core
+#extension GL_ARB_compute_shader : require
+layout(local_size_x = 1, local_size_y = 1, local_size_z = 1) in;
+
+layout(r32f) uniform image2D _group_0_binding_2_cs;
+
+
+void main() {
+    uvec3 id_1 = gl_LocalInvocationID;
+    ivec2 coords = ivec2(id_1.xy);
+    vec4 value = imageLoad(_group_0_binding_2_cs, coords);
+    memoryBarrierImage();
+    barrier();
+    imageStore(_group_0_binding_2_cs, coords, (value * 2.0));
+    return;
+}
+
@@ -0,0 +1,30 @@
+#version 430 core
+#extension GL_ARB_compute_shader : require
+#extension GL_ARB_shader_storage_buffer_object : require
+#extension GL_KHR_shader_subgroup_basic : require
+#extension GL_KHR_shader_subgroup_vote : require
+#extension GL_KHR_shader_subgroup_arithmetic : require
+#extension GL_KHR_shader_subgroup_ballot : require
+#extension GL_KHR_shader_subgroup_shuffle : require
+#extension GL_KHR_shader_subgroup_shuffle_relative : require
+#extension GL_KHR_shader_subgroup_quad : require
+layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+layout(std430) buffer type_2_block_0Compute { vec2 _group_0_binding_0_cs[64]; };
+
+
+void main() {
+    uvec3 global_id = gl_GlobalInvocationID;
+    uint subgroup_invocation_id = gl_SubgroupInvocationID;
+    float value = float((global_id.x * global_id.x));
+    float _e6 = subgroupQuadSwapHorizontal(value);
+    float dx = (_e6 - value);
+    float _e8 = subgroupQuadSwapVertical(value);
+    float dy = (_e8 - value);
+    float _e10 = subgroupQuadSwapDiagonal(value);
+    float _e12 = subgroupQuadBroadcast(value, 0u);
+    uint _e14 = subgroupQuadBroadcast(subgroup_invocation_id, 3u);
+    _group_0_binding_0_cs[(global_id.x % 64u)] = (vec2(dx, dy) + vec2((_e10 - _e12)));
+    return;
+}
+
@@ -0,0 +1,100 @@
+#version 310 es
+
+precision highp float;
+precision highp int;
+
+layout(local_size_x = 64, local_size_y = 1, local_size_z = 1) in;
+
+struct Particle {
+    vec4 position;
+    vec4 velocity;
+};
+struct Particles {
+    uint count;
+    Particle items[8];
+};
+struct Params {
+    vec4 scale;
+    vec4 offsets[4];
+};
+layout(std430) buffer Particles_block_0Compute { Particles _group_0_binding_0_cs; };
+
+layout(std430) readonly buffer Particles_block_1Compute { Particles _group_0_binding_1_cs; };
+
+uniform Params_block_2Compute { Params _group_0_binding_2_cs; };
+
+shared uint counter;
+
+shared uint scratch[64];
+
+
+uint local_sum(inout uint p[4]) {
+    uint _e2 = p[0];
+    uint _e4 = p[1];
+    uint _e7 = p[2];
+    uint _e10 = p[3];
+    return (((_e2 + _e4) + _e7) + _e10);
+}
+
+uint read_count_source() {
+    uint _e2 = _group_0_binding_1_cs.count;
+    return _e2;
+}
+
+void advance_particles_params(uint p_index0_, uint offset_index0_) {
+    vec4 _e10 = _group_0_binding_0_cs.items[p_index0_].velocity;
+    float _e12 = _group_0_binding_2_cs.offsets[offset_index0_].w;
+    vec4 _e14 = _group_0_binding_2_cs.offsets[offset_index0_];
+    vec4 _e16 = _group_0_binding_0_cs.items[p_index0_].position;
+    _group_0_binding_0_cs.items[p_index0_].position = (_e16 + ((_e10 * _e12) + _e14));
+    return;
+}
+
+void advance_all_particles(uint i) {
+    uint _e7 = (i % 4u);
+    advance_particles_params(i, _e7);
+    return;
+}
+
+void advance_particles_params_1() {
+    vec4 _e8 = _group_0_binding_0_cs.items[0].velocity;
+    float _e10 = _group_0_binding_2_cs.offsets[1].w;
+    vec4 _e12 = _group_0_binding_2_cs.offsets[1];
+    vec4 _e14 = _group_0_binding_0_cs.items[0].position;
+    _group_0_binding_0_cs.items[0].position = (_e14 + ((_e8 * _e10) + _e12));
+    return;
+}
+
+uint bump_counter() {
+    uint _e2 = atomicAdd(counter, 1u);
+    return _e2;
+}
+
+void store_scratch(uint index_1, uint value) {
+    scratch[index_1] = value;
+    return;
+}
+
+void main() {
+    if (gl_LocalInvocationID == uvec3(0u)) {
+        counter = 0u;
+        scratch = uint[64](0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u, 0u);
+    }
+    memoryBarrierShared();
+    barrier();
+    uint index = gl_LocalInvocationIndex;
+    uint local[4] = uint[4](1u, 2u, 3u, 4u);
+    uint _e8 = read_count_source();
+    uint _e9 = local_sum(local);
+    uint n = (_e8 + _e9);
+    if ((index < n)) {
+        advance_all_particles(index);
+        advance_particles_params_1();
+    }
+    uint _e20 = bump_counter();
+    store_scratch(_e20, index);
+    uint _e25 = read_count_source();
+    _group_0_binding_0_cs.count = _e25;
+    return;
+}
+
@@ -0,0 +1,15 @@
+uint test_packed_integer_dot_product()
+{
+    int c_signed = dot4add_i8packed(1u, 2u, 0);
+    uint c_unsigned = dot4add_u8packed(3u, 4u, 0u);
+    int c_signed_baked = dot4add_i8packed((5u + c_unsigned), (6u + c_unsigned), 0);
+    uint c_unsigned_baked = dot4add_u8packed((7u + c_unsigned), (8u + c_unsigned), 0u);
+    return c_unsigned_baked;
+}
+
+[numthreads(1, 1, 1)]
+void main()
+{
+    const uint _e0 = test_packed_integer_dot_product();
+    return;
+}
@@ -0,0 +1,32 @@
+Texture2D<float4> tex : register(t0);
+Texture2D<float> tex_depth : register(t1);
+Texture2D<float> tex_depth_2_ : register(t2);
+SamplerState samp : register(s3);
+SamplerComparisonState samp_cmp : register(s4);
+static float2 uv_1 = (float2)0;
+static float4 color = (float4)0;
+
+struct FragmentInput_main {
+    float2 uv_2 : LOC0;
+};
+
+void main_1()
+{
+    float2 _e8 = uv_1;
+    float4 _e9 = tex.GatherGreen(samp, _e8);
+    float4 _e10 = tex.GatherAlpha(samp, _e8, int2(int2(1, -1)));
+    float4 _e11 = tex_depth.GatherCmp(samp_cmp, _e8, 0.5);
+    float4 _e12 = tex_depth.GatherCmp(samp_cmp, _e8, 0.5, int2(int2(1, -1)));
+    float4 _e13 = tex_depth_2_.Gather(samp, _e8);
+    color = ((((_e9 + _e10) + _e11) + _e12) + _e13);
+    return;
+}
+
+float4 main(FragmentInput_main fragmentinput_main) : SV_Target0
+{
+    float2 uv = fragmentinput_main.uv_2;
+    uv_1 = uv;
+    main_1();
+    float4 _e3 = color;
+    return _e3;
+}
@@ -0,0 +1,32 @@
+ByteAddressBuffer _NagaDescriptorHeapIndices : register(t2, space1);
+
+struct Material {
+    float4 tint;
+};
+
+Texture2D<float4> textures(uint index)
+{
+    return ResourceDescriptorHeap[_NagaDescriptorHeapIndices.Load(8) + index];
+}
+Texture2D<float4> textures_non_uniform(uint index)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(_NagaDescriptorHeapIndices.Load(8) + index)];
+}
+
+struct FragmentInput_main {
+    float2 uv_1 : LOC0;
+    nointerpolation uint index_1 : LOC1;
+};
+
+float4 main(FragmentInput_main fragmentinput_main) : SV_Target0
+{
+    ConstantBuffer<Material> material = ResourceDescriptorHeap[_NagaDescriptorHeapIndices.Load(0)];
+    SamplerState samp = SamplerDescriptorHeap[_NagaDescriptorHeapIndices.Load(4)];
+
+    float2 uv = fragmentinput_main.uv_1;
+    uint index = fragmentinput_main.index_1;
+    float4 first = textures(0).Sample(samp, uv);
+    float4 layer = textures_non_uniform(index).Sample(samp, uv);
+    float4 _e13 = material.tint;
+    return ((first + layer) * _e13);
+}
@@ -0,0 +1,72 @@
+cbuffer NagaDescriptorHeapIndices : register(b0) { uint4 _NagaDescriptorHeapIndices[2]; }
+
+struct Params {
+    float scale;
+    uint count;
+};
+
+struct FragmentIn {
+    float2 uv : LOC0;
+    nointerpolation uint index : LOC1;
+};
+
+Texture2D<float4> textures(uint index)
+{
+    return ResourceDescriptorHeap[_NagaDescriptorHeapIndices[1].y + index];
+}
+Texture2D<float4> textures_non_uniform(uint index)
+{
+    return ResourceDescriptorHeap[NonUniformResourceIndex(_NagaDescriptorHeapIndices[1].y + index)];
+}
+SamplerState samplers(uint index)
+{
+    return SamplerDescriptorHeap[_NagaDescriptorHeapIndices[1].z + index];
+}
+SamplerState samplers_non_uniform(uint index)
+{
+    return SamplerDescriptorHeap[NonUniformResourceIndex(_NagaDescriptorHeapIndices[1].z + index)];
+}
+
+struct FragmentInput_fs_main {
+    float2 uv : LOC0;
+    nointerpolation uint index_1 : LOC1;
+};
+
+float scaled(uint index)
+{
+    ConstantBuffer<Params> params = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].x];
+    ByteAddressBuffer input = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].y];
+
+    float _e3 = asfloat(input.Load(index*4));
+    float _e6 = params.scale;
+    return (_e3 * _e6);
+}
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    ConstantBuffer<Params> params = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].x];
+    ByteAddressBuffer input = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].y];
+    RWByteAddressBuffer output = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].z];
+
+    uint _e4 = params.count;
+    if ((id.x < _e4)) {
+        const float _e10 = scaled(id.x);
+        output.Store(id.x*4, asuint(_e10));
+        return;
+    } else {
+        return;
+    }
+}
+
+float4 fs_main(FragmentInput_fs_main fragmentinput_fs_main) : SV_Target0
+{
+    Texture2D<float4> tex = ResourceDescriptorHeap[_NagaDescriptorHeapIndices[0].w];
+    SamplerState samp = SamplerDescriptorHeap[_NagaDescriptorHeapIndices[1].x];
+
+    FragmentIn in_ = { fragmentinput_fs_main.uv, fragmentinput_fs_main.index_1 };
+    float4 base = tex.Sample(samp, in_.uv);
+    float4 first = textures(0).Sample(samplers(0), in_.uv);
+    float4 layer = textures_non_uniform(in_.index).Sample(samplers_non_uniform(in_.index), in_.uv);
+    return ((base + first) + layer);
+}
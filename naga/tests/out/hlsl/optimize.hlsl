@@ -0,0 +1,90 @@
+struct Params {
+    float scale;
+    int _pad1_0;
+    float2 offset;
+    uint count;
+    int _end_pad_0;
+};
+
+cbuffer params : register(b0) { Params params; }
+RWByteAddressBuffer output : register(u1);
+
+uint find_first(float threshold)
+{
+    uint i_1 = 0u;
+
+    bool loop_init = true;
+    while(true) {
+        if (!loop_init) {
+            uint _e14 = i_1;
+            i_1 = (_e14 + 1u);
+        }
+        loop_init = false;
+        uint _e3 = i_1;
+        uint _e4 = params.count;
+        if ((_e3 < _e4)) {
+        } else {
+            break;
+        }
+        {
+            uint _e6 = i_1;
+            float _e10 = asfloat(output.Load(0+_e6*16));
+            if ((_e10 > threshold)) {
+                uint _e13 = i_1;
+                return _e13;
+            }
+        }
+    }
+    uint _e19 = params.count;
+    return _e19;
+}
+
+float unused(float a)
+{
+    float b = (a * 2.0);
+    return b;
+}
+
+[numthreads(64, 1, 1)]
+void main(uint3 id : SV_DispatchThreadID)
+{
+    float total = 0.0;
+    uint i = 0u;
+    float select_axis_result = (float)0;
+
+    total = 3.0;
+    float _e10 = asfloat(output.Load(0+id.x*16));
+    float _e14 = asfloat(output.Load(4+id.x*16));
+    float a_1 = (_e10 + _e14);
+    bool loop_init_1 = true;
+    while(true) {
+        if (!loop_init_1) {
+            uint _e29 = i;
+            i = (_e29 + 1u);
+        }
+        loop_init_1 = false;
+        uint _e21 = i;
+        if ((_e21 < 4u)) {
+        } else {
+            break;
+        }
+        {
+            uint _e24 = i;
+            float _e27 = total;
+            total = (_e27 + ((float(id.y) * 0.5) + float(_e24)));
+        }
+    }
+    float _e34 = params.scale;
+    float2 _e38 = params.offset;
+    float2 p = ((float2(1.0, 2.0) * _e34) + _e38);
+    if ((a_1 > 0.0)) {
+        select_axis_result = p.x;
+    } else {
+        select_axis_result = p.y;
+    }
+    float axis = select_axis_result;
+    const uint _e48 = find_first(axis);
+    float _e51 = total;
+    output.Store4(id.x*16, asuint(float4(p, _e51, float(_e48))));
+    return;
+}
@@ -0,0 +1,25 @@
+uint naga_pack4xI8Clamp(
+    int4 e
+) {
+    int4 c = clamp(e, -128, 127);
+    return uint((c[0] & 0xFF) | ((c[1] & 0xFF) << 8) | ((c[2] & 0xFF) << 16) | ((c[3] & 0xFF) << 24));
+}
+uint naga_pack4xU8Clamp(
+    uint4 e
+) {
+    uint4 c = clamp(e, 0, 255);
+    return ((c[0] & 0xFF) | ((c[1] & 0xFF) << 8) | ((c[2] & 0xFF) << 16) | ((c[3] & 0xFF) << 24));
+}
+[numthreads(1, 1, 1)]
+void main()
+{
+    int4 i4_ = (0).xxxx;
+    uint u = 0u;
+    uint4 u4_ = (0u).xxxx;
+
+    int4 _e8 = i4_;
+    u = naga_pack4xI8Clamp(_e8);
+    uint4 _e10 = u4_;
+    u = naga_pack4xU8Clamp(_e10);
+    return;
+}
@@ -0,0 +1,135 @@
+struct Output {
+    uint visible;
+    float t;
+    uint instance_id;
+    uint front_face;
+    float2 barycentrics;
+    int _pad5_0;
+    int _pad5_1;
+    float3 translation;
+    int _end_pad_0;
+};
+
+struct RayDesc_ {
+    uint flags;
+    uint cull_mask;
+    float tmin;
+    float tmax;
+    float3 origin;
+    int _pad5_0;
+    float3 dir;
+    int _end_pad_0;
+};
+
+struct RayIntersection {
+    uint kind;
+    float t;
+    uint instance_custom_index;
+    uint instance_id;
+    uint sbt_record_offset;
+    uint geometry_index;
+    uint primitive_index;
+    float2 barycentrics;
+    bool front_face;
+    int _pad9_0;
+    int _pad9_1;
+    row_major float4x3 object_to_world;
+    int _pad10_0;
+    row_major float4x3 world_to_object;
+    int _end_pad_0;
+};
+
+RayDesc RayDescFromRayDesc_(RayDesc_ arg0) {
+    RayDesc ret = (RayDesc)0;
+    ret.Origin = arg0.origin;
+    ret.TMin = arg0.tmin;
+    ret.Direction = arg0.dir;
+    ret.TMax = arg0.tmax;
+    return ret;
+}
+
+RaytracingAccelerationStructure acc_struct : register(t0);
+RWByteAddressBuffer output : register(u1);
+
+RayDesc_ ConstructRayDesc_(uint arg0, uint arg1, float arg2, float arg3, float3 arg4, float3 arg5) {
+    RayDesc_ ret = (RayDesc_)0;
+    ret.flags = arg0;
+    ret.cull_mask = arg1;
+    ret.tmin = arg2;
+    ret.tmax = arg3;
+    ret.origin = arg4;
+    ret.dir = arg5;
+    return ret;
+}
+
+RayIntersection GetCandidateIntersection(RayQuery<RAY_FLAG_NONE> rq) {
+    RayIntersection ret = (RayIntersection)0;
+    CANDIDATE_TYPE kind = rq.CandidateType();
+    if (kind == CANDIDATE_NON_OPAQUE_TRIANGLE) {
+        ret.kind = 1;
+        ret.t = rq.CandidateTriangleRayT();
+        ret.barycentrics = rq.CandidateTriangleBarycentrics();
+        ret.front_face = rq.CandidateTriangleFrontFace();
+    } else {
+        ret.kind = 3;
+    }
+    ret.instance_custom_index = rq.CandidateInstanceID();
+    ret.instance_id = rq.CandidateInstanceIndex();
+    ret.sbt_record_offset = rq.CandidateInstanceContributionToHitGroupIndex();
+    ret.geometry_index = rq.CandidateGeometryIndex();
+    ret.primitive_index = rq.CandidatePrimitiveIndex();
+    ret.object_to_world = rq.CandidateObjectToWorld4x3();
+    ret.world_to_object = rq.CandidateWorldToObject4x3();
+    return ret;
+}
+
+RayIntersection GetCommittedIntersection(RayQuery<RAY_FLAG_NONE> rq) {
+    RayIntersection ret = (RayIntersection)0;
+    ret.kind = rq.CommittedStatus();
+    if( rq.CommittedStatus() == COMMITTED_NOTHING) {} else {
+        ret.t = rq.CommittedRayT();
+        ret.instance_custom_index = rq.CommittedInstanceID();
+        ret.instance_id = rq.CommittedInstanceIndex();
+        ret.sbt_record_offset = rq.CommittedInstanceContributionToHitGroupIndex();
+        ret.geometry_index = rq.CommittedGeometryIndex();
+        ret.primitive_index = rq.CommittedPrimitiveIndex();
+        if( rq.CommittedStatus() == COMMITTED_TRIANGLE_HIT ) {
+            ret.barycentrics = rq.CommittedTriangleBarycentrics();
+            ret.front_face = rq.CommittedTriangleFrontFace();
+        }
+        ret.object_to_world = rq.CommittedObjectToWorld4x3();
+        ret.world_to_object = rq.CommittedWorldToObject4x3();
+    }
+    return ret;
+}
+
+void main_1()
+{
+    RayQuery<RAY_FLAG_NONE> rq;
+
+    rq.TraceRayInline(acc_struct, ConstructRayDesc_(4u, 255u, 0.1, 100.0, float3(0.0, 0.0, 0.0), float3(0.0, 1.0, 0.0)).flags, ConstructRayDesc_(4u, 255u, 0.1, 100.0, float3(0.0, 0.0, 0.0), float3(0.0, 1.0, 0.0)).cull_mask, RayDescFromRayDesc_(ConstructRayDesc_(4u, 255u, 0.1, 100.0, float3(0.0, 0.0, 0.0), float3(0.0, 1.0, 0.0))));
+    while(true) {
+        const bool _e12 = rq.Proceed();
+        if (_e12) {
+            if ((((GetCandidateIntersection(rq).kind == 1u) ? 0u : 1u) == 0u)) {
+rq.Abort();
+            }
+            continue;
+        } else {
+            break;
+        }
+    }
+    output.Store(0, asuint(((GetCommittedIntersection(rq).kind == 0u) ? 1u : 0u)));
+    output.Store(4, asuint(GetCommittedIntersection(rq).t));
+    output.Store(8, asuint(GetCommittedIntersection(rq).instance_id));
+    output.Store(12, asuint((GetCommittedIntersection(rq).front_face ? 1u : 0u)));
+    output.Store2(16, asuint(GetCommittedIntersection(rq).barycentrics));
+    output.Store3(32, asuint(GetCommittedIntersection(rq).object_to_world[3]));
+    return;
+}
+
+[numthreads(1, 1, 1)]
+void main()
+{
+    main_1();
+}
@@ -0,0 +1,30 @@
+struct Data {
+    uint shifted;
+    uint picked;
+    int negated;
+    uint sum;
+};
+
+static const uint folded = 12u;
+static const uint a = 10u;
+static const int b = 3;
+static const bool flag = true;
+static const uint wg_x = 32u;
+
+RWByteAddressBuffer data : register(u0);
+
+void main_1()
+{
+    uint _e14 = (a + asuint(b));
+    data.Store(0, asuint((((_e14 > 4u) ? _e14 : 1u) << 2u)));
+    data.Store(4, asuint((false ? 12u : folded)));
+    data.Store(8, asuint(-3));
+    data.Store(12, asuint((_e14 + a)));
+    return;
+}
+
+[numthreads(32, 1, 1)]
+void main()
+{
+    main_1();
+}
@@ -0,0 +1,24 @@
+RWTexture2D<float> s_r_r : register(u0);
+RWTexture2D<float4> s_rgba_r : register(u1);
+RWTexture2D<float> s_r_rw : register(u2);
+RWTexture2D<float4> s_rgba_w : register(u3);
+
+[numthreads(1, 1, 1)]
+void csLoad(uint3 id : SV_GroupThreadID)
+{
+    int2 coords = int2(id.xy);
+    float4 a = s_r_r.Load(coords);
+    float4 b = s_rgba_r.Load(coords);
+    s_rgba_w[coords] = (a + b);
+    return;
+}
+
+[numthreads(1, 1, 1)]
+void csReadWrite(uint3 id_1 : SV_GroupThreadID)
+{
+    int2 coords_1 = int2(id_1.xy);
+    float4 value = s_r_rw.Load(coords_1);
+    DeviceMemoryBarrierWithGroupSync();
+    s_r_rw[coords_1] = (value * 2.0);
+    return;
+}
@@ -0,0 +1,22 @@
+RWByteAddressBuffer derivatives : register(u0);
+
+struct ComputeInput_main {
+    uint3 global_id_1 : SV_DispatchThreadID;
+};
+
+[numthreads(64, 1, 1)]
+void main(ComputeInput_main computeinput_main)
+{
+    uint3 global_id = computeinput_main.global_id_1;
+    uint subgroup_invocation_id = WaveGetLaneIndex();
+    float value = float((global_id.x * global_id.x));
+    const float _e6 = QuadReadAcrossX(value);
+    float dx = (_e6 - value);
+    const float _e8 = QuadReadAcrossY(value);
+    float dy = (_e8 - value);
+    const float _e10 = QuadReadAcrossDiagonal(value);
+    const float _e12 = QuadReadLaneAt(value, 0u);
+    const uint _e14 = QuadReadLaneAt(subgroup_invocation_id, 3u);
+    derivatives.Store2((global_id.x % 64u)*8, asuint((float2(dx, dy) + ((_e10 - _e12)).xx)));
+    return;
+}
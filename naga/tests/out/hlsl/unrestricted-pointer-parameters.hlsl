@@ -0,0 +1,107 @@
+struct Particle {
+    float4 position;
+    float4 velocity;
+};
+
+struct Particles {
+    uint count;
+    int _pad1_0;
+    int _pad1_1;
+    int _pad1_2;
+    Particle items[8];
+};
+
+struct Params {
+    float4 scale;
+    float4 offsets[4];
+};
+
+RWByteAddressBuffer particles : register(u0);
+ByteAddressBuffer source : register(t1);
+cbuffer params : register(b2) { Params params; }
+groupshared uint counter;
+groupshared uint scratch[64];
+
+uint local_sum(inout uint p[4])
+{
+    uint _e2 = p[0];
+    uint _e4 = p[1];
+    uint _e7 = p[2];
+    uint _e10 = p[3];
+    return (((_e2 + _e4) + _e7) + _e10);
+}
+
+uint read_count_source()
+{
+    uint _e2 = asuint(source.Load(0));
+    return _e2;
+}
+
+void advance_particles_params(uint p_index0_, uint offset_index0_)
+{
+    float4 _e10 = asfloat(particles.Load4(16+p_index0_*32+16));
+    float _e12 = params.offsets[offset_index0_].w;
+    float4 _e14 = params.offsets[offset_index0_];
+    float4 _e16 = asfloat(particles.Load4(0+p_index0_*32+16));
+    particles.Store4(0+p_index0_*32+16, asuint((_e16 + ((_e10 * _e12) + _e14))));
+    return;
+}
+
+void advance_all_particles(uint i)
+{
+    uint _e7 = (i % 4u);
+    advance_particles_params(i, _e7);
+    return;
+}
+
+void advance_particles_params_1()
+{
+    float4 _e8 = asfloat(particles.Load4(16+0+16));
+    float _e10 = params.offsets[1].w;
+    float4 _e12 = params.offsets[1];
+    float4 _e14 = asfloat(particles.Load4(0+0+16));
+    particles.Store4(0+0+16, asuint((_e14 + ((_e8 * _e10) + _e12))));
+    return;
+}
+
+uint bump_counter()
+{
+    uint _e2; InterlockedAdd(counter, 1u, _e2);
+    return _e2;
+}
+
+void store_scratch(uint index_1, uint value)
+{
+    scratch[min(uint(index_1), 63u)] = value;
+    return;
+}
+
+typedef uint ret_Constructarray4_uint_[4];
+ret_Constructarray4_uint_ Constructarray4_uint_(uint arg0, uint arg1, uint arg2, uint arg3) {
+    uint ret[4] = { arg0, arg1, arg2, arg3 };
+    return ret;
+}
+
+[numthreads(64, 1, 1)]
+void main(uint index : SV_GroupIndex, uint3 __local_invocation_id : SV_GroupThreadID)
+{
+    if (all(__local_invocation_id == uint3(0u, 0u, 0u))) {
+        counter = (uint)0;
+        scratch = (uint[64])0;
+    }
+    GroupMemoryBarrierWithGroupSync();
+    uint local[4] = Constructarray4_uint_(1u, 2u, 3u, 4u);
+
+    const uint _e8 = read_count_source();
+    const uint _e9 = local_sum(local);
+    uint n = (_e8 + _e9);
+    if ((index < n)) {
+        advance_all_particles(index);
+        advance_particles_params_1();
+    }
+    const uint _e20 = bump_counter();
+    store_scratch(_e20, index);
+    const uint _e25 = read_count_source();
+    particles.Store(0, asuint(_e25));
+    return;
+}
@@ -0,0 +1,85 @@
+struct Particle {
+    pos: vec4<f32>,
+    vel: vec4<f32>,
+}
+
+struct InBuf {
+    count: u32,
+    particles: array<Particle>,
+}
+
+struct OutBuf {
+    particles: array<Particle>,
+}
+
+struct Counter {
+    total: u32,
+}
+
+struct Params {
+    scale: f32,
+    offset: vec2<f32>,
+}
+
+@group(0) @binding(0) 
+var<storage> inBuf: InBuf;
+@group(0) @binding(1) 
+var<storage, read_write> outBuf: OutBuf;
+@group(0) @binding(2) 
+var<storage, read_write> global: Counter;
+@group(0) @binding(3) 
+var<storage> global_1: Params;
+var<workgroup> tile: array<vec4<f32>, 64>;
+var<workgroup> hits: u32;
+var<private> gl_LocalInvocationID_1: vec3<u32>;
+var<private> gl_GlobalInvocationID_1: vec3<u32>;
+
+fn main_1() {
+    var i: u32;
+
+    let _e17 = gl_LocalInvocationID_1;
+    i = _e17.x;
+    let _e20 = i;
+    if (_e20 == 0u) {
+        {
+            hits = 0u;
+        }
+    }
+    storageBarrier();
+    workgroupBarrier();
+    subgroupBarrier();
+    textureBarrier();
+    let _e25 = i;
+    let _e27 = gl_GlobalInvocationID_1;
+    let _e32 = inBuf.particles[_e27.x].pos;
+    let _e33 = global_1.scale;
+    tile[_e25] = (_e32 * _e33);
+    let _e35 = hits;
+    hits = (_e35 + 1u);
+    storageBarrier();
+    workgroupBarrier();
+    textureBarrier();
+    storageBarrier();
+    workgroupBarrier();
+    subgroupBarrier();
+    textureBarrier();
+    let _e38 = gl_GlobalInvocationID_1;
+    let _e44 = i;
+    let _e47 = tile[(63u - _e44)];
+    let _e48 = global_1.offset;
+    outBuf.particles[_e38.x].pos = (_e47 + vec4<f32>(_e48.x, _e48.y, 0f, 0f));
+    let _e55 = gl_GlobalInvocationID_1;
+    outBuf.particles[_e55.x].vel = vec4(f32(i32(arrayLength((&inBuf.particles)))));
+    let _e66 = inBuf.count;
+    let _e67 = hits;
+    global.total = (_e66 + _e67);
+    return;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(local_invocation_id) gl_LocalInvocationID: vec3<u32>, @builtin(global_invocation_id) gl_GlobalInvocationID: vec3<u32>) {
+    gl_LocalInvocationID_1 = gl_LocalInvocationID;
+    gl_GlobalInvocationID_1 = gl_GlobalInvocationID;
+    main_1();
+    return;
+}
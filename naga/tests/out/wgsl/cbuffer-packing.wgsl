@@ -0,0 +1,75 @@
+struct Light {
+    direction: vec3<f32>,
+    intensity: f32,
+    color: vec4<f32>,
+}
+
+struct Scene {
+    view_projection: mat4x4<f32>,
+    camera_position: vec3<f32>,
+    time: f32,
+    jitter: vec2<f32>,
+    resolution: vec2<f32>,
+    lights: array<Light, 2>,
+    exposure: f32,
+    model: mat4x3<f32>,
+    tint: vec3<f32>,
+}
+
+@group(0) @binding(0) 
+var<uniform> Scene_1: Scene;
+
+@vertex 
+fn vs_main(@location(0) position: vec3<f32>) -> @builtin(position) vec4<f32> {
+    var world: vec3<f32>;
+    var clip: vec4<f32>;
+
+    let _e5 = Scene_1.model;
+    world = (_e5 * vec4<f32>(position, 1f));
+    let _e10 = Scene_1.view_projection;
+    let _e12 = world;
+    let _e15 = Scene_1.camera_position;
+    clip = (vec4<f32>((_e12 - _e15), 1f) * transpose(_e10));
+    let _e21 = clip;
+    let _e24 = Scene_1.jitter;
+    let _e27 = Scene_1.resolution;
+    let _e34 = Scene_1.time;
+    return (_e21 + (vec4<f32>((_e24 / _e27), 0f, 0f) * vec4(_e34)));
+}
+
+@fragment 
+fn ps_main(@location(0) normal: vec3<f32>) -> @location(0) vec4<f32> {
+    var color: vec3<f32> = vec3(0f);
+    var i: i32 = 0i;
+    var light: f32;
+
+    loop {
+        let _e6 = i;
+        if (_e6 < 2i) {
+        } else {
+            break;
+        }
+        {
+            {
+                let _e11 = i;
+                let _e14 = Scene_1.lights[_e11].direction;
+                light = saturate(dot(normal, -(_e14)));
+                let _e21 = i;
+                let _e24 = Scene_1.lights[_e21].color;
+                let _e26 = light;
+                let _e31 = i;
+                let _e34 = Scene_1.lights[_e31].intensity;
+                let _e37 = color;
+                color = (_e37 + ((_e24.xyz * vec3(_e26)) * vec3(_e34)));
+            }
+        }
+        continuing {
+            let _e39 = i;
+            i = (_e39 + 1i);
+        }
+    }
+    let _e42 = color;
+    let _e45 = Scene_1.tint;
+    let _e49 = Scene_1.exposure;
+    return vec4<f32>(((_e42 * _e45) * vec3(_e49)), 1f);
+}
@@ -0,0 +1,18 @@
+struct Fog {
+    density: f32,
+    color_x: f32,
+    color_y: f32,
+    color_z: f32,
+}
+
+@group(0) @binding(0) 
+var<uniform> Fog_1: Fog;
+
+@fragment 
+fn main() -> @location(0) vec4<f32> {
+    let _e2 = Fog_1.color_x;
+    let _e4 = Fog_1.color_y;
+    let _e6 = Fog_1.color_z;
+    let _e10 = Fog_1.density;
+    return vec4<f32>(vec3<f32>(_e2, _e4, _e6), _e10);
+}
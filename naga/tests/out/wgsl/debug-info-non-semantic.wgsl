@@ -0,0 +1,18 @@
+struct Data {
+    a: f32,
+    b: f32,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> data: Data;
+
+fn main_1() {
+    let _e3 = data.a;
+    data.b = (_e3 * 2f);
+    return;
+}
+
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    main_1();
+}
@@ -0,0 +1,13 @@
+fn test_packed_integer_dot_product() -> u32 {
+    let c_signed = dot4I8Packed(1u, 2u);
+    let c_unsigned = dot4U8Packed(3u, 4u);
+    let c_signed_baked = dot4I8Packed((5u + c_unsigned), (6u + c_unsigned));
+    let c_unsigned_baked = dot4U8Packed((7u + c_unsigned), (8u + c_unsigned));
+    return c_unsigned_baked;
+}
+
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    let _e0 = test_packed_integer_dot_product();
+    return;
+}
@@ -1,6 +1,8 @@
+enable dual_source_blending;
+
 struct FragmentOutput {
-    @location(0) color: vec4<f32>,
-    @location(0) @second_blend_source mask: vec4<f32>,
+    @location(0) @blend_src(0) color: vec4<f32>,
+    @location(0) @blend_src(1) mask: vec4<f32>,
 }
 
 @fragment 
@@ -0,0 +1,31 @@
+@group(0) @binding(0) 
+var tex: texture_2d<f32>;
+@group(0) @binding(1) 
+var tex_depth: texture_depth_2d;
+@group(0) @binding(2) 
+var tex_depth_2_: texture_depth_2d;
+@group(0) @binding(3) 
+var samp: sampler;
+@group(0) @binding(4) 
+var samp_cmp: sampler_comparison;
+var<private> uv_1: vec2<f32>;
+var<private> color: vec4<f32>;
+
+fn main_1() {
+    let _e8 = uv_1;
+    let _e9 = textureGather(1, tex, samp, _e8);
+    let _e10 = textureGather(3, tex, samp, _e8, vec2<i32>(1i, -1i));
+    let _e11 = textureGatherCompare(tex_depth, samp_cmp, _e8, 0.5f);
+    let _e12 = textureGatherCompare(tex_depth, samp_cmp, _e8, 0.5f, vec2<i32>(1i, -1i));
+    let _e13 = textureGather(tex_depth_2_, samp, _e8);
+    color = ((((_e9 + _e10) + _e11) + _e12) + _e13);
+    return;
+}
+
+@fragment 
+fn main(@location(0) uv: vec2<f32>) -> @location(0) vec4<f32> {
+    uv_1 = uv;
+    main_1();
+    let _e3 = color;
+    return _e3;
+}
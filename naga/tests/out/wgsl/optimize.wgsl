@@ -0,0 +1,83 @@
+struct Params {
+    scale: f32,
+    offset: vec2<f32>,
+    count: u32,
+}
+
+@group(0) @binding(0) 
+var<uniform> params: Params;
+@group(0) @binding(1) 
+var<storage, read_write> output: array<vec4<f32>>;
+
+fn find_first(threshold: f32) -> u32 {
+    var i_1: u32 = 0u;
+
+    loop {
+        let _e3 = i_1;
+        let _e4 = params.count;
+        if (_e3 < _e4) {
+        } else {
+            break;
+        }
+        {
+            let _e6 = i_1;
+            let _e10 = output[_e6].x;
+            if (_e10 > threshold) {
+                let _e13 = i_1;
+                return _e13;
+            }
+        }
+        continuing {
+            let _e14 = i_1;
+            i_1 = (_e14 + 1u);
+        }
+    }
+    let _e19 = params.count;
+    return _e19;
+}
+
+fn unused(a: f32) -> f32 {
+    let b = (a * 2f);
+    return b;
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) id: vec3<u32>) {
+    var total: f32 = 0f;
+    var i: u32 = 0u;
+    var select_axis_result: f32;
+
+    total = 3f;
+    let _e10 = output[id.x].x;
+    let _e14 = output[id.x].y;
+    let a_1 = (_e10 + _e14);
+    loop {
+        let _e21 = i;
+        if (_e21 < 4u) {
+        } else {
+            break;
+        }
+        {
+            let _e24 = i;
+            let _e27 = total;
+            total = (_e27 + ((f32(id.y) * 0.5f) + f32(_e24)));
+        }
+        continuing {
+            let _e29 = i;
+            i = (_e29 + 1u);
+        }
+    }
+    let _e34 = params.scale;
+    let _e38 = params.offset;
+    let p = ((vec2<f32>(1f, 2f) * _e34) + _e38);
+    if (a_1 > 0f) {
+        select_axis_result = p.x;
+    } else {
+        select_axis_result = p.y;
+    }
+    let axis = select_axis_result;
+    let _e48 = find_first(axis);
+    let _e51 = total;
+    output[id.x] = vec4<f32>(p, _e51, f32(_e48));
+    return;
+}
@@ -0,0 +1,12 @@
+@compute @workgroup_size(1, 1, 1) 
+fn main() {
+    var i4_: vec4<i32> = vec4(0i);
+    var u: u32 = 0u;
+    var u4_: vec4<u32> = vec4(0u);
+
+    let _e8 = i4_;
+    u = pack4xI8Clamp(_e8);
+    let _e10 = u4_;
+    u = pack4xU8Clamp(_e10);
+    return;
+}
@@ -0,0 +1,42 @@
+struct Particle {
+    position: vec3<f32>,
+    velocity: vec3<f32>,
+}
+
+@group(0) @binding(0) 
+var<storage> masses: array<f32>;
+@group(0) @binding(1) 
+var<storage, read_write> particles: array<Particle>;
+@group(0) @binding(2) 
+var image: texture_storage_2d<rgba32float,write>;
+var<workgroup> shared_mass: array<f32, 64>;
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) id: vec3<u32>, @builtin(local_invocation_index) index: u32) {
+    var count: u32;
+    var stride: u32;
+    var p: Particle;
+
+    count = arrayLength((&particles));
+    stride = 32u;
+    let _e8 = count;
+    if (id.x >= _e8) {
+        {
+            return;
+        }
+    }
+    let _e15 = masses[id.x];
+    shared_mass[index] = _e15;
+    workgroupBarrier();
+    let _e19 = particles[id.x];
+    p = _e19;
+    let _e23 = p.velocity;
+    let _e26 = shared_mass[index];
+    let _e29 = p.position;
+    p.position = (_e29 + (_e23 * vec3(_e26)));
+    let _e34 = p;
+    particles[id.x] = _e34;
+    let _e39 = p.position;
+    textureStore(image, vec2<i32>(id.xy), vec4<f32>(_e39, 1f));
+    return;
+}
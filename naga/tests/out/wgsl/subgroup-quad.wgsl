@@ -0,0 +1,16 @@
+@group(0) @binding(0) 
+var<storage, read_write> derivatives: array<vec2<f32>, 64>;
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(global_invocation_id) global_id: vec3<u32>, @builtin(subgroup_invocation_id) subgroup_invocation_id: u32) {
+    let value = f32((global_id.x * global_id.x));
+    let _e6 = quadSwapX(value);
+    let dx = (_e6 - value);
+    let _e8 = quadSwapY(value);
+    let dy = (_e8 - value);
+    let _e10 = quadSwapDiagonal(value);
+    let _e12 = quadBroadcast(value, 0u);
+    let _e14 = quadBroadcast(subgroup_invocation_id, 3u);
+    derivatives[(global_id.x % 64u)] = (vec2<f32>(dx, dy) + vec2((_e10 - _e12)));
+    return;
+}
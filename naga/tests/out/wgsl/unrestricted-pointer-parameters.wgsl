@@ -0,0 +1,78 @@
+struct Particle {
+    position: vec4<f32>,
+    velocity: vec4<f32>,
+}
+
+struct Particles {
+    count: u32,
+    items: array<Particle, 8>,
+}
+
+struct Params {
+    scale: vec4<f32>,
+    offsets: array<vec4<f32>, 4>,
+}
+
+@group(0) @binding(0) 
+var<storage, read_write> particles: Particles;
+@group(0) @binding(1) 
+var<storage> source: Particles;
+@group(0) @binding(2) 
+var<uniform> params: Params;
+var<workgroup> counter: atomic<u32>;
+var<workgroup> scratch: array<u32, 64>;
+
+fn advance(p: ptr<storage, Particle, read_write>, offset: ptr<uniform, vec4<f32>>) {
+    let _e4 = (*p).velocity;
+    let _e6 = (*offset).w;
+    let _e8 = (*offset);
+    let _e10 = (*p).position;
+    (*p).position = (_e10 + ((_e4 * _e6) + _e8));
+    return;
+}
+
+fn read_count(p_1: ptr<storage, Particles>) -> u32 {
+    let _e2 = (*p_1).count;
+    return _e2;
+}
+
+fn advance_all(p_2: ptr<storage, Particles, read_write>, i: u32) {
+    advance((&(*p_2).items[i]), (&params.offsets[(i % 4u)]));
+    return;
+}
+
+fn bump(c: ptr<workgroup, atomic<u32>>) -> u32 {
+    let _e2 = atomicAdd(c, 1u);
+    return _e2;
+}
+
+fn store(slots: ptr<workgroup, array<u32, 64>>, index_1: u32, value: u32) {
+    (*slots)[index_1] = value;
+    return;
+}
+
+fn local_sum(p_3: ptr<function, array<u32, 4>>) -> u32 {
+    let _e2 = (*p_3)[0];
+    let _e4 = (*p_3)[1];
+    let _e7 = (*p_3)[2];
+    let _e10 = (*p_3)[3];
+    return (((_e2 + _e4) + _e7) + _e10);
+}
+
+@compute @workgroup_size(64, 1, 1) 
+fn main(@builtin(local_invocation_index) index: u32) {
+    var local: array<u32, 4> = array<u32, 4>(1u, 2u, 3u, 4u);
+
+    let _e8 = read_count((&source));
+    let _e9 = local_sum((&local));
+    let n = (_e8 + _e9);
+    if (index < n) {
+        advance_all((&particles), index);
+        advance((&particles.items[0]), (&params.offsets[1]));
+    }
+    let _e20 = bump((&counter));
+    store((&scratch), _e20, index);
+    let _e25 = read_count((&source));
+    particles.count = _e25;
+    return;
+}
@@ -1,4 +1,5 @@
 mod example_wgsl;
+mod interp;
 mod snapshots;
 mod spirv_capabilities;
 mod validation;
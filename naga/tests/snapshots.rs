@@ -89,6 +89,10 @@ struct Parameters {
     wgsl: WgslOutParameters,
 
     // -- General options --
+    /// Optimization passes to apply after validation, before compaction.
+    #[cfg(all(feature = "deserialize", feature = "opt"))]
+    optimize: Option<naga::opt::Options>,
+
     #[cfg(feature = "deserialize")]
     bounds_check_policies: naga::proc::BoundsCheckPolicies,
 
@@ -317,6 +321,26 @@ fn check_targets(
             );
         });
 
+    #[cfg(all(feature = "deserialize", feature = "opt"))]
+    let info = match params.optimize {
+        Some(ref options) => {
+            naga::opt::optimize(module, options);
+
+            naga::valid::Validator::new(validation_flags, capabilities)
+                .subgroup_stages(subgroup_stages)
+                .subgroup_operations(subgroup_operations)
+                .validate(module)
+                .unwrap_or_else(|err| {
+                    panic!(
+                        "Post-optimization module validation failed on test '{}':\n<{:?}",
+                        name.display(),
+                        err,
+                    )
+                })
+        }
+        None => info,
+    };
+
     #[cfg(feature = "compact")]
     let info = {
         naga::compact::compact(module);
@@ -477,6 +501,8 @@ fn write_output_spv(
     let (module, info) =
         naga::back::pipeline_constants::process_overrides(module, info, pipeline_constants)
             .expect("override evaluation failed");
+    let (module, info) = naga::back::pointer_parameters::specialize(&module, &info)
+        .expect("pointer parameter specialization failed");
 
     if params.separate_entry_points {
         for ep in module.entry_points.iter() {
@@ -583,6 +609,8 @@ fn write_output_glsl(
     let (module, info) =
         naga::back::pipeline_constants::process_overrides(module, info, pipeline_constants)
             .expect("override evaluation failed");
+    let (module, info) = naga::back::pointer_parameters::specialize(&module, &info)
+        .expect("pointer parameter specialization failed");
     let mut writer = glsl::Writer::new(
         &mut buffer,
         &module,
@@ -615,6 +643,8 @@ fn write_output_hlsl(
     let (module, info) =
         naga::back::pipeline_constants::process_overrides(module, info, pipeline_constants)
             .expect("override evaluation failed");
+    let (module, info) = naga::back::pointer_parameters::specialize(&module, &info)
+        .expect("pointer parameter specialization failed");
 
     let mut buffer = String::new();
     let mut writer = hlsl::Writer::new(&mut buffer, options);
@@ -745,6 +775,15 @@ fn convert_wgsl() {
             "fragment-output",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
         ),
+        (
+            "dot4-packed",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
+        ("dot4-packed-native", Targets::SPIRV | Targets::HLSL),
+        (
+            "pack4x8-clamp",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
         (
             "dualsource",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -774,7 +813,7 @@ fn convert_wgsl() {
         ),
         (
             "atomicCompareExchange",
-            Targets::SPIRV | Targets::METAL | Targets::WGSL,
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
         ),
         (
             "padding",
@@ -802,9 +841,21 @@ fn convert_wgsl() {
         ),
         (
             "atomicCompareExchange-int64",
-            Targets::SPIRV | Targets::WGSL,
+            Targets::SPIRV | Targets::HLSL | Targets::WGSL,
         ),
         ("pointers", Targets::SPIRV | Targets::WGSL),
+        (
+            "storage-textures",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
+        (
+            "optimize",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
+        (
+            "unrestricted-pointer-parameters",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
         (
             "control-flow",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -881,11 +932,14 @@ fn convert_wgsl() {
         ("invariant", Targets::GLSL),
         ("ray-query", Targets::SPIRV | Targets::METAL | Targets::HLSL),
         ("hlsl-keyword", Targets::HLSL),
+        ("hlsl-descriptor-heap", Targets::HLSL),
+        ("hlsl-descriptor-heap-buffer", Targets::HLSL),
         (
             "constructors",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
         ),
         ("msl-varyings", Targets::METAL),
+        ("msl-argument-buffers", Targets::METAL),
         (
             "const-exprs",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -896,6 +950,14 @@ fn convert_wgsl() {
             "struct-layout",
             Targets::WGSL | Targets::GLSL | Targets::SPIRV | Targets::HLSL | Targets::METAL,
         ),
+        (
+            "clip-distances",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
+        (
+            "f16",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
         (
             "f64",
             Targets::SPIRV | Targets::GLSL | Targets::HLSL | Targets::WGSL,
@@ -924,6 +986,10 @@ fn convert_wgsl() {
             "subgroup-operations",
             Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
         ),
+        (
+            "subgroup-quad",
+            Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+        ),
         (
             "overrides",
             Targets::IR
@@ -1099,6 +1165,44 @@ fn convert_spv_all() {
         false,
         Targets::IR | Targets::SPIRV | Targets::METAL | Targets::HLSL | Targets::WGSL,
     );
+    convert_spv(
+        "gather",
+        true,
+        Targets::METAL | Targets::GLSL | Targets::HLSL | Targets::WGSL,
+    );
+    convert_spv(
+        "ray-query-intersection",
+        false,
+        Targets::SPIRV | Targets::METAL | Targets::HLSL,
+    );
+    convert_spv("debug-info-line", false, Targets::WGSL);
+    convert_spv("debug-info-non-semantic", false, Targets::WGSL);
+    convert_spv(
+        "spec-constant-op",
+        false,
+        Targets::IR | Targets::SPIRV | Targets::METAL | Targets::GLSL | Targets::HLSL,
+    );
+}
+
+#[cfg(feature = "hlsl-in")]
+fn convert_hlsl(name: &str, targets: Targets) {
+    let _ = env_logger::try_init();
+
+    let input = Input::new(Some("hlsl"), name, "hlsl");
+    let source = input.read_source();
+    // Entry points are marked with `[shader(...)]` or `[numthreads(...)]`.
+    let mut module = naga::front::hlsl::Frontend::new()
+        .parse(&Default::default(), &source)
+        .unwrap_or_else(|error| panic!("{}", error.emit_to_string(&source)));
+    check_targets(&input, &mut module, targets, Some(&source), None);
+}
+
+#[cfg(feature = "hlsl-in")]
+#[test]
+fn convert_hlsl_all() {
+    convert_hlsl("cbuffer-packing", Targets::IR | Targets::WGSL);
+    convert_hlsl("cbuffer-scalarized", Targets::WGSL);
+    convert_hlsl("particles", Targets::WGSL);
 }
 
 #[cfg(feature = "glsl-in")]
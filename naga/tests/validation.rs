@@ -261,6 +261,122 @@ fn emit_workgroup_uniform_load_result() {
     assert!(variant(false).is_err());
 }
 
+/// The index of a quad broadcast must be a const-expression.
+#[test]
+fn quad_broadcast_index_must_be_const() {
+    use naga::{Module, Type, TypeInner};
+
+    fn variant(
+        const_index: bool,
+    ) -> Result<naga::valid::ModuleInfo, naga::WithSpan<naga::valid::ValidationError>> {
+        let span = naga::Span::default();
+        let mut module = Module::default();
+        let ty_u32 = module.types.insert(
+            Type {
+                name: Some("u32".into()),
+                inner: TypeInner::Scalar(Scalar::U32),
+            },
+            span,
+        );
+
+        let mut fun = Function::default();
+        fun.arguments.push(naga::FunctionArgument {
+            name: Some("lane".into()),
+            ty: ty_u32,
+            binding: None,
+        });
+        let ex_argument = fun
+            .expressions
+            .append(Expression::FunctionArgument(0), span);
+        let ex_index = if const_index {
+            fun.expressions
+                .append(Expression::Literal(naga::Literal::U32(1)), span)
+        } else {
+            ex_argument
+        };
+        let ex_result = fun
+            .expressions
+            .append(Expression::SubgroupOperationResult { ty: ty_u32 }, span);
+        fun.body.push(
+            naga::Statement::SubgroupGather {
+                mode: naga::GatherMode::QuadBroadcast(ex_index),
+                argument: ex_argument,
+                result: ex_result,
+            },
+            span,
+        );
+
+        module.functions.append(fun, span);
+
+        valid::Validator::new(
+            valid::ValidationFlags::default(),
+            valid::Capabilities::all(),
+        )
+        .validate(&module)
+    }
+
+    variant(true).expect("module should validate");
+    assert!(matches!(
+        variant(false).map_err(|error| error.into_inner()),
+        Err(valid::ValidationError::Function {
+            source: valid::FunctionError::InvalidSubgroup(_),
+            ..
+        })
+    ));
+}
+
+/// Validation errors in modules parsed from SPIR-V with debug information
+/// should point into the embedded source code.
+#[cfg(feature = "spv-in")]
+#[test]
+fn spirv_debug_info_error_location() {
+    /// Make the store in `bin` write to the whole storage buffer, rather
+    /// than its `b` member.
+    fn store_to_struct(bin: &[u8]) -> Vec<u8> {
+        let mut words: Vec<u32> = bin
+            .chunks(4)
+            .map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()))
+            .collect();
+        let mut variable = None;
+        let mut index = 5;
+        while index < words.len() {
+            let word_count = (words[index] >> 16) as usize;
+            match words[index] & 0xffff {
+                // OpVariable
+                59 => variable = Some(words[index + 2]),
+                // OpStore
+                62 => words[index + 1] = variable.unwrap(),
+                _ => {}
+            }
+            index += word_count;
+        }
+        words.iter().flat_map(|word| word.to_le_bytes()).collect()
+    }
+
+    let inputs: [&[u8]; 2] = [
+        include_bytes!("in/spv/debug-info-line.spv"),
+        include_bytes!("in/spv/debug-info-non-semantic.spv"),
+    ];
+    for bin in inputs {
+        let (module, source) = naga::front::spv::parse_u8_slice_with_source(
+            &store_to_struct(bin),
+            &Default::default(),
+        )
+        .unwrap();
+        let source = source.expect("module should embed its source");
+        let err = valid::Validator::new(Default::default(), valid::Capabilities::all())
+            .validate(&module)
+            .expect_err("module should be invalid");
+
+        // The store is `b = x * 2.0;`, on line 9 of `data.comp`.
+        let location = err.location(&source.source_code).unwrap();
+        assert_eq!((location.line_number, location.line_position), (9, 5));
+        assert!(err
+            .emit_to_string(&source.source_code)
+            .contains("9 │     b = x * 2.0;"));
+    }
+}
+
 #[cfg(feature = "wgsl-in")]
 #[test]
 fn bad_cross_builtin_args() {
@@ -436,6 +552,60 @@ fn no_flat_first_in_glsl() {
     ));
 }
 
+#[cfg(all(feature = "wgsl-in", feature = "msl-out"))]
+#[test]
+fn msl_argument_buffer_ids_do_not_overlap() {
+    use naga::back::msl;
+
+    let module = naga::front::wgsl::parse_str(
+        "
+        @group(0) @binding(0) var textures: binding_array<texture_2d<f32>, 4>;
+        @group(0) @binding(1) var tex: texture_2d<f32>;
+
+        @fragment
+        fn main() -> @location(0) vec4<f32> {
+            return textureLoad(textures[1], vec2(0), 0) + textureLoad(tex, vec2(0), 0);
+        }
+        ",
+    )
+    .unwrap();
+    let info = valid::Validator::new(valid::ValidationFlags::all(), valid::Capabilities::all())
+        .validate(&module)
+        .unwrap();
+
+    let write = |texture_id| {
+        let options = msl::Options {
+            lang_version: (2, 0),
+            per_entry_point_map: [(
+                "main".to_string(),
+                msl::EntryPointResources {
+                    argument_buffers: [(0, 0)].into_iter().collect(),
+                    ..Default::default()
+                },
+            )]
+            .into_iter()
+            .collect(),
+            argument_buffers: [(
+                0,
+                msl::ArgumentBufferLayout {
+                    ids: [(0, 0), (1, texture_id)].into_iter().collect(),
+                },
+            )]
+            .into_iter()
+            .collect(),
+            ..Default::default()
+        };
+        msl::write_string(&module, &info, &options, &Default::default())
+    };
+
+    // The binding array takes ids 0 to 3.
+    assert!(write(4).is_ok());
+    assert!(matches!(
+        write(3),
+        Err(msl::Error::OverlappingArgumentBufferIds(..))
+    ));
+}
+
 #[cfg(all(test, feature = "wgsl-in"))]
 mod dummy_interpolation_shader {
     pub struct DummyInterpolationShader {
@@ -685,3 +855,114 @@ error: Entry point main at Compute is invalid
         assert_eq!(err.emit_to_string(source), expected_err);
     }
 }
+
+#[cfg(feature = "wgsl-in")]
+#[test]
+fn required_capabilities() {
+    use valid::{Capabilities, ShaderStages, SubgroupOperationSet};
+
+    let source = "
+        @group(0) @binding(0) var<storage, read_write> values: array<f64>;
+        @group(0) @binding(1) var cubes: texture_cube_array<f32>;
+
+        fn sum(x: u32) -> u32 {
+            return subgroupAdd(x);
+        }
+
+        @vertex
+        fn vertex() -> @builtin(position) vec4<f32> {
+            return vec4(f32(sum(1u)));
+        }
+
+        @compute @workgroup_size(64)
+        fn compute(@builtin(local_invocation_index) index: u32) {
+            values[index] = f64(quadBroadcast(index, 0u));
+        }
+    ";
+    let module = naga::front::wgsl::parse_str(source).unwrap();
+    let info = valid::Validator::new(valid::ValidationFlags::all(), Capabilities::all())
+        .validate(&module)
+        .unwrap();
+    let requirements = valid::required_capabilities(&module, &info);
+
+    assert_eq!(
+        requirements.capabilities,
+        Capabilities::FLOAT64
+            | Capabilities::CUBE_ARRAY_TEXTURES
+            | Capabilities::SUBGROUP
+            | Capabilities::SUBGROUP_VERTEX_STAGE
+            | Capabilities::SUBGROUP_QUAD
+    );
+    assert_eq!(
+        requirements.subgroup_stages,
+        ShaderStages::VERTEX | ShaderStages::COMPUTE
+    );
+    assert_eq!(
+        requirements.subgroup_operations,
+        SubgroupOperationSet::ARITHMETIC | SubgroupOperationSet::QUAD_FRAGMENT_COMPUTE
+    );
+
+    let text_of = |capability| {
+        let use_ = requirements
+            .uses
+            .iter()
+            .find(|use_| use_.capabilities.contains(capability) && use_.span.is_defined())
+            .unwrap();
+        &source[use_.span.to_range().unwrap()]
+    };
+    assert_eq!(
+        text_of(Capabilities::FLOAT64),
+        "f64(quadBroadcast(index, 0u))"
+    );
+    assert_eq!(
+        text_of(Capabilities::CUBE_ARRAY_TEXTURES),
+        "var cubes: texture_cube_array<f32>;"
+    );
+    assert_eq!(text_of(Capabilities::SUBGROUP), "subgroupAdd");
+    assert_eq!(text_of(Capabilities::SUBGROUP_VERTEX_STAGE), "sum(1u)");
+    assert_eq!(text_of(Capabilities::SUBGROUP_QUAD), "quadBroadcast");
+}
+
+/// The capabilities [`valid::required_capabilities`] reports for the WGSL
+/// snapshot inputs should be enough to validate them, and each of them
+/// should be needed.
+#[cfg(feature = "wgsl-in")]
+#[test]
+fn required_capabilities_are_minimal() {
+    use valid::{Capabilities, ValidationFlags, Validator};
+
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/in");
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().map_or(true, |ext| ext != "wgsl") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&path).unwrap();
+        let Ok(module) = naga::front::wgsl::parse_str(&source) else {
+            continue;
+        };
+        let Ok(info) =
+            Validator::new(ValidationFlags::all(), Capabilities::all()).validate(&module)
+        else {
+            continue;
+        };
+        let required = valid::required_capabilities(&module, &info).capabilities;
+
+        if let Err(error) = Validator::new(ValidationFlags::all(), required).validate(&module) {
+            panic!(
+                "{} doesn't validate with {required:?}:\n{}",
+                path.display(),
+                error.emit_to_string(&source)
+            );
+        }
+        for capability in required.iter() {
+            assert!(
+                Validator::new(ValidationFlags::all(), required - capability)
+                    .validate(&module)
+                    .is_err(),
+                "{} validates without {capability:?}",
+                path.display()
+            );
+        }
+    }
+}
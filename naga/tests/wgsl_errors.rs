@@ -122,6 +122,20 @@ fn unknown_identifier() {
     );
 }
 
+#[test]
+fn error_codes() {
+    #[track_caller]
+    fn check_code(input: &str, code: &str) {
+        let error = naga::front::wgsl::parse_str(input).expect_err("expected parser error");
+        assert_eq!(error.code(), code);
+    }
+
+    check_code("fn f() -> f32 { return schmoo; }", "unknown-ident");
+    check_code("fn f( {", "unexpected-token");
+    check_code("const i32min = -0x80000000i;", "bad-number");
+    check_code("const a = 1; const a = 2;", "redefinition");
+}
+
 #[test]
 fn bad_texture() {
     check(
@@ -590,11 +604,11 @@ fn postfix_pointers() {
                 let a = *pv[3]; // Problematic line
             }
         "#,
-        r#"error: the value indexed by a `[]` subscripting expression must not be a pointer
-  ┌─ wgsl:5:26
+        r#"error: the operand of the `*` operator must be a pointer
+  ┌─ wgsl:5:25
   │
 5 │                 let a = *pv[3]; // Problematic line
-  │                          ^^ expression is a pointer
+  │                         ^^^^^^ expression is not a pointer
 
 "#,
     );
@@ -608,11 +622,11 @@ fn postfix_pointers() {
                 let a = *ps.m; // Problematic line
             }
         "#,
-        r#"error: the value accessed by a `.member` expression must not be a pointer
-  ┌─ wgsl:6:26
+        r#"error: the operand of the `*` operator must be a pointer
+  ┌─ wgsl:6:25
   │
 6 │                 let a = *ps.m; // Problematic line
-  │                          ^^ expression is a pointer
+  │                         ^^^^^ expression is not a pointer
 
 "#,
     );
@@ -966,6 +980,35 @@ fn int64_capability() {
     }
 }
 
+#[test]
+fn quad_capability() {
+    check_validation! {
+        "fn f(x: f32) -> f32 { return quadSwapX(x); }",
+        "fn f(x: f32) -> f32 { return quadBroadcast(x, 1u); }":
+        Err(naga::valid::ValidationError::Function {
+            source: naga::valid::FunctionError::MissingCapability(
+                naga::valid::Capabilities::SUBGROUP_QUAD
+            ),
+            ..
+        }),
+        naga::valid::Capabilities::SUBGROUP
+    }
+}
+
+#[test]
+fn quad_broadcast_dynamic_index() {
+    check(
+        "fn f(x: f32, i: u32) -> f32 { return quadBroadcast(x, i); }",
+        r###"error: must be a const-expression that resolves to a concrete integer scalar (`u32` or `i32`)
+  ┌─ wgsl:1:55
+  │
+1 │ fn f(x: f32, i: u32) -> f32 { return quadBroadcast(x, i); }
+  │                                                       ^ must resolve to `u32` or `i32`
+
+"###,
+    );
+}
+
 #[test]
 fn invalid_arrays() {
     check_validation! {
@@ -1151,41 +1194,24 @@ fn invalid_functions() {
         })
     }
 
-    // Pointers of these address spaces cannot be passed as arguments.
-    check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<storage, array<f32>>) { }":
-        Err(naga::valid::ValidationError::Function {
-            name: function_name,
-            source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
-                index: 0,
-                name: argument_name,
-                space: naga::AddressSpace::Storage { .. },
-            },
-            ..
-        })
-        if function_name == "unacceptable_ptr_space" && argument_name == "arg"
-    }
+    // Pointers of these address spaces can be passed as arguments, thanks to
+    // the `unrestricted_pointer_parameters` language extension.
     check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<uniform, f32>) { }":
-        Err(naga::valid::ValidationError::Function {
-            name: function_name,
-            source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
-                index: 0,
-                name: argument_name,
-                space: naga::AddressSpace::Uniform,
-            },
-            ..
-        })
-        if function_name == "unacceptable_ptr_space" && argument_name == "arg"
+        "fn acceptable_ptr_space(arg: ptr<storage, array<f32>>) { }",
+        "fn acceptable_ptr_space(arg: ptr<uniform, f32>) { }",
+        "fn acceptable_ptr_space(arg: ptr<workgroup, f32>) { }":
+        Ok(_)
     }
+
+    // Pointers of this address space cannot be passed as arguments.
     check_validation! {
-        "fn unacceptable_ptr_space(arg: ptr<workgroup, f32>) { }":
+        "fn unacceptable_ptr_space(arg: ptr<push_constant, f32>) { }":
         Err(naga::valid::ValidationError::Function {
             name: function_name,
             source: naga::valid::FunctionError::InvalidArgumentPointerSpace {
                 index: 0,
                 name: argument_name,
-                space: naga::AddressSpace::WorkGroup,
+                space: naga::AddressSpace::PushConstant,
             },
             ..
         })
@@ -2446,3 +2472,115 @@ fn reject_utf8_bom() {
 "#,
     );
 }
+
+#[test]
+fn f16_requires_enable() {
+    check(
+        "const a: f16 = 1.0h;",
+        r###"error: `f16` enable-extension is not enabled
+  ┌─ wgsl:1:10
+  │
+1 │ const a: f16 = 1.0h;
+  │          ^^^ the `f16` enable-extension is needed for this functionality, but it is not currently enabled
+
+"###,
+    );
+
+    check(
+        "const a = 1.0h;",
+        r###"error: `f16` enable-extension is not enabled
+  ┌─ wgsl:1:11
+  │
+1 │ const a = 1.0h;
+  │           ^^^^ the `f16` enable-extension is needed for this functionality, but it is not currently enabled
+
+"###,
+    );
+}
+
+#[test]
+fn clip_distances_requires_enable() {
+    check(
+        r#"
+        struct VertexOutput {
+            @builtin(position) position: vec4<f32>,
+            @builtin(clip_distances) clip_distances: array<f32, 1>,
+        }
+        "#,
+        r###"error: `clip_distances` enable-extension is not enabled
+  ┌─ wgsl:4:22
+  │
+4 │             @builtin(clip_distances) clip_distances: array<f32, 1>,
+  │                      ^^^^^^^^^^^^^^ the `clip_distances` enable-extension is needed for this functionality, but it is not currently enabled
+
+"###,
+    );
+}
+
+#[test]
+fn blend_src_requires_enable() {
+    check(
+        r#"
+        struct FragmentOutput {
+            @location(0) @blend_src(0) color: vec4<f32>,
+            @location(0) @blend_src(1) mask: vec4<f32>,
+        }
+        "#,
+        r###"error: `dual_source_blending` enable-extension is not enabled
+  ┌─ wgsl:3:27
+  │
+3 │             @location(0) @blend_src(0) color: vec4<f32>,
+  │                           ^^^^^^^^^ the `dual_source_blending` enable-extension is needed for this functionality, but it is not currently enabled
+
+"###,
+    );
+}
+
+#[test]
+fn blend_src_out_of_range() {
+    check(
+        r#"
+        enable dual_source_blending;
+
+        struct FragmentOutput {
+            @location(0) @blend_src(0) color: vec4<f32>,
+            @location(0) @blend_src(2) mask: vec4<f32>,
+        }
+        "#,
+        r###"error: `blend_src` must be either 0 or 1
+  ┌─ wgsl:6:37
+  │
+6 │             @location(0) @blend_src(2) mask: vec4<f32>,
+  │                                     ^ must be either 0 or 1
+
+"###,
+    );
+}
+
+#[test]
+fn too_many_clip_distances() {
+    check_validation! {
+        r#"
+        enable clip_distances;
+
+        struct VertexOutput {
+            @builtin(position) position: vec4<f32>,
+            @builtin(clip_distances) clip_distances: array<f32, 9>,
+        }
+
+        @vertex
+        fn main() -> VertexOutput {
+            var out: VertexOutput;
+            return out;
+        }
+        "#:
+        Err(naga::valid::ValidationError::EntryPoint {
+            stage: naga::ShaderStage::Vertex,
+            source: naga::valid::EntryPointError::Result(
+                naga::valid::VaryingError::TooManyClipDistances(9),
+            ),
+            ..
+        }),
+        naga::valid::Capabilities::CLIP_DISTANCE
+    }
+}
@@ -47,3 +47,25 @@ static SHADER_COMPILE_ERROR: GpuTestConfiguration = GpuTestConfiguration::new()
             "Expected the column number to be 33, because we're counting lines from 1"
         );
     });
+
+#[gpu_test]
+static SHADER_COMPILE_WARNING: GpuTestConfiguration = GpuTestConfiguration::new()
+    .parameters(TestParameters::default())
+    .run_async(|ctx| async move {
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        let sm = ctx
+            .device
+            .create_shader_module(include_wgsl!("warning_shader.wgsl"));
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        let compilation_info = sm.get_compilation_info().await;
+        let warning_message = compilation_info
+            .messages
+            .iter()
+            .find(|message| message.message_type == wgpu::CompilationMessageType::Warning)
+            .expect("Expected warning message not found");
+        let span = warning_message.location.expect("Expected span not found");
+        assert_eq!(span.line_number, 3);
+        assert_eq!(span.line_position, 9);
+        assert_eq!(span.length, 6);
+    });
@@ -906,6 +906,10 @@ impl Global {
                     pipeline::ShaderModuleSource::Glsl(ref code, _) => {
                         trace.make_binary("glsl", code.as_bytes())
                     }
+                    #[cfg(feature = "hlsl")]
+                    pipeline::ShaderModuleSource::Hlsl(ref code, _) => {
+                        trace.make_binary("hlsl", code.as_bytes())
+                    }
                     #[cfg(feature = "spirv")]
                     pipeline::ShaderModuleSource::SpirV(ref code, _) => {
                         trace.make_binary("spirv", bytemuck::cast_slice::<u32, u8>(code))
@@ -990,6 +994,21 @@ impl Global {
         (id, Some(error))
     }
 
+    /// Returns the diagnostics reported while creating the shader module that
+    /// didn't make it fail, like warnings.
+    ///
+    /// Invalid shader modules have none, their errors are reported on creation.
+    pub fn shader_module_diagnostics(
+        &self,
+        shader_module_id: id::ShaderModuleId,
+    ) -> Vec<pipeline::ShaderModuleDiagnostic> {
+        let hub = &self.hub;
+        match hub.shader_modules.get(shader_module_id).get() {
+            Ok(shader_module) => shader_module.diagnostics.clone(),
+            Err(_) => Vec::new(),
+        }
+    }
+
     pub fn shader_module_drop(&self, shader_module_id: id::ShaderModuleId) {
         profiling::scope!("ShaderModule::drop");
         api_log!("ShaderModule::drop {shader_module_id:?}");
@@ -369,102 +369,193 @@ impl ImplicitPipelineIds<'_> {
     }
 }
 
+/// The device feature or downlevel flag that enables each shader capability.
+///
+/// A capability with more than one row is enabled by any of them, and
+/// [`required_features`] asks for the first.
+const CAPABILITY_FEATURES: &[(
+    naga::valid::Capabilities,
+    wgt::Features,
+    wgt::DownlevelFlags,
+)] = {
+    use naga::valid::Capabilities as Caps;
+    use wgt::{DownlevelFlags as Dl, Features as F};
+    &[
+        (Caps::PUSH_CONSTANT, F::PUSH_CONSTANTS, Dl::empty()),
+        (Caps::FLOAT64, F::SHADER_F64, Dl::empty()),
+        (Caps::SHADER_FLOAT16, F::SHADER_F16, Dl::empty()),
+        (
+            Caps::PRIMITIVE_INDEX,
+            F::SHADER_PRIMITIVE_INDEX,
+            Dl::empty(),
+        ),
+        (
+            Caps::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            F::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            Dl::empty(),
+        ),
+        (
+            Caps::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            F::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
+            Dl::empty(),
+        ),
+        // TODO: This needs a proper wgpu feature
+        (
+            Caps::SAMPLER_NON_UNIFORM_INDEXING,
+            F::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
+            Dl::empty(),
+        ),
+        (
+            Caps::STORAGE_TEXTURE_16BIT_NORM_FORMATS,
+            F::TEXTURE_FORMAT_16BIT_NORM,
+            Dl::empty(),
+        ),
+        (Caps::MULTIVIEW, F::MULTIVIEW, Dl::empty()),
+        (
+            Caps::EARLY_DEPTH_TEST,
+            F::SHADER_EARLY_DEPTH_TEST,
+            Dl::empty(),
+        ),
+        (Caps::SHADER_INT64, F::SHADER_INT64, Dl::empty()),
+        (
+            Caps::SHADER_INT64_ATOMIC_MIN_MAX,
+            F::SHADER_INT64_ATOMIC_MIN_MAX,
+            Dl::empty(),
+        ),
+        (
+            Caps::SHADER_INT64_ATOMIC_MIN_MAX,
+            F::SHADER_INT64_ATOMIC_ALL_OPS,
+            Dl::empty(),
+        ),
+        (
+            Caps::SHADER_INT64_ATOMIC_ALL_OPS,
+            F::SHADER_INT64_ATOMIC_ALL_OPS,
+            Dl::empty(),
+        ),
+        (Caps::TEXTURE_ATOMIC, F::TEXTURE_ATOMIC, Dl::empty()),
+        (
+            Caps::TEXTURE_INT64_ATOMIC,
+            F::TEXTURE_INT64_ATOMIC,
+            Dl::empty(),
+        ),
+        (
+            Caps::SHADER_FLOAT32_ATOMIC,
+            F::SHADER_FLOAT32_ATOMIC,
+            Dl::empty(),
+        ),
+        (
+            Caps::MULTISAMPLED_SHADING,
+            F::empty(),
+            Dl::MULTISAMPLED_SHADING,
+        ),
+        (
+            Caps::DUAL_SOURCE_BLENDING,
+            F::DUAL_SOURCE_BLENDING,
+            Dl::empty(),
+        ),
+        (Caps::CLIP_DISTANCE, F::CLIP_DISTANCES, Dl::empty()),
+        (
+            Caps::CUBE_ARRAY_TEXTURES,
+            F::empty(),
+            Dl::CUBE_ARRAY_TEXTURES,
+        ),
+        (Caps::SUBGROUP, F::SUBGROUP, Dl::empty()),
+        (Caps::SUBGROUP, F::SUBGROUP_VERTEX, Dl::empty()),
+        (Caps::SUBGROUP_QUAD, F::SUBGROUP_QUAD, Dl::empty()),
+        (Caps::SUBGROUP_BARRIER, F::SUBGROUP_BARRIER, Dl::empty()),
+        (Caps::RAY_QUERY, F::EXPERIMENTAL_RAY_QUERY, Dl::empty()),
+        (Caps::SUBGROUP_VERTEX_STAGE, F::SUBGROUP_VERTEX, Dl::empty()),
+    ]
+};
+
+/// Returns the shader capabilities that `features` and `downlevel` enable.
+fn capabilities(
+    features: wgt::Features,
+    downlevel: wgt::DownlevelFlags,
+) -> naga::valid::Capabilities {
+    CAPABILITY_FEATURES
+        .iter()
+        .filter(|&&(_, feature, downlevel_flag)| {
+            features.intersects(feature) || downlevel.intersects(downlevel_flag)
+        })
+        .fold(naga::valid::Capabilities::empty(), |caps, &(cap, ..)| {
+            caps | cap
+        })
+}
+
 /// Create a validator with the given validation flags.
 pub fn create_validator(
     features: wgt::Features,
     downlevel: wgt::DownlevelFlags,
     flags: naga::valid::ValidationFlags,
 ) -> naga::valid::Validator {
-    use naga::valid::Capabilities as Caps;
-    let mut caps = Caps::empty();
-    caps.set(
-        Caps::PUSH_CONSTANT,
-        features.contains(wgt::Features::PUSH_CONSTANTS),
-    );
-    caps.set(Caps::FLOAT64, features.contains(wgt::Features::SHADER_F64));
-    caps.set(
-        Caps::PRIMITIVE_INDEX,
-        features.contains(wgt::Features::SHADER_PRIMITIVE_INDEX),
-    );
-    caps.set(
-        Caps::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING,
-        features
-            .contains(wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING),
-    );
-    caps.set(
-        Caps::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING,
-        features
-            .contains(wgt::Features::UNIFORM_BUFFER_AND_STORAGE_TEXTURE_ARRAY_NON_UNIFORM_INDEXING),
-    );
-    // TODO: This needs a proper wgpu feature
-    caps.set(
-        Caps::SAMPLER_NON_UNIFORM_INDEXING,
-        features
-            .contains(wgt::Features::SAMPLED_TEXTURE_AND_STORAGE_BUFFER_ARRAY_NON_UNIFORM_INDEXING),
-    );
-    caps.set(
-        Caps::STORAGE_TEXTURE_16BIT_NORM_FORMATS,
-        features.contains(wgt::Features::TEXTURE_FORMAT_16BIT_NORM),
-    );
-    caps.set(Caps::MULTIVIEW, features.contains(wgt::Features::MULTIVIEW));
-    caps.set(
-        Caps::EARLY_DEPTH_TEST,
-        features.contains(wgt::Features::SHADER_EARLY_DEPTH_TEST),
-    );
-    caps.set(
-        Caps::SHADER_INT64,
-        features.contains(wgt::Features::SHADER_INT64),
-    );
-    caps.set(
-        Caps::SHADER_INT64_ATOMIC_MIN_MAX,
-        features.intersects(
-            wgt::Features::SHADER_INT64_ATOMIC_MIN_MAX | wgt::Features::SHADER_INT64_ATOMIC_ALL_OPS,
-        ),
-    );
-    caps.set(
-        Caps::SHADER_INT64_ATOMIC_ALL_OPS,
-        features.contains(wgt::Features::SHADER_INT64_ATOMIC_ALL_OPS),
-    );
-    caps.set(
-        Caps::TEXTURE_ATOMIC,
-        features.contains(wgt::Features::TEXTURE_ATOMIC),
-    );
-    caps.set(
-        Caps::TEXTURE_INT64_ATOMIC,
-        features.contains(wgt::Features::TEXTURE_INT64_ATOMIC),
-    );
-    caps.set(
-        Caps::SHADER_FLOAT32_ATOMIC,
-        features.contains(wgt::Features::SHADER_FLOAT32_ATOMIC),
-    );
-    caps.set(
-        Caps::MULTISAMPLED_SHADING,
-        downlevel.contains(wgt::DownlevelFlags::MULTISAMPLED_SHADING),
-    );
-    caps.set(
-        Caps::DUAL_SOURCE_BLENDING,
-        features.contains(wgt::Features::DUAL_SOURCE_BLENDING),
-    );
-    caps.set(
-        Caps::CUBE_ARRAY_TEXTURES,
-        downlevel.contains(wgt::DownlevelFlags::CUBE_ARRAY_TEXTURES),
-    );
-    caps.set(
-        Caps::SUBGROUP,
-        features.intersects(wgt::Features::SUBGROUP | wgt::Features::SUBGROUP_VERTEX),
-    );
-    caps.set(
-        Caps::SUBGROUP_BARRIER,
-        features.intersects(wgt::Features::SUBGROUP_BARRIER),
-    );
-    caps.set(
-        Caps::RAY_QUERY,
-        features.intersects(wgt::Features::EXPERIMENTAL_RAY_QUERY),
-    );
-    caps.set(
-        Caps::SUBGROUP_VERTEX_STAGE,
-        features.contains(wgt::Features::SUBGROUP_VERTEX),
-    );
-
-    naga::valid::Validator::new(flags, caps)
+    naga::valid::Validator::new(flags, capabilities(features, downlevel))
+}
+
+/// The features and downlevel flags a device needs to support a set of
+/// shader capabilities, as returned by [`required_features`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RequiredFeatures {
+    pub features: wgt::Features,
+    pub downlevel_flags: wgt::DownlevelFlags,
+    /// Capabilities that no feature or downlevel flag enables, so shaders
+    /// that need them can't be used at all.
+    pub unsupported: naga::valid::Capabilities,
+}
+
+/// Returns what [`create_validator`] needs to allow `capabilities`.
+///
+/// Pass this the capabilities from [`naga::valid::required_capabilities`] to
+/// check a shader against a device's features before creating it.
+pub fn required_features(capabilities: naga::valid::Capabilities) -> RequiredFeatures {
+    let mut required = RequiredFeatures {
+        features: wgt::Features::empty(),
+        downlevel_flags: wgt::DownlevelFlags::empty(),
+        unsupported: naga::valid::Capabilities::empty(),
+    };
+    for cap in capabilities.iter() {
+        match CAPABILITY_FEATURES.iter().find(|&&(c, ..)| c == cap) {
+            Some(&(_, feature, downlevel_flag)) => {
+                required.features |= feature;
+                required.downlevel_flags |= downlevel_flag;
+            }
+            None => required.unsupported |= cap,
+        }
+    }
+    required
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{capabilities, required_features};
+    use naga::valid::Capabilities;
+
+    #[test]
+    fn required_features_round_trip() {
+        let supported = capabilities(wgt::Features::all(), wgt::DownlevelFlags::all());
+        for cap in Capabilities::all().iter() {
+            let required = required_features(cap);
+            if supported.contains(cap) {
+                assert!(required.unsupported.is_empty(), "{cap:?}");
+                assert!(
+                    capabilities(required.features, required.downlevel_flags).contains(cap),
+                    "{cap:?} is not enabled by {required:?}"
+                );
+            } else {
+                assert_eq!(required.unsupported, cap);
+                assert!(required.features.is_empty() && required.downlevel_flags.is_empty());
+            }
+        }
+
+        // The features `required_features` asks for enable at least what the
+        // features they stand for do.
+        for feature in wgt::Features::all().iter() {
+            let enabled = capabilities(feature, wgt::DownlevelFlags::empty());
+            let required = required_features(enabled);
+            assert!(
+                capabilities(required.features, required.downlevel_flags).contains(enabled),
+                "{feature:?}"
+            );
+        }
+    }
 }
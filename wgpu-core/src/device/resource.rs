@@ -1477,17 +1477,20 @@ impl Device {
     ) -> Result<Arc<pipeline::ShaderModule>, pipeline::CreateShaderModuleError> {
         self.check_is_valid()?;
 
+        let mut diagnostics = Vec::new();
         let (module, source) = match source {
             #[cfg(feature = "wgsl")]
             pipeline::ShaderModuleSource::Wgsl(code) => {
-                profiling::scope!("naga::front::wgsl::parse_str");
-                let module = naga::front::wgsl::parse_str(&code).map_err(|inner| {
+                profiling::scope!("naga::front::wgsl::Frontend.parse");
+                let mut frontend = naga::front::wgsl::Frontend::new();
+                let module = frontend.parse(&code).map_err(|inner| {
                     pipeline::CreateShaderModuleError::Parsing(naga::error::ShaderError {
                         source: code.to_string(),
                         label: desc.label.as_ref().map(|l| l.to_string()),
                         inner: Box::new(inner),
                     })
                 })?;
+                diagnostics.extend(frontend.diagnostics().iter().cloned());
                 (Cow::Owned(module), code.into_owned())
             }
             #[cfg(feature = "spirv")]
@@ -1516,6 +1519,19 @@ impl Device {
                 })?;
                 (Cow::Owned(module), code.into_owned())
             }
+            #[cfg(feature = "hlsl")]
+            pipeline::ShaderModuleSource::Hlsl(code, options) => {
+                let mut parser = naga::front::hlsl::Frontend::new();
+                profiling::scope!("naga::front::hlsl::Frontend.parse");
+                let module = parser.parse(&options, &code).map_err(|inner| {
+                    pipeline::CreateShaderModuleError::ParsingHlsl(naga::error::ShaderError {
+                        source: code.to_string(),
+                        label: desc.label.as_ref().map(|l| l.to_string()),
+                        inner: Box::new(inner),
+                    })
+                })?;
+                (Cow::Owned(module), code.into_owned())
+            }
             pipeline::ShaderModuleSource::Naga(module) => (module, String::new()),
             pipeline::ShaderModuleSource::Dummy(_) => panic!("found `ShaderModuleSource::Dummy`"),
         };
@@ -1552,14 +1568,29 @@ impl Device {
             self.downlevel.flags,
             naga::valid::ValidationFlags::all(),
         )
-        .validate(&module)
-        .map_err(|inner| {
-            pipeline::CreateShaderModuleError::Validation(naga::error::ShaderError {
-                source,
-                label: desc.label.as_ref().map(|l| l.to_string()),
-                inner: Box::new(inner),
-            })
-        })?;
+        .validate(&module);
+        let info = match info {
+            Ok(info) => info,
+            Err(inner) => {
+                return Err(pipeline::CreateShaderModuleError::Validation(
+                    naga::error::ShaderError {
+                        source,
+                        label: desc.label.as_ref().map(|l| l.to_string()),
+                        inner: Box::new(inner),
+                    },
+                ))
+            }
+        };
+        diagnostics.extend(info.diagnostics().cloned());
+
+        let label = desc.label.as_deref().unwrap_or("shader");
+        let diagnostics = diagnostics
+            .iter()
+            .map(|diagnostic| pipeline::ShaderModuleDiagnostic::new(diagnostic, &source, label))
+            .collect::<Vec<_>>();
+        for diagnostic in diagnostics.iter() {
+            log::debug!("Shader diagnostic: {}", diagnostic.message);
+        }
 
         let interface = validation::Interface::new(&module, &info, self.limits.clone());
         let hal_shader = hal::ShaderInput::Naga(hal::NagaShader {
@@ -1590,6 +1621,7 @@ impl Device {
             raw: ManuallyDrop::new(raw),
             device: self.clone(),
             interface: Some(interface),
+            diagnostics,
             label: desc.label.to_string(),
         };
 
@@ -1631,6 +1663,7 @@ impl Device {
             raw: ManuallyDrop::new(raw),
             device: self.clone(),
             interface: None,
+            diagnostics: Vec::new(),
             label: desc.label.to_string(),
         };
 
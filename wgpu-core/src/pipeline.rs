@@ -27,6 +27,8 @@ pub enum ShaderModuleSource<'a> {
     Wgsl(Cow<'a, str>),
     #[cfg(feature = "glsl")]
     Glsl(Cow<'a, str>, naga::front::glsl::Options),
+    #[cfg(feature = "hlsl")]
+    Hlsl(Cow<'a, str>, naga::front::hlsl::Options),
     #[cfg(feature = "spirv")]
     SpirV(Cow<'a, [u32]>, naga::front::spv::Options),
     Naga(Cow<'static, naga::Module>),
@@ -44,11 +46,50 @@ pub struct ShaderModuleDescriptor<'a> {
     pub runtime_checks: wgt::ShaderRuntimeChecks,
 }
 
+/// A diagnostic reported while creating a [`ShaderModule`] that didn't make
+/// it fail, like a warning.
+#[derive(Clone, Debug)]
+pub struct ShaderModuleDiagnostic {
+    /// Either [`Severity::Info`] or [`Severity::Warning`].
+    ///
+    /// [`Severity::Info`]: naga::diagnostic_filter::Severity::Info
+    /// [`Severity::Warning`]: naga::diagnostic_filter::Severity::Warning
+    pub severity: naga::diagnostic_filter::Severity,
+    /// The diagnostic, rendered with the source of the shader if there is one.
+    pub message: String,
+    pub location: Option<naga::SourceLocation>,
+}
+
+impl ShaderModuleDiagnostic {
+    pub(crate) fn new(
+        diagnostic: &naga::diagnostic_filter::Diagnostic,
+        source: &str,
+        label: &str,
+    ) -> Self {
+        // Modules given as Naga IR have no source for the spans to point into.
+        let (message, location) = if source.is_empty() {
+            (diagnostic.to_string(), None)
+        } else {
+            (
+                diagnostic.emit_to_string_with_path(source, label),
+                diagnostic.location(source),
+            )
+        };
+        Self {
+            severity: diagnostic.severity(),
+            message,
+            location,
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct ShaderModule {
     pub(crate) raw: ManuallyDrop<Box<dyn hal::DynShaderModule>>,
     pub(crate) device: Arc<Device>,
     pub(crate) interface: Option<validation::Interface>,
+    /// The diagnostics reported while creating the module.
+    pub(crate) diagnostics: Vec<ShaderModuleDiagnostic>,
     /// The `label` from the descriptor used to create the resource.
     pub(crate) label: String,
 }
@@ -98,6 +139,9 @@ pub enum CreateShaderModuleError {
     #[cfg(feature = "glsl")]
     #[error(transparent)]
     ParsingGlsl(#[from] ShaderError<naga::front::glsl::ParseErrors>),
+    #[cfg(feature = "hlsl")]
+    #[error(transparent)]
+    ParsingHlsl(#[from] ShaderError<naga::front::hlsl::Error>),
     #[cfg(feature = "spirv")]
     #[error(transparent)]
     ParsingSpirV(#[from] ShaderError<naga::front::spv::Error>),
@@ -154,6 +154,8 @@ struct EntryPoint {
     #[allow(unused)]
     spec_constants: Vec<SpecializationConstant>,
     sampling_pairs: FastHashSet<(naga::Handle<Resource>, naga::Handle<Resource>)>,
+    /// The binding types of the resources, as reflected by naga.
+    binding_types: FastHashMap<naga::ResourceBinding, naga::proc::reflect::BindingType>,
     workgroup_size: [u32; 3],
     dual_source_blending: bool,
 }
@@ -202,6 +204,11 @@ pub enum BindingError {
         binding: naga::ImageClass,
         shader: naga::ImageClass,
     },
+    #[error("Storage texture access {binding:?} doesn't match the shader {shader:?}")]
+    WrongStorageTextureAccess {
+        binding: naga::StorageAccess,
+        shader: naga::StorageAccess,
+    },
     #[error("Comparison flag doesn't match the shader")]
     WrongSamplerComparison,
     #[error("Derived bind group layout type is not consistent between stages")]
@@ -543,6 +550,24 @@ impl Resource {
                         })
                     }
                 };
+                if let (
+                    naga::ImageClass::Storage {
+                        format: binding_format,
+                        access: binding_access,
+                    },
+                    naga::ImageClass::Storage {
+                        format: shader_format,
+                        access: shader_access,
+                    },
+                ) = (expected_class, class)
+                {
+                    if binding_format == shader_format && binding_access != shader_access {
+                        return Err(BindingError::WrongStorageTextureAccess {
+                            binding: binding_access,
+                            shader: shader_access,
+                        });
+                    }
+                }
                 if class != expected_class {
                     return Err(BindingError::WrongTextureClass {
                         binding: expected_class,
@@ -564,90 +589,91 @@ impl Resource {
         Ok(())
     }
 
+    /// Derives the binding type of the resource from `reflected`, the binding
+    /// type [`naga::proc::reflect`] found for it in an entry point.
     fn derive_binding_type(
         &self,
-        is_reffed_by_sampler_in_entrypoint: bool,
+        reflected: Option<naga::proc::reflect::BindingType>,
     ) -> Result<BindingType, BindingError> {
-        Ok(match self.ty {
-            ResourceType::Buffer { size } => BindingType::Buffer {
-                ty: match self.class {
-                    naga::AddressSpace::Uniform => wgt::BufferBindingType::Uniform,
-                    naga::AddressSpace::Storage { access } => wgt::BufferBindingType::Storage {
-                        read_only: access == naga::StorageAccess::LOAD,
-                    },
-                    _ => return Err(BindingError::WrongBufferAddressSpace { space: self.class }),
+        use naga::proc::reflect as r;
+
+        // Reflection skips buffers in other address spaces.
+        let Some(reflected) = reflected else {
+            return Err(BindingError::WrongBufferAddressSpace { space: self.class });
+        };
+        Ok(match reflected {
+            r::BindingType::Buffer {
+                ty,
+                min_binding_size,
+            } => BindingType::Buffer {
+                ty: match ty {
+                    r::BufferBindingType::Uniform => wgt::BufferBindingType::Uniform,
+                    r::BufferBindingType::Storage { read_only } => {
+                        wgt::BufferBindingType::Storage { read_only }
+                    }
                 },
                 has_dynamic_offset: false,
-                min_binding_size: Some(size),
+                min_binding_size: wgt::BufferSize::new(min_binding_size),
             },
-            ResourceType::Sampler { comparison } => BindingType::Sampler(if comparison {
-                wgt::SamplerBindingType::Comparison
-            } else {
-                wgt::SamplerBindingType::Filtering
+            r::BindingType::Sampler(ty) => BindingType::Sampler(match ty {
+                r::SamplerBindingType::Filtering => wgt::SamplerBindingType::Filtering,
+                r::SamplerBindingType::Comparison => wgt::SamplerBindingType::Comparison,
             }),
-            ResourceType::Texture {
-                dim,
-                arrayed,
-                class,
-            } => {
-                let view_dimension = match dim {
-                    naga::ImageDimension::D1 => wgt::TextureViewDimension::D1,
-                    naga::ImageDimension::D2 if arrayed => wgt::TextureViewDimension::D2Array,
-                    naga::ImageDimension::D2 => wgt::TextureViewDimension::D2,
-                    naga::ImageDimension::D3 => wgt::TextureViewDimension::D3,
-                    naga::ImageDimension::Cube if arrayed => wgt::TextureViewDimension::CubeArray,
-                    naga::ImageDimension::Cube => wgt::TextureViewDimension::Cube,
-                };
-                match class {
-                    naga::ImageClass::Sampled { multi, kind } => BindingType::Texture {
-                        sample_type: match kind {
-                            naga::ScalarKind::Float => wgt::TextureSampleType::Float {
-                                filterable: is_reffed_by_sampler_in_entrypoint,
-                            },
-                            naga::ScalarKind::Sint => wgt::TextureSampleType::Sint,
-                            naga::ScalarKind::Uint => wgt::TextureSampleType::Uint,
-                            naga::ScalarKind::AbstractInt
-                            | naga::ScalarKind::AbstractFloat
-                            | naga::ScalarKind::Bool => unreachable!(),
-                        },
-                        view_dimension,
-                        multisampled: multi,
-                    },
-                    naga::ImageClass::Depth { multi } => BindingType::Texture {
-                        sample_type: wgt::TextureSampleType::Depth,
-                        view_dimension,
-                        multisampled: multi,
-                    },
-                    naga::ImageClass::Storage { format, access } => BindingType::StorageTexture {
-                        access: {
-                            const LOAD_STORE: naga::StorageAccess =
-                                naga::StorageAccess::LOAD.union(naga::StorageAccess::STORE);
-                            match access {
-                                naga::StorageAccess::LOAD => wgt::StorageTextureAccess::ReadOnly,
-                                naga::StorageAccess::STORE => wgt::StorageTextureAccess::WriteOnly,
-                                LOAD_STORE => wgt::StorageTextureAccess::ReadWrite,
-                                _ if access.contains(naga::StorageAccess::ATOMIC) => {
-                                    wgt::StorageTextureAccess::Atomic
-                                }
-                                _ => unreachable!(),
-                            }
-                        },
-                        view_dimension,
-                        format: {
-                            let f = map_storage_format_from_naga(format);
-                            let original = map_storage_format_to_naga(f)
-                                .ok_or(BindingError::BadStorageFormat(f))?;
-                            debug_assert_eq!(format, original);
-                            f
-                        },
-                    },
-                }
-            }
-            ResourceType::AccelerationStructure => BindingType::AccelerationStructure,
+            r::BindingType::Texture {
+                sample_type,
+                view_dimension,
+                multisampled,
+            } => BindingType::Texture {
+                sample_type: match sample_type {
+                    r::TextureSampleType::Float { filterable } => {
+                        wgt::TextureSampleType::Float { filterable }
+                    }
+                    r::TextureSampleType::Depth => wgt::TextureSampleType::Depth,
+                    r::TextureSampleType::Sint => wgt::TextureSampleType::Sint,
+                    r::TextureSampleType::Uint => wgt::TextureSampleType::Uint,
+                },
+                view_dimension: map_view_dimension(view_dimension),
+                multisampled,
+            },
+            r::BindingType::StorageTexture {
+                access,
+                format,
+                view_dimension,
+            } => BindingType::StorageTexture {
+                access: match access {
+                    r::StorageTextureAccess::WriteOnly => wgt::StorageTextureAccess::WriteOnly,
+                    r::StorageTextureAccess::ReadOnly => wgt::StorageTextureAccess::ReadOnly,
+                    r::StorageTextureAccess::ReadWrite => wgt::StorageTextureAccess::ReadWrite,
+                    r::StorageTextureAccess::Atomic => wgt::StorageTextureAccess::Atomic,
+                },
+                view_dimension: map_view_dimension(view_dimension),
+                format: {
+                    let f = map_storage_format_from_naga(format);
+                    let original =
+                        map_storage_format_to_naga(f).ok_or(BindingError::BadStorageFormat(f))?;
+                    debug_assert_eq!(format, original);
+                    f
+                },
+            },
+            r::BindingType::AccelerationStructure => BindingType::AccelerationStructure,
         })
     }
 }
 
+fn map_view_dimension(
+    dimension: naga::proc::reflect::TextureViewDimension,
+) -> wgt::TextureViewDimension {
+    use naga::proc::reflect::TextureViewDimension as Tvd;
+    match dimension {
+        Tvd::D1 => wgt::TextureViewDimension::D1,
+        Tvd::D2 => wgt::TextureViewDimension::D2,
+        Tvd::D2Array => wgt::TextureViewDimension::D2Array,
+        Tvd::Cube => wgt::TextureViewDimension::Cube,
+        Tvd::CubeArray => wgt::TextureViewDimension::CubeArray,
+        Tvd::D3 => wgt::TextureViewDimension::D3,
+    }
+}
+
 impl NumericType {
     fn from_vertex_format(format: wgt::VertexFormat) -> Self {
         use naga::{Scalar, VectorSize as Vs};
@@ -959,11 +985,25 @@ impl Interface {
             resource_mapping.insert(var_handle, handle);
         }
 
+        let reflection = naga::proc::reflect(module, info);
         let mut entry_points = FastHashMap::default();
         entry_points.reserve(module.entry_points.len());
         for (index, entry_point) in module.entry_points.iter().enumerate() {
             let info = info.get_entry_point(index);
-            let mut ep = EntryPoint::default();
+            let mut ep = EntryPoint {
+                binding_types: reflection.entry_points[index]
+                    .resources
+                    .iter()
+                    .map(|resource| {
+                        let bind = naga::ResourceBinding {
+                            group: resource.group,
+                            binding: resource.binding,
+                        };
+                        (bind, resource.ty)
+                    })
+                    .collect(),
+                ..Default::default()
+            };
             for arg in entry_point.function.arguments.iter() {
                 Self::populate(&mut ep.inputs, arg.binding.as_ref(), arg.ty, &module.types);
             }
@@ -1087,12 +1127,9 @@ impl Interface {
                             break 'err Err(BindingError::Missing);
                         };
 
-                        let ty = match res.derive_binding_type(
-                            entry_point
-                                .sampling_pairs
-                                .iter()
-                                .any(|&(im, _samp)| im == handle),
-                        ) {
+                        let ty = match res
+                            .derive_binding_type(entry_point.binding_types.get(&res.bind).copied())
+                        {
                             Ok(ty) => ty,
                             Err(error) => break 'err Err(error),
                         };
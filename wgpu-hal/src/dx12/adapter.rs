@@ -319,7 +319,8 @@ impl super::Adapter {
             | wgt::Features::DUAL_SOURCE_BLENDING
             | wgt::Features::TEXTURE_FORMAT_NV12
             | wgt::Features::FLOAT32_FILTERABLE
-            | wgt::Features::TEXTURE_ATOMIC;
+            | wgt::Features::TEXTURE_ATOMIC
+            | wgt::Features::CLIP_DISTANCES;
 
         //TODO: in order to expose this, we need to run a compute shader
         // that extract the necessary statistics out of the D3D12 result.
@@ -401,6 +402,13 @@ impl super::Adapter {
                 && hr.is_ok()
                 && features1.WaveOps.as_bool(),
         );
+        // Quad operations outside of pixel shaders need SM 6.6.
+        features.set(
+            wgt::Features::SUBGROUP_QUAD,
+            shader_model >= naga::back::hlsl::ShaderModel::V6_6
+                && hr.is_ok()
+                && features1.WaveOps.as_bool(),
+        );
         let mut features5 = Direct3D12::D3D12_FEATURE_DATA_D3D12_OPTIONS5::default();
         let has_features5 = unsafe {
             device.CheckFeatureSupport(
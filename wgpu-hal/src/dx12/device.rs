@@ -277,6 +277,8 @@ impl super::Device {
             stage.constants,
         )
         .map_err(|e| crate::PipelineError::PipelineConstants(stage_bit, format!("HLSL: {e:?}")))?;
+        let (module, info) = naga::back::pointer_parameters::specialize(&module, &info)
+            .map_err(|e| crate::PipelineError::Linkage(stage_bit, format!("HLSL: {e:?}")))?;
 
         let needs_temp_options = stage.zero_initialize_workgroup_memory
             != layout.naga_options.zero_initialize_workgroup_memory
@@ -1241,6 +1243,7 @@ impl crate::Device for super::Device {
                 push_constants_target,
                 zero_initialize_workgroup_memory: true,
                 restrict_indexing: true,
+                descriptor_heap: None,
             },
         })
     }
@@ -224,6 +224,11 @@ impl super::Device {
             let msg = format!("{e}");
             crate::PipelineError::PipelineConstants(map_naga_stage(naga_stage), msg)
         })?;
+        let (module, info) =
+            naga::back::pointer_parameters::specialize(&module, &info).map_err(|e| {
+                let msg = format!("{e}");
+                crate::PipelineError::Linkage(map_naga_stage(naga_stage), msg)
+            })?;
 
         let entry_point_index = module
             .entry_points
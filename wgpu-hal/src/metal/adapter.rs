@@ -910,6 +910,10 @@ impl super::PrivateCapabilities {
             F::DUAL_SOURCE_BLENDING,
             self.msl_version >= MTLLanguageVersion::V1_2 && self.dual_source_blending,
         );
+        features.set(
+            F::CLIP_DISTANCES,
+            self.msl_version >= MTLLanguageVersion::V2_0,
+        );
         features.set(F::TEXTURE_COMPRESSION_ASTC, self.format_astc);
         features.set(F::TEXTURE_COMPRESSION_ASTC_HDR, self.format_astc_hdr);
         features.set(F::TEXTURE_COMPRESSION_BC, self.format_bc);
@@ -961,7 +965,7 @@ impl super::PrivateCapabilities {
         features.set(F::RG11B10UFLOAT_RENDERABLE, self.format_rg11b10_all);
 
         if self.supports_simd_scoped_operations {
-            features.insert(F::SUBGROUP | F::SUBGROUP_BARRIER);
+            features.insert(F::SUBGROUP | F::SUBGROUP_BARRIER | F::SUBGROUP_QUAD);
         }
 
         features
@@ -169,6 +169,8 @@ impl super::Device {
             },
             zero_initialize_workgroup_memory: stage.zero_initialize_workgroup_memory,
             force_loop_bounding: stage.module.bounds_checks.force_loop_bounding,
+            // Resources are bound one by one, see `create_pipeline_layout`.
+            argument_buffers: Default::default(),
         };
 
         let pipeline_options = naga::back::msl::PipelineOptions {
@@ -788,6 +790,10 @@ impl crate::Device for super::Device {
                 .sizes_buffer
                 .map(|buffer_index| buffer_index as naga::back::msl::Slot),
             resources: info.resources,
+            // Bind groups are never passed as argument buffers: binding them
+            // would need the command encoder to encode each group into a
+            // buffer and mark its resources as used.
+            argument_buffers: Default::default(),
         });
 
         self.counters.pipeline_layouts.add(1);
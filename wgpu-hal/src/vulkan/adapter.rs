@@ -294,7 +294,7 @@ impl PhysicalDeviceFeatures {
                         | wgt::Features::STORAGE_RESOURCE_BINDING_ARRAY,
                 ))
                 //.shader_storage_image_array_dynamic_indexing(
-                //.shader_clip_distance(requested_features.contains(wgt::Features::SHADER_CLIP_DISTANCE))
+                .shader_clip_distance(requested_features.contains(wgt::Features::CLIP_DISTANCES))
                 //.shader_cull_distance(requested_features.contains(wgt::Features::SHADER_CULL_DISTANCE))
                 .shader_float64(requested_features.contains(wgt::Features::SHADER_F64))
                 .shader_int64(requested_features.contains(wgt::Features::SHADER_INT64))
@@ -394,7 +394,9 @@ impl PhysicalDeviceFeatures {
                     vk::PhysicalDeviceShaderFloat16Int8Features::default().shader_float16(true),
                     vk::PhysicalDevice16BitStorageFeatures::default()
                         .storage_buffer16_bit_access(true)
-                        .uniform_and_storage_buffer16_bit_access(true),
+                        .uniform_and_storage_buffer16_bit_access(true)
+                        .storage_input_output16(private_caps.shader_f16_input_output)
+                        .storage_push_constant16(private_caps.shader_f16_push_constants),
                 ))
             } else {
                 None
@@ -602,7 +604,7 @@ impl PhysicalDeviceFeatures {
                     && self.core.shader_storage_image_array_dynamic_indexing != 0),
         );
         //if self.core.shader_storage_image_array_dynamic_indexing != 0 {
-        //if self.core.shader_clip_distance != 0 {
+        features.set(F::CLIP_DISTANCES, self.core.shader_clip_distance != 0);
         //if self.core.shader_cull_distance != 0 {
         features.set(F::SHADER_F64, self.core.shader_float64 != 0);
         features.set(F::SHADER_INT64, self.core.shader_int64 != 0);
@@ -722,6 +724,13 @@ impl PhysicalDeviceFeatures {
                         .supported_stages
                         .contains(vk::ShaderStageFlags::VERTEX),
                 );
+                features.set(
+                    F::SUBGROUP_QUAD,
+                    features.contains(F::SUBGROUP)
+                        && subgroup
+                            .supported_operations
+                            .contains(vk::SubgroupFeatureFlags::QUAD),
+                );
                 features.insert(F::SUBGROUP_BARRIER);
             }
         }
@@ -1611,6 +1620,14 @@ impl super::Instance {
                 .properties
                 .limits
                 .max_sampler_allocation_count,
+            shader_f16_input_output: phd_features
+                .shader_float16
+                .as_ref()
+                .is_some_and(|&(_, ref bit16)| bit16.storage_input_output16 != 0),
+            shader_f16_push_constants: phd_features
+                .shader_float16
+                .as_ref()
+                .is_some_and(|&(_, ref bit16)| bit16.storage_push_constant16 != 0),
         };
         let capabilities = crate::Capabilities {
             limits: phd_capabilities.to_wgpu_limits(),
@@ -1847,6 +1864,24 @@ impl super::Adapter {
                 capabilities.push(spv::Capability::RayQueryKHR);
             }
 
+            if features.contains(wgt::Features::CLIP_DISTANCES) {
+                capabilities.push(spv::Capability::ClipDistance);
+            }
+
+            if features.contains(wgt::Features::SHADER_F16) {
+                capabilities.push(spv::Capability::Float16);
+                capabilities.push(spv::Capability::StorageBuffer16BitAccess);
+                capabilities.push(spv::Capability::UniformAndStorageBuffer16BitAccess);
+                // Without these, shaders using `f16` in their inputs, outputs
+                // or push constants fail to compile with a missing capability.
+                if self.private_caps.shader_f16_input_output {
+                    capabilities.push(spv::Capability::StorageInputOutput16);
+                }
+                if self.private_caps.shader_f16_push_constants {
+                    capabilities.push(spv::Capability::StoragePushConstant16);
+                }
+            }
+
             if features.contains(wgt::Features::SHADER_INT64) {
                 capabilities.push(spv::Capability::Int64);
             }
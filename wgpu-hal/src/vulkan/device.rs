@@ -950,6 +950,8 @@ impl super::Device {
                 .map_err(|e| {
                     crate::PipelineError::PipelineConstants(stage_flags, format!("{e}"))
                 })?;
+                let (module, info) = naga::back::pointer_parameters::specialize(&module, &info)
+                    .map_err(|e| crate::PipelineError::Linkage(stage_flags, format!("{e}")))?;
 
                 let spv = {
                     profiling::scope!("naga::spv::write_vec");
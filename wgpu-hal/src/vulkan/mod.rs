@@ -538,6 +538,14 @@ struct PrivateCapabilities {
     zero_initialize_workgroup_memory: bool,
     image_format_list: bool,
     maximum_samplers: u32,
+
+    /// True if this adapter supports `f16` shader inputs and outputs
+    /// (`storageInputOutput16`), used with [`wgt::Features::SHADER_F16`].
+    shader_f16_input_output: bool,
+
+    /// True if this adapter supports `f16` push constants
+    /// (`storagePushConstant16`), used with [`wgt::Features::SHADER_F16`].
+    shader_f16_push_constants: bool,
 }
 
 bitflags::bitflags!(
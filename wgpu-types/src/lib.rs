@@ -293,7 +293,7 @@ bitflags::bitflags! {
     #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
     #[cfg_attr(feature = "serde", serde(transparent))]
     #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
-    pub struct Features: u64 {
+    pub struct Features: u128 {
         //
         // ---- Start numbering at 1 << 0 ----
         //
@@ -434,8 +434,6 @@ bitflags::bitflags! {
 
         /// Allows shaders to acquire the FP16 ability
         ///
-        /// Note: this is not supported in `naga` yet, only through `spirv-passthrough` right now.
-        ///
         /// Supported Platforms:
         /// - Vulkan
         /// - Metal
@@ -476,7 +474,19 @@ bitflags::bitflags! {
         /// This is a web and native feature.
         const FLOAT32_FILTERABLE = 1 << 11;
 
-        // Bits 12-18 available for webgpu features. Should you chose to use some of them for
+        /// Allows vertex shaders to write the `clip_distances` built-in, enabling user-defined
+        /// clip planes. Shaders must also declare `enable clip_distances;`, and can write at
+        /// most 8 distances.
+        ///
+        /// Supported Platforms:
+        /// - Vulkan (with shaderClipDistance)
+        /// - DX12
+        /// - Metal (with MSL 2.0+)
+        ///
+        /// This is a web and native feature.
+        const CLIP_DISTANCES = 1 << 12;
+
+        // Bits 13-17 available for webgpu features. Should you chose to use some of them for
         // for native features, don't forget to update `all_webgpu_mask` and `all_native_mask`
         // accordingly.
 
@@ -1068,6 +1078,19 @@ bitflags::bitflags! {
         ///
         /// [VK_KHR_external_memory_win32]: https://registry.khronos.org/vulkan/specs/latest/man/html/VK_KHR_external_memory_win32.html
         const VULKAN_EXTERNAL_MEMORY_WIN32 = 1 << 63;
+
+        /// Allows compute and fragment shaders to use the quad subgroup operations,
+        /// `quadBroadcast`, `quadSwapX`, `quadSwapY` and `quadSwapDiagonal`.
+        ///
+        /// Requires [`Features::SUBGROUP`].
+        ///
+        /// Supported Platforms:
+        /// - Vulkan (with the quad subgroup operations)
+        /// - DX12 (with SM 6.6+)
+        /// - Metal
+        ///
+        /// This is a native only feature.
+        const SUBGROUP_QUAD = 1 << 64;
     }
 }
 
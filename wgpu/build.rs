@@ -13,7 +13,7 @@ fn main() {
         metal: { all(target_vendor = "apple", feature = "metal") },
         // This alias is _only_ if _we_ need naga in the wrapper. wgpu-core provides
         // its own re-export of naga, which can be used in other situations
-        naga: { any(feature = "naga-ir", feature = "spirv", feature = "glsl") },
+        naga: { any(feature = "naga-ir", feature = "spirv", feature = "glsl", feature = "hlsl") },
         // ⚠️ Keep in sync with target.cfg() definition in wgpu-hal/Cargo.toml and cfg_alias in `wgpu-hal` crate ⚠️
         static_dxc: { all(target_os = "windows", feature = "static-dxc", not(target_arch = "aarch64")) },
     }
@@ -113,6 +113,19 @@ impl From<naga::error::ShaderError<naga::front::glsl::ParseErrors>> for Compilat
     }
 }
 
+#[cfg(feature = "hlsl")]
+impl From<naga::error::ShaderError<naga::front::hlsl::Error>> for CompilationInfo {
+    fn from(value: naga::error::ShaderError<naga::front::hlsl::Error>) -> Self {
+        CompilationInfo {
+            messages: vec![CompilationMessage {
+                message: value.to_string(),
+                message_type: CompilationMessageType::Error,
+                location: value.inner.location(&value.source).map(Into::into),
+            }],
+        }
+    }
+}
+
 #[cfg(feature = "spirv")]
 impl From<naga::error::ShaderError<naga::front::spv::Error>> for CompilationInfo {
     fn from(value: naga::error::ShaderError<naga::front::spv::Error>) -> Self {
@@ -189,6 +202,19 @@ pub enum ShaderSource<'a> {
         /// Defines to unlock configured shader features.
         defines: naga::FastHashMap<String, String>,
     },
+    /// HLSL module as a string slice.
+    ///
+    /// Note: only a subset of HLSL is supported, see [`naga::front::hlsl`].
+    #[cfg(feature = "hlsl")]
+    Hlsl {
+        /// The source code of the shader.
+        shader: Cow<'a, str>,
+        /// The names and stages of the entry points that aren't marked with a
+        /// `[shader("...")]` or `[numthreads(...)]` attribute.
+        entry_points: naga::FastHashMap<String, naga::ShaderStage>,
+        /// The shifts added to the indices of registers to get binding numbers.
+        register_shifts: naga::front::hlsl::RegisterShifts,
+    },
     /// WGSL module as a string slice.
     #[cfg(feature = "wgsl")]
     Wgsl(Cow<'a, str>),
@@ -731,7 +731,7 @@ fn map_map_mode(mode: crate::MapMode) -> u32 {
     }
 }
 
-const FEATURES_MAPPING: [(wgt::Features, webgpu_sys::GpuFeatureName); 12] = [
+const FEATURES_MAPPING: [(wgt::Features, webgpu_sys::GpuFeatureName); 13] = [
     //TODO: update the name
     (
         wgt::Features::DEPTH_CLIP_CONTROL,
@@ -781,6 +781,10 @@ const FEATURES_MAPPING: [(wgt::Features, webgpu_sys::GpuFeatureName); 12] = [
         wgt::Features::FLOAT32_FILTERABLE,
         webgpu_sys::GpuFeatureName::Float32Filterable,
     ),
+    (
+        wgt::Features::CLIP_DISTANCES,
+        webgpu_sys::GpuFeatureName::ClipDistances,
+    ),
 ];
 
 fn map_wgt_features(supported_features: webgpu_sys::GpuSupportedFeatures) -> wgt::Features {
@@ -1781,6 +1785,39 @@ impl dispatch::DeviceInterface for WebDevice {
                         })
                     })
             }
+            #[cfg(feature = "hlsl")]
+            crate::ShaderSource::Hlsl {
+                ref shader,
+                ref entry_points,
+                register_shifts,
+            } => {
+                use naga::front;
+
+                // Parse the given shader code and store its representation.
+                let options = front::hlsl::Options {
+                    entry_points: entry_points.clone(),
+                    register_shifts,
+                };
+                front::hlsl::Frontend::new()
+                    .parse(&options, shader)
+                    .map_err(|inner| {
+                        crate::CompilationInfo::from(naga::error::ShaderError {
+                            source: shader.to_string(),
+                            label: desc.label.map(|s| s.to_string()),
+                            inner: Box::new(inner),
+                        })
+                    })
+                    .and_then(|hlsl_module| {
+                        validate_transformed_shader_module(&hlsl_module, shader, &desc).map(|v| {
+                            (
+                                v,
+                                WebShaderCompilationInfo::Transformed {
+                                    compilation_info: crate::CompilationInfo { messages: vec![] },
+                                },
+                            )
+                        })
+                    })
+            }
             #[cfg(feature = "wgsl")]
             crate::ShaderSource::Wgsl(ref code) => {
                 let shader_module = webgpu_sys::GpuShaderModuleDescriptor::new(code);
@@ -660,6 +660,8 @@ impl From<CreateShaderModuleError> for CompilationInfo {
             CreateShaderModuleError::Parsing(v) => v.into(),
             #[cfg(feature = "glsl")]
             CreateShaderModuleError::ParsingGlsl(v) => v.into(),
+            #[cfg(feature = "hlsl")]
+            CreateShaderModuleError::ParsingHlsl(v) => v.into(),
             #[cfg(feature = "spirv")]
             CreateShaderModuleError::ParsingSpirV(v) => v.into(),
             CreateShaderModuleError::Validation(v) => v.into(),
@@ -682,6 +684,19 @@ impl From<CreateShaderModuleError> for CompilationInfo {
     }
 }
 
+impl From<wgc::pipeline::ShaderModuleDiagnostic> for CompilationMessage {
+    fn from(value: wgc::pipeline::ShaderModuleDiagnostic) -> Self {
+        CompilationMessage {
+            message: value.message,
+            message_type: match value.severity {
+                wgc::naga::diagnostic_filter::Severity::Warning => CompilationMessageType::Warning,
+                _ => CompilationMessageType::Info,
+            },
+            location: value.location.map(Into::into),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct CoreQueueWriteBuffer {
     buffer_id: wgc::id::StagingBufferId,
@@ -854,14 +869,26 @@ impl dispatch::InstanceInterface for ContextWgpuCore {
 
     #[cfg(feature = "wgsl")]
     fn wgsl_language_features(&self) -> crate::WgslLanguageFeatures {
-        wgc::naga::front::wgsl::ImplementedLanguageExtension::all()
-            .iter()
-            .copied()
-            .fold(
-                crate::WgslLanguageFeatures::empty(),
-                #[expect(unreachable_code)]
-                |acc, wle| acc | match wle {},
-            )
+        use wgc::naga::front::wgsl::ImplementedLanguageExtension;
+        ImplementedLanguageExtension::all().iter().copied().fold(
+            crate::WgslLanguageFeatures::empty(),
+            |acc, wle| {
+                acc | match wle {
+                    ImplementedLanguageExtension::ReadOnlyAndReadWriteStorageTextures => {
+                        crate::WgslLanguageFeatures::ReadOnlyAndReadWriteStorageTextures
+                    }
+                    ImplementedLanguageExtension::Packed4x8IntegerDotProduct => {
+                        crate::WgslLanguageFeatures::Packed4x8IntegerDotProduct
+                    }
+                    ImplementedLanguageExtension::UnrestrictedPointerParameters => {
+                        crate::WgslLanguageFeatures::UnrestrictedPointerParameters
+                    }
+                    ImplementedLanguageExtension::PointerCompositeAccess => {
+                        crate::WgslLanguageFeatures::PointerCompositeAccess
+                    }
+                }
+            },
+        )
     }
 }
 
@@ -960,6 +987,7 @@ impl dispatch::DeviceInterface for CoreDevice {
         not(any(
             feature = "spirv",
             feature = "glsl",
+            feature = "hlsl",
             feature = "wgsl",
             feature = "naga-ir"
         )),
@@ -994,6 +1022,18 @@ impl dispatch::DeviceInterface for CoreDevice {
                 let options = naga::front::glsl::Options { stage, defines };
                 wgc::pipeline::ShaderModuleSource::Glsl(Borrowed(shader), options)
             }
+            #[cfg(feature = "hlsl")]
+            ShaderSource::Hlsl {
+                ref shader,
+                entry_points,
+                register_shifts,
+            } => {
+                let options = naga::front::hlsl::Options {
+                    entry_points,
+                    register_shifts,
+                };
+                wgc::pipeline::ShaderModuleSource::Hlsl(Borrowed(shader), options)
+            }
             #[cfg(feature = "wgsl")]
             ShaderSource::Wgsl(ref code) => wgc::pipeline::ShaderModuleSource::Wgsl(Borrowed(code)),
             #[cfg(feature = "naga-ir")]
@@ -1014,7 +1054,15 @@ impl dispatch::DeviceInterface for CoreDevice {
                 );
                 CompilationInfo::from(cause)
             }
-            None => CompilationInfo { messages: vec![] },
+            None => CompilationInfo {
+                messages: self
+                    .context
+                    .0
+                    .shader_module_diagnostics(id)
+                    .into_iter()
+                    .map(CompilationMessage::from)
+                    .collect(),
+            },
         };
 
         CoreShaderModule {